@@ -0,0 +1,68 @@
+//! Draw primitives against a headless screen: `cargo bench --features bench`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use runty8::bench::Headless;
+
+fn sprites(c: &mut Criterion) {
+    let mut headless = Headless::new();
+    let pico8 = headless.pico8();
+
+    // Sprite 1 has transparent pixels, sprite 3 doesn't.
+    for (name, sprite) in [("spr transparent", 1), ("spr opaque", 3)] {
+        c.bench_function(name, |b| {
+            b.iter(|| {
+                for i in 0..256 {
+                    pico8.spr(sprite, i % 16 * 8, i / 16 * 8);
+                }
+            })
+        });
+    }
+}
+
+fn rectfill(c: &mut Criterion) {
+    let mut headless = Headless::new();
+    let pico8 = headless.pico8();
+
+    let mut group = c.benchmark_group("rectfill");
+    for size in [8, 32, 128] {
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &size| {
+            b.iter(|| pico8.rectfill(0, 0, size - 1, size - 1, 8))
+        });
+    }
+    group.finish();
+}
+
+fn print(c: &mut Criterion) {
+    let mut headless = Headless::new();
+    let pico8 = headless.pico8();
+    let text = "THE QUICK BROWN FOX JUMPS OVER THE LAZY DOG 0123456789\n".repeat(20);
+
+    c.bench_function("print", |b| b.iter(|| pico8.print(&text, 0, 0, 7)));
+}
+
+fn map(c: &mut Criterion) {
+    let mut headless = Headless::new();
+    let pico8 = headless.pico8();
+    for x in 0..16 {
+        for y in 0..16 {
+            pico8.mset(x, y, (1 + (x + y) % 4) as u8);
+        }
+    }
+
+    c.bench_function("map full screen", |b| {
+        b.iter(|| pico8.map(0, 0, 0, 0, 16, 16, 0))
+    });
+}
+
+#[cfg(feature = "editor")]
+fn editor(c: &mut Criterion) {
+    let mut headless = Headless::new();
+
+    c.bench_function("editor view", |b| b.iter(|| headless.draw_editor()));
+}
+
+#[cfg(not(feature = "editor"))]
+fn editor(_: &mut Criterion) {}
+
+criterion_group!(benches, sprites, rectfill, print, map, editor);
+criterion_main!(benches);
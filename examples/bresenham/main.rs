@@ -1,7 +1,7 @@
 use runty8::{self, App, Button, Pico8};
 
-fn main() {
-    runty8::run_app::<MyThing>("examples/bresenham".to_owned()).unwrap();
+fn main() -> Result<(), runty8::Error> {
+    runty8::run_app::<MyThing>("examples/bresenham".to_owned())
 }
 
 struct MyThing {
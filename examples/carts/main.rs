@@ -0,0 +1,123 @@
+use runty8::{App, Button, Pico8};
+
+fn main() -> Result<(), runty8::Error> {
+    runty8::run_app::<Launcher>("examples/carts/launcher".to_owned())
+}
+
+// Name shown in the menu, name the cart is registered as.
+const CARTS: [(&str, &str); 2] = [("BOUNCING BALL", "ball"), ("PAINT", "paint")];
+
+/// Lists the other carts, loading the chosen one with `Pico8::load_cart`.
+/// Each cart has its own assets directory.
+struct Launcher {
+    selected: usize,
+}
+
+impl App for Launcher {
+    fn init(pico8: &mut Pico8) -> Self {
+        pico8.register_cart::<Ball>("ball", "examples/carts/ball");
+        pico8.register_cart::<Paint>("paint", "examples/carts/paint");
+
+        Self { selected: 0 }
+    }
+
+    fn update(&mut self, pico8: &mut Pico8) {
+        if pico8.btnp(Button::Up) {
+            self.selected = (self.selected + CARTS.len() - 1) % CARTS.len();
+        }
+        if pico8.btnp(Button::Down) {
+            self.selected = (self.selected + 1) % CARTS.len();
+        }
+        if pico8.btnp(Button::X) {
+            let (_, name) = CARTS[self.selected];
+            pico8.load_cart(name).unwrap();
+        }
+    }
+
+    fn draw(&mut self, pico8: &mut Pico8) {
+        pico8.cls(1);
+        pico8.print("PICK A CART, X TO LOAD", 8, 8, 7);
+
+        for (index, (title, _)) in CARTS.iter().enumerate() {
+            let y = 32 + index as i32 * 10;
+            let color = if index == self.selected { 10 } else { 6 };
+
+            pico8.print(title, 20, y, color);
+            if index == self.selected {
+                pico8.print(">", 12, y, color);
+            }
+        }
+    }
+}
+
+/// Goes back to the launcher when C is pressed.
+fn back_to_launcher(pico8: &mut Pico8) {
+    if pico8.btnp(Button::C) {
+        pico8.load_breadcrumb();
+    }
+}
+
+struct Ball {
+    x: i32,
+    y: i32,
+    dx: i32,
+    dy: i32,
+}
+
+impl App for Ball {
+    fn init(_: &mut Pico8) -> Self {
+        Self {
+            x: 64,
+            y: 20,
+            dx: 2,
+            dy: 1,
+        }
+    }
+
+    fn update(&mut self, pico8: &mut Pico8) {
+        self.x += self.dx;
+        self.y += self.dy;
+        if !(4..124).contains(&self.x) {
+            self.dx = -self.dx;
+        }
+        if !(4..124).contains(&self.y) {
+            self.dy = -self.dy;
+        }
+
+        back_to_launcher(pico8);
+    }
+
+    fn draw(&mut self, pico8: &mut Pico8) {
+        pico8.cls(0);
+        pico8.circfill(self.x, self.y, 4, 8);
+        pico8.print("C: BACK", 1, 1, 5);
+    }
+}
+
+/// Draws with the mouse, the painting is lost when going back.
+struct Paint {
+    strokes: Vec<(i32, i32)>,
+}
+
+impl App for Paint {
+    fn init(_: &mut Pico8) -> Self {
+        Self { strokes: vec![] }
+    }
+
+    fn update(&mut self, pico8: &mut Pico8) {
+        if pico8.btn(Button::Mouse) {
+            self.strokes.push(pico8.mouse());
+        }
+
+        back_to_launcher(pico8);
+    }
+
+    fn draw(&mut self, pico8: &mut Pico8) {
+        pico8.cls(7);
+        for &(x, y) in &self.strokes {
+            pico8.circfill(x, y, 1, 12);
+        }
+        pico8.print("C: BACK", 1, 1, 5);
+        pico8.pset(pico8.mouse().0, pico8.mouse().1, 0);
+    }
+}
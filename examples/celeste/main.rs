@@ -7,8 +7,8 @@ use runty8::{App, Button, Pico8};
 use std::iter::{Chain, Map};
 use std::slice;
 
-fn main() {
-    runty8::run_app::<GameState>("examples/celeste".to_owned()).unwrap();
+fn main() -> Result<(), runty8::Error> {
+    runty8::run_app::<GameState>("examples/celeste".to_owned())
 }
 
 struct GameState {
@@ -1,8 +1,7 @@
-use rand::Rng;
 use runty8::{App, Button, Pico8};
 
-fn main() {
-    runty8::run_app::<Confetti>("examples/confetti".to_owned()).unwrap();
+fn main() -> Result<(), runty8::Error> {
+    runty8::run_app::<Confetti>("examples/confetti".to_owned())
 }
 
 struct Confetti {
@@ -25,8 +24,11 @@ impl App for Confetti {
 
         if state.btn(Button::Mouse) {
             for _ in 0..10 {
-                self.particles
-                    .push(Particle::new(self.mouse_x as f32, self.mouse_y as f32));
+                self.particles.push(Particle::new(
+                    state,
+                    self.mouse_x as f32,
+                    self.mouse_y as f32,
+                ));
             }
         }
 
@@ -79,12 +81,12 @@ struct Particle {
 }
 
 impl Particle {
-    fn new(x: f32, y: f32) -> Self {
-        let x = rand::thread_rng().gen_range(-2.0..2.0) + x;
-        let y = rand::thread_rng().gen_range(-2.0..2.0) + y;
-        let vx = rand::thread_rng().gen_range(-15.0..15.0) / 50.0;
-        let vy = rand::thread_rng().gen_range(-50.0..10.0) / 50.0;
-        let ttl = rand::thread_rng().gen_range(10..70);
+    fn new(pico8: &Pico8, x: f32, y: f32) -> Self {
+        let x = pico8.rnd(4.0) - 2.0 + x;
+        let y = pico8.rnd(4.0) - 2.0 + y;
+        let vx = (pico8.rnd(30.0) - 15.0) / 50.0;
+        let vy = (pico8.rnd(60.0) - 50.0) / 50.0;
+        let ttl = 10 + pico8.rnd(60.0) as i32;
 
         Self {
             x,
@@ -93,7 +95,7 @@ impl Particle {
             vy,
             ay: 0.05,
             ttl,
-            color: rand::thread_rng().gen_range(1..16),
+            color: 1 + pico8.rnd(15.0) as u8,
         }
     }
 
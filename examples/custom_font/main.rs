@@ -0,0 +1,46 @@
+//! A score counter printed with a font drawn in the sprite sheet.
+//! The digits are the 5x7 glyphs at the top left of the sheet.
+//!
+//! Press X to score.
+use runty8::{App, Button, Font, Pico8};
+
+fn main() -> Result<(), runty8::Error> {
+    runty8::run_app::<CustomFont>("examples/custom_font".to_owned())
+}
+
+struct CustomFont {
+    digits: Font,
+    score: u32,
+    shown_score: u32,
+}
+
+impl App for CustomFont {
+    fn init(pico8: &mut Pico8) -> Self {
+        Self {
+            // Monospaced, so that the digits don't move around as the score goes up.
+            digits: pico8.font_from_sprite_sheet((0, 0), 5, 7, '0').monospaced(),
+            score: 0,
+            shown_score: 0,
+        }
+    }
+
+    fn update(&mut self, pico8: &mut Pico8) {
+        if pico8.btnp(Button::X) {
+            self.score += 150;
+        }
+
+        // Count up to the real score.
+        self.shown_score = (self.shown_score + 5).min(self.score);
+    }
+
+    fn draw(&mut self, pico8: &mut Pico8) {
+        pico8.cls(1);
+        pico8.print("SCORE", 4, 4, 6);
+
+        let score = format!("{:06}", self.shown_score);
+        let width = self.digits.measure(&score).0;
+        pico8.print_with(&self.digits, &score, 124 - width, 4, 10);
+
+        pico8.print("PRESS X TO SCORE", 32, 60, 7);
+    }
+}
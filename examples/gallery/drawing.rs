@@ -0,0 +1,80 @@
+//! A drawing toy: hold the mouse button to paint, click the palette to pick a color.
+
+use runty8::{App, Button, Pico8};
+
+// The palette is the bottom row of the screen, the canvas everything above it.
+const PALETTE_Y: i32 = 120;
+
+pub struct Drawing {
+    canvas: Vec<u8>,
+    color: u8,
+    // Where the mouse was on the last update, if it was painting.
+    last: Option<(i32, i32)>,
+}
+
+impl App for Drawing {
+    fn init(_: &mut Pico8) -> Self {
+        Self {
+            canvas: vec![0; 128 * PALETTE_Y as usize],
+            color: 7,
+            last: None,
+        }
+    }
+
+    fn update(&mut self, pico8: &mut Pico8) {
+        if pico8.btnp(Button::C) {
+            pico8.load_breadcrumb();
+        }
+        if pico8.btnp(Button::X) {
+            self.canvas.fill(0);
+        }
+
+        let (x, y) = pico8.mouse();
+        if !pico8.btn(Button::Mouse) {
+            self.last = None;
+        } else if y >= PALETTE_Y {
+            self.color = (x / 8).clamp(0, 15) as u8;
+            self.last = None;
+        } else {
+            // Fills the gap since the last update, for fast strokes.
+            let (last_x, last_y) = self.last.unwrap_or((x, y));
+            let steps = (x - last_x).abs().max((y - last_y).abs()).max(1);
+            for step in 0..=steps {
+                self.paint(
+                    last_x + (x - last_x) * step / steps,
+                    last_y + (y - last_y) * step / steps,
+                );
+            }
+            self.last = Some((x, y));
+        }
+    }
+
+    fn draw(&mut self, pico8: &mut Pico8) {
+        for (index, &color) in self.canvas.iter().enumerate() {
+            pico8.pset(index as i32 % 128, index as i32 / 128, color);
+        }
+        if self.canvas.iter().all(|&color| color == 0) {
+            pico8.print("DRAW WITH THE MOUSE", 26, 50, 5);
+            pico8.print("X: CLEAR  C: BACK", 30, 58, 5);
+        }
+
+        for color in 0..16 {
+            pico8.rectfill(color * 8, PALETTE_Y, color * 8 + 7, 127, color as u8);
+        }
+        let selected = self.color as i32 * 8;
+        pico8.rect(selected, PALETTE_Y, selected + 7, 127, 7);
+
+        let (x, y) = pico8.mouse();
+        pico8.line(x - 2, y, x + 2, y, 7);
+        pico8.line(x, y - 2, x, y + 2, 7);
+        pico8.pset(x, y, self.color);
+    }
+}
+
+impl Drawing {
+    fn paint(&mut self, x: i32, y: i32) {
+        if (0..128).contains(&x) && (0..PALETTE_Y).contains(&y) {
+            self.canvas[(x + y * 128) as usize] = self.color;
+        }
+    }
+}
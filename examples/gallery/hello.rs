@@ -0,0 +1,43 @@
+//! Hello world: a sprite moved around with the arrow keys.
+
+use runty8::{App, Button, Pico8};
+
+pub struct Hello {
+    x: i32,
+    y: i32,
+}
+
+impl App for Hello {
+    fn init(_: &mut Pico8) -> Self {
+        Self { x: 60, y: 60 }
+    }
+
+    fn update(&mut self, pico8: &mut Pico8) {
+        if pico8.btn(Button::Left) {
+            self.x -= 1;
+        }
+        if pico8.btn(Button::Right) {
+            self.x += 1;
+        }
+        if pico8.btn(Button::Up) {
+            self.y -= 1;
+        }
+        if pico8.btn(Button::Down) {
+            self.y += 1;
+        }
+        self.x = self.x.clamp(0, 120);
+        self.y = self.y.clamp(16, 120);
+
+        if pico8.btnp(Button::C) {
+            pico8.load_breadcrumb();
+        }
+    }
+
+    fn draw(&mut self, pico8: &mut Pico8) {
+        pico8.cls(1);
+        pico8.print("HELLO, RUNTY8!", 36, 2, 7);
+        pico8.print("ARROWS: MOVE  C: BACK", 22, 9, 6);
+
+        pico8.spr(1, self.x, self.y);
+    }
+}
@@ -0,0 +1,108 @@
+mod drawing;
+mod hello;
+mod particles;
+mod platformer;
+
+use runty8::ui::text::Text;
+use runty8::ui::{DrawFn, Element, Tree};
+use runty8::{App, Button, Pico8};
+
+// Shared by every demo.
+const ASSETS: &str = "examples/gallery";
+
+fn main() -> Result<(), runty8::Error> {
+    runty8::run_app::<Gallery>(ASSETS.to_owned())
+}
+
+struct Demo {
+    // What the demo's cart is registered as.
+    name: &'static str,
+    title: &'static str,
+    about: &'static str,
+}
+
+const DEMOS: [Demo; 4] = [
+    Demo {
+        name: "hello",
+        title: "HELLO WORLD",
+        about: "A SPRITE MOVED WITH BTN AND SPR.",
+    },
+    Demo {
+        name: "platformer",
+        title: "PLATFORMER",
+        about: "SCROLLS THE MAP, WITH FGET FLAGS FOR COLLISIONS.",
+    },
+    Demo {
+        name: "drawing",
+        title: "DRAWING TOY",
+        about: "PAINTS WITH THE MOUSE.",
+    },
+    Demo {
+        name: "particles",
+        title: "PARTICLES",
+        about: "A FOUNTAIN OF PSET PARTICLES.",
+    },
+];
+
+/// Lists the demos, each one is its own cart (see `Pico8::register_cart`),
+/// and goes back here with `Pico8::load_breadcrumb`.
+struct Gallery {
+    selected: usize,
+}
+
+impl App for Gallery {
+    fn init(pico8: &mut Pico8) -> Self {
+        pico8.register_cart::<hello::Hello>("hello", ASSETS);
+        pico8.register_cart::<platformer::Platformer>("platformer", ASSETS);
+        pico8.register_cart::<drawing::Drawing>("drawing", ASSETS);
+        pico8.register_cart::<particles::Particles>("particles", ASSETS);
+
+        Self { selected: 0 }
+    }
+
+    fn update(&mut self, pico8: &mut Pico8) {
+        if pico8.btnp(Button::Up) {
+            self.selected = (self.selected + DEMOS.len() - 1) % DEMOS.len();
+        }
+        if pico8.btnp(Button::Down) {
+            self.selected = (self.selected + 1) % DEMOS.len();
+        }
+        if pico8.btnp(Button::X) {
+            pico8.load_cart(DEMOS[self.selected].name).unwrap();
+        }
+    }
+
+    fn draw(&mut self, pico8: &mut Pico8) {
+        pico8.cls(1);
+
+        menu(self.selected).as_widget_mut().draw(pico8);
+    }
+}
+
+// Only drawn: the selection moves with the buttons, like in the demos.
+fn menu(selected: usize) -> Element<'static, ()> {
+    let mut tree = Tree::new().push(Text::new("RUNTY8 GALLERY", 36, 8, 7));
+
+    for (index, demo) in DEMOS.iter().enumerate() {
+        let y = 28 + index as i32 * 12;
+
+        if index == selected {
+            tree = tree
+                .push(DrawFn::new(move |pico8| {
+                    pico8.rectfill(8, y - 2, 119, y + 6, 13)
+                }))
+                .push(Text::new(demo.title, 16, y, 7));
+        } else {
+            tree = tree.push(Text::new(demo.title, 16, y, 6));
+        }
+    }
+
+    tree.push(
+        Text::new(DEMOS[selected].about, 8, 84, 6)
+            .max_width(112)
+            .line_spacing(1),
+    )
+    .push(Text::new("UP/DOWN: PICK  X: START", 18, 112, 13))
+    .push(Text::new("C GOES BACK TO THE GALLERY", 12, 119, 13))
+    .into()
+}
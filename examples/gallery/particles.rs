@@ -0,0 +1,81 @@
+//! Particles drawn with `pset`: a fountain, moved with the arrow keys.
+
+use runty8::{App, Button, Pico8};
+
+// Colors as the particles age, brightest first.
+const COLORS: [u8; 5] = [7, 10, 9, 8, 2];
+const MAX_AGE: u32 = 60;
+
+struct Particle {
+    x: f32,
+    y: f32,
+    dx: f32,
+    dy: f32,
+    age: u32,
+}
+
+pub struct Particles {
+    particles: Vec<Particle>,
+    spout_x: f32,
+}
+
+impl App for Particles {
+    fn init(_: &mut Pico8) -> Self {
+        Self {
+            particles: vec![],
+            spout_x: 64.0,
+        }
+    }
+
+    fn update(&mut self, pico8: &mut Pico8) {
+        if pico8.btnp(Button::C) {
+            pico8.load_breadcrumb();
+        }
+        if pico8.btn(Button::Left) {
+            self.spout_x -= 1.0;
+        }
+        if pico8.btn(Button::Right) {
+            self.spout_x += 1.0;
+        }
+        self.spout_x = self.spout_x.clamp(0.0, 127.0);
+
+        let spray = if pico8.btn(Button::X) { 12 } else { 4 };
+        for _ in 0..spray {
+            self.particles.push(Particle {
+                x: self.spout_x,
+                y: 110.0,
+                dx: pico8.rnd(2.0) - 1.0,
+                dy: -2.0 - pico8.rnd(1.5),
+                age: 0,
+            });
+        }
+
+        for particle in &mut self.particles {
+            particle.dy += 0.08;
+            particle.x += particle.dx;
+            particle.y += particle.dy;
+            particle.age += 1;
+        }
+        self.particles
+            .retain(|particle| particle.age < MAX_AGE && particle.y < 128.0);
+    }
+
+    fn draw(&mut self, pico8: &mut Pico8) {
+        pico8.cls(0);
+
+        for particle in &self.particles {
+            let color = COLORS[particle.age as usize * COLORS.len() / MAX_AGE as usize];
+
+            pico8.pset(particle.x as i32, particle.y as i32, color);
+        }
+        pico8.rectfill(
+            self.spout_x as i32 - 3,
+            111,
+            self.spout_x as i32 + 3,
+            114,
+            5,
+        );
+
+        pico8.print("ARROWS: MOVE  X: MORE  C: BACK", 4, 2, 6);
+    }
+}
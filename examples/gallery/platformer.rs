@@ -0,0 +1,145 @@
+//! A platformer skeleton: the level is the map, and sprites with flag 0 are solid.
+//! The camera follows the player along the map.
+
+use runty8::{App, Button, Pico8};
+
+// Flag set on the ground, dirt and brick sprites.
+const SOLID: u8 = 0;
+const GOAL: u8 = 19;
+
+const START: (f32, f32) = (16.0, 80.0);
+const SPEED: f32 = 1.5;
+const JUMP_SPEED: f32 = 4.0;
+const GRAVITY: f32 = 0.3;
+const MAX_FALL_SPEED: f32 = 4.0;
+
+pub struct Platformer {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    dx: f32,
+    dy: f32,
+    pub(crate) on_ground: bool,
+    won: bool,
+}
+
+impl App for Platformer {
+    fn init(_: &mut Pico8) -> Self {
+        Self {
+            x: START.0,
+            y: START.1,
+            dx: 0.0,
+            dy: 0.0,
+            on_ground: false,
+            won: false,
+        }
+    }
+
+    fn update(&mut self, pico8: &mut Pico8) {
+        if pico8.btnp(Button::C) {
+            pico8.load_breadcrumb();
+        }
+
+        self.dx = 0.0;
+        if pico8.btn(Button::Left) {
+            self.dx -= SPEED;
+        }
+        if pico8.btn(Button::Right) {
+            self.dx += SPEED;
+        }
+        if self.on_ground && (pico8.btnp(Button::Up) || pico8.btnp(Button::X)) {
+            self.dy = -JUMP_SPEED;
+        }
+        self.dy = (self.dy + GRAVITY).min(MAX_FALL_SPEED);
+
+        // One axis at a time, so that running into a wall doesn't stop a fall.
+        if let Some((x, _)) = self.slide(pico8, self.dx, 0.0) {
+            self.x = x;
+        }
+        match self.slide(pico8, 0.0, self.dy) {
+            Some((_, y)) => {
+                self.y = y;
+                self.on_ground = false;
+            }
+            None => {
+                self.on_ground = self.dy > 0.0;
+                self.dy = 0.0;
+            }
+        }
+
+        let (width, height) = level_size(pico8);
+        self.x = self.x.clamp(0.0, width - 8.0);
+        if self.y > height {
+            (self.x, self.y) = START;
+        }
+
+        let cell = cell_at(self.x + 4.0, self.y + 4.0);
+        self.won |= pico8.mget(cell.0, cell.1) == GOAL;
+    }
+
+    fn draw(&mut self, pico8: &mut Pico8) {
+        pico8.cls(12);
+
+        let (width, _) = level_size(pico8);
+        let camera_x = (self.x as i32 - 60).clamp(0, width as i32 - 128);
+        pico8.camera(camera_x, 0);
+        let map = pico8.resources().map();
+        let (map_width, map_height) = (map.width() as i32, map.height() as i32);
+        pico8.map(0, 0, 0, 0, map_width, map_height, 0);
+        pico8.spr(1, self.x as i32, self.y as i32);
+
+        pico8.camera(0, 0);
+        if self.won {
+            pico8.print("YOU MADE IT!", 40, 40, 7);
+        } else {
+            pico8.print("ARROWS: RUN  X: JUMP  C: BACK", 6, 2, 1);
+        }
+    }
+}
+
+impl Platformer {
+    /// Moves by (`dx`, `dy`) a pixel at a time,
+    /// returns `None` if the player can't move at all without going into something solid.
+    fn slide(&self, pico8: &Pico8, dx: f32, dy: f32) -> Option<(f32, f32)> {
+        let steps = dx.abs().max(dy.abs()).ceil() as i32;
+        let mut position = None;
+
+        for step in 1..=steps {
+            let t = step as f32 / steps as f32;
+            let (x, y) = (self.x + dx * t, self.y + dy * t);
+
+            if collides(pico8, x, y) {
+                break;
+            }
+            position = Some((x, y));
+        }
+
+        position
+    }
+}
+
+// In pixels.
+fn level_size(pico8: &Pico8) -> (f32, f32) {
+    let map = pico8.resources().map();
+
+    ((map.width() * 8) as f32, (map.height() * 8) as f32)
+}
+
+fn cell_at(x: f32, y: f32) -> (i32, i32) {
+    ((x / 8.0).floor() as i32, (y / 8.0).floor() as i32)
+}
+
+// Whether the player's 6x8 box at (x, y) overlaps a solid cell.
+fn collides(pico8: &Pico8, x: f32, y: f32) -> bool {
+    [
+        (x + 1.0, y),
+        (x + 6.0, y),
+        (x + 1.0, y + 7.0),
+        (x + 6.0, y + 7.0),
+    ]
+    .into_iter()
+    .any(|(x, y)| {
+        let (cell_x, cell_y) = cell_at(x, y);
+
+        pico8.fget_n(pico8.mget(cell_x, cell_y) as usize, SOLID)
+    })
+}
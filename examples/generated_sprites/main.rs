@@ -0,0 +1,73 @@
+use runty8::ui::{DrawFn, Element};
+use runty8::{ElmApp, Event, Resources};
+
+fn main() -> Result<(), runty8::Error> {
+    runty8::run_elm_app::<GeneratedSprites>("examples/generated_sprites".to_owned())
+}
+
+/// Fills a few sprites procedurally when the app starts,
+/// instead of loading them from the sprite sheet file.
+struct GeneratedSprites {
+    sprites: Vec<usize>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Msg {}
+
+impl ElmApp for GeneratedSprites {
+    type Msg = Msg;
+
+    fn init() -> Self {
+        Self {
+            sprites: vec![1, 2, 3, 4],
+        }
+    }
+
+    fn init_with_resources(resources: &mut Resources) -> Self {
+        let app = Self::init();
+
+        for (i, &sprite) in app.sprites.iter().enumerate() {
+            let base_x = (sprite % 16) as i32 * 8;
+            let base_y = (sprite / 16) as i32 * 8;
+
+            for x in 0..8 {
+                for y in 0..8 {
+                    let color = match i {
+                        // Checkerboard
+                        0 => 7 * ((x + y) % 2) as u8,
+                        // Horizontal stripes
+                        1 => 8 + (y % 2) as u8,
+                        // Frame
+                        2 if x == 0 || y == 0 || x == 7 || y == 7 => 12,
+                        2 => 1,
+                        // Diagonal
+                        _ if x == y => 11,
+                        _ => 3,
+                    };
+
+                    resources.sset(base_x + x, base_y + y, color);
+                }
+            }
+        }
+
+        app
+    }
+
+    fn update(&mut self, _: &Self::Msg, _: &mut Resources) {}
+
+    fn view(&mut self, _: &Resources) -> Element<'_, Self::Msg> {
+        let sprites = &self.sprites;
+
+        DrawFn::new(move |draw| {
+            draw.cls(0);
+            draw.print("GENERATED IN INIT", 0, 0, 7);
+
+            for (i, &sprite) in sprites.iter().enumerate() {
+                draw.spr(sprite, 16 + 24 * i as i32, 60);
+            }
+        })
+        .into()
+    }
+
+    fn subscriptions(&self, _: &Event, _: &mut Vec<Self::Msg>) {}
+}
@@ -0,0 +1,46 @@
+//! Press X to jump. Needs the `audio` feature to make sound:
+//!
+//! ```bash
+//! cargo run --example jump_sound --features audio -- --game
+//! ```
+use runty8::{App, Button, Pico8};
+
+fn main() -> Result<(), runty8::Error> {
+    runty8::run_app::<JumpSound>("examples/jump_sound".to_owned())
+}
+
+const GROUND: i32 = 100;
+const JUMP_SFX: i32 = 0;
+
+struct JumpSound {
+    y: i32,
+    speed: i32,
+}
+
+impl App for JumpSound {
+    fn init(_: &mut Pico8) -> Self {
+        Self {
+            y: GROUND,
+            speed: 0,
+        }
+    }
+
+    fn update(&mut self, pico8: &mut Pico8) {
+        let on_ground = self.y >= GROUND;
+
+        if on_ground && pico8.btnp(Button::X) {
+            self.speed = -6;
+            pico8.sfx(JUMP_SFX, -1);
+        }
+
+        self.speed += 1;
+        self.y = (self.y + self.speed).min(GROUND);
+    }
+
+    fn draw(&mut self, pico8: &mut Pico8) {
+        pico8.cls(1);
+        pico8.print("PRESS X TO JUMP", 34, 20, 7);
+        pico8.line(0, GROUND + 8, 127, GROUND + 8, 3);
+        pico8.rectfill(60, self.y, 67, self.y + 7, 8);
+    }
+}
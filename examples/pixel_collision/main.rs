@@ -0,0 +1,118 @@
+use runty8::{collision, App, Button, Pico8};
+
+fn main() -> Result<(), runty8::Error> {
+    runty8::run_app::<PixelCollision>("examples/pixel_collision".to_owned())
+}
+
+const BALL: usize = 1;
+const SLOPE: usize = 2;
+const BLOCK: usize = 3;
+const GRASS: usize = 4;
+
+// Flag set on the sprites the ball can't go through.
+const SOLID: u8 = 0;
+
+/// A ball moved with the arrow keys, which stops at the exact pixels of the map's solid sprites.
+struct PixelCollision {
+    x: i32,
+    y: i32,
+    blocked: bool,
+}
+
+impl App for PixelCollision {
+    fn init(pico8: &mut Pico8) -> Self {
+        for x in 0..8 {
+            for y in 0..8 {
+                let (dx, dy) = (x as f32 - 3.5, y as f32 - 3.5);
+                let ball = if dx * dx + dy * dy < 12.0 { 8 } else { 0 };
+                let slope = if x + y >= 7 { 4 } else { 0 };
+                let grass = if (x + y) % 3 == 0 && y > 4 { 11 } else { 0 };
+
+                pico8.sset(BALL as i32 * 8 + x, y, ball);
+                pico8.sset(SLOPE as i32 * 8 + x, y, slope);
+                pico8.sset(BLOCK as i32 * 8 + x, y, 5);
+                pico8.sset(GRASS as i32 * 8 + x, y, grass);
+            }
+        }
+        pico8.fset(SLOPE, SOLID as usize, true);
+        pico8.fset(BLOCK, SOLID as usize, true);
+
+        let (width, height) = pico8.map_size();
+        for x in 0..width {
+            for y in 0..height {
+                // Walls around the first screen.
+                let on_screen = x < 16 && y < 16;
+                let border = x == 0 || y == 0 || x == 15 || y == 15;
+                let sprite = if on_screen && border { BLOCK } else { 0 };
+
+                pico8.mset(x, y, sprite as u8);
+            }
+        }
+        // A hill going up to the right.
+        for step in 0..6 {
+            let x = 3 + step;
+            pico8.mset(x, 14 - step, SLOPE as u8);
+            for y in 15 - step..15 {
+                pico8.mset(x, y, BLOCK as u8);
+            }
+        }
+        pico8.mset(4, 4, BLOCK as u8);
+        pico8.mset(10, 5, GRASS as u8);
+        pico8.mset(11, 5, GRASS as u8);
+
+        Self {
+            x: 60,
+            y: 20,
+            blocked: false,
+        }
+    }
+
+    fn update(&mut self, pico8: &mut Pico8) {
+        let dx = pico8.btn(Button::Right) as i32 - pico8.btn(Button::Left) as i32;
+        let dy = pico8.btn(Button::Down) as i32 - pico8.btn(Button::Up) as i32;
+
+        // One axis at a time, so the ball can slide along walls.
+        let x_blocked = collides(pico8, self.x + dx, self.y);
+        if !x_blocked {
+            self.x += dx;
+        }
+        let y_blocked = collides(pico8, self.x, self.y + dy);
+        if !y_blocked {
+            self.y += dy;
+        }
+
+        self.blocked = (dx != 0 && x_blocked) || (dy != 0 && y_blocked);
+    }
+
+    fn draw(&mut self, pico8: &mut Pico8) {
+        pico8.cls(1);
+        pico8.map(0, 0, 0, 0, 16, 16, 0);
+        pico8.spr(BALL, self.x, self.y);
+
+        let text = if self.blocked {
+            "BLOCKED"
+        } else {
+            "ARROWS TO MOVE"
+        };
+        pico8.print(text, 10, 10, 7);
+    }
+}
+
+/// Whether any opaque pixel of the ball, drawn at (x, y),
+/// lands on an opaque pixel of a solid sprite of the map.
+fn collides(pico8: &Pico8, x: i32, y: i32) -> bool {
+    // Nothing solid in the ball's box, no need to look at its pixels.
+    if !collision::map_collides(pico8, x, y, 8, 8, SOLID) {
+        return false;
+    }
+
+    let cells = |pixel: i32| pixel.div_euclid(8)..=(pixel + 7).div_euclid(8);
+    cells(y)
+        .flat_map(|cel_y| cells(x).map(move |cel_x| (cel_x, cel_y)))
+        .any(|(cel_x, cel_y)| {
+            let tile = pico8.mget(cel_x, cel_y) as usize;
+
+            pico8.fget_n(tile, SOLID)
+                && collision::sprite_overlap(pico8, BALL, (x, y), tile, (cel_x * 8, cel_y * 8))
+        })
+}
@@ -1,7 +1,7 @@
 use runty8::{App, Button, Pico8};
 
-fn main() {
-    runty8::run_app::<StressLines>("examples/stress_lines".to_owned()).unwrap();
+fn main() -> Result<(), runty8::Error> {
+    runty8::run_app::<StressLines>("examples/stress_lines".to_owned())
 }
 struct StressLines {
     mouse: MouseState,
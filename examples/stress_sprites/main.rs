@@ -0,0 +1,112 @@
+use runty8::{rnd, App, Button, Pico8};
+use std::time::{Duration, Instant};
+
+fn main() -> Result<(), runty8::Error> {
+    runty8::run_app::<StressSprites>("examples/stress_sprites".to_owned())
+}
+
+/// Bounces a few thousand sprites around, showing how long drawing them takes.
+/// Up and down change how many there are.
+struct StressSprites {
+    sprites: Vec<Bouncer>,
+    // Draw times of the last few frames, to show their average.
+    draw_times: Vec<Duration>,
+}
+
+const STEP: usize = 500;
+
+impl App for StressSprites {
+    fn init(pico8: &mut Pico8) -> Self {
+        // Four sprites: a ball with a transparent outline, and three solid ones.
+        for sprite in 1..=4 {
+            for x in 0..8 {
+                for y in 0..8 {
+                    let color = match sprite {
+                        1 if (x - 3) * (x - 4) + (y - 3) * (y - 4) > 8 => 0,
+                        1 => 8 + (x + y) % 3,
+                        _ => (sprite * 3 + x / 2 + y / 2) % 15 + 1,
+                    };
+                    pico8.sset(sprite * 8 + x, y, color as u8);
+                }
+            }
+        }
+
+        Self {
+            sprites: (0..STEP * 6).map(|_| Bouncer::new()).collect(),
+            draw_times: vec![],
+        }
+    }
+
+    fn update(&mut self, pico8: &mut Pico8) {
+        if pico8.btnp(Button::Up) {
+            self.sprites.extend((0..STEP).map(|_| Bouncer::new()));
+        }
+        if pico8.btnp(Button::Down) {
+            self.sprites
+                .truncate(self.sprites.len().saturating_sub(STEP));
+        }
+
+        for sprite in &mut self.sprites {
+            sprite.update();
+        }
+    }
+
+    fn draw(&mut self, pico8: &mut Pico8) {
+        let start = Instant::now();
+
+        pico8.cls(1);
+        for sprite in &self.sprites {
+            pico8.spr(sprite.sprite, sprite.x as i32, sprite.y as i32);
+        }
+
+        self.draw_times.push(start.elapsed());
+        if self.draw_times.len() > 30 {
+            self.draw_times.remove(0);
+        }
+        let average = self.draw_times.iter().sum::<Duration>() / self.draw_times.len() as u32;
+
+        pico8.rectfill(0, 0, 127, 6, 0);
+        pico8.print(
+            &format!(
+                "{} SPRITES {:.2}MS",
+                self.sprites.len(),
+                average.as_secs_f64() * 1000.0
+            ),
+            1,
+            1,
+            7,
+        );
+    }
+}
+
+struct Bouncer {
+    x: f32,
+    y: f32,
+    dx: f32,
+    dy: f32,
+    sprite: usize,
+}
+
+impl Bouncer {
+    fn new() -> Self {
+        Self {
+            x: rnd(120.0),
+            y: rnd(120.0),
+            dx: rnd(2.0) - 1.0,
+            dy: rnd(2.0) - 1.0,
+            sprite: 1 + rnd(4.0) as usize,
+        }
+    }
+
+    fn update(&mut self) {
+        self.x += self.dx;
+        self.y += self.dy;
+
+        if !(0.0..120.0).contains(&self.x) {
+            self.dx = -self.dx;
+        }
+        if !(0.0..120.0).contains(&self.y) {
+            self.dy = -self.dy;
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Going from one scene to another: fade out, swap the scene, fade back in.
+//!
+//! Press X to fade to the other scene, C to melt the screen away before fading in.
+use runty8::{transitions, App, Button, Pico8};
+
+fn main() -> Result<(), runty8::Error> {
+    runty8::run_app::<Transitions>("examples/transitions".to_owned())
+}
+
+const FADE_FRAMES: i32 = 15;
+const MELT_FRAMES: i32 = 45;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Scene {
+    Title,
+    Level,
+}
+
+impl Scene {
+    fn other(self) -> Self {
+        match self {
+            Scene::Title => Scene::Level,
+            Scene::Level => Scene::Title,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Transition {
+    None,
+    FadingOut { frame: i32 },
+    FadingIn { frame: i32 },
+    Melting { frame: i32 },
+}
+
+struct Transitions {
+    scene: Scene,
+    transition: Transition,
+}
+
+impl App for Transitions {
+    fn init(_: &mut Pico8) -> Self {
+        Self {
+            scene: Scene::Title,
+            transition: Transition::None,
+        }
+    }
+
+    fn update(&mut self, pico8: &mut Pico8) {
+        self.transition = match self.transition {
+            Transition::None if pico8.btnp(Button::X) => Transition::FadingOut { frame: 0 },
+            Transition::None if pico8.btnp(Button::C) => Transition::Melting { frame: 0 },
+            Transition::None => Transition::None,
+            // Swap scenes while the screen is black.
+            Transition::FadingOut { frame: FADE_FRAMES }
+            | Transition::Melting { frame: MELT_FRAMES } => {
+                self.scene = self.scene.other();
+
+                Transition::FadingIn { frame: 0 }
+            }
+            Transition::FadingOut { frame } => Transition::FadingOut { frame: frame + 1 },
+            Transition::FadingIn { frame: FADE_FRAMES } => Transition::None,
+            Transition::FadingIn { frame } => Transition::FadingIn { frame: frame + 1 },
+            Transition::Melting { frame } => Transition::Melting { frame: frame + 1 },
+        };
+    }
+
+    fn draw(&mut self, pico8: &mut Pico8) {
+        match self.transition {
+            Transition::None => {
+                draw_scene(pico8, self.scene);
+                transitions::fade_palette(pico8, 0.0);
+            }
+            Transition::FadingOut { frame } => {
+                draw_scene(pico8, self.scene);
+                transitions::fade_palette(pico8, frame as f32 / FADE_FRAMES as f32);
+            }
+            Transition::FadingIn { frame } => {
+                draw_scene(pico8, self.scene);
+                transitions::fade_palette(pico8, 1.0 - frame as f32 / FADE_FRAMES as f32);
+            }
+            Transition::Melting { frame } => {
+                draw_scene(pico8, self.scene);
+                transitions::screen_wipe(pico8, frame as f32 / MELT_FRAMES as f32, 0);
+            }
+        }
+    }
+}
+
+fn draw_scene(pico8: &mut Pico8, scene: Scene) {
+    match scene {
+        Scene::Title => {
+            pico8.cls(1);
+            pico8.circfill(64, 52, 20, 9);
+            pico8.print_shadowed("TRANSITIONS", 42, 86, 7, 0);
+            pico8.print("X: FADE  C: MELT", 32, 100, 6);
+        }
+        Scene::Level => {
+            pico8.cls(12);
+            pico8.rectfill(0, 100, 127, 127, 3);
+            pico8.rectfill(60, 92, 67, 99, 8);
+            pico8.print_outlined("LEVEL 1", 50, 20, 7, 0);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! A waterfall that's drawn once and flows by cycling three blues through the screen palette.
+//!
+//! Press X to stop and restart the water.
+use runty8::{App, Button, Color, Pico8};
+
+fn main() -> Result<(), runty8::Error> {
+    runty8::run_app::<Waterfall>("examples/waterfall".to_owned())
+}
+
+// Only the water can use these, every pixel of these colors cycles.
+const BLUES: [Color; 3] = [Color::DARK_BLUE, Color::BLUE, Color::LAVENDER];
+const PERIOD_FRAMES: u32 = 4;
+
+struct Waterfall {
+    flowing: bool,
+}
+
+impl App for Waterfall {
+    fn init(pico8: &mut Pico8) -> Self {
+        pico8.pal_cycle(&BLUES, PERIOD_FRAMES);
+
+        Self { flowing: true }
+    }
+
+    fn update(&mut self, pico8: &mut Pico8) {
+        if pico8.btnp(Button::X) {
+            self.flowing = !self.flowing;
+            if self.flowing {
+                pico8.pal_cycle(&BLUES, PERIOD_FRAMES);
+            } else {
+                pico8.pal_cycle_stop();
+            }
+        }
+    }
+
+    fn draw(&mut self, pico8: &mut Pico8) {
+        pico8.cls(0);
+
+        // Cliffs on both sides, grass on top.
+        pico8.rectfill(0, 40, 47, 127, 4);
+        pico8.rectfill(80, 40, 127, 127, 4);
+        pico8.rectfill(0, 36, 47, 39, 11);
+        pico8.rectfill(80, 36, 127, 39, 11);
+
+        // Each cycle step shows every stripe in the color the one above it had,
+        // so stripes drawn in reverse cycle order fall down.
+        for y in 30..112 {
+            let blue = BLUES[2 - (y / 2) as usize % 3];
+            pico8.line(48, y, 79, y, blue);
+        }
+
+        // The pool at the bottom flows outwards from the fall.
+        for x in 0..64 {
+            let blue = BLUES[2 - (x / 3) as usize % 3];
+            pico8.line(63 - x, 112, 63 - x, 127, blue);
+            pico8.line(64 + x, 112, 64 + x, 127, blue);
+        }
+        pico8.rectfill(44, 110, 83, 113, 7);
+
+        let label = if self.flowing { "X: STOP" } else { "X: FLOW" };
+        pico8.print(label, 2, 2, 6);
+    }
+}
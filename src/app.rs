@@ -1,15 +1,26 @@
+use crate::cart::{self, Cart};
 use crate::pico8::Pico8;
 use crate::ui::DrawFn;
 use crate::ui::Element;
 use crate::Event;
 use crate::Resources;
 use std::fmt::Debug;
+use std::marker::PhantomData;
 
 /// A regular pico8 app
 pub trait App {
     fn init(pico8: &mut Pico8) -> Self;
     fn update(&mut self, pico8: &mut Pico8);
+    /// Can read the game's resources with [`Pico8::resources`], but not change them.
     fn draw(&mut self, pico8: &mut Pico8);
+
+    /// Called when the editor is opened over the game, to pause its music for example.
+    /// Does nothing by default.
+    fn editor_opened(&mut self, _pico8: &mut Pico8) {}
+
+    /// Called when the game is shown again, after [`App::editor_opened`].
+    /// Does nothing by default.
+    fn editor_closed(&mut self, _pico8: &mut Pico8) {}
 }
 
 /// An Elm-style app
@@ -17,9 +28,19 @@ pub trait App {
 pub trait ElmApp {
     type Msg: Copy + Debug;
     fn init() -> Self;
+    /// Builds the app with the game's resources loaded, to generate sprites
+    /// or look at the map before the first frame. Calls [`ElmApp::init`] by default.
+    fn init_with_resources(_resources: &mut Resources) -> Self
+    where
+        Self: Sized,
+    {
+        Self::init()
+    }
     fn update(&mut self, msg: &Self::Msg, resources: &mut Resources);
     fn view(&mut self, resources: &Resources) -> Element<'_, Self::Msg>;
-    fn subscriptions(&self, event: &Event) -> Vec<Self::Msg>;
+    /// Pushes the messages `event` turns into to `msgs`.
+    /// The same buffer is used for every event, so nothing is allocated once it's big enough.
+    fn subscriptions(&self, event: &Event, msgs: &mut Vec<Self::Msg>);
 }
 
 /// Wrapper structs
@@ -30,8 +51,10 @@ pub(crate) struct ElmAppCompat<A> {
 impl<A: ElmApp> AppCompat for ElmAppCompat<A> {
     type Msg = A::Msg;
 
-    fn init(_: &mut Pico8) -> Self {
-        Self { app: A::init() }
+    fn init(pico8: &mut Pico8) -> Self {
+        Self {
+            app: A::init_with_resources(&mut pico8.resources),
+        }
     }
 
     fn update(&mut self, msg: &Self::Msg, pico8: &mut Pico8) {
@@ -42,43 +65,64 @@ impl<A: ElmApp> AppCompat for ElmAppCompat<A> {
         self.app.view(resources)
     }
 
-    fn subscriptions(&self, event: &Event) -> Vec<Self::Msg> {
-        self.app.subscriptions(event)
+    fn subscriptions(&self, event: &Event, msgs: &mut Vec<Self::Msg>) {
+        self.app.subscriptions(event, msgs)
     }
 }
 
 #[derive(Clone, Copy, Debug)]
 pub enum Pico8AppMsg {
     Tick,
+    EditorOpened,
+    EditorClosed,
 }
 
 pub(crate) struct Pico8AppCompat<A> {
-    app: A,
+    // `A`, or the cart it switched to with `Pico8::load_cart`.
+    app: Box<dyn Cart>,
+    main: PhantomData<fn() -> A>,
 }
 
-impl<A: App> AppCompat for Pico8AppCompat<A> {
+impl<A: App + 'static> AppCompat for Pico8AppCompat<A> {
     type Msg = Pico8AppMsg;
 
+    /// Restarting starts the current cart again.
     fn init(pico8: &mut Pico8) -> Self {
+        let assets_path = pico8.resources.assets_path.clone();
+        pico8.carts.register_main(&assets_path, cart::init::<A>);
+        let init = pico8.carts.current_init().unwrap_or(cart::init::<A>);
+
         Self {
-            app: A::init(pico8),
+            app: init(pico8),
+            main: PhantomData,
         }
     }
 
-    fn update(&mut self, _: &Self::Msg, pico8: &mut Pico8) {
-        self.app.update(pico8);
+    fn update(&mut self, msg: &Self::Msg, pico8: &mut Pico8) {
+        match msg {
+            Pico8AppMsg::Tick => {
+                self.app.update(pico8);
+
+                if let Some(cart) = cart::load_requested(pico8) {
+                    self.app = cart;
+                }
+            }
+            Pico8AppMsg::EditorOpened => self.app.editor_opened(pico8),
+            Pico8AppMsg::EditorClosed => self.app.editor_closed(pico8),
+        }
     }
 
     fn view(&mut self, _: &mut Resources) -> Element<'_, Self::Msg> {
-        DrawFn::new(|draw| self.app.draw(draw)).into()
+        DrawFn::new(|draw| draw.draw_app(|draw| self.app.draw(draw))).into()
     }
 
-    fn subscriptions(&self, event: &Event) -> Vec<Self::Msg> {
-        if let Event::Tick { .. } = event {
-            vec![Pico8AppMsg::Tick]
-        } else {
-            vec![]
-        }
+    fn subscriptions(&self, event: &Event, msgs: &mut Vec<Self::Msg>) {
+        msgs.extend(match event {
+            Event::Tick { .. } => Some(Pico8AppMsg::Tick),
+            Event::EditorOpened => Some(Pico8AppMsg::EditorOpened),
+            Event::EditorClosed => Some(Pico8AppMsg::EditorClosed),
+            _ => None,
+        })
     }
 }
 
@@ -88,5 +132,5 @@ pub(crate) trait AppCompat {
     fn init(pico8: &mut Pico8) -> Self;
     fn update(&mut self, msg: &Self::Msg, pico8: &mut Pico8);
     fn view(&mut self, resources: &mut Resources) -> Element<'_, Self::Msg>;
-    fn subscriptions(&self, event: &Event) -> Vec<Self::Msg>;
+    fn subscriptions(&self, event: &Event, msgs: &mut Vec<Self::Msg>);
 }
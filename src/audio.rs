@@ -0,0 +1,160 @@
+//! The game's sound: sfx and music, played through the system's audio output
+//! with the `audio` feature, and rendered to WAV files.
+//!
+//! Games play them with [`Pico8::sfx`](crate::Pico8::sfx) and [`Pico8::music`](crate::Pico8::music).
+
+#[cfg(any(test, feature = "audio", feature = "editor"))]
+mod mixer;
+pub(crate) mod music;
+#[cfg(feature = "audio")]
+mod output;
+pub(crate) mod sfx;
+#[cfg(any(test, feature = "audio", feature = "editor"))]
+mod synth;
+pub(crate) mod volume;
+#[cfg(any(feature = "audio", feature = "editor"))]
+mod wav;
+
+use self::volume::Volume;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+
+pub use self::music::Music;
+pub use self::sfx::{Sfx, SoundEffects};
+// Also used by the editor (sound effects) and the command line (music patterns).
+#[cfg(any(feature = "audio", feature = "editor"))]
+pub use self::wav::{render_music_to_wav, render_sfx_to_wav};
+
+/// Samples per second generated by the mixer, resampled to whatever the output device uses.
+#[cfg(any(test, feature = "audio", feature = "editor"))]
+pub(crate) const SAMPLE_RATE: u32 = 22050;
+/// How many sounds can play at the same time.
+pub(crate) const CHANNELS: usize = 4;
+
+/// Sent from the game to the audio thread.
+// Only the mixer reads them, which isn't there without the audio output or the editor.
+#[cfg_attr(not(any(feature = "audio", feature = "editor")), allow(dead_code))]
+#[derive(Debug)]
+pub(crate) enum Command {
+    /// Play `sfx` on `channel`, or on a free one if `None`.
+    Play {
+        sfx: Sfx,
+        channel: Option<usize>,
+    },
+    /// Stop the sound on `channel`, or on all of them if `None`.
+    Stop {
+        channel: Option<usize>,
+    },
+    /// Play `music` from `pattern`, fading in over `fade_ms` milliseconds.
+    /// Sound effects without a channel won't use the channels set in `channel_mask`.
+    PlayMusic {
+        music: Music,
+        sound_effects: SoundEffects,
+        pattern: usize,
+        fade_ms: u32,
+        channel_mask: u8,
+    },
+    /// Stop the music, fading out over `fade_ms` milliseconds.
+    StopMusic {
+        fade_ms: u32,
+    },
+    SetVolume(Volume),
+}
+
+/// Where the music is at, written by the audio thread.
+#[derive(Debug)]
+pub(crate) struct MusicPosition {
+    // -1 when there's no music playing.
+    pattern: AtomicI32,
+    ticks: AtomicI32,
+}
+
+impl MusicPosition {
+    fn new() -> Self {
+        Self {
+            pattern: AtomicI32::new(-1),
+            ticks: AtomicI32::new(0),
+        }
+    }
+
+    #[cfg(feature = "audio")]
+    pub(crate) fn set(&self, position: Option<(usize, u32)>) {
+        let (pattern, ticks) =
+            position.map_or((-1, 0), |(pattern, ticks)| (pattern as i32, ticks as i32));
+
+        self.pattern.store(pattern, Ordering::Relaxed);
+        self.ticks.store(ticks, Ordering::Relaxed);
+    }
+}
+
+/// Handle to the audio thread. Silently ignores every command if there's no audio output.
+#[derive(Debug)]
+pub(crate) struct Audio {
+    sender: Option<Sender<Command>>,
+    music_position: Arc<MusicPosition>,
+    volume: Volume,
+}
+
+impl Audio {
+    pub(crate) fn new(volume: Volume) -> Self {
+        let music_position = Arc::new(MusicPosition::new());
+
+        let audio = Self {
+            sender: Self::start(music_position.clone()),
+            music_position,
+            volume,
+        };
+        audio.send(Command::SetVolume(volume));
+
+        audio
+    }
+
+    /// Never makes a sound, even with the `audio` feature.
+    #[cfg(any(test, feature = "bench", feature = "cli"))]
+    pub(crate) fn silent() -> Self {
+        Self {
+            sender: None,
+            music_position: Arc::new(MusicPosition::new()),
+            volume: Volume::new(),
+        }
+    }
+
+    #[cfg(feature = "audio")]
+    fn start(music_position: Arc<MusicPosition>) -> Option<Sender<Command>> {
+        output::start(music_position)
+            .map_err(|error| eprintln!("Couldn't start audio, the game will be silent: {error}"))
+            .ok()
+    }
+
+    #[cfg(not(feature = "audio"))]
+    fn start(_: Arc<MusicPosition>) -> Option<Sender<Command>> {
+        None
+    }
+
+    /// The music pattern being played, -1 if there's none.
+    pub(crate) fn music_pattern(&self) -> i32 {
+        self.music_position.pattern.load(Ordering::Relaxed)
+    }
+
+    /// Ticks (1/120ths of a second) played of the current music pattern.
+    pub(crate) fn music_ticks(&self) -> i32 {
+        self.music_position.ticks.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn volume(&self) -> Volume {
+        self.volume
+    }
+
+    pub(crate) fn set_volume(&mut self, volume: Volume) {
+        self.volume = volume;
+        self.send(Command::SetVolume(volume));
+    }
+
+    pub(crate) fn send(&self, command: Command) {
+        if let Some(sender) = &self.sender {
+            // The audio thread only goes away if the output device failed, keep going without sound.
+            let _ = sender.send(command);
+        }
+    }
+}
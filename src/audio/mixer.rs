@@ -0,0 +1,652 @@
+use super::music::Music;
+use super::sfx::{Note, Sfx, SoundEffects};
+use super::synth::Oscillator;
+use super::{Command, CHANNELS, SAMPLE_RATE};
+use std::cmp::Reverse;
+
+// Pico8 notes last `speed` ticks of 1/120th of a second each.
+const SAMPLES_PER_TICK: u32 = SAMPLE_RATE / 120;
+
+// So that four channels playing at full volume don't clip.
+const CHANNEL_GAIN: f32 = 1.0 / CHANNELS as f32;
+
+// Volume changes are spread over 5ms, jumping straight to the new volume clicks.
+const VOLUME_RAMP_SAMPLES: u32 = SAMPLE_RATE / 200;
+
+/// Plays sound effects and music on [`CHANNELS`] channels, producing mono samples at [`SAMPLE_RATE`].
+///
+/// Sound effects play over the music: a channel playing both only plays the sound effect.
+#[derive(Debug)]
+pub(crate) struct Mixer {
+    channels: [Option<Voice>; CHANNELS],
+    music: Option<MusicPlayer>,
+    sfx_gain: Ramp,
+    music_gain: Ramp,
+}
+
+/// Samples it takes to play every note of `sfx` once.
+pub(crate) fn sfx_length(sfx: &Sfx) -> u32 {
+    Sfx::NOTES as u32 * sfx.speed.max(1) as u32 * SAMPLES_PER_TICK
+}
+
+#[derive(Debug)]
+struct Voice {
+    sfx: Sfx,
+    note: usize,
+    // The note played before the current one, where slides start from.
+    previous_note: Option<Note>,
+    // Samples played of the current note.
+    note_samples: u32,
+    // How many notes have been played, to find the oldest sound.
+    notes_played: usize,
+    oscillator: Oscillator,
+}
+
+impl Voice {
+    fn new(sfx: Sfx) -> Self {
+        Self::with_oscillator(sfx, Oscillator::new())
+    }
+
+    fn with_oscillator(sfx: Sfx, oscillator: Oscillator) -> Self {
+        Self {
+            sfx,
+            note: 0,
+            previous_note: None,
+            note_samples: 0,
+            notes_played: 0,
+            oscillator,
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.note >= self.sfx.notes.len()
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        let note = self.sfx.notes[self.note];
+        let ticks = self.note_samples as f32 / SAMPLES_PER_TICK as f32;
+        let (frequency, volume) = self.sfx.sound(self.note, self.previous_note, ticks);
+        let sample = self.oscillator.next_sample(note.waveform, frequency) * volume;
+
+        self.note_samples += 1;
+        if self.note_samples >= self.sfx.speed.max(1) as u32 * SAMPLES_PER_TICK {
+            self.note_samples = 0;
+            self.previous_note = Some(note);
+            self.notes_played += 1;
+            self.note += 1;
+
+            if self.sfx.loops() && self.note >= self.sfx.loop_end as usize {
+                self.note = self.sfx.loop_start as usize;
+            }
+        }
+
+        sample
+    }
+}
+
+impl Mixer {
+    pub(crate) fn new() -> Self {
+        Self {
+            channels: Default::default(),
+            music: None,
+            sfx_gain: Ramp::new(1.0),
+            music_gain: Ramp::new(1.0),
+        }
+    }
+
+    pub(crate) fn apply(&mut self, command: Command) {
+        match command {
+            Command::Play { sfx, channel } => {
+                if let Some(slot) = channel
+                    .or_else(|| self.free_channel())
+                    .and_then(|channel| self.channels.get_mut(channel))
+                {
+                    *slot = Some(Voice::new(sfx));
+                }
+            }
+            Command::Stop { channel: None } => self.channels = Default::default(),
+            Command::Stop {
+                channel: Some(channel),
+            } => {
+                if let Some(slot) = self.channels.get_mut(channel) {
+                    *slot = None;
+                }
+            }
+            Command::PlayMusic {
+                music,
+                sound_effects,
+                pattern,
+                fade_ms,
+                channel_mask,
+            } => {
+                let previous = self.music.take();
+                self.music = MusicPlayer::new(
+                    music,
+                    sound_effects,
+                    pattern,
+                    Fade::fade_in(fade_ms),
+                    channel_mask,
+                    previous,
+                );
+            }
+            Command::StopMusic { fade_ms } => {
+                if let Some(music) = &mut self.music {
+                    music.fade = Fade::fade_out(music.fade.gain, fade_ms);
+                }
+            }
+            Command::SetVolume(volume) => {
+                let (sfx_gain, music_gain) = volume.gains();
+
+                self.sfx_gain.target = sfx_gain;
+                self.music_gain.target = music_gain;
+            }
+        }
+    }
+
+    // Where to play a sound effect when the game doesn't choose a channel.
+    // Channels reserved for music are never used. Otherwise, in order of preference:
+    // a silent channel, one only playing music, or the one that's been playing a sound for the longest.
+    fn free_channel(&self) -> Option<usize> {
+        let reserved = self.music.as_ref().map_or(0, |music| music.channel_mask);
+
+        (0..CHANNELS)
+            .filter(|channel| reserved & (1 << channel) == 0)
+            .min_by_key(|&channel| match &self.channels[channel] {
+                None if !self.is_playing_music(channel) => (0, Reverse(0)),
+                None => (1, Reverse(0)),
+                Some(voice) => (2, Reverse(voice.notes_played)),
+            })
+    }
+
+    #[cfg(test)]
+    pub(crate) fn is_playing(&self, channel: usize) -> bool {
+        matches!(self.channels.get(channel), Some(Some(_)))
+    }
+
+    fn is_playing_music(&self, channel: usize) -> bool {
+        self.music
+            .as_ref()
+            .is_some_and(|music| music.is_playing(channel))
+    }
+
+    /// The current music pattern, and how many ticks (1/120ths of a second) of it have played.
+    #[cfg(any(test, feature = "audio"))]
+    pub(crate) fn music_position(&self) -> Option<(usize, u32)> {
+        self.music
+            .as_ref()
+            .map(|music| (music.pattern, music.pattern_samples / SAMPLES_PER_TICK))
+    }
+
+    /// Samples left until the current music pattern is over.
+    #[cfg(any(feature = "audio", feature = "editor"))]
+    pub(crate) fn music_pattern_remaining(&self) -> Option<u32> {
+        self.music
+            .as_ref()
+            .map(|music| music.pattern_length - music.pattern_samples)
+    }
+
+    /// Next sample, in `-1.0..=1.0`.
+    pub(crate) fn next_sample(&mut self) -> f32 {
+        let mut sample = 0.0;
+        let sfx_gain = self.sfx_gain.next();
+        let music_gain = self.music_gain.next();
+
+        for channel in 0..CHANNELS {
+            // Music keeps going under sound effects, so that it's still in time when they end.
+            let music_sample = self
+                .music
+                .as_mut()
+                .map_or(0.0, |music| music.next_sample(channel) * music_gain);
+
+            sample += CHANNEL_GAIN
+                * match &mut self.channels[channel] {
+                    Some(voice) => {
+                        let sample = voice.next_sample() * sfx_gain;
+
+                        if voice.is_finished() {
+                            self.channels[channel] = None;
+                        }
+
+                        sample
+                    }
+                    None => music_sample,
+                };
+        }
+
+        if let Some(music) = &mut self.music {
+            if !music.advance() {
+                self.music = None;
+            }
+        }
+
+        sample
+    }
+}
+
+#[derive(Debug)]
+struct MusicPlayer {
+    music: Music,
+    sound_effects: SoundEffects,
+    pattern: usize,
+    // Samples played of the current pattern, and how many it lasts.
+    pattern_samples: u32,
+    pattern_length: u32,
+    voices: [Option<Voice>; CHANNELS],
+    fade: Fade,
+    // Channels sound effects can't take unless asked for explicitly, one bit per channel.
+    channel_mask: u8,
+}
+
+impl MusicPlayer {
+    // `None` if `pattern` is empty.
+    // Reuses the oscillators of `previous` so that switching songs doesn't click.
+    fn new(
+        music: Music,
+        sound_effects: SoundEffects,
+        pattern: usize,
+        fade: Fade,
+        channel_mask: u8,
+        previous: Option<MusicPlayer>,
+    ) -> Option<Self> {
+        let mut player = Self {
+            music,
+            sound_effects,
+            pattern,
+            pattern_samples: 0,
+            pattern_length: 0,
+            voices: previous.map(|previous| previous.voices).unwrap_or_default(),
+            fade,
+            channel_mask,
+        };
+
+        player.start_pattern(pattern).then_some(player)
+    }
+
+    // Returns whether there's anything to play.
+    fn start_pattern(&mut self, index: usize) -> bool {
+        let pattern = match self.music.get(index) {
+            Some(pattern) if !pattern.is_empty() => *pattern,
+            _ => return false,
+        };
+
+        for (voice, sfx) in self.voices.iter_mut().zip(pattern.sfx) {
+            let sfx = sfx.and_then(|sfx| self.sound_effects.get(sfx as usize));
+            let oscillator = voice
+                .take()
+                .map_or_else(Oscillator::new, |voice| voice.oscillator);
+
+            *voice = sfx.map(|sfx| Voice::with_oscillator(sfx.clone(), oscillator));
+        }
+
+        // Like in Pico8, the pattern lasts as long as its leftmost non looping sfx,
+        // or its leftmost sfx if they all loop.
+        let sfx = || self.voices.iter().flatten().map(|voice| &voice.sfx);
+        self.pattern_length = match sfx().find(|sfx| !sfx.loops()).or_else(|| sfx().next()) {
+            Some(sfx) => sfx_length(sfx),
+            None => return false,
+        };
+        self.pattern = index;
+        self.pattern_samples = 0;
+
+        true
+    }
+
+    fn is_playing(&self, channel: usize) -> bool {
+        matches!(&self.voices[channel], Some(voice) if !voice.is_finished())
+    }
+
+    fn next_sample(&mut self, channel: usize) -> f32 {
+        // Finished voices are kept until the next pattern, to reuse their oscillator.
+        match &mut self.voices[channel] {
+            Some(voice) if !voice.is_finished() => voice.next_sample() * self.fade.gain,
+            _ => 0.0,
+        }
+    }
+
+    // Moves forward by one sample, returns whether the music is still playing.
+    fn advance(&mut self) -> bool {
+        if !self.fade.advance() {
+            return false;
+        }
+
+        // Counting samples (instead of time) keeps loops from drifting.
+        self.pattern_samples += 1;
+        if self.pattern_samples < self.pattern_length {
+            return true;
+        }
+
+        match self.music.next(self.pattern) {
+            Some(next) => self.start_pattern(next),
+            None => false,
+        }
+    }
+}
+
+// Volume ramp, used to fade music in and out.
+#[derive(Debug)]
+struct Fade {
+    gain: f32,
+    // Change in gain per sample.
+    step: f32,
+}
+
+impl Fade {
+    fn fade_in(fade_ms: u32) -> Self {
+        match Self::samples(fade_ms) {
+            0 => Self {
+                gain: 1.0,
+                step: 0.0,
+            },
+            samples => Self {
+                gain: 0.0,
+                step: 1.0 / samples as f32,
+            },
+        }
+    }
+
+    fn fade_out(gain: f32, fade_ms: u32) -> Self {
+        Self {
+            gain,
+            step: -gain / Self::samples(fade_ms).max(1) as f32,
+        }
+    }
+
+    fn samples(fade_ms: u32) -> u32 {
+        (fade_ms as u64 * SAMPLE_RATE as u64 / 1000) as u32
+    }
+
+    // Returns whether it's still audible.
+    fn advance(&mut self) -> bool {
+        self.gain = (self.gain + self.step).min(1.0);
+
+        self.gain > 0.0 || self.step > 0.0
+    }
+}
+
+// Moves a gain towards its target at a fixed rate.
+#[derive(Debug)]
+struct Ramp {
+    gain: f32,
+    target: f32,
+}
+
+impl Ramp {
+    fn new(gain: f32) -> Self {
+        Self { gain, target: gain }
+    }
+
+    // The gain for the next sample.
+    fn next(&mut self) -> f32 {
+        const STEP: f32 = 1.0 / VOLUME_RAMP_SAMPLES as f32;
+
+        self.gain += (self.target - self.gain).clamp(-STEP, STEP);
+
+        self.gain
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::music::Pattern;
+    use super::super::sfx::{Effect, Waveform};
+    use super::super::volume::Volume;
+    use super::*;
+
+    fn beep(notes: usize) -> Sfx {
+        let mut sfx = Sfx::new();
+        sfx.speed = 1;
+
+        for note in sfx.notes.iter_mut().take(notes) {
+            *note = Note {
+                pitch: 33,
+                waveform: Waveform::Square,
+                volume: 7,
+                ..Note::default()
+            };
+        }
+
+        sfx
+    }
+
+    fn play(mixer: &mut Mixer, channel: Option<usize>) {
+        mixer.apply(Command::Play {
+            sfx: beep(2),
+            channel,
+        });
+    }
+
+    fn run(mixer: &mut Mixer, samples: u32) -> Vec<f32> {
+        (0..samples).map(|_| mixer.next_sample()).collect()
+    }
+
+    #[test]
+    fn silent_without_sounds() {
+        let mut mixer = Mixer::new();
+
+        assert!(run(&mut mixer, 100).iter().all(|&sample| sample == 0.0));
+    }
+
+    #[test]
+    fn plays_every_note_then_stops() {
+        let mut mixer = Mixer::new();
+        play(&mut mixer, Some(2));
+        assert!(mixer.is_playing(2));
+
+        // Two loud notes, then 30 silent ones.
+        let samples = run(&mut mixer, 32 * SAMPLES_PER_TICK);
+        let (loud, quiet) = samples.split_at(2 * SAMPLES_PER_TICK as usize);
+        assert!(loud.iter().all(|&sample| sample.abs() == CHANNEL_GAIN));
+        assert!(quiet.iter().all(|&sample| sample == 0.0));
+
+        assert!(!mixer.is_playing(2));
+    }
+
+    #[test]
+    fn fade_out_ends_silent() {
+        let mut mixer = Mixer::new();
+        let mut sfx = beep(1);
+        sfx.notes[0].effect = Effect::FadeOut;
+        mixer.apply(Command::Play {
+            sfx,
+            channel: Some(0),
+        });
+
+        let loudness: Vec<f32> = run(&mut mixer, SAMPLES_PER_TICK)
+            .iter()
+            .map(|sample| sample.abs())
+            .collect();
+        assert_eq!(loudness[0], CHANNEL_GAIN);
+        assert!(loudness.windows(2).all(|pair| pair[1] < pair[0]));
+        assert!(*loudness.last().unwrap() < 0.01 * CHANNEL_GAIN);
+    }
+
+    #[test]
+    fn loops_forever() {
+        let mut mixer = Mixer::new();
+        let mut sfx = beep(2);
+        sfx.loop_end = 2;
+        mixer.apply(Command::Play {
+            sfx,
+            channel: Some(0),
+        });
+
+        let samples = run(&mut mixer, 100 * SAMPLES_PER_TICK);
+        assert!(samples.iter().all(|&sample| sample != 0.0));
+        assert!(mixer.is_playing(0));
+    }
+
+    #[test]
+    fn volume_changes_are_ramped() {
+        let mut mixer = Mixer::new();
+        let mut sfx = beep(2);
+        sfx.loop_end = 2;
+        mixer.apply(Command::Play {
+            sfx,
+            channel: Some(0),
+        });
+        run(&mut mixer, 10);
+
+        mixer.apply(Command::SetVolume(Volume {
+            muted: true,
+            ..Volume::new()
+        }));
+        let ramp = run(&mut mixer, VOLUME_RAMP_SAMPLES);
+        let loudness: Vec<f32> = ramp.iter().map(|sample| sample.abs()).collect();
+        assert!(loudness.windows(2).all(|pair| pair[1] < pair[0]));
+        assert!(loudness[0] > 0.9 * CHANNEL_GAIN);
+        assert!(run(&mut mixer, 100).iter().all(|&sample| sample == 0.0));
+
+        mixer.apply(Command::SetVolume(Volume {
+            master: 0.5,
+            ..Volume::new()
+        }));
+        let samples = run(&mut mixer, 2 * VOLUME_RAMP_SAMPLES);
+        assert_eq!(samples.last().unwrap().abs(), 0.5 * CHANNEL_GAIN);
+    }
+
+    #[test]
+    fn picks_free_channels_and_stops() {
+        let mut mixer = Mixer::new();
+
+        play(&mut mixer, Some(0));
+        play(&mut mixer, None);
+        assert!(mixer.is_playing(0) && mixer.is_playing(1));
+
+        // Channels that don't exist are ignored.
+        play(&mut mixer, Some(4));
+
+        mixer.apply(Command::Stop { channel: Some(0) });
+        assert!(!mixer.is_playing(0) && mixer.is_playing(1));
+
+        mixer.apply(Command::Stop { channel: None });
+        assert!((0..CHANNELS).all(|channel| !mixer.is_playing(channel)));
+    }
+
+    #[test]
+    fn busy_channels_are_replaced_oldest_first() {
+        let mut mixer = Mixer::new();
+
+        for channel in 0..CHANNELS {
+            play(&mut mixer, Some(channel));
+            run(&mut mixer, SAMPLES_PER_TICK);
+        }
+
+        // Channel 0 has played the most notes.
+        play(&mut mixer, None);
+        assert_eq!(mixer.free_channel(), Some(1));
+    }
+
+    // Sfx 0 is a long beep. Every pattern plays it on the given channels.
+    fn play_music(mixer: &mut Mixer, patterns: &[(Pattern, &[usize])], channel_mask: u8) {
+        let mut sound_effects = SoundEffects::new();
+        *sound_effects.get_mut(0).unwrap() = beep(Sfx::NOTES);
+
+        let mut music = Music::new();
+        for (index, (pattern, channels)) in patterns.iter().enumerate() {
+            let mut pattern = *pattern;
+            for &channel in channels.iter() {
+                pattern.sfx[channel] = Some(0);
+            }
+
+            *music.get_mut(index).unwrap() = pattern;
+        }
+
+        mixer.apply(Command::PlayMusic {
+            music,
+            sound_effects,
+            pattern: 0,
+            fade_ms: 0,
+            channel_mask,
+        });
+    }
+
+    fn pattern_length() -> u32 {
+        sfx_length(&beep(Sfx::NOTES))
+    }
+
+    #[test]
+    fn music_loops_without_drifting() {
+        let mut mixer = Mixer::new();
+        let begin = Pattern {
+            begin_loop: true,
+            ..Pattern::default()
+        };
+        let end = Pattern {
+            end_loop: true,
+            ..Pattern::default()
+        };
+        play_music(&mut mixer, &[(begin, &[0]), (end, &[0])], 0);
+
+        let length = pattern_length();
+        let samples = run(&mut mixer, length - 1);
+        assert_eq!(mixer.music_position(), Some((0, 31)));
+
+        let boundary = run(&mut mixer, 1);
+        assert_eq!(mixer.music_position(), Some((1, 0)));
+
+        let looped = [samples, boundary, run(&mut mixer, 99 * length)].concat();
+        assert_eq!(mixer.music_position(), Some((0, 0)));
+
+        // Sounds exactly like one long sfx, without clicks between the patterns.
+        let mut sfx = beep(Sfx::NOTES);
+        sfx.loop_end = Sfx::NOTES as u8;
+        let mut reference = Mixer::new();
+        reference.apply(Command::Play {
+            sfx,
+            channel: Some(0),
+        });
+        assert!(looped == run(&mut reference, 100 * length));
+    }
+
+    #[test]
+    fn music_stops_after_a_stop_pattern() {
+        let mut mixer = Mixer::new();
+        let stop = Pattern {
+            stop: true,
+            ..Pattern::default()
+        };
+        play_music(&mut mixer, &[(stop, &[0]), (Pattern::default(), &[0])], 0);
+
+        run(&mut mixer, pattern_length() - 1);
+        assert!(mixer.music_position().is_some());
+
+        run(&mut mixer, 1);
+        assert_eq!(mixer.music_position(), None);
+    }
+
+    #[test]
+    fn music_fades_out() {
+        let mut mixer = Mixer::new();
+        play_music(&mut mixer, &[(Pattern::default(), &[0])], 0);
+        mixer.apply(Command::StopMusic { fade_ms: 100 });
+
+        let samples = run(&mut mixer, SAMPLE_RATE / 20);
+        assert!(samples[SAMPLE_RATE as usize / 20 - 1].abs() < samples[0].abs());
+        assert!(mixer.music_position().is_some());
+
+        run(&mut mixer, SAMPLE_RATE / 10);
+        assert_eq!(mixer.music_position(), None);
+    }
+
+    #[test]
+    fn sfx_only_take_channels_not_reserved_for_music() {
+        let mut mixer = Mixer::new();
+        // Channels 0 and 1 play music, only 0 is reserved.
+        play_music(&mut mixer, &[(Pattern::default(), &[0, 1])], 0b0001);
+
+        // Silent channels first, then the unreserved music one.
+        for channel in [2, 3, 1] {
+            play(&mut mixer, None);
+            assert!(mixer.is_playing(channel));
+            run(&mut mixer, SAMPLES_PER_TICK);
+        }
+        assert!(!mixer.is_playing(0));
+
+        // Then the oldest sound, still not on the reserved channel.
+        assert_eq!(mixer.free_channel(), Some(2));
+
+        // Unless it's asked for.
+        play(&mut mixer, Some(0));
+        assert!(mixer.is_playing(0));
+    }
+}
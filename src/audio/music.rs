@@ -0,0 +1,253 @@
+use super::CHANNELS;
+use crate::serialize::{split_version, version_header, Serialize};
+use std::io::{self, Write};
+
+/// A step of a song: which sfx each channel plays, and where to go next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct Pattern {
+    /// Sfx played on each channel, `None` for silent channels.
+    pub(crate) sfx: [Option<u8>; CHANNELS],
+    /// Where an `end_loop` pattern jumps back to.
+    pub(crate) begin_loop: bool,
+    /// Jump back to the closest previous `begin_loop` pattern (or the first one) when done.
+    pub(crate) end_loop: bool,
+    /// Stop the music when done.
+    pub(crate) stop: bool,
+}
+
+impl Pattern {
+    const BEGIN_LOOP: u8 = 1;
+    const END_LOOP: u8 = 2;
+    const STOP: u8 = 4;
+    // Set on the sfx number of disabled channels.
+    const DISABLED: u8 = 0x40;
+
+    #[cfg(any(test, feature = "audio", feature = "editor"))]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.sfx.iter().all(Option::is_none)
+    }
+
+    // Same layout as a line of Pico8's __music__ section:
+    // the flags, a space, then the sfx number of each channel (2 hex digits each).
+    // Disabled channels have the 0x40 bit set, Pico8 writes them as 41, 42, 43 and 44.
+    fn serialize(&self) -> String {
+        let flags = [
+            (self.begin_loop, Self::BEGIN_LOOP),
+            (self.end_loop, Self::END_LOOP),
+            (self.stop, Self::STOP),
+        ]
+        .iter()
+        .filter(|(set, _)| *set)
+        .fold(0, |flags, (_, flag)| flags | flag);
+        let sfx: String = self
+            .sfx
+            .iter()
+            .enumerate()
+            .map(|(channel, sfx)| {
+                format!(
+                    "{:02x}",
+                    sfx.unwrap_or(Self::DISABLED | (channel as u8 + 1))
+                )
+            })
+            .collect();
+
+        format!("{flags:02x} {sfx}")
+    }
+
+    fn deserialize(line: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid music pattern: {line}");
+
+        let (flags, channels) = line.split_once(' ').ok_or_else(invalid)?;
+        if channels.len() != 2 * CHANNELS || !channels.is_ascii() {
+            return Err(invalid());
+        }
+        let flags = u8::from_str_radix(flags, 16).map_err(|_| invalid())?;
+
+        let mut pattern = Self {
+            begin_loop: flags & Self::BEGIN_LOOP != 0,
+            end_loop: flags & Self::END_LOOP != 0,
+            stop: flags & Self::STOP != 0,
+            ..Self::default()
+        };
+
+        for (channel, sfx) in pattern.sfx.iter_mut().enumerate() {
+            let digits = &channels[2 * channel..2 * channel + 2];
+            let number = u8::from_str_radix(digits, 16).map_err(|_| invalid())?;
+
+            *sfx = if number & Self::DISABLED == 0 {
+                Some(number)
+            } else {
+                None
+            };
+        }
+
+        Ok(pattern)
+    }
+}
+
+/// All of a game's music patterns, like Pico8's 64 pattern slots.
+#[derive(Debug, Clone)]
+pub struct Music {
+    patterns: Vec<Pattern>,
+}
+
+impl Music {
+    pub(crate) const COUNT: usize = 64;
+    const VERSION: u32 = 1;
+
+    pub(crate) fn file_name() -> String {
+        "music.txt".to_owned()
+    }
+
+    pub(crate) fn new() -> Self {
+        Self {
+            patterns: vec![Pattern::default(); Self::COUNT],
+        }
+    }
+
+    #[cfg(any(test, feature = "audio", feature = "editor"))]
+    pub(crate) fn get(&self, index: usize) -> Option<&Pattern> {
+        self.patterns.get(index)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn get_mut(&mut self, index: usize) -> Option<&mut Pattern> {
+        self.patterns.get_mut(index)
+    }
+
+    /// Pattern played after `index`, following its flags. `None` once the song is over.
+    #[cfg(any(test, feature = "audio", feature = "editor"))]
+    pub(crate) fn next(&self, index: usize) -> Option<usize> {
+        let pattern = self.get(index)?;
+
+        let next = if pattern.stop {
+            return None;
+        } else if pattern.end_loop {
+            (0..=index)
+                .rev()
+                .find(|&index| self.patterns[index].begin_loop)
+                .unwrap_or(0)
+        } else {
+            index + 1
+        };
+
+        self.get(next)
+            .filter(|pattern| !pattern.is_empty())
+            .map(|_| next)
+    }
+
+    /// One pattern per line, in the same format as Pico8's __music__ section.
+    /// Missing patterns at the end of the file are left empty.
+    pub(crate) fn deserialize(str: &str) -> Result<Self, String> {
+        let lines = match split_version(str)? {
+            (1, lines) => lines,
+            (version, _) => return Err(format!("Unsupported music version {version}")),
+        };
+
+        let mut patterns = lines
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(Pattern::deserialize)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if patterns.len() > Self::COUNT {
+            return Err(format!(
+                "Too many music patterns, at most {} are supported, got {}",
+                Self::COUNT,
+                patterns.len()
+            ));
+        }
+        patterns.resize(Self::COUNT, Pattern::default());
+
+        Ok(Self { patterns })
+    }
+}
+
+impl Serialize for Music {
+    fn serialize(&self, writer: &mut dyn Write) -> io::Result<()> {
+        write!(writer, "{}", version_header(Self::VERSION))?;
+        for pattern in &self.patterns {
+            write!(writer, "\n{}", pattern.serialize())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialize::to_string;
+
+    fn pattern(sfx: [Option<u8>; CHANNELS]) -> Pattern {
+        Pattern {
+            sfx,
+            ..Pattern::default()
+        }
+    }
+
+    #[test]
+    fn patterns_use_pico8_layout() {
+        let looping = Pattern {
+            begin_loop: true,
+            end_loop: true,
+            ..pattern([Some(1), None, Some(0x3f), None])
+        };
+
+        assert_eq!(looping.serialize(), "03 01423f44");
+        assert_eq!(Pattern::deserialize("03 01423f44"), Ok(looping));
+        assert_eq!(Pattern::default().serialize(), "00 41424344");
+
+        assert!(Pattern::deserialize("03 01423f").is_err());
+        assert!(Pattern::deserialize("0301423f44").is_err());
+    }
+
+    #[test]
+    fn music_round_trip() {
+        let mut music = Music::new();
+        *music.get_mut(3).unwrap() = Pattern {
+            stop: true,
+            ..pattern([Some(8), Some(9), None, None])
+        };
+
+        let serialized = to_string(&music);
+        assert!(serialized.starts_with("version 1\n"));
+
+        let deserialized = Music::deserialize(&serialized).unwrap();
+        assert_eq!(deserialized.get(3), music.get(3));
+        assert_eq!(deserialized.get(4), Some(&Pattern::default()));
+        assert_eq!(deserialized.get(64), None);
+    }
+
+    #[test]
+    fn follows_pattern_flags() {
+        let mut music = Music::new();
+        let beat = pattern([Some(0), None, None, None]);
+
+        *music.get_mut(0).unwrap() = beat;
+        *music.get_mut(1).unwrap() = Pattern {
+            begin_loop: true,
+            ..beat
+        };
+        *music.get_mut(2).unwrap() = beat;
+        *music.get_mut(3).unwrap() = Pattern {
+            end_loop: true,
+            ..beat
+        };
+        assert_eq!(music.next(0), Some(1));
+        assert_eq!(music.next(2), Some(3));
+        assert_eq!(music.next(3), Some(1));
+
+        // Without a begin loop pattern, loops back to the start.
+        music.get_mut(1).unwrap().begin_loop = false;
+        assert_eq!(music.next(3), Some(0));
+
+        music.get_mut(1).unwrap().stop = true;
+        assert_eq!(music.next(1), None);
+
+        // Pattern 4 is empty.
+        music.get_mut(3).unwrap().end_loop = false;
+        assert_eq!(music.next(3), None);
+    }
+}
@@ -0,0 +1,121 @@
+use super::mixer::Mixer;
+use super::{Command, MusicPosition, SAMPLE_RATE};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample, Stream, StreamConfig};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+
+/// Starts playing on the default output device, returning where to send commands.
+///
+/// Streams can't always be moved between threads, so it's created on
+/// (and kept alive by) a thread of its own.
+pub(super) fn start(music_position: Arc<MusicPosition>) -> Result<Sender<Command>, String> {
+    let (sender, receiver) = mpsc::channel();
+    let (started_sender, started_receiver) = mpsc::sync_channel(1);
+
+    std::thread::Builder::new()
+        .name("runty8-audio".to_owned())
+        .spawn(move || match build_stream(receiver, music_position) {
+            Ok(_stream) => {
+                let _ = started_sender.send(Ok(()));
+
+                loop {
+                    std::thread::park();
+                }
+            }
+            Err(error) => {
+                let _ = started_sender.send(Err(error));
+            }
+        })
+        .map_err(|error| error.to_string())?;
+
+    started_receiver
+        .recv()
+        .map_err(|error| error.to_string())??;
+
+    Ok(sender)
+}
+
+fn build_stream(
+    receiver: Receiver<Command>,
+    music_position: Arc<MusicPosition>,
+) -> Result<Stream, String> {
+    let device = cpal::default_host()
+        .default_output_device()
+        .ok_or("no output device")?;
+    let supported_config = device
+        .default_output_config()
+        .map_err(|error| error.to_string())?;
+    let config = supported_config.config();
+
+    let stream = match supported_config.sample_format() {
+        SampleFormat::F32 => build_typed_stream::<f32>(&device, config, receiver, music_position),
+        SampleFormat::I16 => build_typed_stream::<i16>(&device, config, receiver, music_position),
+        SampleFormat::U16 => build_typed_stream::<u16>(&device, config, receiver, music_position),
+        format => return Err(format!("unsupported sample format {format}")),
+    }?;
+    stream.play().map_err(|error| error.to_string())?;
+
+    Ok(stream)
+}
+
+fn build_typed_stream<T: SizedSample + FromSample<f32>>(
+    device: &cpal::Device,
+    config: StreamConfig,
+    receiver: Receiver<Command>,
+    music_position: Arc<MusicPosition>,
+) -> Result<Stream, String> {
+    let channels = config.channels.max(1) as usize;
+    let mut resampler = Resampler::new(config.sample_rate);
+    let mut mixer = Mixer::new();
+
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _| {
+                for command in receiver.try_iter() {
+                    mixer.apply(command);
+                }
+
+                for frame in data.chunks_mut(channels) {
+                    let sample = T::from_sample(resampler.next_sample(&mut mixer));
+
+                    frame.fill(sample);
+                }
+
+                music_position.set(mixer.music_position());
+            },
+            |error| eprintln!("Audio error: {error}"),
+            None,
+        )
+        .map_err(|error| error.to_string())
+}
+
+// Sample and hold: good enough for chiptune sounds, which are all hard edges anyway.
+struct Resampler {
+    // How many mixer samples each device sample advances.
+    step: f32,
+    position: f32,
+    sample: f32,
+}
+
+impl Resampler {
+    fn new(device_rate: u32) -> Self {
+        Self {
+            step: SAMPLE_RATE as f32 / device_rate.max(1) as f32,
+            // Starts "past" the first sample so that it gets mixed right away.
+            position: 1.0,
+            sample: 0.0,
+        }
+    }
+
+    fn next_sample(&mut self, mixer: &mut Mixer) -> f32 {
+        while self.position >= 1.0 {
+            self.sample = mixer.next_sample();
+            self.position -= 1.0;
+        }
+        self.position += self.step;
+
+        self.sample
+    }
+}
@@ -0,0 +1,482 @@
+use crate::serialize::{split_version, version_header, Serialize};
+use std::io::{self, Write};
+
+/// Shape of the sound a note makes, numbered like in Pico8.
+///
+/// Only triangle, sawtooth, square and noise are synthesized for now,
+/// the others are played with the closest of those.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum Waveform {
+    #[default]
+    Triangle,
+    TiltedSaw,
+    Sawtooth,
+    Square,
+    Pulse,
+    Organ,
+    Noise,
+    Phaser,
+}
+
+impl Waveform {
+    pub(crate) const ALL: [Waveform; 8] = [
+        Waveform::Triangle,
+        Waveform::TiltedSaw,
+        Waveform::Sawtooth,
+        Waveform::Square,
+        Waveform::Pulse,
+        Waveform::Organ,
+        Waveform::Noise,
+        Waveform::Phaser,
+    ];
+
+    fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+
+    pub(crate) fn index(self) -> u8 {
+        self as u8
+    }
+}
+
+/// Per-note effect, numbered like in Pico8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum Effect {
+    #[default]
+    None,
+    Slide,
+    Vibrato,
+    Drop,
+    FadeIn,
+    FadeOut,
+    ArpeggioFast,
+    ArpeggioSlow,
+}
+
+impl Effect {
+    pub(crate) const ALL: [Effect; 8] = [
+        Effect::None,
+        Effect::Slide,
+        Effect::Vibrato,
+        Effect::Drop,
+        Effect::FadeIn,
+        Effect::FadeOut,
+        Effect::ArpeggioFast,
+        Effect::ArpeggioSlow,
+    ];
+
+    fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+
+    pub(crate) fn index(self) -> u8 {
+        self as u8
+    }
+}
+
+// Vibrato bends the pitch up and down by this many semitones, 7.5 times a second.
+#[cfg(any(test, feature = "audio", feature = "editor"))]
+const VIBRATO_DEPTH: f32 = 0.5;
+#[cfg(any(test, feature = "audio", feature = "editor"))]
+const VIBRATO_PERIOD_TICKS: f32 = 16.0;
+
+// Arpeggios play a note of their group every 4 (fast) or 8 (slow) ticks.
+#[cfg(any(test, feature = "audio", feature = "editor"))]
+const ARPEGGIO_FAST_TICKS: f32 = 4.0;
+#[cfg(any(test, feature = "audio", feature = "editor"))]
+const ARPEGGIO_SLOW_TICKS: f32 = 8.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct Note {
+    /// Semitones above C0, in `0..=63`.
+    pub(crate) pitch: u8,
+    pub(crate) waveform: Waveform,
+    /// In `0..=7`, 0 is silent.
+    pub(crate) volume: u8,
+    pub(crate) effect: Effect,
+}
+
+impl Note {
+    pub(crate) const MAX_PITCH: u8 = 63;
+    pub(crate) const MAX_VOLUME: u8 = 7;
+
+    /// Frequency in Hz. Pitch 33 is A4 (440Hz).
+    #[cfg(any(test, feature = "audio", feature = "editor"))]
+    pub(crate) fn frequency(&self) -> f32 {
+        pitch_frequency(self.pitch as f32)
+    }
+}
+
+// Pitches between semitones are fine, for slides and vibrato.
+#[cfg(any(test, feature = "audio", feature = "editor"))]
+fn pitch_frequency(pitch: f32) -> f32 {
+    440.0 * 2f32.powf((pitch - 33.0) / 12.0)
+}
+
+/// A sound effect: 32 notes played one after the other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sfx {
+    pub(crate) notes: [Note; Sfx::NOTES],
+    /// How long each note lasts, in 1/120ths of a second.
+    pub(crate) speed: u8,
+    /// Notes `loop_start..loop_end` repeat forever, if `loop_end > loop_start`.
+    pub(crate) loop_start: u8,
+    pub(crate) loop_end: u8,
+}
+
+impl Sfx {
+    pub(crate) const NOTES: usize = 32;
+
+    pub(crate) fn new() -> Self {
+        Self {
+            notes: [Note::default(); Self::NOTES],
+            speed: 16,
+            loop_start: 0,
+            loop_end: 0,
+        }
+    }
+
+    #[cfg(any(test, feature = "audio", feature = "editor"))]
+    pub(crate) fn loops(&self) -> bool {
+        self.loop_end > self.loop_start
+    }
+
+    #[cfg(feature = "editor")]
+    pub(crate) fn is_silent(&self) -> bool {
+        self.notes.iter().all(|note| note.volume == 0)
+    }
+
+    /// Frequency (in Hz) and volume (in `0.0..=1.0`) of note `index`, `ticks` after it started,
+    /// with its effect applied. `previous` is the note played before it, which slides start from.
+    #[cfg(any(test, feature = "audio", feature = "editor"))]
+    pub(crate) fn sound(&self, index: usize, previous: Option<Note>, ticks: f32) -> (f32, f32) {
+        let note = self.notes[index];
+        // How much of the note has played, in `0.0..=1.0`.
+        let progress = (ticks / self.speed.max(1) as f32).clamp(0.0, 1.0);
+        let pitch = note.pitch as f32;
+        let volume = note.volume as f32 / Note::MAX_VOLUME as f32;
+
+        match note.effect {
+            Effect::None => (note.frequency(), volume),
+            Effect::Slide => {
+                let from = previous.map_or(pitch, |previous| previous.pitch as f32);
+
+                (pitch_frequency(from + (pitch - from) * progress), volume)
+            }
+            Effect::Vibrato => {
+                // Triangle wave in `-1.0..=1.0`, starting at 0 and going up.
+                let phase = (ticks / VIBRATO_PERIOD_TICKS + 0.25).fract();
+                let offset = 1.0 - 4.0 * (phase - 0.5).abs();
+
+                (pitch_frequency(pitch + VIBRATO_DEPTH * offset), volume)
+            }
+            Effect::Drop => (note.frequency() * (1.0 - progress), volume),
+            Effect::FadeIn => (note.frequency(), volume * progress),
+            Effect::FadeOut => (note.frequency(), volume * (1.0 - progress)),
+            Effect::ArpeggioFast | Effect::ArpeggioSlow => {
+                let step_ticks = if note.effect == Effect::ArpeggioFast {
+                    ARPEGGIO_FAST_TICKS
+                } else {
+                    ARPEGGIO_SLOW_TICKS
+                };
+                // Groups are notes 0 to 3, 4 to 7, and so on.
+                let group = index - index % 4;
+                let step = (ticks / step_ticks) as usize % 4;
+
+                (self.notes[group + step].frequency(), volume)
+            }
+        }
+    }
+
+    // Same layout as a line of Pico8's __sfx__ section:
+    // editor mode, speed, loop start and loop end (2 hex digits each),
+    // followed by pitch (2), waveform, volume and effect (1 each) for every note.
+    fn serialize(&self) -> String {
+        let header = format!(
+            "00{:02x}{:02x}{:02x}",
+            self.speed, self.loop_start, self.loop_end
+        );
+        let notes = self.notes.iter().map(|note| {
+            format!(
+                "{:02x}{:x}{:x}{:x}",
+                note.pitch,
+                note.waveform.index(),
+                note.volume,
+                note.effect.index()
+            )
+        });
+
+        std::iter::once(header).chain(notes).collect()
+    }
+
+    fn deserialize(line: &str) -> Result<Self, String> {
+        const LENGTH: usize = 8 + 5 * Sfx::NOTES;
+
+        if line.len() != LENGTH || !line.is_ascii() {
+            return Err(format!(
+                "Each sfx needs {LENGTH} hex digits, got {}",
+                line.len()
+            ));
+        }
+
+        let hex = |start: usize, len: usize| {
+            let digits = &line[start..start + len];
+
+            u8::from_str_radix(digits, 16).map_err(|_| format!("Invalid sfx data: {digits}"))
+        };
+
+        let mut sfx = Self {
+            speed: hex(2, 2)?,
+            loop_start: hex(4, 2)?,
+            loop_end: hex(6, 2)?,
+            ..Self::new()
+        };
+
+        for (index, note) in sfx.notes.iter_mut().enumerate() {
+            let start = 8 + 5 * index;
+            let waveform = hex(start + 2, 1)?;
+            let effect = hex(start + 4, 1)?;
+
+            *note = Note {
+                pitch: hex(start, 2)?.min(Note::MAX_PITCH),
+                waveform: Waveform::from_index(waveform)
+                    .ok_or_else(|| format!("Invalid waveform: {waveform}"))?,
+                volume: hex(start + 3, 1)?.min(Note::MAX_VOLUME),
+                effect: Effect::from_index(effect)
+                    .ok_or_else(|| format!("Invalid effect: {effect}"))?,
+            };
+        }
+
+        Ok(sfx)
+    }
+}
+
+/// All of a game's sound effects, like Pico8's 64 sfx slots.
+#[derive(Debug, Clone)]
+pub struct SoundEffects {
+    sfx: Vec<Sfx>,
+}
+
+impl SoundEffects {
+    pub(crate) const COUNT: usize = 64;
+    const VERSION: u32 = 1;
+
+    pub(crate) fn file_name() -> String {
+        "sfx.txt".to_owned()
+    }
+
+    pub(crate) fn new() -> Self {
+        Self {
+            sfx: vec![Sfx::new(); Self::COUNT],
+        }
+    }
+
+    /// Sfx `index`, `None` past the last one.
+    pub fn get(&self, index: usize) -> Option<&Sfx> {
+        self.sfx.get(index)
+    }
+
+    #[cfg(any(test, feature = "editor"))]
+    pub(crate) fn get_mut(&mut self, index: usize) -> Option<&mut Sfx> {
+        self.sfx.get_mut(index)
+    }
+
+    /// One sfx per line, in the same format as Pico8's __sfx__ section.
+    /// Missing sfx at the end of the file are left empty.
+    pub(crate) fn deserialize(str: &str) -> Result<Self, String> {
+        let lines = match split_version(str)? {
+            (1, lines) => lines,
+            (version, _) => return Err(format!("Unsupported sfx version {version}")),
+        };
+
+        let mut sfx = lines
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(Sfx::deserialize)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if sfx.len() > Self::COUNT {
+            return Err(format!(
+                "Too many sfx, at most {} are supported, got {}",
+                Self::COUNT,
+                sfx.len()
+            ));
+        }
+        sfx.resize(Self::COUNT, Sfx::new());
+
+        Ok(Self { sfx })
+    }
+}
+
+impl Serialize for SoundEffects {
+    fn serialize(&self, writer: &mut dyn Write) -> io::Result<()> {
+        write!(writer, "{}", version_header(Self::VERSION))?;
+        for sfx in &self.sfx {
+            write!(writer, "\n{}", sfx.serialize())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialize::to_string;
+
+    // A jump: square wave going up, then fading out.
+    fn jump() -> Sfx {
+        let mut sfx = Sfx::new();
+        sfx.speed = 4;
+
+        for (index, note) in sfx.notes.iter_mut().take(6).enumerate() {
+            *note = Note {
+                pitch: 24 + 2 * index as u8,
+                waveform: Waveform::Square,
+                volume: 7 - index as u8,
+                effect: Effect::None,
+            };
+        }
+
+        sfx
+    }
+
+    #[test]
+    fn pitch_33_is_a440() {
+        let note = |pitch| Note {
+            pitch,
+            ..Note::default()
+        };
+
+        assert!((note(33).frequency() - 440.0).abs() < 0.01);
+        assert!((note(45).frequency() - 880.0).abs() < 0.01);
+    }
+
+    fn with_effect(effect: Effect) -> Sfx {
+        let mut sfx = jump();
+        for note in sfx.notes.iter_mut() {
+            note.effect = effect;
+        }
+
+        sfx
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.01
+    }
+
+    #[test]
+    fn fades_ramp_the_volume() {
+        let fade_in = with_effect(Effect::FadeIn);
+        assert_eq!(
+            fade_in.sound(0, None, 0.0),
+            (fade_in.notes[0].frequency(), 0.0)
+        );
+        assert_eq!(fade_in.sound(0, None, 2.0).1, 0.5);
+        assert_eq!(fade_in.sound(0, None, 4.0).1, 1.0);
+
+        let fade_out = with_effect(Effect::FadeOut);
+        assert_eq!(fade_out.sound(0, None, 0.0).1, 1.0);
+        assert_eq!(fade_out.sound(0, None, 2.0).1, 0.5);
+        assert_eq!(fade_out.sound(0, None, 4.0).1, 0.0);
+    }
+
+    #[test]
+    fn slide_starts_at_the_previous_note() {
+        let sfx = with_effect(Effect::Slide);
+        let previous = Some(sfx.notes[0]);
+
+        assert!(close(
+            sfx.sound(1, previous, 0.0).0,
+            sfx.notes[0].frequency()
+        ));
+        assert!(close(sfx.sound(1, previous, 2.0).0, pitch_frequency(25.0)));
+        assert!(close(
+            sfx.sound(1, previous, 4.0).0,
+            sfx.notes[1].frequency()
+        ));
+
+        // Nothing to slide from.
+        assert_eq!(sfx.sound(1, None, 0.0).0, sfx.notes[1].frequency());
+    }
+
+    #[test]
+    fn vibrato_bends_around_the_pitch() {
+        let sfx = with_effect(Effect::Vibrato);
+        let frequency = |ticks| sfx.sound(0, None, ticks).0;
+
+        assert!(close(frequency(0.0), pitch_frequency(24.0)));
+        assert!(close(frequency(4.0), pitch_frequency(24.5)));
+        assert!(close(frequency(8.0), pitch_frequency(24.0)));
+        assert!(close(frequency(12.0), pitch_frequency(23.5)));
+        assert!(close(frequency(16.0), pitch_frequency(24.0)));
+    }
+
+    #[test]
+    fn drop_falls_to_zero() {
+        let sfx = with_effect(Effect::Drop);
+
+        assert_eq!(sfx.sound(0, None, 0.0).0, sfx.notes[0].frequency());
+        assert_eq!(sfx.sound(0, None, 2.0).0, sfx.notes[0].frequency() / 2.0);
+        assert_eq!(sfx.sound(0, None, 4.0).0, 0.0);
+    }
+
+    #[test]
+    fn arpeggios_cycle_through_groups_of_four() {
+        let mut sfx = with_effect(Effect::ArpeggioFast);
+        sfx.speed = 32;
+        let pitches = |sfx: &Sfx, note| {
+            (0..8)
+                .map(|step| sfx.sound(note, None, step as f32 * 4.0).0)
+                .collect::<Vec<_>>()
+        };
+        let frequencies = sfx.notes.map(|note| note.frequency());
+        let frequency = |note: usize| frequencies[note];
+
+        // Same group, same notes.
+        let group = [0, 1, 2, 3, 0, 1, 2, 3].map(frequency);
+        assert_eq!(pitches(&sfx, 0), group);
+        assert_eq!(pitches(&sfx, 2), group);
+        assert_eq!(pitches(&sfx, 4)[..2], [frequency(4), frequency(5)]);
+
+        for note in sfx.notes.iter_mut() {
+            note.effect = Effect::ArpeggioSlow;
+        }
+        assert_eq!(pitches(&sfx, 1), [0, 0, 1, 1, 2, 2, 3, 3].map(frequency));
+    }
+
+    #[test]
+    fn sfx_uses_pico8_layout() {
+        let line = jump().serialize();
+
+        assert_eq!(line.len(), 168);
+        assert!(line.starts_with("00040000"));
+        assert_eq!(&line[8..18], "183701a360");
+        assert_eq!(Sfx::deserialize(&line), Ok(jump()));
+    }
+
+    #[test]
+    fn sound_effects_round_trip() {
+        let mut sound_effects = SoundEffects::new();
+        *sound_effects.get_mut(5).unwrap() = jump();
+
+        let serialized = to_string(&sound_effects);
+        assert!(serialized.starts_with("version 1\n"));
+
+        let deserialized = SoundEffects::deserialize(&serialized).unwrap();
+        assert_eq!(deserialized.get(5), Some(&jump()));
+        assert_eq!(deserialized.get(6), Some(&Sfx::new()));
+        assert_eq!(deserialized.get(64), None);
+    }
+
+    #[test]
+    fn short_files_are_padded() {
+        let sound_effects = SoundEffects::deserialize(&jump().serialize()).unwrap();
+
+        assert_eq!(sound_effects.get(0), Some(&jump()));
+        assert_eq!(sound_effects.get(63), Some(&Sfx::new()));
+
+        assert!(SoundEffects::deserialize("0010").is_err());
+    }
+}
@@ -0,0 +1,103 @@
+use super::sfx::Waveform;
+use super::SAMPLE_RATE;
+
+/// Generates one waveform at a time, keeping its phase between notes
+/// so that changing pitch doesn't click.
+#[derive(Debug, Clone)]
+pub(crate) struct Oscillator {
+    // Position within the current period, in `0.0..1.0`.
+    phase: f32,
+    noise_seed: u32,
+    noise_value: f32,
+}
+
+impl Oscillator {
+    pub(crate) fn new() -> Self {
+        Self {
+            phase: 0.0,
+            noise_seed: 0x2545_F491,
+            noise_value: 0.0,
+        }
+    }
+
+    /// Next sample, in `-1.0..=1.0`.
+    pub(crate) fn next_sample(&mut self, waveform: Waveform, frequency: f32) -> f32 {
+        let sample = match waveform {
+            Waveform::Triangle | Waveform::Organ | Waveform::Phaser => {
+                1.0 - 4.0 * (self.phase - 0.5).abs()
+            }
+            Waveform::Sawtooth | Waveform::TiltedSaw => 2.0 * self.phase - 1.0,
+            Waveform::Square | Waveform::Pulse => {
+                if self.phase < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Waveform::Noise => self.noise_value,
+        };
+
+        self.phase += frequency / SAMPLE_RATE as f32;
+        if self.phase >= 1.0 {
+            self.phase = self.phase.fract();
+            self.next_noise();
+        }
+
+        sample
+    }
+
+    // Noise is a new random value every period, so that it still follows the note's pitch.
+    fn next_noise(&mut self) {
+        // xorshift32
+        let mut x = self.noise_seed;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.noise_seed = x;
+
+        self.noise_value = (x as f32 / u32::MAX as f32) * 2.0 - 1.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One period, at a frequency that makes it exactly 8 samples long.
+    fn period(waveform: Waveform) -> Vec<f32> {
+        let mut oscillator = Oscillator::new();
+        let frequency = SAMPLE_RATE as f32 / 8.0;
+
+        (0..8)
+            .map(|_| oscillator.next_sample(waveform, frequency))
+            .collect()
+    }
+
+    #[test]
+    fn basic_waveforms() {
+        assert_eq!(
+            period(Waveform::Square),
+            [1.0, 1.0, 1.0, 1.0, -1.0, -1.0, -1.0, -1.0]
+        );
+        assert_eq!(
+            period(Waveform::Triangle),
+            [-1.0, -0.5, 0.0, 0.5, 1.0, 0.5, 0.0, -0.5]
+        );
+        assert_eq!(
+            period(Waveform::Sawtooth),
+            [-1.0, -0.75, -0.5, -0.25, 0.0, 0.25, 0.5, 0.75]
+        );
+    }
+
+    #[test]
+    fn noise_stays_in_range_and_changes() {
+        let mut oscillator = Oscillator::new();
+        let samples: Vec<f32> = (0..1000)
+            .map(|_| oscillator.next_sample(Waveform::Noise, 2000.0))
+            .collect();
+
+        assert!(samples.iter().all(|sample| (-1.0..=1.0).contains(sample)));
+        assert!(samples.iter().any(|&sample| sample > 0.1));
+        assert!(samples.iter().any(|&sample| sample < -0.1));
+    }
+}
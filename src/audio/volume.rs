@@ -0,0 +1,144 @@
+use itertools::Itertools;
+
+use crate::serialize::{split_version, version_header, Serialize};
+use std::io::{self, Write};
+
+/// Volume settings, saved in the assets directory so that they're restored on startup.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Volume {
+    /// Applies to everything. All volumes are in `0.0..=1.0`.
+    pub(crate) master: f32,
+    pub(crate) sfx: f32,
+    pub(crate) music: f32,
+    /// Silences everything, without forgetting the volumes.
+    pub(crate) muted: bool,
+}
+
+impl Volume {
+    const VERSION: u32 = 1;
+
+    pub(crate) fn file_name() -> String {
+        "settings.txt".to_owned()
+    }
+
+    pub(crate) fn new() -> Self {
+        Self {
+            master: 1.0,
+            sfx: 1.0,
+            music: 1.0,
+            muted: false,
+        }
+    }
+
+    /// Clamps `volume` to `0.0..=1.0`, NaN is silent.
+    pub(crate) fn clamp(volume: f32) -> f32 {
+        if volume.is_nan() {
+            0.0
+        } else {
+            volume.clamp(0.0, 1.0)
+        }
+    }
+
+    /// What sound effects and music are multiplied by.
+    #[cfg(any(test, feature = "audio", feature = "editor"))]
+    pub(crate) fn gains(&self) -> (f32, f32) {
+        let master = if self.muted { 0.0 } else { self.master };
+
+        (master * self.sfx, master * self.music)
+    }
+
+    /// One `name value` setting per line. Missing settings keep their default.
+    pub(crate) fn deserialize(str: &str) -> Result<Self, String> {
+        let lines = match split_version(str)? {
+            (1, lines) => lines,
+            (version, _) => return Err(format!("Unsupported settings version {version}")),
+        };
+
+        let mut volume = Self::new();
+        for line in lines.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let invalid = || format!("Invalid setting: {line}");
+            let (name, value) = line.split_once(' ').ok_or_else(invalid)?;
+            let parse_volume = || value.parse().map(Self::clamp).map_err(|_| invalid());
+
+            match name {
+                "volume" => volume.master = parse_volume()?,
+                "sfx_volume" => volume.sfx = parse_volume()?,
+                "music_volume" => volume.music = parse_volume()?,
+                "muted" => volume.muted = value.parse().map_err(|_| invalid())?,
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(volume)
+    }
+}
+
+impl Serialize for Volume {
+    fn serialize(&self, writer: &mut dyn Write) -> io::Result<()> {
+        let settings = [
+            version_header(Self::VERSION),
+            format!("volume {}", self.master),
+            format!("sfx_volume {}", self.sfx),
+            format!("music_volume {}", self.music),
+            format!("muted {}", self.muted),
+        ]
+        .iter()
+        .join("\n");
+
+        writer.write_all(settings.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialize::to_string;
+
+    #[test]
+    fn settings_round_trip() {
+        let volume = Volume {
+            master: 0.5,
+            sfx: 0.25,
+            music: 0.0,
+            muted: true,
+        };
+
+        let serialized = to_string(&volume);
+        assert_eq!(
+            serialized,
+            "version 1\nvolume 0.5\nsfx_volume 0.25\nmusic_volume 0\nmuted true"
+        );
+        assert_eq!(Volume::deserialize(&serialized), Ok(volume));
+
+        assert_eq!(
+            Volume::deserialize("version 1\nvolume 3\n"),
+            Ok(Volume {
+                master: 1.0,
+                ..Volume::new()
+            })
+        );
+        assert!(Volume::deserialize("version 1\nvolume loud").is_err());
+        assert!(Volume::deserialize("version 1\nbass 1").is_err());
+    }
+
+    #[test]
+    fn muting_silences_everything() {
+        let volume = Volume {
+            master: 0.5,
+            sfx: 0.5,
+            music: 1.0,
+            muted: false,
+        };
+        assert_eq!(volume.gains(), (0.25, 0.5));
+
+        let muted = Volume {
+            muted: true,
+            ..volume
+        };
+        assert_eq!(muted.gains(), (0.0, 0.0));
+
+        assert_eq!(Volume::clamp(-1.0), 0.0);
+        assert_eq!(Volume::clamp(f32::NAN), 0.0);
+        assert_eq!(Volume::clamp(0.3), 0.3);
+    }
+}
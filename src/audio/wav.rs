@@ -0,0 +1,179 @@
+//! Offline rendering, through the same mixer the audio thread plays.
+
+use super::mixer::{sfx_length, Mixer};
+use super::music::Music;
+use super::sfx::{Sfx, SoundEffects};
+use super::{Command, SAMPLE_RATE};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+const BITS_PER_SAMPLE: u16 = 16;
+
+/// Writes `sfx` to a 16 bit mono WAV file at 22050Hz, sounding like it does in the game
+/// (for trailers, say). Get it from [`Resources::sound_effects`](crate::Resources::sound_effects).
+/// Looping sfx are rendered for as long as it takes to play 32 notes.
+///
+/// Not in Pico8.
+pub fn render_sfx_to_wav(sfx: &Sfx, path: impl AsRef<Path>) -> io::Result<()> {
+    write_wav_file(path, &render_sfx(sfx))
+}
+
+/// Writes music pattern `pattern` to a 16 bit mono WAV file at 22050Hz, playing its sfx from
+/// `sound_effects`. Get both from [`Resources::music`](crate::Resources::music)
+/// and [`Resources::sound_effects`](crate::Resources::sound_effects).
+/// Empty patterns make an empty file.
+///
+/// Not in Pico8.
+pub fn render_music_to_wav(
+    music: &Music,
+    sound_effects: &SoundEffects,
+    pattern: usize,
+    path: impl AsRef<Path>,
+) -> io::Result<()> {
+    write_wav_file(path, &render_music(music, sound_effects, pattern))
+}
+
+fn render_sfx(sfx: &Sfx) -> Vec<i16> {
+    let mut mixer = Mixer::new();
+    mixer.apply(Command::Play {
+        sfx: sfx.clone(),
+        channel: Some(0),
+    });
+
+    render(&mut mixer, sfx_length(sfx))
+}
+
+// Empty if there's nothing to play in `pattern`.
+fn render_music(music: &Music, sound_effects: &SoundEffects, pattern: usize) -> Vec<i16> {
+    let mut mixer = Mixer::new();
+    mixer.apply(Command::PlayMusic {
+        music: music.clone(),
+        sound_effects: sound_effects.clone(),
+        pattern,
+        fade_ms: 0,
+        channel_mask: 0,
+    });
+    let length = mixer.music_pattern_remaining().unwrap_or(0);
+
+    render(&mut mixer, length)
+}
+
+fn render(mixer: &mut Mixer, samples: u32) -> Vec<i16> {
+    (0..samples)
+        .map(|_| (mixer.next_sample().clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+        .collect()
+}
+
+fn write_wav_file(path: impl AsRef<Path>, samples: &[i16]) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_wav(&mut writer, samples)?;
+
+    writer.flush()
+}
+
+// A canonical 44 byte header followed by the samples, all little endian.
+fn write_wav(writer: &mut impl Write, samples: &[i16]) -> io::Result<()> {
+    let block_align = BITS_PER_SAMPLE / 8;
+    let data_size = samples.len() as u32 * block_align as u32;
+
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&(36 + data_size).to_le_bytes())?;
+    writer.write_all(b"WAVE")?;
+
+    writer.write_all(b"fmt ")?;
+    writer.write_all(&16u32.to_le_bytes())?;
+    // PCM, mono.
+    writer.write_all(&1u16.to_le_bytes())?;
+    writer.write_all(&1u16.to_le_bytes())?;
+    writer.write_all(&SAMPLE_RATE.to_le_bytes())?;
+    writer.write_all(&(SAMPLE_RATE * block_align as u32).to_le_bytes())?;
+    writer.write_all(&block_align.to_le_bytes())?;
+    writer.write_all(&BITS_PER_SAMPLE.to_le_bytes())?;
+
+    writer.write_all(b"data")?;
+    writer.write_all(&data_size.to_le_bytes())?;
+    for sample in samples {
+        writer.write_all(&sample.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::music::Pattern;
+    use super::super::sfx::{Note, Waveform};
+    use super::*;
+
+    // A note of every waveform, at different pitches and volumes.
+    fn arpeggio() -> Sfx {
+        let mut sfx = Sfx::new();
+        sfx.speed = 4;
+
+        for (index, waveform) in Waveform::ALL.into_iter().enumerate() {
+            sfx.notes[index] = Note {
+                pitch: 24 + 5 * index as u8,
+                waveform,
+                volume: 7 - index as u8 % 4,
+                ..Note::default()
+            };
+        }
+
+        sfx
+    }
+
+    // FNV-1a
+    fn checksum(samples: &[i16]) -> u64 {
+        samples
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+            })
+    }
+
+    #[test]
+    fn writes_wav_header() {
+        let mut bytes = vec![];
+        write_wav(&mut bytes, &[1, -2]).unwrap();
+
+        assert_eq!(bytes.len(), 44 + 4);
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(bytes[4..8], 40u32.to_le_bytes());
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(bytes[24..28], 22050u32.to_le_bytes());
+        assert_eq!(bytes[34..36], 16u16.to_le_bytes());
+        assert_eq!(&bytes[36..40], b"data");
+        assert_eq!(bytes[40..44], 4u32.to_le_bytes());
+        assert_eq!(bytes[44..], [1, 0, 0xfe, 0xff]);
+    }
+
+    // Catches any change to how sfx sound. If it was on purpose, update the checksums.
+    #[test]
+    fn sfx_render_is_stable() {
+        let samples = render_sfx(&arpeggio());
+
+        // 32 notes of 4 ticks, 183 samples each.
+        assert_eq!(samples.len(), 32 * 4 * 183);
+        assert_eq!(checksum(&samples), 0xda81_c169_c1df_c06a);
+    }
+
+    #[test]
+    fn renders_one_music_pattern() {
+        let mut sound_effects = SoundEffects::new();
+        *sound_effects.get_mut(1).unwrap() = arpeggio();
+
+        let mut music = Music::new();
+        *music.get_mut(0).unwrap() = Pattern {
+            sfx: [None, Some(1), None, None],
+            end_loop: true,
+            ..Pattern::default()
+        };
+
+        let samples = render_music(&music, &sound_effects, 0);
+        assert_eq!(samples, render_sfx(&arpeggio()));
+
+        assert!(render_music(&music, &sound_effects, 1).is_empty());
+    }
+}
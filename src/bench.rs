@@ -0,0 +1,74 @@
+//! Headless drawing, for the benchmarks in `benches/` and the examples' smoke tests.
+//! Not a stable API: only built with the `bench` feature.
+
+#[cfg(feature = "editor")]
+use crate::editor::Editor;
+use crate::runtime::draw_data::DrawData;
+use crate::runtime::state::State;
+use crate::{Error, Pico8, Resources};
+
+/// A [`Pico8`] drawing to an in-memory screen, with a few sprites to draw.
+#[derive(Debug)]
+pub struct Headless {
+    pico8: Pico8,
+    #[cfg(feature = "editor")]
+    editor: Editor,
+}
+
+impl Headless {
+    /// Sprites 1 to 4 are filled (1 and 2 with some transparent pixels), the map is empty.
+    pub fn new() -> Self {
+        let mut resources = Resources::empty();
+        for sprite in 1..=4 {
+            for (x, y) in (0..8).flat_map(|x| (0..8).map(move |y| (x, y))) {
+                let color = match sprite {
+                    1 | 2 => (x + y + sprite) % 3 * 4,
+                    _ => 1 + (x * y + sprite) % 15,
+                };
+                resources.sset(sprite * 8 + x, y, color as u8);
+            }
+        }
+
+        Self::with_resources(resources)
+    }
+
+    /// With the assets in `assets_path` instead, to run a game's `init`, `update` and `draw`
+    /// without a window.
+    pub fn with_assets(assets_path: &str) -> Result<Self, Error> {
+        let mut resources = Resources::empty();
+        resources.load_assets(assets_path.to_owned())?;
+
+        Ok(Self::with_resources(resources))
+    }
+
+    // Only the editor changes the resources.
+    #[cfg_attr(not(feature = "editor"), allow(unused_mut))]
+    fn with_resources(mut resources: Resources) -> Self {
+        #[cfg(feature = "editor")]
+        let editor = <Editor as crate::ElmApp>::init_with_resources(&mut resources);
+
+        Self {
+            pico8: Pico8::new(DrawData::new(), State::new(), resources),
+            #[cfg(feature = "editor")]
+            editor,
+        }
+    }
+
+    pub fn pico8(&mut self) -> &mut Pico8 {
+        &mut self.pico8
+    }
+
+    /// Draws a frame of the editor, as it looks when it's opened.
+    #[cfg(feature = "editor")]
+    pub fn draw_editor(&mut self) {
+        let mut view = <Editor as crate::ElmApp>::view(&mut self.editor, &self.pico8.resources);
+
+        view.as_widget_mut().draw(&mut self.pico8);
+    }
+}
+
+impl Default for Headless {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,216 @@
+//! Switching between several apps ("carts") in the same window, see [`Pico8::load_cart`].
+
+use crate::{App, Pico8};
+
+/// A running cart, whatever its type.
+pub(crate) trait Cart {
+    fn update(&mut self, pico8: &mut Pico8);
+    fn draw(&mut self, pico8: &mut Pico8);
+    fn editor_opened(&mut self, pico8: &mut Pico8);
+    fn editor_closed(&mut self, pico8: &mut Pico8);
+}
+
+impl<A: App> Cart for A {
+    fn update(&mut self, pico8: &mut Pico8) {
+        App::update(self, pico8)
+    }
+
+    fn draw(&mut self, pico8: &mut Pico8) {
+        App::draw(self, pico8)
+    }
+
+    fn editor_opened(&mut self, pico8: &mut Pico8) {
+        App::editor_opened(self, pico8)
+    }
+
+    fn editor_closed(&mut self, pico8: &mut Pico8) {
+        App::editor_closed(self, pico8)
+    }
+}
+
+pub(crate) type Init = fn(&mut Pico8) -> Box<dyn Cart>;
+
+pub(crate) fn init<A: App + 'static>(pico8: &mut Pico8) -> Box<dyn Cart> {
+    Box::new(A::init(pico8))
+}
+
+#[derive(Debug)]
+struct Entry {
+    name: String,
+    assets_path: String,
+    init: Init,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Request {
+    cart: usize,
+    breadcrumb: Option<usize>,
+}
+
+/// The carts games can switch to.
+/// The first one is the app passed to `run_app`, registered when it starts.
+#[derive(Debug, Default)]
+pub(crate) struct Carts {
+    carts: Vec<Entry>,
+    current: usize,
+    // The cart that loaded the current one.
+    breadcrumb: Option<usize>,
+    requested: Option<Request>,
+}
+
+impl Carts {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the app passed to `run_app`, unless it already was.
+    pub(crate) fn register_main(&mut self, assets_path: &str, init: Init) {
+        if self.carts.is_empty() {
+            self.register("", assets_path, init);
+        }
+    }
+
+    /// Registering a name again replaces the cart.
+    pub(crate) fn register(&mut self, name: &str, assets_path: &str, init: Init) {
+        let entry = Entry {
+            name: name.to_owned(),
+            assets_path: assets_path.to_owned(),
+            init,
+        };
+
+        match self.carts.iter().position(|cart| cart.name == name) {
+            Some(index) => self.carts[index] = entry,
+            None => self.carts.push(entry),
+        }
+    }
+
+    /// Returns whether there's a cart called `name`.
+    pub(crate) fn request(&mut self, name: &str) -> bool {
+        let cart = self.carts.iter().position(|cart| cart.name == name);
+
+        self.requested = cart.map(|cart| Request {
+            cart,
+            breadcrumb: Some(self.current),
+        });
+
+        cart.is_some()
+    }
+
+    /// Returns whether there's a cart to go back to.
+    pub(crate) fn request_breadcrumb(&mut self) -> bool {
+        self.requested = self.breadcrumb.map(|cart| Request {
+            cart,
+            breadcrumb: None,
+        });
+
+        self.breadcrumb.is_some()
+    }
+
+    pub(crate) fn current_init(&self) -> Option<Init> {
+        self.carts.get(self.current).map(|cart| cart.init)
+    }
+}
+
+/// Switches to the cart requested during the last update, if any:
+/// loads its assets and starts it.
+///
+/// If its assets can't be loaded, the current cart keeps running.
+pub(crate) fn load_requested(pico8: &mut Pico8) -> Option<Box<dyn Cart>> {
+    let request = pico8.carts.requested.take()?;
+    let cart = &pico8.carts.carts[request.cart];
+    let (name, assets_path, init) = (cart.name.clone(), cart.assets_path.clone(), cart.init);
+
+    if let Err(error) = pico8.resources.load_assets(assets_path) {
+        eprintln!("Couldn't load cart `{name}`: {error}");
+        return None;
+    }
+    pico8.carts.current = request.cart;
+    pico8.carts.breadcrumb = request.breadcrumb;
+    pico8.reset_for_new_cart();
+
+    Some(init(pico8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{AppCompat, Pico8AppCompat};
+    use crate::runtime::draw_data::DrawData;
+    use crate::runtime::state::State;
+    use crate::{Color, Resources};
+
+    struct Launcher;
+
+    impl App for Launcher {
+        fn init(pico8: &mut Pico8) -> Self {
+            pico8.register_cart::<Counter>("counter", &temp_assets("counter"));
+            pico8.pal(Color::RED, Color::GREEN);
+
+            Self
+        }
+
+        fn update(&mut self, _: &mut Pico8) {}
+
+        fn draw(&mut self, pico8: &mut Pico8) {
+            pico8.cls(Color::BLUE);
+        }
+    }
+
+    struct Counter;
+
+    impl App for Counter {
+        fn init(_: &mut Pico8) -> Self {
+            Self
+        }
+
+        fn update(&mut self, _: &mut Pico8) {}
+
+        fn draw(&mut self, pico8: &mut Pico8) {
+            pico8.cls(Color::RED);
+        }
+    }
+
+    fn temp_assets(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("runty8-carts-{}-{name}", std::process::id()));
+
+        path.to_str().unwrap().to_owned()
+    }
+
+    fn cleared_to(app: &mut Pico8AppCompat<Launcher>, pico8: &mut Pico8) -> Option<Color> {
+        let mut view = app.view(&mut Resources::empty());
+        view.as_widget_mut().draw(pico8);
+
+        pico8.draw_data.pixel(0, 0)
+    }
+
+    #[test]
+    fn carts_switch_and_come_back() {
+        let resources = Resources {
+            assets_path: temp_assets("launcher"),
+            ..Resources::empty()
+        };
+        let mut pico8 = Pico8::new(DrawData::new(), State::new(), resources);
+        let mut app = Pico8AppCompat::<Launcher>::init(&mut pico8);
+        let tick = crate::app::Pico8AppMsg::Tick;
+
+        assert!(!pico8.load_breadcrumb());
+        assert!(pico8.load_cart("counter").is_ok());
+        assert_eq!(cleared_to(&mut app, &mut pico8), Some(Color::BLUE));
+
+        app.update(&tick, &mut pico8);
+        assert_eq!(pico8.resources.assets_path, temp_assets("counter"));
+        // The launcher maps red to green, that doesn't carry over to the counter.
+        assert_eq!(cleared_to(&mut app, &mut pico8), Some(Color::RED));
+
+        assert!(pico8.load_cart("missing").is_err());
+        assert!(pico8.load_breadcrumb());
+        app.update(&tick, &mut pico8);
+        assert_eq!(pico8.resources.assets_path, temp_assets("launcher"));
+        assert_eq!(cleared_to(&mut app, &mut pico8), Some(Color::BLUE));
+        assert!(!pico8.load_breadcrumb());
+
+        for name in ["launcher", "counter"] {
+            std::fs::remove_dir_all(temp_assets(name)).unwrap();
+        }
+    }
+}
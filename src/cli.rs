@@ -0,0 +1,257 @@
+//! The `runty8` command line tool (`src/main.rs`), to work on a game's assets without writing a `main.rs`.
+//! Not a stable API: only built with the `cli` feature.
+
+use std::io;
+use std::path::Path;
+use std::process::ExitCode;
+
+use crate::audio::{self, music::Music, sfx::SoundEffects};
+use crate::editor::ppm::{Png, Ppm};
+use crate::p8::P8;
+use crate::serialize::{self, Serialize};
+use crate::{App, Error, Flags, Map, Pico8, Resources, SpriteSheet};
+
+const USAGE: &str = "\
+Usage:
+    runty8 edit <assets_dir>               Opens the editor on the assets, with an empty game
+    runty8 export-png <assets_dir>         Writes map.png and sprite_sheet.png into the assets directory
+    runty8 export-wav <assets_dir> <n>     Writes music pattern n (0 to 63), as music_<n>.wav, into the assets directory
+    runty8 convert <cart.p8> <assets_dir>  Imports the sprites, flags, map, sfx and music of a Pico8 cart
+
+Exits with 1 if the command fails, and 2 if the arguments are wrong.";
+
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Edit {
+        assets_path: String,
+    },
+    ExportPng {
+        assets_path: String,
+    },
+    ExportWav {
+        assets_path: String,
+        pattern: usize,
+    },
+    Convert {
+        cart_path: String,
+        assets_path: String,
+    },
+    Help,
+}
+
+impl Command {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+        match args.as_slice() {
+            ["edit", assets_path] => Ok(Self::Edit {
+                assets_path: assets_path.to_string(),
+            }),
+            ["export-png", assets_path] => Ok(Self::ExportPng {
+                assets_path: assets_path.to_string(),
+            }),
+            ["export-wav", assets_path, pattern] => match pattern.parse() {
+                Ok(pattern) if pattern < Music::COUNT => Ok(Self::ExportWav {
+                    assets_path: assets_path.to_string(),
+                    pattern,
+                }),
+                _ => Err(format!("There's no music pattern `{pattern}`.")),
+            },
+            ["convert", cart_path, assets_path] => Ok(Self::Convert {
+                cart_path: cart_path.to_string(),
+                assets_path: assets_path.to_string(),
+            }),
+            ["help" | "--help" | "-h"] => Ok(Self::Help),
+            [] => Err("Missing a command.".to_owned()),
+            [command @ ("edit" | "export-png" | "export-wav" | "convert"), ..] => {
+                Err(format!("Wrong arguments for `{command}`."))
+            }
+            [command, ..] => Err(format!("Unknown command `{command}`.")),
+        }
+    }
+}
+
+/// Runs the command in `args` (without the program's name).
+/// Errors go to stderr, the files written to stdout, one per line.
+pub fn main(args: impl IntoIterator<Item = String>) -> ExitCode {
+    let args: Vec<String> = args.into_iter().collect();
+
+    let command = match Command::parse(&args) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("runty8: {message}\n\n{USAGE}");
+
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("runty8: {error}");
+
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: Command) -> Result<(), Error> {
+    match command {
+        Command::Edit { assets_path } => crate::run_app::<EmptyGame>(assets_path),
+        Command::ExportPng { assets_path } => export_png(&assets_path),
+        Command::ExportWav {
+            assets_path,
+            pattern,
+        } => export_wav(&assets_path, pattern),
+        Command::Convert {
+            cart_path,
+            assets_path,
+        } => convert(&cart_path, &assets_path),
+        Command::Help => {
+            println!("{USAGE}");
+
+            Ok(())
+        }
+    }
+}
+
+// Without opening a window, unlike the editor's save.
+fn export_png(assets_path: &str) -> Result<(), Error> {
+    let resources = load_assets(assets_path)?;
+
+    let map = Ppm::from_map(&resources);
+    let sprite_sheet = Ppm::from_sprite_sheet(&resources.sprite_sheet, &resources.palette);
+    write_files(
+        assets_path,
+        &[
+            ("map.png".to_owned(), &Png(&map)),
+            ("sprite_sheet.png".to_owned(), &Png(&sprite_sheet)),
+        ],
+    )
+}
+
+// There's no music editor to export from.
+fn export_wav(assets_path: &str, pattern: usize) -> Result<(), Error> {
+    let resources = load_assets(assets_path)?;
+    if resources
+        .music
+        .get(pattern)
+        .is_none_or(|pattern| pattern.is_empty())
+    {
+        return Err(Error::Io(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Music pattern {pattern} has nothing to play."),
+        )));
+    }
+
+    let file_path = format!("{assets_path}/music_{pattern:0>2}.wav");
+    audio::render_music_to_wav(
+        &resources.music,
+        &resources.sound_effects,
+        pattern,
+        &file_path,
+    )?;
+    println!("{file_path}");
+
+    Ok(())
+}
+
+fn load_assets(assets_path: &str) -> Result<Resources, Error> {
+    if !Path::new(assets_path).is_dir() {
+        return Err(Error::Io(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("There's no assets directory at `{assets_path}`."),
+        )));
+    }
+
+    let mut resources = Resources::empty();
+    resources.load_assets(assets_path.to_owned())?;
+
+    Ok(resources)
+}
+
+fn convert(cart_path: &str, assets_path: &str) -> Result<(), Error> {
+    let cart_error = |reason| Error::AssetLoad {
+        path: cart_path.to_owned(),
+        reason,
+    };
+    let contents =
+        std::fs::read_to_string(cart_path).map_err(|error| cart_error(error.to_string()))?;
+    let cart = P8::parse(&contents).map_err(cart_error)?;
+
+    crate::create_directory(assets_path)?;
+    write_files(
+        assets_path,
+        &[
+            (SpriteSheet::file_name(), &cart.sprite_sheet),
+            (Flags::file_name(), &cart.sprite_flags),
+            (Map::file_name(), &cart.map),
+            (SoundEffects::file_name(), &cart.sound_effects),
+            (Music::file_name(), &cart.music),
+        ],
+    )
+}
+
+fn write_files(assets_path: &str, files: &[(String, &dyn Serialize)]) -> Result<(), Error> {
+    for (file_name, serializable) in files {
+        let file_path = format!("{assets_path}/{file_name}");
+
+        serialize::write_file(&file_path, serializable)?;
+        println!("{file_path}");
+    }
+
+    Ok(())
+}
+
+struct EmptyGame;
+
+impl App for EmptyGame {
+    fn init(_: &mut Pico8) -> Self {
+        Self
+    }
+
+    fn update(&mut self, _: &mut Pico8) {}
+
+    fn draw(&mut self, draw: &mut Pico8) {
+        draw.cls(0);
+        draw.print("EMPTY", 0, 0, 7);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        Command::parse(&args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(
+            parse(&["convert", "game.p8", "assets"]),
+            Ok(Command::Convert {
+                cart_path: "game.p8".to_owned(),
+                assets_path: "assets".to_owned()
+            })
+        );
+        assert_eq!(
+            parse(&["export-wav", "assets", "7"]),
+            Ok(Command::ExportWav {
+                assets_path: "assets".to_owned(),
+                pattern: 7
+            })
+        );
+        assert_eq!(
+            parse(&["export-wav", "assets", "64"]),
+            Err("There's no music pattern `64`.".to_owned())
+        );
+        assert_eq!(parse(&["--help"]), Ok(Command::Help));
+        assert_eq!(
+            parse(&["edit"]),
+            Err("Wrong arguments for `edit`.".to_owned())
+        );
+        assert_eq!(parse(&["run"]), Err("Unknown command `run`.".to_owned()));
+        assert!(parse(&[]).is_err());
+    }
+}
@@ -0,0 +1,230 @@
+//! Overlap checks for games: against the map's flagged tiles, and between two sprites.
+//!
+//! Positions are in pixels, like for [`Pico8::spr`], and can be negative.
+//!
+//! ```no_run
+//! # use runty8::{collision, Pico8};
+//! # fn update(pico8: &mut Pico8, x: i32, y: i32) {
+//! // Flag 0 marks the walls, the player is 8x8.
+//! let blocked = collision::map_collides(pico8, x + 1, y, 8, 8, 0);
+//! # }
+//! ```
+//!
+//! Maps made of 16x16 metasprites (see [`Pico8::spr16`]) use [`map_collides16`],
+//! which reads the flags of each block's top left sprite only.
+use crate::{Color, Pico8, Sprite};
+
+/// Whether any map cell under the `width` by `height` pixel rectangle at (`x`, `y`)
+/// has `flag` set on its sprite.
+///
+/// A cell is under the rectangle when at least one of its pixels is,
+/// so a rectangle ending exactly on a cell's edge doesn't touch it.
+/// Cells outside the map never collide, and neither do empty rectangles.
+pub fn map_collides(pico8: &Pico8, x: i32, y: i32, width: i32, height: i32, flag: u8) -> bool {
+    if width <= 0 || height <= 0 {
+        return false;
+    }
+
+    // Rounded down, also for negative positions: pixel -1 is in cell -1, not 0.
+    let cell = |pixel: i32| pixel.div_euclid(Sprite::WIDTH as i32);
+    let (map_width, map_height) = pico8.map_size();
+    let columns = cell(x).max(0)..=cell(x + width - 1).min(map_width - 1);
+    let rows = cell(y).max(0)..=cell(y + height - 1).min(map_height - 1);
+
+    rows.into_iter().any(|cel_y| {
+        columns.clone().any(|cel_x| {
+            let sprite = pico8.mget(cel_x, cel_y);
+
+            pico8.fget_n(sprite as usize, flag)
+        })
+    })
+}
+
+/// Like [`map_collides`], for maps made of 2x2 blocks of cells, as placed by the map editor's
+/// block mode: blocks start on even cells, and only the flags of their top left sprite count.
+/// The flags of the other three sprites are ignored, they don't need to be set.
+pub fn map_collides16(pico8: &Pico8, x: i32, y: i32, width: i32, height: i32, flag: u8) -> bool {
+    const BLOCK_SIZE: i32 = 2 * Sprite::WIDTH as i32;
+
+    if width <= 0 || height <= 0 {
+        return false;
+    }
+
+    let block = |pixel: i32| pixel.div_euclid(BLOCK_SIZE);
+    let (map_width, map_height) = pico8.map_size();
+    // Blocks whose top left cell is in the map.
+    let columns = block(x).max(0)..=block(x + width - 1).min((map_width - 1) / 2);
+    let rows = block(y).max(0)..=block(y + height - 1).min((map_height - 1) / 2);
+
+    rows.into_iter().any(|block_y| {
+        columns.clone().any(|block_x| {
+            let sprite = pico8.mget(2 * block_x, 2 * block_y);
+
+            pico8.fget_n(sprite as usize, flag)
+        })
+    })
+}
+
+/// Whether `sprite_a` drawn at `position_a` and `sprite_b` at `position_b`
+/// have a non transparent pixel in the same place.
+///
+/// Color 0 is what's transparent, whatever [`Pico8::palt`] says,
+/// and sprites past the end of the sheet don't overlap anything.
+pub fn sprite_overlap(
+    pico8: &Pico8,
+    sprite_a: usize,
+    (a_x, a_y): (i32, i32),
+    sprite_b: usize,
+    (b_x, b_y): (i32, i32),
+) -> bool {
+    let sprite_sheet = &pico8.resources().sprite_sheet;
+    let (Some(a), Some(b)) = (
+        sprite_sheet.try_get_sprite(sprite_a),
+        sprite_sheet.try_get_sprite(sprite_b),
+    ) else {
+        return false;
+    };
+    // From a's pixels to b's, pixels outside of b read as transparent.
+    let (offset_x, offset_y) = ((a_x - b_x) as isize, (a_y - b_y) as isize);
+
+    a.iter_pixels()
+        .filter(|&(.., color)| color != Color::BLACK)
+        .any(|(x, y, _)| b.pget(x as isize + offset_x, y as isize + offset_y) != Color::BLACK)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::draw_data::DrawData;
+    use crate::runtime::state::State;
+    use crate::{Map, Resources};
+
+    // A 4x4 map, with a wall (sprite 1, flag 0) in cell (1, 1)
+    // and a ledge (sprite 2, flag 1) in cell (3, 0).
+    fn pico8() -> Pico8 {
+        let mut resources = Resources::empty();
+        resources.map = Map::with_size(4, 4);
+        resources.mset(1, 1, 1);
+        resources.mset(3, 0, 2);
+        resources.sprite_flags.fset(1, 0, true);
+        resources.sprite_flags.fset(2, 1, true);
+
+        Pico8::new(DrawData::new(), State::new(), resources)
+    }
+
+    #[test]
+    fn rectangles_collide_with_the_cells_they_cover() {
+        let pico8 = pico8();
+
+        assert!(map_collides(&pico8, 8, 8, 8, 8, 0));
+        assert!(map_collides(&pico8, 12, 12, 1, 1, 0));
+        // A whole cell around the wall, not touching it.
+        assert!(!map_collides(&pico8, 0, 0, 8, 8, 0));
+        assert!(!map_collides(&pico8, 16, 16, 8, 8, 0));
+        // Another flag.
+        assert!(!map_collides(&pico8, 8, 8, 8, 8, 1));
+        assert!(map_collides(&pico8, 24, 0, 8, 8, 1));
+    }
+
+    #[test]
+    fn the_seams_between_cells() {
+        let pico8 = pico8();
+
+        // Ending right before the wall, and starting right after it.
+        assert!(!map_collides(&pico8, 0, 8, 8, 8, 0));
+        assert!(!map_collides(&pico8, 16, 8, 8, 8, 0));
+        assert!(!map_collides(&pico8, 8, 0, 8, 8, 0));
+        assert!(!map_collides(&pico8, 8, 16, 8, 8, 0));
+        // One pixel into it, from every side.
+        assert!(map_collides(&pico8, 1, 8, 8, 8, 0));
+        assert!(map_collides(&pico8, 15, 8, 8, 8, 0));
+        assert!(map_collides(&pico8, 8, 1, 8, 8, 0));
+        assert!(map_collides(&pico8, 8, 15, 8, 8, 0));
+        // Diagonally, only the corner pixels meet.
+        assert!(map_collides(&pico8, 15, 15, 8, 8, 0));
+        assert!(!map_collides(&pico8, 16, 15, 8, 8, 0));
+    }
+
+    #[test]
+    fn negative_positions_round_down() {
+        let pico8 = pico8();
+
+        // -1 is in cell -1, which is outside of the map.
+        assert!(!map_collides(&pico8, -1, 8, 1, 1, 0));
+        // From cell -1 to cell 1.
+        assert!(map_collides(&pico8, -1, 8, 10, 1, 0));
+        assert!(!map_collides(&pico8, -9, 8, 9, 8, 0));
+        assert!(map_collides(&pico8, -9, -9, 18, 18, 0));
+    }
+
+    #[test]
+    fn rectangles_past_the_edges_of_the_map() {
+        let mut pico8 = pico8();
+        // Sprite 0 is what's read outside of the map.
+        pico8.fset(0, 0, true);
+
+        assert!(!map_collides(&pico8, -100, -100, 50, 50, 0));
+        assert!(!map_collides(&pico8, 32, 0, 8, 8, 1));
+        // Straddling the right edge, over the ledge.
+        assert!(map_collides(&pico8, 28, 0, 8, 8, 1));
+        assert!(map_collides(&pico8, -4, -4, 8, 8, 0));
+    }
+
+    #[test]
+    fn empty_rectangles_dont_collide() {
+        let pico8 = pico8();
+
+        assert!(!map_collides(&pico8, 8, 8, 0, 8, 0));
+        assert!(!map_collides(&pico8, 8, 8, 8, -8, 0));
+    }
+
+    #[test]
+    fn blocks_collide_by_their_top_left_sprite() {
+        let mut resources = Resources::empty();
+        resources.map = Map::with_size(6, 4);
+        // A wall block in cells (2, 0) to (3, 1), only its top left sprite is flagged.
+        for (x, y, sprite) in [(2, 0, 1), (3, 0, 2), (2, 1, 17), (3, 1, 18)] {
+            resources.mset(x, y, sprite);
+        }
+        resources.sprite_flags.fset(1, 0, true);
+        // Flagged, but not on a block's top left cell.
+        resources.mset(1, 3, 3);
+        resources.sprite_flags.fset(3, 0, true);
+        let pico8 = Pico8::new(DrawData::new(), State::new(), resources);
+
+        // Anywhere in the block, even over the unflagged sprites.
+        assert!(map_collides16(&pico8, 16, 0, 16, 16, 0));
+        assert!(map_collides16(&pico8, 31, 15, 1, 1, 0));
+        assert!(map_collides16(&pico8, 0, 0, 17, 1, 0));
+        assert!(!map_collides16(&pico8, 0, 0, 16, 16, 0));
+        assert!(!map_collides16(&pico8, 32, 0, 16, 16, 0));
+        // Cell (1, 3) is the bottom right of block (0, 1).
+        assert!(!map_collides16(&pico8, 8, 24, 8, 8, 0));
+        assert!(map_collides(&pico8, 8, 24, 8, 8, 0));
+        // Outside of the map.
+        assert!(!map_collides16(&pico8, -32, -32, 16, 16, 0));
+        assert!(!map_collides16(&pico8, 16, 0, 0, 16, 0));
+    }
+
+    #[test]
+    fn sprites_overlap_on_their_opaque_pixels() {
+        let mut resources = Resources::empty();
+        // A single pixel in sprite 1's bottom right corner, a full sprite 2.
+        resources.sset(15, 7, 8);
+        for (x, y) in (0..8).flat_map(|y| (0..8).map(move |x| (x, y))) {
+            resources.sset(16 + x, y, 12);
+        }
+        let pico8 = Pico8::new(DrawData::new(), State::new(), resources);
+
+        assert!(sprite_overlap(&pico8, 2, (0, 0), 2, (7, -7)));
+        assert!(!sprite_overlap(&pico8, 2, (0, 0), 2, (8, 0)));
+        // The corner pixel is at (7, 7).
+        assert!(sprite_overlap(&pico8, 1, (0, 0), 2, (7, 7)));
+        assert!(!sprite_overlap(&pico8, 1, (0, 0), 2, (8, 7)));
+        assert!(sprite_overlap(&pico8, 2, (-20, -20), 1, (-27, -27)));
+        assert!(!sprite_overlap(&pico8, 2, (-20, -20), 1, (-28, -27)));
+        // Inside sprite 2's box, but on sprite 1's transparent pixels.
+        assert!(!sprite_overlap(&pico8, 1, (0, 0), 2, (-2, -2)));
+        assert!(!sprite_overlap(&pico8, 0, (0, 0), 2, (0, 0)));
+    }
+}
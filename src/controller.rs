@@ -1,19 +1,27 @@
 use std::fmt::Debug;
 
+#[cfg(feature = "editor")]
+use crate::{
+    app::ElmApp,
+    debug_controls::DebugControls,
+    editor::{self, Editor},
+    inspector::Inspector,
+    Color,
+};
+
+use crate::key_combo::KeyCombos;
 use crate::pico8::Pico8;
 use crate::runtime::draw_data::DrawData;
 use crate::runtime::input::Keys;
-use crate::ui::DispatchEvent;
+use crate::ui::{Layer, UiState};
 use crate::{
-    app::{AppCompat, ElmApp},
-    editor::{self, key_combo::KeyCombos, Editor},
-    runtime::state::State,
-    ui::Element,
-    Event, Key, KeyboardEvent, MouseButton, MouseEvent, Resources,
+    app::AppCompat, runtime::state::State, ui::Element, CrtFilter, CursorMode, Event, Key,
+    KeyboardEvent, MouseButton, MouseEvent, Resources, ScalingMode,
 };
 
 #[derive(Debug, Clone, Copy)]
 pub(crate) enum Msg<AppMsg> {
+    #[cfg(feature = "editor")]
     Editor(editor::Msg),
     App(AppMsg),
     KeyboardEvent(KeyboardEvent),
@@ -24,46 +32,133 @@ pub(crate) enum Msg<AppMsg> {
 #[derive(Copy, Clone, Debug)]
 enum KeyComboAction {
     RestartGame,
+    ToggleMute,
+    ToggleCrtFilter,
+    #[cfg(feature = "editor")]
     SwitchScene,
+    #[cfg(feature = "editor")]
+    TogglePause,
+    #[cfg(feature = "editor")]
+    StepFrame,
+    #[cfg(feature = "editor")]
+    CycleTimeScale,
+    #[cfg(feature = "editor")]
+    ToggleInspector,
 }
 
 #[derive(Debug)]
-pub(crate) struct Controller<Game> {
+pub(crate) struct Controller<Game: AppCompat> {
     scene: Scene,
+    #[cfg(feature = "editor")]
     editor: Editor,
+    #[cfg(feature = "editor")]
+    debug_controls: DebugControls,
+    #[cfg(feature = "editor")]
+    inspector: Inspector,
+    // The draw state of the scene that isn't showing, swapped in when switching scenes.
+    #[cfg(feature = "editor")]
+    hidden_draw_state: DrawData,
     app: Game,
     key_combos: KeyCombos<KeyComboAction>,
     keys: Keys,
+    ui: UiState,
     pico8: Pico8,
+    // Kept between events, so that handling one doesn't allocate.
+    msg_queue: Vec<Msg<Game::Msg>>,
+    #[cfg(feature = "editor")]
+    editor_msgs: Vec<editor::Msg>,
+    app_msgs: Vec<Game::Msg>,
 }
-impl<T> Controller<T> {
-    pub(crate) fn screen_buffer(&self) -> &[u8] {
-        self.pico8.draw_data.buffer()
+impl<T: AppCompat> Controller<T> {
+    /// The screen as RGB, ready to display.
+    pub(crate) fn screen_buffer(&self) -> Vec<u8> {
+        // The game's screen palette (and its cycle) doesn't apply to the editor.
+        let screen_frame = matches!(self.scene, Scene::App).then(|| self.pico8.frame_count());
+
+        self.pico8
+            .draw_data
+            .rgb(screen_frame, &self.pico8.resources.palette)
     }
 
     pub(crate) fn take_new_title(&mut self) -> Option<String> {
         self.pico8.take_new_title()
     }
+
+    pub(crate) fn cursor_mode(&self) -> CursorMode {
+        self.pico8.cursor_mode()
+    }
+
+    pub(crate) fn crt_filter(&self) -> Option<CrtFilter> {
+        self.pico8.crt_filter()
+    }
+
+    pub(crate) fn scaling_mode(&self) -> ScalingMode {
+        self.pico8.scaling_mode()
+    }
+
+    pub(crate) fn drop_updates(&mut self, count: u32) {
+        self.pico8.state.drop_updates(count);
+    }
+
+    /// What the main loop multiplies the time that passed by, to slow the game down.
+    pub(crate) fn time_scale(&self) -> f64 {
+        match self.scene {
+            #[cfg(feature = "editor")]
+            Scene::Editor => 1.0,
+            #[cfg(feature = "editor")]
+            Scene::App => self.debug_controls.time_scale(),
+            #[cfg(not(feature = "editor"))]
+            Scene::App => 1.0,
+        }
+    }
+
+    /// Red, green and blue of the window's border.
+    pub(crate) fn border_rgb(&self) -> (u8, u8, u8) {
+        self.pico8.resources.palette.rgb(self.pico8.border_color())
+    }
 }
 
 impl<Game: AppCompat> Controller<Game> {
     pub fn init(scene: Scene, resources: Resources) -> Self {
         let mut pico8 = Pico8::new(DrawData::new(), State::new(), resources);
 
+        let key_combos = KeyCombos::new()
+            .push(KeyComboAction::RestartGame, Key::R, &[Key::Control])
+            .push(KeyComboAction::ToggleMute, Key::M, &[Key::Control])
+            .push(KeyComboAction::ToggleCrtFilter, Key::T, &[Key::Control]);
+        #[cfg(feature = "editor")]
+        let key_combos = key_combos
+            .push(KeyComboAction::SwitchScene, Key::Escape, &[])
+            .push(KeyComboAction::TogglePause, Key::P, &[Key::Control])
+            .push(KeyComboAction::StepFrame, Key::N, &[Key::Control])
+            .push(KeyComboAction::CycleTimeScale, Key::L, &[Key::Control])
+            .push(KeyComboAction::ToggleInspector, Key::K, &[Key::Control]);
+
         Self {
             scene,
-            editor: <Editor as ElmApp>::init(),
+            #[cfg(feature = "editor")]
+            editor: <Editor as ElmApp>::init_with_resources(&mut pico8.resources),
+            #[cfg(feature = "editor")]
+            debug_controls: DebugControls::new(),
+            #[cfg(feature = "editor")]
+            inspector: Inspector::new(),
+            #[cfg(feature = "editor")]
+            hidden_draw_state: DrawData::new(),
             app: Game::init(&mut pico8),
-            key_combos: KeyCombos::new()
-                .push(KeyComboAction::RestartGame, Key::R, &[Key::Control])
-                .push(KeyComboAction::SwitchScene, Key::Escape, &[]),
+            key_combos,
             keys: Keys::new(),
+            ui: UiState::new(),
             pico8,
+            msg_queue: vec![],
+            #[cfg(feature = "editor")]
+            editor_msgs: vec![],
+            app_msgs: vec![],
         }
     }
 
     fn update(&mut self, msg: &Msg<Game::Msg>) {
         match msg {
+            #[cfg(feature = "editor")]
             Msg::Editor(editor_msg) => {
                 <Editor as ElmApp>::update(&mut self.editor, editor_msg, &mut self.pico8.resources);
             }
@@ -74,58 +169,87 @@ impl<Game: AppCompat> Controller<Game> {
                 self.pico8.state.on_mouse_move(x, y);
             }
             &Msg::MouseEvent(event) => {
-                let left_pressed = match event {
-                    MouseEvent::Down(MouseButton::Left) => Some(true),
-                    MouseEvent::Up(MouseButton::Left) => Some(false),
-                    _ => None,
+                let (button, pressed) = match event {
+                    MouseEvent::Down(button) => (button, true),
+                    MouseEvent::Up(button) => (button, false),
+                    MouseEvent::Move { .. } => return,
+                };
+                let key_ref = match button {
+                    MouseButton::Left => &mut self.keys.mouse,
+                    MouseButton::Right => &mut self.keys.mouse_right,
+                    MouseButton::Middle => &mut self.keys.mouse_middle,
                 };
 
-                if let Some(left_pressed) = left_pressed {
-                    self.keys.mouse = Some(left_pressed);
-                }
+                key_ref.set(pressed);
             }
 
             &Msg::KeyboardEvent(event) => {
-                self.handle_key_combos(event);
                 self.keys.on_event(event);
             }
             &Msg::Tick => {
-                self.pico8.state.update_keys(&self.keys);
+                self.pico8.state.tick();
+                // Held back from the game while the editor is open, see `Scene::switch`.
+                if matches!(self.scene, Scene::App) {
+                    self.pico8.state.update_keys(&mut self.keys);
+                }
             }
         }
     }
 
-    fn subscriptions(&self, event: &Event) -> Vec<Msg<Game::Msg>> {
-        let sub_msgs: Vec<Msg<Game::Msg>> = match self.scene {
-            Scene::Editor => <Editor as ElmApp>::subscriptions(&self.editor, event)
-                .into_iter()
-                .map(Msg::Editor)
-                .collect(),
+    /// Pushes the messages `event` turns into to `msgs`.
+    fn subscriptions(
+        &mut self,
+        event: &Event,
+        keyboard_captured: bool,
+        msgs: &mut Vec<Msg<Game::Msg>>,
+    ) {
+        match self.scene {
+            _ if keyboard_captured => {}
+            #[cfg(feature = "editor")]
+            Scene::Editor => {
+                <Editor as ElmApp>::subscriptions(&self.editor, event, &mut self.editor_msgs);
+                msgs.extend(self.editor_msgs.drain(..).map(Msg::Editor));
 
-            Scene::App => <Game as AppCompat>::subscriptions(&self.app, event)
-                .into_iter()
-                .map(Msg::App)
-                .collect(),
-        };
+                // Behind the editor, the game only gets its updates, and only if it asked to.
+                if matches!(event, Event::Tick { .. }) && self.pico8.updates_in_editor() {
+                    self.app.subscriptions(event, &mut self.app_msgs);
+                }
+            }
+            Scene::App => self.app.subscriptions(event, &mut self.app_msgs),
+        }
+        msgs.extend(self.app_msgs.drain(..).map(Msg::App));
 
-        let own_msgs = match event {
+        msgs.extend(match event {
             Event::Mouse(mouse_event) => Some(Msg::MouseEvent(*mouse_event)),
             Event::Keyboard(keyboard_event) => Some(Msg::KeyboardEvent(*keyboard_event)),
+            Event::Character(_) | Event::EditorOpened | Event::EditorClosed => None,
             Event::Tick { .. } => Some(Msg::Tick),
-        }
-        .into_iter();
-
-        sub_msgs.into_iter().chain(own_msgs).collect()
+        });
     }
 }
 
+/// "PAUSED" or the time scale, in the top right corner, whatever the game's palette.
+#[cfg(feature = "editor")]
+fn draw_debug_indicator(pico8: &mut Pico8, text: &str) {
+    pico8.with_saved_state(|pico8| {
+        pico8.reset_pal();
+        pico8.camera(0, 0);
+
+        let (width, height) = pico8.measure_text(text);
+        let x = 127 - width;
+        pico8.rectfill(x - 2, 0, 127, height + 1, Color::BLACK);
+        pico8.print(text, x, 1, Color::WHITE);
+    });
+}
+
 fn view<'a, Game: AppCompat>(
     scene: &'a Scene,
-    editor: &'a mut Editor,
+    #[cfg(feature = "editor")] editor: &'a mut Editor,
     app: &'a mut Game,
     resources: &mut Resources,
 ) -> Element<'a, Msg<Game::Msg>> {
     match scene {
+        #[cfg(feature = "editor")]
         Scene::Editor => <Editor as ElmApp>::view(editor, resources).map(Msg::Editor),
         Scene::App => app.view(resources).map(Msg::App),
     }
@@ -135,54 +259,563 @@ impl<Game: AppCompat> Controller<Game> {
     fn handle_key_combos(&mut self, key_event: KeyboardEvent) {
         self.key_combos.on_event(key_event, |action| match action {
             KeyComboAction::RestartGame => {
+                #[cfg(feature = "editor")]
+                if matches!(self.scene, Scene::Editor) {
+                    self.scene.switch(
+                        &mut self.app,
+                        &mut self.pico8,
+                        &mut self.keys,
+                        &mut self.hidden_draw_state,
+                    );
+                    self.ui.clear_focus();
+                }
                 self.app = Game::init(&mut self.pico8);
-                self.scene = Scene::App;
             }
-            KeyComboAction::SwitchScene => self.scene.flip(),
+            KeyComboAction::ToggleMute => {
+                self.pico8.resources.toggle_mute();
+            }
+            KeyComboAction::ToggleCrtFilter => {
+                let filter = self.pico8.crt_filter().xor(Some(CrtFilter::default()));
+                self.pico8.set_crt_filter(filter);
+            }
+            #[cfg(feature = "editor")]
+            KeyComboAction::SwitchScene => {
+                self.scene.switch(
+                    &mut self.app,
+                    &mut self.pico8,
+                    &mut self.keys,
+                    &mut self.hidden_draw_state,
+                );
+                self.ui.clear_focus();
+            }
+            #[cfg(feature = "editor")]
+            KeyComboAction::TogglePause => self.debug_controls.toggle_pause(),
+            #[cfg(feature = "editor")]
+            KeyComboAction::StepFrame => self.debug_controls.step(),
+            #[cfg(feature = "editor")]
+            KeyComboAction::CycleTimeScale => self.debug_controls.cycle_time_scale(),
+            #[cfg(feature = "editor")]
+            KeyComboAction::ToggleInspector => self.inspector.toggle(),
         });
     }
 
     /// Thing that actually calls update/orchestrates stuff
     pub(crate) fn step(&mut self, event: Option<Event>) {
+        #[cfg(feature = "editor")]
+        if let Some(event) = &event {
+            self.inspector.record(event);
+        }
+
+        // A paused game is still drawn, but nothing updates: not even time() or the buttons.
+        #[cfg(feature = "editor")]
+        let event = match event {
+            Some(Event::Tick { .. })
+                if matches!(self.scene, Scene::App) && !self.debug_controls.take_update() =>
+            {
+                None
+            }
+            event => event,
+        };
+
+        // Numbered here, where the frames are counted.
+        let event = event.map(|event| match event {
+            Event::Tick { delta_millis, .. } => Event::Tick {
+                delta_millis,
+                frame: self.pico8.state.frames(),
+            },
+            event => event,
+        });
+
         let mut view = view(
             &self.scene,
+            #[cfg(feature = "editor")]
             &mut self.editor,
             &mut self.app,
             &mut self.pico8.resources,
         );
 
-        let mut msg_queue = vec![];
-        let dispatch_event = &mut DispatchEvent::new(&mut msg_queue);
+        let mut msg_queue = std::mem::take(&mut self.msg_queue);
 
+        // Key presses taken by the ui don't go to the key combos
+        // or the current scene's subscriptions.
         let cursor_position = (self.pico8.state.mouse_x, self.pico8.state.mouse_y);
-        if let Some(event) = event {
-            view.as_widget_mut()
-                .on_event(event, cursor_position, dispatch_event);
-        }
+        let keyboard_captured =
+            self.ui
+                .dispatch(view.as_widget_mut(), event, cursor_position, &mut msg_queue);
 
-        view.as_widget_mut().draw(&mut self.pico8);
+        let widget = view.as_widget_mut();
+        widget.draw_layer(&mut self.pico8, Layer::Base);
+        // Before the overlays and the cursor, so they don't leave trails in `Pico8::blit_prev`.
+        self.pico8.draw_data.keep_frame();
+        for layer in [Layer::Overlay, Layer::Cursor] {
+            widget.draw_layer(&mut self.pico8, layer);
+        }
         drop(view);
+        #[cfg(feature = "editor")]
+        if let (Scene::App, Some(text)) = (&self.scene, self.debug_controls.indicator()) {
+            draw_debug_indicator(&mut self.pico8, &text);
+        }
+        #[cfg(feature = "editor")]
+        self.inspector.draw(&mut self.pico8, &self.scene);
+
+        // The editor draws its own cursor.
+        if matches!(self.scene, Scene::App) {
+            self.pico8.draw_cursor();
+        }
 
-        for subscription_msg in event.into_iter().flat_map(|e| self.subscriptions(&e)) {
-            msg_queue.push(subscription_msg);
+        if let Some(Event::Keyboard(key_event)) = event {
+            if !keyboard_captured {
+                self.handle_key_combos(key_event);
+            }
         }
-        for msg in msg_queue.into_iter() {
+
+        if let Some(event) = &event {
+            self.subscriptions(event, keyboard_captured, &mut msg_queue);
+        }
+        for msg in msg_queue.drain(..) {
             self.update(&msg);
         }
+        self.msg_queue = msg_queue;
+
+        if let Some(Event::Tick { .. }) = event {
+            self.pico8.resources.save_volume(false);
+        }
+    }
+
+    /// Writes the settings that are still waiting to be saved, before the window closes.
+    pub(crate) fn exit(&mut self) {
+        self.pico8.resources.save_volume(true);
+        #[cfg(feature = "editor")]
+        self.editor.save_settings(&self.pico8.resources, true);
     }
 }
 
 #[derive(Debug)]
 pub enum Scene {
+    #[cfg(feature = "editor")]
     Editor,
     App,
 }
 
 impl Scene {
+    #[cfg(feature = "editor")]
     pub fn flip(&mut self) {
         *self = match self {
             Scene::Editor => Scene::App,
             Scene::App => Scene::Editor,
         }
     }
+
+    /// Flips, and swaps the draw state with the other scene's:
+    /// neither sees the palette, camera, clip... the other one left behind.
+    /// The game's buttons only get input while it's showing.
+    ///
+    /// The game is told with [`Event::EditorOpened`] or [`Event::EditorClosed`],
+    /// whatever it gets while the editor is open.
+    #[cfg(feature = "editor")]
+    fn switch<Game: AppCompat>(
+        &mut self,
+        app: &mut Game,
+        pico8: &mut Pico8,
+        keys: &mut Keys,
+        hidden_draw_state: &mut DrawData,
+    ) {
+        self.flip();
+        pico8.draw_data.swap_draw_state(hidden_draw_state);
+        let event = match self {
+            Scene::Editor => {
+                pico8.state.release_keys();
+                Event::EditorOpened
+            }
+            Scene::App => {
+                pico8.state.resume_keys(keys);
+                Event::EditorClosed
+            }
+        };
+
+        let mut msgs = vec![];
+        app.subscriptions(&event, &mut msgs);
+        for msg in msgs {
+            app.update(&msg, pico8);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{ElmAppCompat, Pico8AppCompat};
+    use crate::ui::Tree;
+    use crate::{App, Color, ElmApp, Palette};
+    #[cfg(feature = "editor")]
+    use crate::{Button, KeyState};
+
+    struct Game;
+
+    impl App for Game {
+        fn init(_: &mut Pico8) -> Self {
+            Self
+        }
+
+        fn update(&mut self, _: &mut Pico8) {}
+
+        fn draw(&mut self, _: &mut Pico8) {}
+    }
+
+    type TestController = Controller<Pico8AppCompat<Game>>;
+
+    // Shows whether the editor is open in the window's title.
+    #[cfg(feature = "editor")]
+    struct Jukebox;
+
+    #[cfg(feature = "editor")]
+    impl App for Jukebox {
+        fn init(_: &mut Pico8) -> Self {
+            Self
+        }
+
+        fn update(&mut self, _: &mut Pico8) {}
+
+        fn draw(&mut self, _: &mut Pico8) {}
+
+        fn editor_opened(&mut self, pico8: &mut Pico8) {
+            pico8.set_title("PAUSED".to_owned());
+        }
+
+        fn editor_closed(&mut self, pico8: &mut Pico8) {
+            pico8.set_title("PLAYING".to_owned());
+        }
+    }
+
+    // Marks the map cell of every frame number it's ticked with.
+    struct Clock;
+
+    impl ElmApp for Clock {
+        type Msg = u64;
+
+        fn init() -> Self {
+            Self
+        }
+
+        fn update(&mut self, &frame: &u64, resources: &mut Resources) {
+            resources.mset(frame as i32, 0, 1);
+        }
+
+        fn view(&mut self, _: &Resources) -> Element<'_, u64> {
+            Tree::new().into()
+        }
+
+        fn subscriptions(&self, event: &Event, msgs: &mut Vec<u64>) {
+            if let &Event::Tick { frame, .. } = event {
+                msgs.push(frame);
+            }
+        }
+    }
+
+    #[cfg(feature = "editor")]
+    fn press<G: AppCompat>(controller: &mut Controller<G>, key: Key, modifiers: &[Key]) {
+        let event = |key, state| Some(Event::Keyboard(KeyboardEvent { key, state }));
+
+        for &modifier in modifiers {
+            controller.step(event(modifier, KeyState::Down));
+        }
+        controller.step(event(key, KeyState::Down));
+        controller.step(event(key, KeyState::Up));
+        for &modifier in modifiers {
+            controller.step(event(modifier, KeyState::Up));
+        }
+    }
+
+    fn tick<G: AppCompat>(controller: &mut Controller<G>) {
+        controller.step(Some(Event::Tick {
+            delta_millis: 1000.0 / 30.0,
+            frame: 0,
+        }));
+    }
+
+    #[test]
+    fn ticks_are_numbered_from_the_frame_count() {
+        let mut controller =
+            Controller::<ElmAppCompat<Clock>>::init(Scene::App, Resources::empty());
+        for _ in 0..3 {
+            tick(&mut controller);
+        }
+
+        let marked = |x| controller.pico8.resources.mget(x, 0) == 1;
+        assert!(marked(0) && marked(1) && marked(2));
+        assert!(!marked(3));
+        assert_eq!(controller.pico8.frame_count(), 3);
+        assert_eq!(controller.pico8.elapsed_seconds(), 0.1);
+    }
+
+    #[cfg(feature = "editor")]
+    #[test]
+    fn games_are_told_when_the_editor_opens_and_closes() {
+        let mut controller =
+            Controller::<Pico8AppCompat<Jukebox>>::init(Scene::App, Resources::empty());
+        tick(&mut controller);
+        assert_eq!(controller.take_new_title(), None);
+
+        press(&mut controller, Key::Escape, &[]);
+        assert_eq!(controller.take_new_title().as_deref(), Some("PAUSED"));
+        press(&mut controller, Key::Escape, &[]);
+        assert_eq!(controller.take_new_title().as_deref(), Some("PLAYING"));
+
+        // Restarting from the editor closes it.
+        press(&mut controller, Key::Escape, &[]);
+        controller.take_new_title();
+        press(&mut controller, Key::R, &[Key::Control]);
+        assert_eq!(controller.take_new_title().as_deref(), Some("PLAYING"));
+    }
+
+    #[test]
+    fn the_screen_and_border_are_shown_with_the_palette() {
+        let mut controller = TestController::init(Scene::App, Resources::empty());
+        let mut palette = Palette::pico8();
+        palette.set_rgb(Color::BLACK, (1, 2, 3));
+        controller.pico8.set_palette(palette);
+
+        assert_eq!(controller.screen_buffer()[..3], [1, 2, 3]);
+        assert_eq!(controller.border_rgb(), (1, 2, 3));
+    }
+
+    #[cfg(feature = "editor")]
+    #[test]
+    fn paused_games_only_update_when_stepped() {
+        let mut controller = TestController::init(Scene::App, Resources::empty());
+        tick(&mut controller);
+        assert_eq!(controller.pico8.state.frames(), 1);
+
+        press(&mut controller, Key::P, &[Key::Control]);
+        tick(&mut controller);
+        assert_eq!(controller.pico8.state.frames(), 1);
+
+        press(&mut controller, Key::N, &[Key::Control]);
+        tick(&mut controller);
+        tick(&mut controller);
+        assert_eq!(controller.pico8.state.frames(), 2);
+
+        press(&mut controller, Key::L, &[Key::Control]);
+        assert_eq!(controller.time_scale(), 0.5);
+        press(&mut controller, Key::P, &[Key::Control]);
+        tick(&mut controller);
+        assert_eq!(controller.pico8.state.frames(), 3);
+    }
+
+    #[cfg(feature = "editor")]
+    #[test]
+    fn the_inspector_leaves_input_to_the_game() {
+        let mut controller = TestController::init(Scene::App, Resources::empty());
+        press(&mut controller, Key::K, &[Key::Control]);
+        assert!(controller.inspector.is_visible());
+
+        controller.step(Some(Event::Keyboard(KeyboardEvent {
+            key: Key::LeftArrow,
+            state: KeyState::Down,
+        })));
+        tick(&mut controller);
+        assert!(controller.pico8.state.button(Button::Left).btnp());
+
+        press(&mut controller, Key::K, &[Key::Control]);
+        assert!(!controller.inspector.is_visible());
+    }
+
+    // Draws its frame number in the top left pixel, and what was there last frame next to it.
+    struct Feedback {
+        frame: u8,
+    }
+
+    impl App for Feedback {
+        fn init(_: &mut Pico8) -> Self {
+            Self { frame: 0 }
+        }
+
+        fn update(&mut self, _: &mut Pico8) {}
+
+        fn draw(&mut self, pico8: &mut Pico8) {
+            self.frame += 1;
+            let previous = pico8.pget_prev(0, 0);
+            pico8.cls(Color::BLACK);
+            pico8.pset(1, 0, previous);
+            pico8.pset(0, 0, self.frame);
+        }
+    }
+
+    #[test]
+    fn the_previous_frame_is_kept_while_drawing_the_next() {
+        let mut controller =
+            Controller::<Pico8AppCompat<Feedback>>::init(Scene::App, Resources::empty());
+
+        tick(&mut controller);
+        tick(&mut controller);
+        let draw_data = &controller.pico8.draw_data;
+        assert_eq!(draw_data.pixel(0, 0), Some(Color::DARK_PURPLE));
+        assert_eq!(draw_data.pixel(1, 0), Some(Color::DARK_BLUE));
+
+        // The window shows the frame that was just drawn.
+        let palette = &controller.pico8.resources.palette;
+        let (r, g, b) = palette.rgb(Color::DARK_PURPLE);
+        assert_eq!(controller.screen_buffer()[..3], [r, g, b]);
+    }
+
+    #[cfg(feature = "editor")]
+    #[test]
+    fn scenes_keep_their_own_draw_state() {
+        struct PaletteGame;
+
+        impl App for PaletteGame {
+            fn init(pico8: &mut Pico8) -> Self {
+                pico8.pal(Color::WHITE, Color::RED);
+                pico8.palt(None);
+                pico8.camera(4, 4);
+
+                Self
+            }
+
+            fn update(&mut self, _: &mut Pico8) {}
+
+            fn draw(&mut self, _: &mut Pico8) {}
+        }
+
+        let mut controller =
+            Controller::<Pico8AppCompat<PaletteGame>>::init(Scene::App, Resources::empty());
+        tick(&mut controller);
+        let game_pal = controller.pico8.draw_data.get_pal();
+
+        press(&mut controller, Key::Escape, &[]);
+        tick(&mut controller);
+        let draw_data = &mut controller.pico8.draw_data;
+        assert_eq!(draw_data.get_pal(), DrawData::new().get_pal());
+        assert_eq!(draw_data.get_palt(), Some(Color::BLACK));
+        assert_eq!(draw_data.get_clip(), (0, 0, 128, 128));
+        // Left behind by the editor.
+        draw_data.pal(Color::RED, Color::BLUE);
+        draw_data.clip(0, 0, 8, 8);
+
+        press(&mut controller, Key::Escape, &[]);
+        tick(&mut controller);
+        let draw_data = &controller.pico8.draw_data;
+        assert_eq!(draw_data.get_pal(), game_pal);
+        assert_eq!(draw_data.get_pal()[7], Color::RED);
+        assert_eq!(draw_data.get_palt(), None);
+        assert_eq!(draw_data.get_clip(), (0, 0, 128, 128));
+        assert_eq!(draw_data.get_camera(), (4, 4));
+    }
+
+    // Counts its updates, and the updates where it saw Right pressed and held, in the map.
+    #[cfg(feature = "editor")]
+    struct InputGame;
+
+    #[cfg(feature = "editor")]
+    impl App for InputGame {
+        fn init(pico8: &mut Pico8) -> Self {
+            for x in 0..3 {
+                pico8.mset(x, 0, 0);
+            }
+
+            Self
+        }
+
+        fn update(&mut self, pico8: &mut Pico8) {
+            let counters = [
+                true,
+                pico8.btnp(Button::Right),
+                pico8.btn(Button::Right) || pico8.btn(Button::X),
+            ];
+            for (x, counter) in counters.into_iter().enumerate() {
+                let x = x as i32;
+                pico8.mset(x, 0, pico8.mget(x, 0) + counter as u8);
+            }
+        }
+
+        fn draw(&mut self, _: &mut Pico8) {}
+    }
+
+    #[cfg(feature = "editor")]
+    fn counters(controller: &Controller<Pico8AppCompat<InputGame>>) -> (u8, u8, u8) {
+        let pico8 = &controller.pico8;
+
+        (pico8.mget(0, 0), pico8.mget(1, 0), pico8.mget(2, 0))
+    }
+
+    #[cfg(feature = "editor")]
+    fn key(controller: &mut Controller<Pico8AppCompat<InputGame>>, key: Key, state: KeyState) {
+        controller.step(Some(Event::Keyboard(KeyboardEvent { key, state })));
+    }
+
+    #[cfg(feature = "editor")]
+    #[test]
+    fn keys_held_through_the_editor_are_not_pressed_again() {
+        let mut controller =
+            Controller::<Pico8AppCompat<InputGame>>::init(Scene::App, Resources::empty());
+        key(&mut controller, Key::RightArrow, KeyState::Down);
+        // Updates see the buttons as of the tick before.
+        tick(&mut controller);
+        tick(&mut controller);
+        assert_eq!(counters(&controller), (2, 1, 1));
+
+        press(&mut controller, Key::Escape, &[]);
+        key(&mut controller, Key::X, KeyState::Down);
+        key(&mut controller, Key::X, KeyState::Up);
+        tick(&mut controller);
+        assert!(!controller.pico8.state.button(Button::Right).btn());
+        // Frozen in the editor.
+        assert_eq!(counters(&controller), (2, 1, 1));
+
+        press(&mut controller, Key::Escape, &[]);
+        tick(&mut controller);
+        tick(&mut controller);
+        assert_eq!(counters(&controller), (4, 1, 3));
+        assert!(!controller.pico8.state.button(Button::X).btn());
+
+        key(&mut controller, Key::RightArrow, KeyState::Up);
+        key(&mut controller, Key::RightArrow, KeyState::Down);
+        tick(&mut controller);
+        tick(&mut controller);
+        assert_eq!(counters(&controller), (6, 2, 5));
+    }
+
+    #[cfg(feature = "editor")]
+    #[test]
+    fn keys_released_in_the_editor_are_released_in_the_game() {
+        let mut controller =
+            Controller::<Pico8AppCompat<InputGame>>::init(Scene::App, Resources::empty());
+        key(&mut controller, Key::RightArrow, KeyState::Down);
+        tick(&mut controller);
+        tick(&mut controller);
+        assert_eq!(counters(&controller), (2, 1, 1));
+
+        press(&mut controller, Key::Escape, &[]);
+        key(&mut controller, Key::RightArrow, KeyState::Up);
+        tick(&mut controller);
+        press(&mut controller, Key::Escape, &[]);
+        assert!(!controller.pico8.state.button(Button::Right).btn());
+        tick(&mut controller);
+        tick(&mut controller);
+        assert_eq!(counters(&controller), (4, 1, 1));
+
+        // Pressing it again is a new press.
+        key(&mut controller, Key::RightArrow, KeyState::Down);
+        tick(&mut controller);
+        tick(&mut controller);
+        assert_eq!(counters(&controller), (6, 2, 2));
+    }
+
+    #[cfg(feature = "editor")]
+    #[test]
+    fn games_can_update_without_input_in_the_editor() {
+        let mut controller =
+            Controller::<Pico8AppCompat<InputGame>>::init(Scene::App, Resources::empty());
+        controller.pico8.set_updates_in_editor(true);
+        key(&mut controller, Key::RightArrow, KeyState::Down);
+        press(&mut controller, Key::Escape, &[]);
+
+        tick(&mut controller);
+        tick(&mut controller);
+        assert_eq!(counters(&controller), (2, 0, 0));
+
+        press(&mut controller, Key::Escape, &[]);
+        tick(&mut controller);
+        assert_eq!(counters(&controller), (3, 0, 1));
+    }
 }
@@ -0,0 +1,116 @@
+//! Pausing, stepping and slowing down the game while debugging it.
+//! Only with the `editor` feature.
+
+/// How fast the game runs, see [`DebugControls::cycle_time_scale`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum TimeScale {
+    #[default]
+    Full,
+    Half,
+    Quarter,
+}
+
+impl TimeScale {
+    fn next(self) -> Self {
+        match self {
+            Self::Full => Self::Half,
+            Self::Half => Self::Quarter,
+            Self::Quarter => Self::Full,
+        }
+    }
+
+    fn factor(self) -> f64 {
+        match self {
+            Self::Full => 1.0,
+            Self::Half => 0.5,
+            Self::Quarter => 0.25,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct DebugControls {
+    paused: bool,
+    // Whether the next update runs even though the game is paused.
+    step: bool,
+    time_scale: TimeScale,
+}
+
+impl DebugControls {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.step = false;
+    }
+
+    /// Runs exactly one update, if the game is paused.
+    pub(crate) fn step(&mut self) {
+        self.step = self.paused;
+    }
+
+    /// Full speed, half speed, quarter speed, and back.
+    pub(crate) fn cycle_time_scale(&mut self) {
+        self.time_scale = self.time_scale.next();
+    }
+
+    /// What the time that passed is multiplied by before it's turned into updates.
+    pub(crate) fn time_scale(&self) -> f64 {
+        self.time_scale.factor()
+    }
+
+    /// Whether the game gets to update now. Uses up a requested step.
+    pub(crate) fn take_update(&mut self) -> bool {
+        !self.paused || std::mem::take(&mut self.step)
+    }
+
+    /// Shown over the game while it isn't running normally.
+    pub(crate) fn indicator(&self) -> Option<String> {
+        match (self.paused, self.time_scale) {
+            (true, _) => Some("PAUSED".to_owned()),
+            (false, TimeScale::Full) => None,
+            (false, time_scale) => Some(format!("X{}", time_scale.factor())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paused_games_only_update_when_stepped() {
+        let mut controls = DebugControls::new();
+        assert!(controls.take_update());
+
+        controls.toggle_pause();
+        assert!(!controls.take_update());
+        assert_eq!(controls.indicator().as_deref(), Some("PAUSED"));
+
+        controls.step();
+        assert!(controls.take_update());
+        assert!(!controls.take_update());
+
+        controls.toggle_pause();
+        // Stepping only means something while paused.
+        controls.step();
+        assert!(controls.take_update());
+        assert!(controls.take_update());
+        assert_eq!(controls.indicator(), None);
+    }
+
+    #[test]
+    fn time_scales_cycle() {
+        let mut controls = DebugControls::new();
+
+        controls.cycle_time_scale();
+        assert_eq!(controls.time_scale(), 0.5);
+        assert_eq!(controls.indicator().as_deref(), Some("X0.5"));
+        controls.cycle_time_scale();
+        assert_eq!(controls.time_scale(), 0.25);
+        controls.cycle_time_scale();
+        assert_eq!(controls.time_scale(), 1.0);
+    }
+}
@@ -1,3 +1,9 @@
+/// Thresholds (0 to 15) of a 4x4 ordered dither, for mixing two colors in a fixed pattern:
+/// a pixel gets the first color when its threshold is below the amount of it (out of 16).
+#[cfg(feature = "editor")]
+pub(crate) const BAYER_4X4: [[u8; 4]; 4] =
+    [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
 pub fn line(x0: i32, y0: i32, x1: i32, y1: i32) -> LineIter {
     LineIter::new(x0, y0, x1, y1)
 }
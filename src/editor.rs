@@ -1,29 +1,63 @@
 mod brush_size;
-pub mod key_combo;
+mod display_palette;
+mod dither;
 mod map;
+mod minimap;
 mod notification;
-pub mod serialize;
+mod pal_remap;
+mod palette;
+#[cfg(feature = "cli")]
+mod png;
+pub(crate) mod ppm;
+mod settings;
+mod sfx;
+pub(crate) mod shade;
 mod sprite;
+mod sprite_file;
+mod sprite_index;
+mod stats;
+mod transparency;
 mod undo_redo;
 
 use crate::app::ElmApp;
+use crate::audio::{self, music::Music, sfx::SoundEffects};
+use crate::draw;
 use crate::editor::notification::Notification;
+use crate::font::{truncate, Font};
+use crate::key_combo::KeyCombos;
 use crate::runtime::flags::Flags;
 use crate::runtime::map::Map;
-use crate::runtime::sprite_sheet::{Color, Sprite, SpriteSheet};
+use crate::runtime::sprite_sheet::{Sprite, SpriteSheet};
+use crate::serialize::{self, Saver, Serialize};
 use crate::ui::button::{self, Button};
+use crate::ui::hover::Hover;
+use crate::ui::scroll_area;
+use crate::ui::text_input::{self, TextInput};
 use crate::ui::{
-    cursor::{self, Cursor},
-    text::Text,
+    cursor::{self, Cursor, CursorKind},
+    drag::{self, Draggable, DropTarget},
 };
-use crate::ui::{DrawFn, Element, Tree};
+use crate::ui::{DrawFn, Element, Layer, Tree};
+use crate::util::vec2::vec2;
+use crate::Color;
+use crate::Palette;
 use crate::Resources;
-use crate::{Event, Key, KeyState, KeyboardEvent};
+use crate::{Event, Key, KeyState, KeyboardEvent, MouseButton, MouseEvent};
 use brush_size::BrushSize;
-use serialize::serialize;
-
-use self::key_combo::KeyCombos;
-use self::serialize::{Ppm, Serialize};
+use dither::Dither;
+use shade::ShadeRamp;
+use sprite_index::SPRITES_PER_PAGE;
+use std::collections::HashSet;
+use std::io;
+use std::ops::Range;
+
+use self::display_palette::DisplayNotes;
+use self::pal_remap::PalRemap;
+use self::ppm::Ppm;
+use self::settings::EditorSettings;
+use self::sprite_file::SpriteStrip;
+use self::stats::SheetStats;
+use self::transparency::TransparentPages;
 use self::undo_redo::{Command, Commands};
 
 #[derive(Debug)]
@@ -33,11 +67,22 @@ pub(crate) struct Editor {
     selected_sprite_page: usize,
     sprite_button_state: button::State,
     map_button_state: button::State,
-    tab_buttons: [button::State; 4],
+    sfx_button_state: button::State,
+    palette_button_state: button::State,
+    // One per sprite page, resized to match the sprite sheet.
+    tab_buttons: Vec<button::State>,
     sprite_buttons: Vec<button::State>,
+    sprite_drag_states: Vec<drag::State>,
     selected_tool: usize,
     tool_buttons: Vec<button::State>,
-    bottom_bar_text: String,
+    // Clicking the sprite number (or pressing G) opens a field to type the number of a sprite to go to.
+    goto_sprite_button: button::State,
+    goto_sprite_input: text_input::State,
+    // What the bottom bar describes, the hovered element or the last thing done.
+    help: Option<Help>,
+    // Formatted only when what they show changes, not on every frame.
+    bottom_bar_text: CachedText<(Option<Help>, BrushSize, Dither, TransparentPages)>,
+    sprite_number_text: CachedText<usize>,
     notification: notification::State,
     key_combos: KeyCombos<KeyComboAction>,
     clipboard: Clipboard,
@@ -45,95 +90,723 @@ pub(crate) struct Editor {
     editor_sprites: SpriteSheet,
     map_editor: map::Editor,
     sprite_editor: sprite::Editor,
+    sfx_editor: sfx::Editor,
+    palette_editor: palette::Editor,
     brush_size: BrushSize,
+    dither: Dither,
+    dither_button: button::State,
     selected_sprite: usize,
+    // The other end of the sprites picked with Shift+click, starting from the selected sprite.
+    sprite_selection_end: Option<usize>,
+    // Where the pencil last painted, segments drawn with Shift+click start there.
+    last_painted: Option<(isize, isize)>,
+    shade_ramp: ShadeRamp,
+    // Sprite pages drawn with color 0 transparent, right clicking a page's tab toggles it.
+    transparent_pages: TransparentPages,
+    // The extended colors sprites are previewed with, chosen in the color selector's second page.
+    display_notes: DisplayNotes,
+    // The corner the gradient rectangle is being dragged from, and the sprite before the gradient,
+    // which is redrawn as the rectangle changes.
+    gradient: Option<((isize, isize), Sprite)>,
+    // Pixels the shade brush already stepped during the current stroke.
+    shaded_pixels: HashSet<(isize, isize)>,
+    shift_held: bool,
+    control_held: bool,
+    alt_held: bool,
+    // Shown instead of the current tab, `None` when the stats page is closed.
+    stats: Option<SheetStats>,
+    // Shown over the current tab (and the stats page) while open.
+    notification_log_open: bool,
+    notification_log_scroll: scroll_area::State,
+    copy_log_button: button::State,
+    settings_saver: Saver<EditorSettings>,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[allow(clippy::enum_variant_names)]
 enum Tab {
     SpriteEditor,
     MapEditor,
+    SfxEditor,
+    PaletteEditor,
 }
 
 impl Tab {
+    fn name(self) -> &'static str {
+        match self {
+            Self::SpriteEditor => "sprite",
+            Self::MapEditor => "map",
+            Self::SfxEditor => "sfx",
+            Self::PaletteEditor => "palette",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [
+            Self::SpriteEditor,
+            Self::MapEditor,
+            Self::SfxEditor,
+            Self::PaletteEditor,
+        ]
+        .into_iter()
+        .find(|tab| tab.name() == name)
+    }
+
     fn previous(self) -> Self {
         match self {
-            Self::SpriteEditor => Self::MapEditor,
+            Self::PaletteEditor => Self::SfxEditor,
+            Self::SpriteEditor => Self::PaletteEditor,
             Self::MapEditor => Self::SpriteEditor,
+            Self::SfxEditor => Self::MapEditor,
         }
     }
 
     fn next(self) -> Self {
         match self {
+            Self::PaletteEditor => Self::SpriteEditor,
             Self::SpriteEditor => Self::MapEditor,
-            Self::MapEditor => Self::SpriteEditor,
+            Self::MapEditor => Self::SfxEditor,
+            Self::SfxEditor => Self::PaletteEditor,
         }
     }
 }
 
 #[derive(Debug, Clone, Copy)]
+#[allow(clippy::enum_variant_names)]
 pub(crate) enum Msg {
     SpriteTabClicked,
     MapButtonClicked,
-    ColorHovered(Color),
+    SfxButtonClicked,
+    PaletteButtonClicked,
     SpritePageSelected(usize),
+    SpritePageTransparencyToggled(usize),
     SpriteButtonClicked(usize),
+    // Arrows and PageUp/PageDown while the sprite view has the focus.
+    SpriteGridKeyPressed(Key),
+    SpriteDropped { from: usize, to: usize },
     FlagToggled(usize),
-    FlagHovered { bit_number: usize },
+    // An extended color (0 for 128) to show the primary color as in the selected sprite.
+    ExtendedColorChosen(u8),
     SpriteEdited { x: usize, y: usize, color: Color }, // TODO: Improve
+    // A mouse button was released, wherever it was.
+    StrokeEnded,
     ToolSelected(usize),
+    // The cursor entered or left an element with help, see `with_help`.
+    HelpShown(Help),
+    HelpHidden(Help),
     ClickedMapTile { x: usize, y: usize },
+    // Middle click on the map.
+    MapTilePicked,
     KeyboardEvent(KeyboardEvent),
-    BrushSizeSliderHovered,
     BrushSizeSelected(BrushSize),
+    DitherToggled,
+    GotoSpriteOpened,
+    GotoSpriteSubmitted,
+    NotificationLogCopied,
+    PalRemapCopied,
     MapEditorMsg(map::Msg),
     SpriteEditorMsg(sprite::Msg),
+    SfxEditorMsg(sfx::Msg),
+    PaletteEditorMsg(palette::Msg),
+    Tick,
+}
+
+/// What the bottom bar says about an element, shortcuts go in parentheses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Help {
+    Text(&'static str),
+    Color(Color),
+    // Pico8's color 128 + n.
+    ExtendedColor(u8),
+    Flag(usize),
+    Pixel {
+        x: usize,
+        y: usize,
+    },
+    Page(usize),
+    // A sprite page's tab, which also says whether color 0 is transparent on it.
+    SpritePage(usize),
+    // A slot in the sprite view, number keys toggle its flags.
+    Sprite(usize),
+    BrushSize,
+    Dither,
+    // Not hovered, left in the bottom bar after picking a sprite from the map.
+    Picked(usize),
+    // Also not hovered, after toggling a flag with a number key.
+    FlagSet {
+        sprite: usize,
+        flag: u8,
+        value: bool,
+    },
+    // After trying to stamp a block from a sprite in the sheet's last column or row.
+    NoBlock(usize),
+}
+
+/// Shows `help` in the bottom bar while the cursor is over the given area,
+/// usually the same as `element`'s.
+fn with_help<'a>(
+    element: impl Into<Element<'a, Msg>>,
+    (x, y, width, height): (i32, i32, i32, i32),
+    help: Help,
+) -> Element<'a, Msg> {
+    Hover::new(element, x, y, width, height)
+        .on_enter(Msg::HelpShown(help))
+        .on_leave(Msg::HelpHidden(help))
+        .into()
+}
+
+impl Help {
+    // The brush size, dither mode and page transparency are described as they currently are.
+    fn text(
+        self,
+        brush_size: BrushSize,
+        dither: Dither,
+        transparent_pages: TransparentPages,
+    ) -> String {
+        match self {
+            Help::Text(text) => text.to_owned(),
+            Help::Color(color) => format!("COLOUR {}, RMB: SECONDARY", color),
+            Help::ExtendedColor(extended) => format!(
+                "COLOUR {}: SHOW PRIMARY AS IT",
+                display_palette::FIRST_EXTENDED + extended
+            ),
+            Help::Flag(bit_number) => format!("FLAG {} (0X{:X})", bit_number, 1 << bit_number),
+            Help::Pixel { x, y } => format!("X {} Y {}", x, y),
+            Help::Page(page) => format!("PAGE {}", page),
+            Help::SpritePage(page) => format!(
+                "PAGE {page}, 0 {} (RMB)",
+                if transparent_pages.contains_page(page) {
+                    "TRANSPARENT"
+                } else {
+                    "OPAQUE"
+                }
+            ),
+            Help::Sprite(sprite) => format!("SPR {:0>3}, 0-7: TOGGLE FLAG", sprite),
+            Help::BrushSize => format!("BRUSH SIZE: {}", brush_size.to_human_readable()),
+            Help::Dither => dither.label().to_owned(),
+            Help::Picked(sprite) => format!("PICKED SPR {:0>3}", sprite),
+            Help::FlagSet {
+                sprite,
+                flag,
+                value,
+            } => format!(
+                "SPR {sprite:0>3} FLAG {flag} -> {}",
+                if value { "ON" } else { "OFF" }
+            ),
+            Help::NoBlock(sprite) => format!("SPR {sprite:0>3} CAN'T START A 2X2 BLOCK"),
+        }
+    }
+}
+
+/// Text that's only formatted again when the value it's made from changes,
+/// instead of on every frame.
+#[derive(Debug)]
+struct CachedText<T> {
+    value: Option<T>,
+    text: String,
+}
+
+impl<T: PartialEq> CachedText<T> {
+    fn new() -> Self {
+        Self {
+            value: None,
+            text: String::new(),
+        }
+    }
+
+    fn get(&mut self, value: T, format: impl FnOnce(&T) -> String) -> &str {
+        if self.value.as_ref() != Some(&value) {
+            self.text = format(&value);
+            self.value = Some(value);
+        }
+
+        &self.text
+    }
+}
+
+fn help_text(
+    help: Option<Help>,
+    brush_size: BrushSize,
+    dither: Dither,
+    transparent_pages: TransparentPages,
+) -> String {
+    help.map_or_else(String::new, |help| {
+        help.text(brush_size, dither, transparent_pages)
+    })
 }
 
 impl Editor {
+    /// Writes the settings once they've stayed the same for a bit, called every frame.
+    /// With `now`, writes them if they changed at all (when the window closes).
+    pub(crate) fn save_settings(&mut self, resources: &Resources, now: bool) {
+        let settings = self.settings();
+        let changed = if now {
+            self.settings_saver.flush(settings)
+        } else {
+            self.settings_saver.tick(settings)
+        };
+
+        if let Some(settings) = changed {
+            let file_name = EditorSettings::file_name();
+            // The editor still works from a read-only directory, it just won't remember its settings.
+            if let Err(error) = write_assets_file(&file_name, &settings, resources) {
+                eprintln!("Couldn't save the editor settings to {file_name}: {error}");
+            }
+        }
+    }
+
+    fn settings(&self) -> EditorSettings {
+        let (map_camera, map_sprites) = self.map_editor.view_settings();
+
+        EditorSettings {
+            tab: self.tab,
+            sprite_page: self.selected_sprite_page,
+            brush_size: self.brush_size,
+            map_camera,
+            map_sprites,
+            shade_ramp: self.shade_ramp,
+            pal_remap: self.sprite_editor.pal_remap(),
+            transparent_pages: self.transparent_pages,
+            display_notes: self.display_notes.clone(),
+        }
+    }
+
+    // Paints with the brush centered on a pixel of the selected sprite.
+    // When dithering, `color` is mixed with the other selected color.
+    fn paint(&mut self, sprite_sheet: &mut SpriteSheet, x: isize, y: isize, color: Color) {
+        let (primary, secondary) = self.sprite_editor.colors();
+        let other = if color == primary { secondary } else { primary };
+        let color_at = |x, y| self.dither.color(x, y, color, other);
+
+        let Some(sprite) = sprite_sheet.try_get_sprite_mut(self.selected_sprite) else {
+            return;
+        };
+        let previous_color = sprite.pget(x, y);
+
+        self.commands.push(Command::pixel_changed(
+            self.selected_sprite,
+            x,
+            y,
+            previous_color,
+            color_at(x, y),
+        ));
+
+        for (x, y) in self
+            .brush_size
+            .iter()
+            .map(|(local_x, local_y)| (local_x + x, local_y + y))
+        {
+            sprite.pset(x, y, color_at(x, y));
+        }
+    }
+
+    // Steps the pixels under the brush along the shade ramp (lighter with Shift held),
+    // each one only once per stroke.
+    fn shade(&mut self, sprite_sheet: &mut SpriteSheet, x: isize, y: isize) {
+        let Some(sprite) = sprite_sheet.try_get_sprite_mut(self.selected_sprite) else {
+            return;
+        };
+
+        for (x, y) in self
+            .brush_size
+            .iter()
+            .map(|(local_x, local_y)| (local_x + x, local_y + y))
+        {
+            let inside = (0..Sprite::WIDTH as isize).contains(&x)
+                && (0..Sprite::HEIGHT as isize).contains(&y);
+            if !inside || !self.shaded_pixels.insert((x, y)) {
+                continue;
+            }
+
+            let previous_color = sprite.pget(x, y);
+            let color = self.shade_ramp.step(previous_color, self.shift_held);
+            if color != previous_color {
+                self.commands.push(Command::pixel_changed(
+                    self.selected_sprite,
+                    x,
+                    y,
+                    previous_color,
+                    color,
+                ));
+                sprite.pset(x, y, color);
+            }
+        }
+    }
+
+    fn select_tool(&mut self, tool: usize) {
+        self.selected_tool = tool;
+    }
+
+    // In the current tab's sprite view, the map tab has a selection of its own.
+    fn select_sprite(&mut self, sprite: usize) {
+        match self.tab {
+            Tab::MapEditor => self.map_editor.select_tile(sprite),
+            _ => {
+                self.selected_sprite = sprite;
+                self.sprite_selection_end = None;
+                self.selected_sprite_page = sprite_index::page_of(sprite);
+            }
+        }
+    }
+
+    // The sprite view's focus is on a single slot's button, which changes with the selection
+    // and page. The focus goes by position, so it has to be moved to the new slot's button.
+    // The map tab's sprite view can't be focused, it loses the focus.
+    fn move_sprite_view_focus(&mut self) {
+        if !self.sprite_buttons.iter().any(button::State::is_focused) {
+            return;
+        }
+
+        let focus_slot = (self.tab != Tab::MapEditor)
+            .then(|| sprite_view_focus_slot(self.selected_sprite, self.selected_sprite_page));
+        for (slot, button) in self.sprite_buttons.iter_mut().enumerate() {
+            if Some(slot) == focus_slot {
+                button.focus();
+            } else {
+                button.blur();
+            }
+        }
+    }
+
+    // The game can shrink the sheet, or load a cart with a smaller one, while the editor is open.
+    // What was selected past the new end goes to the last sprite.
+    fn fit_selection(&mut self, sprite_count: usize) {
+        let last = sprite_count - 1;
+        self.selected_sprite = self.selected_sprite.min(last);
+        self.sprite_selection_end = self.sprite_selection_end.map(|end| end.min(last));
+        self.selected_sprite_page = self.selected_sprite_page.min(sprite_index::page_of(last));
+    }
+
+    // Every sprite between the selected one and the end of the Shift+click selection.
+    fn selected_sprites(&self) -> Range<usize> {
+        let end = self.sprite_selection_end.unwrap_or(self.selected_sprite);
+
+        self.selected_sprite.min(end)..self.selected_sprite.max(end) + 1
+    }
+
+    // Gives the whole selection the selected sprite's flags.
+    fn apply_flags_to_selection(&mut self, resources: &mut Resources) {
+        let sprites = self.selected_sprites();
+        let flags = resources.fget(self.selected_sprite);
+        let count = sprites
+            .clone()
+            .filter(|&sprite| resources.sprite_flags.get(sprite).is_some())
+            .count();
+
+        resources.sprite_flags.set_range(sprites, flags);
+        self.notification.alert(format!(
+            "FLAGS OF SPR {:0>3} SET ON {count} SPRITES",
+            self.selected_sprite
+        ));
+    }
+
+    // Selects the sprite on a map cell to place, without touching the map.
+    fn pick_map_tile(&mut self, x: usize, y: usize, resources: &Resources) {
+        let sprite = resources.mget(x as i32, y as i32) as usize;
+
+        self.map_editor.select_tile(sprite);
+        self.help = Some(Help::Picked(sprite));
+    }
+
     fn shift_sprite(&mut self, shift_direction: ShiftDirection, sprite_sheet: &mut SpriteSheet) {
-        let sprite = sprite_sheet.get_sprite_mut(self.selected_sprite);
-        shift_direction.shift(sprite);
+        if let Some(sprite) = sprite_sheet.try_get_sprite_mut(self.selected_sprite) {
+            shift_direction.shift(sprite);
+        }
+    }
+
+    /// The sprite under the cursor in the sprite view, `None` in the sfx tab, which has none.
+    fn hovered_sprite(&self) -> Option<usize> {
+        let page = match self.tab {
+            Tab::SfxEditor => return None,
+            Tab::MapEditor => self.map_editor.tile_page(),
+            Tab::SpriteEditor | Tab::PaletteEditor => self.selected_sprite_page,
+        };
+        let (x, y) = self.cursor.position();
+
+        sprite_index::index_from_click(page, x, y - (SPRITE_VIEW_Y + 1))
+    }
+
+    // Lets whole rows of tiles be tagged without selecting each of them.
+    // Only sprites with flags (the first 256) can be tagged.
+    fn toggle_hovered_flag(&mut self, flag: u8, resources: &mut Resources) {
+        let Some(sprite) = self
+            .hovered_sprite()
+            .filter(|&sprite| resources.sprite_flags.get(sprite).is_some())
+        else {
+            return;
+        };
+
+        let value = !resources.fget_n(sprite, flag);
+        resources.fset(sprite, flag as usize, value);
+        self.help = Some(Help::FlagSet {
+            sprite,
+            flag,
+            value,
+        });
+    }
+
+    fn cursor_kind(&self, resources: &Resources) -> CursorKind {
+        match self.tab {
+            Tab::MapEditor if self.map_editor.is_dragging() => CursorKind::Hand,
+            Tab::MapEditor
+                if self.stats.is_none() && self.map_tile_under_cursor(resources).is_some() =>
+            {
+                CursorKind::Crosshair
+            }
+            Tab::SpriteEditor if sprite::canvas_contains(self.cursor.position()) => {
+                if self.selected_tool == FILL_TOOL {
+                    CursorKind::Bucket
+                } else {
+                    CursorKind::Pencil
+                }
+            }
+            _ => CursorKind::Arrow,
+        }
+    }
+
+    fn map_tile_under_cursor(&self, resources: &Resources) -> Option<(usize, usize)> {
+        let origin = vec2(MAP_VIEW_X, MAP_VIEW_Y);
+
+        self.map_editor
+            .tile_under(self.cursor.position(), origin, &resources.map)
+    }
+
+    /// Every tab keeps its state (view, selection, tool...) while another one is showing,
+    /// except for what it was in the middle of.
+    fn switch_tab(&mut self, tab: Tab) {
+        // The tab doesn't get the events that would end it anymore.
+        if self.tab == Tab::MapEditor && tab != Tab::MapEditor {
+            self.map_editor.stop_gestures();
+        }
+        self.tab = tab;
     }
 
     fn handle_key_combos(&mut self, key_event: KeyboardEvent, resources: &mut Resources) {
-        self.key_combos.on_event(key_event, |action| {
-            handle_key_combo(
+        // Selected after the combos are handled, they can't borrow the whole editor.
+        let mut selected_tool = None;
+        // Same for switching tabs.
+        let mut tab = self.tab;
+        // And for applying flags to the selection.
+        let mut apply_flags = false;
+        // The closure can't borrow the editor to find them.
+        let selected_sprites = self.selected_sprites();
+
+        self.key_combos.on_event(key_event, |action| match action {
+            // Scanned when opening the page, not every frame.
+            KeyComboAction::ToggleStats => {
+                self.stats = match self.stats {
+                    Some(_) => None,
+                    None => Some(SheetStats::scan(resources)),
+                };
+            }
+            KeyComboAction::ToggleNotificationLog => {
+                self.notification_log_open = !self.notification_log_open;
+                self.notification_log_scroll.scroll_to_top();
+            }
+            KeyComboAction::ShiftMap(direction, wrap) => {
+                if self.tab == Tab::MapEditor {
+                    map::shift_map(resources, &mut self.commands, direction, wrap);
+                    self.notification
+                        .alert(format!("MAP SHIFTED {}", direction.name()));
+                }
+            }
+            KeyComboAction::GotoSprite => {
+                self.goto_sprite_input.set_text(String::new());
+                self.goto_sprite_input.focus();
+            }
+            KeyComboAction::SelectTool(tool) => {
+                if self.tab == Tab::SpriteEditor {
+                    selected_tool = Some(tool);
+                }
+            }
+            KeyComboAction::ApplyFlagsToSelection => {
+                apply_flags = self.tab == Tab::SpriteEditor;
+            }
+            action => handle_key_combo(
                 action,
                 self.selected_sprite,
+                selected_sprites.clone(),
+                self.sfx_editor.selected_sfx(),
                 &mut self.notification,
                 &mut self.clipboard,
                 resources,
                 &mut self.commands,
-                &mut self.tab,
-            );
-        })
+                &mut tab,
+            ),
+        });
+
+        if let Some(tool) = selected_tool {
+            self.select_tool(tool);
+        }
+        if apply_flags {
+            self.apply_flags_to_selection(resources);
+        }
+        self.switch_tab(tab);
+    }
+
+    /// The editor as it was left, minus what depends on the game's resources.
+    fn with_settings(settings: EditorSettings) -> Self {
+        let mut sprite_editor = sprite::Editor::new();
+        sprite_editor.restore_pal_remap(settings.pal_remap);
+
+        Self {
+            cursor: cursor::State::new(),
+            sprite_button_state: button::State::new(),
+            map_button_state: button::State::new(),
+            sfx_button_state: button::State::new(),
+            palette_button_state: button::State::new(),
+            tab: settings.tab,
+            // Clamped to the sprite sheet when drawing.
+            selected_sprite_page: settings.sprite_page,
+            tab_buttons: vec![button::State::new(); 4],
+            sprite_buttons: vec![button::State::new(); SPRITES_PER_PAGE],
+            sprite_drag_states: vec![drag::State::new(); SPRITES_PER_PAGE],
+            selected_tool: 0,
+            tool_buttons: vec![button::State::new(); TOOLS.len()],
+            goto_sprite_button: button::State::new(),
+            goto_sprite_input: text_input::State::new(),
+            help: None,
+            bottom_bar_text: CachedText::new(),
+            sprite_number_text: CachedText::new(),
+            notification: notification::State::new(),
+            key_combos: KeyCombos::new()
+                .push(KeyComboAction::Copy, Key::C, &[Key::Control])
+                .push(KeyComboAction::Paste, Key::V, &[Key::Control])
+                .push(KeyComboAction::Undo, Key::Z, &[Key::Control])
+                .push(KeyComboAction::Redo, Key::Y, &[Key::Control])
+                .push(KeyComboAction::Save, Key::S, &[Key::Control])
+                .push(KeyComboAction::FlipVertically, Key::V, &[])
+                .push(KeyComboAction::SelectTool(PENCIL_TOOL), Key::B, &[])
+                .push(KeyComboAction::SelectTool(SHADE_TOOL), Key::D, &[])
+                // Before F, which also matches while Shift or Control is held.
+                .push(KeyComboAction::SelectTool(FILL_TOOL), Key::F, &[Key::Shift])
+                .push(
+                    KeyComboAction::ApplyFlagsToSelection,
+                    Key::F,
+                    &[Key::Control],
+                )
+                .push(KeyComboAction::FlipHorizontally, Key::F, &[])
+                .push(KeyComboAction::Rotate, Key::R, &[])
+                .push(KeyComboAction::PreviousTab, Key::LeftArrow, &[Key::Alt])
+                .push(KeyComboAction::NextTab, Key::RightArrow, &[Key::Alt])
+                // Before Ctrl+E, which also matches while Shift is held.
+                .push(
+                    KeyComboAction::ExportRustSource,
+                    Key::E,
+                    &[Key::Control, Key::Shift],
+                )
+                .push(KeyComboAction::Export, Key::E, &[Key::Control])
+                .push(KeyComboAction::ImportSprites, Key::O, &[Key::Control])
+                .push(KeyComboAction::ToggleStats, Key::I, &[Key::Control])
+                .push(
+                    KeyComboAction::ToggleNotificationLog,
+                    Key::H,
+                    &[Key::Control],
+                )
+                // Before G, which also matches while Shift is held.
+                .push(
+                    KeyComboAction::SelectTool(GRADIENT_TOOL),
+                    Key::G,
+                    &[Key::Shift],
+                )
+                .push(KeyComboAction::GotoSprite, Key::G, &[])
+                // Before the ones without Shift, which also match while it's held.
+                .push(
+                    KeyComboAction::ShiftMap(ShiftDirection::Up, true),
+                    Key::UpArrow,
+                    &[Key::Control, Key::Shift],
+                )
+                .push(
+                    KeyComboAction::ShiftMap(ShiftDirection::Down, true),
+                    Key::DownArrow,
+                    &[Key::Control, Key::Shift],
+                )
+                .push(
+                    KeyComboAction::ShiftMap(ShiftDirection::Left, true),
+                    Key::LeftArrow,
+                    &[Key::Control, Key::Shift],
+                )
+                .push(
+                    KeyComboAction::ShiftMap(ShiftDirection::Right, true),
+                    Key::RightArrow,
+                    &[Key::Control, Key::Shift],
+                )
+                .push(
+                    KeyComboAction::ShiftMap(ShiftDirection::Up, false),
+                    Key::UpArrow,
+                    &[Key::Control],
+                )
+                .push(
+                    KeyComboAction::ShiftMap(ShiftDirection::Down, false),
+                    Key::DownArrow,
+                    &[Key::Control],
+                )
+                .push(
+                    KeyComboAction::ShiftMap(ShiftDirection::Left, false),
+                    Key::LeftArrow,
+                    &[Key::Control],
+                )
+                .push(
+                    KeyComboAction::ShiftMap(ShiftDirection::Right, false),
+                    Key::RightArrow,
+                    &[Key::Control],
+                ),
+            clipboard: Clipboard::new(),
+            commands: Commands::new(),
+            editor_sprites: load_editor_sprite_sheet(),
+            map_editor: map::Editor::new(),
+            sprite_editor,
+            sfx_editor: sfx::Editor::new(),
+            palette_editor: palette::Editor::new(),
+            brush_size: settings.brush_size,
+            dither: Dither::Off,
+            dither_button: button::State::new(),
+            selected_sprite: 0,
+            sprite_selection_end: None,
+            last_painted: None,
+            shade_ramp: settings.shade_ramp,
+            transparent_pages: settings.transparent_pages,
+            display_notes: settings.display_notes.clone(),
+            gradient: None,
+            shaded_pixels: HashSet::new(),
+            shift_held: false,
+            control_held: false,
+            alt_held: false,
+            stats: None,
+            notification_log_open: false,
+            notification_log_scroll: scroll_area::State::new(),
+            copy_log_button: button::State::new(),
+            settings_saver: Saver::new(settings),
+        }
     }
 }
 
 #[derive(Debug)]
 struct Clipboard {
-    data: Vec<Color>,
+    data: Sprite,
 }
 impl Clipboard {
     fn new() -> Self {
-        Self { data: vec![0; 64] }
+        Self {
+            data: Sprite::EMPTY,
+        }
     }
 
     fn copy_sprite(&mut self, sprite: &Sprite) {
-        self.data = sprite.to_owned();
+        self.data = *sprite;
     }
 
     fn paste_into(&self, sprite: &mut Sprite) {
-        for (sprite_pixel, clipboard_pixel) in sprite.iter_mut().zip(self.data.iter().copied()) {
-            *sprite_pixel = clipboard_pixel;
-        }
+        *sprite = self.data;
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_key_combo(
     key_combo: KeyComboAction,
     selected_sprite: usize,
+    selected_sprites: Range<usize>,
+    selected_sfx: usize,
     notification: &mut notification::State,
     clipboard: &mut Clipboard,
     resources: &mut Resources,
@@ -142,31 +815,37 @@ fn handle_key_combo(
 ) {
     match key_combo {
         KeyComboAction::Copy => {
-            let sprite = resources.sprite_sheet.get_sprite(selected_sprite);
-            notification.alert("COPIED 1 X 1 SPRITES".to_owned());
-            clipboard.copy_sprite(sprite);
+            if let Some(sprite) = resources.sprite_sheet.try_get_sprite(selected_sprite) {
+                notification.alert("COPIED 1 X 1 SPRITES".to_owned());
+                clipboard.copy_sprite(sprite);
+            }
         }
         KeyComboAction::Paste => {
-            let sprite = resources.sprite_sheet.get_sprite_mut(selected_sprite);
-            notification.alert("PASTED 1 X 1 SPRITES".to_owned());
-
-            clipboard.paste_into(sprite);
+            if let Some(sprite) = resources.sprite_sheet.try_get_sprite_mut(selected_sprite) {
+                notification.alert("PASTED 1 X 1 SPRITES".to_owned());
+                clipboard.paste_into(sprite);
+            }
         }
         KeyComboAction::FlipVertically => {
-            let sprite = resources.sprite_sheet.get_sprite_mut(selected_sprite);
-
-            sprite.flip_vertically()
+            if let Some(sprite) = resources.sprite_sheet.try_get_sprite_mut(selected_sprite) {
+                sprite.flip_vertically()
+            }
         }
         KeyComboAction::FlipHorizontally => {
-            let sprite = resources.sprite_sheet.get_sprite_mut(selected_sprite);
-
-            sprite.flip_horizontally()
+            if let Some(sprite) = resources.sprite_sheet.try_get_sprite_mut(selected_sprite) {
+                sprite.flip_horizontally()
+            }
+        }
+        KeyComboAction::Rotate => {
+            if let Some(sprite) = resources.sprite_sheet.try_get_sprite_mut(selected_sprite) {
+                sprite.rotate_clockwise()
+            }
         }
         KeyComboAction::Undo => {
-            commands.undo(notification, &mut resources.sprite_sheet);
+            commands.undo(notification, resources);
         }
         KeyComboAction::Redo => {
-            commands.redo(notification, &mut resources.sprite_sheet);
+            commands.redo(notification, resources);
         }
         KeyComboAction::Save => {
             save(notification, resources);
@@ -177,102 +856,340 @@ fn handle_key_combo(
         KeyComboAction::NextTab => {
             *tab = tab.next();
         }
+        KeyComboAction::Export => match *tab {
+            Tab::SfxEditor => export_sfx(selected_sfx, notification, resources),
+            Tab::SpriteEditor => export_sprites(selected_sprites, notification, resources),
+            Tab::MapEditor | Tab::PaletteEditor => {}
+        },
+        KeyComboAction::ImportSprites => {
+            if *tab == Tab::SpriteEditor {
+                import_sprites(selected_sprite, notification, resources, commands);
+            }
+        }
+        KeyComboAction::ExportRustSource => {
+            export_rust_source(notification, resources);
+        }
+        // Handled by the editor, it owns the stats page, the notification log and the sprite number field.
+        KeyComboAction::ToggleStats
+        | KeyComboAction::ToggleNotificationLog
+        | KeyComboAction::GotoSprite => {}
+        // Handled by the editor, they only apply to one tab.
+        KeyComboAction::ShiftMap(..)
+        | KeyComboAction::SelectTool(_)
+        | KeyComboAction::ApplyFlagsToSelection => {}
     }
 }
 
-fn save(notification: &mut notification::State, resources: &Resources) {
-    notification.alert("SAVED".to_owned());
+// Writes the sprite sheet, map and flags as Rust source, for games to embed them.
+fn export_rust_source(notification: &mut notification::State, resources: &Resources) {
+    let file_name = "assets.rs";
+
+    match std::fs::write(
+        format!("{}/{file_name}", resources.assets_path),
+        resources.to_rust_source(),
+    ) {
+        Ok(()) => notification.alert(format!("EXPORTED {}", file_name.to_uppercase())),
+        Err(error) => {
+            eprintln!("Couldn't export {file_name}: {error}");
+            notification.error("COULDN'T EXPORT ASSETS".to_owned());
+        }
+    }
+}
+
+// Renders the sfx to a WAV file in the assets directory.
+fn export_sfx(index: usize, notification: &mut notification::State, resources: &Resources) {
+    let Some(sfx) = resources.sound_effects.get(index) else {
+        return;
+    };
+    let file_name = format!("sfx_{index:0>2}.wav");
+
+    match audio::render_sfx_to_wav(sfx, format!("{}/{file_name}", resources.assets_path)) {
+        Ok(()) => notification.alert(format!("EXPORTED {}", file_name.to_uppercase())),
+        Err(error) => {
+            eprintln!("Couldn't export {file_name}: {error}");
+            notification.error("COULDN'T EXPORT SFX".to_owned());
+        }
+    }
+}
+
+// Where sprites are exported to and imported from, in the assets directory.
+const EXPORTS_DIRECTORY: &str = "exports";
+
+fn sprite_file_name(sprite: usize) -> String {
+    format!("sprite_{sprite:0>3}.txt")
+}
+
+// Writes the sprites to a file named after the first one, side by side.
+fn export_sprites(
+    sprites: Range<usize>,
+    notification: &mut notification::State,
+    resources: &Resources,
+) {
+    let file_name = sprite_file_name(sprites.start);
+    let sprites: Vec<Sprite> = sprites
+        .filter_map(|sprite| resources.sprite_sheet.try_get_sprite(sprite).copied())
+        .collect();
+    let directory = format!("{}/{EXPORTS_DIRECTORY}", resources.assets_path);
+
+    match std::fs::create_dir_all(&directory).and_then(|()| {
+        std::fs::write(
+            format!("{directory}/{file_name}"),
+            SpriteStrip::from_sprites(&sprites).to_text(),
+        )
+    }) {
+        Ok(()) => notification.alert(format!("EXPORTED {}", file_name.to_uppercase())),
+        Err(error) => {
+            eprintln!("Couldn't export {file_name}: {error}");
+            notification.error("COULDN'T EXPORT SPRITES".to_owned());
+        }
+    }
+}
+
+// Reads the file named after `first` over it, and over the sprites after it for wider files.
+// Rename a file exported from another game to import it into another slot.
+fn import_sprites(
+    first: usize,
+    notification: &mut notification::State,
+    resources: &mut Resources,
+    commands: &mut Commands,
+) {
+    let file_name = sprite_file_name(first);
+    let path = format!("{}/{EXPORTS_DIRECTORY}/{file_name}", resources.assets_path);
+
+    let strip = match std::fs::read_to_string(&path) {
+        Ok(text) => SpriteStrip::from_text(&text),
+        Err(error) => {
+            eprintln!("Couldn't read {path}: {error}");
+            notification.error(format!("NO {}", file_name.to_uppercase()));
+
+            return;
+        }
+    };
+    let strip = match strip {
+        Ok(strip) => strip,
+        Err(error) => {
+            eprintln!("Couldn't import {path}: {error}");
+            notification.error(format!("INVALID {}", file_name.to_uppercase()));
+
+            return;
+        }
+    };
+
+    let sprite_sheet = &mut resources.sprite_sheet;
+    let last = (first + strip.sprite_count()).min(sprite_sheet.sprite_count());
+    let previous: Vec<Sprite> = (first..last)
+        .filter_map(|sprite| sprite_sheet.try_get_sprite(sprite).copied())
+        .collect();
+    let mut new = previous.clone();
+    strip.paste_onto(&mut new);
+
+    for (sprite, &pixels) in (first..last).zip(&new) {
+        if let Some(sprite) = sprite_sheet.try_get_sprite_mut(sprite) {
+            *sprite = pixels;
+        }
+    }
+    commands.push(Command::sprites_changed(first, previous, new));
+    notification.alert(format!("IMPORTED {}", file_name.to_uppercase()));
+}
+
+// Adds text to the end of a file in the assets directory, creating it if needed.
+fn append_to_assets_file(file_name: &str, text: &str, resources: &Resources) -> io::Result<()> {
+    use std::io::Write;
+
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(format!("{}/{file_name}", resources.assets_path))
+        .and_then(|mut file| file.write_all(text.as_bytes()))
+}
+
+// Appends the notification history to a log file in the assets directory, newest first.
+fn copy_notification_log(notification: &mut notification::State, resources: &Resources) {
+    let file_name = "notifications.log";
+    let text = notification.history().text();
+
+    match append_to_assets_file(file_name, &text, resources) {
+        Ok(()) => notification.alert(format!("COPIED LOG TO {}", file_name.to_uppercase())),
+        Err(error) => {
+            eprintln!("Couldn't write {file_name}: {error}");
+            notification.error("COULDN'T COPY LOG".to_owned());
+        }
+    }
+}
+
+// Appends the `pal` calls for the sprite editor's remap rules to a file in the assets directory,
+// to paste into the game from there.
+fn copy_pal_remap(
+    pal_remap: PalRemap,
+    notification: &mut notification::State,
+    resources: &Resources,
+) {
+    let file_name = "pal_remap.log";
+    let code = pal_remap.to_code();
+    if code.is_empty() {
+        notification.alert("NO REMAP RULES TO COPY".to_owned());
+
+        return;
+    }
+
+    match append_to_assets_file(file_name, &code, resources) {
+        Ok(()) => notification.alert(format!("COPIED PAL() TO {}", file_name.to_uppercase())),
+        Err(error) => {
+            eprintln!("Couldn't write {file_name}: {error}");
+            notification.error("COULDN'T COPY REMAP".to_owned());
+        }
+    }
+}
+
+// Writes a file in the assets directory, replacing it.
+fn write_assets_file(
+    file_name: &str,
+    serializable: &impl Serialize,
+    resources: &Resources,
+) -> io::Result<()> {
+    serialize::write_file(
+        &format!("{}/{file_name}", resources.assets_path),
+        serializable,
+    )
+}
 
-    let map_ppm = Ppm::from_map(&resources.map, &resources.sprite_sheet);
-    let sprite_sheet_ppm = Ppm::from_sprite_sheet(&resources.sprite_sheet);
+fn save(notification: &mut notification::State, resources: &Resources) {
+    let map_ppm = Ppm::from_map(resources);
+    let sprite_sheet_ppm = Ppm::from_sprite_sheet(&resources.sprite_sheet, &resources.palette);
     let to_serialize: &[(&str, &dyn Serialize)] = &[
         (&Flags::file_name(), &resources.sprite_flags),
         (&SpriteSheet::file_name(), &resources.sprite_sheet),
         (&Map::file_name(), &resources.map),
+        (&SoundEffects::file_name(), &resources.sound_effects),
+        (&Music::file_name(), &resources.music),
+        (&Palette::file_name(), &resources.palette),
         ("map.ppm", &map_ppm),
         ("sprite_sheet.ppm", &sprite_sheet_ppm),
     ];
 
     for (name, serializable) in to_serialize.iter() {
-        serialize(&resources.assets_path, name, serializable);
+        if let Err(error) = write_assets_file(name, serializable, resources) {
+            eprintln!("Couldn't write {name}: {error}");
+            notification.error(format!("COULDN'T SAVE {}", name.to_uppercase()));
+
+            return;
+        }
     }
+    notification.alert("SAVED".to_owned());
 }
 
+// Top left corner of the map editor's view.
+const MAP_VIEW_X: i32 = 0;
+const MAP_VIEW_Y: i32 = 8;
+// Top of the sprite view, under the tools row. Its grid starts a pixel lower.
+const SPRITE_VIEW_Y: i32 = 87;
+
+// Index (in the tools row) of the pencil.
+const PENCIL_TOOL: usize = 0;
+const PENCIL_TOOL_SPRITE: usize = 15;
+// Index (in the tools row) of the fill tool.
+// TODO: Filling isn't implemented yet, the tool only changes the cursor for now.
+const FILL_TOOL: usize = 1;
+const FILL_TOOL_SPRITE: usize = 31;
+// Index (in the tools row) of the shade brush.
+const SHADE_TOOL: usize = 2;
+const SHADE_TOOL_SPRITE: usize = 47;
+// Index (in the tools row) of the gradient fill, dragging out a rectangle.
+const GRADIENT_TOOL: usize = 3;
+const GRADIENT_TOOL_SPRITE: usize = 63;
+
+// Sprite, and bottom bar text (with the hotkey), of each tool in the tools row.
+const TOOLS: [(usize, &str); 4] = [
+    (PENCIL_TOOL_SPRITE, "PENCIL (B)"),
+    (FILL_TOOL_SPRITE, "FILL (SHIFT+F)"),
+    (SHADE_TOOL_SPRITE, "SHADE, SHIFT: LIGHTER (D)"),
+    (GRADIENT_TOOL_SPRITE, "GRADIENT (SHIFT+G)"),
+];
+
 #[derive(Copy, Clone, Debug)]
 enum KeyComboAction {
     Copy,
     Paste,
     FlipVertically,
     FlipHorizontally,
+    Rotate,
     Undo,
     Redo,
     Save,
     PreviousTab,
     NextTab,
+    // The selected sfx in the sfx tab, the selected sprites in the sprite tab.
+    Export,
+    // Only in the sprite tab.
+    ImportSprites,
+    ExportRustSource,
+    ToggleStats,
+    ToggleNotificationLog,
+    GotoSprite,
+    // Only in the map tab, wrapping around if set.
+    ShiftMap(ShiftDirection, bool),
+    // Only in the sprite tab.
+    SelectTool(usize),
+    // Only in the sprite tab.
+    ApplyFlagsToSelection,
 }
 
-fn load_editor_sprite_sheet() -> Result<SpriteSheet, String> {
-    let editor_sprites = std::fs::read_to_string("./src/editor/sprite_sheet.txt")
-        .map_err(|_| "Couldn't find editor sprite sheet file.".to_owned())?;
-
-    SpriteSheet::deserialize(&editor_sprites)
-        .map_err(|_| "Couldn't parse editor sprite sheet.".to_owned())
+// The editor's icons, drawn with `spr_from` so that they don't depend on the game's sprites.
+fn load_editor_sprite_sheet() -> SpriteSheet {
+    SpriteSheet::deserialize(include_str!("editor_assets/sprite_sheet.txt"))
+        .expect("The editor's sprite sheet is built in")
 }
 
 impl ElmApp for Editor {
     type Msg = Msg;
 
     fn init() -> Self {
-        Self {
-            cursor: cursor::State::new(),
-            sprite_button_state: button::State::new(),
-            map_button_state: button::State::new(),
-            tab: Tab::SpriteEditor,
-            selected_sprite_page: 0,
-            tab_buttons: [
-                button::State::new(),
-                button::State::new(),
-                button::State::new(),
-                button::State::new(),
-            ],
-            sprite_buttons: vec![button::State::new(); 64],
-            selected_tool: 0,
-            tool_buttons: vec![button::State::new(); 2],
-            bottom_bar_text: "".to_owned(),
-            notification: notification::State::new(),
-            key_combos: KeyCombos::new()
-                .push(KeyComboAction::Copy, Key::C, &[Key::Control])
-                .push(KeyComboAction::Paste, Key::V, &[Key::Control])
-                .push(KeyComboAction::Undo, Key::Z, &[Key::Control])
-                .push(KeyComboAction::Redo, Key::Y, &[Key::Control])
-                .push(KeyComboAction::Save, Key::S, &[Key::Control])
-                .push(KeyComboAction::FlipVertically, Key::V, &[])
-                .push(KeyComboAction::FlipHorizontally, Key::F, &[])
-                .push(KeyComboAction::PreviousTab, Key::LeftArrow, &[Key::Alt])
-                .push(KeyComboAction::NextTab, Key::RightArrow, &[Key::Alt]),
-            clipboard: Clipboard::new(),
-            commands: Commands::new(),
-            editor_sprites: load_editor_sprite_sheet()
-                // TODO: Change this to actually crash if it failed.
-                .unwrap_or_else(|_| SpriteSheet::new()),
-            map_editor: map::Editor::new(),
-            sprite_editor: sprite::Editor::new(),
-            brush_size: BrushSize::tiny(),
-            selected_sprite: 0,
+        Self::with_settings(EditorSettings::new())
+    }
+
+    fn init_with_resources(resources: &mut Resources) -> Self {
+        let settings = EditorSettings::load(&resources.assets_path);
+        let (map_camera, map_sprites) = (settings.map_camera, settings.map_sprites);
+        let mut editor = Self::with_settings(settings);
+        editor
+            .map_editor
+            .restore_view_settings(map_camera, map_sprites, &resources.map);
+        // What was fixed in broken asset files, until they're saved again.
+        for warning in resources.asset_warnings.drain(..) {
+            editor.notification.warn(warning.to_uppercase());
         }
+
+        editor
     }
 
     fn update(&mut self, msg: &Msg, resources: &mut Resources) {
+        self.fit_selection(resources.sprite_sheet.sprite_count());
+
         match msg {
             &Msg::SpriteEditorMsg(sprite_msg) => {
                 self.sprite_editor.update(sprite_msg);
             }
             &Msg::MapEditorMsg(map_msg) => {
-                self.map_editor.update(map_msg);
+                self.map_editor
+                    .update(map_msg, resources, &mut self.commands);
+            }
+            &Msg::SfxEditorMsg(sfx_msg) => {
+                self.sfx_editor
+                    .update(sfx_msg, resources, &mut self.commands);
+            }
+            &Msg::PaletteEditorMsg(palette_msg) => {
+                self.palette_editor.update(palette_msg, resources);
             }
             &Msg::KeyboardEvent(event) => {
                 self.handle_key_combos(event, resources);
 
+                match event.key {
+                    Key::Shift => self.shift_held = event.state == KeyState::Down,
+                    Key::Control => self.control_held = event.state == KeyState::Down,
+                    Key::Alt => self.alt_held = event.state == KeyState::Down,
+                    _ => {}
+                }
+
                 match event {
                     KeyboardEvent {
                         key,
@@ -281,31 +1198,96 @@ impl ElmApp for Editor {
                         if let Some(shift_direction) = ShiftDirection::from_key(&key) {
                             self.shift_sprite(shift_direction, &mut resources.sprite_sheet)
                         }
+                        if let Some(flag) = key.digit().filter(|&digit| digit < 8) {
+                            self.toggle_hovered_flag(flag, resources);
+                        }
                     }
                     _ => {}
                 }
             }
+            Msg::NotificationLogCopied => {
+                copy_notification_log(&mut self.notification, resources);
+            }
+            Msg::PalRemapCopied => {
+                copy_pal_remap(
+                    self.sprite_editor.pal_remap(),
+                    &mut self.notification,
+                    resources,
+                );
+            }
+            Msg::Tick => {
+                self.save_settings(resources, false);
+            }
             Msg::SpriteTabClicked => {
-                self.tab = Tab::SpriteEditor;
+                self.switch_tab(Tab::SpriteEditor);
                 println!("Sprite button clicked");
             }
             Msg::MapButtonClicked => {
-                self.tab = Tab::MapEditor;
+                self.switch_tab(Tab::MapEditor);
                 println!("Map button clicked");
             }
+            Msg::SfxButtonClicked => {
+                self.switch_tab(Tab::SfxEditor);
+            }
+            Msg::PaletteButtonClicked => {
+                self.switch_tab(Tab::PaletteEditor);
+            }
             Msg::SpritePageSelected(selected_sprite_page) => {
                 self.selected_sprite_page = *selected_sprite_page;
             }
-            Msg::SpriteButtonClicked(selected_sprite) => {
-                self.selected_sprite = *selected_sprite;
+            &Msg::SpritePageTransparencyToggled(page) => {
+                self.transparent_pages.toggle(page);
             }
-            Msg::FlagHovered { bit_number } => {
-                self.bottom_bar_text = format!("FLAG {} (0X{:X})", bit_number, 1 << bit_number);
+            &Msg::ExtendedColorChosen(extended) => {
+                let (color, _) = self.sprite_editor.colors();
+                self.display_notes
+                    .toggle(self.selected_sprite, color, extended);
+
+                let shown_as =
+                    match self.display_notes.get(self.selected_sprite)[color.index() as usize] {
+                        Some(extended) => (display_palette::FIRST_EXTENDED + extended).to_string(),
+                        None => "ITSELF".to_owned(),
+                    };
+                self.notification.alert(format!(
+                    "SPR {:0>3}: {color} SHOWN AS {shown_as}",
+                    self.selected_sprite
+                ));
             }
-            Msg::FlagToggled(flag_index) => {
-                let flag_index = *flag_index;
-
-                let flag_value = resources
+            &Msg::SpriteButtonClicked(sprite) => {
+                if self.shift_held {
+                    self.sprite_selection_end = Some(sprite);
+                } else {
+                    self.selected_sprite = sprite;
+                    self.sprite_selection_end = None;
+                }
+            }
+            &Msg::SpriteGridKeyPressed(key) => {
+                let pages = resources.sprite_sheet.sprite_count() / SPRITES_PER_PAGE;
+                // From the page that's showing, even if the selected sprite is on another one.
+                let from =
+                    if sprite_index::page_of(self.selected_sprite) == self.selected_sprite_page {
+                        self.selected_sprite
+                    } else {
+                        sprite_index::index(self.selected_sprite_page, 0)
+                    };
+
+                if let Some(sprite) = sprite_index::step(from, key, pages) {
+                    self.select_sprite(sprite);
+                }
+            }
+            &Msg::SpriteDropped { from, to } => {
+                if from != to {
+                    resources.sprite_sheet.swap_sprites(from, to);
+                    resources.sprite_flags.swap(from, to);
+                    self.select_sprite(to);
+                    self.notification
+                        .alert(format!("SWAPPED SPRITES {} AND {}", from, to));
+                }
+            }
+            Msg::FlagToggled(flag_index) => {
+                let flag_index = *flag_index;
+
+                let flag_value = resources
                     .sprite_flags
                     .fget_n(self.selected_sprite, flag_index as u8);
                 resources
@@ -313,156 +1295,468 @@ impl ElmApp for Editor {
                     .fset(self.selected_sprite, flag_index, !flag_value);
             }
             &Msg::SpriteEdited { x, y, color } => {
-                let sprite = resources.sprite_sheet.get_sprite_mut(self.selected_sprite);
-                let x = x as isize;
-                let y = y as isize;
-                let previous_color = sprite.pget(x, y);
-
-                self.commands.push(Command::pixel_changed(
-                    self.selected_sprite,
-                    x,
-                    y,
-                    previous_color,
-                    color,
-                ));
+                let (x, y) = (x as isize, y as isize);
+
+                if self.selected_tool == SHADE_TOOL {
+                    self.shade(&mut resources.sprite_sheet, x, y);
+                } else if self.selected_tool == GRADIENT_TOOL {
+                    let Some(sprite) = resources
+                        .sprite_sheet
+                        .try_get_sprite_mut(self.selected_sprite)
+                    else {
+                        return;
+                    };
+                    let (start, mut gradient) = *self.gradient.get_or_insert(((x, y), *sprite));
+
+                    dither::fill_gradient(
+                        &mut gradient,
+                        start,
+                        (x, y),
+                        self.sprite_editor.colors(),
+                        self.shift_held,
+                    );
+                    *sprite = gradient;
+                } else if self.shift_held && self.selected_tool == PENCIL_TOOL {
+                    // Nothing to draw a segment from yet.
+                    let Some((from_x, from_y)) = self.last_painted else {
+                        return;
+                    };
+
+                    for (x, y) in draw::line(from_x as i32, from_y as i32, x as i32, y as i32) {
+                        self.paint(&mut resources.sprite_sheet, x as isize, y as isize, color);
+                    }
+                } else {
+                    self.paint(&mut resources.sprite_sheet, x, y, color);
+                }
 
-                for (x, y) in self
-                    .brush_size
-                    .iter()
-                    .map(|(local_x, local_y)| (local_x + x, local_y + y))
-                {
-                    sprite.pset(x, y, color);
+                self.last_painted = Some((x, y));
+            }
+            Msg::StrokeEnded => {
+                self.shaded_pixels.clear();
+
+                let gradient = self.gradient.take().zip(
+                    resources
+                        .sprite_sheet
+                        .try_get_sprite(self.selected_sprite)
+                        .copied(),
+                );
+                if let Some(((_, previous), new)) = gradient {
+                    self.commands.push(Command::sprite_changed(
+                        self.selected_sprite,
+                        previous,
+                        new,
+                    ));
                 }
             }
             &Msg::ToolSelected(selected_tool) => {
-                self.selected_tool = selected_tool;
+                self.select_tool(selected_tool);
+            }
+            &Msg::HelpShown(help) => {
+                self.help = Some(help);
             }
-            &Msg::ColorHovered(color) => {
-                self.bottom_bar_text = format!("COLOUR {}", color);
+            &Msg::HelpHidden(help) => {
+                // The next element's help may already be shown, when moving between neighbours.
+                if self.help == Some(help) {
+                    self.help = None;
+                }
             }
 
             &Msg::ClickedMapTile { x, y } => {
-                resources.map.mset(x, y, self.selected_sprite as u8);
+                // Alt+click picks the tile, for mice without a middle button.
+                if self.alt_held {
+                    self.pick_map_tile(x, y, resources);
+
+                    return;
+                }
+                if self
+                    .map_editor
+                    .tile_pressed(x, y, self.shift_held, self.control_held)
+                {
+                    return;
+                }
+
+                if !self.map_editor.place_tile(x, y, resources) {
+                    self.help = Some(Help::NoBlock(self.map_editor.tile().into()));
+                }
+            }
+            Msg::MapTilePicked => {
+                if let Some((x, y)) = self.map_tile_under_cursor(resources) {
+                    self.pick_map_tile(x, y, resources);
+                }
             }
             &Msg::BrushSizeSelected(brush_size) => {
                 self.brush_size = brush_size;
-                self.bottom_bar_text =
-                    format!("BRUSH SIZE: {}", self.brush_size.to_human_readable());
             }
-            &Msg::BrushSizeSliderHovered => {
-                self.bottom_bar_text =
-                    format!("BRUSH SIZE: {}", self.brush_size.to_human_readable());
+            Msg::GotoSpriteOpened => {
+                self.goto_sprite_input.set_text(String::new());
+                self.goto_sprite_input.focus();
+                // The field can take the focused sprite's place in the focus order,
+                // which would hand the focus back to the sprite.
+                for button in &mut self.sprite_buttons {
+                    button.blur();
+                }
+            }
+            Msg::GotoSpriteSubmitted => {
+                // Nothing typed just closes the field.
+                if let Ok(sprite) = self.goto_sprite_input.text().parse::<usize>() {
+                    self.select_sprite(sprite.min(resources.sprite_sheet.sprite_count() - 1));
+                }
+                self.goto_sprite_input.blur();
+            }
+            Msg::DitherToggled => {
+                self.dither = self.dither.next();
             }
         }
+
+        self.move_sprite_view_focus();
     }
 
     fn view(&mut self, resources: &Resources) -> Element<'_, Msg> {
         const BACKGROUND: u8 = 5;
 
-        Tree::new()
+        // The game can change the sheet's size (in `init`) after the editor is created.
+        let pages = resources.sprite_sheet.sprite_count() / SPRITES_PER_PAGE;
+        self.tab_buttons.resize(pages, button::State::new());
+        self.fit_selection(resources.sprite_sheet.sprite_count());
+
+        let cursor_kind = self.cursor_kind(resources);
+        let selected_sprites = self.selected_sprites();
+        let help_text = self.bottom_bar_text.get(
+            (
+                self.help,
+                self.brush_size,
+                self.dither,
+                self.transparent_pages,
+            ),
+            |&(help, brush_size, dither, transparent_pages)| {
+                bottom_bar_text(&help_text(help, brush_size, dither, transparent_pages))
+            },
+        );
+
+        // The map tab picks the tile to place with its own selection and messages,
+        // so that map work doesn't move the sprite tab's selection.
+        // Map cells are bytes, the tiles are only on the first pages.
+        let (sprite, page, page_count, on_select, on_page): (_, _, _, fn(_) -> _, fn(_) -> _) =
+            match self.tab {
+                Tab::MapEditor => (
+                    self.map_editor.tile() as usize,
+                    self.map_editor.tile_page(),
+                    pages.min(256 / SPRITES_PER_PAGE),
+                    |sprite| Msg::MapEditorMsg(map::Msg::TileSelected(sprite)),
+                    |page| Msg::MapEditorMsg(map::Msg::TilePageSelected(page)),
+                ),
+                _ => (
+                    self.selected_sprite,
+                    self.selected_sprite_page,
+                    pages,
+                    Msg::SpriteButtonClicked,
+                    Msg::SpritePageSelected,
+                ),
+            };
+        // In block mode, the whole block that gets stamped.
+        let stamps_block = self.tab == Tab::MapEditor
+            && self.map_editor.stamps_blocks()
+            && self.map_editor.block(&resources.sprite_sheet).is_some();
+        let selection_size = if stamps_block { 16 } else { 8 };
+        let selection = match self.tab {
+            Tab::MapEditor => sprite..sprite + 1,
+            _ => selected_sprites,
+        };
+
+        // At most: the background, top bar, tab, tools, sprite view, dither toggle,
+        // stats, notification log, bottom bar, notification and cursor.
+        let tree = Tree::with_capacity(11)
             .push(DrawFn::new(|draw| {
                 draw.rectfill(0, 0, 127, 127, BACKGROUND)
             }))
             .push(top_bar(
                 &mut self.sprite_button_state,
                 &mut self.map_button_state,
+                &mut self.sfx_button_state,
+                &mut self.palette_button_state,
                 self.tab,
-            ))
-            .push(match self.tab {
-                Tab::SpriteEditor => {
-                    let selected_sprite_flags =
-                        resources.sprite_flags.get(self.selected_sprite).unwrap();
-                    let selected_sprite = resources.sprite_sheet.get_sprite(self.selected_sprite);
+                &self.editor_sprites,
+            ));
+
+        let tree = match self.tab {
+            Tab::SpriteEditor => {
+                let selected_sprite_flags = resources.fget(self.selected_sprite);
+                let selected_sprite = resources.sprite_sheet.get_sprite(self.selected_sprite);
 
+                tree.push(
                     self.sprite_editor.view(
+                        self.selected_sprite,
                         selected_sprite_flags,
                         selected_sprite,
+                        self.transparent_pages.contains_sprite(self.selected_sprite),
+                        self.display_notes.get(self.selected_sprite),
                         &self.editor_sprites,
-                        self.brush_size,
+                        // Only the pencil and the shade brush have a brush.
+                        [PENCIL_TOOL, SHADE_TOOL]
+                            .contains(&self.selected_tool)
+                            .then_some(self.brush_size),
+                        resources,
                         &Msg::SpriteEditorMsg,
-                    )
-                }
-                Tab::MapEditor => Tree::new()
-                    .push(self.map_editor.view(
-                        &resources.map,
-                        0,
-                        8,
-                        &|x, y| Msg::ClickedMapTile { x, y },
-                        &Msg::MapEditorMsg,
-                    ))
-                    .into(),
-            })
-            .push(tools_row(
+                    ),
+                )
+            }
+            Tab::MapEditor => tree.push(self.map_editor.view(
+                resources,
+                MAP_VIEW_X,
+                MAP_VIEW_Y,
+                self.cursor.position(),
+                self.transparent_pages,
+                &|x, y| Msg::ClickedMapTile { x, y },
+                Msg::MapEditorMsg,
+            )),
+            Tab::SfxEditor => tree.push(self.sfx_editor.view(
+                resources,
+                &self.editor_sprites,
+                &Msg::SfxEditorMsg,
+            )),
+            Tab::PaletteEditor => {
+                tree.push(self.palette_editor.view(resources, &Msg::PaletteEditorMsg))
+            }
+        };
+
+        // The sfx editor has its own slot selector instead of the sprite sheet.
+        let tree = if self.tab == Tab::SfxEditor {
+            tree
+        } else {
+            tree.push(tools_row(
                 76,
-                self.selected_sprite,
-                self.selected_sprite_page,
-                &mut self.tab_buttons,
+                sprite,
+                self.sprite_number_text
+                    .get(sprite, |sprite| format!("{sprite:0>3}")),
+                page,
+                &mut self.tab_buttons[..page_count],
+                on_page,
+                self.transparent_pages,
                 self.selected_tool,
                 &mut self.tool_buttons,
+                &mut self.goto_sprite_button,
+                &mut self.goto_sprite_input,
+                &self.editor_sprites,
             ))
             .push(sprite_view(
-                self.selected_sprite,
-                self.selected_sprite_page,
+                sprite,
+                selection_size,
+                selection,
+                page,
+                self.transparent_pages.contains_page(page),
+                on_select,
+                // The map tab picks its tile with the mouse only.
+                (self.tab != Tab::MapEditor).then_some(Msg::SpriteGridKeyPressed as fn(_) -> _),
                 &mut self.sprite_buttons,
-                87,
+                &mut self.sprite_drag_states,
+                SPRITE_VIEW_Y,
+            ))
+        };
+
+        // Only the pencil dithers.
+        let tree = if self.tab == Tab::SpriteEditor && self.selected_tool == PENCIL_TOOL {
+            tree.push(dither_toggle(27, 78, self.dither, &mut self.dither_button))
+        } else {
+            tree
+        };
+
+        let tree = match &self.stats {
+            Some(stats) => tree.push(stats.view()),
+            None => tree,
+        };
+
+        let (popup, history) = self.notification.split();
+        let tree = if self.notification_log_open {
+            tree.push(notification::log_view(
+                history,
+                &mut self.notification_log_scroll,
+                &mut self.copy_log_button,
+                Msg::NotificationLogCopied,
             ))
-            .push(bottom_bar(&self.bottom_bar_text))
-            .push(Cursor::new(&mut self.cursor))
-            .push(Notification::new(&mut self.notification))
+        } else {
+            tree
+        };
+
+        tree.push(bottom_bar(help_text))
+            .push_to(Layer::Overlay, Notification::new(popup))
+            .push_to(
+                Layer::Cursor,
+                Cursor::new(&mut self.cursor).kind(cursor_kind),
+            )
             .into()
     }
 
-    fn subscriptions(&self, event: &Event) -> Vec<Msg> {
-        match event {
+    fn subscriptions(&self, event: &Event, msgs: &mut Vec<Msg>) {
+        msgs.extend(match event {
+            Event::Mouse(MouseEvent::Down(MouseButton::Middle)) if self.tab == Tab::MapEditor => {
+                Some(Msg::MapTilePicked)
+            }
+            Event::Mouse(MouseEvent::Up(MouseButton::Left | MouseButton::Right)) => {
+                Some(Msg::StrokeEnded)
+            }
             Event::Mouse(_) => None,
             Event::Keyboard(event) => Some(Msg::KeyboardEvent(*event)),
-            Event::Tick { .. } => None,
-        }
-        .into_iter()
-        .chain(match self.tab {
-            Tab::MapEditor => map::Editor::subscriptions(event)
-                .map(Msg::MapEditorMsg)
-                .into_iter(),
-            _ => None.into_iter(),
-        })
-        .collect()
+            Event::Character(_) | Event::EditorOpened | Event::EditorClosed => None,
+            Event::Tick { .. } => Some(Msg::Tick),
+        });
+        msgs.extend(match self.tab {
+            Tab::MapEditor => map::Editor::subscriptions(event).map(Msg::MapEditorMsg),
+            Tab::SfxEditor => sfx::Editor::subscriptions(event).map(Msg::SfxEditorMsg),
+            _ => None,
+        });
     }
 }
 
 fn top_bar<'a>(
     sprite_button_state: &'a mut button::State,
     map_button_state: &'a mut button::State,
+    sfx_button_state: &'a mut button::State,
+    palette_button_state: &'a mut button::State,
     tab: Tab,
+    editor_sprites: &'a SpriteSheet,
 ) -> Element<'a, Msg> {
     Tree::new()
         .push(DrawFn::new(|draw| {
             draw.rectfill(0, 0, 127, 7, 8);
         }))
-        .push(sprite_editor_button(sprite_button_state, tab))
-        .push(map_editor_button(map_button_state, tab))
+        .push(with_help(
+            palette_editor_button(palette_button_state, tab),
+            (94, 0, 8, 8),
+            Help::Text("PALETTE EDITOR (ALT+LEFT/RIGHT)"),
+        ))
+        .push(with_help(
+            sprite_editor_button(sprite_button_state, tab, editor_sprites),
+            (102, 0, 8, 8),
+            Help::Text("SPRITE EDITOR (ALT+LEFT/RIGHT)"),
+        ))
+        .push(with_help(
+            map_editor_button(map_button_state, tab, editor_sprites),
+            (110, 0, 8, 8),
+            Help::Text("MAP EDITOR (ALT+LEFT/RIGHT)"),
+        ))
+        .push(with_help(
+            sfx_editor_button(sfx_button_state, tab),
+            (118, 0, 8, 8),
+            Help::Text("SFX EDITOR (ALT+LEFT/RIGHT)"),
+        ))
         .into()
 }
 
-fn sprite_editor_button(state: &mut button::State, tab: Tab) -> Element<'_, Msg> {
+fn sprite_editor_button<'a>(
+    state: &'a mut button::State,
+    tab: Tab,
+    editor_sprites: &'a SpriteSheet,
+) -> Element<'a, Msg> {
     let selected = tab == Tab::SpriteEditor;
 
-    editor_button(state, 63, 110, 0, Msg::SpriteTabClicked, selected)
+    editor_button(
+        state,
+        editor_sprites,
+        63,
+        102,
+        0,
+        Msg::SpriteTabClicked,
+        selected,
+    )
 }
 
-fn map_editor_button(state: &mut button::State, tab: Tab) -> Element<'_, Msg> {
+fn map_editor_button<'a>(
+    state: &'a mut button::State,
+    tab: Tab,
+    editor_sprites: &'a SpriteSheet,
+) -> Element<'a, Msg> {
     let selected = tab == Tab::MapEditor;
 
-    editor_button(state, 62, 118, 0, Msg::MapButtonClicked, selected)
+    editor_button(
+        state,
+        editor_sprites,
+        62,
+        110,
+        0,
+        Msg::MapButtonClicked,
+        selected,
+    )
+}
+
+// The editor's sprite sheet has no icon for it, so it's a note drawn by hand.
+fn sfx_editor_button(state: &mut button::State, tab: Tab) -> Element<'_, Msg> {
+    let selected = tab == Tab::SfxEditor;
+
+    Button::new(
+        118,
+        0,
+        8,
+        8,
+        Some(Msg::SfxButtonClicked),
+        state,
+        DrawFn::new(move |draw| {
+            let color = if selected { 15 } else { 2 };
+
+            draw.line(4, 1, 4, 5, color);
+            draw.line(5, 1, 6, 2, color);
+            draw.rectfill(2, 5, 3, 6, color);
+        }),
+    )
+    .focusable()
+    .into()
+}
+
+// Four squares, drawn by hand like the sfx tab's note.
+fn palette_editor_button(state: &mut button::State, tab: Tab) -> Element<'_, Msg> {
+    let selected = tab == Tab::PaletteEditor;
+
+    Button::new(
+        94,
+        0,
+        8,
+        8,
+        Some(Msg::PaletteButtonClicked),
+        state,
+        DrawFn::new(move |draw| {
+            let color = if selected { 15 } else { 2 };
+
+            for (x, y) in [(1, 1), (5, 1), (1, 5), (5, 5)] {
+                draw.rectfill(x, y, x + 1, y + 1, color);
+            }
+        }),
+    )
+    .focusable()
+    .into()
+}
+
+// Next to the tools, a small checkerboard that's lit while dithering.
+fn dither_toggle(x: i32, y: i32, dither: Dither, state: &mut button::State) -> Element<'_, Msg> {
+    let color = if dither == Dither::Off { 13 } else { 7 };
+
+    let button = Button::new(
+        x,
+        y,
+        8,
+        8,
+        Some(Msg::DitherToggled),
+        state,
+        DrawFn::new(move |draw| {
+            for (x, y) in (1..7).flat_map(|y| (1..7).map(move |x| (x, y))) {
+                if (x + y) % 2 == 0 {
+                    draw.pset(x, y, color);
+                }
+            }
+        }),
+    )
+    .focusable();
+
+    with_help(button, (x, y, 8, 8), Help::Dither)
 }
 
-fn editor_button(
-    state: &mut button::State,
+fn editor_button<'a>(
+    state: &'a mut button::State,
+    editor_sprites: &'a SpriteSheet,
     sprite: usize,
     x: i32,
     y: i32,
     msg: Msg,
     selected: bool,
-) -> Element<'_, Msg> {
+) -> Element<'a, Msg> {
     Button::new(
         x,
         y,
@@ -470,151 +1764,289 @@ fn editor_button(
         8,
         Some(msg),
         state,
-        DrawFn::new(move |draw| {
+        DrawFn::with_interaction(move |draw, interaction| {
             let color = if selected { 15 } else { 2 };
+            // Pushed in while held down.
+            let y = if interaction.pressed { 1 } else { 0 };
 
-            draw.pal(15, color);
-            draw.spr(sprite, 0, 0);
-            draw.pal(15, 15);
+            draw.with_saved_state(|draw| {
+                draw.pal(15, color);
+                draw.spr_from(editor_sprites, sprite, 0, y);
+            });
         }),
     )
+    .focusable()
     .into()
 }
 
+#[allow(clippy::too_many_arguments)]
 fn tools_row<'a>(
     y: i32,
     sprite: usize,
+    // `sprite`, as three digits.
+    sprite_number: &'a str,
     selected_tab: usize,
     tab_buttons: &'a mut [button::State],
+    on_page: fn(usize) -> Msg,
+    transparent_pages: TransparentPages,
     selected_tool: usize,
     tool_buttons: &'a mut [button::State],
+    goto_sprite_button: &'a mut button::State,
+    goto_sprite_input: &'a mut text_input::State,
+    editor_sprites: &'a SpriteSheet,
 ) -> Element<'a, Msg> {
-    let mut children = vec![DrawFn::new(move |draw| {
-        const HEIGHT: i32 = 11;
-        draw.rectfill(0, y, 127, y + HEIGHT - 1, 5)
-    })
-    .into()];
-
-    const TOOLS: &[usize] = &[15, 31];
+    // The background, tools, pages, sprite preview and sprite number.
+    let mut children = Vec::with_capacity(tool_buttons.len() + tab_buttons.len() + 3);
+    children.push(
+        DrawFn::new(move |draw| {
+            const HEIGHT: i32 = 11;
+            draw.rectfill(0, y, 127, y + HEIGHT - 1, 5)
+        })
+        .into(),
+    );
 
     for (tool_index, tool_button) in tool_buttons.iter_mut().enumerate() {
-        let spr = TOOLS[tool_index];
+        let spr = TOOLS[tool_index].0;
 
         let x = (9 + 8 * tool_index) as i32;
         let y = y + 2;
-        children.push(
-            Button::new(
-                x,
-                y,
-                8,
-                8,
-                Some(Msg::ToolSelected(tool_index)),
-                tool_button,
-                DrawFn::new(move |draw| {
-                    draw.palt(Some(0));
+        let button = Button::new(
+            x,
+            y,
+            8,
+            8,
+            Some(Msg::ToolSelected(tool_index)),
+            tool_button,
+            DrawFn::new(move |draw| {
+                draw.with_saved_state(|draw| {
+                    draw.palt(Some(Color::BLACK));
                     if selected_tool == tool_index {
                         draw.pal(13, 7);
                     }
-                    draw.spr(spr, 0, 0);
-                    draw.pal(13, 13);
-                }),
-            )
-            .into(),
-        );
+                    draw.spr_from(editor_sprites, spr, 0, 0);
+                });
+            }),
+        )
+        .focusable();
+        children.push(with_help(
+            button,
+            (x, y, 8, 8),
+            Help::Text(TOOLS[tool_index].1),
+        ));
     }
 
-    for (sprite_tab, tab_button_state) in tab_buttons.iter_mut().enumerate() {
-        let base_sprite = if selected_tab == sprite_tab { 33 } else { 17 };
-
-        let x = 96 + sprite_tab as i32 * 8;
-
-        children.push(
-            Button::new(
-                x,
-                y + 3,
-                8,
-                8,
-                Some(Msg::SpritePageSelected(sprite_tab)),
-                tab_button_state,
-                DrawFn::new(move |draw| {
-                    draw.palt(Some(0));
-                    draw.spr(base_sprite + sprite_tab, 0, 0);
-                }),
-            )
-            .into(),
-        );
-    }
+    children.extend(page_buttons(
+        96,
+        y + 3,
+        selected_tab,
+        tab_buttons,
+        editor_sprites,
+        Some(transparent_pages),
+        on_page,
+    ));
 
     const X: i32 = 70;
     let sprite_preview = DrawFn::new(move |draw| {
         draw.palt(None);
         draw.spr(sprite, X, y + 2);
-        draw.palt(Some(0));
+        draw.palt(Some(Color::BLACK));
     });
     children.push(sprite_preview.into());
 
-    let spr_str = format!("{:0>3}", sprite);
-    let sprite_number = DrawFn::new(move |draw| {
-        let y = y + 2;
-        draw.rectfill(X + 9, y + 1, X + 9 + 13 - 1, y + 7, 6);
-        draw.print(&spr_str, X + 10, y + 2, 13);
-    })
-    .into();
-    children.push(sprite_number);
+    // Monospaced, so that the box doesn't change size while scrolling through sprites.
+    let font = Font::builtin_monospaced();
+    let (text_width, text_height) = font.measure(sprite_number);
+    let sprite_number: Element<'a, Msg> = if goto_sprite_input.is_focused() {
+        TextInput::new(X + 9, y + 3, text_width + 4, goto_sprite_input)
+            .numeric()
+            .max_length(3)
+            .on_submit(Msg::GotoSpriteSubmitted)
+            .into()
+    } else {
+        Button::new(
+            X + 9,
+            y + 3,
+            text_width + 1,
+            text_height + 1,
+            Some(Msg::GotoSpriteOpened),
+            goto_sprite_button,
+            DrawFn::new(move |draw| {
+                // One pixel of padding on each side.
+                draw.rectfill(0, 0, text_width, text_height, 6);
+                draw.print_with(font, sprite_number, 1, 1, 13);
+            }),
+        )
+        .into()
+    };
+    // The same help on both, so that it doesn't flicker when the field opens.
+    children.push(with_help(
+        sprite_number,
+        (X + 9, y + 3, text_width + 4, text_height + 1),
+        Help::Text("GO TO SPRITE (G)"),
+    ));
 
     Tree::with_children(children).into()
 }
 
-/// The 4 rows of sprites at the bottom of the sprite editor
-fn sprite_view(
+/// Buttons to pick a page, used for sprites and sfx.
+/// Up to 4 pages get an 8x8 button each, more than that are squeezed into the same width.
+/// Sprite pages (with `transparent_pages`) are also right clicked to toggle their transparency,
+/// transparent ones are underlined with a checkerboard.
+fn page_buttons<'a>(
+    x: i32,
+    y: i32,
+    selected_page: usize,
+    buttons: &'a mut [button::State],
+    editor_sprites: &'a SpriteSheet,
+    transparent_pages: Option<TransparentPages>,
+    on_press: impl Fn(usize) -> Msg + 'a,
+) -> impl Iterator<Item = Element<'a, Msg>> {
+    const MAX_WIDE_BUTTONS: usize = 4;
+    let width = 8 * MAX_WIDE_BUTTONS.min(buttons.len()) as i32 / buttons.len() as i32;
+    let compact = buttons.len() > MAX_WIDE_BUTTONS;
+
+    buttons.iter_mut().enumerate().map(move |(page, state)| {
+        let selected = selected_page == page;
+        let content: Element<'a, Msg> = if compact {
+            // Just the page number, on the same colors as the icons.
+            let background = if selected { 7 } else { 6 };
+            DrawFn::new(move |draw| {
+                draw.rectfill(0, 0, width - 2, 6, background);
+                draw.print(&page.to_string(), 0, 1, 13);
+            })
+            .into()
+        } else {
+            let base_sprite = if selected { 33 } else { 17 };
+            DrawFn::new(move |draw| {
+                draw.palt(Some(Color::BLACK));
+                draw.spr_from(editor_sprites, base_sprite + page, 0, 0);
+            })
+            .into()
+        };
+
+        let transparent = transparent_pages.is_some_and(|pages| pages.contains_page(page));
+        let content = Tree::new().push(content).push(DrawFn::new(move |draw| {
+            // Along the bottom edge, under the page number.
+            if transparent {
+                transparency::checkerboard(draw, 0, 7, width - 1, 1, 1);
+            }
+        }));
+
+        let x = x + page as i32 * width;
+        let button = Button::new(x, y, width, 8, Some(on_press(page)), state, content).focusable();
+        match transparent_pages {
+            Some(_) => with_help(
+                button.on_right_press(Msg::SpritePageTransparencyToggled(page)),
+                (x, y, width, 8),
+                Help::SpritePage(page),
+            ),
+            None => with_help(button, (x, y, width, 8), Help::Page(page)),
+        }
+    })
+}
+
+/// The slot that takes the focus for the whole sprite view:
+/// the selected sprite's, or the first one when it's on another page.
+fn sprite_view_focus_slot(selected_sprite: usize, page: usize) -> usize {
+    if sprite_index::page_of(selected_sprite) == page {
+        sprite_index::slot_in_page(selected_sprite)
+    } else {
+        0
+    }
+}
+
+/// The 4 rows of sprites at the bottom of the sprite editor.
+/// Sprites can be dragged onto other slots to swap them.
+/// The sprites of `selected_tab`, with the selected sprite highlighted
+/// (by a `selection_size` pixels square, 16 for 2x2 blocks) and the rest of `selection` outlined.
+#[allow(clippy::too_many_arguments)]
+fn sprite_view<'a>(
     selected_sprite: usize,
+    selection_size: i32,
+    selection: Range<usize>,
     selected_tab: usize,
-    sprite_buttons: &mut [button::State],
+    // Whether color 0 is transparent on the page, drawn over a checkerboard.
+    transparent: bool,
+    on_select: fn(usize) -> Msg,
+    // Keys pressed while the view has the focus, `None` makes it unreachable with Tab.
+    on_key: Option<fn(Key) -> Msg>,
+    sprite_buttons: &'a mut [button::State],
+    drag_states: &'a mut [drag::State],
     y: i32,
-) -> Element<'_, Msg> {
-    let mut children = vec![DrawFn::new(move |draw| {
-        draw.palt(None);
-        draw.rectfill(0, y, 127, y + 32 + 1, 0);
-    })
-    .into()];
+) -> Element<'a, Msg> {
+    // The background, the slots, the selection's outline and the selected sprite's highlight.
+    let mut children = Vec::with_capacity(sprite_buttons.len() + 3);
+    children.push(
+        DrawFn::new(move |draw| {
+            draw.palt(None);
+            draw.rectfill(0, y, 127, y + 32 + 1, 0);
+        })
+        .into(),
+    );
 
-    let sprite_position = |sprite| {
-        let index = sprite % 64;
-        let row = (index / 16) as i32;
-        let col = (index % 16) as i32;
+    let sprite_position = move |sprite| {
+        let (x, grid_y) = sprite_index::position_in_grid(sprite);
 
-        (col * 8, y + 1 + row * 8)
+        (x, y + 1 + grid_y)
     };
 
-    for (index, sprite_state) in sprite_buttons.iter_mut().enumerate() {
-        let sprite = index + selected_tab * 64;
+    let focus_slot = sprite_view_focus_slot(selected_sprite, selected_tab);
+
+    let slots = sprite_buttons.iter_mut().zip(drag_states.iter_mut());
+    for (index, (sprite_state, drag_state)) in slots.enumerate() {
+        let sprite = sprite_index::index(selected_tab, index);
 
         let (x, y) = sprite_position(sprite);
-        children.push(
-            Button::new(
-                x,
-                y,
-                8,
-                8,
-                Some(Msg::SpriteButtonClicked(sprite)),
-                sprite_state,
-                DrawFn::new(move |draw| {
+        let mut button = Button::new(
+            x,
+            y,
+            8,
+            8,
+            Some(on_select(sprite)),
+            sprite_state,
+            DrawFn::new(move |draw| {
+                if transparent {
+                    transparency::checkerboard(draw, 0, 0, 8, 8, 2);
+                    draw.palt(Some(Color::BLACK));
+                } else {
                     draw.palt(None);
-                    draw.spr(sprite, 0, 0);
-                }),
-            )
-            .event_on_press()
+                }
+                draw.spr(sprite, 0, 0);
+            }),
+        )
+        .event_on_press();
+        if let Some(on_key) = on_key.filter(|_| index == focus_slot) {
+            button = button.focusable().on_key_press(on_key);
+        }
+        let draggable = Draggable::new(button, x, y, 8, 8, sprite, drag_state).ghost_sprite(sprite);
+        let drop_target = DropTarget::new(draggable, x, y, 8, 8, move |from| Msg::SpriteDropped {
+            from,
+            to: sprite,
+        });
+
+        children.push(with_help(drop_target, (x, y, 8, 8), Help::Sprite(sprite)));
+    }
+
+    if selection.len() > 1 {
+        children.push(
+            DrawFn::new(move |draw| {
+                for sprite in selection.clone() {
+                    if sprite_index::page_of(sprite) == selected_tab {
+                        let (x, y) = sprite_position(sprite);
+                        draw.rect(x - 1, y - 1, x + 8, y + 8, 6);
+                    }
+                }
+            })
             .into(),
         );
     }
 
-    // Draw selected sprite highlight
-    {
-        // TODO: Fix (wrong highlight when switching pages)
+    // Only highlighted on its own page.
+    if sprite_index::page_of(selected_sprite) == selected_tab {
         let (x, y) = sprite_position(selected_sprite);
         children.push(
             DrawFn::new(move |draw| {
-                draw.rect(x - 1, y - 1, x + 8, y + 8, 7);
+                draw.rect(x - 1, y - 1, x + selection_size, y + selection_size, 7);
             })
             .into(),
         )
@@ -623,42 +2055,27 @@ fn sprite_view(
     Tree::with_children(children).into()
 }
 
+const BOTTOM_BAR_WIDTH: i32 = 128;
+
+/// Long messages get cut instead of running off the screen.
+fn bottom_bar_text(text: &str) -> String {
+    truncate(text, BOTTOM_BAR_WIDTH - 1)
+}
+
+/// Shows `text`, cut with [`bottom_bar_text`].
 fn bottom_bar(text: &str) -> Element<'_, Msg> {
     const X: i32 = 0;
     const Y: i32 = 121;
-    const BAR_WIDTH: i32 = 128;
     const BAR_HEIGHT: i32 = 7;
 
-    Tree::new()
+    Tree::with_capacity(2)
         .push(DrawFn::new(|draw| {
-            draw.rectfill(X, Y, X + BAR_WIDTH - 1, Y + BAR_HEIGHT - 1, 8)
+            draw.rectfill(X, Y, X + BOTTOM_BAR_WIDTH - 1, Y + BAR_HEIGHT - 1, 8)
         }))
-        .push(Text::new(text, X + 1, Y + 1, 2))
+        .push(DrawFn::new(move |draw| draw.print(text, X + 1, Y + 1, 2)))
         .into()
 }
 
-pub(crate) static MOUSE_SPRITE: &[Color] = &[
-    0, 0, 0, 0, 0, 0, 0, 0, //
-    0, 0, 0, 1, 0, 0, 0, 0, //
-    0, 0, 1, 7, 1, 0, 0, 0, //
-    0, 0, 1, 7, 7, 1, 0, 0, //
-    0, 0, 1, 7, 7, 7, 1, 0, //
-    0, 0, 1, 7, 7, 7, 7, 1, //
-    0, 0, 1, 7, 7, 1, 1, 0, //
-    0, 0, 0, 1, 1, 7, 1, 0, //
-];
-
-// static MOUSE_TARGET_SPRITE: &[Color] = &[
-//     0, 0, 0, 1, 0, 0, 0, 0, //
-//     0, 0, 1, 7, 1, 0, 0, 0, //
-//     0, 1, 0, 0, 0, 1, 0, 0, //
-//     1, 7, 0, 0, 0, 7, 1, 0, //
-//     0, 1, 0, 0, 0, 1, 0, 0, //
-//     0, 0, 1, 7, 1, 0, 0, 0, //
-//     0, 0, 0, 1, 0, 0, 0, 0, //
-//     0, 0, 0, 0, 0, 0, 0, 0, //
-// ];
-
 #[derive(Clone, Copy, Debug)]
 enum ShiftDirection {
     Up,
@@ -679,12 +2096,1322 @@ impl ShiftDirection {
             _ => None,
         }
     }
+    fn name(self) -> &'static str {
+        match self {
+            ShiftDirection::Up => "UP",
+            ShiftDirection::Down => "DOWN",
+            ShiftDirection::Left => "LEFT",
+            ShiftDirection::Right => "RIGHT",
+        }
+    }
+
+    // One cell in this direction.
+    fn offset(self) -> (i32, i32) {
+        match self {
+            ShiftDirection::Up => (0, -1),
+            ShiftDirection::Down => (0, 1),
+            ShiftDirection::Left => (-1, 0),
+            ShiftDirection::Right => (1, 0),
+        }
+    }
+
     fn shift(&self, sprite: &mut Sprite) {
         match self {
-            ShiftDirection::Up => sprite.shift_up(),
-            ShiftDirection::Down => sprite.shift_down(),
-            ShiftDirection::Left => sprite.shift_left(),
-            ShiftDirection::Right => sprite.shift_right(),
+            ShiftDirection::Up => sprite.shift_up_wrapped(1),
+            ShiftDirection::Down => sprite.shift_down_wrapped(1),
+            ShiftDirection::Left => sprite.shift_left_wrapped(1),
+            ShiftDirection::Right => sprite.shift_right_wrapped(1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::draw_data::DrawData;
+    use crate::runtime::state::State;
+    use crate::serialize::{to_string, Recovery};
+    use crate::ui::testing::{click, count_allocations, mouse_move, tick, Harness};
+    use crate::Pico8;
+
+    type SpriteViewState = (Vec<button::State>, Vec<drag::State>);
+
+    impl Editor {
+        fn help_text(&self) -> String {
+            help_text(
+                self.help,
+                self.brush_size,
+                self.dither,
+                self.transparent_pages,
+            )
+        }
+    }
+
+    fn sprite_view_with(states: &mut SpriteViewState) -> Element<'_, Msg> {
+        let (buttons, drag_states) = states;
+
+        sprite_view(
+            3,
+            8,
+            3..4,
+            0,
+            false,
+            Msg::SpriteButtonClicked,
+            None,
+            buttons,
+            drag_states,
+            87,
+        )
+    }
+
+    #[test]
+    fn sprite_view_selects_and_highlights() {
+        let mut harness = Harness::new();
+        let mut states = (vec![button::State::new(); 64], vec![drag::State::new(); 64]);
+
+        // Sprite 21 is on the second row, sixth column.
+        let msgs = harness.run(&mut states, sprite_view_with, click(42, 98));
+        assert!(matches!(msgs[..], [Msg::SpriteButtonClicked(21)]));
+
+        // Sprite 3 is the selected one.
+        assert_eq!(harness.pixel(23, 87), Color::WHITE);
+        assert_eq!(harness.pixel(40, 100), Color::BLACK);
+    }
+
+    #[test]
+    fn sprite_view_highlights_on_the_selected_sprites_page() {
+        let mut harness = Harness::new();
+        let mut states = (vec![button::State::new(); 64], vec![drag::State::new(); 64]);
+
+        // Sprite 255 is the bottom right slot of the last page.
+        let msgs = harness.run(
+            &mut states,
+            |(buttons, drag_states)| {
+                sprite_view(
+                    255,
+                    8,
+                    255..256,
+                    3,
+                    false,
+                    Msg::SpriteButtonClicked,
+                    None,
+                    buttons,
+                    drag_states,
+                    87,
+                )
+            },
+            click(124, 115),
+        );
+        assert!(matches!(msgs[..], [Msg::SpriteButtonClicked(255)]));
+        assert_eq!(harness.pixel(119, 111), Color::WHITE);
+
+        // The same slot on another page isn't highlighted.
+        harness.run(
+            &mut states,
+            |(buttons, drag_states)| {
+                sprite_view(
+                    255,
+                    8,
+                    255..256,
+                    0,
+                    false,
+                    Msg::SpriteButtonClicked,
+                    None,
+                    buttons,
+                    drag_states,
+                    87,
+                )
+            },
+            [tick()],
+        );
+        assert_eq!(harness.pixel(119, 111), Color::BLACK);
+    }
+
+    #[test]
+    fn page_buttons_squeeze_in_more_pages() {
+        let mut harness = Harness::new();
+        let mut state = (vec![button::State::new(); 8], load_editor_sprite_sheet());
+
+        let msgs = harness.run(
+            &mut state,
+            |(buttons, editor_sprites)| {
+                let buttons = page_buttons(
+                    96,
+                    0,
+                    7,
+                    buttons,
+                    editor_sprites,
+                    None,
+                    Msg::SpritePageSelected,
+                );
+
+                Tree::with_children(buttons.collect()).into()
+            },
+            click(125, 3),
+        );
+
+        assert!(matches!(msgs[..], [Msg::SpritePageSelected(7)]));
+        // The selected page is the light one.
+        assert_eq!(harness.pixel(124, 6), Color::WHITE);
+        assert_eq!(harness.pixel(96, 6), Color::LIGHT_GREY);
+    }
+
+    #[test]
+    fn the_last_sprite_can_be_edited_saved_and_drawn() {
+        let mut resources = Resources::empty();
+        let mut editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+
+        for msg in [
+            Msg::SpritePageSelected(3),
+            Msg::SpriteButtonClicked(255),
+            Msg::SpriteEdited {
+                x: 7,
+                y: 7,
+                color: Color::PINK,
+            },
+            Msg::FlagToggled(5),
+        ] {
+            <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
+        }
+
+        // Saved and loaded back, like the sprite sheet and flags files.
+        resources.sprite_sheet =
+            SpriteSheet::deserialize(&to_string(&resources.sprite_sheet)).unwrap();
+        resources.sprite_flags = Flags::deserialize(&to_string(&resources.sprite_flags)).unwrap();
+        assert_eq!(resources.sget(127, 127), Color::PINK);
+        assert_eq!(resources.fget(255), 1 << 5);
+
+        let mut pico8 = Pico8::new(DrawData::new(), State::new(), resources);
+        pico8.spr(255, 10, 20);
+        assert_eq!(pico8.draw_data.pixel(17, 27), Some(Color::PINK));
+        assert_eq!(pico8.draw_data.pixel(16, 27), Some(Color::BLACK));
+    }
+
+    #[test]
+    fn bottom_bar_shows_the_hovered_elements_help() {
+        let mut resources = Resources::empty();
+        let mut editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        let mut update = |editor: &mut Editor, msg| {
+            <Editor as ElmApp>::update(editor, &msg, &mut resources);
+        };
+
+        let pixel = Help::Pixel { x: 5, y: 2 };
+        update(&mut editor, Msg::HelpShown(pixel));
+        assert_eq!(editor.help_text(), "X 5 Y 2");
+
+        // Entering the next pixel can come before leaving this one.
+        let next_pixel = Help::Pixel { x: 6, y: 2 };
+        update(&mut editor, Msg::HelpShown(next_pixel));
+        update(&mut editor, Msg::HelpHidden(pixel));
+        assert_eq!(editor.help_text(), "X 6 Y 2");
+
+        update(&mut editor, Msg::HelpHidden(next_pixel));
+        assert_eq!(editor.help_text(), "");
+
+        // Over the map tab's button.
+        let mut state = (editor, resources);
+        send_events(
+            &mut Harness::new(),
+            &mut state,
+            [mouse_move(113, 3), tick()],
+        );
+        assert_eq!(state.0.help_text(), "MAP EDITOR (ALT+LEFT/RIGHT)");
+    }
+
+    fn painted_pixels(resources: &Resources, sprite: usize) -> Vec<(usize, usize)> {
+        resources
+            .sprite_sheet
+            .get_sprite(sprite)
+            .iter_pixels()
+            .filter(|&(.., color)| color == Color::RED)
+            .map(|(x, y, _)| (x, y))
+            .collect()
+    }
+
+    #[test]
+    fn shift_click_paints_a_segment_from_the_last_pixel() {
+        let mut resources = Resources::empty();
+        let mut editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        let shift = |state| {
+            Msg::KeyboardEvent(KeyboardEvent {
+                key: Key::Shift,
+                state,
+            })
+        };
+        let paint = |x, y| Msg::SpriteEdited {
+            x,
+            y,
+            color: Color::RED,
+        };
+
+        // Without a pixel to start from, shift-clicking does nothing.
+        for msg in [shift(KeyState::Down), paint(6, 2), shift(KeyState::Up)] {
+            <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
+        }
+        assert_eq!(painted_pixels(&resources, 0), []);
+
+        for msg in [
+            paint(0, 0),
+            shift(KeyState::Down),
+            paint(6, 2),
+            shift(KeyState::Up),
+        ] {
+            <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
+        }
+        assert_eq!(
+            painted_pixels(&resources, 0),
+            [(0, 0), (1, 0), (2, 1), (3, 1), (4, 1), (5, 2), (6, 2)]
+        );
+
+        // Undoing goes back through the segment's pixels.
+        for _ in 0..6 {
+            editor
+                .commands
+                .undo(&mut editor.notification, &mut resources);
+        }
+        assert_eq!(painted_pixels(&resources, 0), [(0, 0)]);
+
+        // Other tools don't draw segments.
+        editor.selected_tool = FILL_TOOL;
+        for msg in [shift(KeyState::Down), paint(3, 7)] {
+            <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
+        }
+        assert_eq!(painted_pixels(&resources, 0), [(0, 0), (3, 7)]);
+    }
+
+    #[test]
+    fn dithering_mixes_the_selected_colors() {
+        let mut resources = Resources::empty();
+        let mut editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        editor.brush_size = BrushSize::large();
+        for msg in [
+            Msg::SpriteEditorMsg(sprite::Msg::ColorSelected(Color::RED)),
+            Msg::SpriteEditorMsg(sprite::Msg::SecondaryColorSelected(Color::BLUE)),
+            Msg::DitherToggled,
+        ] {
+            <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
+        }
+        assert_eq!(editor.dither, Dither::Checkerboard);
+
+        // Two overlapping strokes, still one checkerboard.
+        for (x, y) in [(2, 2), (3, 2)] {
+            let msg = Msg::SpriteEdited {
+                x,
+                y,
+                color: Color::RED,
+            };
+            <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
+        }
+        let sprite = resources.sprite_sheet.get_sprite(0);
+        for (x, y, color) in sprite.iter_pixels().filter(|&(x, y, _)| x < 5 && y < 5) {
+            let expected = if (x + y) % 2 == 0 {
+                Color::RED
+            } else {
+                Color::BLUE
+            };
+            assert_eq!(color, expected, "{x}, {y}");
+        }
+        assert_eq!(sprite.pget(5, 2), Color::BLUE);
+        assert_eq!(sprite.pget(6, 2), Color::BLACK);
+
+        // Painting with the secondary color swaps them.
+        let msg = Msg::SpriteEdited {
+            x: 2,
+            y: 2,
+            color: Color::BLUE,
+        };
+        <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
+        let sprite = resources.sprite_sheet.get_sprite(0);
+        assert_eq!(sprite.pget(2, 2), Color::BLUE);
+        assert_eq!(sprite.pget(2, 3), Color::RED);
+
+        // Undo restores the dithered color, not the solid one.
+        editor
+            .commands
+            .undo(&mut editor.notification, &mut resources);
+        assert_eq!(resources.sprite_sheet.get_sprite(0).pget(2, 2), Color::RED);
+    }
+
+    // Sends each event to the editor's view, updating it with the messages it sends.
+    fn send_events(
+        harness: &mut Harness,
+        (editor, resources): &mut (Editor, Resources),
+        events: impl IntoIterator<Item = Event>,
+    ) {
+        for event in events {
+            let msgs = harness.step(<Editor as ElmApp>::view(editor, resources), event);
+            for msg in msgs {
+                <Editor as ElmApp>::update(editor, &msg, resources);
+            }
+        }
+    }
+
+    // Sends `event` through the subscriptions, like the runtime does after the view got it.
+    fn subscribe((editor, resources): &mut (Editor, Resources), event: Event) {
+        let mut msgs = vec![];
+        <Editor as ElmApp>::subscriptions(editor, &event, &mut msgs);
+        for msg in msgs {
+            <Editor as ElmApp>::update(editor, &msg, resources);
+        }
+    }
+
+    fn typed(text: &str) -> Vec<Event> {
+        text.chars().map(Event::Character).collect()
+    }
+
+    fn key(key: Key, state: KeyState) -> Event {
+        Event::Keyboard(KeyboardEvent { key, state })
+    }
+
+    #[test]
+    fn typing_a_sprite_number_goes_to_it() {
+        let mut resources = Resources::empty();
+        let editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        let mut state = (editor, resources);
+        let mut harness = Harness::new();
+
+        // Clicking the sprite number, letters can't be typed.
+        send_events(&mut harness, &mut state, click(81, 81));
+        assert!(state.0.goto_sprite_input.is_focused());
+        send_events(&mut harness, &mut state, typed("g2x03"));
+        send_events(&mut harness, &mut state, [key(Key::Enter, KeyState::Down)]);
+        assert_eq!(state.0.selected_sprite, 203);
+        assert_eq!(state.0.selected_sprite_page, 3);
+        assert!(!state.0.goto_sprite_input.is_focused());
+
+        // Pressing G, out of range numbers go to the last sprite.
+        for state_ in [KeyState::Down, KeyState::Up] {
+            let event = KeyboardEvent {
+                key: Key::G,
+                state: state_,
+            };
+            <Editor as ElmApp>::update(&mut state.0, &Msg::KeyboardEvent(event), &mut state.1);
+        }
+        send_events(&mut harness, &mut state, typed("999"));
+        send_events(&mut harness, &mut state, [key(Key::Enter, KeyState::Down)]);
+        assert_eq!(state.0.selected_sprite, 255);
+
+        // Escape closes the field without going anywhere.
+        send_events(&mut harness, &mut state, click(81, 81));
+        send_events(&mut harness, &mut state, typed("12"));
+        send_events(
+            &mut harness,
+            &mut state,
+            [key(Key::Escape, KeyState::Down), tick()],
+        );
+        assert!(!state.0.goto_sprite_input.is_focused());
+        assert_eq!(state.0.selected_sprite, 255);
+    }
+
+    #[test]
+    fn selected_sprites_get_the_selected_sprites_flags() {
+        let mut resources = Resources::empty();
+        resources.fset_all(3, 0b101);
+        resources.fset_all(20, 0b1);
+        let editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        let mut state = (editor, resources);
+        let mut harness = Harness::new();
+        let key_event = |(editor, resources): &mut (Editor, Resources), key, state| {
+            let event = KeyboardEvent { key, state };
+            <Editor as ElmApp>::update(editor, &Msg::KeyboardEvent(event), resources);
+        };
+
+        // From sprite 3 to sprite 18, on the next row.
+        send_events(&mut harness, &mut state, click(27, 91));
+        key_event(&mut state, Key::Shift, KeyState::Down);
+        send_events(&mut harness, &mut state, click(19, 99));
+        key_event(&mut state, Key::Shift, KeyState::Up);
+        send_events(&mut harness, &mut state, [tick()]);
+        assert_eq!(state.0.selected_sprite, 3);
+        assert_eq!(state.0.selected_sprites(), 3..19);
+        // Outlined around sprite 10.
+        assert_eq!(harness.pixel(79, 90), Color::LIGHT_GREY);
+
+        key_event(&mut state, Key::Control, KeyState::Down);
+        key_event(&mut state, Key::F, KeyState::Down);
+        key_event(&mut state, Key::F, KeyState::Up);
+        key_event(&mut state, Key::Control, KeyState::Up);
+        assert!((3..19).all(|sprite| state.1.fget(sprite) == 0b101));
+        assert_eq!(state.1.fget(2), 0);
+        assert_eq!(state.1.fget(19), 0);
+        assert_eq!(state.1.fget(20), 0b1);
+        assert_eq!(
+            state.0.notification.content(),
+            "FLAGS OF SPR 003 SET ON 16 SPRITES"
+        );
+
+        // A plain click selects a single sprite again.
+        send_events(&mut harness, &mut state, click(43, 91));
+        assert_eq!(state.0.selected_sprites(), 5..6);
+    }
+
+    #[test]
+    fn number_keys_toggle_the_hovered_sprites_flags() {
+        let mut resources = Resources::empty();
+        let editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        let mut state = (editor, resources);
+        let mut harness = Harness::new();
+        let press = |(editor, resources): &mut (Editor, Resources), key| {
+            let event = KeyboardEvent {
+                key,
+                state: KeyState::Down,
+            };
+            <Editor as ElmApp>::update(editor, &Msg::KeyboardEvent(event), resources);
+        };
+
+        // Over sprite 21, in the sprite view.
+        send_events(&mut harness, &mut state, [mouse_move(42, 98), tick()]);
+        press(&mut state, Key::Digit1);
+        assert_eq!(state.1.fget(21), 0b10);
+        assert_eq!(state.0.help_text(), "SPR 021 FLAG 1 -> ON");
+        // It doesn't get selected.
+        assert_eq!(state.0.selected_sprite, 0);
+
+        press(&mut state, Key::Digit1);
+        assert_eq!(state.1.fget(21), 0);
+        assert_eq!(state.0.help_text(), "SPR 021 FLAG 1 -> OFF");
+
+        // There's no flag 8, and nothing is toggled away from the sprite view.
+        press(&mut state, Key::Digit8);
+        send_events(&mut harness, &mut state, [mouse_move(42, 60), tick()]);
+        press(&mut state, Key::Digit1);
+        assert_eq!(state.1.fget(21), 0);
+
+        // The map tab's picker is on its own page, the same slot is sprite 85 on the second one.
+        let (editor, resources) = &mut state;
+        editor.tab = Tab::MapEditor;
+        <Editor as ElmApp>::update(
+            editor,
+            &Msg::MapEditorMsg(map::Msg::TilePageSelected(1)),
+            resources,
+        );
+        send_events(&mut harness, &mut state, [mouse_move(42, 98), tick()]);
+        press(&mut state, Key::Digit2);
+        assert_eq!(state.1.fget(85), 0b100);
+        assert_eq!(state.1.fget(21), 0);
+    }
+
+    #[test]
+    fn extended_colors_only_change_how_the_sprite_is_shown() {
+        let mut resources = Resources::empty();
+        let editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        let mut state = (editor, resources);
+        let mut harness = Harness::new();
+
+        // Red, then the canvas' top left pixel.
+        send_events(&mut harness, &mut state, click(84, 35));
+        send_events(&mut harness, &mut state, click(10, 12));
+        // The extended page, then 129.
+        send_events(&mut harness, &mut state, click(124, 14));
+        send_events(&mut harness, &mut state, [mouse_move(94, 15), tick()]);
+        assert_eq!(state.0.help_text(), "COLOUR 129: SHOW PRIMARY AS IT");
+        send_events(&mut harness, &mut state, click(94, 15));
+        assert_eq!(state.0.notification.content(), "SPR 000: 8 SHOWN AS 129");
+
+        send_events(&mut harness, &mut state, [tick()]);
+        assert_eq!(harness.pixel(10, 12), Color::DARK_BLUE);
+        assert_eq!(state.1.sprite_sheet.get_sprite(0).pget(0, 0), Color::RED);
+        assert_eq!(state.0.settings().display_notes.get(0)[8], Some(1));
+
+        // Again, and it's shown as itself.
+        send_events(&mut harness, &mut state, click(94, 15));
+        assert_eq!(state.0.notification.content(), "SPR 000: 8 SHOWN AS ITSELF");
+        send_events(&mut harness, &mut state, [tick()]);
+        assert_eq!(harness.pixel(10, 12), Color::RED);
+    }
+
+    #[test]
+    fn arrows_move_through_the_focused_sprite_view() {
+        let mut resources = Resources::empty();
+        let editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        let mut state = (editor, resources);
+        let mut harness = Harness::new();
+        let press = |harness: &mut Harness, state: &mut (Editor, Resources), pressed| {
+            send_events(
+                harness,
+                state,
+                [
+                    key(pressed, KeyState::Down),
+                    key(pressed, KeyState::Up),
+                    tick(),
+                ],
+            );
+        };
+
+        send_events(&mut harness, &mut state, [tick()]);
+        let mut tabs = 0;
+        while !state.0.sprite_buttons[0].is_focused() {
+            press(&mut harness, &mut state, Key::Tab);
+            tabs += 1;
+            assert!(tabs < 100, "the sprite view never got the focus");
+        }
+
+        press(&mut harness, &mut state, Key::RightArrow);
+        press(&mut harness, &mut state, Key::DownArrow);
+        assert_eq!(state.0.selected_sprite, 17);
+        // The focus follows the selection.
+        assert!(state.0.sprite_buttons[17].is_focused());
+
+        // Down from the bottom row, onto page 1.
+        for _ in 0..3 {
+            press(&mut harness, &mut state, Key::DownArrow);
+        }
+        assert_eq!(state.0.selected_sprite, 65);
+        assert_eq!(state.0.selected_sprite_page, 1);
+        press(&mut harness, &mut state, Key::PageDown);
+        assert_eq!(state.0.selected_sprite, 129);
+        assert_eq!(state.0.selected_sprite_page, 2);
+        // Highlighted like a clicked sprite.
+        assert_eq!(harness.pixel(7, 87), Color::WHITE);
+
+        // Still focused after clicking another sprite, from which the arrows go on.
+        send_events(&mut harness, &mut state, click(41, 89));
+        press(&mut harness, &mut state, Key::RightArrow);
+        assert_eq!(state.0.selected_sprite, 134);
+
+        // Nothing moves while typing a sprite number.
+        <Editor as ElmApp>::update(&mut state.0, &Msg::GotoSpriteOpened, &mut state.1);
+        press(&mut harness, &mut state, Key::LeftArrow);
+        assert!(state.0.goto_sprite_input.is_focused());
+        assert_eq!(state.0.selected_sprite, 134);
+    }
+
+    #[test]
+    fn the_map_tab_has_no_sprite_view_navigation() {
+        let mut resources = Resources::empty();
+        let editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        let mut state = (editor, resources);
+        let mut harness = Harness::new();
+
+        <Editor as ElmApp>::update(&mut state.0, &Msg::MapButtonClicked, &mut state.1);
+        for _ in 0..40 {
+            let events = [Key::Tab, Key::RightArrow]
+                .into_iter()
+                .flat_map(|pressed| [key(pressed, KeyState::Down), key(pressed, KeyState::Up)]);
+            send_events(&mut harness, &mut state, events);
+        }
+
+        assert_eq!(state.0.map_editor.tile(), 0);
+        assert_eq!(state.0.selected_sprite, 0);
+    }
+
+    #[test]
+    fn a_smaller_sheet_moves_the_selection_back_into_it() {
+        let mut resources = Resources::empty();
+        resources.set_sprite_sheet_size(crate::SpriteSheetSize::Double);
+        let editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        let mut state = (editor, resources);
+        let mut harness = Harness::new();
+        let ctrl = |state: &mut (Editor, Resources), pressed| {
+            for event in [
+                key(Key::Control, KeyState::Down),
+                key(pressed, KeyState::Down),
+                key(pressed, KeyState::Up),
+                key(Key::Control, KeyState::Up),
+            ] {
+                subscribe(state, event);
+            }
+        };
+        let red = Msg::SpriteEditorMsg(sprite::Msg::ColorSelected(Color::RED));
+        <Editor as ElmApp>::update(&mut state.0, &red, &mut state.1);
+
+        // Painting on sprite 400, and keeping it selected.
+        send_events(&mut harness, &mut state, click(81, 81));
+        send_events(&mut harness, &mut state, typed("400"));
+        send_events(&mut harness, &mut state, [key(Key::Enter, KeyState::Down)]);
+        send_events(&mut harness, &mut state, click(12, 14));
+        assert_eq!(state.1.sprite_sheet.get_sprite(400).pget(0, 0), Color::RED);
+
+        // A short file, padded to a Pico8 sized sheet.
+        let mut warnings = vec![];
+        state.1.sprite_sheet = SpriteSheet::deserialize_with(
+            "0000\n1111",
+            &mut Recovery::PadAndTruncate(&mut warnings),
+        )
+        .unwrap();
+        assert_eq!(state.1.sprite_sheet.sprite_count(), 256);
+
+        send_events(&mut harness, &mut state, [tick()]);
+        assert_eq!(state.0.selected_sprite, 255);
+        assert_eq!(state.0.selected_sprite_page, 3);
+
+        // Undoing the paint on sprite 400 leaves the sheet alone.
+        ctrl(&mut state, Key::Z);
+        assert_eq!(state.1.sprite_sheet.get_sprite(255), &Sprite::EMPTY);
+
+        // Everything else goes to the last sprite.
+        for pressed in [Key::C, Key::V, Key::Y] {
+            ctrl(&mut state, pressed);
+        }
+        send_events(&mut harness, &mut state, click(20, 14));
+        send_events(&mut harness, &mut state, click(1, 89));
+        send_events(&mut harness, &mut state, [tick()]);
+        assert_eq!(state.1.sprite_sheet.get_sprite(255).pget(1, 0), Color::RED);
+        assert_eq!(state.0.selected_sprite, 192);
+    }
+
+    #[test]
+    fn right_clicking_a_page_makes_its_black_transparent() {
+        let mut resources = Resources::empty();
+        let editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        let mut state = (editor, resources);
+        let mut harness = Harness::new();
+        let right_click = |x, y| {
+            [
+                mouse_move(x, y),
+                Event::Mouse(MouseEvent::Down(MouseButton::Right)),
+                Event::Mouse(MouseEvent::Up(MouseButton::Right)),
+                tick(),
+            ]
+        };
+
+        // Page 1's tab, it doesn't get selected.
+        send_events(&mut harness, &mut state, right_click(105, 82));
+        assert!(state.0.transparent_pages.contains_page(1));
+        assert_eq!(state.0.selected_sprite_page, 0);
+        assert_eq!(state.0.help_text(), "PAGE 1, 0 TRANSPARENT (RMB)");
+        assert_eq!(
+            state.0.settings().transparent_pages,
+            state.0.transparent_pages
+        );
+        // Underlined with a checkerboard.
+        assert_eq!(harness.pixel(104, 86), Color::DARK_BLUE);
+        assert_eq!(harness.pixel(112, 86), Color::LAVENDER);
+
+        // Sprite 64 (all black) is drawn over a checkerboard in the sprite view and the canvas.
+        send_events(&mut harness, &mut state, click(105, 82));
+        send_events(&mut harness, &mut state, click(1, 89));
+        send_events(&mut harness, &mut state, [tick()]);
+        assert_eq!(state.0.selected_sprite, 64);
+        assert_eq!(harness.pixel(16, 88), Color::DARK_BLUE);
+        assert_eq!(harness.pixel(18, 88), Color::BLACK);
+        assert_eq!(harness.pixel(8, 11), Color::DARK_BLUE);
+        assert_eq!(harness.pixel(12, 11), Color::BLACK);
+
+        // And back to opaque.
+        send_events(&mut harness, &mut state, right_click(105, 82));
+        assert_eq!(state.0.help_text(), "PAGE 1, 0 OPAQUE (RMB)");
+        assert_eq!(harness.pixel(16, 88), Color::BLACK);
+        assert_eq!(harness.pixel(8, 11), Color::BLACK);
+    }
+
+    #[test]
+    fn ctrl_arrows_shift_the_map_in_the_map_tab() {
+        let mut resources = Resources::empty();
+        resources.map = Map::with_size(16, 16);
+        resources.mset(0, 0, 5);
+        let mut editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        let press = |editor: &mut Editor, resources: &mut Resources, keys: &[Key]| {
+            for &state in &[KeyState::Down, KeyState::Up] {
+                for &key in keys {
+                    let msg = Msg::KeyboardEvent(KeyboardEvent { key, state });
+                    <Editor as ElmApp>::update(editor, &msg, resources);
+                }
+            }
+        };
+
+        // Only in the map tab.
+        editor.tab = Tab::SpriteEditor;
+        press(
+            &mut editor,
+            &mut resources,
+            &[Key::Control, Key::RightArrow],
+        );
+        assert_eq!(resources.mget(0, 0), 5);
+
+        editor.tab = Tab::MapEditor;
+        press(
+            &mut editor,
+            &mut resources,
+            &[Key::Control, Key::RightArrow],
+        );
+        assert_eq!((resources.mget(0, 0), resources.mget(1, 0)), (0, 5));
+        assert_eq!(editor.notification.content(), "MAP SHIFTED RIGHT");
+
+        press(
+            &mut editor,
+            &mut resources,
+            &[Key::Control, Key::Shift, Key::UpArrow],
+        );
+        assert_eq!(resources.mget(1, 15), 5);
+    }
+
+    #[test]
+    fn failed_saves_are_reported() {
+        let mut resources = Resources::empty();
+        resources.assets_path = "this/directory/does/not/exist".to_owned();
+        let mut notification = notification::State::new();
+
+        save(&mut notification, &resources);
+        assert_eq!(notification.content(), "COULDN'T SAVE SPRITE_FLAGS.TXT");
+    }
+
+    #[test]
+    fn settings_changed_right_before_closing_are_saved() {
+        let dir = std::env::temp_dir().join(format!("runty8-editor-exit-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut resources = Resources::empty();
+        resources.assets_path = dir.to_str().unwrap().to_owned();
+        let mut editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+
+        editor.switch_tab(Tab::MapEditor);
+        <Editor as ElmApp>::update(&mut editor, &Msg::Tick, &mut resources);
+        assert!(!dir.join(EditorSettings::file_name()).exists());
+
+        editor.save_settings(&resources, true);
+        assert_eq!(
+            EditorSettings::load(&resources.assets_path).tab,
+            Tab::MapEditor
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn sprites_go_through_their_own_files() {
+        let dir = std::env::temp_dir().join(format!("runty8-sprite-files-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let exports = dir.join(EXPORTS_DIRECTORY);
+        let mut resources = Resources::empty();
+        resources.assets_path = dir.to_str().unwrap().to_owned();
+        resources.sprite_sheet.get_sprite_mut(3).pset(1, 2, 8);
+        resources.sprite_sheet.get_sprite_mut(4).pset(7, 7, 12);
+        let mut editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        let press = |editor: &mut Editor, resources: &mut Resources, key: Key| {
+            for state in [KeyState::Down, KeyState::Up] {
+                for key in [Key::Control, key] {
+                    let msg = Msg::KeyboardEvent(KeyboardEvent { key, state });
+                    <Editor as ElmApp>::update(editor, &msg, resources);
+                }
+            }
+        };
+
+        // Sprites 3 and 4, into 40 and 41.
+        editor.selected_sprite = 3;
+        editor.sprite_selection_end = Some(4);
+        press(&mut editor, &mut resources, Key::E);
+        assert_eq!(editor.notification.content(), "EXPORTED SPRITE_003.TXT");
+        let two_sprites = exports.join("sprite_003.txt");
+        std::fs::copy(&two_sprites, exports.join("sprite_040.txt")).unwrap();
+
+        editor.select_sprite(40);
+        press(&mut editor, &mut resources, Key::O);
+        assert_eq!(editor.notification.content(), "IMPORTED SPRITE_040.TXT");
+        let sheet = &resources.sprite_sheet;
+        assert_eq!(sheet.get_sprite(40), sheet.get_sprite(3));
+        assert_eq!(sheet.get_sprite(41), sheet.get_sprite(4));
+        // A single undo for both.
+        editor
+            .commands
+            .undo(&mut editor.notification, &mut resources);
+        assert_eq!(resources.sprite_sheet.get_sprite(40), &Sprite::EMPTY);
+        assert_eq!(resources.sprite_sheet.get_sprite(41), &Sprite::EMPTY);
+
+        // A single sprite, over the last one: the rest of the two sprite file is left out.
+        editor.select_sprite(3);
+        press(&mut editor, &mut resources, Key::E);
+        assert_eq!(
+            std::fs::read_to_string(&two_sprites)
+                .unwrap()
+                .lines()
+                .count(),
+            8
+        );
+        std::fs::copy(
+            exports.join("sprite_040.txt"),
+            exports.join("sprite_255.txt"),
+        )
+        .unwrap();
+        editor.select_sprite(255);
+        press(&mut editor, &mut resources, Key::O);
+        assert_eq!(
+            resources.sprite_sheet.get_sprite(255).pget(1, 2),
+            Color::RED
+        );
+
+        // Nothing changes for broken or missing files.
+        std::fs::write(exports.join("sprite_100.txt"), "0000\nXYZ").unwrap();
+        editor.select_sprite(100);
+        press(&mut editor, &mut resources, Key::O);
+        assert_eq!(editor.notification.content(), "INVALID SPRITE_100.TXT");
+        editor.select_sprite(101);
+        press(&mut editor, &mut resources, Key::O);
+        assert_eq!(editor.notification.content(), "NO SPRITE_101.TXT");
+        assert_eq!(resources.sprite_sheet.get_sprite(100), &Sprite::EMPTY);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn blocks_that_would_wrap_around_are_rejected() {
+        let mut resources = Resources::empty();
+        resources.map = Map::with_size(16, 16);
+        let mut editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        editor.tab = Tab::MapEditor;
+        for msg in [
+            Msg::MapEditorMsg(map::Msg::ToggleBlocks),
+            Msg::MapEditorMsg(map::Msg::TileSelected(31)),
+            Msg::ClickedMapTile { x: 4, y: 4 },
+            Msg::StrokeEnded,
+        ] {
+            <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
+        }
+
+        assert!((0..16).all(|x| (0..16).all(|y| resources.mget(x, y) == 0)));
+        assert_eq!(editor.help_text(), "SPR 031 CAN'T START A 2X2 BLOCK");
+    }
+
+    #[test]
+    fn fixed_asset_files_are_warned_about() {
+        let mut resources = Resources::empty();
+        resources.asset_warnings = vec![
+            "map.txt: 63 lines, needed 64, padded with zeros".to_owned(),
+            "sprite_sheet.txt: Line 13 has 129 values, needed 128, dropped the rest".to_owned(),
+        ];
+        let editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+
+        assert!(resources.asset_warnings.is_empty());
+        assert_eq!(
+            editor.notification.history().text(),
+            "0 WARNING SPRITE_SHEET.TXT: LINE 13 HAS 129 VALUES, NEEDED 128, DROPPED THE REST\n\
+             0 WARNING MAP.TXT: 63 LINES, NEEDED 64, PADDED WITH ZEROS\n"
+        );
+    }
+
+    #[test]
+    fn the_notification_log_keeps_everything_while_closed() {
+        let mut resources = Resources::empty();
+        let editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        let mut state = (editor, resources);
+        let mut harness = Harness::new();
+        let frame = |frame| Event::Tick {
+            delta_millis: 0.0,
+            frame,
+        };
+        let toggle_log = [
+            key(Key::Control, KeyState::Down),
+            key(Key::H, KeyState::Down),
+            key(Key::H, KeyState::Up),
+            key(Key::Control, KeyState::Up),
+        ];
+
+        send_events(&mut harness, &mut state, [frame(10)]);
+        state.0.notification.alert("SAVED".to_owned());
+        for event in toggle_log {
+            subscribe(&mut state, event);
+        }
+        assert!(state.0.notification_log_open);
+        send_events(&mut harness, &mut state, [frame(11)]);
+        // The log covers the tab.
+        assert_eq!(harness.pixel(64, 60), Color::DARK_BLUE);
+
+        // Switching tabs and closing the log don't lose anything.
+        send_events(&mut harness, &mut state, click(113, 3));
+        assert_eq!(state.0.tab, Tab::MapEditor);
+        for event in toggle_log {
+            subscribe(&mut state, event);
+        }
+        assert!(!state.0.notification_log_open);
+        send_events(&mut harness, &mut state, [frame(20)]);
+        state.0.notification.error("COULDN'T EXPORT SFX".to_owned());
+
+        assert_eq!(
+            state.0.notification.history().text(),
+            "20 ERROR COULDN'T EXPORT SFX\n10 INFO SAVED\n"
+        );
+    }
+
+    #[test]
+    fn middle_click_and_alt_click_pick_map_tiles() {
+        let mut resources = Resources::empty();
+        resources.map = Map::with_size(16, 16);
+        resources.mset(2, 1, 73);
+        resources.mset(3, 1, 4);
+        let mut editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        editor.tab = Tab::MapEditor;
+        let mut state = (editor, resources);
+        let mut harness = Harness::new();
+
+        // Over the cell (2, 1), below the top bar.
+        send_events(&mut harness, &mut state, [mouse_move(20, 20), tick()]);
+        let middle_click = Event::Mouse(MouseEvent::Down(MouseButton::Middle));
+        subscribe(&mut state, middle_click);
+        let (editor, resources) = &mut state;
+        assert_eq!(editor.map_editor.tile(), 73);
+        assert_eq!(editor.map_editor.tile_page(), 1);
+        assert_eq!(editor.help_text(), "PICKED SPR 073");
+
+        let alt = |state| {
+            Msg::KeyboardEvent(KeyboardEvent {
+                key: Key::Alt,
+                state,
+            })
+        };
+        for msg in [
+            alt(KeyState::Down),
+            Msg::ClickedMapTile { x: 3, y: 1 },
+            alt(KeyState::Up),
+        ] {
+            <Editor as ElmApp>::update(editor, &msg, resources);
+        }
+        assert_eq!(editor.map_editor.tile(), 4);
+        assert_eq!(editor.map_editor.tile_page(), 0);
+        // The sprite editor's selection is its own.
+        assert_eq!(editor.selected_sprite, 0);
+
+        // Nothing was placed.
+        assert_eq!((resources.mget(2, 1), resources.mget(3, 1)), (73, 4));
+    }
+
+    #[test]
+    fn idle_frames_allocate_little() {
+        let mut resources = Resources::empty();
+        resources.map = Map::with_size(128, 64);
+        let editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        let mut state = (editor, resources);
+        let mut harness = Harness::new();
+
+        // Mostly the boxed widgets, one per element. Before reusing the formatted text
+        // and pre-sizing the trees, these were 976, 999, 289 and 666.
+        for (msg, max_allocations) in [
+            (Msg::SpriteTabClicked, 720),
+            (Msg::MapButtonClicked, 720),
+            (Msg::SfxButtonClicked, 260),
+            (Msg::PaletteButtonClicked, 500),
+        ] {
+            let (editor, resources) = &mut state;
+            <Editor as ElmApp>::update(editor, &msg, resources);
+            // Over a pixel (or a tile), which shows its help in the bottom bar.
+            send_events(
+                &mut harness,
+                &mut state,
+                [mouse_move(20, 30), tick(), tick()],
+            );
+
+            let allocations = count_allocations(|| {
+                send_events(&mut harness, &mut state, [tick()]);
+                subscribe(&mut state, tick());
+            });
+            assert!(allocations <= max_allocations, "{msg:?}: {allocations}");
+        }
+    }
+
+    #[test]
+    fn the_sprite_and_map_tabs_keep_their_own_selections() {
+        let mut resources = Resources::empty();
+        resources.map = Map::with_size(16, 16);
+        let mut editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+
+        for msg in [
+            Msg::SpriteButtonClicked(130),
+            Msg::MapButtonClicked,
+            Msg::MapEditorMsg(map::Msg::TilePageSelected(1)),
+            Msg::MapEditorMsg(map::Msg::TileSelected(70)),
+            Msg::ClickedMapTile { x: 1, y: 1 },
+            Msg::SpriteTabClicked,
+        ] {
+            <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
+        }
+        assert_eq!(editor.selected_sprite, 130);
+        assert_eq!(resources.mget(1, 1), 70);
+
+        for msg in [Msg::SpriteButtonClicked(3), Msg::MapButtonClicked] {
+            <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
+        }
+        assert_eq!(editor.tab, Tab::MapEditor);
+        assert_eq!(editor.map_editor.tile(), 70);
+        assert_eq!(editor.map_editor.tile_page(), 1);
+        assert_eq!(editor.selected_sprite, 3);
+    }
+
+    #[test]
+    fn the_map_cell_under_the_cursor_is_highlighted() {
+        let mut resources = Resources::empty();
+        resources.map = Map::with_size(16, 16);
+        let mut editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        editor.tab = Tab::MapEditor;
+        let mut state = (editor, resources);
+        let mut harness = Harness::new();
+
+        // Over the cell (2, 1), drawn from (16, 16) to (23, 23).
+        send_events(
+            &mut harness,
+            &mut state,
+            [mouse_move(20, 20), tick(), tick()],
+        );
+        assert_eq!(harness.pixel(16, 16), Color::WHITE);
+        assert_eq!(harness.pixel(16, 23), Color::WHITE);
+        assert_eq!(harness.pixel(15, 16), Color::BLACK);
+        assert_eq!(state.0.cursor_kind(&state.1), CursorKind::Crosshair);
+
+        // Over the sprite sheet.
+        send_events(
+            &mut harness,
+            &mut state,
+            [mouse_move(20, 100), tick(), tick()],
+        );
+        assert_eq!(harness.pixel(16, 16), Color::BLACK);
+        assert_eq!(state.0.cursor_kind(&state.1), CursorKind::Arrow);
+    }
+
+    #[test]
+    fn clicking_the_minimap_moves_the_map_view() {
+        let mut resources = Resources::empty();
+        resources.map = Map::with_size(128, 64);
+        let mut editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        editor.tab = Tab::MapEditor;
+        editor.map_editor.select_tile(5);
+        let mut state = (editor, resources);
+        let mut harness = Harness::new();
+
+        // Two cells per pixel, the minimap's top left corner is at (62, 10).
+        send_events(&mut harness, &mut state, click(92, 20));
+        let (editor, resources) = &mut state;
+        assert_eq!(editor.map_editor.view_settings().0, vec2(-420, -128));
+        // Nothing was placed under it.
+        assert!((0..64).all(|y| (0..128).all(|x| resources.mget(x, y) == 0)));
+        assert_eq!(editor.cursor_kind(resources), CursorKind::Arrow);
+
+        // Hidden with M, the map is clickable again.
+        subscribe(&mut state, key(Key::M, KeyState::Down));
+        send_events(&mut harness, &mut state, click(92, 20));
+        assert_eq!(state.1.mget(64, 17), 5);
+    }
+
+    #[test]
+    fn tools_have_hotkeys_in_the_sprite_tab() {
+        let mut resources = Resources::empty();
+        resources.sset(0, 0, 8);
+        let mut editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        let mut press = |editor: &mut Editor, keys: &[Key]| {
+            let events = keys
+                .iter()
+                .map(|&key| (key, KeyState::Down))
+                .chain(keys.iter().rev().map(|&key| (key, KeyState::Up)));
+            for (key, state) in events {
+                let msg = Msg::KeyboardEvent(KeyboardEvent { key, state });
+                <Editor as ElmApp>::update(editor, &msg, &mut resources);
+            }
+        };
+
+        press(&mut editor, &[Key::Shift, Key::F]);
+        assert_eq!(editor.selected_tool, FILL_TOOL);
+        press(&mut editor, &[Key::B]);
+        assert_eq!(editor.selected_tool, PENCIL_TOOL);
+
+        // Only in the sprite tab.
+        editor.tab = Tab::MapEditor;
+        press(&mut editor, &[Key::Shift, Key::F]);
+        assert_eq!(editor.selected_tool, PENCIL_TOOL);
+
+        // Shift+F didn't flip the sprite.
+        assert_eq!(resources.sget(0, 0), Color::RED);
+
+        editor.tab = Tab::SpriteEditor;
+        let mut state = (editor, resources);
+        send_events(
+            &mut Harness::new(),
+            &mut state,
+            [mouse_move(20, 80), tick()],
+        );
+        assert_eq!(state.0.help_text(), "FILL (SHIFT+F)");
+    }
+
+    #[test]
+    fn shade_brush_steps_pixels_once_per_stroke() {
+        let mut resources = Resources::empty();
+        resources.sset(2, 2, 7);
+        let mut editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        let edited = Msg::SpriteEdited {
+            x: 2,
+            y: 2,
+            color: Color::RED,
+        };
+
+        for msg in [Msg::ToolSelected(SHADE_TOOL), edited, edited] {
+            <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
+        }
+        assert_eq!(resources.sget(2, 2), Color::LIGHT_GREY);
+
+        for msg in [Msg::StrokeEnded, edited] {
+            <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
+        }
+        assert_eq!(resources.sget(2, 2), Color::LAVENDER);
+
+        editor.shift_held = true;
+        for msg in [Msg::StrokeEnded, edited] {
+            <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
+        }
+        assert_eq!(resources.sget(2, 2), Color::LIGHT_GREY);
+
+        // Every step is undone.
+        for _ in 0..3 {
+            editor
+                .commands
+                .undo(&mut editor.notification, &mut resources);
+        }
+        assert_eq!(resources.sget(2, 2), Color::WHITE);
+    }
+
+    #[test]
+    fn gradients_are_undone_at_once() {
+        let mut resources = Resources::empty();
+        let mut editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        let edited = |x, y| Msg::SpriteEdited {
+            x,
+            y,
+            color: Color::WHITE,
+        };
+
+        for msg in [
+            Msg::SpriteEditorMsg(sprite::Msg::ColorSelected(Color::RED)),
+            Msg::SpriteEditorMsg(sprite::Msg::SecondaryColorSelected(Color::BLUE)),
+            Msg::ToolSelected(GRADIENT_TOOL),
+            edited(0, 0),
+            edited(7, 7),
+            // Made smaller before letting go.
+            edited(7, 3),
+            Msg::StrokeEnded,
+        ] {
+            <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
+        }
+        assert_eq!(resources.sget(0, 0), Color::RED);
+        assert_eq!(resources.sget(7, 3), Color::BLUE);
+        assert_eq!(resources.sget(7, 7), Color::BLACK);
+
+        editor
+            .commands
+            .undo(&mut editor.notification, &mut resources);
+        assert_eq!(resources.sget(0, 0), Color::BLACK);
+        assert_eq!(resources.sget(7, 3), Color::BLACK);
+    }
+
+    #[test]
+    fn brush_is_clipped_at_the_sprite_edges() {
+        let mut resources = Resources::empty();
+        let mut editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        editor.selected_sprite = 1;
+        editor.brush_size = BrushSize::large();
+
+        let corner = Msg::SpriteEdited {
+            x: 7,
+            y: 7,
+            color: Color::RED,
+        };
+        <Editor as ElmApp>::update(&mut editor, &corner, &mut resources);
+
+        // A 5x5 brush centered on the corner only paints the 3x3 inside the sprite.
+        let painted: Vec<_> = resources
+            .sprite_sheet
+            .get_sprite(1)
+            .iter_pixels()
+            .filter(|&(.., color)| color == Color::RED)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert_eq!(
+            painted,
+            [
+                (5, 5),
+                (6, 5),
+                (7, 5),
+                (5, 6),
+                (6, 6),
+                (7, 6),
+                (5, 7),
+                (6, 7),
+                (7, 7)
+            ]
+        );
+
+        // Neighbouring sprites are left alone.
+        for sprite in [0, 2, 17] {
+            let sprite = resources.sprite_sheet.get_sprite(sprite);
+            assert!(sprite
+                .iter_pixels()
+                .all(|(.., color)| color == Color::BLACK));
+        }
+    }
+
+    #[test]
+    fn tabs_keep_their_state_when_switching() {
+        let mut resources = Resources::empty();
+        resources.map = Map::with_size(128, 64);
+        let mut editor = <Editor as ElmApp>::init_with_resources(&mut resources);
+        let keyboard = |key, state| Msg::KeyboardEvent(KeyboardEvent { key, state });
+        let alt_arrow = |arrow| {
+            [
+                keyboard(Key::Alt, KeyState::Down),
+                keyboard(arrow, KeyState::Down),
+                keyboard(arrow, KeyState::Up),
+                keyboard(Key::Alt, KeyState::Up),
+            ]
+        };
+
+        // Set up in the sprite tab.
+        for msg in [
+            Msg::ToolSelected(FILL_TOOL),
+            Msg::SpriteEditorMsg(sprite::Msg::ColorSelected(Color::RED)),
+        ] {
+            <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
+        }
+
+        // Then in the map tab: scrolled, with a selection being dragged while panning.
+        let map_msgs = [
+            Msg::MapButtonClicked,
+            Msg::MapEditorMsg(map::Msg::MinimapPressed(64, 32)),
+            Msg::MapEditorMsg(map::Msg::SwitchMapMode),
+            keyboard(Key::Shift, KeyState::Down),
+            Msg::ClickedMapTile { x: 2, y: 2 },
+            Msg::ClickedMapTile { x: 3, y: 3 },
+            Msg::MapEditorMsg(map::Msg::MouseReleased),
+            keyboard(Key::Shift, KeyState::Up),
+            Msg::ClickedMapTile { x: 2, y: 2 },
+            Msg::MapEditorMsg(map::Msg::SetDragging(true)),
+        ];
+        for msg in map_msgs {
+            <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
+        }
+        let view = editor.map_editor.view_settings();
+
+        // Over to the sprite tab and back, with the mouse and space released in between.
+        for msg in [Msg::SpriteTabClicked, Msg::MapButtonClicked] {
+            <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
+        }
+        assert_eq!(editor.map_editor.view_settings(), view);
+        assert!(editor.map_editor.has_selection());
+        assert!(!editor.map_editor.is_dragging());
+        // The selection isn't stuck to the mouse, clicking elsewhere drops it.
+        <Editor as ElmApp>::update(
+            &mut editor,
+            &Msg::ClickedMapTile { x: 9, y: 9 },
+            &mut resources,
+        );
+        assert!(!editor.map_editor.has_selection());
+
+        // The same cycling through the tabs with Alt+arrows, past the palette tab.
+        for msg in alt_arrow(Key::RightArrow)
+            .into_iter()
+            .chain(alt_arrow(Key::RightArrow))
+            .chain(alt_arrow(Key::RightArrow))
+        {
+            <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
+        }
+        assert_eq!(editor.tab, Tab::SpriteEditor);
+        assert_eq!(editor.selected_tool, FILL_TOOL);
+        assert_eq!(editor.sprite_editor.colors().0, Color::RED);
+        for msg in alt_arrow(Key::LeftArrow) {
+            <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
+        }
+        assert_eq!(editor.tab, Tab::PaletteEditor);
+        for msg in alt_arrow(Key::LeftArrow) {
+            <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
+        }
+        assert_eq!(editor.tab, Tab::SfxEditor);
+        for msg in alt_arrow(Key::LeftArrow) {
+            <Editor as ElmApp>::update(&mut editor, &msg, &mut resources);
         }
+        assert_eq!(editor.tab, Tab::MapEditor);
+        assert_eq!(editor.map_editor.view_settings(), view);
     }
 }
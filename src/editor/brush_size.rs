@@ -3,15 +3,16 @@ use std::fmt::Debug;
 use itertools::Itertools;
 
 use crate::{
-    runtime::{draw_data::colors, sprite_sheet::Color},
+    runtime::draw_data::colors,
     ui::{
         button::{self, Button},
         slider::{self, SliderValue},
         DrawFn, Element, Tree,
     },
+    Color, SpriteSheet,
 };
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub(crate) struct BrushSize {
     size: SliderValue,
 }
@@ -23,6 +24,13 @@ impl BrushSize {
         }
     }
 
+    #[cfg(test)]
+    pub(crate) fn large() -> Self {
+        Self {
+            size: SliderValue::Large,
+        }
+    }
+
     pub(crate) fn to_human_readable(self) -> &'static str {
         match self.size {
             SliderValue::Tiny => "1",
@@ -32,6 +40,18 @@ impl BrushSize {
         }
     }
 
+    pub(crate) fn from_human_readable(size: &str) -> Option<Self> {
+        let size = match size {
+            "1" => SliderValue::Tiny,
+            "2" => SliderValue::Small,
+            "3" => SliderValue::Medium,
+            "4" => SliderValue::Large,
+            _ => return None,
+        };
+
+        Some(Self { size })
+    }
+
     fn to_screen_size(self) -> i32 {
         match self.size {
             SliderValue::Tiny => 1,
@@ -61,14 +81,14 @@ impl BrushSize {
     }
 }
 
-pub(crate) struct BrushSizeSelector<'a, Msg, F> {
+pub(crate) struct BrushSizeSelector<'a, F> {
     pub(crate) x: i32,
     pub(crate) y: i32,
     pub(crate) selected_color: Color,
     pub(crate) brush_size: BrushSize,
     pub(crate) on_press: F,
-    pub(crate) on_hover: Msg,
     pub(crate) state: &'a mut State,
+    pub(crate) editor_sprites: &'a SpriteSheet,
 }
 
 #[derive(Debug)]
@@ -86,7 +106,7 @@ impl State {
     }
 }
 
-impl<'a, Msg: Copy + Debug + 'a, F: Fn(BrushSize) -> Msg> BrushSizeSelector<'a, Msg, F> {
+impl<'a, Msg: Copy + Debug + 'a, F: Fn(BrushSize) -> Msg> BrushSizeSelector<'a, F> {
     pub(crate) fn view(self) -> Element<'a, Msg> {
         Tree::new()
             .push(size_indicator(
@@ -102,8 +122,8 @@ impl<'a, Msg: Copy + Debug + 'a, F: Fn(BrushSize) -> Msg> BrushSizeSelector<'a,
                 self.y - 3,
                 self.brush_size.size,
                 |new_size| (self.on_press)(BrushSize { size: new_size }),
-                self.on_hover,
                 &mut self.state.slider_state,
+                self.editor_sprites,
             ))
             .into()
     }
@@ -150,7 +170,7 @@ fn size_indicator<'a, Msg: Copy + Debug + 'a>(
                 local_top + size - 1,
                 selected_color,
             );
-            draw.palt(Some(0));
+            draw.palt(Some(Color::BLACK));
         }),
     )
     .event_on_press()
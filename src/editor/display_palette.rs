@@ -0,0 +1,125 @@
+//! Which of Pico8's extended colors (128 to 143) each sprite is meant to be shown with,
+//! through the game's `screen_pal`. Only a note in the editor settings to preview the sprite with,
+//! the sprite's pixels stay in the 16 colors.
+
+use std::collections::BTreeMap;
+
+use itertools::Itertools;
+
+use crate::Color;
+
+/// Pico8's number for the first extended color.
+pub(crate) const FIRST_EXTENDED: u8 = 128;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DisplayNotes {
+    // For each sprite with a note, the extended color (0 for 128) each color is shown as.
+    notes: BTreeMap<usize, [Option<u8>; 16]>,
+}
+
+impl DisplayNotes {
+    /// Every sprite shown in the 16 colors.
+    pub(crate) fn new() -> Self {
+        Self {
+            notes: BTreeMap::new(),
+        }
+    }
+
+    /// The extended color each of `sprite`'s colors is shown as, `None` for the ones shown as they are.
+    pub(crate) fn get(&self, sprite: usize) -> [Option<u8>; 16] {
+        self.notes.get(&sprite).copied().unwrap_or([None; 16])
+    }
+
+    /// Shows `color` as `extended` in `sprite`, or as itself again if it already was.
+    pub(crate) fn toggle(&mut self, sprite: usize, color: Color, extended: u8) {
+        let note = self.notes.entry(sprite).or_insert([None; 16]);
+        let shown_as = &mut note[color.index() as usize];
+        *shown_as = (*shown_as != Some(extended)).then_some(extended);
+
+        if note.iter().all(Option::is_none) {
+            self.notes.remove(&sprite);
+        }
+    }
+
+    /// One note per sprite: its number, then each color and the extended one it's shown as,
+    /// like `12 8 136 7 135`.
+    pub(crate) fn to_human_readable(&self) -> impl Iterator<Item = String> + '_ {
+        self.notes.iter().map(|(sprite, note)| {
+            let pairs = note
+                .iter()
+                .enumerate()
+                .filter_map(|(color, shown_as)| {
+                    shown_as.map(|extended| format!("{color} {}", FIRST_EXTENDED + extended))
+                })
+                .join(" ");
+
+            format!("{sprite} {pairs}")
+        })
+    }
+
+    /// Adds a note written by [`DisplayNotes::to_human_readable`].
+    pub(crate) fn add_from_human_readable(&mut self, str: &str) -> Option<()> {
+        let numbers: Vec<usize> = str
+            .split_whitespace()
+            .map(|number| number.parse().ok())
+            .collect::<Option<_>>()?;
+        let (&sprite, pairs) = numbers.split_first()?;
+        if pairs.is_empty() || pairs.len() % 2 != 0 {
+            return None;
+        }
+
+        let mut note = [None; 16];
+        for pair in pairs.chunks(2) {
+            let extended = pair[1]
+                .checked_sub(FIRST_EXTENDED.into())
+                .filter(|&extended| extended < 16)?;
+            *note.get_mut(pair[0])? = Some(extended as u8);
+        }
+        self.notes.insert(sprite, note);
+
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn choosing_the_same_color_again_takes_it_back() {
+        let mut notes = DisplayNotes::new();
+        notes.toggle(3, Color::RED, 8);
+        notes.toggle(3, Color::WHITE, 7);
+        notes.toggle(3, Color::WHITE, 7);
+
+        let mut expected = [None; 16];
+        expected[8] = Some(8);
+        assert_eq!(notes.get(3), expected);
+        assert_eq!(notes.get(4), [None; 16]);
+
+        // Nothing left to note.
+        notes.toggle(3, Color::RED, 8);
+        assert_eq!(notes, DisplayNotes::new());
+    }
+
+    #[test]
+    fn notes_round_trip() {
+        let mut notes = DisplayNotes::new();
+        notes.toggle(12, Color::RED, 8);
+        notes.toggle(12, Color::WHITE, 7);
+        notes.toggle(300, Color::BLACK, 0);
+
+        let lines: Vec<String> = notes.to_human_readable().collect();
+        assert_eq!(lines, ["12 7 135 8 136", "300 0 128"]);
+
+        let mut read = DisplayNotes::new();
+        for line in &lines {
+            read.add_from_human_readable(line).unwrap();
+        }
+        assert_eq!(read, notes);
+
+        for invalid in ["12", "12 8", "12 8 127", "12 8 144", "12 16 130", "12 8 x"] {
+            assert_eq!(read.add_from_human_readable(invalid), None, "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Dithered painting, mixing the two selected colors in a fixed pattern for shading.
+
+use crate::draw::BAYER_4X4;
+use crate::runtime::sprite_sheet::Sprite;
+use crate::Color;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Dither {
+    Off,
+    /// Half of each color, in a checkerboard.
+    Checkerboard,
+    /// A quarter of the painted color, scattered over the other one.
+    Quarter,
+}
+
+impl Dither {
+    pub(crate) fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Checkerboard,
+            Self::Checkerboard => Self::Quarter,
+            Self::Quarter => Self::Off,
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Off => "DITHER OFF",
+            Self::Checkerboard => "DITHER ON",
+            Self::Quarter => "DITHER ON (4X4)",
+        }
+    }
+
+    /// The color to paint the sprite's pixel `(x, y)` with, `color` or `other`.
+    /// Only depends on the position, so strokes line up however they're painted.
+    pub(crate) fn color(self, x: isize, y: isize, color: Color, other: Color) -> Color {
+        let amount = match self {
+            Self::Off => return color,
+            Self::Checkerboard => 8,
+            Self::Quarter => 4,
+        };
+
+        let threshold = BAYER_4X4[y.rem_euclid(4) as usize][x.rem_euclid(4) as usize];
+        if threshold < amount {
+            color
+        } else {
+            other
+        }
+    }
+}
+
+/// Fills the rectangle between two corners of a sprite with a dithered gradient,
+/// from `from` at the `start` corner to `to` at the `end` one,
+/// top to bottom (or left to right, if `horizontal`) for a rectangle dragged that way.
+///
+/// The pattern only depends on the position in the sprite. Sprites are a multiple of its size,
+/// so gradients filled separately on neighbouring sprites line up.
+pub(crate) fn fill_gradient(
+    sprite: &mut Sprite,
+    start: (isize, isize),
+    end: (isize, isize),
+    (from, to): (Color, Color),
+    horizontal: bool,
+) {
+    let along = |(x, y): (isize, isize)| if horizontal { x } else { y };
+    let length = (along(end) - along(start)).abs() + 1;
+
+    for y in start.1.min(end.1)..=start.1.max(end.1) {
+        for x in start.0.min(end.0)..=start.0.max(end.0) {
+            // Out of 16, rounded. Single pixel gradients are all `from`.
+            let amount = match length {
+                1 => 16,
+                _ => {
+                    let position = (along((x, y)) - along(start)).abs();
+                    16 - (position * 32 + length - 1) / (2 * (length - 1))
+                }
+            };
+            let threshold = BAYER_4X4[y.rem_euclid(4) as usize][x.rem_euclid(4) as usize];
+
+            sprite.pset(
+                x,
+                y,
+                if (threshold as isize) < amount {
+                    from
+                } else {
+                    to
+                },
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(dither: Dither) -> Vec<String> {
+        (0..4)
+            .map(|y| {
+                (0..4)
+                    .map(|x| match dither.color(x, y, Color::RED, Color::BLUE) {
+                        Color::RED => 'r',
+                        _ => '.',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn patterns() {
+        assert_eq!(pattern(Dither::Off), ["rrrr"; 4]);
+        assert_eq!(
+            pattern(Dither::Checkerboard),
+            ["r.r.", ".r.r", "r.r.", ".r.r"]
+        );
+        assert_eq!(pattern(Dither::Quarter), ["r.r.", "....", "r.r.", "...."]);
+    }
+
+    #[test]
+    fn gradients_go_from_one_color_to_the_other() {
+        let rows = |start, end, horizontal| {
+            let mut sprite = Sprite::EMPTY;
+            fill_gradient(
+                &mut sprite,
+                start,
+                end,
+                (Color::RED, Color::BLUE),
+                horizontal,
+            );
+
+            (0..8)
+                .map(|y| {
+                    (0..8)
+                        .map(|x| match sprite.pget(x, y) {
+                            Color::RED => 'r',
+                            Color::BLUE => 'b',
+                            _ => '.',
+                        })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let vertical = rows((1, 0), (6, 7), false);
+        assert_eq!(vertical[0], ".rrrrrr.");
+        assert_eq!(vertical[7], ".bbbbbb.");
+        let reds = |row: &String| row.matches('r').count();
+        assert!(vertical
+            .windows(2)
+            .all(|rows| reds(&rows[0]) >= reds(&rows[1])));
+
+        // Dragged up, and sideways.
+        assert_eq!(rows((0, 7), (7, 0), false)[0], "bbbbbbbb");
+        let horizontal = rows((0, 0), (7, 7), true);
+        assert!(horizontal
+            .iter()
+            .all(|row| row.starts_with('r') && row.ends_with('b')));
+
+        assert_eq!(rows((2, 3), (5, 3), false)[3], "..rrrr..");
+    }
+
+    #[test]
+    fn patterns_repeat_past_the_edges() {
+        for dither in [Dither::Checkerboard, Dither::Quarter] {
+            for (x, y) in [(-1, 0), (0, -3), (5, 6), (-9, 13)] {
+                assert_eq!(
+                    dither.color(x, y, Color::RED, Color::BLUE),
+                    dither.color(x.rem_euclid(4), y.rem_euclid(4), Color::RED, Color::BLUE),
+                );
+            }
+        }
+    }
+}
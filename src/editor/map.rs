@@ -1,36 +1,329 @@
+use super::minimap::{self, Layout};
+use super::sprite_index;
+use super::transparency::{self, TransparentPages};
+use super::undo_redo::{Command, Commands};
+use super::ShiftDirection;
 use crate::ui::button::{self, Button};
-use crate::ui::{DrawFn, Element, Tree};
+use crate::ui::{DrawFn, Element, Layer, Tree};
 use crate::util::vec2::{vec2, Vec2i};
-use crate::Map;
-use crate::{Event, Key, KeyState, KeyboardEvent, MouseEvent};
+use crate::{Event, Key, KeyState, KeyboardEvent, MouseButton, MouseEvent};
+use crate::{Map, Resources, SpriteSheet};
 use itertools::Itertools;
 use std::fmt::Debug;
 
+// Tiles shown at once.
+const VISIBLE_COLUMNS: usize = 16;
+const VISIBLE_ROWS: usize = 9;
+
 #[derive(Debug)]
 pub(crate) struct Editor {
     buttons: Vec<button::State>,
     show_sprites_in_map: bool,
-    hovered_tile: (usize, usize),
     mouse_position: Vec2i,
     camera: Vec2i,
     // TODO: Use a proper enum
     dragging: bool,
+    selection: Option<Selection>,
+    gesture: Gesture,
+    show_minimap: bool,
+    minimap: minimap::State,
+    // The sprite placed on the map, picked in the sprite view below it.
+    // Separate from the sprite tab's selection, and a byte like the map's cells.
+    tile: u8,
+    // The page of the sprite view, which only shows the sprites that fit in a cell.
+    tile_page: usize,
+    // Whether placing a tile stamps the 2x2 block (16x16 metasprite) it's the top left of.
+    stamp_blocks: bool,
+}
+
+/// A rectangle of map cells, in map coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Selection {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+}
+
+impl Selection {
+    fn from_corners((x0, y0): (i32, i32), (x1, y1): (i32, i32)) -> Self {
+        Self {
+            x: x0.min(x1),
+            y: y0.min(y1),
+            width: (x1 - x0).abs() + 1,
+            height: (y1 - y0).abs() + 1,
+        }
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        (self.x..self.x + self.width).contains(&x) && (self.y..self.y + self.height).contains(&y)
+    }
+
+    fn offset(self, (dx, dy): (i32, i32)) -> Self {
+        Self {
+            x: self.x + dx,
+            y: self.y + dy,
+            ..self
+        }
+    }
+
+    // The part of the selection inside a `width` by `height` map, if any.
+    fn clip(self, width: i32, height: i32) -> Option<Self> {
+        let (x0, y0) = (self.x.max(0), self.y.max(0));
+        let (x1, y1) = (
+            (self.x + self.width).min(width),
+            (self.y + self.height).min(height),
+        );
+
+        (x0 < x1 && y0 < y1).then(|| Self::from_corners((x0, y0), (x1 - 1, y1 - 1)))
+    }
+
+    /// Row by row.
+    fn cells(self) -> impl Iterator<Item = (i32, i32)> {
+        (self.y..self.y + self.height)
+            .flat_map(move |y| (self.x..self.x + self.width).map(move |x| (x, y)))
+    }
+}
+
+/// What pressing (and dragging over) map tiles is doing, other than placing sprites.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Gesture {
+    Idle,
+    /// Shift+dragging out a selection, from the first tile pressed.
+    Selecting {
+        start: (i32, i32),
+    },
+    /// Dragging the selection around by one of its tiles.
+    /// Copies the selection instead of moving it if Ctrl was held when grabbing it.
+    Moving {
+        grabbed: (i32, i32),
+        to: (i32, i32),
+        copy: bool,
+    },
+}
+
+impl Gesture {
+    // How far the selection is being dragged.
+    fn offset(self) -> (i32, i32) {
+        match self {
+            Gesture::Moving { grabbed, to, .. } => (to.0 - grabbed.0, to.1 - grabbed.1),
+            _ => (0, 0),
+        }
+    }
 }
 
 impl Editor {
     pub(crate) fn new() -> Self {
         Self {
-            buttons: vec![button::State::new(); 144],
+            buttons: vec![button::State::new(); VISIBLE_COLUMNS * VISIBLE_ROWS],
             show_sprites_in_map: true,
-            hovered_tile: (0, 0),
             mouse_position: vec2(64, 64),
             camera: Vec2i::zero(),
             dragging: false,
+            selection: None,
+            gesture: Gesture::Idle,
+            show_minimap: true,
+            minimap: minimap::State::new(),
+            tile: 0,
+            tile_page: 0,
+            stamp_blocks: false,
+        }
+    }
+
+    pub(crate) fn tile(&self) -> u8 {
+        self.tile
+    }
+
+    pub(crate) fn tile_page(&self) -> usize {
+        self.tile_page
+    }
+
+    /// Selects `sprite` to place, turning to its page.
+    /// Sprites past 255 can't be placed, so they're ignored.
+    pub(crate) fn select_tile(&mut self, sprite: usize) {
+        if let Ok(tile) = u8::try_from(sprite) {
+            self.tile = tile;
+            self.tile_page = sprite_index::page_of(sprite);
+        }
+    }
+
+    pub(crate) fn stamps_blocks(&self) -> bool {
+        self.stamp_blocks
+    }
+
+    /// The sprites stamped in block mode, row by row, see [`SpriteSheet::metasprite`].
+    /// `None` when the tile is in the sheet's last column or row: blocks don't wrap around,
+    /// those can't be placed.
+    pub(crate) fn block(&self, sprite_sheet: &SpriteSheet) -> Option<[u8; 4]> {
+        let block = sprite_sheet.metasprite(self.tile.into())?;
+
+        // Past 255 on bigger sheets, which the map can't hold.
+        block
+            .iter()
+            .all(|&sprite| u8::try_from(sprite).is_ok())
+            .then(|| block.map(|sprite| sprite as u8))
+    }
+
+    /// Places the tile at (`x`, `y`), or in block mode its whole block, on the 2x2 cell grid
+    /// the block's cell is in (blocks start on even cells, see [`crate::collision::map_collides16`]).
+    ///
+    /// Returns `false` without placing anything if the tile can't start a block.
+    pub(crate) fn place_tile(&self, x: usize, y: usize, resources: &mut Resources) -> bool {
+        let (x, y) = (x as i32, y as i32);
+        if !self.stamp_blocks {
+            resources.mset(x, y, self.tile);
+
+            return true;
+        }
+
+        let Some(block) = self.block(&resources.sprite_sheet) else {
+            return false;
+        };
+        let (x, y) = (x - x % 2, y - y % 2);
+        for ((dx, dy), sprite) in [(0, 0), (1, 0), (0, 1), (1, 1)].into_iter().zip(block) {
+            resources.mset(x + dx, y + dy, sprite);
         }
+
+        true
+    }
+
+    /// Top left corner of the view, and whether sprites are shown (instead of their numbers).
+    pub(crate) fn view_settings(&self) -> (Vec2i, bool) {
+        (self.camera, self.show_sprites_in_map)
+    }
+
+    pub(crate) fn restore_view_settings(&mut self, camera: Vec2i, show_sprites: bool, map: &Map) {
+        self.camera = clamp_camera(camera, map);
+        self.show_sprites_in_map = show_sprites;
+    }
+
+    /// The map cell under `position` (on the screen), if it's over the view drawn at `origin`.
+    pub(crate) fn tile_under(
+        &self,
+        position: (i32, i32),
+        origin: Vec2i,
+        map: &Map,
+    ) -> Option<(usize, usize)> {
+        let (x, y) = (position.0 - origin.x, position.1 - origin.y);
+        let on_view = (0..VISIBLE_COLUMNS as i32 * 8).contains(&x)
+            && (0..VISIBLE_ROWS as i32 * 8).contains(&y);
+        let (column, row) = (
+            ((x - self.camera.x) / 8) as usize,
+            ((y - self.camera.y) / 8) as usize,
+        );
+
+        let on_minimap = self.show_minimap
+            && Layout::new((map.width() as i32, map.height() as i32), origin)
+                .contains(position.0, position.1);
+
+        (on_view && !on_minimap && column < map.width() && row < map.height())
+            .then_some((column, row))
+    }
+
+    /// Stops panning, and drops whatever the mouse is doing with the selection
+    /// (which stays where it was), for when the map stops getting events.
+    pub(crate) fn stop_gestures(&mut self) {
+        self.dragging = false;
+        self.gesture = Gesture::Idle;
     }
 
-    pub(crate) fn update(&mut self, msg: Msg) {
+    #[cfg(test)]
+    pub(crate) fn has_selection(&self) -> bool {
+        self.selection.is_some()
+    }
+
+    /// Whether the map is being panned around.
+    pub(crate) fn is_dragging(&self) -> bool {
+        self.dragging
+    }
+
+    /// Called when a map tile is pressed (or dragged over while pressed),
+    /// returns whether it was used for the selection instead of placing a sprite.
+    ///
+    /// Shift+dragging selects a rectangle of tiles, and dragging the selection moves it
+    /// (or copies it, with Ctrl held).
+    pub(crate) fn tile_pressed(&mut self, x: usize, y: usize, shift: bool, control: bool) -> bool {
+        let tile = (x as i32, y as i32);
+
+        match &mut self.gesture {
+            Gesture::Selecting { start } => {
+                self.selection = Some(Selection::from_corners(*start, tile));
+            }
+            Gesture::Moving { to, .. } => {
+                *to = tile;
+            }
+            Gesture::Idle if shift => {
+                self.gesture = Gesture::Selecting { start: tile };
+                self.selection = Some(Selection::from_corners(tile, tile));
+            }
+            Gesture::Idle => match self.selection {
+                Some(selection) if selection.contains(tile.0, tile.1) => {
+                    self.gesture = Gesture::Moving {
+                        grabbed: tile,
+                        to: tile,
+                        copy: control,
+                    };
+                }
+                // Clicking anywhere else drops the selection, and places a sprite as usual.
+                _ => {
+                    self.selection = None;
+
+                    return false;
+                }
+            },
+        }
+
+        true
+    }
+
+    // Puts the selection where it was dragged to, as a single undoable change.
+    fn drop_selection(&mut self, resources: &mut Resources, commands: &mut Commands) {
+        let (Some(selection), Gesture::Moving { copy, .. }) = (self.selection, self.gesture) else {
+            return;
+        };
+        let offset = self.gesture.offset();
+        if offset == (0, 0) {
+            return;
+        }
+
+        let (width, height) = resources.map_size();
+        let destination = selection.offset(offset).clip(width, height);
+
+        // Staged first, the destination can overlap the original cells.
+        let block: Vec<_> = selection
+            .cells()
+            .map(|(x, y)| resources.mget(x, y))
+            .collect();
+        let changed: Vec<_> = selection
+            .cells()
+            .chain(destination.into_iter().flat_map(Selection::cells))
+            .unique()
+            .collect();
+        let previous: Vec<_> = changed.iter().map(|&(x, y)| resources.mget(x, y)).collect();
+
+        if !copy {
+            for (x, y) in selection.cells() {
+                resources.mset(x, y, 0);
+            }
+        }
+        for ((x, y), sprite) in selection.cells().zip(block) {
+            let (x, y) = (x + offset.0, y + offset.1);
+            if (0..width).contains(&x) && (0..height).contains(&y) {
+                resources.mset(x, y, sprite);
+            }
+        }
+
+        let new = changed.iter().map(|&(x, y)| resources.mget(x, y)).collect();
+        commands.push(Command::map_changed(changed, previous, new));
+        self.selection = destination;
+    }
+
+    pub(crate) fn update(&mut self, msg: Msg, resources: &mut Resources, commands: &mut Commands) {
         match msg {
+            Msg::MouseReleased => {
+                self.drop_selection(resources, commands);
+                self.gesture = Gesture::Idle;
+            }
             Msg::MouseMove(mouse_position) => {
                 let delta = self.mouse_position - mouse_position;
 
@@ -38,7 +331,7 @@ impl Editor {
 
                 self.mouse_position = mouse_position;
                 if self.dragging {
-                    self.camera = self.camera - delta;
+                    self.camera = clamp_camera(self.camera - delta, &resources.map);
                 }
             }
             Msg::SetDragging(dragging) => {
@@ -47,8 +340,23 @@ impl Editor {
             Msg::SwitchMapMode => {
                 self.show_sprites_in_map = !self.show_sprites_in_map;
             }
-            Msg::HoveredTile(hovered_tile) => {
-                self.hovered_tile = hovered_tile;
+            Msg::ToggleMinimap => {
+                self.show_minimap = !self.show_minimap;
+            }
+            Msg::ToggleBlocks => {
+                self.stamp_blocks = !self.stamp_blocks;
+            }
+            Msg::MinimapPressed(column, row) => {
+                // Centered on the cell.
+                let center = vec2(VISIBLE_COLUMNS as i32 * 4, VISIBLE_ROWS as i32 * 4);
+                let camera = center - vec2(column as i32 * 8 + 4, row as i32 * 8 + 4);
+                self.camera = clamp_camera(camera, &resources.map);
+            }
+            Msg::TileSelected(sprite) => {
+                self.select_tile(sprite);
+            }
+            Msg::TilePageSelected(page) => {
+                self.tile_page = page;
             }
         }
     }
@@ -58,38 +366,56 @@ impl Editor {
             Event::Keyboard(event) => match event {
                 KeyboardEvent { key, state } => match (key, state) {
                     (Key::C, KeyState::Down) => Some(Msg::SwitchMapMode),
+                    (Key::M, KeyState::Down) => Some(Msg::ToggleMinimap),
+                    (Key::X, KeyState::Down) => Some(Msg::ToggleBlocks),
                     (Key::Space, key_state) => Some(Msg::SetDragging(*key_state == KeyState::Down)),
                     _ => None,
                 },
             },
             &Event::Mouse(MouseEvent::Move { x, y }) => Some(Msg::MouseMove(vec2(x, y))),
+            Event::Mouse(MouseEvent::Up(MouseButton::Left)) => Some(Msg::MouseReleased),
             _ => None,
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn view<'a, 'b, Msg: Copy + Debug + 'a>(
         &'a mut self,
-        map: &'b Map,
+        resources: &'b Resources,
         x: i32,
         y: i32,
+        cursor: (i32, i32),
+        // Tiles from these pages are drawn over a checkerboard, with color 0 transparent.
+        transparent_pages: TransparentPages,
         on_tile_click: &impl Fn(usize, usize) -> Msg,
-        on_map_editor_msg: &impl Fn(self::Msg) -> Msg,
+        on_map_editor_msg: fn(self::Msg) -> Msg,
     ) -> Element<'a, Msg> {
         let show_sprites_in_map = self.show_sprites_in_map;
         let camera = self.camera;
+        let (selection, gesture) = (self.selection, self.gesture);
+        // First visible tile.
+        let (first_column, first_row) = ((-camera.x / 8) as usize, (-camera.y / 8) as usize);
 
-        let highlighted_tile_position =
-            tile_position(self.camera, self.hovered_tile.0, self.hovered_tile.1) + vec2(x, y);
+        let hovered_tile = self.tile_under(cursor, vec2(x, y), &resources.map);
 
-        let v: Vec<Element<'_, Msg>> = self
+        // The tiles, and room for the divider, selection, highlight and minimap.
+        let mut v: Vec<Element<'_, Msg>> = Vec::with_capacity(self.buttons.len() + 4);
+        let tiles = self
             .buttons
-            .iter_mut()
-            .chunks(16)
-            .into_iter()
+            .chunks_mut(VISIBLE_COLUMNS)
             .enumerate()
             .flat_map(|(row_index, row)| {
-                row.into_iter().enumerate().map(move |(col_index, state)| {
-                    let sprite = map.mget(col_index as i32, row_index as i32);
+                let row_index = first_row + row_index;
+
+                row.iter_mut().enumerate().map(move |(col_index, state)| {
+                    let col_index = first_column + col_index;
+                    let sprite = preview_mget(
+                        resources,
+                        selection,
+                        gesture,
+                        col_index as i32,
+                        row_index as i32,
+                    );
 
                     let Vec2i { x, y } = tile_position(camera, col_index, row_index) + vec2(x, y);
                     Button::new(
@@ -100,7 +426,11 @@ impl Editor {
                         Some(on_tile_click(col_index, row_index)),
                         state,
                         DrawFn::new(move |draw| {
-                            draw.palt(None);
+                            let palt = transparent_pages.palt(sprite.into());
+                            if show_sprites_in_map && palt.is_some() {
+                                transparency::checkerboard(draw, 0, 0, 8, 8, 2);
+                            }
+                            draw.palt(palt);
                             if show_sprites_in_map {
                                 draw.spr(sprite.into(), 0, 0);
                             } else {
@@ -109,41 +439,412 @@ impl Editor {
                         }),
                     )
                     .event_on_press()
-                    .on_hover(on_map_editor_msg(self::Msg::HoveredTile((
-                        col_index, row_index,
-                    ))))
                     .into()
                 })
-            })
-            .collect();
+            });
+        v.extend(tiles);
+
+        let mut tree = Tree::with_children(v);
+        if resources.shared_memory() {
+            let divider_y = tile_position(camera, 0, Map::HEIGHT_SPRITES / 2).y + y;
+            tree = tree.push(shared_memory_divider(divider_y));
+        }
+
+        if let Some(selection) = selection {
+            let selection = selection.offset(gesture.offset());
+            let position =
+                tile_position(camera, 0, 0) + vec2(selection.x * 8 + x, selection.y * 8 + y);
+            tree = tree.push(outline_selection(position, selection));
+        }
+
+        // Drawn last, over the tiles and the selection.
+        // In block mode, the whole block that would be stamped.
+        if let Some((column, row)) = hovered_tile {
+            let (column, row, size) = if self.stamp_blocks {
+                (column - column % 2, row - row % 2, 16)
+            } else {
+                (column, row, 8)
+            };
+            let position = tile_position(camera, column, row) + vec2(x, y);
+            tree = tree.push(highlight_hovered(position, size));
+        }
 
-        Tree::with_children(v)
-            .push(highlight_hovered(highlighted_tile_position))
-            .into()
+        // Above everything else, so that clicks on it don't reach the map underneath.
+        if self.show_minimap {
+            let (width, height) = (resources.map.width(), resources.map.height());
+            let viewport = (
+                -camera.x / 8,
+                -camera.y / 8,
+                VISIBLE_COLUMNS.min(width) as i32,
+                VISIBLE_ROWS.min(height) as i32,
+            );
+            let minimap = minimap::view(
+                &mut self.minimap,
+                resources,
+                vec2(x, y),
+                viewport,
+                self::Msg::MinimapPressed,
+            );
+            tree = tree.push_to(Layer::Overlay, minimap.map(on_map_editor_msg));
+        }
+
+        tree.into()
     }
 }
 
 #[derive(Debug, Copy, Clone)]
 pub(crate) enum Msg {
     SwitchMapMode,
-    HoveredTile((usize, usize)),
+    ToggleMinimap,
+    ToggleBlocks,
+    MinimapPressed(usize, usize),
     MouseMove(Vec2i),
+    MouseReleased,
     SetDragging(bool),
+    TileSelected(usize),
+    TilePageSelected(usize),
 }
 
-fn highlight_hovered<'a, Msg: Copy + Debug + 'a>(tile_position: Vec2i) -> Element<'a, Msg> {
+/// Moves the whole map one cell over, as a single undoable change.
+/// The cells pushed off one edge come back on the other one if `wrap` is set,
+/// otherwise the exposed edge is left empty.
+pub(super) fn shift_map(
+    resources: &mut Resources,
+    commands: &mut Commands,
+    direction: ShiftDirection,
+    wrap: bool,
+) {
+    let (width, height) = resources.map_size();
+    let (dx, dy) = direction.offset();
+
+    let mut cells = vec![];
+    let mut previous = vec![];
+    let mut new = vec![];
+    for (x, y) in Selection::from_corners((0, 0), (width - 1, height - 1)).cells() {
+        let (from_x, from_y) = (x - dx, y - dy);
+        let sprite = if wrap {
+            resources.mget(from_x.rem_euclid(width), from_y.rem_euclid(height))
+        } else if (0..width).contains(&from_x) && (0..height).contains(&from_y) {
+            resources.mget(from_x, from_y)
+        } else {
+            0
+        };
+
+        // Only the cells that change are kept for undoing.
+        if sprite != resources.mget(x, y) {
+            cells.push((x, y));
+            previous.push(resources.mget(x, y));
+            new.push(sprite);
+        }
+    }
+
+    // Read everything before writing, every cell is read by its neighbour.
+    for (&(x, y), &sprite) in cells.iter().zip(&new) {
+        resources.mset(x, y, sprite);
+    }
+    commands.push(Command::map_changed(cells, previous, new));
+}
+
+// The sprite shown at a map cell, with the selection where it's being dragged to.
+fn preview_mget(
+    resources: &Resources,
+    selection: Option<Selection>,
+    gesture: Gesture,
+    x: i32,
+    y: i32,
+) -> u8 {
+    let (Some(selection), Gesture::Moving { copy, .. }) = (selection, gesture) else {
+        return resources.mget(x, y);
+    };
+    let (dx, dy) = gesture.offset();
+
+    if selection.offset((dx, dy)).contains(x, y) {
+        resources.mget(x - dx, y - dy)
+    } else if !copy && selection.contains(x, y) {
+        0
+    } else {
+        resources.mget(x, y)
+    }
+}
+
+fn outline_selection<'a, Msg: Copy + Debug + 'a>(
+    position: Vec2i,
+    selection: Selection,
+) -> Element<'a, Msg> {
     DrawFn::new(move |draw| {
         draw.rect(
-            tile_position.x as i32,
-            tile_position.y as i32,
-            (tile_position.x + 7) as i32,
-            (tile_position.y + 7) as i32,
+            position.x - 1,
+            position.y - 1,
+            position.x + selection.width * 8,
+            position.y + selection.height * 8,
+            10,
+        )
+    })
+    .into()
+}
+
+fn highlight_hovered<'a, Msg: Copy + Debug + 'a>(
+    tile_position: Vec2i,
+    size: i32,
+) -> Element<'a, Msg> {
+    DrawFn::new(move |draw| {
+        draw.rect(
+            tile_position.x,
+            tile_position.y,
+            tile_position.x + size - 1,
+            tile_position.y + size - 1,
             7,
         )
     })
     .into()
 }
 
+// Rows below this line share their memory with the sprite sheet.
+fn shared_memory_divider<'a, Msg: Copy + Debug + 'a>(y: i32) -> Element<'a, Msg> {
+    DrawFn::new(move |draw| {
+        for x in (0..128).step_by(4) {
+            draw.line(x, y - 1, x + 1, y - 1, 8);
+        }
+    })
+    .into()
+}
+
 fn tile_position(camera: Vec2i, col_index: usize, row_index: usize) -> Vec2i {
     camera + vec2(col_index as i32 * 8, row_index as i32 * 8)
 }
+
+// Keeps the visible area inside the map.
+fn clamp_camera(camera: Vec2i, map: &Map) -> Vec2i {
+    let min_x = -(((map.width() - VISIBLE_COLUMNS.min(map.width())) * 8) as i32);
+    let min_y = -(((map.height() - VISIBLE_ROWS.min(map.height())) * 8) as i32);
+
+    vec2(camera.x.clamp(min_x, 0), camera.y.clamp(min_y, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pan(editor: &mut Editor, map: &Map, delta: Vec2i) {
+        let mut resources = Resources {
+            map: map.clone(),
+            ..Resources::empty()
+        };
+        let mut commands = Commands::new();
+        let from = editor.mouse_position;
+
+        for msg in [
+            Msg::SetDragging(true),
+            Msg::MouseMove(from + delta),
+            Msg::SetDragging(false),
+        ] {
+            editor.update(msg, &mut resources, &mut commands);
+        }
+    }
+
+    #[test]
+    fn panning_stays_inside_the_map() {
+        let map = Map::with_size(256, 64);
+        let mut editor = Editor::new();
+
+        pan(&mut editor, &map, vec2(10, 10));
+        assert_eq!(editor.camera, Vec2i::zero());
+
+        pan(&mut editor, &map, vec2(-10_000, -10_000));
+        assert_eq!(editor.camera, vec2(-(256 - 16) * 8, -(64 - 9) * 8));
+
+        let small_map = Map::with_size(4, 4);
+        let mut editor = Editor::new();
+        pan(&mut editor, &small_map, vec2(-100, -100));
+        assert_eq!(editor.camera, Vec2i::zero());
+    }
+
+    // A 16x16 map with the tiles from `rows`, starting at the top left corner.
+    fn resources_with(rows: &[&[u8]]) -> Resources {
+        let mut resources = Resources {
+            map: Map::with_size(16, 16),
+            ..Resources::empty()
+        };
+        for (y, row) in rows.iter().enumerate() {
+            for (x, &sprite) in row.iter().enumerate() {
+                resources.mset(x as i32, y as i32, sprite);
+            }
+        }
+
+        resources
+    }
+
+    fn row(resources: &Resources, y: i32) -> Vec<u8> {
+        (0..6).map(|x| resources.mget(x, y)).collect()
+    }
+
+    // Presses `tiles` one after the other in a single drag, then releases the mouse.
+    fn drag(
+        editor: &mut Editor,
+        resources: &mut Resources,
+        commands: &mut Commands,
+        tiles: &[(usize, usize)],
+        (shift, control): (bool, bool),
+    ) -> Vec<bool> {
+        let used = tiles
+            .iter()
+            .map(|&(x, y)| editor.tile_pressed(x, y, shift, control))
+            .collect();
+        editor.update(Msg::MouseReleased, resources, commands);
+
+        used
+    }
+
+    #[test]
+    fn dragging_a_selection_moves_it() {
+        let mut resources = resources_with(&[&[1, 2, 3], &[4, 5, 6]]);
+        let mut commands = Commands::new();
+        let mut editor = Editor::new();
+
+        let used = drag(
+            &mut editor,
+            &mut resources,
+            &mut commands,
+            &[(0, 0), (1, 1)],
+            (true, false),
+        );
+        assert_eq!(used, [true, true]);
+        assert_eq!(
+            editor.selection,
+            Some(Selection::from_corners((0, 0), (1, 1)))
+        );
+
+        // Overlapping the original cells, one tile to the right.
+        editor.tile_pressed(1, 0, false, false);
+        editor.tile_pressed(2, 0, false, false);
+        // Previewed, but not placed until released.
+        assert_eq!(
+            preview_mget(&resources, editor.selection, editor.gesture, 0, 0),
+            0
+        );
+        assert_eq!(
+            preview_mget(&resources, editor.selection, editor.gesture, 1, 0),
+            1
+        );
+        assert_eq!(row(&resources, 0), [1, 2, 3, 0, 0, 0]);
+
+        editor.update(Msg::MouseReleased, &mut resources, &mut commands);
+        assert_eq!(row(&resources, 0), [0, 1, 2, 0, 0, 0]);
+        assert_eq!(row(&resources, 1), [0, 4, 5, 0, 0, 0]);
+        assert_eq!(
+            editor.selection,
+            Some(Selection::from_corners((1, 0), (2, 1)))
+        );
+
+        // Undone in one go.
+        let mut notification = super::super::notification::State::new();
+        commands.undo(&mut notification, &mut resources);
+        assert_eq!(row(&resources, 0), [1, 2, 3, 0, 0, 0]);
+        assert_eq!(row(&resources, 1), [4, 5, 6, 0, 0, 0]);
+        commands.redo(&mut notification, &mut resources);
+        assert_eq!(row(&resources, 0), [0, 1, 2, 0, 0, 0]);
+    }
+
+    #[test]
+    fn control_copies_and_edges_clip() {
+        let mut resources = resources_with(&[&[1, 2]]);
+        let mut commands = Commands::new();
+        let mut editor = Editor::new();
+
+        drag(
+            &mut editor,
+            &mut resources,
+            &mut commands,
+            &[(0, 0), (1, 0)],
+            (true, false),
+        );
+        drag(
+            &mut editor,
+            &mut resources,
+            &mut commands,
+            &[(1, 0), (4, 0)],
+            (false, true),
+        );
+        assert_eq!(row(&resources, 0), [1, 2, 0, 1, 2, 0]);
+
+        // Half of it falls off the right edge of the map.
+        drag(
+            &mut editor,
+            &mut resources,
+            &mut commands,
+            &[(3, 0), (15, 0)],
+            (false, false),
+        );
+        assert_eq!(row(&resources, 0), [1, 2, 0, 0, 0, 0]);
+        assert_eq!(resources.mget(15, 0), 1);
+        assert_eq!(
+            editor.selection,
+            Some(Selection::from_corners((15, 0), (15, 0)))
+        );
+
+        // Clicking outside of the selection drops it, and places sprites as usual.
+        let used = drag(
+            &mut editor,
+            &mut resources,
+            &mut commands,
+            &[(5, 5)],
+            (false, false),
+        );
+        assert_eq!(used, [false]);
+        assert_eq!(editor.selection, None);
+    }
+
+    #[test]
+    fn shifting_the_map_wraps_or_empties_the_edge() {
+        let mut resources = resources_with(&[&[1, 2], &[3, 4]]);
+        resources.mset(15, 0, 9);
+        let mut commands = Commands::new();
+
+        shift_map(&mut resources, &mut commands, ShiftDirection::Right, false);
+        assert_eq!(row(&resources, 0), [0, 1, 2, 0, 0, 0]);
+        assert_eq!(row(&resources, 1), [0, 3, 4, 0, 0, 0]);
+        // Pushed off the right edge.
+        assert_eq!(resources.mget(15, 0), 0);
+
+        let mut notification = super::super::notification::State::new();
+        commands.undo(&mut notification, &mut resources);
+        assert_eq!(row(&resources, 0), [1, 2, 0, 0, 0, 0]);
+        assert_eq!(resources.mget(15, 0), 9);
+
+        shift_map(&mut resources, &mut commands, ShiftDirection::Left, true);
+        assert_eq!(row(&resources, 0), [2, 0, 0, 0, 0, 0]);
+        assert_eq!(resources.mget(14, 0), 9);
+        assert_eq!(resources.mget(15, 0), 1);
+
+        shift_map(&mut resources, &mut commands, ShiftDirection::Up, true);
+        assert_eq!(row(&resources, 0), [4, 0, 0, 0, 0, 0]);
+        assert_eq!(resources.mget(0, 15), 2);
+    }
+
+    #[test]
+    fn block_mode_stamps_2x2_blocks_on_even_cells() {
+        let mut resources = resources_with(&[]);
+        let mut commands = Commands::new();
+        let mut editor = Editor::new();
+        editor.update(Msg::ToggleBlocks, &mut resources, &mut commands);
+        editor.select_tile(3);
+
+        // Anywhere in the block's cells.
+        assert!(editor.place_tile(3, 1, &mut resources));
+        assert_eq!(row(&resources, 0), [0, 0, 3, 4, 0, 0]);
+        assert_eq!(row(&resources, 1), [0, 0, 19, 20, 0, 0]);
+
+        // Blocks don't wrap around from the last column or row.
+        for tile in [15, 240] {
+            editor.select_tile(tile);
+            assert_eq!(editor.block(&resources.sprite_sheet), None);
+            assert!(!editor.place_tile(0, 0, &mut resources));
+        }
+        assert_eq!(row(&resources, 0), [0, 0, 3, 4, 0, 0]);
+
+        // Single tiles are placed as usual.
+        editor.update(Msg::ToggleBlocks, &mut resources, &mut commands);
+        assert!(editor.place_tile(5, 1, &mut resources));
+        assert_eq!(resources.mget(5, 1), 240);
+    }
+}
@@ -0,0 +1,287 @@
+//! An overview of the whole map in a corner of the map editor.
+//! Clicking (or dragging) on it moves the view there.
+
+use std::fmt::Debug;
+
+use crate::runtime::sprite_sheet::{Sprite, SpriteSheet};
+use crate::ui::{DispatchEvent, Element, Widget};
+use crate::util::vec2::Vec2i;
+use crate::{Color, Event, MouseButton, MouseEvent, Palette, Pico8, Resources};
+
+// Largest size of the minimap, in pixels. Maps too big for it have several cells per pixel.
+const MAX_WIDTH: i32 = 64;
+const MAX_HEIGHT: i32 = 32;
+
+#[derive(Debug)]
+pub(crate) struct State {
+    dragging: bool,
+    // Each sprite as it was when its colors were last added up, and that sum.
+    colors: Vec<(Sprite, [u32; 3])>,
+    // The palette the sums were made with.
+    palette: Palette,
+}
+
+impl State {
+    pub(crate) fn new() -> Self {
+        Self {
+            dragging: false,
+            colors: vec![],
+            palette: Palette::pico8(),
+        }
+    }
+
+    // Only the sprites that changed since the last frame are added up again.
+    fn refresh(&mut self, sprite_sheet: &SpriteSheet, palette: &Palette) {
+        // Every sum changes with the palette.
+        if *palette != self.palette {
+            self.palette = *palette;
+            self.colors.clear();
+        }
+
+        // New entries are empty sprites, which add up to black.
+        self.colors
+            .resize(sprite_sheet.sprite_count(), (Sprite::EMPTY, [0; 3]));
+
+        for (index, (cached, sum)) in self.colors.iter_mut().enumerate() {
+            let sprite = sprite_sheet.get_sprite(index);
+            if cached != sprite {
+                *cached = *sprite;
+                *sum = color_sum(sprite, palette);
+            }
+        }
+    }
+}
+
+/// Where the minimap of a map goes on the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Layout {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    // Map cells (in each direction) per pixel of the minimap.
+    scale: i32,
+}
+
+impl Layout {
+    /// In the top right corner of the (128 pixel wide) map view drawn at `origin`.
+    pub(crate) fn new((map_width, map_height): (i32, i32), origin: Vec2i) -> Self {
+        let scale = (map_width.max(1) as u32)
+            .div_ceil(MAX_WIDTH as u32)
+            .max((map_height.max(1) as u32).div_ceil(MAX_HEIGHT as u32)) as i32;
+        let (width, height) = (
+            (map_width as u32).div_ceil(scale as u32) as i32,
+            (map_height as u32).div_ceil(scale as u32) as i32,
+        );
+
+        Self {
+            x: origin.x + 126 - width,
+            y: origin.y + 2,
+            width,
+            height,
+            scale,
+        }
+    }
+
+    /// Whether a point on the screen is on the minimap (border included).
+    pub(crate) fn contains(&self, x: i32, y: i32) -> bool {
+        (self.x - 1..=self.x + self.width).contains(&x)
+            && (self.y - 1..=self.y + self.height).contains(&y)
+    }
+
+    // The map cell under a point on the screen, the closest one if it's off the minimap.
+    fn cell_at(&self, x: i32, y: i32) -> (usize, usize) {
+        let column = (x - self.x).clamp(0, self.width - 1) * self.scale;
+        let row = (y - self.y).clamp(0, self.height - 1) * self.scale;
+
+        (column as usize, row as usize)
+    }
+}
+
+pub(crate) struct Minimap<'a, Msg> {
+    layout: Layout,
+    // One per pixel of the minimap, row by row.
+    pixels: Vec<Color>,
+    // The part of the map that's on screen, in map cells: x, y, width and height.
+    viewport: (i32, i32, i32, i32),
+    on_press: fn(usize, usize) -> Msg,
+    state: &'a mut State,
+}
+
+/// `viewport` is the part of the map that's visible, in map cells: x, y, width and height.
+/// `on_press` gets the map cell that was clicked or dragged over.
+pub(crate) fn view<'a, Msg: Copy + Debug + 'a>(
+    state: &'a mut State,
+    resources: &Resources,
+    origin: Vec2i,
+    viewport: (i32, i32, i32, i32),
+    on_press: fn(usize, usize) -> Msg,
+) -> Element<'a, Msg> {
+    state.refresh(&resources.sprite_sheet, &resources.palette);
+
+    let (map_width, map_height) = resources.map_size();
+    let layout = Layout::new((map_width, map_height), origin);
+    let scale = layout.scale;
+
+    let pixels = (0..layout.height)
+        .flat_map(|y| (0..layout.width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let mut sum = [0; 3];
+            let mut cells = 0;
+            for cell_y in (y * scale..(y + 1) * scale).filter(|&cell_y| cell_y < map_height) {
+                for cell_x in (x * scale..(x + 1) * scale).filter(|&cell_x| cell_x < map_width) {
+                    let sprite = resources.mget(cell_x, cell_y) as usize;
+                    for (total, component) in sum.iter_mut().zip(state.colors[sprite].1) {
+                        *total += component;
+                    }
+                    cells += 1;
+                }
+            }
+
+            let pixel_count = cells * Sprite::WIDTH as u32 * Sprite::HEIGHT as u32;
+            nearest_color(
+                sum.map(|total| total / pixel_count.max(1)),
+                &resources.palette,
+            )
+        })
+        .collect();
+
+    Minimap {
+        layout,
+        pixels,
+        viewport,
+        on_press,
+        state,
+    }
+    .into()
+}
+
+impl<'a, Msg: Copy + Debug> Widget for Minimap<'a, Msg> {
+    type Msg = Msg;
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        cursor_position: (i32, i32),
+        dispatch_event: &mut DispatchEvent<Self::Msg>,
+    ) {
+        let press = |(x, y): (i32, i32), dispatch_event: &mut DispatchEvent<Msg>| {
+            let (column, row) = self.layout.cell_at(x, y);
+            dispatch_event.call((self.on_press)(column, row));
+        };
+
+        match event {
+            Event::Mouse(MouseEvent::Down(MouseButton::Left))
+                if self.layout.contains(cursor_position.0, cursor_position.1) =>
+            {
+                press(cursor_position, dispatch_event);
+                self.state.dragging = true;
+                // The map underneath shouldn't be painted on.
+                dispatch_event.consume();
+            }
+            Event::Mouse(MouseEvent::Move { x, y }) if self.state.dragging => {
+                press((x, y), dispatch_event);
+            }
+            Event::Mouse(MouseEvent::Up(MouseButton::Left)) => {
+                self.state.dragging = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn draw(&mut self, draw: &mut Pico8) {
+        let Layout {
+            x,
+            y,
+            width,
+            height,
+            scale,
+        } = self.layout;
+
+        draw.rect(x - 1, y - 1, x + width, y + height, 6);
+        for (index, &color) in self.pixels.iter().enumerate() {
+            let index = index as i32;
+            draw.pset(x + index % width, y + index / width, color);
+        }
+
+        let (view_x, view_y, view_width, view_height) = self.viewport;
+        draw.rect(
+            x + view_x / scale,
+            y + view_y / scale,
+            x + (view_x + view_width - 1) / scale,
+            y + (view_y + view_height - 1) / scale,
+            7,
+        );
+    }
+}
+
+fn color_sum(sprite: &Sprite, palette: &Palette) -> [u32; 3] {
+    sprite
+        .iter_pixels()
+        .fold([0; 3], |[r, g, b], (_, _, color)| {
+            let (pixel_r, pixel_g, pixel_b) = palette.rgb(color);
+
+            [r + pixel_r as u32, g + pixel_g as u32, b + pixel_b as u32]
+        })
+}
+
+fn nearest_color([r, g, b]: [u32; 3], palette: &Palette) -> Color {
+    Color::all()
+        .min_by_key(|color| {
+            let (color_r, color_g, color_b) = palette.rgb(*color);
+
+            [(r, color_r), (g, color_g), (b, color_b)]
+                .iter()
+                .map(|&(a, b)| (a as i32 - b as i32).pow(2))
+                .sum::<i32>()
+        })
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::vec2::vec2;
+
+    #[test]
+    fn big_maps_are_scaled_down_to_fit() {
+        assert_eq!(
+            Layout::new((128, 64), vec2(0, 8)),
+            Layout {
+                x: 62,
+                y: 10,
+                width: 64,
+                height: 32,
+                scale: 2
+            }
+        );
+        assert_eq!(Layout::new((40, 20), vec2(0, 8)).scale, 1);
+        assert_eq!(Layout::new((256, 16), vec2(0, 8)).width, 64);
+    }
+
+    #[test]
+    fn sprites_are_averaged_again_when_they_change() {
+        let mut resources = Resources::empty();
+        for x in 0..8 {
+            for y in 0..8 {
+                resources.sprite_sheet.get_sprite_mut(2).pset(x, y, 8);
+            }
+        }
+        let mut state = State::new();
+
+        state.refresh(&resources.sprite_sheet, &resources.palette);
+        assert_eq!(state.colors[0].1, [0; 3]);
+        assert_eq!(
+            nearest_color(state.colors[2].1.map(|sum| sum / 64), &resources.palette),
+            Color::RED
+        );
+
+        resources.sprite_sheet.get_sprite_mut(2).pset(0, 0, 0);
+        state.refresh(&resources.sprite_sheet, &resources.palette);
+        let (r, g, b) = Color::RED.rgb();
+        assert_eq!(
+            state.colors[2].1,
+            [r as u32 * 63, g as u32 * 63, b as u32 * 63]
+        );
+    }
+}
@@ -1,15 +1,107 @@
+use crate::font;
 use crate::runtime::draw_data::colors;
-use crate::ui::DispatchEvent;
+use crate::ui::button::{self, Button};
+use crate::ui::scroll_area::{self, ScrollArea};
+use crate::ui::text::Text;
+use crate::ui::{DispatchEvent, DrawFn, Element, Tree};
 use crate::Pico8;
 use crate::{ui::Widget, Event};
+use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 
+/// How many notifications are kept in the history, the oldest ones are dropped first.
+pub const HISTORY_LENGTH: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    fn name(self) -> &'static str {
+        match self {
+            Severity::Info => "INFO",
+            Severity::Warning => "WARNING",
+            Severity::Error => "ERROR",
+        }
+    }
+
+    // On the log's dark blue.
+    fn color(self) -> u8 {
+        match self {
+            Severity::Info => 6,
+            Severity::Warning => 9,
+            Severity::Error => 8,
+        }
+    }
+}
+
+/// A notification as it was shown, for the history.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    /// Number of the frame it was shown on, see [`Event::Tick`].
+    pub frame: u64,
+    pub severity: Severity,
+    pub content: String,
+}
+
 #[derive(Debug)]
 pub struct State {
+    popup: Popup,
+    history: History,
+}
+
+/// The notification sliding in over the bottom bar.
+#[derive(Debug)]
+pub struct Popup {
     timer: i32,
     enter_state: EnterState,
     content: String,
+    // The last frame number seen, what new notifications are stamped with.
+    frame: u64,
+}
+
+/// Every notification since the editor started (up to [`HISTORY_LENGTH`]),
+/// whether the log was open or not.
+#[derive(Debug)]
+pub struct History {
+    // Oldest first.
+    entries: VecDeque<Entry>,
+}
+
+impl History {
+    fn record(&mut self, entry: Entry) {
+        if self.entries.len() == HISTORY_LENGTH {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// Newest first.
+    pub fn iter(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter().rev()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// As listed in the log, one notification per line.
+    pub fn text(&self) -> String {
+        self.iter()
+            .map(|entry| {
+                format!(
+                    "{} {} {}\n",
+                    entry.frame,
+                    entry.severity.name(),
+                    entry.content
+                )
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -43,17 +135,59 @@ impl EnterState {
 impl State {
     pub fn new() -> Self {
         Self {
-            timer: Left.duration(),
-            enter_state: Left,
-            content: "".to_owned(),
+            popup: Popup {
+                timer: Left.duration(),
+                enter_state: Left,
+                content: "".to_owned(),
+                frame: 0,
+            },
+            history: History {
+                entries: VecDeque::with_capacity(HISTORY_LENGTH),
+            },
         }
     }
 
     pub fn alert(&mut self, content: String) {
-        self.content = content;
-        self.reset()
+        self.notify(Severity::Info, content)
+    }
+
+    /// Like [`State::alert`], for something that went wrong but was dealt with.
+    pub fn warn(&mut self, content: String) {
+        self.notify(Severity::Warning, content)
+    }
+
+    /// Like [`State::alert`], for something that went wrong. Stands out in the history.
+    pub fn error(&mut self, content: String) {
+        self.notify(Severity::Error, content)
+    }
+
+    fn notify(&mut self, severity: Severity, content: String) {
+        self.history.record(Entry {
+            frame: self.popup.frame,
+            severity,
+            content: content.clone(),
+        });
+
+        self.popup.content = content;
+        self.popup.reset()
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// The popup for [`Notification`] and the history for [`log_view`], both showing at once.
+    pub fn split(&mut self) -> (&mut Popup, &History) {
+        (&mut self.popup, &self.history)
+    }
+
+    #[cfg(test)]
+    pub fn content(&self) -> &str {
+        &self.popup.content
     }
+}
 
+impl Popup {
     fn reset(&mut self) {
         self.set_state(Entering)
     }
@@ -63,7 +197,9 @@ impl State {
         self.enter_state = state;
     }
 
-    fn tick(&mut self) {
+    fn tick(&mut self, frame: u64) {
+        self.frame = frame;
+
         match self.enter_state {
             Left => {}
             _ => {
@@ -76,22 +212,17 @@ impl State {
             }
         }
     }
-
-    #[cfg(test)]
-    pub fn content(&self) -> &str {
-        &self.content
-    }
 }
 
 pub struct Notification<'a, Msg> {
-    state: &'a mut State,
+    state: &'a mut Popup,
     phantom: PhantomData<Msg>,
 }
 
 use EnterState::*;
 
 impl<'a, Msg> Notification<'a, Msg> {
-    pub fn new(state: &'a mut State) -> Self {
+    pub fn new(state: &'a mut Popup) -> Self {
         Self {
             state,
             phantom: PhantomData,
@@ -105,8 +236,8 @@ impl<'a, Msg: Copy + Debug> Widget for Notification<'a, Msg> {
     fn on_event(&mut self, event: Event, _: (i32, i32), _: &mut DispatchEvent<Self::Msg>) {
         let state = &mut self.state;
 
-        if let Event::Tick { .. } = event {
-            state.tick()
+        if let Event::Tick { frame, .. } = event {
+            state.tick(frame)
         }
     }
 
@@ -128,6 +259,124 @@ impl<'a, Msg: Copy + Debug> Widget for Notification<'a, Msg> {
 
         // Cover regular bar messages
         draw.rectfill(0, 121, 127, 127, colors::RED);
-        draw.print(&self.state.content, x, y, colors::LIGHT_PEACH);
+        // One less pixel of room, for the shadow.
+        let content = font::truncate(&self.state.content, 127 - x);
+        draw.print_shadowed(&content, x, y, colors::LIGHT_PEACH, colors::DARK_PURPLE);
+    }
+}
+
+/// The history, newest first, covering everything between the top and bottom bars.
+/// Scrolled with the arrow keys while the cursor is over it.
+pub fn log_view<'a, Msg: Copy + Debug + 'a>(
+    history: &'a History,
+    scroll: &'a mut scroll_area::State,
+    copy_button: &'a mut button::State,
+    on_copy: Msg,
+) -> Element<'a, Msg> {
+    const LIST_Y: i32 = 18;
+    const LIST_HEIGHT: i32 = 102;
+    // Where the notifications start, after their frame number.
+    const CONTENT_X: i32 = 26;
+
+    let mut entries = Vec::with_capacity(2 * history.len());
+    let mut y = LIST_Y;
+    for entry in history.iter() {
+        let frame = entry.frame;
+        entries.push(DrawFn::new(move |draw| draw.print(&frame.to_string(), 2, y, 13)).into());
+
+        let content = Text::new(&entry.content, CONTENT_X, y, entry.severity.color())
+            .max_width(128 - CONTENT_X - 5);
+        y += content.height() + 2;
+        entries.push(content.into());
+    }
+    let content_height = y - LIST_Y;
+
+    let copy_width = font::measure_text("COPY").0 + 2;
+    let copy = Button::new(
+        126 - copy_width,
+        9,
+        copy_width,
+        7,
+        Some(on_copy),
+        copy_button,
+        DrawFn::new(move |draw| {
+            draw.rectfill(0, 0, copy_width - 1, 6, 13);
+            draw.print("COPY", 1, 1, 7);
+        }),
+    )
+    .tooltip("WRITE TO NOTIFICATIONS.LOG")
+    .focusable();
+
+    Tree::with_capacity(4)
+        .push(DrawFn::new(|draw| {
+            draw.rectfill(0, 8, 127, 120, 1);
+            draw.print("NOTIFICATIONS (CTRL+H)", 2, 10, 7);
+        }))
+        .push(copy)
+        .push(ScrollArea::new(
+            Tree::with_children(entries),
+            (0, LIST_Y, 128, LIST_HEIGHT),
+            content_height,
+            scroll,
+        ))
+        .push(DrawFn::new(move |draw| {
+            if content_height == 0 {
+                draw.print("NOTHING YET", 2, LIST_Y, 13);
+            }
+        }))
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contents(history: &History) -> Vec<&str> {
+        history.iter().map(|entry| entry.content.as_str()).collect()
+    }
+
+    #[test]
+    fn the_history_keeps_the_latest_notifications() {
+        let mut state = State::new();
+        for n in 0..HISTORY_LENGTH + 5 {
+            state.alert(n.to_string());
+        }
+
+        let history = contents(state.history());
+        assert_eq!(history.len(), HISTORY_LENGTH);
+        // Newest first, the first five were dropped.
+        assert_eq!(history[0], (HISTORY_LENGTH + 4).to_string());
+        assert_eq!(history[HISTORY_LENGTH - 1], "5");
+    }
+
+    #[test]
+    fn entries_have_the_frame_they_were_shown_on() {
+        let mut state = State::new();
+        state.alert("SAVED".to_owned());
+        state.popup.tick(42);
+        state.error("COULDN'T EXPORT SFX".to_owned());
+
+        let entries: Vec<_> = state.history().iter().cloned().collect();
+        assert_eq!(
+            entries,
+            [
+                Entry {
+                    frame: 42,
+                    severity: Severity::Error,
+                    content: "COULDN'T EXPORT SFX".to_owned(),
+                },
+                Entry {
+                    frame: 0,
+                    severity: Severity::Info,
+                    content: "SAVED".to_owned(),
+                },
+            ]
+        );
+        assert_eq!(
+            state.history().text(),
+            "42 ERROR COULDN'T EXPORT SFX\n0 INFO SAVED\n"
+        );
+        // Errors pop up like any other notification.
+        assert_eq!(state.content(), "COULDN'T EXPORT SFX");
     }
 }
@@ -0,0 +1,150 @@
+//! Palette swaps the selected sprite can be previewed with, like games draw it with `pal`
+//! (team colors, damage flashes...). They only live in the editor settings,
+//! the sprite itself never changes.
+
+use itertools::Itertools;
+
+use crate::{Color, Pico8};
+
+pub(crate) const RULE_COUNT: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PalRemap {
+    // Drawn as (from, to), rules from a color to itself are unused.
+    rules: [(Color, Color); RULE_COUNT],
+}
+
+impl PalRemap {
+    /// No rules in use.
+    pub(crate) fn new() -> Self {
+        Self {
+            rules: [(Color::BLACK, Color::BLACK); RULE_COUNT],
+        }
+    }
+
+    pub(crate) fn rule(&self, rule: usize) -> (Color, Color) {
+        self.rules[rule]
+    }
+
+    /// Moves one of the rule's colors to the next (or previous) one in the palette, wrapping around.
+    pub(crate) fn step(&mut self, rule: usize, to: bool, forward: bool) {
+        let (from, new) = &mut self.rules[rule];
+        let color = if to { new } else { from };
+        let step = if forward { 1 } else { 15 };
+
+        *color = Color::from_index_lossy((color.index() + step) % 16);
+    }
+
+    fn used_rules(self) -> impl Iterator<Item = (Color, Color)> {
+        self.rules.into_iter().filter(|(from, to)| from != to)
+    }
+
+    /// Swaps the colors for what's drawn next, like the rules' `pal` calls would.
+    pub(crate) fn apply(self, draw: &mut Pico8) {
+        for (from, to) in self.used_rules() {
+            draw.pal(from, to);
+        }
+    }
+
+    /// The `pal` calls a game would make to draw with the rules, one per line.
+    pub(crate) fn to_code(self) -> String {
+        self.used_rules()
+            .map(|(from, to)| format!("draw.pal({}, {});\n", from.index(), to.index()))
+            .collect()
+    }
+
+    /// The rules' colors, from and to for each rule, separated by spaces.
+    pub(crate) fn to_human_readable(self) -> String {
+        self.rules
+            .iter()
+            .flat_map(|(from, to)| [from.index(), to.index()])
+            .join(" ")
+    }
+
+    /// Rules written by [`PalRemap::to_human_readable`].
+    pub(crate) fn from_human_readable(str: &str) -> Option<Self> {
+        let colors: Vec<Color> = str
+            .split_whitespace()
+            .map(|color| {
+                color
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|&color| color < 16)
+                    .map(Color::from)
+            })
+            .collect::<Option<_>>()?;
+        if colors.len() != 2 * RULE_COUNT {
+            return None;
+        }
+        let rules: Vec<_> = colors.chunks(2).map(|pair| (pair[0], pair[1])).collect();
+
+        Some(Self {
+            rules: rules.try_into().unwrap(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::draw_data::DrawData;
+    use crate::runtime::state::State;
+    use crate::Resources;
+
+    // Rule 0 turns red into blue, rule 2 dark blue into white.
+    fn remap() -> PalRemap {
+        let mut remap = PalRemap::new();
+        for _ in 0..8 {
+            remap.step(0, false, true);
+        }
+        for _ in 0..4 {
+            remap.step(0, true, false);
+        }
+        remap.step(2, false, true);
+        for _ in 0..7 {
+            remap.step(2, true, true);
+        }
+
+        remap
+    }
+
+    #[test]
+    fn colors_step_around_the_palette() {
+        let remap = remap();
+
+        assert_eq!(remap.rule(0), (Color::RED, Color::BLUE));
+        assert_eq!(remap.rule(1), (Color::BLACK, Color::BLACK));
+        assert_eq!(remap.rule(2), (Color::DARK_BLUE, Color::WHITE));
+    }
+
+    #[test]
+    fn unused_rules_arent_copied() {
+        assert_eq!(remap().to_code(), "draw.pal(8, 12);\ndraw.pal(1, 7);\n");
+        assert_eq!(PalRemap::new().to_code(), "");
+    }
+
+    #[test]
+    fn rules_round_trip() {
+        let remap = remap();
+
+        assert_eq!(remap.to_human_readable(), "8 12 0 0 1 7 0 0");
+        assert_eq!(
+            PalRemap::from_human_readable(&remap.to_human_readable()),
+            Some(remap)
+        );
+        assert_eq!(PalRemap::from_human_readable("8 12 0 0 1 7 0"), None);
+        assert_eq!(PalRemap::from_human_readable("8 12 0 0 1 7 0 16"), None);
+    }
+
+    #[test]
+    fn drawing_with_the_rules_applied() {
+        let mut pico8 = Pico8::new(DrawData::new(), State::new(), Resources::empty());
+
+        remap().apply(&mut pico8);
+        pico8.rectfill(0, 0, 0, 0, Color::RED);
+        pico8.rectfill(1, 0, 1, 0, Color::GREEN);
+
+        assert_eq!(pico8.draw_data.pixel(0, 0), Some(Color::BLUE));
+        assert_eq!(pico8.draw_data.pixel(1, 0), Some(Color::GREEN));
+    }
+}
@@ -0,0 +1,417 @@
+//! Editing the colors the screen is shown with, saved in `palette.txt`.
+//! The whole editor is drawn through the palette, so the sprite sheet below it is the preview.
+
+use super::{with_help, Help};
+use crate::ui::button::{self, Button};
+use crate::ui::text_input::{self, TextInput};
+use crate::ui::{DispatchEvent, DrawFn, Element, Tree, Widget};
+use crate::{Color, Event, MouseButton, MouseEvent, Palette, Pico8, Resources};
+use std::fmt::Debug;
+
+// Top left corner of the 4x4 grid of swatches.
+const SWATCHES_X: i32 = 2;
+const SWATCHES_Y: i32 = 10;
+const SWATCH_SIZE: i32 = 14;
+// Left edge of the selected color's details.
+const DETAILS_X: i32 = 66;
+// Top of the red, green and blue sliders, one under the other.
+const SLIDERS_Y: i32 = 30;
+const SLIDER_WIDTH: i32 = 32;
+
+const CHANNEL_NAMES: [&str; 3] = ["R", "G", "B"];
+const CHANNEL_HELP: [&str; 3] = ["DRAG: SET RED", "DRAG: SET GREEN", "DRAG: SET BLUE"];
+// Color each slider is filled with.
+const CHANNEL_COLORS: [u8; 3] = [8, 11, 12];
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Msg {
+    ColorSelected(Color),
+    // `channel` is 0 for red, 1 for green and 2 for blue.
+    ChannelSet { channel: usize, value: u8 },
+    HexOpened,
+    HexSubmitted,
+    Reset,
+}
+
+#[derive(Debug)]
+pub(crate) struct Editor {
+    selected: Color,
+    swatch_buttons: Vec<button::State>,
+    sliders: [SliderState; 3],
+    hex_button: button::State,
+    hex_input: text_input::State,
+    reset_button: button::State,
+}
+
+impl Editor {
+    pub(crate) fn new() -> Self {
+        Self {
+            selected: Color::BLACK,
+            swatch_buttons: vec![button::State::new(); 16],
+            sliders: [SliderState::new(), SliderState::new(), SliderState::new()],
+            hex_button: button::State::new(),
+            hex_input: text_input::State::new(),
+            reset_button: button::State::new(),
+        }
+    }
+
+    pub(crate) fn update(&mut self, msg: Msg, resources: &mut Resources) {
+        let (r, g, b) = resources.palette.rgb(self.selected);
+
+        match msg {
+            Msg::ColorSelected(color) => {
+                self.selected = color;
+            }
+            Msg::ChannelSet { channel, value } => {
+                let rgb = match channel {
+                    0 => (value, g, b),
+                    1 => (r, value, b),
+                    _ => (r, g, value),
+                };
+                resources.palette.set_rgb(self.selected, rgb);
+            }
+            Msg::HexOpened => {
+                self.hex_input.set_text(String::new());
+                self.hex_input.focus();
+            }
+            Msg::HexSubmitted => {
+                // Anything that isn't a color just closes the field.
+                if let Some(rgb) = Palette::parse_hex(self.hex_input.text()) {
+                    resources.palette.set_rgb(self.selected, rgb);
+                }
+                self.hex_input.blur();
+            }
+            Msg::Reset => {
+                resources.palette = Palette::pico8();
+            }
+        }
+    }
+
+    pub(crate) fn view<'a>(
+        &'a mut self,
+        resources: &Resources,
+        to_editor_msg: &'a (impl Fn(Msg) -> super::Msg + Copy),
+    ) -> Element<'a, super::Msg> {
+        let selected = self.selected;
+        let (r, g, b) = resources.palette.rgb(selected);
+
+        // Behind the swatches, so that the ones the color of the background still stand out.
+        let grid_size = 4 * (SWATCH_SIZE + 1);
+        // The background, swatches, color number, hex field, channels and reset button.
+        let mut children: Vec<Element<'a, super::Msg>> =
+            Vec::with_capacity(1 + 16 + 2 + 2 * self.sliders.len() + 1);
+        children.push(
+            DrawFn::new(move |draw| {
+                draw.rectfill(
+                    SWATCHES_X - 1,
+                    SWATCHES_Y - 1,
+                    SWATCHES_X + grid_size - 1,
+                    SWATCHES_Y + grid_size - 1,
+                    0,
+                );
+            })
+            .into(),
+        );
+        children.extend(
+            Color::all()
+                .zip(self.swatch_buttons.iter_mut())
+                .map(|(color, state)| swatch(color, color == selected, state, to_editor_msg)),
+        );
+
+        children.push(
+            DrawFn::new(move |draw| {
+                draw.print(
+                    &format!("COLOUR {}", selected.index()),
+                    DETAILS_X,
+                    SWATCHES_Y + 1,
+                    7,
+                );
+            })
+            .into(),
+        );
+        children.push(hex_field(
+            Palette::to_hex((r, g, b)),
+            &mut self.hex_button,
+            &mut self.hex_input,
+            to_editor_msg,
+        ));
+
+        for (channel, (state, value)) in self.sliders.iter_mut().zip([r, g, b]).enumerate() {
+            let y = SLIDERS_Y + channel as i32 * 9;
+
+            children.push(
+                DrawFn::new(move |draw| {
+                    draw.print(CHANNEL_NAMES[channel], DETAILS_X, y + 1, 6);
+                    draw.print(
+                        &format!("{value:>3}"),
+                        DETAILS_X + 8 + SLIDER_WIDTH + 3,
+                        y + 1,
+                        7,
+                    );
+                })
+                .into(),
+            );
+            let slider = ChannelSlider {
+                x: DETAILS_X + 6,
+                y,
+                value,
+                color: CHANNEL_COLORS[channel],
+                state,
+                on_set: Box::new(move |value| to_editor_msg(Msg::ChannelSet { channel, value })),
+            };
+            children.push(with_help(
+                slider,
+                (
+                    DETAILS_X + 6,
+                    y,
+                    SLIDER_WIDTH,
+                    ChannelSlider::<super::Msg>::HEIGHT,
+                ),
+                Help::Text(CHANNEL_HELP[channel]),
+            ));
+        }
+
+        let reset_width = crate::measure_text("RESET").0 + 2;
+        let reset = Button::new(
+            DETAILS_X,
+            SLIDERS_Y + 29,
+            reset_width,
+            7,
+            Some(to_editor_msg(Msg::Reset)),
+            &mut self.reset_button,
+            DrawFn::new(move |draw| {
+                draw.rectfill(0, 0, reset_width - 1, 6, 13);
+                draw.print("RESET", 1, 1, 7);
+            }),
+        )
+        .tooltip("BACK TO PICO8'S COLORS")
+        .focusable();
+        children.push(with_help(
+            reset,
+            (DETAILS_X, SLIDERS_Y + 29, reset_width, 7),
+            Help::Text("RESET ALL 16 COLOURS"),
+        ));
+
+        Tree::with_children(children).into()
+    }
+}
+
+fn swatch<'a>(
+    color: Color,
+    selected: bool,
+    state: &'a mut button::State,
+    to_editor_msg: &'a (impl Fn(Msg) -> super::Msg + Copy),
+) -> Element<'a, super::Msg> {
+    let index = color.index() as i32;
+    let step = SWATCH_SIZE + 1;
+    let (x, y) = (SWATCHES_X + index % 4 * step, SWATCHES_Y + index / 4 * step);
+
+    let button = Button::new(
+        x,
+        y,
+        SWATCH_SIZE,
+        SWATCH_SIZE,
+        Some(to_editor_msg(Msg::ColorSelected(color))),
+        state,
+        DrawFn::new(move |draw| {
+            draw.rectfill(0, 0, SWATCH_SIZE - 1, SWATCH_SIZE - 1, color);
+            if selected {
+                draw.rect(0, 0, SWATCH_SIZE - 1, SWATCH_SIZE - 1, 7);
+                draw.rect(1, 1, SWATCH_SIZE - 2, SWATCH_SIZE - 2, 0);
+            }
+        }),
+    )
+    .event_on_press();
+
+    with_help(
+        button,
+        (x, y, SWATCH_SIZE, SWATCH_SIZE),
+        Help::Text("CLICK: EDIT THIS COLOUR"),
+    )
+}
+
+// The selected color as `#RRGGBB`, clicking it opens a field to type a new one.
+fn hex_field<'a>(
+    hex: String,
+    button: &'a mut button::State,
+    input: &'a mut text_input::State,
+    to_editor_msg: &'a (impl Fn(Msg) -> super::Msg + Copy),
+) -> Element<'a, super::Msg> {
+    const Y: i32 = SWATCHES_Y + 9;
+
+    let label = DrawFn::new(|draw| draw.print("#", DETAILS_X, Y + 1, 6));
+    let field: Element<'a, super::Msg> = if input.is_focused() {
+        TextInput::new(DETAILS_X + 5, Y, 30, input)
+            .max_length(6)
+            .on_submit(to_editor_msg(Msg::HexSubmitted))
+            .into()
+    } else {
+        let width = crate::measure_text(&hex).0 + 2;
+
+        Button::new(
+            DETAILS_X + 5,
+            Y,
+            width,
+            7,
+            Some(to_editor_msg(Msg::HexOpened)),
+            button,
+            DrawFn::new(move |draw| {
+                draw.rectfill(0, 0, width - 1, 6, 6);
+                draw.print(&hex, 1, 1, 13);
+            }),
+        )
+        .into()
+    };
+
+    // Around both the button and the text field, which is wider.
+    let field = with_help(
+        field,
+        (DETAILS_X + 5, Y, 30, 7),
+        Help::Text("TYPE A HEX COLOUR"),
+    );
+
+    Tree::new().push(label).push(field).into()
+}
+
+/// Whether a [`ChannelSlider`] is being dragged.
+#[derive(Debug)]
+struct SliderState {
+    held: bool,
+}
+
+impl SliderState {
+    fn new() -> Self {
+        Self { held: false }
+    }
+}
+
+// A bar from 0 to 255, set by clicking (or dragging) along it.
+struct ChannelSlider<'a, Msg> {
+    x: i32,
+    y: i32,
+    value: u8,
+    color: u8,
+    state: &'a mut SliderState,
+    on_set: Box<dyn Fn(u8) -> Msg + 'a>,
+}
+
+impl<Msg> ChannelSlider<'_, Msg> {
+    const HEIGHT: i32 = 7;
+
+    fn contains(&self, (x, y): (i32, i32)) -> bool {
+        (self.x..self.x + SLIDER_WIDTH).contains(&x) && (self.y..self.y + Self::HEIGHT).contains(&y)
+    }
+
+    // The ends of the bar are 0 and 255, dragging past them sticks to them.
+    fn value_at(&self, x: i32) -> u8 {
+        ((x - self.x).clamp(0, SLIDER_WIDTH - 1) * 255 / (SLIDER_WIDTH - 1)) as u8
+    }
+}
+
+impl<'a, Msg: Copy + Debug + 'a> Widget for ChannelSlider<'a, Msg> {
+    type Msg = Msg;
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        cursor_position: (i32, i32),
+        dispatch_event: &mut DispatchEvent<Self::Msg>,
+    ) {
+        match event {
+            Event::Mouse(MouseEvent::Down(MouseButton::Left)) if self.contains(cursor_position) => {
+                self.state.held = true;
+            }
+            Event::Mouse(MouseEvent::Move { .. }) => {}
+            Event::Mouse(MouseEvent::Up(_)) => {
+                self.state.held = false;
+                return;
+            }
+            _ => return,
+        }
+
+        if self.state.held {
+            dispatch_event.call((self.on_set)(self.value_at(cursor_position.0)));
+        }
+    }
+
+    fn draw(&mut self, draw: &mut Pico8) {
+        let right = self.x + SLIDER_WIDTH - 1;
+        let bottom = self.y + Self::HEIGHT - 1;
+        let filled = self.x + self.value as i32 * (SLIDER_WIDTH - 1) / 255;
+
+        draw.rectfill(self.x, self.y + 1, right, bottom - 1, 0);
+        draw.rectfill(self.x, self.y + 1, filled, bottom - 1, self.color);
+        draw.line(filled, self.y, filled, bottom, 7);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::testing::{mouse_down, mouse_move, mouse_up, Harness};
+
+    fn slider(state: &mut SliderState) -> Element<'_, u8> {
+        ChannelSlider {
+            x: 10,
+            y: 10,
+            value: 0,
+            color: 8,
+            state,
+            on_set: Box::new(|value| value),
+        }
+        .into()
+    }
+
+    #[test]
+    fn dragging_a_slider_sets_its_value() {
+        let mut harness = Harness::new();
+        let mut state = SliderState::new();
+
+        let events = [
+            // Not on the slider.
+            mouse_move(0, 0),
+            mouse_down(),
+            mouse_move(20, 12),
+            mouse_up(),
+            mouse_move(10, 12),
+            mouse_down(),
+            mouse_move(41, 12),
+            // Past the end.
+            mouse_move(90, 40),
+            mouse_up(),
+            mouse_move(20, 12),
+        ];
+        assert_eq!(harness.run(&mut state, slider, events), [0, 255, 255]);
+    }
+
+    #[test]
+    fn typed_colors_replace_the_selected_one() {
+        let mut resources = Resources::empty();
+        let mut editor = Editor::new();
+
+        editor.update(Msg::ColorSelected(Color::RED), &mut resources);
+        editor.update(Msg::HexOpened, &mut resources);
+        editor.hex_input.set_text("12ab00".to_owned());
+        editor.update(Msg::HexSubmitted, &mut resources);
+        assert_eq!(resources.palette.rgb(Color::RED), (0x12, 0xAB, 0x00));
+        assert!(!editor.hex_input.is_focused());
+
+        editor.update(
+            Msg::ChannelSet {
+                channel: 2,
+                value: 7,
+            },
+            &mut resources,
+        );
+        assert_eq!(resources.palette.rgb(Color::RED), (0x12, 0xAB, 7));
+
+        // Not a color, nothing changes.
+        editor.update(Msg::HexOpened, &mut resources);
+        editor.hex_input.set_text("red".to_owned());
+        editor.update(Msg::HexSubmitted, &mut resources);
+        assert_eq!(resources.palette.rgb(Color::RED), (0x12, 0xAB, 7));
+
+        editor.update(Msg::Reset, &mut resources);
+        assert!(resources.palette.is_pico8());
+    }
+}
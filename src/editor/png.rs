@@ -0,0 +1,127 @@
+//! Just enough PNG to export images: 8 bit RGB, stored without compression.
+//! The exported images are small, so it isn't worth pulling in a deflate implementation.
+
+use std::io::{self, Write};
+
+const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Writes a `width` x `height` image, `rgb` holds 3 bytes per pixel, row by row.
+pub(crate) fn write(
+    writer: &mut dyn Write,
+    width: usize,
+    height: usize,
+    rgb: &[u8],
+) -> io::Result<()> {
+    debug_assert_eq!(rgb.len(), width * height * 3);
+
+    let mut header = vec![];
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+    // Bit depth, color type (RGB), compression, filter and interlace methods.
+    header.extend([8, 2, 0, 0, 0]);
+
+    // Each row starts with its filter type, 0 is none.
+    let rows: Vec<u8> = rgb
+        .chunks(width * 3)
+        .flat_map(|row| std::iter::once(0).chain(row.iter().copied()))
+        .collect();
+
+    writer.write_all(SIGNATURE)?;
+    write_chunk(writer, b"IHDR", &header)?;
+    write_chunk(writer, b"IDAT", &zlib_stored(&rows))?;
+    write_chunk(writer, b"IEND", &[])
+}
+
+fn write_chunk(writer: &mut dyn Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    writer.write_all(&crc32(kind.iter().chain(data)).to_be_bytes())
+}
+
+// A zlib stream made of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = u16::MAX as usize;
+
+    let mut stream = vec![0x78, 0x01];
+    // There's always a block, even if it's empty.
+    let blocks: Vec<&[u8]> = if data.is_empty() {
+        vec![&[]]
+    } else {
+        data.chunks(MAX_BLOCK).collect()
+    };
+
+    for (index, block) in blocks.iter().enumerate() {
+        let len = block.len() as u16;
+        let is_last = index + 1 == blocks.len();
+
+        stream.push(is_last as u8);
+        stream.extend(len.to_le_bytes());
+        stream.extend((!len).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+    stream.extend(adler32(data).to_be_bytes());
+
+    stream
+}
+
+fn crc32<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u32 {
+    let mut crc = !0u32;
+
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    const MODULO: u32 = 65521;
+    let (mut a, mut b) = (1, 0);
+
+    for &byte in bytes {
+        a = (a + byte as u32) % MODULO;
+        b = (b + a) % MODULO;
+    }
+
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksums_match_known_values() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn writes_the_header_and_every_pixel() {
+        let (width, height) = (200, 200);
+        let rgb: Vec<u8> = (0..width * height * 3).map(|i| i as u8).collect();
+        let mut png = vec![];
+        write(&mut png, width, height, &rgb).unwrap();
+
+        assert!(png.starts_with(SIGNATURE));
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(png[16..20], 200u32.to_be_bytes());
+        assert_eq!(png[20..24], 200u32.to_be_bytes());
+        assert!(png.ends_with(&[b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]));
+
+        // The rows don't fit in a single stored block.
+        let rows_len = height * (1 + width * 3);
+        assert!(rows_len > u16::MAX as usize);
+        let overhead = 2 + 5 * rows_len.div_ceil(u16::MAX as usize) + 4;
+        let idat_len = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+        assert_eq!(idat_len, rows_len + overhead);
+    }
+}
@@ -0,0 +1,175 @@
+use itertools::Itertools;
+
+use crate::runtime::sprite_sheet::{Sprite, SpriteSheet};
+use crate::serialize::Serialize;
+use crate::{Palette, Resources};
+use std::fmt::Display;
+use std::io::{self, Write};
+
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug)]
+struct Color {
+    r: u8,
+    g: u8,
+    b: u8,
+}
+
+impl Color {
+    fn from_pico8(color: crate::Color, palette: &Palette) -> Self {
+        let (r, g, b) = palette.rgb(color);
+
+        Self { r, g, b }
+    }
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{} {} {}", self.r, self.g, self.b))
+    }
+}
+
+/// Utility to create PPM images.
+/// Useful for debugging our data structures (sprite sheet, map)
+/// in regular image viewers.
+pub(crate) struct Ppm {
+    height: usize,
+    width: usize,
+    data: Vec<Color>,
+}
+
+impl Ppm {
+    #[allow(dead_code)]
+    pub(crate) fn from_map(resources: &Resources) -> Self {
+        let (map_width, map_height) = (resources.map.width(), resources.map.height());
+        let width = map_width * Sprite::WIDTH;
+        let height = map_height * Sprite::HEIGHT;
+        let mut data = vec![Color { r: 0, g: 0, b: 0 }; width * height];
+
+        for y in 0..map_height {
+            for x in 0..map_width {
+                let real_x = x * 8;
+                let real_y = y * 8;
+
+                // Through `Resources`, so the rows shared with the sprite sheet are read from there.
+                let sprite_id = resources.mget(x as i32, y as i32);
+                let sprite = resources.sprite_sheet.get_sprite(sprite_id as usize);
+
+                for (offset_x, offset_y, pixel) in sprite.iter_pixels() {
+                    let color = Color::from_pico8(pixel, &resources.palette);
+
+                    data[(real_x + offset_x) + (real_y + offset_y) * width] = color;
+                }
+            }
+        }
+
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn from_sprite_sheet(sprite_sheet: &SpriteSheet, palette: &Palette) -> Self {
+        let width = sprite_sheet.width();
+        let height = SpriteSheet::HEIGHT;
+        let sprites_per_row = sprite_sheet.sprites_per_row();
+        let sprite_sheet = &sprite_sheet.sprite_sheet;
+
+        let mut data = vec![Color { r: 0, g: 0, b: 0 }; sprite_sheet.len()];
+
+        const SPRITE_WIDTH: usize = 8;
+        const SPRITE_SIZE: usize = SPRITE_WIDTH.pow(2);
+
+        #[allow(clippy::never_loop)]
+        for (sprite_index, sprite) in sprite_sheet
+            .iter()
+            .copied()
+            .chunks(SPRITE_SIZE)
+            .into_iter()
+            .enumerate()
+        {
+            let base_x = SPRITE_WIDTH * (sprite_index % sprites_per_row);
+            let base_y = SPRITE_WIDTH * (sprite_index / sprites_per_row);
+
+            for (pixel_index, c) in sprite.enumerate() {
+                let x = base_x + pixel_index % SPRITE_WIDTH;
+                let y = base_y + pixel_index / SPRITE_WIDTH;
+
+                let color = Color::from_pico8(crate::Color::from_index_lossy(c), palette);
+                data[x + y * width] = color;
+            }
+        }
+
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+}
+
+/// The same image as a PNG, for tools that don't read PPM.
+#[cfg(feature = "cli")]
+pub(crate) struct Png<'a>(pub(crate) &'a Ppm);
+
+#[cfg(feature = "cli")]
+impl Serialize for Png<'_> {
+    fn serialize(&self, writer: &mut dyn Write) -> io::Result<()> {
+        let Png(ppm) = self;
+        let rgb: Vec<u8> = ppm
+            .data
+            .iter()
+            .flat_map(|color| [color.r, color.g, color.b])
+            .collect();
+
+        super::png::write(writer, ppm.width, ppm.height, &rgb)
+    }
+}
+
+impl Serialize for Ppm {
+    /// Plain PPM format (P3)
+    fn serialize(&self, writer: &mut dyn Write) -> io::Result<()> {
+        write!(writer, "P3\n{} {}\n255\n", self.width, self.height)?;
+        for component in &self.data {
+            write!(writer, " {} ", component)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::map::Map;
+
+    #[test]
+    fn map_image_has_the_map_size() {
+        let mut resources = Resources {
+            map: Map::with_size(20, 3),
+            ..Resources::empty()
+        };
+        resources
+            .sprite_sheet
+            .get_sprite_mut(1)
+            .pset(7, 7, crate::Color::RED);
+        resources.mset(19, 2, 1);
+
+        let ppm = Ppm::from_map(&resources);
+        assert_eq!((ppm.width, ppm.height), (160, 24));
+
+        let Color { r, g, b } = ppm.data[159 + 23 * 160];
+        assert_eq!((r, g, b), crate::Color::RED.rgb());
+    }
+
+    #[test]
+    fn images_use_the_palette() {
+        let mut palette = Palette::pico8();
+        palette.set_rgb(crate::Color::BLACK, (1, 2, 3));
+
+        let ppm = Ppm::from_sprite_sheet(&SpriteSheet::new(), &palette);
+        let Color { r, g, b } = ppm.data[0];
+        assert_eq!((r, g, b), (1, 2, 3));
+    }
+}
@@ -0,0 +1,213 @@
+//! Editor preferences, restored the next time the editor is opened.
+//! Only editor state goes here, never game data, so the file can be gitignored.
+
+use itertools::Itertools;
+
+use super::brush_size::BrushSize;
+use super::display_palette::DisplayNotes;
+use super::pal_remap::PalRemap;
+use super::shade::ShadeRamp;
+use super::transparency::TransparentPages;
+use super::Tab;
+use crate::serialize::{split_version, version_header, Serialize};
+use crate::util::vec2::{vec2, Vec2i};
+use std::io::{self, Write};
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct EditorSettings {
+    pub(crate) tab: Tab,
+    pub(crate) sprite_page: usize,
+    pub(crate) brush_size: BrushSize,
+    /// Top left corner of the map editor's view.
+    pub(crate) map_camera: Vec2i,
+    /// Whether the map editor shows sprites, or sprite numbers.
+    pub(crate) map_sprites: bool,
+    /// Colors the shade brush steps through. Not editable in the editor, only in the file.
+    pub(crate) shade_ramp: ShadeRamp,
+    /// The sprite editor's palette remap preview rules.
+    pub(crate) pal_remap: PalRemap,
+    /// Sprite pages previewed with color 0 transparent.
+    pub(crate) transparent_pages: TransparentPages,
+    /// The extended colors each sprite is previewed with.
+    pub(crate) display_notes: DisplayNotes,
+}
+
+impl EditorSettings {
+    const VERSION: u32 = 1;
+
+    pub(crate) fn file_name() -> String {
+        "editor_settings.txt".to_owned()
+    }
+
+    pub(crate) fn new() -> Self {
+        Self {
+            tab: Tab::SpriteEditor,
+            sprite_page: 0,
+            brush_size: BrushSize::tiny(),
+            map_camera: Vec2i::zero(),
+            map_sprites: true,
+            shade_ramp: ShadeRamp::new(),
+            pal_remap: PalRemap::new(),
+            transparent_pages: TransparentPages::new(),
+            display_notes: DisplayNotes::new(),
+        }
+    }
+
+    /// The settings saved in the assets directory.
+    /// Missing or unreadable settings are replaced by the defaults, without complaining.
+    pub(crate) fn load(assets_path: &str) -> Self {
+        std::fs::read_to_string(format!("{assets_path}/{}", Self::file_name()))
+            .ok()
+            .and_then(|contents| Self::deserialize(&contents).ok())
+            .unwrap_or_else(Self::new)
+    }
+
+    /// One `name value` setting per line, like the volume settings.
+    /// Missing settings keep their default. `display_palette` is repeated, once per sprite.
+    pub(crate) fn deserialize(str: &str) -> Result<Self, String> {
+        let lines = match split_version(str)? {
+            (1, lines) => lines,
+            (version, _) => return Err(format!("Unsupported editor settings version {version}")),
+        };
+
+        let mut settings = Self::new();
+        for line in lines.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let invalid = || format!("Invalid editor setting: {line}");
+            let (name, value) = line.split_once(' ').ok_or_else(invalid)?;
+
+            match name {
+                "tab" => settings.tab = Tab::from_name(value).ok_or_else(invalid)?,
+                "sprite_page" => settings.sprite_page = value.parse().map_err(|_| invalid())?,
+                "brush_size" => {
+                    settings.brush_size =
+                        BrushSize::from_human_readable(value).ok_or_else(invalid)?
+                }
+                "map_camera" => {
+                    let (x, y) = value.split_once(' ').ok_or_else(invalid)?;
+                    let parse = |n: &str| n.trim().parse().map_err(|_| invalid());
+                    settings.map_camera = vec2(parse(x)?, parse(y)?);
+                }
+                "map_sprites" => settings.map_sprites = value.parse().map_err(|_| invalid())?,
+                "shade_darker" | "shade_lighter" => settings
+                    .shade_ramp
+                    .set_from_human_readable(value, name == "shade_lighter")
+                    .ok_or_else(invalid)?,
+                "pal_remap" => {
+                    settings.pal_remap = PalRemap::from_human_readable(value).ok_or_else(invalid)?
+                }
+                "transparent_pages" => {
+                    settings.transparent_pages =
+                        TransparentPages::from_human_readable(value).ok_or_else(invalid)?
+                }
+                "display_palette" => settings
+                    .display_notes
+                    .add_from_human_readable(value)
+                    .ok_or_else(invalid)?,
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(settings)
+    }
+}
+
+impl Serialize for EditorSettings {
+    fn serialize(&self, writer: &mut dyn Write) -> io::Result<()> {
+        let settings = [
+            version_header(Self::VERSION),
+            format!("tab {}", self.tab.name()),
+            format!("sprite_page {}", self.sprite_page),
+            format!("brush_size {}", self.brush_size.to_human_readable()),
+            format!("map_camera {} {}", self.map_camera.x, self.map_camera.y),
+            format!("map_sprites {}", self.map_sprites),
+            format!("shade_darker {}", self.shade_ramp.to_human_readable(false)),
+            format!("shade_lighter {}", self.shade_ramp.to_human_readable(true)),
+            format!("pal_remap {}", self.pal_remap.to_human_readable()),
+            format!(
+                "transparent_pages {}",
+                self.transparent_pages.to_human_readable()
+            ),
+        ]
+        .into_iter()
+        .chain(
+            self.display_notes
+                .to_human_readable()
+                .map(|note| format!("display_palette {note}")),
+        )
+        .join("\n");
+
+        writer.write_all(settings.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialize::to_string;
+
+    fn changed() -> EditorSettings {
+        let mut shade_ramp = ShadeRamp::new();
+        shade_ramp.set_from_human_readable("7 6 5 4 3 2 1 0 7 6 5 4 3 2 1 0", false);
+        let mut transparent_pages = TransparentPages::new();
+        transparent_pages.toggle(1);
+        let mut display_notes = DisplayNotes::new();
+        display_notes.toggle(3, crate::Color::RED, 8);
+        display_notes.toggle(40, crate::Color::WHITE, 15);
+
+        EditorSettings {
+            tab: Tab::MapEditor,
+            sprite_page: 2,
+            brush_size: BrushSize::large(),
+            map_camera: vec2(-40, -16),
+            map_sprites: false,
+            shade_ramp,
+            pal_remap: PalRemap::from_human_readable("8 12 0 0 1 7 0 0").unwrap(),
+            transparent_pages,
+            display_notes,
+        }
+    }
+
+    #[test]
+    fn settings_round_trip() {
+        let settings = changed();
+
+        assert_eq!(
+            EditorSettings::deserialize(&to_string(&settings)),
+            Ok(settings.clone())
+        );
+    }
+
+    #[test]
+    fn missing_settings_keep_their_default() {
+        let settings = EditorSettings::deserialize("version 1\ntab sfx\n").unwrap();
+
+        assert_eq!(settings.tab, Tab::SfxEditor);
+        assert_eq!(settings.brush_size, BrushSize::tiny());
+        assert!(settings.map_sprites);
+    }
+
+    #[test]
+    fn corrupt_settings_are_rejected() {
+        for corrupt in [
+            "version 1\ntab paint",
+            "version 1\nbrush_size 9",
+            "version 1\nmap_camera 3",
+            "version 1\nsprites 255",
+            "version 1\nshade_darker 0 1 2",
+            "version 1\npal_remap 8 12",
+            "version 1\ntransparent_pages 9",
+            "version 1\ndisplay_palette 3 8 12",
+            "version 7\n",
+        ] {
+            assert!(EditorSettings::deserialize(corrupt).is_err(), "{corrupt}");
+        }
+    }
+
+    #[test]
+    fn missing_files_load_the_defaults() {
+        assert_eq!(
+            EditorSettings::load("this/directory/does/not/exist"),
+            EditorSettings::new()
+        );
+    }
+}
@@ -0,0 +1,685 @@
+use super::undo_redo::{Command, Commands};
+use super::{with_help, Help};
+use crate::audio::{
+    self,
+    sfx::{Effect, Note, Sfx, Waveform},
+};
+use crate::ui::button::{self, Button};
+use crate::ui::{DispatchEvent, DrawFn, Element, Tree, Widget};
+use crate::{
+    Event, Key, KeyState, KeyboardEvent, MouseButton, MouseEvent, Pico8, Resources, SpriteSheet,
+};
+use std::fmt::Debug;
+
+const HEADER_Y: i32 = 9;
+// Top of the piano roll, one pixel per semitone.
+const PITCH_Y: i32 = 17;
+// Top of the volume lane, one pixel per volume level.
+const VOLUME_Y: i32 = 82;
+const WAVEFORM_Y: i32 = 92;
+const EFFECT_Y: i32 = 101;
+const SLOTS_Y: i32 = 111;
+// Width of each note's column in the piano roll and the volume lane.
+const NOTE_WIDTH: i32 = 4;
+
+const SLOTS_PER_PAGE: usize = 16;
+// Volume given to silent notes when drawing their pitch.
+const DEFAULT_VOLUME: u8 = 5;
+// The sfx being edited always plays on the same channel, so that it can be stopped.
+const PREVIEW_CHANNEL: usize = 0;
+
+// Color of the notes played with each waveform.
+const WAVEFORM_COLORS: [u8; 8] = [8, 9, 10, 11, 12, 13, 14, 15];
+const WAVEFORM_NAMES: [&str; 8] = [
+    "TRIANGLE",
+    "TILTED SAW",
+    "SAW",
+    "SQUARE",
+    "PULSE",
+    "ORGAN",
+    "NOISE",
+    "PHASER",
+];
+const EFFECT_NAMES: [&str; 8] = [
+    "NO EFFECT",
+    "SLIDE",
+    "VIBRATO",
+    "DROP",
+    "FADE IN",
+    "FADE OUT",
+    "FAST ARPEGGIO",
+    "SLOW ARPEGGIO",
+];
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Msg {
+    SlotSelected(usize),
+    PageSelected(usize),
+    PitchSet { note: usize, pitch: u8 },
+    VolumeSet { note: usize, volume: u8 },
+    NoteCleared(usize),
+    WaveformSelected(Waveform),
+    EffectSelected(Effect),
+    SpeedChanged(i32),
+    PlayToggled,
+    Tick,
+}
+
+#[derive(Debug)]
+pub(crate) struct Editor {
+    selected_sfx: usize,
+    page: usize,
+    // Waveform and effect of the notes being drawn.
+    waveform: Waveform,
+    effect: Effect,
+    // Frames until the sfx being previewed is over, `None` if it isn't playing.
+    playing_frames: Option<u32>,
+    pitch_lane: LaneState,
+    volume_lane: LaneState,
+    waveform_buttons: Vec<button::State>,
+    effect_buttons: Vec<button::State>,
+    slot_buttons: Vec<button::State>,
+    page_buttons: Vec<button::State>,
+    speed_buttons: [button::State; 2],
+    play_button: button::State,
+}
+
+impl Editor {
+    pub(crate) fn new() -> Self {
+        Self {
+            selected_sfx: 0,
+            page: 0,
+            waveform: Waveform::default(),
+            effect: Effect::default(),
+            playing_frames: None,
+            pitch_lane: LaneState::new(),
+            volume_lane: LaneState::new(),
+            waveform_buttons: vec![button::State::new(); Waveform::ALL.len()],
+            effect_buttons: vec![button::State::new(); Effect::ALL.len()],
+            slot_buttons: vec![button::State::new(); SLOTS_PER_PAGE],
+            page_buttons: vec![button::State::new(); 4],
+            speed_buttons: [button::State::new(), button::State::new()],
+            play_button: button::State::new(),
+        }
+    }
+
+    pub(crate) fn selected_sfx(&self) -> usize {
+        self.selected_sfx
+    }
+
+    pub(crate) fn update(&mut self, msg: Msg, resources: &mut Resources, commands: &mut Commands) {
+        let index = self.selected_sfx;
+
+        match msg {
+            Msg::SlotSelected(selected_sfx) => {
+                self.selected_sfx = selected_sfx;
+            }
+            Msg::PageSelected(page) => {
+                self.page = page;
+            }
+            Msg::PitchSet { note, pitch } => {
+                let (waveform, effect) = (self.waveform, self.effect);
+
+                edit(index, resources, commands, |sfx| {
+                    let note = &mut sfx.notes[note];
+                    note.pitch = pitch;
+                    note.waveform = waveform;
+                    note.effect = effect;
+                    if note.volume == 0 {
+                        note.volume = DEFAULT_VOLUME;
+                    }
+                });
+            }
+            Msg::VolumeSet { note, volume } => {
+                edit(index, resources, commands, |sfx| {
+                    sfx.notes[note].volume = volume;
+                });
+            }
+            Msg::NoteCleared(note) => {
+                edit(index, resources, commands, |sfx| {
+                    sfx.notes[note] = Note::default();
+                });
+            }
+            Msg::WaveformSelected(waveform) => {
+                self.waveform = waveform;
+            }
+            Msg::EffectSelected(effect) => {
+                self.effect = effect;
+            }
+            Msg::SpeedChanged(delta) => {
+                edit(index, resources, commands, |sfx| {
+                    sfx.speed = (sfx.speed as i32 + delta).clamp(1, u8::MAX as i32) as u8;
+                });
+            }
+            Msg::PlayToggled => {
+                if self.playing_frames.take().is_some() {
+                    resources.audio.send(audio::Command::Stop {
+                        channel: Some(PREVIEW_CHANNEL),
+                    });
+                } else if let Some(sfx) = resources.sound_effects.get(index) {
+                    self.playing_frames = Some(playing_frames(sfx));
+                    resources.audio.send(audio::Command::Play {
+                        sfx: sfx.clone(),
+                        channel: Some(PREVIEW_CHANNEL),
+                    });
+                }
+            }
+            Msg::Tick => {
+                self.playing_frames = self.playing_frames.and_then(|frames| frames.checked_sub(1));
+            }
+        }
+    }
+
+    pub(crate) fn subscriptions(event: &Event) -> Option<Msg> {
+        match event {
+            Event::Keyboard(KeyboardEvent {
+                key: Key::Space,
+                state: KeyState::Down,
+            }) => Some(Msg::PlayToggled),
+            Event::Tick { .. } => Some(Msg::Tick),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn view<'a>(
+        &'a mut self,
+        resources: &Resources,
+        editor_sprites: &'a SpriteSheet,
+        to_editor_msg: &'a (impl Fn(Msg) -> super::Msg + Copy),
+    ) -> Element<'a, super::Msg> {
+        let sfx = resources
+            .sound_effects
+            .get(self.selected_sfx)
+            .cloned()
+            .unwrap_or_else(Sfx::new);
+
+        let pitches = sfx.notes.map(|note| {
+            (note.volume > 0).then(|| (note.pitch, WAVEFORM_COLORS[note.waveform.index() as usize]))
+        });
+        let volumes = sfx
+            .notes
+            .map(|note| (note.volume > 0).then_some((note.volume, 12)));
+
+        let slots = (0..SLOTS_PER_PAGE).map(|slot| {
+            let index = self.page * SLOTS_PER_PAGE + slot;
+            let silent = resources
+                .sound_effects
+                .get(index)
+                .is_none_or(Sfx::is_silent);
+
+            (index, silent)
+        });
+
+        let mut children = Vec::with_capacity(6 + self.page_buttons.len());
+        children.extend([
+            header(
+                self.selected_sfx,
+                sfx.speed,
+                self.playing_frames.is_some(),
+                &mut self.speed_buttons,
+                &mut self.play_button,
+                to_editor_msg,
+            ),
+            pitch_lane(pitches, &mut self.pitch_lane, to_editor_msg),
+            Lane {
+                x: 0,
+                y: VOLUME_Y,
+                scale: 1,
+                max: Note::MAX_VOLUME,
+                bars: volumes,
+                state: &mut self.volume_lane,
+                on_set: Box::new(move |note, volume| {
+                    to_editor_msg(Msg::VolumeSet { note, volume })
+                }),
+                on_clear: Box::new(move |note| to_editor_msg(Msg::NoteCleared(note))),
+            }
+            .into(),
+            waveform_selector(self.waveform, &mut self.waveform_buttons, to_editor_msg),
+            effect_selector(self.effect, &mut self.effect_buttons, to_editor_msg),
+            slot_selector(
+                slots.collect(),
+                self.selected_sfx,
+                &mut self.slot_buttons,
+                to_editor_msg,
+            ),
+        ]);
+        children.extend(super::page_buttons(
+            96,
+            WAVEFORM_Y - 1,
+            self.page,
+            &mut self.page_buttons,
+            editor_sprites,
+            None,
+            move |page| to_editor_msg(Msg::PageSelected(page)),
+        ));
+
+        Tree::with_children(children).into()
+    }
+}
+
+// Applies `f` to the sfx, recording it for undo (if it changed anything).
+fn edit(
+    index: usize,
+    resources: &mut Resources,
+    commands: &mut Commands,
+    f: impl FnOnce(&mut Sfx),
+) {
+    if let Some(sfx) = resources.sound_effects.get_mut(index) {
+        let previous = sfx.clone();
+        f(sfx);
+
+        if *sfx != previous {
+            commands.push(Command::sfx_changed(index, previous, sfx.clone()));
+        }
+    }
+}
+
+// How long the sfx plays for, in frames. Looping ones go on until stopped.
+fn playing_frames(sfx: &Sfx) -> u32 {
+    if sfx.loops() {
+        u32::MAX
+    } else {
+        // Notes last `speed` 120ths of a second, and there's 30 frames per second.
+        Sfx::NOTES as u32 * sfx.speed as u32 / 4
+    }
+}
+
+fn header<'a>(
+    selected_sfx: usize,
+    speed: u8,
+    playing: bool,
+    speed_buttons: &'a mut [button::State; 2],
+    play_button: &'a mut button::State,
+    to_editor_msg: &'a (impl Fn(Msg) -> super::Msg + Copy),
+) -> Element<'a, super::Msg> {
+    let [slower, faster] = speed_buttons;
+    let text_width = |text| crate::measure_text(text).0 + 2;
+    let text_button = |x: i32, text: &'static str, msg: Msg, state: &'a mut button::State| {
+        let width = text_width(text);
+
+        Button::new(
+            x,
+            HEADER_Y,
+            width,
+            7,
+            Some(to_editor_msg(msg)),
+            state,
+            DrawFn::new(move |draw| {
+                draw.rectfill(0, 0, width - 1, 6, 13);
+                draw.print(text, 1, 1, 7);
+            }),
+        )
+    };
+
+    Tree::new()
+        .push(DrawFn::new(move |draw| {
+            draw.print(&format!("SFX {selected_sfx:0>2}"), 1, HEADER_Y + 1, 7);
+            draw.print("SPD", 40, HEADER_Y + 1, 6);
+            draw.print(&format!("{speed:>3}"), 61, HEADER_Y + 1, 7);
+        }))
+        .push(with_help(
+            text_button(54, "<", Msg::SpeedChanged(-1), slower)
+                .repeat_on_hold(10, 2)
+                .event_on_press(),
+            (54, HEADER_Y, text_width("<"), 7),
+            Help::Text("SLOWER, HOLD TO REPEAT"),
+        ))
+        .push(with_help(
+            text_button(74, ">", Msg::SpeedChanged(1), faster)
+                .repeat_on_hold(10, 2)
+                .event_on_press(),
+            (74, HEADER_Y, text_width(">"), 7),
+            Help::Text("FASTER, HOLD TO REPEAT"),
+        ))
+        .push(with_help(
+            text_button(
+                108,
+                if playing { "STOP" } else { "PLAY" },
+                Msg::PlayToggled,
+                play_button,
+            )
+            .tooltip("SPACE")
+            .focusable(),
+            (108, HEADER_Y, text_width("STOP"), 7),
+            Help::Text("PLAY/STOP (SPACE)"),
+        ))
+        .into()
+}
+
+fn pitch_lane<'a>(
+    pitches: [Option<(u8, u8)>; Sfx::NOTES],
+    state: &'a mut LaneState,
+    to_editor_msg: &'a (impl Fn(Msg) -> super::Msg + Copy),
+) -> Element<'a, super::Msg> {
+    Lane {
+        x: 0,
+        y: PITCH_Y,
+        scale: 1,
+        max: Note::MAX_PITCH,
+        bars: pitches,
+        state,
+        on_set: Box::new(move |note, pitch| to_editor_msg(Msg::PitchSet { note, pitch })),
+        on_clear: Box::new(move |note| to_editor_msg(Msg::NoteCleared(note))),
+    }
+    .into()
+}
+
+fn waveform_selector<'a>(
+    selected: Waveform,
+    buttons: &'a mut [button::State],
+    to_editor_msg: &'a (impl Fn(Msg) -> super::Msg + Copy),
+) -> Element<'a, super::Msg> {
+    let children = Waveform::ALL
+        .into_iter()
+        .zip(buttons.iter_mut())
+        .map(|(waveform, state)| {
+            let index = waveform.index() as usize;
+            let color = WAVEFORM_COLORS[index];
+            let x = 1 + index as i32 * 9;
+
+            let button = Button::new(
+                x,
+                WAVEFORM_Y,
+                8,
+                7,
+                Some(to_editor_msg(Msg::WaveformSelected(waveform))),
+                state,
+                DrawFn::new(move |draw| {
+                    draw.rectfill(0, 0, 7, 6, color);
+                    draw.print(&index.to_string(), 2, 1, 0);
+                    if waveform == selected {
+                        draw.rect(0, 0, 7, 6, 7);
+                    }
+                }),
+            )
+            .tooltip(WAVEFORM_NAMES[index]);
+
+            with_help(
+                button,
+                (x, WAVEFORM_Y, 8, 7),
+                Help::Text("WAVEFORM OF THE NOTES DRAWN"),
+            )
+        })
+        .collect();
+
+    Tree::with_children(children).into()
+}
+
+fn effect_selector<'a>(
+    selected: Effect,
+    buttons: &'a mut [button::State],
+    to_editor_msg: &'a (impl Fn(Msg) -> super::Msg + Copy),
+) -> Element<'a, super::Msg> {
+    let children = Effect::ALL
+        .into_iter()
+        .zip(buttons.iter_mut())
+        .map(|(effect, state)| {
+            let index = effect.index() as usize;
+            let (background, text) = if effect == selected { (7, 0) } else { (1, 6) };
+            let x = 1 + index as i32 * 9;
+
+            let button = Button::new(
+                x,
+                EFFECT_Y,
+                8,
+                7,
+                Some(to_editor_msg(Msg::EffectSelected(effect))),
+                state,
+                DrawFn::new(move |draw| {
+                    draw.rectfill(0, 0, 7, 6, background);
+                    draw.print(&index.to_string(), 2, 1, text);
+                }),
+            )
+            .tooltip(EFFECT_NAMES[index]);
+
+            with_help(
+                button,
+                (x, EFFECT_Y, 8, 7),
+                Help::Text("EFFECT OF THE NOTES DRAWN"),
+            )
+        })
+        .collect();
+
+    Tree::with_children(children).into()
+}
+
+// `slots` are the sfx numbers of the current page, and whether they're silent.
+fn slot_selector<'a>(
+    slots: Vec<(usize, bool)>,
+    selected_sfx: usize,
+    buttons: &'a mut [button::State],
+    to_editor_msg: &'a (impl Fn(Msg) -> super::Msg + Copy),
+) -> Element<'a, super::Msg> {
+    let children = slots
+        .into_iter()
+        .zip(buttons.iter_mut())
+        .enumerate()
+        .map(|(column, ((index, silent), state))| {
+            let (background, text) = match (index == selected_sfx, silent) {
+                (true, _) => (7, 0),
+                (false, false) => (6, 0),
+                (false, true) => (1, 5),
+            };
+
+            let x = column as i32 * 8;
+            let button = Button::new(
+                x,
+                SLOTS_Y,
+                8,
+                7,
+                Some(to_editor_msg(Msg::SlotSelected(index))),
+                state,
+                DrawFn::new(move |draw| {
+                    draw.rectfill(0, 0, 7, 6, background);
+                    draw.print(&format!("{index:0>2}"), 1, 1, text);
+                }),
+            )
+            .event_on_press();
+
+            with_help(
+                button,
+                (x, SLOTS_Y, 8, 7),
+                Help::Text("CLICK: EDIT THIS SFX"),
+            )
+        })
+        .collect();
+
+    Tree::with_children(children).into()
+}
+
+/// Which mouse button is drawing on a [`Lane`].
+#[derive(Debug)]
+struct LaneState {
+    held: Option<MouseButton>,
+}
+
+impl LaneState {
+    fn new() -> Self {
+        Self { held: None }
+    }
+}
+
+// A bar per note, set by clicking (or dragging) with the left button and cleared with the right one.
+struct Lane<'a, Msg> {
+    x: i32,
+    y: i32,
+    // Pixels per value.
+    scale: i32,
+    max: u8,
+    // Value and color of each note's bar, `None` for silent notes.
+    bars: [Option<(u8, u8)>; Sfx::NOTES],
+    state: &'a mut LaneState,
+    on_set: Box<dyn Fn(usize, u8) -> Msg + 'a>,
+    on_clear: Box<dyn Fn(usize) -> Msg + 'a>,
+}
+
+impl<'a, Msg> Lane<'a, Msg> {
+    fn height(&self) -> i32 {
+        (self.max as i32 + 1) * self.scale
+    }
+
+    // Note and value under the cursor, if it's inside the lane.
+    fn hovered(&self, (x, y): (i32, i32)) -> Option<(usize, u8)> {
+        let width = Sfx::NOTES as i32 * NOTE_WIDTH;
+        let bottom = self.y + self.height() - 1;
+
+        let inside = (self.x..self.x + width).contains(&x) && (self.y..=bottom).contains(&y);
+        inside.then(|| {
+            let note = ((x - self.x) / NOTE_WIDTH) as usize;
+            let value = ((bottom - y) / self.scale).clamp(0, self.max as i32) as u8;
+
+            (note, value)
+        })
+    }
+}
+
+impl<'a, Msg: Copy + Debug + 'a> Widget for Lane<'a, Msg> {
+    type Msg = Msg;
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        cursor_position: (i32, i32),
+        dispatch_event: &mut DispatchEvent<Self::Msg>,
+    ) {
+        let hovered = self.hovered(cursor_position);
+
+        match event {
+            Event::Mouse(MouseEvent::Down(button @ (MouseButton::Left | MouseButton::Right)))
+                if hovered.is_some() =>
+            {
+                self.state.held = Some(button);
+            }
+            Event::Mouse(MouseEvent::Move { .. }) => {}
+            Event::Mouse(MouseEvent::Up(_)) => {
+                self.state.held = None;
+                return;
+            }
+            _ => return,
+        }
+
+        if let Some((note, value)) = hovered {
+            match self.state.held {
+                Some(MouseButton::Left) => dispatch_event.call((self.on_set)(note, value)),
+                Some(MouseButton::Right) => dispatch_event.call((self.on_clear)(note)),
+                _ => {}
+            }
+        }
+    }
+
+    fn draw(&mut self, draw: &mut Pico8) {
+        let width = Sfx::NOTES as i32 * NOTE_WIDTH;
+        let bottom = self.y + self.height() - 1;
+
+        draw.rectfill(self.x, self.y, self.x + width - 1, bottom, 0);
+
+        for (note, bar) in self.bars.iter().enumerate() {
+            if let Some((value, color)) = *bar {
+                let x = self.x + note as i32 * NOTE_WIDTH;
+                let top = bottom - value as i32 * self.scale;
+
+                draw.rectfill(x, top, x + NOTE_WIDTH - 2, bottom, color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::testing::{mouse_down, mouse_move, mouse_up, Harness};
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum TestMsg {
+        Set(usize, u8),
+        Clear(usize),
+    }
+
+    fn lane(state: &mut LaneState) -> Element<'_, TestMsg> {
+        Lane {
+            x: 0,
+            y: PITCH_Y,
+            scale: 1,
+            max: Note::MAX_PITCH,
+            bars: [None; Sfx::NOTES],
+            state,
+            on_set: Box::new(TestMsg::Set),
+            on_clear: Box::new(TestMsg::Clear),
+        }
+        .into()
+    }
+
+    #[test]
+    fn dragging_sets_notes_and_right_click_clears() {
+        let mut harness = Harness::new();
+        let mut state = LaneState::new();
+
+        let events = [
+            mouse_move(1, PITCH_Y),
+            mouse_down(),
+            mouse_move(5, PITCH_Y + 63),
+            // Outside of the lane.
+            mouse_move(5, PITCH_Y + 64),
+            mouse_up(),
+            mouse_move(9, PITCH_Y + 20),
+        ];
+        assert_eq!(
+            harness.run(&mut state, lane, events),
+            [TestMsg::Set(0, 63), TestMsg::Set(1, 0)]
+        );
+
+        let events = [
+            Event::Mouse(MouseEvent::Down(MouseButton::Right)),
+            mouse_move(13, PITCH_Y),
+            Event::Mouse(MouseEvent::Up(MouseButton::Right)),
+        ];
+        assert_eq!(
+            harness.run(&mut state, lane, events),
+            [TestMsg::Clear(2), TestMsg::Clear(3)]
+        );
+    }
+
+    #[test]
+    fn edits_can_be_undone() {
+        let mut resources = Resources::empty();
+        let mut commands = Commands::new();
+        let mut notification = super::super::notification::State::new();
+        let mut editor = Editor::new();
+        editor.update(Msg::SlotSelected(2), &mut resources, &mut commands);
+        editor.update(
+            Msg::WaveformSelected(Waveform::Square),
+            &mut resources,
+            &mut commands,
+        );
+        editor.update(
+            Msg::EffectSelected(Effect::Vibrato),
+            &mut resources,
+            &mut commands,
+        );
+
+        let pitch_set = Msg::PitchSet { note: 3, pitch: 40 };
+        editor.update(pitch_set, &mut resources, &mut commands);
+        // Dragging over the same note again doesn't add another undo step.
+        editor.update(pitch_set, &mut resources, &mut commands);
+
+        let note = resources.sound_effects.get(2).unwrap().notes[3];
+        assert_eq!(
+            note,
+            Note {
+                pitch: 40,
+                waveform: Waveform::Square,
+                volume: DEFAULT_VOLUME,
+                effect: Effect::Vibrato,
+            }
+        );
+
+        commands.undo(&mut notification, &mut resources);
+        assert_eq!(resources.sound_effects.get(2), Some(&Sfx::new()));
+
+        commands.undo(&mut notification, &mut resources);
+        assert_eq!(notification.content(), "NOTHING TO UNDO");
+
+        commands.redo(&mut notification, &mut resources);
+        assert_eq!(resources.sound_effects.get(2).unwrap().notes[3], note);
+    }
+}
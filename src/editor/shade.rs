@@ -0,0 +1,88 @@
+//! Ramps for the shade brush, which steps pixels to a darker (or lighter) color
+//! instead of painting over them.
+
+use itertools::Itertools;
+
+use crate::Color;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ShadeRamp {
+    // The next color along the ramp, indexed by color.
+    darker: [u8; 16],
+    lighter: [u8; 16],
+}
+
+impl ShadeRamp {
+    /// Close to the usual Pico8 pairs: 7 → 6 → 13 → 5 → 1 → 0, 10 → 9 → 4 → 2 → 1, ...
+    pub(crate) fn new() -> Self {
+        Self {
+            darker: [0, 0, 1, 1, 2, 1, 13, 6, 2, 4, 9, 3, 13, 5, 8, 9],
+            lighter: [1, 5, 8, 11, 9, 13, 7, 7, 14, 10, 7, 7, 6, 6, 15, 7],
+        }
+    }
+
+    pub(crate) fn step(&self, color: Color, lighter: bool) -> Color {
+        let ramp = if lighter { &self.lighter } else { &self.darker };
+
+        Color::from_index_lossy(ramp[color.index() as usize])
+    }
+
+    /// The darker (or lighter) color of each color, in palette order, separated by spaces.
+    pub(crate) fn to_human_readable(self, lighter: bool) -> String {
+        let ramp = if lighter { &self.lighter } else { &self.darker };
+
+        ramp.iter().join(" ")
+    }
+
+    /// Replaces the darker (or lighter) colors with ones written by [`ShadeRamp::to_human_readable`].
+    pub(crate) fn set_from_human_readable(&mut self, str: &str, lighter: bool) -> Option<()> {
+        let colors: Vec<u8> = str
+            .split_whitespace()
+            .map(|color| color.parse().ok().filter(|&color| color < 16))
+            .collect::<Option<_>>()?;
+        let ramp = if lighter {
+            &mut self.lighter
+        } else {
+            &mut self.darker
+        };
+
+        *ramp = colors.try_into().ok()?;
+
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_along_the_grey_ramp() {
+        let ramp = ShadeRamp::new();
+        let darkened: Vec<_> = std::iter::successors(Some(Color::WHITE), |&color| {
+            Some(ramp.step(color, false)).filter(|&darker| darker != color)
+        })
+        .map(Color::index)
+        .collect();
+
+        assert_eq!(darkened, [7, 6, 13, 5, 1, 0]);
+        assert_eq!(ramp.step(Color::LAVENDER, true), Color::LIGHT_GREY);
+    }
+
+    #[test]
+    fn ramps_round_trip() {
+        let mut ramp = ShadeRamp::new();
+        let mut other = ShadeRamp::new();
+        assert_eq!(ramp.set_from_human_readable("1 2 3", false), None);
+        assert_eq!(
+            ramp.set_from_human_readable("0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 16", true),
+            None
+        );
+        assert_eq!(ramp, ShadeRamp::new());
+
+        ramp.set_from_human_readable("0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 15", true);
+        other.set_from_human_readable(&ramp.to_human_readable(true), true);
+        assert_eq!(other, ramp);
+        assert_eq!(ramp.step(Color::WHITE, true), Color::BLACK);
+    }
+}
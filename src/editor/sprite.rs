@@ -1,69 +1,223 @@
 use super::brush_size::{self, BrushSize, BrushSizeSelector};
+use super::pal_remap::{self, PalRemap};
+use super::transparency;
+use super::Help;
 use crate::runtime::sprite_sheet::{Sprite, SpriteSheet};
 use crate::ui::{
     button::{self, Button},
     DrawFn, Element, Tree,
 };
-use crate::Color;
-use itertools::Itertools;
+use crate::{Color, Resources};
 use std::fmt::Debug;
 
+// Top left corner of the sprite canvas (including its 1 pixel border).
+const CANVAS_X: i32 = 7;
+const CANVAS_Y: i32 = 10;
+const CANVAS_SIZE: i32 = 8 * Sprite::WIDTH as i32 + 2;
+
+/// Whether the given screen position is over the sprite canvas.
+pub(crate) fn canvas_contains((x, y): (i32, i32)) -> bool {
+    let contains_x = x > CANVAS_X && x < CANVAS_X + CANVAS_SIZE - 1;
+    let contains_y = y > CANVAS_Y && y < CANVAS_Y + CANVAS_SIZE - 1;
+
+    contains_x && contains_y
+}
+
 #[derive(Clone, Copy, Debug)]
 pub(crate) enum Msg {
     ColorSelected(Color),
+    SecondaryColorSelected(Color),
+    TilePreviewToggled,
+    /// Switches the color selector between the 16 colors and Pico8's extended ones.
+    ExtendedPageToggled,
+    /// Steps the rule's from (or to) color through the palette.
+    RemapColorStepped {
+        rule: usize,
+        to: bool,
+        forward: bool,
+    },
+}
+
+/// What the preview box shows, to judge how the sprite looks next to other tiles.
+/// It takes the color selector's place while it's open.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TilePreview {
+    Hidden,
+    /// The sprite repeated 3x3, so that the seams between copies are visible.
+    Tiled,
+    /// The 3x3 cells around the first map cell with the sprite.
+    OnMap,
+    /// The sprite next to a copy drawn with the palette remap rules.
+    Remap,
+}
+
+impl TilePreview {
+    fn next(self) -> Self {
+        match self {
+            Self::Hidden => Self::Tiled,
+            Self::Tiled => Self::OnMap,
+            Self::OnMap => Self::Remap,
+            Self::Remap => Self::Hidden,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Hidden => "PREVIEW",
+            Self::Tiled => "TILED",
+            Self::OnMap => "ON MAP",
+            Self::Remap => "REMAP",
+        }
+    }
 }
 
 #[derive(Debug)]
 pub(crate) struct Editor {
     selected_color: Color,
+    // Painted with the right mouse button.
+    secondary_color: Color,
     color_selector_state: Vec<button::State>,
+    // Whether the color selector shows Pico8's extended colors instead of the 16.
+    extended_page: bool,
+    extended_page_button: button::State,
     flag_buttons: Vec<button::State>,
     pixel_buttons: Vec<button::State>,
     brush_size_state: brush_size::State,
+    tile_preview: TilePreview,
+    tile_preview_button: button::State,
+    pal_remap: PalRemap,
+    // The from and to swatches of each rule, then the copy button.
+    remap_buttons: Vec<button::State>,
 }
 
 impl Editor {
     pub(crate) fn new() -> Self {
         Self {
-            selected_color: 0,
+            selected_color: Color::BLACK,
+            secondary_color: Color::BLACK,
             color_selector_state: vec![button::State::new(); 16],
+            extended_page: false,
+            extended_page_button: button::State::new(),
             flag_buttons: vec![button::State::new(); 8],
             pixel_buttons: vec![button::State::new(); Sprite::WIDTH * Sprite::HEIGHT],
             brush_size_state: brush_size::State::new(),
+            tile_preview: TilePreview::Hidden,
+            tile_preview_button: button::State::new(),
+            pal_remap: PalRemap::new(),
+            remap_buttons: vec![button::State::new(); 2 * pal_remap::RULE_COUNT + 1],
         }
     }
+
+    pub(crate) fn pal_remap(&self) -> PalRemap {
+        self.pal_remap
+    }
+
+    /// Puts back the remap rules from the editor settings.
+    pub(crate) fn restore_pal_remap(&mut self, pal_remap: PalRemap) {
+        self.pal_remap = pal_remap;
+    }
+
+    /// The primary and secondary colors.
+    pub(crate) fn colors(&self) -> (Color, Color) {
+        (self.selected_color, self.secondary_color)
+    }
+
     pub(crate) fn update(&mut self, msg: Msg) {
         match msg {
             Msg::ColorSelected(selected_color) => {
-                self.selected_color = selected_color as u8;
+                self.selected_color = selected_color;
+            }
+            Msg::SecondaryColorSelected(secondary_color) => {
+                self.secondary_color = secondary_color;
+            }
+            Msg::TilePreviewToggled => {
+                self.tile_preview = self.tile_preview.next();
+            }
+            Msg::ExtendedPageToggled => {
+                self.extended_page = !self.extended_page;
+            }
+            Msg::RemapColorStepped { rule, to, forward } => {
+                self.pal_remap.step(rule, to, forward);
             }
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn view<'a, 'b>(
         &'a mut self,
+        sprite: usize,
         selected_sprite_flags: u8,
         selected_sprite: &'b Sprite,
+        // Whether color 0 is transparent on the sprite's page, drawn as a checkerboard.
+        transparent: bool,
+        // The extended color each color is previewed as, see `display_palette`.
+        display: [Option<u8>; 16],
         editor_sprites: &'a SpriteSheet,
-        brush_size: BrushSize,
+        // `None` hides the brush size slider, for tools without a brush.
+        brush_size: Option<BrushSize>,
+        resources: &Resources,
         to_editor_msg: &(impl Fn(Msg) -> super::Msg + Copy),
     ) -> Element<'a, super::Msg> {
-        Tree::new()
-            .push(color_selector(
+        let palette_or_preview = match self.tile_preview {
+            TilePreview::Hidden if self.extended_page => Tree::new()
+                .push(extended_color_selector(
+                    79,
+                    10,
+                    10,
+                    display[self.selected_color.index() as usize],
+                    &mut self.color_selector_state,
+                ))
+                .push(extended_page_toggle(
+                    true,
+                    &mut self.extended_page_button,
+                    to_editor_msg(Msg::ExtendedPageToggled),
+                ))
+                .into(),
+            TilePreview::Hidden => Tree::new()
+                .push(color_selector(
+                    79,
+                    10,
+                    10,
+                    (self.selected_color, self.secondary_color),
+                    &mut self.color_selector_state,
+                    move |color| to_editor_msg(Msg::ColorSelected(color)),
+                    move |color| to_editor_msg(Msg::SecondaryColorSelected(color)),
+                ))
+                .push(extended_page_toggle(
+                    false,
+                    &mut self.extended_page_button,
+                    to_editor_msg(Msg::ExtendedPageToggled),
+                ))
+                .into(),
+            TilePreview::Tiled => tile_preview(87, 17, Some((None, [sprite; 9]))),
+            TilePreview::OnMap => tile_preview(87, 17, map_neighbourhood(resources, sprite)),
+            TilePreview::Remap => remap_preview(
+                81,
+                12,
+                resources.sprite_sheet.sprite_position(sprite),
+                self.pal_remap,
+                &mut self.remap_buttons,
+                to_editor_msg,
+            ),
+        };
+
+        let tree = Tree::new()
+            .push(palette_or_preview)
+            .push(tile_preview_toggle(
                 79,
-                10,
-                10,
-                self.selected_color,
-                &mut self.color_selector_state,
-                move |color| to_editor_msg(Msg::ColorSelected(color)),
-                super::Msg::ColorHovered,
+                63,
+                self.tile_preview,
+                &mut self.tile_preview_button,
+                to_editor_msg(Msg::TilePreviewToggled),
             ))
             .push(canvas_view(
-                7,
-                10,
-                self.selected_color,
+                CANVAS_X,
+                CANVAS_Y,
+                (self.selected_color, self.secondary_color),
                 &mut self.pixel_buttons,
                 selected_sprite,
+                transparent,
+                display,
             ))
             .push(flags(
                 selected_sprite_flags,
@@ -71,33 +225,224 @@ impl Editor {
                 70,
                 &mut self.flag_buttons,
                 editor_sprites,
-            ))
-            .push(
-                BrushSizeSelector {
-                    x: 79,
-                    y: 55,
-                    brush_size,
-                    selected_color: self.selected_color,
-                    on_press: super::Msg::BrushSizeSelected,
-                    on_hover: super::Msg::BrushSizeSliderHovered,
-                    state: &mut self.brush_size_state,
-                }
-                .view(),
-            )
-            .into()
+            ));
+
+        match brush_size {
+            Some(brush_size) => tree
+                .push(super::with_help(
+                    BrushSizeSelector {
+                        x: 79,
+                        y: 55,
+                        brush_size,
+                        selected_color: self.selected_color,
+                        on_press: super::Msg::BrushSizeSelected,
+                        state: &mut self.brush_size_state,
+                        editor_sprites,
+                    }
+                    .view(),
+                    // The size indicator and the slider next to it.
+                    (79, 54, 42, 8),
+                    Help::BrushSize,
+                ))
+                .into(),
+            None => tree.into(),
+        }
+    }
+}
+
+/// Cycles through the preview modes, with the current one's name next to it.
+fn tile_preview_toggle<'a>(
+    x: i32,
+    y: i32,
+    tile_preview: TilePreview,
+    state: &'a mut button::State,
+    on_press: super::Msg,
+) -> Element<'a, super::Msg> {
+    let open = tile_preview != TilePreview::Hidden;
+    let color = if open { 7 } else { 6 };
+
+    let button = Button::new(
+        x,
+        y,
+        7,
+        7,
+        Some(on_press),
+        state,
+        DrawFn::new(move |draw| {
+            // A tiny 3x3 grid.
+            draw.rect(0, 0, 6, 6, color);
+            draw.line(2, 0, 2, 6, color);
+            draw.line(4, 0, 4, 6, color);
+            draw.line(0, 2, 6, 2, color);
+            draw.line(0, 4, 6, 4, color);
+        }),
+    )
+    .focusable();
+
+    Tree::new()
+        .push(super::with_help(
+            button,
+            (x, y, 7, 7),
+            Help::Text("TILE PREVIEW, CLICK: CYCLE"),
+        ))
+        .push(DrawFn::new(move |draw| {
+            draw.print(tile_preview.label(), x + 9, y + 1, color)
+        }))
+        .into()
+}
+
+/// 3x3 sprites drawn at map scale, with the position of the middle cell on the map (if any).
+type Neighbourhood = (Option<(i32, i32)>, [usize; 9]);
+
+/// The cells around the first map cell (row by row) with `sprite`, cells outside the map are 0.
+/// `None` if the sprite isn't on the map.
+fn map_neighbourhood(resources: &Resources, sprite: usize) -> Option<Neighbourhood> {
+    let map_sprite = u8::try_from(sprite).ok()?;
+    let (width, height) = resources.map_size();
+    let (x, y) = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .find(|&(x, y)| resources.mget(x, y) == map_sprite)?;
+
+    let mut cells = [0; 9];
+    for (index, cell) in cells.iter_mut().enumerate() {
+        let (dx, dy) = (index as i32 % 3 - 1, index as i32 / 3 - 1);
+        *cell = resources.mget(x + dx, y + dy) as usize;
     }
+
+    Some((Some((x, y)), cells))
 }
 
-fn color_selector<'a, Msg: Debug + Copy + 'a>(
+fn tile_preview<'a>(
+    x: i32,
+    y: i32,
+    neighbourhood: Option<Neighbourhood>,
+) -> Element<'a, super::Msg> {
+    DrawFn::new(move |draw| {
+        let Some((position, sprites)) = neighbourhood else {
+            draw.print("NOT ON\nTHE MAP", x, y + 8, 6);
+            return;
+        };
+
+        draw.palt(None);
+        draw.rect(x - 1, y - 1, x + 24, y + 24, 0);
+        for (index, sprite) in sprites.into_iter().enumerate() {
+            let (column, row) = (index as i32 % 3, index as i32 / 3);
+            draw.spr(sprite, x + 8 * column, y + 8 * row);
+        }
+
+        if let Some((map_x, map_y)) = position {
+            draw.print(&format!("AT {map_x},{map_y}"), x - 1, y + 27, 6);
+        }
+    })
+    .into()
+}
+
+/// The sprite and its remapped copy at 2x, with the rules below them.
+/// Clicking a rule's swatch steps its color forward, right clicking steps it back.
+fn remap_preview<'a>(
+    x: i32,
+    y: i32,
+    (sprite_x, sprite_y): (i32, i32),
+    pal_remap: PalRemap,
+    buttons: &'a mut [button::State],
+    to_editor_msg: &(impl Fn(Msg) -> super::Msg + Copy),
+) -> Element<'a, super::Msg> {
+    // The two sprites, the rules' swatches and the copy button.
+    let mut elements = Vec::with_capacity(buttons.len() + 1);
+    elements.push(
+        DrawFn::new(move |draw| {
+            draw.palt(None);
+            for (dx, remapped) in [(0, false), (22, true)] {
+                let x = x + dx;
+                draw.rect(x - 1, y - 1, x + 16, y + 16, 0);
+                draw.with_saved_state(|draw| {
+                    if remapped {
+                        pal_remap.apply(draw);
+                    }
+                    draw.sspr(sprite_x, sprite_y, 8, 8, x, y, 16, 16);
+                });
+            }
+            draw.print(">", x + 18, y + 5, 6);
+
+            for rule in 0..pal_remap::RULE_COUNT {
+                let (rule_x, rule_y) = rule_position(x, y, rule);
+                draw.print(">", rule_x + 7, rule_y, 6);
+            }
+        })
+        .into(),
+    );
+
+    let (swatch_buttons, copy_button) = buttons.split_at_mut(2 * pal_remap::RULE_COUNT);
+    for (index, state) in swatch_buttons.iter_mut().enumerate() {
+        let (rule, to) = (index / 2, index % 2 == 1);
+        let (rule_x, rule_y) = rule_position(x, y, rule);
+        let swatch_x = if to { rule_x + 11 } else { rule_x };
+        let (from_color, to_color) = pal_remap.rule(rule);
+        let color = if to { to_color } else { from_color };
+        let step = move |forward| to_editor_msg(Msg::RemapColorStepped { rule, to, forward });
+
+        let button = Button::new(
+            swatch_x,
+            rule_y,
+            6,
+            6,
+            Some(step(true)),
+            state,
+            DrawFn::new(move |draw| {
+                draw.palt(None);
+                draw.rectfill(0, 0, 5, 5, color);
+                draw.rect(0, 0, 5, 5, 0);
+            }),
+        )
+        .on_right_press(step(false));
+
+        elements.push(super::with_help(
+            button,
+            (swatch_x, rule_y, 6, 6),
+            Help::Text("CLICK/RMB: NEXT/PREVIOUS COLOUR"),
+        ));
+    }
+
+    let (copy_x, copy_y) = (x - 1, y + 35);
+    let copy = Button::new(
+        copy_x,
+        copy_y,
+        19,
+        7,
+        Some(super::Msg::PalRemapCopied),
+        &mut copy_button[0],
+        DrawFn::new(|draw| {
+            draw.rectfill(0, 0, 18, 6, 6);
+            draw.print("COPY", 2, 1, 0);
+        }),
+    );
+    elements.push(super::with_help(
+        copy,
+        (copy_x, copy_y, 19, 7),
+        Help::Text("COPY THE RULES AS PAL() CALLS"),
+    ));
+
+    Tree::with_children(elements).into()
+}
+
+// Top left corner of a rule's from swatch, two rules per row under the sprites.
+fn rule_position(x: i32, y: i32, rule: usize) -> (i32, i32) {
+    (x + 22 * (rule as i32 % 2), y + 19 + 8 * (rule as i32 / 2))
+}
+
+/// Left clicking a color selects it as the primary color, and right clicking as the secondary one.
+/// The primary color has a ring around it, the secondary one a mark in its corner.
+fn color_selector(
     start_x: i32,
     start_y: i32,
     tile_size: i32,
-    selected_color: u8,
-    states: &'a mut [button::State],
-    on_press: impl (Fn(Color) -> Msg) + Copy,
-    on_hover: impl (Fn(Color) -> Msg) + Copy,
-) -> Element<'_, Msg> {
-    let mut v = Vec::with_capacity(16);
+    (selected_color, secondary_color): (Color, Color),
+    states: &mut [button::State],
+    on_press: impl (Fn(Color) -> super::Msg) + Copy,
+    on_right_press: impl (Fn(Color) -> super::Msg) + Copy,
+) -> Element<'_, super::Msg> {
+    // The colors, the border and the two highlights.
+    let mut v = Vec::with_capacity(16 + 3);
 
     let coordinates = move |index| {
         let i = index % 4;
@@ -108,26 +453,30 @@ fn color_selector<'a, Msg: Debug + Copy + 'a>(
         (x, y)
     };
 
-    for (index, state) in states.iter_mut().enumerate() {
-        let (x, y) = coordinates(index);
+    for (color, state) in Color::all().zip(states.iter_mut()) {
+        let (x, y) = coordinates(color.index() as usize);
 
-        let button: Element<'_, Msg> = Button::new(
+        let button = Button::new(
             x,
             y,
             tile_size,
             tile_size,
-            Some(on_press(index as Color)),
+            Some(on_press(color)),
             state,
             DrawFn::new(move |draw| {
                 draw.palt(None);
-                draw.rectfill(0, 0, tile_size - 1, tile_size - 1, index as u8);
-                draw.palt(Some(0));
+                draw.rectfill(0, 0, tile_size - 1, tile_size - 1, color);
+                draw.palt(Some(Color::BLACK));
             }),
         )
         .event_on_press()
-        .on_hover(on_hover(index as Color))
-        .into();
-        v.push(button);
+        .on_right_press(on_right_press(color));
+
+        v.push(super::with_help(
+            button,
+            (x, y, tile_size, tile_size),
+            Help::Color(color),
+        ));
     }
 
     // Draw border
@@ -141,7 +490,7 @@ fn color_selector<'a, Msg: Debug + Copy + 'a>(
                 start_y + 4 * tile_size + 1,
                 0,
             );
-            draw.palt(Some(0));
+            draw.palt(Some(Color::BLACK));
         })
         .into(),
     );
@@ -149,12 +498,133 @@ fn color_selector<'a, Msg: Debug + Copy + 'a>(
     // Draw highlight
     v.push(
         DrawFn::new(move |draw| {
-            let (x, y) = coordinates(selected_color as usize);
+            let (x, y) = coordinates(selected_color.index() as usize);
 
             draw.palt(None);
             draw.rect(x, y, x + tile_size - 1, y + tile_size - 1, 0);
             draw.rect(x - 1, y - 1, x + tile_size, y + tile_size, 7);
-            draw.palt(Some(0));
+            draw.palt(Some(Color::BLACK));
+        })
+        .into(),
+    );
+
+    v.push(
+        DrawFn::new(move |draw| {
+            let (x, y) = coordinates(secondary_color.index() as usize);
+            let (x, y) = (x + tile_size - 4, y + tile_size - 4);
+
+            draw.palt(None);
+            draw.rectfill(x, y, x + 3, y + 3, 0);
+            draw.rectfill(x + 1, y + 1, x + 3, y + 3, 7);
+            draw.palt(Some(Color::BLACK));
+        })
+        .into(),
+    );
+
+    Tree::with_children(v).into()
+}
+
+// Where the selector's page toggle is, right of the selector.
+const EXTENDED_TOGGLE: (i32, i32) = (122, 10);
+
+/// Switches the color selector's page, showing a bit of the other page's colors.
+fn extended_page_toggle(
+    extended_page: bool,
+    state: &mut button::State,
+    on_press: super::Msg,
+) -> Element<'_, super::Msg> {
+    let (x, y) = EXTENDED_TOGGLE;
+    let button = Button::new(
+        x,
+        y,
+        5,
+        9,
+        Some(on_press),
+        state,
+        DrawFn::new(move |draw| {
+            draw.palt(None);
+            draw.rect(0, 0, 4, 8, 0);
+            for (index, color) in [8, 9, 11, 12].into_iter().enumerate() {
+                let y = 1 + 2 * index as i32;
+                if extended_page {
+                    draw.rectfill(1, y, 3, y + 1, color);
+                } else {
+                    draw.rectfill_extended(1, y, 3, y + 1, color);
+                }
+            }
+        }),
+    );
+
+    super::with_help(
+        button,
+        (x, y, 5, 9),
+        Help::Text(if extended_page {
+            "BACK TO COLOURS 0-15"
+        } else {
+            "COLOURS 128-143 (SCREEN PAL)"
+        }),
+    )
+}
+
+/// Pico8's extended colors, which games can only show through `screen_pal`.
+/// Clicking one shows the primary color as it in the selected sprite (only in the editor),
+/// clicking it again shows the primary color as itself. It's ringed if it's what the primary color is shown as.
+fn extended_color_selector(
+    start_x: i32,
+    start_y: i32,
+    tile_size: i32,
+    primary_shown_as: Option<u8>,
+    states: &mut [button::State],
+) -> Element<'_, super::Msg> {
+    // The colors, the border and the highlight.
+    let mut v = Vec::with_capacity(16 + 2);
+
+    let coordinates = move |index: u8| {
+        let x = start_x + 1 + (index % 4) as i32 * tile_size;
+        let y = start_y + 1 + (index / 4) as i32 * tile_size;
+
+        (x, y)
+    };
+
+    for (extended, state) in (0..16).zip(states.iter_mut()) {
+        let (x, y) = coordinates(extended);
+
+        let button = Button::new(
+            x,
+            y,
+            tile_size,
+            tile_size,
+            Some(super::Msg::ExtendedColorChosen(extended)),
+            state,
+            DrawFn::new(move |draw| {
+                draw.rectfill_extended(0, 0, tile_size - 1, tile_size - 1, extended);
+            }),
+        )
+        .event_on_press();
+
+        v.push(super::with_help(
+            button,
+            (x, y, tile_size, tile_size),
+            Help::ExtendedColor(extended),
+        ));
+    }
+
+    v.push(
+        DrawFn::new(move |draw| {
+            draw.palt(None);
+            draw.rect(
+                start_x,
+                start_y,
+                start_x + 4 * tile_size + 1,
+                start_y + 4 * tile_size + 1,
+                0,
+            );
+            if let Some(extended) = primary_shown_as {
+                let (x, y) = coordinates(extended);
+                draw.rect(x, y, x + tile_size - 1, y + tile_size - 1, 0);
+                draw.rect(x - 1, y - 1, x + tile_size, y + tile_size, 7);
+            }
+            draw.palt(Some(Color::BLACK));
         })
         .into(),
     );
@@ -167,7 +637,7 @@ fn flags<'a>(
     x: i32,
     y: i32,
     flag_buttons: &'a mut [button::State],
-    _editor_sprites: &'a SpriteSheet,
+    editor_sprites: &'a SpriteSheet,
 ) -> Element<'a, super::Msg> {
     const SPR_SIZE: i32 = 5;
     const FLAG_COLORS: [u8; 8] = [8, 9, 10, 11, 12, 13, 14, 15];
@@ -180,23 +650,16 @@ fn flags<'a>(
             let flag_on = selected_sprite_flags & (1 << index) != 0;
             let color = if flag_on { FLAG_COLORS[index] } else { 1 };
 
-            let button_content: Element<'a, super::Msg> = Tree::new()
-                .push(DrawFn::new(move |pico8| {
-                    pico8.palt(Some(7));
+            let button_content = DrawFn::new(move |pico8| {
+                pico8.with_saved_state(|pico8| {
+                    pico8.palt(Some(Color::WHITE));
                     pico8.pal(1, color);
                     if flag_on {
                         pico8.pal(13, 7);
                     }
-                    // TODO: Use the editor sprite sheet (not doing so currently,
-                    // because it's still WIP).
-                    //
-                    // pico8.spr_from(editor_sprites, 58, 0, 0);
-                    pico8.spr(58, 0, 0);
-                    pico8.pal(1, 1);
-                    pico8.palt(Some(0));
-                    pico8.pal(13, 13);
-                }))
-                .into();
+                    pico8.spr_from(editor_sprites, 58, 0, 0);
+                });
+            });
 
             let button = Button::new(
                 x,
@@ -206,10 +669,9 @@ fn flags<'a>(
                 Some(super::Msg::FlagToggled(index)),
                 button,
                 button_content,
-            )
-            .on_hover(super::Msg::FlagHovered { bit_number: index });
+            );
 
-            button.into()
+            super::with_help(button, (x, y, 5, 5), Help::Flag(index))
         })
         .collect();
 
@@ -219,50 +681,296 @@ fn flags<'a>(
 fn canvas_view<'a, 'b>(
     x: i32,
     y: i32,
-    selected_color: Color,
+    (selected_color, secondary_color): (Color, Color),
     pixel_buttons: &'a mut [button::State],
     sprite: &'b Sprite,
+    transparent: bool,
+    display: [Option<u8>; 16],
 ) -> Element<'a, super::Msg> {
-    let mut elements = vec![];
+    // The pixels and the highlight.
+    let mut elements = Vec::with_capacity(pixel_buttons.len() + 1);
 
-    for (y_index, chunk) in pixel_buttons
-        .iter_mut()
-        .zip(sprite.iter())
-        .chunks(8)
-        .into_iter()
-        .enumerate()
-    {
+    for (y_index, row) in pixel_buttons.chunks_mut(Sprite::WIDTH).enumerate() {
         let y = y + 1 + (y_index * Sprite::HEIGHT) as i32;
-        for (x_index, (button, pixel_color)) in chunk.enumerate() {
+        for (x_index, button) in row.iter_mut().enumerate() {
             let x = x + 1 + (x_index * Sprite::WIDTH) as i32;
+            let pixel_color = sprite.pget(x_index as isize, y_index as isize);
 
-            elements.push(
-                Button::new(
-                    x,
-                    y,
-                    Sprite::WIDTH as i32,
-                    Sprite::HEIGHT as i32,
-                    Some(super::Msg::SpriteEdited {
-                        x: x_index,
-                        y: y_index,
-                        color: selected_color,
-                    }),
-                    button,
-                    DrawFn::new(move |draw| {
-                        draw.palt(None);
+            let button = Button::new(
+                x,
+                y,
+                Sprite::WIDTH as i32,
+                Sprite::HEIGHT as i32,
+                Some(super::Msg::SpriteEdited {
+                    x: x_index,
+                    y: y_index,
+                    color: selected_color,
+                }),
+                button,
+                DrawFn::new(move |draw| {
+                    draw.palt(None);
+                    if transparent && pixel_color == Color::BLACK {
+                        transparency::checkerboard(draw, 0, 0, 8, 8, 4);
+                    } else if let Some(extended) = display[pixel_color.index() as usize] {
+                        draw.rectfill_extended(0, 0, 7, 7, extended);
+                    } else {
                         draw.rectfill(0, 0, 7, 7, pixel_color);
-                    }),
-                )
-                .event_on_press()
-                .into(),
+                    }
+                }),
             )
+            .event_on_press()
+            .on_right_press(super::Msg::SpriteEdited {
+                x: x_index,
+                y: y_index,
+                color: secondary_color,
+            });
+
+            // Same area as the pixel's button, so it follows the canvas' scale.
+            elements.push(super::with_help(
+                button,
+                (x, y, Sprite::WIDTH as i32, Sprite::HEIGHT as i32),
+                Help::Pixel {
+                    x: x_index,
+                    y: y_index,
+                },
+            ))
         }
     }
 
-    let highlight = DrawFn::new(move |draw| {
-        draw.palt(None);
-        draw.rect(x, y, x + 64 + 1, y + 64 + 1, 0)
-    });
+    elements.push(
+        DrawFn::new(move |draw| {
+            draw.palt(None);
+            draw.rect(x, y, x + 64 + 1, y + 64 + 1, 0)
+        })
+        .into(),
+    );
+
+    Tree::with_children(elements).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::map::Map;
+    use crate::ui::testing::{click, mouse_move, tick, Harness};
+    use crate::{Event, MouseButton, MouseEvent};
+
+    #[test]
+    fn finds_the_first_map_cell_with_the_sprite() {
+        let mut resources = Resources {
+            map: Map::with_size(16, 16),
+            ..Resources::empty()
+        };
+        resources.mset(4, 1, 3);
+        resources.mset(5, 2, 7);
+        resources.mset(6, 2, 9);
+        resources.mset(1, 10, 7);
+
+        assert_eq!(
+            map_neighbourhood(&resources, 7),
+            Some((Some((5, 2)), [3, 0, 0, 0, 7, 9, 0, 0, 0]))
+        );
+        assert_eq!(map_neighbourhood(&resources, 8), None);
+        assert_eq!(map_neighbourhood(&resources, 256 + 7), None);
+
+        // Cells outside of the map are empty.
+        resources.mset(0, 0, 12);
+        resources.mset(1, 1, 5);
+        assert_eq!(
+            map_neighbourhood(&resources, 12),
+            Some((Some((0, 0)), [0, 0, 0, 0, 12, 0, 0, 0, 5]))
+        );
+    }
+
+    // Sprite 1 has a red top left pixel and a blue bottom right one,
+    // and sits on the map next to sprite 2 (with a green top left pixel).
+    fn preview_resources() -> Resources {
+        let mut resources = Resources {
+            map: Map::with_size(16, 16),
+            ..Resources::empty()
+        };
+        resources.sset(8, 0, 8);
+        resources.sset(15, 7, 12);
+        resources.sset(16, 0, 11);
+        resources.mset(3, 3, 1);
+        resources.mset(4, 3, 2);
+
+        resources
+    }
+
+    fn preview_view<'a>(
+        (editor, editor_sprites, resources): &'a mut (Editor, SpriteSheet, Resources),
+    ) -> Element<'a, super::super::Msg> {
+        editor.view(
+            1,
+            0,
+            resources.sprite_sheet.get_sprite(1),
+            false,
+            [None; 16],
+            editor_sprites,
+            Some(BrushSize::tiny()),
+            resources,
+            &super::super::Msg::SpriteEditorMsg,
+        )
+    }
+
+    #[test]
+    fn preview_cycles_through_its_modes() {
+        // The harness draws sprites from its own resources.
+        let mut harness = Harness::with_resources(preview_resources());
+        let mut state = (Editor::new(), SpriteSheet::new(), preview_resources());
+        let toggle = |harness: &mut Harness, state: &mut (Editor, SpriteSheet, Resources)| {
+            for msg in harness.run(state, preview_view, click(82, 66)) {
+                if let super::super::Msg::SpriteEditorMsg(msg) = msg {
+                    state.0.update(msg);
+                }
+            }
+            harness.run(state, preview_view, [tick()]);
+        };
+
+        // The palette is there until the preview is opened.
+        harness.run(&mut state, preview_view, [tick()]);
+        assert_eq!(harness.pixel(95, 15), Color::DARK_BLUE);
+
+        toggle(&mut harness, &mut state);
+        assert_eq!(state.0.tile_preview, TilePreview::Tiled);
+        for (column, row) in [(0, 0), (1, 1), (2, 2)] {
+            let (x, y) = (87 + 8 * column, 17 + 8 * row);
+            assert_eq!(harness.pixel(x, y), Color::RED);
+            assert_eq!(harness.pixel(x + 7, y + 7), Color::BLUE);
+        }
 
-    Tree::with_children(elements).push(highlight).into()
+        // Sprite 2 is to the right of the first (and only) cell with sprite 1.
+        toggle(&mut harness, &mut state);
+        assert_eq!(state.0.tile_preview, TilePreview::OnMap);
+        assert_eq!(harness.pixel(87 + 8, 17 + 8), Color::RED);
+        assert_eq!(harness.pixel(87 + 16, 17 + 8), Color::GREEN);
+        assert_eq!(harness.pixel(87, 17), Color::BLACK);
+
+        // No rules yet, so both copies look the same.
+        toggle(&mut harness, &mut state);
+        assert_eq!(state.0.tile_preview, TilePreview::Remap);
+        assert_eq!(harness.pixel(81, 12), Color::RED);
+        assert_eq!(harness.pixel(103, 12), Color::RED);
+
+        toggle(&mut harness, &mut state);
+        assert_eq!(state.0.tile_preview, TilePreview::Hidden);
+        assert_eq!(harness.pixel(95, 15), Color::DARK_BLUE);
+    }
+
+    // Runs the events, updating the sprite editor with its messages and returning the others.
+    fn run_and_update(
+        harness: &mut Harness,
+        state: &mut (Editor, SpriteSheet, Resources),
+        events: impl IntoIterator<Item = Event>,
+    ) -> Vec<super::super::Msg> {
+        let mut msgs = harness.run(state, preview_view, events);
+        msgs.retain(|&msg| match msg {
+            super::super::Msg::SpriteEditorMsg(msg) => {
+                state.0.update(msg);
+                false
+            }
+            _ => true,
+        });
+        harness.run(state, preview_view, [tick()]);
+
+        msgs
+    }
+
+    #[test]
+    fn remap_preview_leaves_the_sprite_alone() {
+        let mut harness = Harness::with_resources(preview_resources());
+        let mut state = (Editor::new(), SpriteSheet::new(), preview_resources());
+        let right_click = |x, y| {
+            [
+                mouse_move(x, y),
+                Event::Mouse(MouseEvent::Down(MouseButton::Right)),
+                Event::Mouse(MouseEvent::Up(MouseButton::Right)),
+            ]
+        };
+        for _ in 0..3 {
+            run_and_update(&mut harness, &mut state, click(82, 66));
+        }
+        assert_eq!(state.0.tile_preview, TilePreview::Remap);
+
+        // Red to blue: forward from black to red, back from black to blue.
+        for _ in 0..8 {
+            run_and_update(&mut harness, &mut state, click(83, 33));
+        }
+        for _ in 0..4 {
+            run_and_update(&mut harness, &mut state, right_click(94, 33));
+        }
+        assert_eq!(state.0.pal_remap().rule(0), (Color::RED, Color::BLUE));
+
+        assert_eq!(harness.pixel(81, 12), Color::RED);
+        assert_eq!(harness.pixel(103, 12), Color::BLUE);
+        assert_eq!(harness.pixel(118, 27), Color::BLUE);
+        assert_eq!(state.2.sprite_sheet.get_sprite(1).pget(0, 0), Color::RED);
+
+        let msgs = run_and_update(&mut harness, &mut state, click(85, 50));
+        assert!(matches!(msgs[..], [super::super::Msg::PalRemapCopied]));
+    }
+
+    #[test]
+    fn hovering_the_canvas_shows_pixel_coordinates() {
+        let mut harness = Harness::new();
+        let mut state = (Editor::new(), SpriteSheet::new(), Resources::empty());
+        let mut hover = |x, y| {
+            harness
+                .run(&mut state, preview_view, [mouse_move(x, y), tick()])
+                .into_iter()
+                .map(|msg| format!("{msg:?}"))
+                .collect::<Vec<_>>()
+        };
+
+        // Each pixel is 8x8 on screen, starting right inside the canvas' border.
+        assert_eq!(hover(48, 27), ["HelpShown(Pixel { x: 5, y: 2 })"]);
+        assert_eq!(hover(55, 34), Vec::<String>::new());
+        assert_eq!(
+            hover(71, 73),
+            [
+                "HelpHidden(Pixel { x: 5, y: 2 })",
+                "HelpShown(Pixel { x: 7, y: 7 })"
+            ]
+        );
+        assert_eq!(hover(72, 73), ["HelpHidden(Pixel { x: 7, y: 7 })"]);
+        assert_eq!(hover(8, 11), ["HelpShown(Pixel { x: 0, y: 0 })"]);
+    }
+
+    #[test]
+    fn right_button_paints_with_the_secondary_color() {
+        let mut harness = Harness::new();
+        let mut state = (Editor::new(), SpriteSheet::new(), Resources::empty());
+        let right_click = |x, y| {
+            [
+                mouse_move(x, y),
+                Event::Mouse(MouseEvent::Down(MouseButton::Right)),
+                Event::Mouse(MouseEvent::Up(MouseButton::Right)),
+            ]
+        };
+
+        // Right clicking the red swatch.
+        for msg in harness.run(&mut state, preview_view, right_click(84, 35)) {
+            if let super::super::Msg::SpriteEditorMsg(msg) = msg {
+                state.0.update(msg);
+            }
+        }
+        assert_eq!(state.0.secondary_color, Color::RED);
+        assert_eq!(state.0.selected_color, Color::BLACK);
+
+        // Marked in its bottom right corner.
+        harness.run(&mut state, preview_view, [tick()]);
+        assert_eq!(harness.pixel(88, 39), Color::WHITE);
+        assert_eq!(harness.pixel(84, 35), Color::RED);
+
+        let msgs = harness.run(&mut state, preview_view, right_click(18, 21));
+        assert!(matches!(
+            msgs[..],
+            [super::super::Msg::SpriteEdited {
+                x: 1,
+                y: 1,
+                color: Color::RED
+            }]
+        ));
+    }
 }
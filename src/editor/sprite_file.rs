@@ -0,0 +1,169 @@
+//! Sprites written to their own small file, to carry them over to another game.
+//!
+//! Same format as `sprite_sheet.txt`, one hex digit per pixel and a line per row of pixels,
+//! with several sprites side by side: 8 lines of 8 pixels per sprite.
+
+use itertools::Itertools;
+
+use crate::runtime::sprite_sheet::Sprite;
+use crate::Color;
+
+/// Pixels read from a sprite file, not necessarily a whole number of sprites.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SpriteStrip {
+    rows: Vec<Vec<Color>>,
+}
+
+impl SpriteStrip {
+    pub(crate) fn from_sprites(sprites: &[Sprite]) -> Self {
+        let rows = (0..Sprite::HEIGHT)
+            .map(|y| {
+                sprites
+                    .iter()
+                    .flat_map(|sprite| {
+                        (0..Sprite::WIDTH).map(move |x| sprite.pget(x as isize, y as isize))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self { rows }
+    }
+
+    pub(crate) fn to_text(&self) -> String {
+        self.rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|color| format!("{:X}", color.index()))
+                    .join("")
+            })
+            .join("\n")
+    }
+
+    /// Every line has to be as long as the first one.
+    pub(crate) fn from_text(str: &str) -> Result<Self, String> {
+        let rows: Vec<Vec<Color>> = str
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                line.chars()
+                    .map(|c| {
+                        c.to_digit(16)
+                            .map(|color| Color::from(color as u8))
+                            .ok_or_else(|| format!("Invalid color {c}"))
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+
+        let width = rows.first().map_or(0, Vec::len);
+        if width == 0 {
+            return Err("No pixels".to_owned());
+        }
+        if let Some((line, row)) = rows.iter().find_position(|row| row.len() != width) {
+            return Err(format!(
+                "Line {} has {} pixels instead of {width}",
+                line + 1,
+                row.len()
+            ));
+        }
+
+        Ok(Self { rows })
+    }
+
+    /// How many sprites the pixels reach into, side by side.
+    pub(crate) fn sprite_count(&self) -> usize {
+        self.rows[0].len().div_ceil(Sprite::WIDTH)
+    }
+
+    /// Draws the pixels over `sprites`, from the first one's top left corner.
+    /// What doesn't fit (more sprites, or more than 8 rows) is left out,
+    /// and pixels the strip doesn't reach are left as they were.
+    pub(crate) fn paste_onto(&self, sprites: &mut [Sprite]) {
+        for (y, row) in self.rows.iter().take(Sprite::HEIGHT).enumerate() {
+            for (x, &color) in row.iter().enumerate() {
+                if let Some(sprite) = sprites.get_mut(x / Sprite::WIDTH) {
+                    sprite.pset((x % Sprite::WIDTH) as isize, y as isize, color);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sprite(color: u8) -> Sprite {
+        let mut sprite = Sprite::EMPTY;
+        sprite.pset(0, 0, Color::from(color));
+        sprite.pset(7, 7, Color::from(15 - color));
+
+        sprite
+    }
+
+    #[test]
+    fn single_sprites_round_trip() {
+        let strip = SpriteStrip::from_sprites(&[sprite(8)]);
+        let text = strip.to_text();
+
+        assert_eq!(text.lines().count(), 8);
+        assert_eq!(text.lines().next(), Some("80000000"));
+        assert_eq!(text.lines().last(), Some("00000007"));
+
+        let mut imported = [Sprite::EMPTY];
+        SpriteStrip::from_text(&text)
+            .unwrap()
+            .paste_onto(&mut imported);
+        assert_eq!(imported, [sprite(8)]);
+    }
+
+    #[test]
+    fn several_sprites_round_trip() {
+        let sprites = [sprite(1), sprite(2), sprite(3)];
+        let strip = SpriteStrip::from_sprites(&sprites);
+
+        assert_eq!(strip.sprite_count(), 3);
+        assert!(strip.to_text().lines().all(|line| line.len() == 24));
+
+        let mut imported = [Sprite::EMPTY; 3];
+        SpriteStrip::from_text(&strip.to_text())
+            .unwrap()
+            .paste_onto(&mut imported);
+        assert_eq!(imported, sprites);
+    }
+
+    #[test]
+    fn bigger_strips_are_clipped() {
+        let strip = SpriteStrip::from_sprites(&[sprite(1), sprite(2)]);
+        let mut text = strip.to_text();
+        text.push_str("\nFFFFFFFFFFFFFFFF");
+
+        let mut imported = [Sprite::EMPTY];
+        SpriteStrip::from_text(&text)
+            .unwrap()
+            .paste_onto(&mut imported);
+        assert_eq!(imported, [sprite(1)]);
+    }
+
+    #[test]
+    fn smaller_strips_leave_the_rest_alone() {
+        let mut imported = [sprite(8)];
+        SpriteStrip::from_text("CC\nCC")
+            .unwrap()
+            .paste_onto(&mut imported);
+
+        assert_eq!(imported[0].pget(1, 1), Color::BLUE);
+        assert_eq!(imported[0].pget(2, 2), Color::BLACK);
+        assert_eq!(imported[0].pget(7, 7), Color::WHITE);
+    }
+
+    #[test]
+    fn malformed_files_are_rejected() {
+        for malformed in ["", "\n\n", "0012\n00G0", "0000\n000"] {
+            assert!(SpriteStrip::from_text(malformed).is_err(), "{malformed:?}");
+        }
+    }
+}
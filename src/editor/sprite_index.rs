@@ -0,0 +1,158 @@
+//! Where sprites are in the sprite view: pages of 4 rows of 16 sprites, 8x8 pixels each.
+//!
+//! Positions are in pixels from the top left corner of the page's grid.
+
+use crate::runtime::sprite_sheet::Sprite;
+use crate::Key;
+
+pub(crate) const SPRITES_PER_PAGE: usize = 64;
+const COLUMNS: usize = 16;
+const ROWS: usize = SPRITES_PER_PAGE / COLUMNS;
+
+pub(crate) fn page_of(index: usize) -> usize {
+    index / SPRITES_PER_PAGE
+}
+
+/// Where in its page the sprite is, from 0 (top left) to 63 (bottom right), row by row.
+pub(crate) fn slot_in_page(index: usize) -> usize {
+    index % SPRITES_PER_PAGE
+}
+
+/// The sprite in `slot` of `page`, the reverse of [`page_of`] and [`slot_in_page`].
+pub(crate) fn index(page: usize, slot: usize) -> usize {
+    page * SPRITES_PER_PAGE + slot
+}
+
+/// Top left corner of the sprite's slot, whichever page it's on.
+pub(crate) fn position_in_grid(index: usize) -> (i32, i32) {
+    let slot = slot_in_page(index);
+    let (column, row) = (slot % COLUMNS, slot / COLUMNS);
+
+    (
+        (column * Sprite::WIDTH) as i32,
+        (row * Sprite::HEIGHT) as i32,
+    )
+}
+
+/// The sprite under (`x`, `y`) while `page` is showing, `None` outside of the grid.
+pub(crate) fn index_from_click(page: usize, x: i32, y: i32) -> Option<usize> {
+    let column = usize::try_from(x).ok()? / Sprite::WIDTH;
+    let row = usize::try_from(y).ok()? / Sprite::HEIGHT;
+
+    (column < COLUMNS && row < ROWS).then(|| index(page, row * COLUMNS + column))
+}
+
+/// Where `key` moves the selection from `sprite` in a sheet of `pages` pages,
+/// `None` for keys that don't move it.
+///
+/// Arrows move one slot, crossing the page's edge onto the same row (or column) of the next
+/// (or previous) page, and PageUp / PageDown move to the same slot of the previous / next page.
+/// The selection stays put at the sheet's edges.
+pub(crate) fn step(sprite: usize, key: Key, pages: usize) -> Option<usize> {
+    let page = page_of(sprite);
+    let slot = slot_in_page(sprite);
+    let (column, row) = (slot % COLUMNS, slot / COLUMNS);
+    let last_page = pages.saturating_sub(1);
+
+    let (page, column, row) = match key {
+        Key::LeftArrow if column > 0 => (page, column - 1, row),
+        Key::LeftArrow if page > 0 => (page - 1, COLUMNS - 1, row),
+        Key::RightArrow if column < COLUMNS - 1 => (page, column + 1, row),
+        Key::RightArrow if page < last_page => (page + 1, 0, row),
+        Key::UpArrow if row > 0 => (page, column, row - 1),
+        Key::UpArrow if page > 0 => (page - 1, column, ROWS - 1),
+        Key::DownArrow if row < ROWS - 1 => (page, column, row + 1),
+        Key::DownArrow if page < last_page => (page + 1, column, 0),
+        Key::PageUp => (page.saturating_sub(1), column, row),
+        Key::PageDown => ((page + 1).min(last_page), column, row),
+        Key::LeftArrow | Key::RightArrow | Key::UpArrow | Key::DownArrow => (page, column, row),
+        _ => return None,
+    };
+
+    Some(index(page, row * COLUMNS + column))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_and_slots() {
+        assert_eq!((page_of(0), slot_in_page(0)), (0, 0));
+        assert_eq!((page_of(63), slot_in_page(63)), (0, 63));
+        assert_eq!((page_of(64), slot_in_page(64)), (1, 0));
+        assert_eq!((page_of(255), slot_in_page(255)), (3, 63));
+
+        for sprite in 0..256 {
+            assert_eq!(index(page_of(sprite), slot_in_page(sprite)), sprite);
+        }
+    }
+
+    #[test]
+    fn positions_in_the_grid() {
+        assert_eq!(position_in_grid(0), (0, 0));
+        assert_eq!(position_in_grid(15), (120, 0));
+        assert_eq!(position_in_grid(16), (0, 8));
+        assert_eq!(position_in_grid(63), (120, 24));
+        // The same on every page.
+        assert_eq!(position_in_grid(64), (0, 0));
+        assert_eq!(position_in_grid(200), position_in_grid(200 - 3 * 64));
+
+        for sprite in 0..256 {
+            let (x, y) = position_in_grid(sprite);
+            assert!((0..128).contains(&x) && (0..32).contains(&y), "{sprite}");
+        }
+    }
+
+    #[test]
+    fn clicks_anywhere_in_a_slot_find_its_sprite() {
+        for sprite in 0..256 {
+            let page = page_of(sprite);
+            let (x, y) = position_in_grid(sprite);
+
+            for (dx, dy) in [(0, 0), (7, 0), (0, 7), (7, 7), (3, 4)] {
+                assert_eq!(index_from_click(page, x + dx, y + dy), Some(sprite));
+            }
+            // On any other page, it's the sprite in the same slot.
+            let other_page = (page + 1) % 4;
+            assert_eq!(
+                index_from_click(other_page, x, y),
+                Some(index(other_page, slot_in_page(sprite)))
+            );
+        }
+    }
+
+    #[test]
+    fn clicks_outside_the_grid() {
+        for (x, y) in [(-1, 0), (0, -1), (128, 0), (0, 32), (128, 32), (-8, -8)] {
+            assert_eq!(index_from_click(0, x, y), None, "{x}, {y}");
+        }
+    }
+
+    #[test]
+    fn arrows_cross_onto_the_neighbouring_pages() {
+        // Sprite 20 is in the middle of page 0's second row.
+        assert_eq!(step(20, Key::LeftArrow, 4), Some(19));
+        assert_eq!(step(20, Key::UpArrow, 4), Some(4));
+        assert_eq!(step(20, Key::DownArrow, 4), Some(36));
+        assert_eq!(step(20, Key::PageDown, 4), Some(84));
+
+        // Page 1's left column is next to page 0's right column, its top row under its bottom row.
+        assert_eq!(step(80, Key::LeftArrow, 4), Some(31));
+        assert_eq!(step(31, Key::RightArrow, 4), Some(80));
+        assert_eq!(step(68, Key::UpArrow, 4), Some(52));
+        assert_eq!(step(52, Key::DownArrow, 4), Some(68));
+    }
+
+    #[test]
+    fn the_selection_stays_at_the_sheets_edges() {
+        assert_eq!(step(0, Key::LeftArrow, 4), Some(0));
+        assert_eq!(step(0, Key::UpArrow, 4), Some(0));
+        assert_eq!(step(0, Key::PageUp, 4), Some(0));
+        assert_eq!(step(255, Key::RightArrow, 4), Some(255));
+        assert_eq!(step(255, Key::DownArrow, 4), Some(255));
+        assert_eq!(step(255, Key::PageDown, 4), Some(255));
+
+        assert_eq!(step(20, Key::A, 4), None);
+    }
+}
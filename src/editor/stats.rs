@@ -0,0 +1,140 @@
+//! How much of the sprite sheet a game uses, to find what can be cleaned up before shipping.
+
+use std::fmt::Debug;
+
+use crate::ui::{DrawFn, Element};
+use crate::{Color, Resources};
+
+/// Usage of the sprite sheet, scanned once when the stats page is opened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SheetStats {
+    pub(crate) sprites: usize,
+    /// Sprites with every pixel of color 0.
+    pub(crate) blank_sprites: usize,
+    /// Sprites with something drawn on them that never appear on the map.
+    /// Cells with sprite 0 are empty, and don't count as using it.
+    pub(crate) unused_sprites: usize,
+    /// How many pixels of each color there are in the whole sheet.
+    pub(crate) color_pixels: [usize; 16],
+    /// How many sprites have each flag set.
+    pub(crate) flag_sprites: [usize; 8],
+}
+
+impl SheetStats {
+    pub(crate) fn scan(resources: &Resources) -> Self {
+        let mut on_map = vec![false; resources.sprite_sheet.sprite_count()];
+        let (width, height) = resources.map_size();
+        for (x, y) in (0..height).flat_map(|y| (0..width).map(move |x| (x, y))) {
+            on_map[resources.mget(x, y) as usize] = true;
+        }
+
+        let mut stats = Self {
+            sprites: on_map.len(),
+            blank_sprites: 0,
+            unused_sprites: 0,
+            color_pixels: [0; 16],
+            flag_sprites: [0; 8],
+        };
+
+        for (sprite, on_map) in on_map.into_iter().enumerate() {
+            let mut blank = true;
+            for (_, _, color) in resources.sprite_sheet.get_sprite(sprite).iter_pixels() {
+                stats.color_pixels[color.index() as usize] += 1;
+                blank &= color == Color::BLACK;
+            }
+
+            if blank {
+                stats.blank_sprites += 1;
+            } else if sprite == 0 || !on_map {
+                stats.unused_sprites += 1;
+            }
+        }
+
+        // Only the first 256 sprites have flags.
+        stats.flag_sprites =
+            std::array::from_fn(|flag| resources.sprite_flags.count_with_flag(flag as u8));
+
+        stats
+    }
+
+    /// A page listing the stats, covering everything between the top and bottom bars.
+    pub(crate) fn view<'a, Msg: Copy + Debug + 'a>(&self) -> Element<'a, Msg> {
+        let stats = self.clone();
+
+        DrawFn::new(move |draw| {
+            draw.rectfill(0, 8, 127, 120, 1);
+            draw.print("SPRITE SHEET", 2, 10, 7);
+            draw.print(
+                &format!(
+                    "BLANK SPRITES: {}/{}\nNOT ON THE MAP: {}",
+                    stats.blank_sprites, stats.sprites, stats.unused_sprites
+                ),
+                2,
+                18,
+                6,
+            );
+
+            draw.print("PIXELS PER COLOUR", 2, 34, 7);
+            for (color, count) in stats.color_pixels.iter().enumerate() {
+                let x = 2 + 32 * (color as i32 % 4);
+                let y = 42 + 7 * (color as i32 / 4);
+                draw.rectfill(x, y, x + 4, y + 4, color as u8);
+                draw.rect(x, y, x + 4, y + 4, 6);
+                draw.print(&count.to_string(), x + 7, y, 6);
+            }
+
+            draw.print("SPRITES PER FLAG", 2, 74, 7);
+            for (flag, count) in stats.flag_sprites.iter().enumerate() {
+                let x = 2 + 32 * (flag as i32 % 4);
+                let y = 82 + 7 * (flag as i32 / 4);
+                draw.print(&format!("{flag}: {count}"), x, y, 6);
+            }
+
+            draw.print("CTRL+I TO CLOSE", 2, 112, 13);
+        })
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_sprite_sheet_usage() {
+        let mut resources = Resources::empty();
+        // Sprite 1 is on the map, 2 isn't, and 0 never counts as being on the map.
+        resources.sset(0, 0, 8);
+        resources.sset(8, 0, 8);
+        resources.sset(9, 1, 12);
+        resources.sset(16, 0, 7);
+        resources.mset(3, 3, 1);
+        resources.mset(4, 3, 0);
+        resources.fset(1, 0, true);
+        resources.fset(2, 0, true);
+        resources.fset(2, 7, true);
+
+        let stats = SheetStats::scan(&resources);
+
+        assert_eq!(stats.blank_sprites, 253);
+        assert_eq!(stats.unused_sprites, 2);
+        assert_eq!(stats.color_pixels[8], 2);
+        assert_eq!(stats.color_pixels[12], 1);
+        assert_eq!(stats.color_pixels[7], 1);
+        assert_eq!(stats.color_pixels[0], 256 * 64 - 4);
+        assert_eq!(stats.flag_sprites, [2, 0, 0, 0, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn counts_every_sprite_of_a_double_sheet() {
+        let mut resources = Resources::empty();
+        resources.set_sprite_sheet_size(crate::SpriteSheetSize::Double);
+        resources.sset(255, 127, 8);
+
+        let stats = SheetStats::scan(&resources);
+
+        assert_eq!(stats.sprites, 512);
+        assert_eq!(stats.blank_sprites, 511);
+        assert_eq!(stats.unused_sprites, 1);
+    }
+}
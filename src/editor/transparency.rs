@@ -0,0 +1,127 @@
+//! Which sprite pages the editor draws with color 0 transparent, the way the game draws them.
+//! Some pages hold tiles drawn over others, some UI where black is black,
+//! so it's chosen per page. Only the editor's previews use it, never the game.
+
+use itertools::Itertools;
+
+use super::sprite_index::page_of;
+use crate::{Color, Pico8};
+
+// Sprite sheets have at most 8 pages.
+const MAX_PAGES: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TransparentPages {
+    // One bit per page, page 0 in the lowest.
+    pages: u8,
+}
+
+impl TransparentPages {
+    /// Color 0 is opaque on every page.
+    pub(crate) fn new() -> Self {
+        Self { pages: 0 }
+    }
+
+    pub(crate) fn contains_page(self, page: usize) -> bool {
+        page < MAX_PAGES && self.pages & (1 << page) != 0
+    }
+
+    pub(crate) fn contains_sprite(self, sprite: usize) -> bool {
+        self.contains_page(page_of(sprite))
+    }
+
+    pub(crate) fn toggle(&mut self, page: usize) {
+        if page < MAX_PAGES {
+            self.pages ^= 1 << page;
+        }
+    }
+
+    /// The `palt` to draw `sprite` with.
+    pub(crate) fn palt(self, sprite: usize) -> Option<Color> {
+        self.contains_sprite(sprite).then_some(Color::BLACK)
+    }
+
+    /// The transparent pages separated by spaces, or "none".
+    pub(crate) fn to_human_readable(self) -> String {
+        if self.pages == 0 {
+            return "none".to_owned();
+        }
+
+        (0..MAX_PAGES)
+            .filter(|&page| self.contains_page(page))
+            .join(" ")
+    }
+
+    /// Pages written by [`TransparentPages::to_human_readable`].
+    pub(crate) fn from_human_readable(str: &str) -> Option<Self> {
+        let mut pages = Self::new();
+        if str.trim() == "none" {
+            return Some(pages);
+        }
+
+        for page in str.split_whitespace() {
+            let page = page
+                .parse::<usize>()
+                .ok()
+                .filter(|&page| page < MAX_PAGES)?;
+            if !pages.contains_page(page) {
+                pages.toggle(page);
+            }
+        }
+
+        Some(pages)
+    }
+}
+
+/// Where transparent pixels are drawn, so they don't look like black ones:
+/// `cell` pixels wide squares, black and dark blue.
+pub(crate) fn checkerboard(draw: &mut Pico8, x: i32, y: i32, width: i32, height: i32, cell: i32) {
+    draw.rectfill(x, y, x + width - 1, y + height - 1, 0);
+    for row in 0..height / cell {
+        for column in (row % 2..width / cell).step_by(2) {
+            let (cell_x, cell_y) = (x + column * cell, y + row * cell);
+            draw.rectfill(cell_x, cell_y, cell_x + cell - 1, cell_y + cell - 1, 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_are_toggled_one_by_one() {
+        let mut pages = TransparentPages::new();
+        pages.toggle(1);
+        pages.toggle(3);
+        pages.toggle(1);
+        // Past the biggest sheet, nothing to toggle.
+        pages.toggle(8);
+
+        assert!(!pages.contains_page(1));
+        assert!(pages.contains_page(3));
+        assert!(!pages.contains_page(8));
+        assert_eq!(pages.palt(3 * 64), Some(Color::BLACK));
+        assert_eq!(pages.palt(3 * 64 - 1), None);
+    }
+
+    #[test]
+    fn pages_round_trip() {
+        let mut pages = TransparentPages::new();
+        assert_eq!(pages.to_human_readable(), "none");
+        pages.toggle(0);
+        pages.toggle(5);
+
+        assert_eq!(pages.to_human_readable(), "0 5");
+        assert_eq!(
+            TransparentPages::from_human_readable(&pages.to_human_readable()),
+            Some(pages)
+        );
+        assert_eq!(
+            TransparentPages::from_human_readable("none"),
+            Some(TransparentPages::new())
+        );
+        assert_eq!(TransparentPages::from_human_readable("0 8"), None);
+        assert_eq!(TransparentPages::from_human_readable("one"), None);
+    }
+}
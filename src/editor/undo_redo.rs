@@ -1,4 +1,6 @@
-use crate::runtime::sprite_sheet::SpriteSheet;
+use crate::audio::sfx::Sfx;
+use crate::runtime::sprite_sheet::{Sprite, SpriteSheet};
+use crate::{Color, Resources};
 
 use super::notification;
 
@@ -22,23 +24,23 @@ impl Commands {
         self.current += 1;
     }
 
-    pub fn undo(&mut self, notification: &mut notification::State, sprite_sheet: &mut SpriteSheet) {
+    pub fn undo(&mut self, notification: &mut notification::State, resources: &mut Resources) {
         if self.current > 0 {
             let command = &self.commands[self.current - 1];
             self.current -= 1;
 
-            command.undo(sprite_sheet);
+            command.undo(resources);
         } else {
             notification.alert("NOTHING TO UNDO".to_owned());
         }
     }
 
-    pub fn redo(&mut self, notification: &mut notification::State, sprite_sheet: &mut SpriteSheet) {
+    pub fn redo(&mut self, notification: &mut notification::State, resources: &mut Resources) {
         if self.current < self.commands.len() {
             let command = &self.commands[self.current];
             self.current += 1;
 
-            command.redo(sprite_sheet);
+            command.redo(resources);
         } else {
             notification.alert("NOTHING TO REDO".to_owned());
         }
@@ -47,11 +49,21 @@ impl Commands {
 
 // Undoable actions
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Command {
     // TODO: This currently treats each edited pixel as its own action.
     // Pico8 instead tracks "strokes", i.e, drawing with the pen until you lift it
     // counts as a single command/undoable action. We should do that.
     PixelChanged(PixelChanged),
+    // A whole sprite changed at once, like when filling it with a gradient.
+    SpriteChanged(SpriteChanged),
+    // Consecutive sprites changed at once, like when importing several of them.
+    SpritesChanged(SpritesChanged),
+    // Same as pixels, every edited note is its own action.
+    // Boxed, since a whole sfx is much bigger than a pixel.
+    SfxChanged(Box<SfxChanged>),
+    // Several map cells changed at once, like when moving a selection.
+    MapChanged(MapChanged),
 }
 
 impl Command {
@@ -59,8 +71,8 @@ impl Command {
         sprite: usize,
         x: isize,
         y: isize,
-        previous_color: u8,
-        new_color: u8,
+        previous_color: Color,
+        new_color: Color,
     ) -> Self {
         Self::PixelChanged(PixelChanged {
             sprite,
@@ -71,15 +83,66 @@ impl Command {
         })
     }
 
-    fn undo(&self, sprite_sheet: &mut SpriteSheet) {
+    pub fn sprite_changed(sprite: usize, previous: Sprite, new: Sprite) -> Self {
+        Self::SpriteChanged(SpriteChanged {
+            sprite,
+            previous,
+            new,
+        })
+    }
+
+    /// The sprites from `first` on, before and after the change.
+    pub fn sprites_changed(first: usize, previous: Vec<Sprite>, new: Vec<Sprite>) -> Self {
+        Self::SpritesChanged(SpritesChanged {
+            first,
+            previous,
+            new,
+        })
+    }
+
+    pub fn sfx_changed(index: usize, previous: Sfx, new: Sfx) -> Self {
+        Self::SfxChanged(Box::new(SfxChanged {
+            index,
+            previous,
+            new,
+        }))
+    }
+
+    /// `cells` are the map coordinates of every cell that changed,
+    /// with their sprite before and after the change.
+    pub fn map_changed(cells: Vec<(i32, i32)>, previous: Vec<u8>, new: Vec<u8>) -> Self {
+        Self::MapChanged(MapChanged {
+            cells,
+            previous,
+            new,
+        })
+    }
+
+    fn undo(&self, resources: &mut Resources) {
         match self {
-            Command::PixelChanged(pixel_changed) => pixel_changed.undo(sprite_sheet),
+            Command::PixelChanged(pixel_changed) => pixel_changed.undo(&mut resources.sprite_sheet),
+            Command::SpriteChanged(sprite_changed) => {
+                sprite_changed.set(&mut resources.sprite_sheet, sprite_changed.previous)
+            }
+            Command::SpritesChanged(sprites_changed) => {
+                sprites_changed.set(&mut resources.sprite_sheet, &sprites_changed.previous)
+            }
+            Command::SfxChanged(sfx_changed) => sfx_changed.undo(resources),
+            Command::MapChanged(map_changed) => map_changed.set(resources, &map_changed.previous),
         }
     }
 
-    fn redo(&self, sprite_sheet: &mut SpriteSheet) {
+    fn redo(&self, resources: &mut Resources) {
         match self {
-            Command::PixelChanged(pixel_changed) => pixel_changed.redo(sprite_sheet),
+            Command::PixelChanged(pixel_changed) => pixel_changed.redo(&mut resources.sprite_sheet),
+            Command::SpriteChanged(sprite_changed) => {
+                sprite_changed.set(&mut resources.sprite_sheet, sprite_changed.new)
+            }
+            Command::SpritesChanged(sprites_changed) => {
+                sprites_changed.set(&mut resources.sprite_sheet, &sprites_changed.new)
+            }
+            Command::SfxChanged(sfx_changed) => sfx_changed.redo(resources),
+            Command::MapChanged(map_changed) => map_changed.set(resources, &map_changed.new),
         }
     }
 }
@@ -89,21 +152,90 @@ pub struct PixelChanged {
     sprite: usize,
     x: isize,
     y: isize,
-    previous_color: u8,
-    new_color: u8,
+    previous_color: Color,
+    new_color: Color,
 }
 
+// Sprites past the end of the sheet (the game shrank it after the change) are left out.
 impl PixelChanged {
     fn undo(&self, sprite_sheet: &mut SpriteSheet) {
-        let sprite = sprite_sheet.get_sprite_mut(self.sprite);
-
-        sprite.pset(self.x, self.y, self.previous_color);
+        if let Some(sprite) = sprite_sheet.try_get_sprite_mut(self.sprite) {
+            sprite.pset(self.x, self.y, self.previous_color);
+        }
     }
 
     fn redo(&self, sprite_sheet: &mut SpriteSheet) {
-        let sprite = sprite_sheet.get_sprite_mut(self.sprite);
+        if let Some(sprite) = sprite_sheet.try_get_sprite_mut(self.sprite) {
+            sprite.pset(self.x, self.y, self.new_color);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SpriteChanged {
+    sprite: usize,
+    previous: Sprite,
+    new: Sprite,
+}
+
+impl SpriteChanged {
+    fn set(&self, sprite_sheet: &mut SpriteSheet, pixels: Sprite) {
+        if let Some(sprite) = sprite_sheet.try_get_sprite_mut(self.sprite) {
+            *sprite = pixels;
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SpritesChanged {
+    first: usize,
+    previous: Vec<Sprite>,
+    new: Vec<Sprite>,
+}
+
+impl SpritesChanged {
+    fn set(&self, sprite_sheet: &mut SpriteSheet, sprites: &[Sprite]) {
+        for (index, &pixels) in sprites.iter().enumerate() {
+            if let Some(sprite) = sprite_sheet.try_get_sprite_mut(self.first + index) {
+                *sprite = pixels;
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SfxChanged {
+    index: usize,
+    previous: Sfx,
+    new: Sfx,
+}
+
+impl SfxChanged {
+    fn undo(&self, resources: &mut Resources) {
+        if let Some(sfx) = resources.sound_effects.get_mut(self.index) {
+            *sfx = self.previous.clone();
+        }
+    }
+
+    fn redo(&self, resources: &mut Resources) {
+        if let Some(sfx) = resources.sound_effects.get_mut(self.index) {
+            *sfx = self.new.clone();
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct MapChanged {
+    cells: Vec<(i32, i32)>,
+    previous: Vec<u8>,
+    new: Vec<u8>,
+}
 
-        sprite.pset(self.x, self.y, self.new_color);
+impl MapChanged {
+    fn set(&self, resources: &mut Resources, sprites: &[u8]) {
+        for (&(x, y), &sprite) in self.cells.iter().zip(sprites) {
+            resources.mset(x, y, sprite);
+        }
     }
 }
 
@@ -117,9 +249,9 @@ mod tests {
     fn undo_empty() {
         let mut commands = Commands::new();
         let mut notification = notification::State::new();
-        let mut sprite_sheet = SpriteSheet::new();
+        let mut resources = Resources::empty();
 
-        commands.undo(&mut notification, &mut sprite_sheet);
+        commands.undo(&mut notification, &mut resources);
 
         assert_eq!(commands.current, 0);
         assert_eq!(notification.content(), "NOTHING TO UNDO");
@@ -129,9 +261,9 @@ mod tests {
     fn redo_empty() {
         let mut commands = Commands::new();
         let mut notification = notification::State::new();
-        let mut sprite_sheet = SpriteSheet::new();
+        let mut resources = Resources::empty();
 
-        commands.redo(&mut notification, &mut sprite_sheet);
+        commands.redo(&mut notification, &mut resources);
 
         assert_eq!(commands.current, 0);
         assert_eq!(notification.content(), "NOTHING TO REDO");
@@ -141,28 +273,52 @@ mod tests {
     fn undo_many() {
         let mut commands = Commands::new();
         let mut notification = notification::State::new();
-        let mut sprite_sheet = SpriteSheet::new();
+        let mut resources = Resources::empty();
 
-        commands.push(Command::pixel_changed(20, 0, 0, 1, 2));
-        commands.push(Command::pixel_changed(20, 0, 0, 2, 3));
-        commands.push(Command::pixel_changed(20, 0, 0, 3, 4));
-        commands.push(Command::pixel_changed(20, 0, 0, 4, 5));
-        sprite_sheet.get_sprite_mut(20).pset(0, 0, 5);
+        commands.push(Command::pixel_changed(
+            20,
+            0,
+            0,
+            Color::from(1),
+            Color::from(2),
+        ));
+        commands.push(Command::pixel_changed(
+            20,
+            0,
+            0,
+            Color::from(2),
+            Color::from(3),
+        ));
+        commands.push(Command::pixel_changed(
+            20,
+            0,
+            0,
+            Color::from(3),
+            Color::from(4),
+        ));
+        commands.push(Command::pixel_changed(
+            20,
+            0,
+            0,
+            Color::from(4),
+            Color::from(5),
+        ));
+        resources.sprite_sheet.get_sprite_mut(20).pset(0, 0, 5);
 
-        fn get_pixel(sprite_sheet: &mut SpriteSheet) -> u8 {
+        fn get_pixel(sprite_sheet: &mut SpriteSheet) -> Color {
             sprite_sheet.get_sprite_mut(20).pget(0, 0)
         }
 
-        assert_eq!(get_pixel(&mut sprite_sheet), 5);
-        commands.undo(&mut notification, &mut sprite_sheet);
-        assert_eq!(get_pixel(&mut sprite_sheet), 4);
-        commands.undo(&mut notification, &mut sprite_sheet);
-        assert_eq!(get_pixel(&mut sprite_sheet), 3);
-        commands.undo(&mut notification, &mut sprite_sheet);
-        assert_eq!(get_pixel(&mut sprite_sheet), 2);
-        commands.undo(&mut notification, &mut sprite_sheet);
-        assert_eq!(get_pixel(&mut sprite_sheet), 1);
-        commands.undo(&mut notification, &mut sprite_sheet);
-        assert_eq!(get_pixel(&mut sprite_sheet), 1);
+        assert_eq!(get_pixel(&mut resources.sprite_sheet), Color::from(5));
+        commands.undo(&mut notification, &mut resources);
+        assert_eq!(get_pixel(&mut resources.sprite_sheet), Color::from(4));
+        commands.undo(&mut notification, &mut resources);
+        assert_eq!(get_pixel(&mut resources.sprite_sheet), Color::from(3));
+        commands.undo(&mut notification, &mut resources);
+        assert_eq!(get_pixel(&mut resources.sprite_sheet), Color::from(2));
+        commands.undo(&mut notification, &mut resources);
+        assert_eq!(get_pixel(&mut resources.sprite_sheet), Color::from(1));
+        commands.undo(&mut notification, &mut resources);
+        assert_eq!(get_pixel(&mut resources.sprite_sheet), Color::from(1));
     }
 }
@@ -0,0 +1,78 @@
+use std::fmt::{Debug, Display};
+
+/// Errors that can happen while starting or running an app.
+pub enum Error {
+    /// The window or its OpenGL context couldn't be created.
+    WindowCreation(String),
+    /// The shaders (or GPU buffers needed to draw) couldn't be set up.
+    ShaderCompile(String),
+    /// An asset file exists but couldn't be parsed.
+    AssetLoad {
+        /// Path of the offending file.
+        path: String,
+        /// What went wrong while parsing it.
+        reason: String,
+    },
+    /// Filesystem errors (creating the assets directory, etc).
+    Io(std::io::Error),
+    /// [`crate::Pico8::load_cart`] was given a name that wasn't registered.
+    UnknownCart(String),
+}
+
+const GL_HINT: &str = "Runty8 needs OpenGL 3.1 or newer. \
+    Make sure your graphics drivers are up to date, \
+    and that you're not running in a headless environment (SSH without a display, CI, etc).";
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::WindowCreation(reason) => {
+                write!(f, "Couldn't create the window: {reason}.\n{GL_HINT}")
+            }
+            Error::ShaderCompile(reason) => {
+                write!(f, "Couldn't set up the renderer: {reason}.\n{GL_HINT}")
+            }
+            Error::AssetLoad { path, reason } => {
+                write!(f, "Couldn't load asset `{path}`: {reason}.")
+            }
+            Error::Io(error) => write!(f, "{error}"),
+            Error::UnknownCart(name) => write!(f, "There's no cart called `{name}`."),
+        }
+    }
+}
+
+// The same as `Display`, which is what gets printed when `main` returns the error,
+// so that games returning it from `main` show the message (and hints) as they are.
+impl Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returning_from_main_shows_the_message() {
+        let error = Error::WindowCreation("no display".to_owned());
+
+        assert_eq!(format!("{error:?}"), error.to_string());
+        assert!(format!("{error:?}").contains("OpenGL 3.1"));
+    }
+}
@@ -1,16 +1,12 @@
 #![allow(dead_code)]
+use std::sync::OnceLock;
+
+use crate::glyphs;
+use crate::runtime::sprite_sheet::SpriteSheet;
+
 const CHAR_SIZE: usize = 4 * 6;
 type Character = [u8; CHAR_SIZE];
 
-pub const MISSING_CHAR: Character = [
-    1, 1, 1, 1, //
-    1, 1, 1, 1, //
-    1, 1, 1, 1, //
-    1, 1, 1, 1, //
-    1, 1, 1, 1, //
-    1, 1, 1, 1, //
-];
-
 const SYMBOLS1: [Character; 16] = [
     [
         0, 0, 0, 0, //
@@ -495,11 +491,114 @@ const LETTERS: [Character; 26] = [
     ],
 ];
 
+// [ \ ] ^ _ `
+const SYMBOLS3: [Character; 6] = [
+    [
+        1, 1, 0, 0, //
+        1, 0, 0, 0, //
+        1, 0, 0, 0, //
+        1, 0, 0, 0, //
+        1, 1, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        1, 0, 0, 0, //
+        1, 0, 0, 0, //
+        0, 1, 0, 0, //
+        0, 0, 1, 0, //
+        0, 0, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 1, 1, 0, //
+        0, 0, 1, 0, //
+        0, 0, 1, 0, //
+        0, 0, 1, 0, //
+        0, 1, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 1, 0, 0, //
+        1, 0, 1, 0, //
+        0, 0, 0, 0, //
+        0, 0, 0, 0, //
+        0, 0, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        0, 0, 0, 0, //
+        0, 0, 0, 0, //
+        0, 0, 0, 0, //
+        1, 1, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 1, 0, 0, //
+        0, 0, 1, 0, //
+        0, 0, 0, 0, //
+        0, 0, 0, 0, //
+        0, 0, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+];
+
+// { | } ~
+const SYMBOLS4: [Character; 4] = [
+    [
+        0, 1, 1, 0, //
+        0, 1, 0, 0, //
+        1, 1, 0, 0, //
+        0, 1, 0, 0, //
+        0, 1, 1, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 1, 0, 0, //
+        0, 1, 0, 0, //
+        0, 1, 0, 0, //
+        0, 1, 0, 0, //
+        0, 1, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        1, 1, 0, 0, //
+        0, 1, 0, 0, //
+        0, 1, 1, 0, //
+        0, 1, 0, 0, //
+        1, 1, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+    [
+        0, 0, 0, 0, //
+        0, 0, 1, 0, //
+        1, 1, 1, 0, //
+        1, 0, 0, 0, //
+        0, 0, 0, 0, //
+        0, 0, 0, 0, //
+    ],
+];
+
 pub const FIRST_CHAR: char = ' ';
 
-pub const FONT: [Character; SYMBOLS1.len() + NUMBERS.len() + SYMBOLS2.len() + LETTERS.len()] = {
-    let arr = [[0; CHAR_SIZE]; SYMBOLS1.len() + NUMBERS.len() + SYMBOLS2.len() + LETTERS.len()];
-    copy(arr, &[&SYMBOLS1, &NUMBERS, &SYMBOLS2, &LETTERS])
+// Every printable ASCII character, from ' ' to '~'.
+// Like in Pico8, lowercase letters look the same as uppercase ones.
+const FONT_LEN: usize = SYMBOLS1.len()
+    + NUMBERS.len()
+    + SYMBOLS2.len()
+    + LETTERS.len()
+    + SYMBOLS3.len()
+    + LETTERS.len()
+    + SYMBOLS4.len();
+
+pub const FONT: [Character; FONT_LEN] = {
+    let arr = [[0; CHAR_SIZE]; FONT_LEN];
+    copy(
+        arr,
+        &[
+            &SYMBOLS1, &NUMBERS, &SYMBOLS2, &LETTERS, &SYMBOLS3, &LETTERS, &SYMBOLS4,
+        ],
+    )
 };
 
 const fn copy<const N: usize>(mut arr: [Character; N], arrays: &[&[Character]]) -> [Character; N] {
@@ -520,3 +619,782 @@ const fn copy<const N: usize>(mut arr: [Character; N], arrays: &[&[Character]])
 
     arr
 }
+
+/// Size of the wide glyphs (see [`glyphs`]), which every font has.
+pub(crate) const WIDE_GLYPH_WIDTH: i32 = 7;
+pub(crate) const WIDE_GLYPH_HEIGHT: i32 = 5;
+type WideCharacter = [u8; (WIDE_GLYPH_WIDTH * WIDE_GLYPH_HEIGHT) as usize];
+
+// Pico8's wide glyphs (7x5, advancing 8 pixels), with the characters that print them.
+// In the order of their P8SCII codes.
+const WIDE_GLYPHS: [(char, WideCharacter); 14] = [
+    (
+        glyphs::SOLID,
+        [
+            1, 1, 1, 1, 1, 1, 1, //
+            1, 1, 1, 1, 1, 1, 1, //
+            1, 1, 1, 1, 1, 1, 1, //
+            1, 1, 1, 1, 1, 1, 1, //
+            1, 1, 1, 1, 1, 1, 1, //
+        ],
+    ),
+    (
+        glyphs::CHECKER,
+        [
+            1, 0, 1, 0, 1, 0, 1, //
+            0, 1, 0, 1, 0, 1, 0, //
+            1, 0, 1, 0, 1, 0, 1, //
+            0, 1, 0, 1, 0, 1, 0, //
+            1, 0, 1, 0, 1, 0, 1, //
+        ],
+    ),
+    (
+        glyphs::BTN_DOWN,
+        [
+            0, 1, 1, 1, 1, 1, 0, //
+            1, 0, 0, 0, 0, 0, 1, //
+            1, 1, 0, 0, 0, 1, 1, //
+            1, 1, 1, 0, 1, 1, 1, //
+            0, 1, 1, 1, 1, 1, 0, //
+        ],
+    ),
+    (
+        glyphs::DOTS,
+        [
+            1, 0, 0, 0, 1, 0, 0, //
+            0, 0, 1, 0, 0, 0, 1, //
+            1, 0, 0, 0, 1, 0, 0, //
+            0, 0, 1, 0, 0, 0, 1, //
+            1, 0, 0, 0, 1, 0, 0, //
+        ],
+    ),
+    (
+        glyphs::CIRCLE,
+        [
+            0, 1, 1, 1, 1, 1, 0, //
+            1, 1, 1, 1, 1, 1, 1, //
+            1, 1, 1, 1, 1, 1, 1, //
+            1, 1, 1, 1, 1, 1, 1, //
+            0, 1, 1, 1, 1, 1, 0, //
+        ],
+    ),
+    (
+        glyphs::HEART,
+        [
+            0, 1, 1, 0, 1, 1, 0, //
+            1, 1, 1, 1, 1, 1, 1, //
+            1, 1, 1, 1, 1, 1, 1, //
+            0, 1, 1, 1, 1, 1, 0, //
+            0, 0, 0, 1, 0, 0, 0, //
+        ],
+    ),
+    (
+        glyphs::BTN_LEFT,
+        [
+            0, 1, 1, 1, 1, 1, 0, //
+            1, 1, 0, 0, 1, 1, 1, //
+            1, 0, 0, 0, 1, 1, 1, //
+            1, 1, 0, 0, 1, 1, 1, //
+            0, 1, 1, 1, 1, 1, 0, //
+        ],
+    ),
+    (
+        glyphs::BTN_O,
+        [
+            0, 1, 1, 1, 1, 1, 0, //
+            1, 1, 0, 0, 0, 1, 1, //
+            1, 1, 0, 1, 0, 1, 1, //
+            1, 1, 0, 0, 0, 1, 1, //
+            0, 1, 1, 1, 1, 1, 0, //
+        ],
+    ),
+    (
+        glyphs::DIAMOND,
+        [
+            0, 0, 0, 1, 0, 0, 0, //
+            0, 0, 1, 1, 1, 0, 0, //
+            0, 1, 1, 1, 1, 1, 0, //
+            0, 0, 1, 1, 1, 0, 0, //
+            0, 0, 0, 1, 0, 0, 0, //
+        ],
+    ),
+    (
+        glyphs::ELLIPSIS,
+        [
+            0, 0, 0, 0, 0, 0, 0, //
+            0, 0, 0, 0, 0, 0, 0, //
+            0, 0, 0, 0, 0, 0, 0, //
+            0, 0, 0, 0, 0, 0, 0, //
+            1, 0, 1, 0, 1, 0, 0, //
+        ],
+    ),
+    (
+        glyphs::BTN_RIGHT,
+        [
+            0, 1, 1, 1, 1, 1, 0, //
+            1, 1, 1, 0, 0, 1, 1, //
+            1, 1, 1, 0, 0, 0, 1, //
+            1, 1, 1, 0, 0, 1, 1, //
+            0, 1, 1, 1, 1, 1, 0, //
+        ],
+    ),
+    (
+        glyphs::STAR,
+        [
+            0, 0, 0, 1, 0, 0, 0, //
+            0, 0, 1, 1, 1, 0, 0, //
+            1, 1, 1, 1, 1, 1, 1, //
+            0, 1, 1, 1, 1, 1, 0, //
+            0, 1, 0, 0, 0, 1, 0, //
+        ],
+    ),
+    (
+        glyphs::BTN_UP,
+        [
+            0, 1, 1, 1, 1, 1, 0, //
+            1, 1, 1, 0, 1, 1, 1, //
+            1, 1, 0, 0, 0, 1, 1, //
+            1, 0, 0, 0, 0, 0, 1, //
+            0, 1, 1, 1, 1, 1, 0, //
+        ],
+    ),
+    (
+        glyphs::BTN_X,
+        [
+            0, 1, 1, 1, 1, 1, 0, //
+            1, 1, 0, 1, 0, 1, 1, //
+            1, 1, 1, 0, 1, 1, 1, //
+            1, 1, 0, 1, 0, 1, 1, //
+            0, 1, 1, 1, 1, 1, 0, //
+        ],
+    ),
+];
+
+/// Pixels of `char`'s wide glyph, row by row, if it's one of the special glyphs.
+pub(crate) fn wide_glyph(char: char) -> Option<&'static WideCharacter> {
+    WIDE_GLYPHS
+        .iter()
+        .find(|(wide_char, _)| *wide_char == char)
+        .map(|(_, pixels)| pixels)
+}
+
+/// Horizontal advance of the widest glyphs, including the spacing between them.
+pub const GLYPH_WIDTH: i32 = 4;
+/// Vertical advance of every line of text, including the spacing between them.
+pub const GLYPH_HEIGHT: i32 = 6;
+
+/// A bitmap font where every glyph fits in the same box.
+///
+/// Glyphs are as wide as their pixels, one pixel apart from the next one,
+/// unless the font is [monospaced](Font::monospaced). Empty glyphs (like space) take the
+/// whole box. Characters without a glyph are drawn as a hollow box, so that they stand out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Font {
+    glyph_width: i32,
+    glyph_height: i32,
+    first_char: char,
+    // `glyph_width * glyph_height` pixels per glyph, row by row.
+    pixels: Vec<bool>,
+    // First column with pixels and width of every glyph.
+    spans: Vec<(i32, i32)>,
+    monospace: bool,
+}
+
+impl Font {
+    /// Pico8's 3x5 font, used unless a game picks another one.
+    pub(crate) fn builtin() -> &'static Font {
+        static BUILTIN: OnceLock<Font> = OnceLock::new();
+
+        BUILTIN.get_or_init(|| {
+            let (width, height) = (GLYPH_WIDTH - 1, GLYPH_HEIGHT - 1);
+            let pixels = FONT
+                .iter()
+                .flat_map(|char| {
+                    (0..height).flat_map(move |y| {
+                        (0..width).map(move |x| char[(x + y * GLYPH_WIDTH) as usize] != 0)
+                    })
+                })
+                .collect();
+
+            Font::new(width, height, FIRST_CHAR, pixels)
+        })
+    }
+
+    /// The built-in font, with every glyph in the same column width.
+    pub(crate) fn builtin_monospaced() -> &'static Font {
+        static BUILTIN_MONOSPACED: OnceLock<Font> = OnceLock::new();
+
+        BUILTIN_MONOSPACED.get_or_init(|| Font::builtin().clone().monospaced())
+    }
+
+    /// Slices `glyph_width` x `glyph_height` glyphs out of the sprite sheet, starting at `origin`.
+    /// Glyphs go left to right, continuing on the next row (back at `origin.0`) when there's
+    /// no room left, until the bottom of the sheet. `first_char` is the first glyph's character.
+    ///
+    /// Any color but 0 is part of the glyph.
+    pub(crate) fn from_sprite_sheet(
+        sprite_sheet: &SpriteSheet,
+        origin: (i32, i32),
+        glyph_width: i32,
+        glyph_height: i32,
+        first_char: char,
+    ) -> Self {
+        let (width, height) = (sprite_sheet.width() as i32, SpriteSheet::HEIGHT as i32);
+
+        let (glyph_width, glyph_height) = (glyph_width.max(1), glyph_height.max(1));
+        let (origin_x, origin_y) = (origin.0.clamp(0, width), origin.1.clamp(0, height));
+        let columns = (width - origin_x) / glyph_width;
+        let rows = (height - origin_y) / glyph_height;
+
+        let pixels = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (column, row)))
+            .flat_map(|(column, row)| {
+                let (left, top) = (
+                    origin_x + column * glyph_width,
+                    origin_y + row * glyph_height,
+                );
+
+                (0..glyph_height).flat_map(move |y| {
+                    (0..glyph_width).map(move |x| {
+                        sprite_sheet
+                            .checked_index(left + x, top + y)
+                            .is_some_and(|index| sprite_sheet.sprite_sheet[index] != 0)
+                    })
+                })
+            })
+            .collect();
+
+        Self::new(glyph_width, glyph_height, first_char, pixels)
+    }
+
+    fn new(glyph_width: i32, glyph_height: i32, first_char: char, pixels: Vec<bool>) -> Self {
+        let spans = pixels
+            .chunks((glyph_width * glyph_height) as usize)
+            .map(|glyph| {
+                let mut columns = (0..glyph_width)
+                    .filter(|&x| (0..glyph_height).any(|y| glyph[(x + y * glyph_width) as usize]));
+
+                match (columns.next(), columns.next_back()) {
+                    (Some(first), last) => (first, last.unwrap_or(first) - first + 1),
+                    (None, _) => (0, glyph_width),
+                }
+            })
+            .collect();
+
+        Self {
+            glyph_width,
+            glyph_height,
+            first_char,
+            pixels,
+            spans,
+            monospace: false,
+        }
+    }
+
+    /// Makes every glyph take the whole glyph width, so that characters line up in columns.
+    pub fn monospaced(mut self) -> Self {
+        self.monospace = true;
+        self
+    }
+
+    /// Size of every glyph, not counting the spacing between them.
+    pub fn glyph_size(&self) -> (i32, i32) {
+        (self.glyph_width, self.glyph_height)
+    }
+
+    /// Horizontal distance taken by the widest characters, and every character when monospaced.
+    pub fn advance(&self) -> i32 {
+        self.glyph_width + 1
+    }
+
+    /// Horizontal distance between `char` and the next character.
+    pub fn char_advance(&self, char: char) -> i32 {
+        match char {
+            glyphs::VARIATION_SELECTOR => 0,
+            _ => self.span(char).1 + 1,
+        }
+    }
+
+    // Where `char`'s glyph starts in its box, and how wide it is.
+    fn span(&self, char: char) -> (i32, i32) {
+        if wide_glyph(char).is_some() {
+            return (0, WIDE_GLYPH_WIDTH);
+        }
+
+        let index = (char as usize).wrapping_sub(self.first_char as usize);
+
+        match self.spans.get(index) {
+            Some(&span) if !self.monospace => span,
+            _ => (0, self.glyph_width),
+        }
+    }
+
+    /// Horizontal offset to draw `char`'s glyph box at, so that its first column lands at the cursor.
+    pub(crate) fn glyph_offset(&self, char: char) -> i32 {
+        -self.span(char).0
+    }
+
+    /// Vertical distance between consecutive lines.
+    pub fn line_height(&self) -> i32 {
+        self.glyph_height + 1
+    }
+
+    /// Pixels of `char`'s glyph, row by row. `None` if the font doesn't have it.
+    pub(crate) fn glyph(&self, char: char) -> Option<&[bool]> {
+        let size = (self.glyph_width * self.glyph_height) as usize;
+        // Characters before the first one wrap around, and are missing too.
+        let index = (char as usize).wrapping_sub(self.first_char as usize);
+        let start = index.checked_mul(size)?;
+
+        self.pixels.get(start..start.checked_add(size)?)
+    }
+
+    /// Size in pixels that `text` takes when printed with this font.
+    /// Each `\n` starts a new line, and other control codes are taken into account
+    /// the same way [`crate::Pico8::print`] does.
+    pub fn measure(&self, text: &str) -> (i32, i32) {
+        let width = layout(text, self)
+            .iter()
+            .map(|glyph| glyph.x - self.glyph_offset(glyph.char) + self.char_advance(glyph.char))
+            .max();
+        let line_count = text.matches(NEWLINE).count() as i32 + 1;
+
+        (width.unwrap_or(0), line_count * self.line_height())
+    }
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        Self::builtin().clone()
+    }
+}
+
+// Control codes understood by `print`, a subset of Pico8's P8SCII ones.
+// Pico8 writes them as `\*`, `\t`, `\n` and `\f`, Rust needs the first and last one as hex escapes.
+
+/// `\x01<n><char>`: prints `char` `n` times, `n` being a single `0-9a-z` digit (up to 35).
+pub const REPEAT: char = '\x01';
+/// `\t`: moves the cursor to the next tab stop, every [`TAB_GLYPHS`] glyphs.
+pub const TAB: char = '\t';
+/// `\n`: moves the cursor to the start of the next line.
+pub const NEWLINE: char = '\n';
+/// `\x0c<color>`: prints whatever follows with `color`, a single hex digit.
+pub const SET_COLOR: char = '\x0c';
+
+const TAB_GLYPHS: i32 = 4;
+
+/// A glyph to draw, positioned relative to where printing started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PlacedGlyph {
+    pub(crate) char: char,
+    /// Where the glyph's box goes, left of the cursor when the glyph doesn't start at the box's edge.
+    pub(crate) x: i32,
+    pub(crate) y: i32,
+    /// Set by [`SET_COLOR`], `None` for the color `print` was called with.
+    pub(crate) color: Option<u8>,
+}
+
+/// Where each glyph of `text` goes when printed with `font`, interpreting control codes.
+/// Codes that aren't understood, or are missing their parameters, are drawn as glyphs.
+pub(crate) fn layout(text: &str, font: &Font) -> Vec<PlacedGlyph> {
+    let tab_width = TAB_GLYPHS * font.advance();
+    let mut glyphs = vec![];
+    let (mut x, mut y, mut color) = (0, 0, None);
+    let mut chars = text.chars();
+
+    while let Some(char) = chars.next() {
+        let mut lookahead = chars.clone();
+
+        let (char, count) = match char {
+            NEWLINE => {
+                x = 0;
+                y += font.line_height();
+                continue;
+            }
+            TAB => {
+                x = (x / tab_width + 1) * tab_width;
+                continue;
+            }
+            glyphs::VARIATION_SELECTOR => continue,
+            SET_COLOR => match lookahead.next().and_then(|digit| digit.to_digit(16)) {
+                Some(new_color) => {
+                    color = Some(new_color as u8);
+                    chars = lookahead;
+                    continue;
+                }
+                None => (char, 1),
+            },
+            REPEAT => match (
+                lookahead.next().and_then(|digit| digit.to_digit(36)),
+                lookahead.next(),
+            ) {
+                (Some(count), Some(repeated)) => {
+                    chars = lookahead;
+                    (repeated, count)
+                }
+                _ => (char, 1),
+            },
+            _ => (char, 1),
+        };
+
+        for _ in 0..count {
+            glyphs.push(PlacedGlyph {
+                char,
+                x: x + font.glyph_offset(char),
+                y,
+                color,
+            });
+            x += font.char_advance(char);
+        }
+    }
+
+    glyphs
+}
+
+/// Size in pixels that `text` takes when printed with the built-in font.
+/// Each `\n` starts a new line. See [`crate::Pico8::measure_text`] for the current font.
+pub fn measure_text(text: &str) -> (i32, i32) {
+    Font::builtin().measure(text)
+}
+
+/// Splits `text` into lines no wider than `max_width` pixels, breaking at spaces when possible.
+/// Words that don't fit on a line of their own get split.
+pub(crate) fn wrap(text: &str, max_width: i32) -> Vec<String> {
+    let fits = |line: &str| Font::builtin().measure(line).0 <= max_width;
+    let mut lines = vec![];
+
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+
+        for word in paragraph.split(' ') {
+            if !line.is_empty() && !fits(&format!("{line} {word}")) {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+
+            while !fits(&line) {
+                // At least one character per line, however narrow.
+                let first_char = line.chars().next().map_or(0, char::len_utf8);
+                let rest = line.split_off(longest_fitting_prefix(&line, max_width).max(first_char));
+                lines.push(std::mem::replace(&mut line, rest));
+            }
+        }
+
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Shortens `text` so that it fits in `max_width` pixels, ending it with "..." if it had to be cut.
+pub(crate) fn truncate(text: &str, max_width: i32) -> String {
+    const ELLIPSIS: &str = "...";
+
+    if measure_text(text).0 <= max_width {
+        return text.to_owned();
+    }
+
+    let room = max_width - measure_text(ELLIPSIS).0;
+    if room < 0 {
+        return ELLIPSIS[..longest_fitting_prefix(ELLIPSIS, max_width)].to_owned();
+    }
+    let kept = &text[..longest_fitting_prefix(text, room)];
+
+    format!("{}{ELLIPSIS}", kept.trim_end())
+}
+
+// Length in bytes of the longest start of `text` that fits in `max_width` pixels.
+fn longest_fitting_prefix(text: &str, max_width: i32) -> usize {
+    let mut width = 0;
+
+    text.char_indices()
+        .find_map(|(index, char)| {
+            width += Font::builtin().char_advance(char);
+            (width > max_width).then_some(index)
+        })
+        .unwrap_or(text.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::draw_data::DrawData;
+    use crate::Color;
+
+    // Every printable character, and DEL to show the missing glyph, 16 per row.
+    // Monospaced, so that every glyph is in its own column.
+    fn printed_ascii() -> String {
+        let mut draw_data = DrawData::new();
+        let chars: Vec<char> = (' '..='\u{7f}').collect();
+
+        for (row, line) in chars.chunks(16).enumerate() {
+            let line: String = line.iter().collect();
+            let y = row as i32 * GLYPH_HEIGHT;
+            draw_data.print_with(Font::builtin_monospaced(), &line, 0, y, Color::from(7));
+        }
+
+        let rows = chars.len().div_ceil(16) as i32;
+        pixels_as_text(&draw_data, 16 * GLYPH_WIDTH, rows * GLYPH_HEIGHT)
+    }
+
+    fn pixels_as_text(draw_data: &DrawData, width: i32, height: i32) -> String {
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| match draw_data.pixel(x, y) {
+                        Some(color) if color == Color::from(7) => '#',
+                        _ => '.',
+                    })
+                    .chain(std::iter::once('\n'))
+                    .collect::<String>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn prints_every_ascii_character() {
+        assert_eq!(
+            printed_ascii(),
+            include_str!("test_data/printable_ascii.txt")
+        );
+    }
+
+    #[test]
+    fn prints_text_with_narrow_glyphs() {
+        let mut draw_data = DrawData::new();
+        let text = "HI, I'M 1.0!\n(LIFE: 3/3)";
+        draw_data.print(text, 0, 0, Color::from(7));
+
+        let (width, height) = measure_text(text);
+        assert_eq!((width, height), (42, 12));
+        assert_eq!(
+            pixels_as_text(&draw_data, width, height),
+            include_str!("test_data/proportional_text.txt")
+        );
+    }
+
+    #[test]
+    fn glyphs_take_their_own_width() {
+        let font = Font::builtin();
+
+        assert_eq!(font.char_advance('A'), 4);
+        assert_eq!(font.char_advance('.'), 2);
+        // Empty glyphs take the whole box.
+        assert_eq!(font.char_advance(' '), 4);
+        // '!' starts in the middle of its box.
+        assert_eq!(positions("!A"), [('!', -1, 0), ('A', 2, 0)]);
+        assert_eq!(measure_text("I.I"), (10, 6));
+
+        let monospaced = Font::builtin_monospaced();
+        assert_eq!(monospaced.char_advance('.'), 4);
+        assert_eq!(monospaced.measure("I.I"), (12, 6));
+    }
+
+    #[test]
+    fn prints_every_wide_glyph() {
+        let mut draw_data = DrawData::new();
+        let glyphs: Vec<char> = WIDE_GLYPHS.iter().map(|(char, _)| *char).collect();
+
+        for (row, line) in glyphs.chunks(7).enumerate() {
+            let line: String = line.iter().collect();
+            draw_data.print(&line, 0, row as i32 * GLYPH_HEIGHT, Color::from(7));
+        }
+
+        assert_eq!(
+            pixels_as_text(&draw_data, 7 * 8, 2 * GLYPH_HEIGHT),
+            include_str!("test_data/wide_glyphs.txt")
+        );
+    }
+
+    #[test]
+    fn wide_glyphs_advance_by_two_glyphs() {
+        let font = Font::builtin();
+
+        assert_eq!(font.char_advance(glyphs::BTN_X), 8);
+        assert_eq!(measure_text("A\u{274e}A"), (16, 6));
+        // Written as an emoji, the variation selector takes no space.
+        assert_eq!(
+            positions("\u{2b05}\u{fe0f}A"),
+            [(glyphs::BTN_LEFT, 0, 0), ('A', 8, 0)]
+        );
+        assert_eq!(Font::builtin_monospaced().char_advance(glyphs::STAR), 8);
+    }
+
+    fn positions(text: &str) -> Vec<(char, i32, i32)> {
+        layout(text, Font::builtin())
+            .iter()
+            .map(|glyph| (glyph.char, glyph.x, glyph.y))
+            .collect()
+    }
+
+    #[test]
+    fn newlines_and_tabs_move_the_cursor() {
+        assert_eq!(positions("AB\nC"), [('A', 0, 0), ('B', 4, 0), ('C', 0, 6)]);
+        assert_eq!(
+            positions("A\tB\tC"),
+            [('A', 0, 0), ('B', 16, 0), ('C', 32, 0)]
+        );
+        // Already on a tab stop, goes to the next one.
+        assert_eq!(positions("ABCD\tE")[4], ('E', 32, 0));
+    }
+
+    #[test]
+    fn repeats_the_next_character() {
+        assert_eq!(
+            positions("\x013-A"),
+            [('-', 0, 0), ('-', 4, 0), ('-', 8, 0), ('A', 12, 0)]
+        );
+        assert_eq!(positions("\x01b=").len(), 11);
+
+        // Missing parameters are printed as they are.
+        assert_eq!(positions("\x01"), [(REPEAT, 0, 0)]);
+        assert_eq!(positions("\x012"), [(REPEAT, 0, 0), ('2', 4, 0)]);
+    }
+
+    #[test]
+    fn sets_the_color() {
+        let colors: Vec<Option<u8>> = layout("A\x0c8B\x0cfC\x0czD", Font::builtin())
+            .iter()
+            .map(|glyph| glyph.color)
+            .collect();
+
+        // The invalid `\x0cz` is printed, keeping the last color.
+        assert_eq!(
+            colors,
+            [None, Some(8), Some(15), Some(15), Some(15), Some(15)]
+        );
+        assert_eq!(positions("\x0c8B"), [('B', 0, 0)]);
+    }
+
+    #[test]
+    fn unknown_control_codes_are_visible() {
+        assert_eq!(positions("\x02A"), [('\x02', 0, 0), ('A', 4, 0)]);
+        assert_eq!(Font::builtin().glyph('\x02'), None);
+        assert_eq!(Font::builtin().glyph('\u{7f}'), None);
+        assert!(Font::builtin().glyph('A').is_some());
+    }
+
+    // Two 2x3 glyphs at (8, 16): a vertical bar, then an L.
+    fn sheet_font() -> Font {
+        let mut sprite_sheet = SpriteSheet::new();
+        for (x, y) in [
+            (8, 16),
+            (8, 17),
+            (8, 18),
+            (10, 16),
+            (10, 17),
+            (10, 18),
+            (11, 18),
+        ] {
+            let index = sprite_sheet.checked_index(x, y).unwrap();
+            sprite_sheet.sprite_sheet[index] = 7;
+        }
+
+        Font::from_sprite_sheet(&sprite_sheet, (8, 16), 2, 3, '0')
+    }
+
+    #[test]
+    fn slices_glyphs_from_the_sprite_sheet() {
+        let font = sheet_font();
+
+        assert_eq!(
+            font.glyph('0'),
+            Some(&[true, false, true, false, true, false][..])
+        );
+        assert_eq!(
+            font.glyph('1'),
+            Some(&[true, false, true, false, true, true][..])
+        );
+        assert_eq!(font.glyph('/'), None);
+        // 60 glyphs per row, 37 rows.
+        assert!(font
+            .glyph(char::from_u32('0' as u32 + 60 * 37 - 1).unwrap())
+            .is_some());
+        assert_eq!(
+            font.glyph(char::from_u32('0' as u32 + 60 * 37).unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn monospaced_glyphs_advance_by_the_font_size() {
+        let font = sheet_font().monospaced();
+
+        assert_eq!((font.advance(), font.line_height()), (3, 4));
+        assert_eq!(font.measure("01\n0"), (6, 8));
+        let positions: Vec<(i32, i32)> = layout("01\n\t0", &font)
+            .iter()
+            .map(|glyph| (glyph.x, glyph.y))
+            .collect();
+        assert_eq!(positions, [(0, 0), (3, 0), (12, 4)]);
+
+        let mut draw_data = DrawData::new();
+        draw_data.print_with(&font, "1/", 0, 0, Color::from(7));
+        let printed: Vec<bool> = (0..3)
+            .flat_map(|y| (0..5).map(move |x| (x, y)))
+            .map(|(x, y)| draw_data.pixel(x, y) == Some(Color::from(7)))
+            .collect();
+        // The missing glyph is a box as big as the others.
+        #[rustfmt::skip]
+        assert_eq!(printed, [
+            true, false, false, true, true,
+            true, false, false, true, true,
+            true, true, false, true, true,
+        ]);
+    }
+
+    #[test]
+    fn the_current_font_is_used_for_printing() {
+        let mut draw_data = DrawData::new();
+        draw_data.set_font(Some(sheet_font()));
+        draw_data.print("0", 0, 0, Color::from(7));
+        // The bar is a single pixel wide.
+        assert_eq!(draw_data.font().measure("00"), (4, 4));
+        assert_eq!(draw_data.pixel(2, 0), Some(Color::BLACK));
+
+        draw_data.set_font(None);
+        assert_eq!(draw_data.font(), Font::builtin());
+        assert_eq!(measure_text("00"), (8, 6));
+    }
+
+    #[test]
+    fn measuring_works() {
+        assert_eq!(measure_text(""), (0, 6));
+        assert_eq!(measure_text("HELLO"), (20, 6));
+        assert_eq!(measure_text("HI\nTHERE"), (20, 12));
+    }
+
+    #[test]
+    fn measuring_matches_what_print_draws() {
+        for text in ["\x0c8AB\x0caA", "\x013AB", "A\tA", "AA\n\x0c8\tA"] {
+            let mut draw_data = DrawData::new();
+            draw_data.print(text, 0, 0, Color::from(7));
+
+            let drawn = (0..128)
+                .flat_map(|y| (0..128).map(move |x| (x, y)))
+                .filter(|&(x, y)| draw_data.pixel(x, y) != Some(Color::BLACK));
+            let (right, bottom) = drawn.fold((0, 0), |(right, bottom), (x, y)| {
+                (i32::max(right, x), i32::max(bottom, y))
+            });
+
+            // Glyphs leave a column and a row of space after them.
+            assert_eq!(measure_text(text), (right + 2, bottom + 2), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn wrapping_works() {
+        assert_eq!(wrap("HELLO THERE WORLD", 44), ["HELLO THERE", "WORLD"]);
+        assert_eq!(wrap("ABCDEFGH", 12), ["ABC", "DEF", "GH"]);
+        assert_eq!(wrap("A\nB", 40), ["A", "B"]);
+    }
+
+    #[test]
+    fn truncating_works() {
+        assert_eq!(truncate("HELLO", 20), "HELLO");
+        assert_eq!(truncate("HELLO THERE", 32), "HELLO...");
+    }
+}
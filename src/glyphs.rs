@@ -0,0 +1,59 @@
+//! Pico8's special glyphs: the button icons and a few symbols, for text like "PRESS ❎".
+//!
+//! They're printed by the same characters Pico8 shows them as in its editor,
+//! so text copied from a cart prints the same, emoji variation selectors (`\u{FE0F}`) included.
+//! The constants are for writing them without having to find the characters.
+//!
+//! They're twice as wide as the built-in font's glyphs: 7x5 pixels, taking 8 pixels of the line.
+//!
+//! ```no_run
+//! # use runty8::{glyphs, Pico8};
+//! # fn draw(pico8: &mut Pico8) {
+//! pico8.print(&format!("PRESS {} TO JUMP", glyphs::BTN_X), 30, 60, 7);
+//! # }
+//! ```
+
+/// A solid block. `█`
+pub const SOLID: char = '\u{2588}';
+
+/// A checkerboard. `▒`
+pub const CHECKER: char = '\u{2592}';
+
+/// The down button. `⬇️`
+pub const BTN_DOWN: char = '\u{2B07}';
+
+/// Scattered dots. `░`
+pub const DOTS: char = '\u{2591}';
+
+/// A filled circle. `●`
+pub const CIRCLE: char = '\u{25CF}';
+
+/// A heart. `♥`
+pub const HEART: char = '\u{2665}';
+
+/// The left button. `⬅️`
+pub const BTN_LEFT: char = '\u{2B05}';
+
+/// The O button. `🅾️`
+pub const BTN_O: char = '\u{1F17E}';
+
+/// A diamond. `◆`
+pub const DIAMOND: char = '\u{25C6}';
+
+/// Three dots, for text that goes on. `…`
+pub const ELLIPSIS: char = '\u{2026}';
+
+/// The right button. `➡️`
+pub const BTN_RIGHT: char = '\u{27A1}';
+
+/// A star. `★`
+pub const STAR: char = '\u{2605}';
+
+/// The up button. `⬆️`
+pub const BTN_UP: char = '\u{2B06}';
+
+/// The X button. `❎`
+pub const BTN_X: char = '\u{274E}';
+
+// Follows some of the glyphs when they're written as emoji ("⬅️"), printed as nothing.
+pub(crate) const VARIATION_SELECTOR: char = '\u{FE0F}';
@@ -0,0 +1,315 @@
+//! Golden image tests: scenes are drawn headlessly and compared, pixel by pixel,
+//! to the PPM images in `src/test_data/golden`.
+//!
+//! Run with `RUNTY8_BLESS=1` to write the current output as the new expected images,
+//! after checking that the changes are intended.
+//! On a mismatch the actual output is written next to the expected image,
+//! as `<name>.actual.ppm`.
+
+use crate::runtime::color;
+use crate::runtime::draw_data::DrawData;
+use crate::runtime::flags::Flags;
+use crate::runtime::map::Map;
+use crate::runtime::sprite_sheet::SpriteSheet;
+use crate::runtime::state::State;
+use crate::{Color, Palette, Pico8, Resources};
+use std::path::PathBuf;
+
+const WIDTH: usize = 128;
+const HEIGHT: usize = 128;
+
+fn golden_path(name: &str, suffix: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/test_data/golden")
+        .join(format!("{name}{suffix}.ppm"))
+}
+
+/// A screen as binary PPM (P6).
+fn to_ppm(draw_data: &DrawData) -> Vec<u8> {
+    let mut ppm = format!("P6\n{WIDTH} {HEIGHT}\n255\n").into_bytes();
+    ppm.extend(draw_data.rgb(None, &Palette::pico8()));
+
+    ppm
+}
+
+/// Palette colors of a binary PPM screen, or `None` for the ones that aren't in the palette.
+/// Pico8's extended colors, which only the editor shows, read as the color with the same index,
+/// like `DrawData::pixel` does.
+fn from_ppm(ppm: &[u8]) -> Result<Vec<Option<Color>>, String> {
+    let header = format!("P6\n{WIDTH} {HEIGHT}\n255\n");
+    let pixels = ppm
+        .strip_prefix(header.as_bytes())
+        .ok_or("Expected a 128x128 binary PPM")?;
+
+    if pixels.len() != WIDTH * HEIGHT * 3 {
+        return Err(format!("Expected {} bytes of pixels", WIDTH * HEIGHT * 3));
+    }
+
+    Ok(pixels
+        .chunks(3)
+        .map(|rgb| {
+            let rgb = (rgb[0], rgb[1], rgb[2]);
+            Color::all().find(|color| color.rgb() == rgb).or_else(|| {
+                (0..16)
+                    .find(|&extended| color::extended_rgb(extended) == rgb)
+                    .map(Color::from_index_lossy)
+            })
+        })
+        .collect())
+}
+
+fn assert_golden(name: &str, draw_data: &DrawData) {
+    let expected_path = golden_path(name, "");
+    let actual_path = golden_path(name, ".actual");
+    let actual_ppm = to_ppm(draw_data);
+
+    if std::env::var_os("RUNTY8_BLESS").is_some() {
+        std::fs::write(&expected_path, &actual_ppm).unwrap();
+        let _ = std::fs::remove_file(&actual_path);
+        return;
+    }
+
+    let expected = std::fs::read(&expected_path).unwrap_or_else(|_| {
+        std::fs::write(&actual_path, &actual_ppm).unwrap();
+        panic!(
+            "No golden image for {name}, wrote the current output to {}. Run with RUNTY8_BLESS=1 to accept it.",
+            actual_path.display()
+        )
+    });
+    let expected = from_ppm(&expected)
+        .unwrap_or_else(|err| panic!("Couldn't read {}: {err}", expected_path.display()));
+
+    let mut different = 0;
+    let mut first_difference = None;
+    for (index, expected) in expected.into_iter().enumerate() {
+        let (x, y) = ((index % WIDTH) as i32, (index / WIDTH) as i32);
+        let pixel = draw_data.pixel(x, y);
+
+        if pixel != expected {
+            different += 1;
+            first_difference.get_or_insert((x, y, expected, pixel));
+        }
+    }
+
+    if let Some((x, y, expected, actual)) = first_difference {
+        std::fs::write(&actual_path, &actual_ppm).unwrap();
+        panic!(
+            "{different} pixels of {name} differ from {}, the first at ({x}, {y}): expected {expected:?}, got {actual:?}.\nWrote the actual output to {}.",
+            expected_path.display(),
+            actual_path.display(),
+        );
+    }
+
+    let _ = std::fs::remove_file(&actual_path);
+}
+
+fn fixture_resources() -> Resources {
+    Resources {
+        sprite_sheet: SpriteSheet::deserialize(include_str!("test_data/golden/sprite_sheet.txt"))
+            .unwrap(),
+        map: Map::deserialize(include_str!("test_data/golden/map.txt")).unwrap(),
+        sprite_flags: Flags::deserialize(include_str!("test_data/golden/sprite_flags.txt"))
+            .unwrap(),
+        ..Resources::empty()
+    }
+}
+
+fn draw_scene(draw: impl FnOnce(&mut Pico8)) -> DrawData {
+    let mut pico8 = Pico8::new(DrawData::new(), State::new(), fixture_resources());
+    pico8.cls(Color::DARK_BLUE);
+    draw(&mut pico8);
+
+    pico8.draw_data
+}
+
+#[test]
+fn lines() {
+    let screen = draw_scene(|pico8| {
+        for i in 0..16 {
+            pico8.line(64, 64, i * 8, 0, i as u8);
+            pico8.line(64, 64, 127, i * 8, i as u8);
+            pico8.line(64, 64, 127 - i * 8, 127, i as u8);
+            pico8.line(64, 64, 0, 127 - i * 8, i as u8);
+        }
+        pico8.line(-20, 10, 150, 30, Color::WHITE);
+    });
+
+    assert_golden("lines", &screen);
+}
+
+#[test]
+fn rects() {
+    let screen = draw_scene(|pico8| {
+        for i in 0..8 {
+            pico8.rect(i * 4, i * 4, 127 - i * 4, 63 - i * 2, i as u8 + 8);
+        }
+        pico8.rectfill(10, 70, 50, 120, Color::RED);
+        pico8.rectfill(60, 120, 40, 90, Color::GREEN);
+        pico8.rectfill(100, 100, 100, 100, Color::WHITE);
+        pico8.rectfill(110, 80, 200, 200, Color::YELLOW);
+        pico8.rect(-5, 70, 4, 90, Color::PINK);
+    });
+
+    assert_golden("rects", &screen);
+}
+
+#[test]
+fn circles() {
+    let screen = draw_scene(|pico8| {
+        for r in 0..8 {
+            pico8.circ(32, 32, r * 4, r as u8 + 8);
+            pico8.circfill(96, 32, 28 - r * 4, r as u8);
+        }
+        pico8.circfill(32, 96, 0, Color::WHITE);
+        pico8.circfill(48, 96, 1, Color::WHITE);
+        pico8.circfill(64, 96, 2, Color::WHITE);
+        pico8.circ(80, 96, 3, Color::WHITE);
+        pico8.circfill(127, 127, 20, Color::ORANGE);
+    });
+
+    assert_golden("circles", &screen);
+}
+
+#[test]
+fn pixels() {
+    let screen = draw_scene(|pico8| {
+        for x in 0..128 {
+            for y in 0..128 {
+                if (x * 7 + y * 3) % 5 == 0 {
+                    pico8.pset(x, y, ((x / 8 + y / 8) % 16) as u8);
+                }
+            }
+        }
+        pico8.pset(-1, 0, Color::WHITE);
+        pico8.pset(128, 0, Color::WHITE);
+    });
+
+    assert_golden("pixels", &screen);
+}
+
+#[test]
+fn text() {
+    let screen = draw_scene(|pico8| {
+        pico8.print(
+            "THE QUICK BROWN FOX\nJUMPS OVER THE LAZY DOG",
+            1,
+            1,
+            Color::WHITE,
+        );
+        pico8.print(
+            "the quick brown fox\njumps over the lazy dog",
+            1,
+            15,
+            Color::PINK,
+        );
+        pico8.print("0123456789 !?.,:;+-*/=()[]{}<>", 1, 29, Color::YELLOW);
+        pico8.print_shadowed("SHADOWED", 1, 40, Color::WHITE, Color::DARK_GREY);
+        pico8.print_outlined("OUTLINED", 1, 50, Color::WHITE, Color::BLACK);
+        pico8.print(
+            "\u{274e}\u{1f17e}\u{fe0f}\u{2b05}\u{fe0f}\u{27a1}\u{fe0f}",
+            1,
+            60,
+            Color::GREEN,
+        );
+        pico8.print("CUT OFF AT THE EDGE OF THE SCREEN", 100, 120, Color::ORANGE);
+    });
+
+    assert_golden("text", &screen);
+}
+
+#[test]
+fn sprites() {
+    let screen = draw_scene(|pico8| {
+        for spr in 0..64 {
+            pico8.spr(spr, (spr as i32 % 16) * 8, (spr as i32 / 16) * 8);
+        }
+        pico8.spr_(1, 0, 40, 2.0, 2.0, false, false);
+        pico8.spr_(1, 20, 40, 2.0, 2.0, true, false);
+        pico8.spr_(1, 40, 40, 2.0, 2.0, false, true);
+        pico8.spr_(1, 60, 40, 1.5, 0.5, true, true);
+
+        pico8.palt(Some(Color::DARK_BLUE));
+        pico8.pal(Color::RED, Color::GREEN);
+        pico8.spr(1, 0, 64);
+        pico8.reset_pal();
+        pico8.spr(1, -4, 80);
+        pico8.spr(1, 124, 124);
+    });
+
+    assert_golden("sprites", &screen);
+}
+
+#[test]
+fn clip_and_camera() {
+    let screen = draw_scene(|pico8| {
+        pico8.clip(16, 16, 64, 48);
+        pico8.camera(-8, -8);
+        pico8.rectfill(0, 0, 127, 127, Color::DARK_GREEN);
+        pico8.circfill(20, 20, 16, Color::YELLOW);
+        pico8.clip_push(40, 40, 100, 100);
+        pico8.with_camera(10, 0, |pico8| pico8.spr(1, 40, 40));
+        pico8.line(0, 127, 127, 0, Color::WHITE);
+        pico8.clip_pop();
+        pico8.clip(0, 0, 128, 128);
+        pico8.camera(0, 0);
+        pico8.rect(16, 16, 79, 63, Color::RED);
+    });
+
+    assert_golden("clip_and_camera", &screen);
+}
+
+#[test]
+fn map() {
+    let screen = draw_scene(|pico8| {
+        pico8.map(0, 0, 0, 0, 16, 16, 0);
+    });
+
+    assert_golden("map", &screen);
+}
+
+#[test]
+fn map_offset_and_layers() {
+    let screen = draw_scene(|pico8| {
+        pico8.map(16, 0, -4, 4, 17, 8, 0);
+        pico8.map(32, 0, 0, 72, 16, 7, 0b1);
+    });
+
+    assert_golden("map_offset_and_layers", &screen);
+}
+
+#[cfg(feature = "editor")]
+#[test]
+fn editor_sprite_tab() {
+    use crate::editor::Editor;
+    use crate::ElmApp;
+
+    let mut pico8 = Pico8::new(DrawData::new(), State::new(), fixture_resources());
+    let mut editor = <Editor as ElmApp>::init_with_resources(&mut pico8.resources);
+
+    let mut view = <Editor as ElmApp>::view(&mut editor, &pico8.resources);
+    view.as_widget_mut().draw(&mut pico8);
+    drop(view);
+
+    assert_golden("editor_sprite_tab", &pico8.draw_data);
+}
+
+#[cfg(feature = "editor")]
+#[test]
+fn editor_palette_tab() {
+    use crate::editor::{Editor, Msg};
+    use crate::ElmApp;
+
+    let mut pico8 = Pico8::new(DrawData::new(), State::new(), fixture_resources());
+    let mut editor = <Editor as ElmApp>::init_with_resources(&mut pico8.resources);
+    <Editor as ElmApp>::update(
+        &mut editor,
+        &Msg::PaletteButtonClicked,
+        &mut pico8.resources,
+    );
+
+    let mut view = <Editor as ElmApp>::view(&mut editor, &pico8.resources);
+    view.as_widget_mut().draw(&mut pico8);
+    drop(view);
+
+    assert_golden("editor_palette_tab", &pico8.draw_data);
+}
@@ -1,5 +1,6 @@
 use glium::backend::Facade;
 use glium::implement_vertex;
+use glium::vertex::BufferCreationError;
 use glium::VertexBuffer;
 // Rendering boilerplate
 
@@ -11,7 +12,9 @@ pub struct Vertex {
 
 implement_vertex!(Vertex, position, tex_coords); // don't forget to add `tex_coords` here
 
-pub fn whole_screen_vertex_buffer(display: &impl Facade) -> VertexBuffer<Vertex> {
+pub fn whole_screen_vertex_buffer(
+    display: &impl Facade,
+) -> Result<VertexBuffer<Vertex>, BufferCreationError> {
     let vertex1 = Vertex {
         position: [-1.0, -1.0, 0.0, 1.0],
         tex_coords: [0.0, 0.0],
@@ -40,7 +43,7 @@ pub fn whole_screen_vertex_buffer(display: &impl Facade) -> VertexBuffer<Vertex>
 
     let shape = vec![vertex1, vertex2, vertex3, vertex4, vertex5, vertex6];
 
-    glium::VertexBuffer::new(display, &shape).unwrap()
+    glium::VertexBuffer::new(display, &shape)
 }
 
 pub const VERTEX_SHADER: &str = r#"
@@ -71,3 +74,145 @@ void main() {
     color = texture(tex, vec2(v_tex_coords.x, y));
 }
 "#;
+
+/// How the 128x128 screen is scaled up to the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScalingMode {
+    /// Fills the whole window, even if screen pixels end up different sizes.
+    #[default]
+    Stretch,
+    /// As big as it fits while every screen pixel stays the same (whole) number of window pixels,
+    /// centered, with the border color around it. Scrolling doesn't shimmer.
+    Integer,
+}
+
+/// The part of the window the screen is drawn on, in physical pixels from the top left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Viewport {
+    pub(crate) x: u32,
+    pub(crate) y: u32,
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+}
+
+impl Viewport {
+    pub(crate) fn new(mode: ScalingMode, (window_width, window_height): (u32, u32)) -> Self {
+        match mode {
+            ScalingMode::Stretch => Self {
+                x: 0,
+                y: 0,
+                width: window_width,
+                height: window_height,
+            },
+            ScalingMode::Integer => {
+                let size = 128 * (window_width.min(window_height) / 128).max(1);
+
+                Self {
+                    x: window_width.saturating_sub(size) / 2,
+                    y: window_height.saturating_sub(size) / 2,
+                    width: size,
+                    height: size,
+                }
+            }
+        }
+    }
+
+    /// The screen pixel under a point of the window, which may be off the screen.
+    pub(crate) fn to_screen(self, x: f64, y: f64) -> (i32, i32) {
+        let scale = |position: f64, start: u32, size: u32| {
+            ((position - start as f64) / size.max(1) as f64 * 128.).floor() as i32
+        };
+
+        (scale(x, self.x, self.width), scale(y, self.y, self.height))
+    }
+}
+
+/// Makes the screen look like an old CRT: darker lines between the rows of pixels,
+/// a curved screen and darker corners. Only changes how the screen is shown in the window.
+///
+/// Each effect goes from 0 (none) up, see [`crate::Pico8::set_crt_filter`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CrtFilter {
+    /// How much darker it gets between rows, up to 1 (black).
+    pub scanlines: f32,
+    /// How much the screen bulges out.
+    pub curvature: f32,
+    /// How much darker it gets towards the corners.
+    pub vignette: f32,
+}
+
+impl Default for CrtFilter {
+    /// Subtle enough to still read small text.
+    fn default() -> Self {
+        Self {
+            scanlines: 0.3,
+            curvature: 0.06,
+            vignette: 0.4,
+        }
+    }
+}
+
+pub const CRT_FRAGMENT_SHADER: &str = r#"
+#version 140
+
+in vec2 v_tex_coords;
+out vec4 color;
+
+uniform sampler2D tex;
+uniform float scanlines;
+uniform float curvature;
+uniform float vignette;
+
+void main() {
+    // Barrel distortion: the further from the center, the further out it's sampled.
+    vec2 centered = v_tex_coords * 2.0 - 1.0;
+    float falloff = dot(centered, centered);
+    vec2 uv = centered * (1.0 + curvature * falloff) * 0.5 + 0.5;
+
+    if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+        color = vec4(0.0, 0.0, 0.0, 1.0);
+        return;
+    }
+
+    vec3 pixel = texture(tex, vec2(uv.x, 1.0 - uv.y)).rgb;
+    // Brightest in the middle of a row of pixels, darkest between rows.
+    float scanline = 1.0 - scanlines * pow(cos(uv.y * 128.0 * 3.14159265), 2.0);
+    float shade = 1.0 - vignette * falloff * 0.5;
+
+    color = vec4(pixel * scanline * shade, 1.0);
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer_scaling_centers_whole_multiples() {
+        let viewport = Viewport::new(ScalingMode::Integer, (700, 500));
+        assert_eq!(
+            viewport,
+            Viewport {
+                x: 158,
+                y: 58,
+                width: 384,
+                height: 384
+            }
+        );
+        assert_eq!(viewport.to_screen(158.0, 58.0), (0, 0));
+        assert_eq!(viewport.to_screen(541.9, 441.9), (127, 127));
+        assert_eq!(viewport.to_screen(10.0, 250.0), (-50, 64));
+
+        // Too small windows still get the screen, cut off.
+        assert_eq!(Viewport::new(ScalingMode::Integer, (100, 300)).width, 128);
+    }
+
+    #[test]
+    fn stretching_fills_the_window() {
+        let viewport = Viewport::new(ScalingMode::Stretch, (640, 320));
+
+        assert_eq!((viewport.x, viewport.width, viewport.height), (0, 640, 320));
+        assert_eq!(viewport.to_screen(639.0, 319.0), (127, 127));
+        assert_eq!(viewport.to_screen(320.0, 80.0), (64, 32));
+    }
+}
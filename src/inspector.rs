@@ -0,0 +1,295 @@
+//! A panel over the screen showing what the runtime sees: the mouse, the buttons,
+//! the draw state and the last few events. Only with the `editor` feature.
+
+use std::collections::VecDeque;
+use std::iter;
+
+use crate::controller::Scene;
+use crate::editor::shade::ShadeRamp;
+use crate::runtime::state::ButtonState;
+use crate::{Button, Color, Event, KeyboardEvent, MouseButton, MouseEvent, Pico8};
+
+const MAX_EVENTS: usize = 5;
+const LINE_HEIGHT: i32 = 6;
+
+/// Some text and the color it's printed in.
+type Span = (String, Color);
+
+#[derive(Debug, Default)]
+pub(crate) struct Inspector {
+    visible: bool,
+    // Oldest first.
+    events: VecDeque<Event>,
+}
+
+impl Inspector {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    #[cfg(test)]
+    pub(crate) fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Remembers `event`, whether the panel is shown or not.
+    /// Ticks aren't worth showing, and mouse moves in a row only keep the last one.
+    pub(crate) fn record(&mut self, event: &Event) {
+        match (event, self.events.back()) {
+            (Event::Tick { .. }, _) => return,
+            (
+                Event::Mouse(MouseEvent::Move { .. }),
+                Some(Event::Mouse(MouseEvent::Move { .. })),
+            ) => {
+                self.events.pop_back();
+            }
+            _ => {}
+        }
+
+        if self.events.len() == MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(*event);
+    }
+
+    /// Draws the panel at the bottom of the screen, if it's shown.
+    /// The game's draw state is left as it was, so the next frame draws the same.
+    pub(crate) fn draw(&self, pico8: &mut Pico8, scene: &Scene) {
+        if !self.visible {
+            return;
+        }
+
+        let lines = self.lines(pico8, scene);
+        let clip = pico8.draw_data.get_clip();
+        pico8.draw_data.clip(0, 0, 128, 128);
+
+        pico8.with_saved_state(|pico8| {
+            pico8.draw_data.set_font(None);
+            pico8.camera(0, 0);
+            for color in 0..16u8 {
+                pico8.pal(color, color);
+            }
+
+            // Darkens what's behind instead of covering it.
+            let top = 128 - lines.len() as i32 * LINE_HEIGHT - 2;
+            let ramp = ShadeRamp::new();
+            for y in top..128 {
+                for x in 0..128 {
+                    if let Some(color) = pico8.draw_data.pixel(x, y) {
+                        let darker = ramp.step(ramp.step(color, false), false);
+                        pico8.draw_data.set_screen_pixel(x, y, darker);
+                    }
+                }
+            }
+
+            for (line, y) in lines.iter().zip((top + 2..).step_by(LINE_HEIGHT as usize)) {
+                let mut x = 1;
+                for (text, color) in line {
+                    pico8.print(text, x, y, *color);
+                    x += pico8.measure_text(text).0 + 4;
+                }
+            }
+        });
+
+        let (x, y, width, height) = clip;
+        pico8.draw_data.clip(x, y, width, height);
+    }
+
+    fn lines(&self, pico8: &Pico8, scene: &Scene) -> Vec<Vec<Span>> {
+        let text = |text: String| (text, Color::WHITE);
+        let state = &pico8.state;
+        let scene = match scene {
+            Scene::Editor => "EDITOR",
+            Scene::App => "GAME",
+        };
+        let (mouse_x, mouse_y) = state.mouse();
+        let (camera_x, camera_y) = pico8.draw_data.get_camera();
+        let (clip_x, clip_y, clip_width, clip_height) = pico8.draw_data.get_clip();
+
+        let mut lines = vec![
+            vec![
+                text(format!("SCENE {scene}")),
+                text(format!("FRAME {}", state.frames())),
+            ],
+            iter::once(text(format!("MOUSE {mouse_x},{mouse_y}")))
+                .chain(
+                    [
+                        (MouseButton::Left, "L"),
+                        (MouseButton::Middle, "M"),
+                        (MouseButton::Right, "R"),
+                    ]
+                    .into_iter()
+                    .map(|(button, label)| button_span(state.mouse_button(button), label)),
+                )
+                .collect(),
+            iter::once(text("BTN".to_owned()))
+                .chain(
+                    [
+                        (Button::Left, "L"),
+                        (Button::Right, "R"),
+                        (Button::Up, "U"),
+                        (Button::Down, "D"),
+                        (Button::X, "X"),
+                        (Button::C, "C"),
+                    ]
+                    .into_iter()
+                    .map(|(button, label)| button_span(state.button(button), label)),
+                )
+                .collect(),
+            vec![
+                text(format!("CAMERA {camera_x},{camera_y}")),
+                text(format!("CLIP {clip_x},{clip_y},{clip_width},{clip_height}")),
+            ],
+            vec![
+                text(format!("PAL {}", describe_pal(pico8.draw_data.get_pal()))),
+                text(format!(
+                    "PALT {}",
+                    pico8
+                        .draw_data
+                        .get_palt()
+                        .map_or("-".to_owned(), |color| color.index().to_string())
+                )),
+            ],
+        ];
+        lines.extend(
+            self.events
+                .iter()
+                .map(|event| vec![(describe_event(event), Color::LIGHT_GREY)]),
+        );
+
+        lines
+    }
+}
+
+/// Yellow on the update it was pressed, white while held.
+fn button_span(state: &ButtonState, label: &str) -> Span {
+    let color = match state {
+        ButtonState::JustPressed => Color::YELLOW,
+        ButtonState::Held => Color::WHITE,
+        ButtonState::NotPressed => Color::DARK_GREY,
+    };
+
+    (label.to_owned(), color)
+}
+
+/// The colors that are swapped, like "8>11", or "-" if there's none.
+fn describe_pal(palette: [Color; 16]) -> String {
+    let swaps: Vec<String> = palette
+        .iter()
+        .enumerate()
+        .filter(|&(index, color)| color.index() as usize != index)
+        .map(|(index, color)| format!("{index}>{}", color.index()))
+        .collect();
+
+    if swaps.is_empty() {
+        "-".to_owned()
+    } else {
+        swaps.join(" ")
+    }
+}
+
+fn describe_event(event: &Event) -> String {
+    match event {
+        Event::Mouse(MouseEvent::Move { x, y }) => format!("MOVE {x},{y}"),
+        Event::Mouse(MouseEvent::Down(button)) => format!("MOUSE {button:?} DOWN"),
+        Event::Mouse(MouseEvent::Up(button)) => format!("MOUSE {button:?} UP"),
+        Event::Keyboard(KeyboardEvent { key, state }) => format!("KEY {key:?} {state:?}"),
+        Event::Character(char) => format!("CHAR {char:?}"),
+        Event::Tick { delta_millis, .. } => format!("TICK {delta_millis}"),
+        Event::EditorOpened => "EDITOR OPENED".to_owned(),
+        Event::EditorClosed => "EDITOR CLOSED".to_owned(),
+    }
+    .to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::{draw_data::DrawData, state::State};
+    use crate::{Key, KeyState, Resources};
+
+    fn text(lines: &[Vec<Span>]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| {
+                line.iter()
+                    .map(|(text, _)| text.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keeps_the_last_few_events() {
+        let mut inspector = Inspector::new();
+        let key = |key| {
+            Event::Keyboard(KeyboardEvent {
+                key,
+                state: KeyState::Down,
+            })
+        };
+
+        inspector.record(&Event::Tick {
+            delta_millis: 33.0,
+            frame: 0,
+        });
+        inspector.record(&Event::Mouse(MouseEvent::Move { x: 1, y: 2 }));
+        inspector.record(&Event::Mouse(MouseEvent::Move { x: 3, y: 4 }));
+        assert_eq!(
+            inspector
+                .events
+                .iter()
+                .map(describe_event)
+                .collect::<Vec<_>>(),
+            ["MOVE 3,4"]
+        );
+
+        for k in [Key::A, Key::B, Key::C, Key::D, Key::E] {
+            inspector.record(&key(k));
+        }
+        assert_eq!(
+            inspector
+                .events
+                .iter()
+                .map(describe_event)
+                .collect::<Vec<_>>(),
+            [
+                "KEY A DOWN",
+                "KEY B DOWN",
+                "KEY C DOWN",
+                "KEY D DOWN",
+                "KEY E DOWN"
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_the_draw_state_without_changing_it() {
+        let mut pico8 = Pico8::new(DrawData::new(), State::new(), Resources::empty());
+        pico8.camera(4, -2);
+        pico8.clip(10, 20, 30, 40);
+        pico8.pal(8, 11);
+        pico8.palt(None);
+
+        let mut inspector = Inspector::new();
+        inspector.toggle();
+        let lines = text(&inspector.lines(&pico8, &Scene::App));
+        assert_eq!(lines[0], "SCENE GAME FRAME 0");
+        assert_eq!(lines[1], "MOUSE 64,64 L M R");
+        assert_eq!(lines[3], "CAMERA 4,-2 CLIP 10,20,30,40");
+        assert_eq!(lines[4], "PAL 8>11 PALT -");
+
+        inspector.draw(&mut pico8, &Scene::App);
+        assert_eq!(pico8.draw_data.get_camera(), (4, -2));
+        assert_eq!(pico8.draw_data.get_clip(), (10, 20, 30, 40));
+        assert_eq!(pico8.draw_data.get_pal()[8], Color::GREEN);
+        assert_eq!(pico8.draw_data.get_palt(), None);
+        // Drawn outside the game's clip region.
+        assert!((0..128).any(|x| pico8.draw_data.pixel(x, 124) == Some(Color::WHITE)));
+    }
+}
@@ -2,32 +2,60 @@
 #![allow(clippy::new_without_default)]
 // #![deny(missing_docs)]
 mod app;
+pub mod audio;
+#[cfg(feature = "bench")]
+pub mod bench;
+#[cfg(feature = "cli")]
+pub mod cli;
+pub mod collision;
+mod error;
+pub mod glyphs;
 mod pico8;
+#[cfg(feature = "pico_math")]
+pub mod pico_math;
 mod runtime;
+pub mod transitions;
 pub mod ui;
 
 pub use app::App;
 pub use app::ElmApp;
+pub use error::Error;
+pub use font::{measure_text, Font};
+pub use graphics::{CrtFilter, ScalingMode};
 pub use pico8::{rnd, sin, Pico8};
+pub use runtime::color::Color;
 pub use runtime::draw_data::colors;
-pub use runtime::sprite_sheet::Color;
+pub use runtime::flags::Flags;
+pub use runtime::map::Map;
+pub use runtime::palette::Palette;
+pub use runtime::sprite_sheet::{Sprite, SpriteSheet, SpriteSheetSize};
 pub use runtime::state::Button;
+pub use ui::cursor::{CursorKind, CursorMode};
 
+mod cart;
 mod controller;
+#[cfg(feature = "editor")]
+mod debug_controls;
 mod draw;
+#[cfg(feature = "editor")]
 mod editor;
 mod font;
+#[cfg(test)]
+mod golden;
 mod graphics;
+#[cfg(feature = "editor")]
+mod inspector;
+mod key_combo;
+#[cfg(feature = "cli")]
+mod p8;
 mod run;
+mod serialize;
 mod util;
 use app::{AppCompat, ElmAppCompat, Pico8AppCompat};
+use audio::{music::Music, sfx::SoundEffects, volume::Volume, Audio};
 use controller::Scene;
 use glium::glutin::event::{ElementState, VirtualKeyCode};
-use runtime::{
-    flags::Flags,
-    map::Map,
-    sprite_sheet::{Sprite, SpriteSheet},
-};
+use serialize::{Recovery, Saver};
 use std::fmt::Debug;
 
 /// Mouse buttons.
@@ -86,6 +114,17 @@ pub enum Key {
     X,
     Y,
     Z,
+    // The number keys above the letters, not the numpad.
+    Digit0,
+    Digit1,
+    Digit2,
+    Digit3,
+    Digit4,
+    Digit5,
+    Digit6,
+    Digit7,
+    Digit8,
+    Digit9,
     Control,
     LeftArrow,
     RightArrow,
@@ -94,6 +133,15 @@ pub enum Key {
     Escape,
     Alt,
     Space,
+    Backspace,
+    Delete,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Enter,
+    Tab,
+    Shift,
 }
 
 impl Key {
@@ -125,6 +173,16 @@ impl Key {
             VirtualKeyCode::X => Some(Self::X),
             VirtualKeyCode::Y => Some(Self::Y),
             VirtualKeyCode::Z => Some(Self::Z),
+            VirtualKeyCode::Key0 => Some(Self::Digit0),
+            VirtualKeyCode::Key1 => Some(Self::Digit1),
+            VirtualKeyCode::Key2 => Some(Self::Digit2),
+            VirtualKeyCode::Key3 => Some(Self::Digit3),
+            VirtualKeyCode::Key4 => Some(Self::Digit4),
+            VirtualKeyCode::Key5 => Some(Self::Digit5),
+            VirtualKeyCode::Key6 => Some(Self::Digit6),
+            VirtualKeyCode::Key7 => Some(Self::Digit7),
+            VirtualKeyCode::Key8 => Some(Self::Digit8),
+            VirtualKeyCode::Key9 => Some(Self::Digit9),
             VirtualKeyCode::LControl => Some(Self::Control),
             VirtualKeyCode::Left => Some(Self::LeftArrow),
             VirtualKeyCode::Right => Some(Self::RightArrow),
@@ -133,9 +191,40 @@ impl Key {
             VirtualKeyCode::Escape => Some(Self::Escape),
             VirtualKeyCode::LAlt => Some(Self::Alt),
             VirtualKeyCode::Space => Some(Self::Space),
+            VirtualKeyCode::Back => Some(Self::Backspace),
+            VirtualKeyCode::Delete => Some(Self::Delete),
+            VirtualKeyCode::Home => Some(Self::Home),
+            VirtualKeyCode::End => Some(Self::End),
+            VirtualKeyCode::PageUp => Some(Self::PageUp),
+            VirtualKeyCode::PageDown => Some(Self::PageDown),
+            VirtualKeyCode::Return => Some(Self::Enter),
+            VirtualKeyCode::Tab => Some(Self::Tab),
+            VirtualKeyCode::LShift | VirtualKeyCode::RShift => Some(Self::Shift),
             _ => None,
         }
     }
+
+    /// The number on a number key.
+    #[cfg(feature = "editor")]
+    pub(crate) fn digit(self) -> Option<u8> {
+        let digits = [
+            Self::Digit0,
+            Self::Digit1,
+            Self::Digit2,
+            Self::Digit3,
+            Self::Digit4,
+            Self::Digit5,
+            Self::Digit6,
+            Self::Digit7,
+            Self::Digit8,
+            Self::Digit9,
+        ];
+
+        digits
+            .iter()
+            .position(|&digit| digit == self)
+            .map(|digit| digit as u8)
+    }
 }
 
 /// Keyboard events (key up, key down).
@@ -164,10 +253,26 @@ impl KeyState {
 pub enum Event {
     Mouse(MouseEvent),
     Keyboard(KeyboardEvent),
-    Tick { delta_millis: f64 },
+    /// A character was typed, with the keyboard layout and modifiers already applied.
+    Character(char),
+    /// One update of the fixed timestep loop, 30 times per (simulated) second.
+    ///
+    /// Both fields are simulation time, not wall time: a slow frame is caught up with
+    /// several ticks, and ticks stop while the game is paused.
+    Tick {
+        /// Always 1/30 of a second, in milliseconds.
+        delta_millis: f64,
+        /// How many ticks came before this one, see [`Pico8::frame_count`].
+        frame: u64,
+    },
+    /// The game was hidden behind the editor, with Escape.
+    /// Whether it keeps updating is up to [`Pico8::set_updates_in_editor`].
+    EditorOpened,
+    /// The game is showing again.
+    EditorClosed,
 }
 
-fn create_sprite_flags(assets_path: &str) -> Flags {
+fn create_sprite_flags(assets_path: &str) -> Result<Flags, Error> {
     let path = format!(
         "{}{}{}",
         assets_path,
@@ -176,14 +281,113 @@ fn create_sprite_flags(assets_path: &str) -> Flags {
     );
 
     if let Ok(content) = std::fs::read_to_string(&path) {
-        Flags::deserialize(&content).unwrap()
+        Flags::deserialize(&content).map_err(|reason| Error::AssetLoad { path, reason })
     } else {
         println!("Couldn't read flags from {}, creating new flags.", path);
-        Flags::new()
+        Ok(Flags::new())
+    }
+}
+
+fn create_sound_effects(assets_path: &str) -> Result<SoundEffects, Error> {
+    let path = format!(
+        "{}{}{}",
+        assets_path,
+        std::path::MAIN_SEPARATOR,
+        SoundEffects::file_name()
+    );
+
+    if let Ok(content) = std::fs::read_to_string(&path) {
+        SoundEffects::deserialize(&content).map_err(|reason| Error::AssetLoad { path, reason })
+    } else {
+        println!("Couldn't read sfx from {}, creating new sfx.", path);
+        Ok(SoundEffects::new())
+    }
+}
+
+fn create_music(assets_path: &str) -> Result<Music, Error> {
+    let path = format!(
+        "{}{}{}",
+        assets_path,
+        std::path::MAIN_SEPARATOR,
+        Music::file_name()
+    );
+
+    if let Ok(content) = std::fs::read_to_string(&path) {
+        Music::deserialize(&content).map_err(|reason| Error::AssetLoad { path, reason })
+    } else {
+        println!("Couldn't read music from {}, creating new music.", path);
+        Ok(Music::new())
+    }
+}
+
+fn create_palette(assets_path: &str) -> Result<Palette, Error> {
+    let path = format!(
+        "{}{}{}",
+        assets_path,
+        std::path::MAIN_SEPARATOR,
+        Palette::file_name()
+    );
+
+    // Most games keep Pico8's colors, so there's nothing to complain about.
+    match std::fs::read_to_string(&path) {
+        Ok(content) => {
+            Palette::deserialize(&content).map_err(|reason| Error::AssetLoad { path, reason })
+        }
+        Err(_) => Ok(Palette::pico8()),
+    }
+}
+
+fn create_volume(assets_path: &str) -> Result<Volume, Error> {
+    let path = format!(
+        "{}{}{}",
+        assets_path,
+        std::path::MAIN_SEPARATOR,
+        Volume::file_name()
+    );
+
+    if let Ok(content) = std::fs::read_to_string(&path) {
+        Volume::deserialize(&content).map_err(|reason| Error::AssetLoad { path, reason })
+    } else {
+        println!(
+            "Couldn't read settings from {}, using the default volume.",
+            path
+        );
+        Ok(Volume::new())
+    }
+}
+
+/// Deserializes the file at `path`, padding and truncating it (see [`Recovery`])
+/// if there's somewhere to put the warnings about it, which start with the file's name.
+fn deserialize_file<T>(
+    path: String,
+    warnings: Option<&mut Vec<String>>,
+    deserialize: impl FnOnce(&mut Recovery) -> Result<T, String>,
+) -> Result<T, Error> {
+    let mut file_warnings = vec![];
+    let mut recovery = match warnings {
+        Some(_) => Recovery::PadAndTruncate(&mut file_warnings),
+        None => Recovery::Strict,
+    };
+    let deserialized = deserialize(&mut recovery).map_err(|reason| Error::AssetLoad {
+        path: path.clone(),
+        reason,
+    })?;
+
+    if let Some(warnings) = warnings {
+        let file_name = std::path::Path::new(&path)
+            .file_name()
+            .map_or(path.clone(), |name| name.to_string_lossy().into_owned());
+        warnings.extend(
+            file_warnings
+                .into_iter()
+                .map(|warning| format!("{file_name}: {warning}")),
+        );
     }
+
+    Ok(deserialized)
 }
 
-fn create_map(assets_path: &str) -> Map {
+fn create_map(assets_path: &str, warnings: Option<&mut Vec<String>>) -> Result<Map, Error> {
     let path = format!(
         "{}{}{}",
         assets_path,
@@ -192,14 +396,19 @@ fn create_map(assets_path: &str) -> Map {
     );
 
     if let Ok(content) = std::fs::read_to_string(&path) {
-        Map::deserialize(&content).unwrap()
+        deserialize_file(path, warnings, |recovery| {
+            Map::deserialize_with(&content, recovery)
+        })
     } else {
         println!("Couldn't read map from {}, creating new map.", path);
-        Map::new()
+        Ok(Map::new())
     }
 }
 
-fn create_sprite_sheet(assets_path: &str) -> SpriteSheet {
+fn create_sprite_sheet(
+    assets_path: &str,
+    warnings: Option<&mut Vec<String>>,
+) -> Result<SpriteSheet, Error> {
     let path = format!(
         "{}{}{}",
         assets_path,
@@ -208,13 +417,15 @@ fn create_sprite_sheet(assets_path: &str) -> SpriteSheet {
     );
 
     if let Ok(content) = std::fs::read_to_string(&path) {
-        SpriteSheet::deserialize(&content).unwrap()
+        deserialize_file(path, warnings, |recovery| {
+            SpriteSheet::deserialize_with(&content, recovery)
+        })
     } else {
         println!(
             "Couldn't read sprite sheet from {}, creating new sprite sheet.",
             path
         );
-        SpriteSheet::new()
+        Ok(SpriteSheet::new())
     }
 }
 
@@ -237,35 +448,51 @@ fn create_directory(path: &str) -> std::io::Result<()> {
 }
 
 /// Run a Pico8 application.
-pub fn run_app<T: App + 'static>(assets_path: String) -> std::io::Result<()> {
+pub fn run_app<T: App + 'static>(assets_path: String) -> Result<(), Error> {
     run_app_compat::<Pico8AppCompat<T>>(assets_path)
 }
 
 /// Run an Elm-style application.
-pub fn run_elm_app<T: ElmApp + 'static>(assets_path: String) -> std::io::Result<()> {
+pub fn run_elm_app<T: ElmApp + 'static>(assets_path: String) -> Result<(), Error> {
     run_app_compat::<ElmAppCompat<T>>(assets_path)
 }
 // TODO: add example
-fn run_app_compat<T: AppCompat + 'static>(assets_path: String) -> std::io::Result<()> {
+fn run_app_compat<T: AppCompat + 'static>(assets_path: String) -> Result<(), Error> {
     create_directory(&assets_path)?;
 
-    let map: Map = create_map(&assets_path);
-    let sprite_flags: Flags = create_sprite_flags(&assets_path);
-    let sprite_sheet = create_sprite_sheet(&assets_path);
+    let starting_scene = start_scene();
+    // The editor opens broken sprite sheets and maps, fixing them as well as it can
+    // and warning about it, so that they can be repaired there. Games refuse them.
+    let mut asset_warnings = vec![];
+    let mut warnings = recovers_assets(&starting_scene).then_some(&mut asset_warnings);
+
+    let map: Map = create_map(&assets_path, warnings.as_deref_mut())?;
+    let sprite_flags: Flags = create_sprite_flags(&assets_path)?;
+    let sprite_sheet = create_sprite_sheet(&assets_path, warnings)?;
+    let sound_effects = create_sound_effects(&assets_path)?;
+    let music = create_music(&assets_path)?;
+    let palette = create_palette(&assets_path)?;
+    let volume = create_volume(&assets_path)?;
 
     let resources = Resources {
         assets_path,
         sprite_sheet,
         sprite_flags,
         map,
+        sound_effects,
+        music,
+        palette,
+        audio: Audio::new(volume),
+        volume_saver: Saver::new(volume),
+        shared_memory: false,
+        #[cfg(feature = "editor")]
+        asset_warnings,
     };
 
-    let starting_scene = start_scene();
-    crate::run::run_app::<T>(starting_scene, resources);
-
-    Ok(())
+    crate::run::run_app::<T>(starting_scene, resources)
 }
 
+#[cfg(feature = "editor")]
 fn start_scene() -> Scene {
     if std::env::args().any(|arg| arg == "--game") {
         Scene::App
@@ -274,18 +501,426 @@ fn start_scene() -> Scene {
     }
 }
 
-/// Game assets: sprite sheet, map, flags.
+#[cfg(feature = "editor")]
+fn recovers_assets(scene: &Scene) -> bool {
+    matches!(scene, Scene::Editor)
+}
+
+#[cfg(not(feature = "editor"))]
+fn recovers_assets(_: &Scene) -> bool {
+    false
+}
+
+#[cfg(not(feature = "editor"))]
+fn start_scene() -> Scene {
+    Scene::App
+}
+
+/// Game assets: sprite sheet, map, flags, sound effects, music, palette.
 #[derive(Debug)]
 pub struct Resources {
     pub(crate) assets_path: String,
     pub(crate) sprite_sheet: SpriteSheet,
     pub(crate) sprite_flags: Flags,
     pub(crate) map: Map,
+    pub(crate) sound_effects: SoundEffects,
+    pub(crate) music: Music,
+    pub(crate) palette: Palette,
+    // Lives here so that both the game and the editor can play sounds.
+    pub(crate) audio: Audio,
+    volume_saver: Saver<Volume>,
+    // Whether map rows 32 to 63 are stored in the lower half of the sprite sheet, like in Pico8.
+    pub(crate) shared_memory: bool,
+    // What was fixed while loading the assets for the editor, which shows (and empties) it when it starts.
+    #[cfg(feature = "editor")]
+    pub(crate) asset_warnings: Vec<String>,
+}
+
+impl Resources {
+    /// Empty sprite sheet, map, flags, sound effects and music.
+    #[cfg(any(test, feature = "bench", feature = "cli"))]
+    pub(crate) fn empty() -> Self {
+        Self {
+            assets_path: String::new(),
+            sprite_sheet: SpriteSheet::new(),
+            sprite_flags: Flags::new(),
+            map: Map::new(),
+            sound_effects: SoundEffects::new(),
+            music: Music::new(),
+            palette: Palette::pico8(),
+            audio: Audio::silent(),
+            volume_saver: Saver::new(Volume::new()),
+            shared_memory: false,
+            #[cfg(feature = "editor")]
+            asset_warnings: vec![],
+        }
+    }
+
+    /// Replaces the sprite sheet, flags, map, sound effects, music and palette
+    /// with the ones in `assets_path`, for [`Pico8::load_cart`].
+    /// The volume (and the audio output) stay the same.
+    pub(crate) fn load_assets(&mut self, assets_path: String) -> Result<(), Error> {
+        create_directory(&assets_path)?;
+
+        // Everything is read before anything is replaced, so a broken cart doesn't leave a mix behind.
+        let map = create_map(&assets_path, None)?;
+        let sprite_flags = create_sprite_flags(&assets_path)?;
+        let sprite_sheet = create_sprite_sheet(&assets_path, None)?;
+        let sound_effects = create_sound_effects(&assets_path)?;
+        let music = create_music(&assets_path)?;
+        let palette = create_palette(&assets_path)?;
+
+        self.assets_path = assets_path;
+        self.map = map;
+        self.sprite_flags = sprite_flags;
+        self.sprite_sheet = sprite_sheet;
+        self.sound_effects = sound_effects;
+        self.music = music;
+        self.palette = palette;
+        self.shared_memory = false;
+
+        Ok(())
+    }
+
+    /// Makes map rows 32 to 63 read and write the lower half of the sprite sheet
+    /// (sprites 128 to 255), like in Pico8. Off by default.
+    ///
+    /// While enabled, the map's own data for those rows is ignored.
+    /// Only meant for Pico8 sized maps, and does nothing with a
+    /// [`SpriteSheetSize::Double`] sprite sheet.
+    pub fn set_shared_memory(&mut self, shared_memory: bool) {
+        self.shared_memory = shared_memory;
+    }
+
+    pub fn shared_memory(&self) -> bool {
+        self.shared_memory
+    }
+
+    /// The sprite sheet, to read sprite pixels with [`SpriteSheet::get_sprite`] and [`Sprite::pget`].
+    pub fn sprite_sheet(&self) -> &SpriteSheet {
+        &self.sprite_sheet
+    }
+
+    pub fn sprite_flags(&self) -> &Flags {
+        &self.sprite_flags
+    }
+
+    /// The sound effects, to render one with [`audio::render_sfx_to_wav`].
+    pub fn sound_effects(&self) -> &SoundEffects {
+        &self.sound_effects
+    }
+
+    /// The music patterns, to render one with [`audio::render_music_to_wav`].
+    pub fn music(&self) -> &Music {
+        &self.music
+    }
+
+    /// The map as it's stored, ignoring [`Resources::set_shared_memory`].
+    /// [`Resources::mget`] reads the shared rows from the sprite sheet instead.
+    pub fn map(&self) -> &Map {
+        &self.map
+    }
+
+    /// The colors the screen is shown with, loaded from `palette.txt`
+    /// in the assets directory (Pico8's colors if there's none).
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    /// Changes the volume. It's saved to the settings file once it stops changing, see [`Resources::save_volume`].
+    pub(crate) fn set_volume(&mut self, volume: Volume) {
+        if volume != self.audio.volume() {
+            self.audio.set_volume(volume);
+        }
+    }
+
+    /// Writes the volume to the settings file once it's stayed the same for a bit, called every frame.
+    /// With `now`, writes it if it changed at all (when the game exits).
+    pub(crate) fn save_volume(&mut self, now: bool) {
+        let volume = self.audio.volume();
+        let changed = if now {
+            self.volume_saver.flush(volume)
+        } else {
+            self.volume_saver.tick(volume)
+        };
+
+        if let Some(volume) = changed {
+            let file_path = format!("{}/{}", self.assets_path, Volume::file_name());
+            // Games can be run from read-only directories, they just won't remember the volume.
+            if let Err(error) = serialize::write_file(&file_path, &volume) {
+                eprintln!("Couldn't save the volume to {file_path}: {error}");
+            }
+        }
+    }
+
+    pub(crate) fn toggle_mute(&mut self) {
+        let volume = self.audio.volume();
+
+        self.set_volume(Volume {
+            muted: !volume.muted,
+            ..volume
+        });
+    }
+
+    /// Grows or shrinks the sprite sheet. New sprites are blank,
+    /// and shrinking drops the sprites that don't fit.
+    ///
+    /// Sprites keep their numbers, but since the sheet's rows change length,
+    /// sprites past the first row move to different `sget`/`sset` coordinates.
+    pub fn set_sprite_sheet_size(&mut self, size: SpriteSheetSize) {
+        self.sprite_sheet.resize(size);
+    }
+
+    pub fn sprite_sheet_size(&self) -> SpriteSheetSize {
+        self.sprite_sheet.size()
+    }
+
+    // Where (x, y) lives in the sprite sheet, if it's currently shared.
+    fn shared_map_offset(&self, x: i32, y: i32) -> Option<usize> {
+        if self.shared_memory && self.sprite_sheet.size() == SpriteSheetSize::Pico8 {
+            Map::shared_offset(x, y)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the color of the pixel at (x, y) in the sprite sheet,
+    /// or 0 if the coordinates are out of bounds.
+    pub fn sget(&self, x: i32, y: i32) -> Color {
+        self.sprite_sheet.sget(x, y)
+    }
+
+    /// Sets the pixel at (x, y) in the sprite sheet, out of bounds writes are ignored.
+    pub fn sset(&mut self, x: i32, y: i32, color: impl Into<Color>) {
+        if let Some(index) = self.sprite_sheet.checked_index(x, y) {
+            self.sprite_sheet.sprite_sheet[index] = color.into().index();
+        }
+    }
+
+    pub fn mget(&self, x: i32, y: i32) -> u8 {
+        match self.shared_map_offset(x, y) {
+            Some(offset) => self.sprite_sheet.shared_map_cell(offset),
+            None => self.map.mget(x, y),
+        }
+    }
+
+    /// Writes outside of the map are ignored.
+    pub fn mset(&mut self, x: i32, y: i32, spr: u8) {
+        match self.shared_map_offset(x, y) {
+            Some(offset) => self.sprite_sheet.set_shared_map_cell(offset, spr),
+            None => self.map.mset(x, y, spr),
+        }
+    }
+
+    /// Width and height of the map, in sprites.
+    pub fn map_size(&self) -> (i32, i32) {
+        (self.map.width() as i32, self.map.height() as i32)
+    }
+
+    /// Returns all the flags of a sprite as a bitfield.
+    pub fn fget(&self, sprite: usize) -> u8 {
+        self.sprite_flags.fget(sprite)
+    }
+
+    pub fn fget_n(&self, sprite: usize, flag: u8) -> bool {
+        self.sprite_flags.fget_n(sprite, flag)
+    }
+
+    pub fn fset(&mut self, sprite: usize, flag: usize, value: bool) -> u8 {
+        self.sprite_flags.fset(sprite, flag, value)
+    }
+
+    /// Sets all the flags of a sprite at once.
+    pub fn fset_all(&mut self, sprite: usize, flags: u8) -> u8 {
+        self.sprite_flags.fset_all(sprite, flags)
+    }
+
+    /// Replaces the sprite sheet with one embedded in the game,
+    /// like the `SPRITE_SHEET` exported by the editor (Ctrl+Shift+E).
+    pub fn load_sprite_sheet(&mut self, pixels: &[u8]) -> Result<(), Error> {
+        self.sprite_sheet =
+            SpriteSheet::from_raw(pixels).map_err(embedded_error("sprite sheet"))?;
+
+        Ok(())
+    }
+
+    /// Replaces the map with one embedded in the game,
+    /// like the `MAP` exported by the editor (Ctrl+Shift+E).
+    pub fn load_map(&mut self, width: usize, height: usize, cells: &[u8]) -> Result<(), Error> {
+        self.map = Map::from_raw(width, height, cells).map_err(embedded_error("map"))?;
+
+        Ok(())
+    }
+
+    /// Replaces the map, for example with a bigger one built with [`Map::with_size`].
+    /// The editor saves it with the size it has.
+    pub fn set_map(&mut self, map: Map) {
+        self.map = map;
+    }
+
+    /// Replaces the sprite flags with ones embedded in the game,
+    /// like the `SPRITE_FLAGS` exported by the editor (Ctrl+Shift+E).
+    pub fn load_sprite_flags(&mut self, flags: &[u8]) -> Result<(), Error> {
+        self.sprite_flags = Flags::from_slice(flags).map_err(embedded_error("sprite flags"))?;
+
+        Ok(())
+    }
+
+    /// The sprite sheet, map and flags as a Rust source file,
+    /// so that games can ship them inside the binary (with `include!`).
+    pub fn to_rust_source(&self) -> String {
+        [
+            "// Generated by the Runty8 editor, changes will be overwritten.\n".to_owned(),
+            self.sprite_sheet.to_rust_source("SPRITE_SHEET"),
+            self.map.to_rust_source("MAP"),
+            self.sprite_flags.to_rust_source("SPRITE_FLAGS"),
+        ]
+        .join("\n")
+    }
+}
+
+fn embedded_error(asset: &str) -> impl Fn(String) -> Error + '_ {
+    move |reason| Error::AssetLoad {
+        path: format!("embedded {asset}"),
+        reason,
+    }
 }
 
-/* UTILS */
-pub(crate) fn write_and_log(file_name: &str, contents: &str) {
-    print!("Writing {file_name}... ");
-    std::fs::write(&file_name, contents).unwrap();
-    println!("success.")
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialize::to_string;
+
+    #[test]
+    fn shared_map_rows_write_to_the_sprite_sheet() {
+        let mut resources = Resources::empty();
+        resources.set_shared_memory(true);
+
+        // Cell (3, 40) is byte 8 * 128 + 3 of the shared region,
+        // which holds pixels (6, 80) and (7, 80) of the sprite sheet.
+        resources.mset(3, 40, 0x2A);
+        assert_eq!(resources.sget(6, 80), Color::from(0xA));
+        assert_eq!(resources.sget(7, 80), Color::from(0x2));
+        assert_eq!(resources.mget(3, 40), 0x2A);
+
+        // The map itself isn't touched.
+        assert_eq!(resources.map.mget(3, 40), 0);
+
+        // Rows above 32 aren't shared.
+        resources.mset(3, 8, 0x2A);
+        assert_eq!(resources.map.mget(3, 8), 0x2A);
+        assert_eq!(resources.sget(6, 16), Color::BLACK);
+    }
+
+    #[test]
+    fn sprite_edits_show_up_in_shared_map_rows() {
+        let mut resources = Resources::empty();
+        resources.set_shared_memory(true);
+
+        resources.sset(0, 64, 5);
+        resources.sset(1, 64, 1);
+        assert_eq!(resources.mget(0, 32), 0x15);
+
+        resources.sset(127, 127, 15);
+        assert_eq!(resources.mget(127, 63), 0xF0);
+    }
+
+    #[test]
+    fn memory_isnt_shared_by_default() {
+        let mut resources = Resources::empty();
+
+        resources.mset(3, 40, 0x2A);
+        resources.sset(0, 64, 5);
+
+        assert_eq!(resources.sget(6, 80), Color::BLACK);
+        assert_eq!(resources.mget(3, 40), 0x2A);
+        assert_eq!(resources.mget(0, 32), 0);
+    }
+
+    #[test]
+    fn double_sprite_sheets_reach_every_pixel() {
+        let mut resources = Resources::empty();
+        resources.set_sprite_sheet_size(SpriteSheetSize::Double);
+
+        resources.sset(255, 127, 9);
+        assert_eq!(resources.sget(255, 127), Color::ORANGE);
+        assert_eq!(
+            resources.sprite_sheet.get_sprite(511).pget(7, 7),
+            Color::ORANGE
+        );
+        resources.sset(256, 0, 9);
+        assert_eq!(resources.sget(256, 0), Color::BLACK);
+
+        // There's no memory shared with the map.
+        resources.set_shared_memory(true);
+        resources.mset(3, 40, 0x2A);
+        assert_eq!(resources.map.mget(3, 40), 0x2A);
+    }
+
+    // Resources with a bit of everything, small enough to keep the exported fixture short.
+    fn embedded_test_resources() -> Resources {
+        let mut resources = Resources::empty();
+        resources.map = Map::with_size(4, 3);
+        resources.sset(0, 0, 8);
+        resources.sset(9, 1, 15);
+        resources.sset(127, 127, 12);
+        resources.mset(1, 0, 1);
+        resources.mset(3, 2, 255);
+        resources.fset(1, 0, true);
+        resources.fset_all(255, 0b1000_0001);
+
+        resources
+    }
+
+    #[test]
+    fn exports_assets_as_rust_source() {
+        assert_eq!(
+            embedded_test_resources().to_rust_source(),
+            include_str!("test_data/embedded_assets.rs")
+        );
+    }
+
+    #[test]
+    fn loads_exported_assets() {
+        mod embedded {
+            include!("test_data/embedded_assets.rs");
+        }
+
+        let original = embedded_test_resources();
+        let mut resources = Resources::empty();
+        resources
+            .load_sprite_sheet(&embedded::SPRITE_SHEET)
+            .unwrap();
+        resources
+            .load_map(embedded::MAP_WIDTH, embedded::MAP_HEIGHT, &embedded::MAP)
+            .unwrap();
+        resources
+            .load_sprite_flags(&embedded::SPRITE_FLAGS)
+            .unwrap();
+
+        assert_eq!(
+            resources.sprite_sheet.sprite_sheet,
+            original.sprite_sheet.sprite_sheet
+        );
+        assert_eq!(resources.map_size(), (4, 3));
+        assert_eq!(to_string(&resources.map), to_string(&original.map));
+        assert_eq!(
+            to_string(&resources.sprite_flags),
+            to_string(&original.sprite_flags)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_embedded_assets() {
+        let mut resources = Resources::empty();
+
+        assert!(resources.load_sprite_sheet(&[16; 128 * 128]).is_err());
+        assert!(resources.load_sprite_sheet(&[0; 10]).is_err());
+        assert!(resources.load_map(4, 3, &[0; 11]).is_err());
+        assert!(resources.load_sprite_flags(&[0; 255]).is_err());
+    }
 }
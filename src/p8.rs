@@ -0,0 +1,204 @@
+//! Importing Pico8's `.p8` text carts: their sprites, flags, map, sound effects and music.
+//! The Lua code has no equivalent here, so it's left out.
+
+use crate::audio::{music::Music, sfx::SoundEffects};
+use crate::runtime::{flags::Flags, map::Map, sprite_sheet::SpriteSheet};
+
+const HEADER: &str = "pico-8 cartridge";
+
+/// Everything a `.p8` cart has that runty8 can use.
+#[derive(Debug)]
+pub(crate) struct P8 {
+    pub(crate) sprite_sheet: SpriteSheet,
+    pub(crate) sprite_flags: Flags,
+    pub(crate) map: Map,
+    pub(crate) sound_effects: SoundEffects,
+    pub(crate) music: Music,
+}
+
+impl P8 {
+    /// Missing sections are left empty, like Pico8 does when saving a cart.
+    pub(crate) fn parse(str: &str) -> Result<Self, String> {
+        if !str.starts_with(HEADER) {
+            return Err(format!("Not a Pico8 cart, it should start with `{HEADER}`"));
+        }
+
+        let sprite_sheet = parse_gfx(&section(str, "gfx"))?;
+        let sprite_flags = parse_gff(&section(str, "gff"))?;
+        let map = parse_map(&section(str, "map"), &sprite_sheet)?;
+        let sound_effects = SoundEffects::deserialize(&section(str, "sfx").join("\n"))?;
+        let music = Music::deserialize(&section(str, "music").join("\n"))?;
+
+        Ok(Self {
+            sprite_sheet,
+            sprite_flags,
+            map,
+            sound_effects,
+            music,
+        })
+    }
+}
+
+/// The non empty lines between `__name__` and the next section.
+fn section<'a>(str: &'a str, name: &str) -> Vec<&'a str> {
+    let start = format!("__{name}__");
+    let is_section = |line: &str| line.len() > 4 && line.starts_with("__") && line.ends_with("__");
+
+    str.lines()
+        .map(str::trim)
+        .skip_while(|&line| line != start)
+        .skip(1)
+        .take_while(|&line| !is_section(line))
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+// One hex digit per pixel, one line per row.
+fn parse_gfx(lines: &[&str]) -> Result<SpriteSheet, String> {
+    let mut sprite_sheet = SpriteSheet::new();
+
+    for (y, line) in lines.iter().enumerate() {
+        for (x, digit) in line.chars().enumerate() {
+            let color = digit
+                .to_digit(16)
+                .ok_or_else(|| format!("Invalid __gfx__ pixel: {digit}"))?;
+            let index = sprite_sheet
+                .checked_index(x as i32, y as i32)
+                .ok_or_else(|| {
+                    format!("__gfx__ is bigger than {0}x{0} pixels", SpriteSheet::HEIGHT)
+                })?;
+
+            sprite_sheet.sprite_sheet[index] = color as u8;
+        }
+    }
+
+    Ok(sprite_sheet)
+}
+
+// Two hex digits per sprite.
+fn parse_gff(lines: &[&str]) -> Result<Flags, String> {
+    let mut flags = lines
+        .iter()
+        .map(|line| hex_bytes(line, "__gff__"))
+        .collect::<Result<Vec<_>, _>>()?
+        .concat();
+
+    if flags.len() > SpriteSheet::SPRITE_COUNT {
+        return Err(format!(
+            "__gff__ has flags for {} sprites, at most {} are supported",
+            flags.len(),
+            SpriteSheet::SPRITE_COUNT
+        ));
+    }
+    flags.resize(SpriteSheet::SPRITE_COUNT, 0);
+
+    Flags::from_slice(&flags)
+}
+
+// Two hex digits per cell, for the top half of the map.
+// The bottom half lives in the lower half of the sprite sheet.
+fn parse_map(lines: &[&str], sprite_sheet: &SpriteSheet) -> Result<Map, String> {
+    let mut map = Map::with_size(Map::WIDTH_SPRITES, Map::HEIGHT_SPRITES);
+
+    if lines.len() > Map::HEIGHT_SPRITES / 2 {
+        return Err(format!(
+            "__map__ has {} rows, at most {} are supported",
+            lines.len(),
+            Map::HEIGHT_SPRITES / 2
+        ));
+    }
+    for (y, line) in lines.iter().enumerate() {
+        for (x, sprite) in hex_bytes(line, "__map__")?.into_iter().enumerate() {
+            map.mset(x as i32, y as i32, sprite);
+        }
+    }
+
+    for y in Map::HEIGHT_SPRITES / 2..Map::HEIGHT_SPRITES {
+        for x in 0..Map::WIDTH_SPRITES {
+            if let Some(offset) = Map::shared_offset(x as i32, y as i32) {
+                map.mset(x as i32, y as i32, sprite_sheet.shared_map_cell(offset));
+            }
+        }
+    }
+
+    Ok(map)
+}
+
+fn hex_bytes(line: &str, section: &str) -> Result<Vec<u8>, String> {
+    if !line.len().is_multiple_of(2) || !line.is_ascii() {
+        return Err(format!("Invalid {section} line: {line}"));
+    }
+
+    (0..line.len())
+        .step_by(2)
+        .map(|start| {
+            let digits = &line[start..start + 2];
+
+            u8::from_str_radix(digits, 16).map_err(|_| format!("Invalid {section} data: {digits}"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CART: &str = "pico-8 cartridge // http://www.pico-8.com
+version 41
+__lua__
+function _draw()
+ cls()
+end
+__gfx__
+00000000000000000000000000000000
+0000000000e800000000000000000000
+__gff__
+0001800000000000000000000000000000000000000000000000000000000000
+__map__
+0102000000000000000000000000000000000000000000000000000000000000
+0000000000000000000000000000000000000000000000000000000000000002
+__sfx__
+000400001c0501e050200500000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000
+__music__
+03 00414243
+";
+
+    #[test]
+    fn reads_every_section() {
+        let cart = P8::parse(CART).unwrap();
+
+        assert_eq!(
+            cart.sprite_sheet.get_sprite(1).pget(2, 1),
+            crate::Color::PINK
+        );
+        assert_eq!(
+            cart.sprite_sheet.get_sprite(1).pget(3, 1),
+            crate::Color::RED
+        );
+        assert_eq!(cart.sprite_flags.fget(1), 0x01);
+        assert_eq!(cart.sprite_flags.fget(2), 0x80);
+        assert_eq!((cart.map.mget(0, 0), cart.map.mget(1, 0)), (1, 2));
+        assert_eq!(cart.map.mget(31, 1), 2);
+        assert_eq!(cart.sound_effects.get(0).unwrap().speed, 4);
+        assert_eq!(cart.music.get(0).unwrap().sfx[0], Some(0));
+        assert_eq!(cart.music.get(0).unwrap().sfx[1], None);
+    }
+
+    #[test]
+    fn reads_the_bottom_of_the_map_from_the_sprite_sheet() {
+        let mut gfx = vec!["0".repeat(128); 64];
+        gfx.push(format!("21{}", "0".repeat(126)));
+        let cart = format!("{HEADER}\n__gfx__\n{}\n", gfx.join("\n"));
+
+        let cart = P8::parse(&cart).unwrap();
+        assert_eq!(cart.map.mget(0, 32), 0x12);
+        assert_eq!(cart.map.mget(1, 32), 0);
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(P8::parse("-- just some lua").is_err());
+        assert!(P8::parse(&format!("{HEADER}\n__gfx__\n0g\n")).is_err());
+        assert!(P8::parse(&format!("{HEADER}\n__map__\n010\n")).is_err());
+    }
+}
@@ -1,10 +1,18 @@
 use rand::Rng;
 use std::f32::consts::PI;
 
+use crate::audio::music::Music;
+use crate::audio::volume::Volume;
+use crate::audio::Command;
+use crate::cart::{self, Carts};
 use crate::runtime::draw_data::DrawData;
 use crate::runtime::sprite_sheet::{Sprite, SpriteSheet};
 use crate::runtime::state::State;
-use crate::{Button, Color, Resources};
+use crate::ui::cursor::{self, CursorKind, CursorMode};
+use crate::{
+    App, Button, Color, CrtFilter, Error, Font, Map, MouseButton, Palette, Resources, ScalingMode,
+    SpriteSheetSize,
+};
 
 /// Struct providing an implementation of the pico8 API.
 #[derive(Debug)]
@@ -13,6 +21,15 @@ pub struct Pico8 {
     pub(crate) state: State,
     pub(crate) resources: Resources,
     new_title: Option<String>,
+    cursor: CursorKind,
+    cursor_mode: CursorMode,
+    border_color: Color,
+    crt_filter: Option<CrtFilter>,
+    scaling_mode: ScalingMode,
+    updates_in_editor: bool,
+    // Whether `App::draw` is running, which can't change the resources.
+    drawing: bool,
+    pub(crate) carts: Carts,
 }
 
 impl Pico8 {
@@ -22,20 +39,107 @@ impl Pico8 {
             state,
             resources,
             new_title: None,
+            cursor: CursorKind::Hidden,
+            cursor_mode: CursorMode::Sprite,
+            border_color: Color::BLACK,
+            crt_filter: None,
+            scaling_mode: ScalingMode::Stretch,
+            updates_in_editor: false,
+            drawing: false,
+            carts: Carts::new(),
         }
     }
 }
 
 // Public (Pico8) interface
 impl Pico8 {
-    pub fn mget(&self, x: i32, y: i32) -> u8 {
-        self.resources.map.mget(x, y)
+    pub fn mget(&self, x: impl Into<i32>, y: impl Into<i32>) -> u8 {
+        self.resources.mget(x.into(), y.into())
     }
 
-    pub fn mset(&mut self, x: i32, y: i32, spr: u8) {
-        self.resources
-            .map
-            .mset(x.try_into().unwrap(), y.try_into().unwrap(), spr);
+    pub fn mset(&mut self, x: impl Into<i32>, y: impl Into<i32>, spr: u8) {
+        self.resources_mut().mset(x.into(), y.into(), spr);
+    }
+
+    /// Width and height of the map, in sprites.
+    pub fn map_size(&self) -> (i32, i32) {
+        self.resources.map_size()
+    }
+
+    /// See [`Resources::set_shared_memory`].
+    pub fn set_shared_memory(&mut self, shared_memory: bool) {
+        self.resources_mut().set_shared_memory(shared_memory);
+    }
+
+    /// Switches to a bigger (or back to the regular) sprite sheet,
+    /// see [`Resources::set_sprite_sheet_size`].
+    /// Sprites 256 and up can be drawn with [`Pico8::spr`], but can't go on the map
+    /// or have flags, since map cells are a single byte.
+    ///
+    /// Call it in `init`, so that the editor works with the same size.
+    ///
+    /// Not in Pico8.
+    pub fn set_sprite_sheet_size(&mut self, size: SpriteSheetSize) {
+        self.resources_mut().set_sprite_sheet_size(size);
+    }
+
+    /// Not in Pico8.
+    pub fn sprite_sheet_size(&self) -> SpriteSheetSize {
+        self.resources.sprite_sheet_size()
+    }
+
+    /// See [`Resources::load_sprite_sheet`].
+    ///
+    /// Not in Pico8.
+    pub fn load_sprite_sheet(&mut self, pixels: &[u8]) -> Result<(), crate::Error> {
+        self.resources_mut().load_sprite_sheet(pixels)
+    }
+
+    /// See [`Resources::load_map`].
+    ///
+    /// Not in Pico8.
+    pub fn load_map(
+        &mut self,
+        width: usize,
+        height: usize,
+        cells: &[u8],
+    ) -> Result<(), crate::Error> {
+        self.resources_mut().load_map(width, height, cells)
+    }
+
+    /// See [`Resources::set_map`].
+    ///
+    /// Not in Pico8.
+    pub fn set_map(&mut self, map: Map) {
+        self.resources_mut().set_map(map);
+    }
+
+    /// See [`Resources::load_sprite_flags`].
+    ///
+    /// Not in Pico8.
+    pub fn load_sprite_flags(&mut self, flags: &[u8]) -> Result<(), crate::Error> {
+        self.resources_mut().load_sprite_flags(flags)
+    }
+
+    /// Changes the colors the screen is shown with.
+    /// The `palette.txt` saved by the editor's palette tab is already loaded on startup,
+    /// games that embed their assets can load it with [`Palette::deserialize`].
+    ///
+    /// Not in Pico8.
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.resources_mut().set_palette(palette);
+    }
+
+    /// See [`Resources::palette`].
+    ///
+    /// Not in Pico8.
+    pub fn palette(&self) -> &Palette {
+        self.resources.palette()
+    }
+
+    /// Returns all the flags of a sprite as a bitfield.
+    pub fn fget(&self, sprite: usize) -> u8 {
+        self.resources.fget(sprite)
     }
 
     // TODO: Check we do the same left-to-right (or vice versa)
@@ -45,13 +149,13 @@ impl Pico8 {
     }
 
     pub fn fset(&mut self, sprite: usize, flag: usize, value: bool) -> u8 {
-        self.resources.sprite_flags.fset(sprite, flag, value)
+        self.resources_mut().sprite_flags.fset(sprite, flag, value)
     }
 
     // TODO: Find a better naming scheme
     // TODO: Do we need to return the new flags?
     pub fn fset_all(&mut self, sprite: usize, flags: u8) {
-        self.resources.sprite_flags.fset_all(sprite, flags);
+        self.resources_mut().sprite_flags.fset_all(sprite, flags);
     }
 
     pub fn btnp(&self, button: Button) -> bool {
@@ -62,144 +166,866 @@ impl Pico8 {
         self.state.button(button).btn()
     }
 
-    pub fn pal(&mut self, old: Color, new: Color) {
-        self.draw_data.pal(old, new);
+    pub fn pal(&mut self, old: impl Into<Color>, new: impl Into<Color>) {
+        self.draw_data.pal(old.into(), new.into());
     }
 
     pub fn palt(&mut self, transparent_color: Option<Color>) {
         self.draw_data.palt(transparent_color);
     }
 
+    /// Displays every `old` pixel on the screen as `new`, including what was drawn before.
+    /// Same as `pal(old, new, 1)` in Pico8.
+    ///
+    /// Reset by [`Pico8::reset_pal`], and never applied to the editor.
+    pub fn screen_pal(&mut self, old: impl Into<Color>, new: impl Into<Color>) {
+        self.draw_data.screen_pal(old.into(), new.into());
+    }
+
     pub fn reset_pal(&mut self) {
         self.draw_data.reset_pal();
     }
 
-    pub fn pset(&mut self, x: i32, y: i32, color: Color) {
-        self.draw_data.pset(x, y, color);
+    /// Rotates the screen palette slots of `colors` by one every `period_frames` frames,
+    /// for waterfalls and fire: the first color is shown as the second one, and so on,
+    /// the last one as the first. Replaces the previous cycle.
+    ///
+    /// Like [`Pico8::screen_pal`] it's applied when the screen is displayed, and never to the editor.
+    /// It follows [`Pico8::frame_count`], so it's the same on every run and stops while paused.
+    /// Colors set with [`Pico8::screen_pal`] win over the cycle, until [`Pico8::reset_pal`],
+    /// which leaves the cycle running. Stopped by [`Pico8::pal_cycle_stop`].
+    ///
+    /// Not in Pico8.
+    pub fn pal_cycle(&mut self, colors: &[Color], period_frames: u32) {
+        let frame = self.frame_count();
+        self.draw_data.pal_cycle(colors, period_frames, frame);
     }
 
-    pub fn cls(&mut self, color: Color) {
-        self.draw_data.cls_color(color);
+    /// Stops the cycle started by [`Pico8::pal_cycle`], colors are shown as they were drawn again.
+    ///
+    /// Not in Pico8.
+    pub fn pal_cycle_stop(&mut self) {
+        self.draw_data.pal_cycle_stop();
     }
 
-    pub fn camera(&mut self, x: i32, y: i32) {
-        self.draw_data.camera(x, y);
+    /// Saves the palette (including transparency), camera, font and clip region,
+    /// to be restored by the next [`Pico8::pop_draw_state`].
+    ///
+    /// Not in Pico8.
+    pub fn push_draw_state(&mut self) {
+        self.draw_data.push_state();
     }
 
-    pub fn clip(&mut self, _x: i32, _y: i32, _w: i32, _h: i32) {
-        todo!()
+    /// Restores the draw state saved by the last [`Pico8::push_draw_state`].
+    /// Does nothing if nothing was saved.
+    ///
+    /// Not in Pico8.
+    pub fn pop_draw_state(&mut self) {
+        self.draw_data.pop_state();
+    }
+
+    /// Runs `f`, then undoes whatever it did to the draw state (see [`Pico8::push_draw_state`]).
+    ///
+    /// Not in Pico8.
+    pub fn with_saved_state<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        self.push_draw_state();
+        let result = f(self);
+        self.pop_draw_state();
+
+        result
+    }
+
+    pub fn pset(&mut self, x: impl Into<i32>, y: impl Into<i32>, color: impl Into<Color>) {
+        self.draw_data.pset(x.into(), y.into(), color.into());
+    }
+
+    pub fn cls(&mut self, color: impl Into<Color>) {
+        self.draw_data.cls_color(color.into());
+    }
+
+    pub fn camera(&mut self, x: impl Into<i32>, y: impl Into<i32>) {
+        self.draw_data.camera(x.into(), y.into());
+    }
+
+    /// Only draws inside the `w` x `h` rectangle at (`x`, `y`) on the screen (the camera doesn't move it).
+    /// `clip(0, 0, 128, 128)` draws everywhere again.
+    pub fn clip(
+        &mut self,
+        x: impl Into<i32>,
+        y: impl Into<i32>,
+        w: impl Into<i32>,
+        h: impl Into<i32>,
+    ) {
+        self.draw_data.clip(x.into(), y.into(), w.into(), h.into());
+    }
+
+    /// Like [`Pico8::clip`], but only inside the current clip region, so nested clips compose.
+    /// The current region is restored by the next [`Pico8::clip_pop`].
+    ///
+    /// Not in Pico8.
+    pub fn clip_push(
+        &mut self,
+        x: impl Into<i32>,
+        y: impl Into<i32>,
+        w: impl Into<i32>,
+        h: impl Into<i32>,
+    ) {
+        self.draw_data
+            .clip_push(x.into(), y.into(), w.into(), h.into());
+    }
+
+    /// Restores the clip region from before the last [`Pico8::clip_push`].
+    /// Does nothing if nothing was pushed.
+    ///
+    /// Not in Pico8.
+    pub fn clip_pop(&mut self) {
+        self.draw_data.clip_pop();
     }
 
     // todo
+    #[allow(clippy::too_many_arguments)]
     pub fn map(
         &mut self,
-        cell_x: i32,
-        cell_y: i32,
-        sx: i32,
-        sy: i32,
-        celw: i32,
-        celh: i32,
+        cell_x: impl Into<i32>,
+        cell_y: impl Into<i32>,
+        sx: impl Into<i32>,
+        sy: impl Into<i32>,
+        celw: impl Into<i32>,
+        celh: impl Into<i32>,
         layer: u8,
     ) {
         self.draw_data.map(
-            cell_x,
-            cell_y,
-            sx,
-            sy,
-            celw,
-            celh,
+            cell_x.into(),
+            cell_y.into(),
+            sx.into(),
+            sy.into(),
+            celw.into(),
+            celh.into(),
             layer,
-            &self.resources.map,
-            &self.resources.sprite_flags,
+            &self.resources,
+        );
+    }
+
+    /// Sprites past the end of the sheet aren't drawn.
+    pub fn spr(&mut self, spr: usize, x: impl Into<i32>, y: impl Into<i32>) {
+        self.draw_data
+            .spr_from(&self.resources.sprite_sheet, spr, x.into(), y.into());
+    }
+
+    /// Draws the 16x16 block of sprites whose top left sprite is `spr`, see [`SpriteSheet::metasprite`].
+    /// Like Pico8's `spr(n, x, y, 2, 2)`, except at the edges of the sheet: unlike Pico8,
+    /// nothing is drawn for sprites in its last column or row (their blocks don't wrap around).
+    ///
+    /// Not in Pico8.
+    pub fn spr16(&mut self, spr: usize, x: impl Into<i32>, y: impl Into<i32>) {
+        let sprite_sheet = &self.resources.sprite_sheet;
+        if sprite_sheet.metasprite(spr).is_none() {
+            return;
+        }
+        let (sx, sy) = sprite_sheet.sprite_position(spr);
+
+        self.draw_data
+            .sspr(sprite_sheet, (sx, sy, 16, 16), (x.into(), y.into(), 16, 16));
+    }
+
+    /// Like [`Pico8::spr`], from another sprite sheet than the game's,
+    /// for UI skins or extra tilesets. The palette and transparency apply the same way.
+    ///
+    /// Not in Pico8.
+    pub fn spr_from(
+        &mut self,
+        sprite_sheet: &SpriteSheet,
+        spr: usize,
+        x: impl Into<i32>,
+        y: impl Into<i32>,
+    ) {
+        self.draw_data
+            .spr_from(sprite_sheet, spr, x.into(), y.into());
+    }
+
+    /// Draws the `sw` x `sh` pixels at (`sx`, `sy`) in the sprite sheet,
+    /// stretched to `dw` x `dh` at (`dx`, `dy`) on the screen.
+    /// Pixels outside the sprite sheet are black.
+    #[allow(clippy::too_many_arguments)]
+    pub fn sspr(
+        &mut self,
+        sx: impl Into<i32>,
+        sy: impl Into<i32>,
+        sw: impl Into<i32>,
+        sh: impl Into<i32>,
+        dx: impl Into<i32>,
+        dy: impl Into<i32>,
+        dw: impl Into<i32>,
+        dh: impl Into<i32>,
+    ) {
+        self.draw_data.sspr(
             &self.resources.sprite_sheet,
+            (sx.into(), sy.into(), sw.into(), sh.into()),
+            (dx.into(), dy.into(), dw.into(), dh.into()),
         );
     }
 
-    pub fn spr(&mut self, spr: usize, x: i32, y: i32) {
-        let spr = self.resources.sprite_sheet.get_sprite(spr);
+    /// Like [`Pico8::sspr`], from another sprite sheet, see [`Pico8::spr_from`].
+    ///
+    /// Not in Pico8.
+    #[allow(clippy::too_many_arguments)]
+    pub fn sspr_from(
+        &mut self,
+        sprite_sheet: &SpriteSheet,
+        sx: impl Into<i32>,
+        sy: impl Into<i32>,
+        sw: impl Into<i32>,
+        sh: impl Into<i32>,
+        dx: impl Into<i32>,
+        dy: impl Into<i32>,
+        dw: impl Into<i32>,
+        dh: impl Into<i32>,
+    ) {
+        self.draw_data.sspr(
+            sprite_sheet,
+            (sx.into(), sy.into(), sw.into(), sh.into()),
+            (dx.into(), dy.into(), dw.into(), dh.into()),
+        );
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn spr_(
+        &mut self,
+        spr: usize,
+        x: impl Into<i32>,
+        y: impl Into<i32>,
+        w: f32,
+        h: f32,
+        flip_x: bool,
+        flip_y: bool,
+    ) {
+        let Some(spr) = self.resources.sprite_sheet.try_get_sprite(spr) else {
+            return;
+        };
 
-        self.draw_data.spr(spr, x, y);
+        self.draw_data
+            .spr_(spr, x.into(), y.into(), w, h, flip_x, flip_y);
     }
 
-    pub fn spr_(&mut self, spr: usize, x: i32, y: i32, w: f32, h: f32, flip_x: bool, flip_y: bool) {
-        let spr = self.resources.sprite_sheet.get_sprite(spr);
+    /// Color of the pixel at (`x`, `y`) on the screen at the end of the previous frame,
+    /// for motion blur and feedback effects. Ignores the camera, black outside of the screen.
+    ///
+    /// Not in Pico8.
+    pub fn pget_prev(&self, x: impl Into<i32>, y: impl Into<i32>) -> Color {
+        self.draw_data
+            .previous_pixel(x.into(), y.into())
+            .unwrap_or(Color::BLACK)
+    }
 
-        self.draw_data.spr_(spr, x, y, w, h, flip_x, flip_y);
+    /// Copies the `w` x `h` pixels at (`sx`, `sy`) on the screen at the end of the previous frame
+    /// to (`dx`, `dy`), for trails or redrawing the last frame shaken.
+    /// The camera and clip region apply to where it's copied, the palette and transparency don't.
+    ///
+    /// Not in Pico8.
+    pub fn blit_prev(
+        &mut self,
+        sx: impl Into<i32>,
+        sy: impl Into<i32>,
+        w: impl Into<i32>,
+        h: impl Into<i32>,
+        dx: impl Into<i32>,
+        dy: impl Into<i32>,
+    ) {
+        self.draw_data.blit_previous(
+            (sx.into(), sy.into()),
+            (w.into(), h.into()),
+            (dx.into(), dy.into()),
+        );
     }
 
     // TODO: Test
-    pub fn sset(&mut self, x: i32, y: i32, color: Color) {
-        if let (Ok(x), Ok(y)) = (x.try_into(), y.try_into()) {
-            self.resources.sprite_sheet.set(x, y, color);
-        }
+    pub fn sset(&mut self, x: impl Into<i32>, y: impl Into<i32>, color: impl Into<Color>) {
+        self.resources_mut().sset(x.into(), y.into(), color);
     }
 
     pub fn fillp(&mut self) {
         todo!()
     }
 
-    pub fn circ(&mut self, x: i32, y: i32, r: i32, color: Color) {
-        self.draw_data.circ(x, y, r, color);
+    pub fn circ(
+        &mut self,
+        x: impl Into<i32>,
+        y: impl Into<i32>,
+        r: impl Into<i32>,
+        color: impl Into<Color>,
+    ) {
+        self.draw_data
+            .circ(x.into(), y.into(), r.into(), color.into());
+    }
+    pub fn circfill(
+        &mut self,
+        x: impl Into<i32>,
+        y: impl Into<i32>,
+        r: impl Into<i32>,
+        color: impl Into<Color>,
+    ) {
+        self.draw_data
+            .circfill(x.into(), y.into(), r.into(), color.into());
+    }
+
+    pub fn rect(
+        &mut self,
+        x0: impl Into<i32>,
+        y0: impl Into<i32>,
+        x1: impl Into<i32>,
+        y1: impl Into<i32>,
+        color: impl Into<Color>,
+    ) {
+        self.draw_data
+            .rect(x0.into(), y0.into(), x1.into(), y1.into(), color.into());
+    }
+
+    pub fn rectfill(
+        &mut self,
+        x0: impl Into<i32>,
+        y0: impl Into<i32>,
+        x1: impl Into<i32>,
+        y1: impl Into<i32>,
+        color: impl Into<Color>,
+    ) {
+        self.draw_data
+            .rectfill(x0.into(), y0.into(), x1.into(), y1.into(), color.into());
+    }
+
+    /// See [`DrawData::rectfill_extended`].
+    #[cfg(feature = "editor")]
+    pub(crate) fn rectfill_extended(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, extended: u8) {
+        self.draw_data.rectfill_extended(x0, y0, x1, y1, extended);
+    }
+
+    pub fn line(
+        &mut self,
+        x0: impl Into<i32>,
+        y0: impl Into<i32>,
+        x1: impl Into<i32>,
+        y1: impl Into<i32>,
+        color: impl Into<Color>,
+    ) {
+        self.draw_data
+            .line(x0.into(), y0.into(), x1.into(), y1.into(), color.into());
+    }
+
+    /// Prints `text` with its top left corner at (`x`, `y`).
+    ///
+    /// Understands some of Pico8's control codes:
+    ///
+    /// - `\n` starts a new line.
+    /// - `\t` moves to the next tab stop (every 4 characters).
+    /// - `\x0c` followed by a hex digit changes the color (`\f` in Pico8), e.g. `"\x0c8RED"`.
+    /// - `\x01` followed by a count (`0-9a-z`, up to 35) and a character repeats the character
+    ///   (`\*` in Pico8), e.g. `"\x01a-"` prints 10 dashes.
+    ///
+    /// Other control characters, or codes missing their parameters, are printed as a box.
+    /// Use [`Pico8::print_raw`] to print the text as it is.
+    ///
+    /// Uses the built-in font, unless another one was set with [`Pico8::set_font`].
+    /// Pico8's button icons and symbols (see [`crate::glyphs`]) are printed in any font, twice as wide.
+    pub fn print(
+        &mut self,
+        text: &str,
+        x: impl Into<i32>,
+        y: impl Into<i32>,
+        color: impl Into<Color>,
+    ) {
+        self.draw_data.print(text, x.into(), y.into(), color.into());
+    }
+
+    /// Like [`Pico8::print`], but without control codes: every character is drawn, on a single line.
+    ///
+    /// Not in Pico8.
+    pub fn print_raw(
+        &mut self,
+        text: &str,
+        x: impl Into<i32>,
+        y: impl Into<i32>,
+        color: impl Into<Color>,
+    ) {
+        self.draw_data
+            .print_raw(text, x.into(), y.into(), color.into());
+    }
+
+    /// Like [`Pico8::print`], with a drop shadow in `shadow_color` one pixel down and to the right.
+    /// Color codes in `text` don't apply to the shadow.
+    ///
+    /// Not in Pico8.
+    pub fn print_shadowed(
+        &mut self,
+        text: &str,
+        x: impl Into<i32>,
+        y: impl Into<i32>,
+        color: impl Into<Color>,
+        shadow_color: impl Into<Color>,
+    ) {
+        self.draw_data
+            .print_shadowed(text, x.into(), y.into(), color.into(), shadow_color.into());
     }
-    pub fn circfill(&mut self, x: i32, y: i32, r: i32, color: Color) {
-        self.draw_data.circfill(x, y, r, color);
+
+    /// Like [`Pico8::print`], surrounded by a one pixel outline in `outline_color`.
+    /// Color codes in `text` don't apply to the outline.
+    ///
+    /// Not in Pico8.
+    pub fn print_outlined(
+        &mut self,
+        text: &str,
+        x: impl Into<i32>,
+        y: impl Into<i32>,
+        color: impl Into<Color>,
+        outline_color: impl Into<Color>,
+    ) {
+        self.draw_data
+            .print_outlined(text, x.into(), y.into(), color.into(), outline_color.into());
     }
 
-    pub fn rect(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
-        self.draw_data.rect(x0, y0, x1, y1, color);
+    /// Like [`Pico8::print`], but with `font` instead of the current one.
+    ///
+    /// Not in Pico8.
+    pub fn print_with(
+        &mut self,
+        font: &Font,
+        text: &str,
+        x: impl Into<i32>,
+        y: impl Into<i32>,
+        color: impl Into<Color>,
+    ) {
+        self.draw_data
+            .print_with(font, text, x.into(), y.into(), color.into());
     }
 
-    pub fn rectfill(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
-        self.draw_data.rectfill(x0, y0, x1, y1, color);
+    /// Sets the font used by [`Pico8::print`], `None` goes back to the built-in one.
+    /// The editor keeps using the built-in font.
+    ///
+    /// Not in Pico8.
+    pub fn set_font(&mut self, font: Option<Font>) {
+        self.draw_data.set_font(font);
     }
 
-    pub fn line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
-        self.draw_data.line(x0, y0, x1, y1, color);
+    /// Size in pixels that `text` takes when printed with the current font.
+    /// Each `\n` starts a new line.
+    pub fn measure_text(&self, text: &str) -> (i32, i32) {
+        self.draw_data.font().measure(text)
     }
 
-    pub fn print(&mut self, text: &str, x: i32, y: i32, color: Color) {
-        self.draw_data.print(text, x, y, color);
+    /// Makes a font out of `glyph_width` x `glyph_height` glyphs drawn in the sprite sheet.
+    ///
+    /// Glyphs start at `origin` and go left to right, continuing on the next row
+    /// (back at `origin.0`) when there's no room left. The first one is `first_char`'s,
+    /// the next one `first_char + 1`'s and so on. Any color but 0 is part of a glyph.
+    ///
+    /// Later changes to the sprite sheet don't affect the font.
+    ///
+    /// Not in Pico8.
+    pub fn font_from_sprite_sheet(
+        &self,
+        origin: (i32, i32),
+        glyph_width: i32,
+        glyph_height: i32,
+        first_char: char,
+    ) -> Font {
+        Font::from_sprite_sheet(
+            &self.resources.sprite_sheet,
+            origin,
+            glyph_width,
+            glyph_height,
+            first_char,
+        )
     }
 
     // audio
-    pub fn sfx(&mut self, _sound_id: u8) {
-        todo!()
+    /// Plays sound effect `n` (0 to 63) on `channel` (0 to 3), or on a free channel if `channel` is -1.
+    ///
+    /// A negative `n` stops the sound playing on `channel`, or on every channel if `channel` is -1.
+    ///
+    /// Only makes sound with the `audio` feature enabled.
+    pub fn sfx(&mut self, n: i32, channel: i32) {
+        let channel = usize::try_from(channel).ok();
+
+        let command = match usize::try_from(n) {
+            Ok(n) => match self.resources.sound_effects.get(n) {
+                Some(sfx) => Command::Play {
+                    sfx: sfx.clone(),
+                    channel,
+                },
+                None => return,
+            },
+            Err(_) => Command::Stop { channel },
+        };
+
+        self.resources.audio.send(command);
     }
-    pub fn music(&mut self, _music_id: u8) {
-        todo!()
+
+    /// Plays the music starting at pattern `n` (0 to 63), fading in over `fade_ms` milliseconds.
+    /// Sound effects played on channel -1 won't use the channels in `channel_mask` (one bit per channel),
+    /// otherwise they play over the music.
+    ///
+    /// A negative `n` stops the music, fading out over `fade_ms` milliseconds.
+    ///
+    /// Only makes sound with the `audio` feature enabled.
+    pub fn music(&mut self, n: i32, fade_ms: i32, channel_mask: u8) {
+        let fade_ms = fade_ms.max(0) as u32;
+
+        let command = match usize::try_from(n) {
+            Ok(pattern) if pattern < Music::COUNT => Command::PlayMusic {
+                music: self.resources.music.clone(),
+                sound_effects: self.resources.sound_effects.clone(),
+                pattern,
+                fade_ms,
+                channel_mask,
+            },
+            Ok(_) => return,
+            Err(_) => Command::StopMusic { fade_ms },
+        };
+
+        self.resources.audio.send(command);
+    }
+
+    /// Sets the overall volume, from 0.0 (silent) to 1.0 (the default).
+    /// Saved in the assets directory, and restored the next time the game runs.
+    ///
+    /// Not in Pico8.
+    pub fn set_volume(&mut self, volume: f32) {
+        let current = self.resources.audio.volume();
+
+        self.resources.set_volume(Volume {
+            master: Volume::clamp(volume),
+            ..current
+        });
+    }
+
+    /// Like [`Pico8::set_volume`], but only for sound effects. Applied on top of the overall volume.
+    pub fn set_sfx_volume(&mut self, volume: f32) {
+        let current = self.resources.audio.volume();
+
+        self.resources.set_volume(Volume {
+            sfx: Volume::clamp(volume),
+            ..current
+        });
+    }
+
+    /// Like [`Pico8::set_volume`], but only for music. Applied on top of the overall volume.
+    pub fn set_music_volume(&mut self, volume: f32) {
+        let current = self.resources.audio.volume();
+
+        self.resources.set_volume(Volume {
+            music: Volume::clamp(volume),
+            ..current
+        });
+    }
+
+    /// System status. Only the music and mouse queries are supported for now:
+    ///
+    /// - 24: The music pattern being played, -1 if there's no music.
+    /// - 26: Ticks (1/120ths of a second) played of the current pattern.
+    /// - 32, 33: The mouse's x and y (see [`Pico8::mouse`]).
+    /// - 34: The mouse buttons held down: 1 for left, 2 for right and 4 for middle, added up.
+    /// - 200: Updates skipped since the game started, when it fell too far behind
+    ///   to catch up (not in Pico8).
+    ///
+    /// Unlike Pico8, the mouse queries don't need the devkit mode turned on with `poke`.
+    ///
+    /// Every other query returns 0.
+    pub fn stat(&self, n: i32) -> i32 {
+        match n {
+            24 => self.resources.audio.music_pattern(),
+            26 => self.resources.audio.music_ticks(),
+            200 => self.state.dropped_updates() as i32,
+            32 => self.mouse().0,
+            33 => self.mouse().1,
+            34 => [MouseButton::Left, MouseButton::Right, MouseButton::Middle]
+                .into_iter()
+                .enumerate()
+                .filter(|&(_, button)| self.mouse_pressed(button))
+                .map(|(bit, _)| 1 << bit)
+                .sum(),
+            _ => 0,
+        }
     }
 
     // Non-standard stuf
-    pub fn append_camera(&mut self, x: i32, y: i32) {
-        self.draw_data.append_camera(x, y);
+    pub fn append_camera(&mut self, x: impl Into<i32>, y: impl Into<i32>) {
+        self.draw_data.append_camera(x.into(), y.into());
+    }
+
+    /// Moves the camera by (`dx`, `dy`) from where it is, until the next [`Pico8::camera_pop`].
+    /// Pushes add up, so `camera_push(-x, -y)` lets nested code draw relative to (`x`, `y`).
+    /// Clip regions stay where they are on the screen.
+    ///
+    /// Not in Pico8.
+    pub fn camera_push(&mut self, dx: impl Into<i32>, dy: impl Into<i32>) {
+        self.draw_data.camera_push(dx.into(), dy.into());
     }
 
+    /// Puts the camera back where it was before the last [`Pico8::camera_push`],
+    /// even if [`Pico8::camera`] was called since. Does nothing if nothing was pushed.
+    ///
+    /// Not in Pico8.
+    pub fn camera_pop(&mut self) {
+        self.draw_data.camera_pop();
+    }
+
+    /// Runs `f` with the camera moved by (`dx`, `dy`) (see [`Pico8::camera_push`]).
+    ///
+    /// Not in Pico8.
+    pub fn with_camera<R>(
+        &mut self,
+        dx: impl Into<i32>,
+        dy: impl Into<i32>,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        self.camera_push(dx, dy);
+        let result = f(self);
+        self.camera_pop();
+
+        result
+    }
+
+    /// Where the mouse is on the screen, as of the start of this frame.
+    /// Always in `0..=127`, even when the cursor is out of the window.
     pub fn mouse(&self) -> (i32, i32) {
-        self.state.mouse()
+        let (x, y) = self.state.mouse();
+
+        (x.clamp(0, 127), y.clamp(0, 127))
+    }
+
+    /// Whether `button` is held down, like [`Pico8::btn`].
+    ///
+    /// Not in Pico8.
+    pub fn mouse_pressed(&self, button: MouseButton) -> bool {
+        self.state.mouse_button(button).btn()
+    }
+
+    /// Whether `button` was pressed this frame, like [`Pico8::btnp`].
+    ///
+    /// Not in Pico8.
+    pub fn mouse_btnp(&self, button: MouseButton) -> bool {
+        self.state.mouse_button(button).btnp()
+    }
+
+    /// Seconds since the app started, advances by 1/30 every frame.
+    ///
+    /// <https://pico-8.fandom.com/wiki/Time>
+    pub fn time(&self) -> f32 {
+        self.state.frames() as f32 / 30.0
+    }
+
+    /// How many updates happened before the current one, 0 in the first `update`.
+    ///
+    /// Counted by the fixed timestep loop, so it's the same on every run of the game,
+    /// however fast the machine is. It doesn't advance while the game is paused.
+    ///
+    /// Not in Pico8.
+    pub fn frame_count(&self) -> u64 {
+        self.state.frames()
+    }
+
+    /// Like [`Pico8::time`], without losing precision in long sessions.
+    ///
+    /// Simulation time: it's [`Pico8::frame_count`] at 30 frames per second,
+    /// not the wall time since the game started.
+    ///
+    /// Not in Pico8.
+    pub fn elapsed_seconds(&self) -> f64 {
+        self.state.frames() as f64 / 30.0
+    }
+
+    /// Random number in `0.0..limit`, same as the free standing [`rnd`].
+    pub fn rnd(&self, limit: f32) -> f32 {
+        rnd(limit)
     }
 
     pub fn set_title(&mut self, new_title: String) {
         self.new_title = Some(new_title);
     }
+
+    /// Sets the mouse cursor drawn on top of the game.
+    /// There's no cursor until a game sets one.
+    ///
+    /// Use [`CursorKind::Sprite`] for a cursor from the game's own sprite sheet.
+    pub fn set_cursor(&mut self, cursor: CursorKind) {
+        self.cursor = cursor;
+    }
+
+    pub fn hide_cursor(&mut self) {
+        self.set_cursor(CursorKind::Hidden);
+    }
+
+    /// Whether the cursor is drawn as a sprite (the default), left to the operating system,
+    /// or not shown at all. Applies to the editor too.
+    ///
+    /// Not in Pico8.
+    pub fn set_cursor_mode(&mut self, mode: CursorMode) {
+        self.cursor_mode = mode;
+    }
+
+    pub fn cursor_mode(&self) -> CursorMode {
+        self.cursor_mode
+    }
+
+    /// Color of the window around the screen, with [`ScalingMode::Integer`].
+    /// Black by default, can be changed every frame.
+    ///
+    /// Not in Pico8.
+    pub fn set_border_color(&mut self, color: impl Into<Color>) {
+        self.border_color = color.into();
+    }
+
+    pub fn border_color(&self) -> Color {
+        self.border_color
+    }
+
+    /// Shows the screen through a CRT filter, or as crisp pixels with `None` (the default).
+    /// Players can also toggle the default filter with Ctrl+T.
+    ///
+    /// Only changes how the screen looks in the window: the screen's pixels stay the same.
+    ///
+    /// Not in Pico8.
+    pub fn set_crt_filter(&mut self, filter: Option<CrtFilter>) {
+        self.crt_filter = filter;
+    }
+
+    pub fn crt_filter(&self) -> Option<CrtFilter> {
+        self.crt_filter
+    }
+
+    /// How the screen is scaled up to the window, stretched to fill it by default.
+    /// The mouse position follows the screen either way.
+    ///
+    /// Not in Pico8.
+    pub fn set_scaling_mode(&mut self, mode: ScalingMode) {
+        self.scaling_mode = mode;
+    }
+
+    pub fn scaling_mode(&self) -> ScalingMode {
+        self.scaling_mode
+    }
+
+    /// Keeps calling [`App::update`](crate::App::update) while the editor is open,
+    /// instead of freezing the game until it's closed (the default).
+    ///
+    /// Either way the game gets no input in the editor: every button reads as released,
+    /// and keys still held when going back to the game are held, not just pressed.
+    ///
+    /// Not in Pico8.
+    pub fn set_updates_in_editor(&mut self, updates: bool) {
+        self.updates_in_editor = updates;
+    }
+
+    pub fn updates_in_editor(&self) -> bool {
+        self.updates_in_editor
+    }
+
+    /// The game's sprite sheet, flags and map, to read them directly
+    /// (for pathfinding, or pixel perfect collisions).
+    ///
+    /// These are the resources the editor shows and saves, see [`Pico8::resources_mut`]
+    /// for how the game's changes and the editor's go together.
+    ///
+    /// Not in Pico8.
+    pub fn resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    /// Like [`Pico8::resources`], to change them.
+    ///
+    /// The game and the editor share the same resources, nothing is copied between them.
+    /// In a frame, the editor handles its input first, then the game updates (if it
+    /// [updates in the editor](Pico8::set_updates_in_editor)), seeing the editor's changes
+    /// right away. Whichever writes a sprite, flag or map cell last wins.
+    /// The game's changes aren't in the editor's undo history: undoing an edit puts back
+    /// what was there before it, over anything the game wrote since.
+    /// Saving in the editor saves the game's changes too.
+    ///
+    /// For [`App::update`](crate::App::update): drawing should leave the resources as they are.
+    /// Calling this, or a function changing them like [`Pico8::mset`], [`Pico8::sset`]
+    /// or [`Pico8::fset`], from [`App::draw`](crate::App::draw) panics in debug builds.
+    ///
+    /// Not in Pico8.
+    pub fn resources_mut(&mut self) -> &mut Resources {
+        debug_assert!(
+            !self.drawing,
+            "App::draw can't change the resources, change them in App::update"
+        );
+
+        &mut self.resources
+    }
+
+    /// Adds a cart that [`Pico8::load_cart`] can switch to, called `name`,
+    /// with its assets in `assets_path`.
+    ///
+    /// Not in Pico8, where carts are files.
+    pub fn register_cart<A: App + 'static>(&mut self, name: &str, assets_path: &str) {
+        self.carts.register(name, assets_path, cart::init::<A>);
+    }
+
+    /// Switches to the cart registered as `name` after the current update:
+    /// its assets are loaded, the draw state is reset, and it starts in the same window.
+    /// The sound stops, the volume and input carry over.
+    ///
+    /// The current cart becomes the new one's breadcrumb, see [`Pico8::load_breadcrumb`].
+    /// If the assets can't be loaded, the current cart keeps running.
+    ///
+    /// Like Pico8's `load`, with registered carts instead of files.
+    pub fn load_cart(&mut self, name: &str) -> Result<(), Error> {
+        if self.carts.request(name) {
+            Ok(())
+        } else {
+            Err(Error::UnknownCart(name.to_owned()))
+        }
+    }
+
+    /// Goes back to the cart that loaded this one with [`Pico8::load_cart`],
+    /// after the current update. Returns `false` if there's none.
+    ///
+    /// Not in Pico8.
+    pub fn load_breadcrumb(&mut self) -> bool {
+        self.carts.request_breadcrumb()
+    }
 }
 
 // Utility pub(crate) methods
 impl Pico8 {
-    // TODO: Remove this `allow` when we use it in the editor.
-    #[allow(dead_code)]
-    pub(crate) fn spr_from(&mut self, sprite_sheet: &SpriteSheet, spr: usize, x: i32, y: i32) {
-        let spr = sprite_sheet.get_sprite(spr);
+    pub(crate) fn raw_spr(&mut self, sprite: &Sprite, x: i32, y: i32) {
+        self.draw_data.raw_spr(sprite, x, y);
+    }
 
-        self.draw_data.spr(spr, x, y);
+    /// Everything a cart sets up for itself goes back to how it is when the app starts.
+    pub(crate) fn reset_for_new_cart(&mut self) {
+        self.draw_data = DrawData::new();
+        self.cursor = CursorKind::Hidden;
+        self.cursor_mode = CursorMode::Sprite;
+        self.border_color = Color::BLACK;
+        self.crt_filter = None;
+        self.scaling_mode = ScalingMode::Stretch;
+        self.updates_in_editor = false;
+        self.resources.audio.send(Command::Stop { channel: None });
+        self.resources.audio.send(Command::StopMusic { fade_ms: 0 });
     }
 
-    pub(crate) fn raw_spr(&mut self, sprite: &Sprite, x: i32, y: i32) {
-        self.draw_data.raw_spr(sprite, x, y);
+    /// Runs `App::draw`, so that [`Pico8::resources_mut`] knows it's drawing.
+    pub(crate) fn draw_app(&mut self, draw: impl FnOnce(&mut Self)) {
+        self.drawing = true;
+        draw(self);
+        self.drawing = false;
     }
 
     pub(crate) fn take_new_title(&mut self) -> Option<String> {
         self.new_title.take()
     }
+
+    /// Draws the cursor set by the game (if any), ignoring the camera.
+    /// Leaves the game's camera and transparency as they were.
+    pub(crate) fn draw_cursor(&mut self) {
+        self.with_saved_state(|pico8| {
+            pico8.draw_data.camera(0, 0);
+
+            let (kind, position) = (pico8.cursor, pico8.mouse());
+            cursor::draw_cursor(pico8, kind, position);
+        });
+    }
 }
 
 // Top level functions that pico8 provides that don't modify the global state.
@@ -211,13 +1037,20 @@ pub fn sin(f: f32) -> f32 {
 }
 
 /// <https://pico-8.fandom.com/wiki/Rnd>
+///
+/// There are no numbers in an empty range, so limits of `0.0` or less (and NaN) give `0.0`.
 pub fn rnd(limit: f32) -> f32 {
+    if limit.is_nan() || limit <= 0.0 {
+        return 0.0;
+    }
+
     rand::thread_rng().gen_range(0.0..limit)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{rnd, sin};
+    use super::*;
+    use crate::runtime::input::Keys;
 
     macro_rules! assert_delta {
         ($x:expr, $y:expr, $d:expr) => {
@@ -240,6 +1073,188 @@ mod tests {
         assert_delta!(sin(1.0), 0.0, 0.00001);
     }
 
+    #[test]
+    fn the_mouse_stays_on_screen() {
+        let mut pico8 = Pico8::new(DrawData::new(), State::new(), Resources::empty());
+        pico8.state.on_mouse_move(-5, 200);
+        let mut keys = Keys::new();
+        keys.mouse_right.set(true);
+        keys.mouse_middle.set(true);
+        pico8.state.update_keys(&mut keys);
+
+        assert_eq!(pico8.mouse(), (0, 127));
+        assert_eq!(
+            (pico8.stat(32), pico8.stat(33), pico8.stat(34)),
+            (0, 127, 6)
+        );
+        assert!(pico8.mouse_btnp(MouseButton::Right));
+        assert!(!pico8.mouse_pressed(MouseButton::Left));
+
+        pico8.state.update_keys(&mut keys);
+        assert!(!pico8.mouse_btnp(MouseButton::Right));
+        assert!(pico8.mouse_pressed(MouseButton::Right));
+    }
+
+    #[test]
+    fn games_read_the_resources_they_change() {
+        let mut pico8 = Pico8::new(DrawData::new(), State::new(), Resources::empty());
+        pico8.resources_mut().mset(3, 4, 5);
+        pico8.resources_mut().fset(5, 2, true);
+        pico8.resources_mut().sset(5 * 8 + 1, 2, Color::RED);
+
+        let resources = pico8.resources();
+        let sprite = resources.map().mget(3, 4);
+        assert!(resources.sprite_flags().fget_n(sprite as usize, 2));
+        assert_eq!(
+            resources
+                .sprite_sheet()
+                .get_sprite(sprite as usize)
+                .pget(1, 2),
+            Color::RED
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "App::draw can't change the resources")]
+    fn drawing_cant_change_the_resources() {
+        let mut pico8 = Pico8::new(DrawData::new(), State::new(), Resources::empty());
+
+        pico8.draw_app(|pico8| pico8.resources_mut().mset(3, 4, 5));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "App::draw can't change the resources")]
+    fn drawing_cant_set_map_cells() {
+        let mut pico8 = Pico8::new(DrawData::new(), State::new(), Resources::empty());
+
+        pico8.draw_app(|pico8| pico8.mset(3, 4, 5));
+    }
+
+    #[test]
+    fn saved_states_restore_the_clip_region() {
+        let mut pico8 = Pico8::new(DrawData::new(), State::new(), Resources::empty());
+        pico8.clip(0, 0, 64, 128);
+
+        pico8.with_saved_state(|pico8| {
+            pico8.clip(0, 0, 8, 8);
+            pico8.cls(Color::RED);
+        });
+        assert_eq!(pico8.draw_data.pixel(8, 0), Some(Color::BLACK));
+
+        pico8.cls(Color::BLUE);
+        assert_eq!(pico8.draw_data.pixel(63, 100), Some(Color::BLUE));
+        assert_eq!(pico8.draw_data.pixel(64, 0), Some(Color::BLACK));
+    }
+
+    #[test]
+    fn other_sprite_sheets_draw_like_the_games() {
+        let mut resources = Resources::empty();
+        resources.sset(8, 0, Color::RED);
+        resources.sset(9, 0, Color::BLUE);
+        let mut sheet = SpriteSheet::new();
+        sheet.get_sprite_mut(1).pset(0, 0, Color::RED);
+        sheet.get_sprite_mut(1).pset(1, 0, Color::BLUE);
+        let mut pico8 = Pico8::new(DrawData::new(), State::new(), resources);
+        pico8.cls(Color::WHITE);
+        pico8.pal(Color::RED, Color::GREEN);
+
+        pico8.spr(1, 0, 0);
+        pico8.spr_from(&sheet, 1, 0, 8);
+        for x in 0..3 {
+            assert_eq!(pico8.draw_data.pixel(x, 0), pico8.draw_data.pixel(x, 8));
+        }
+        assert_eq!(pico8.draw_data.pixel(0, 8), Some(Color::GREEN));
+        // Black is still transparent.
+        assert_eq!(pico8.draw_data.pixel(2, 8), Some(Color::WHITE));
+
+        // Twice as big.
+        pico8.sspr(8, 0, 2, 1, 0, 16, 4, 2);
+        pico8.sspr_from(&sheet, 8, 0, 2, 1, 0, 24, 4, 2);
+        for (x, y) in (0..5).flat_map(|x| (0..2).map(move |y| (x, y))) {
+            assert_eq!(
+                pico8.draw_data.pixel(x, 16 + y),
+                pico8.draw_data.pixel(x, 24 + y)
+            );
+        }
+        let row: Vec<_> = (0..5).map(|x| pico8.draw_data.pixel(x, 25)).collect();
+        assert_eq!(
+            row,
+            [
+                Color::GREEN,
+                Color::GREEN,
+                Color::BLUE,
+                Color::BLUE,
+                Color::WHITE
+            ]
+            .map(Some)
+        );
+    }
+
+    #[test]
+    fn blit_prev_copies_through_the_camera_and_clip() {
+        let mut pico8 = Pico8::new(DrawData::new(), State::new(), Resources::empty());
+        pico8.rectfill(0, 0, 3, 3, Color::RED);
+        pico8.pset(4, 4, Color::GREEN);
+        pico8.draw_data.keep_frame();
+        pico8.cls(Color::BLACK);
+
+        assert_eq!(pico8.pget_prev(4, 4), Color::GREEN);
+        assert_eq!(pico8.pget_prev(-1, 0), Color::BLACK);
+
+        // Shaken one pixel right, and the first row left out.
+        pico8.camera(-1, 0);
+        pico8.clip(0, 1, 128, 127);
+        pico8.blit_prev(0, 0, 5, 5, 0, 0);
+        assert_eq!(pico8.draw_data.pixel(1, 0), Some(Color::BLACK));
+        assert_eq!(pico8.draw_data.pixel(1, 1), Some(Color::RED));
+        assert_eq!(pico8.draw_data.pixel(0, 1), Some(Color::BLACK));
+        assert_eq!(pico8.draw_data.pixel(5, 4), Some(Color::GREEN));
+    }
+
+    #[test]
+    fn games_can_install_bigger_maps() {
+        let mut map = Map::with_size(256, 64);
+        map.mset(200, 60, 7);
+        let mut pico8 = Pico8::new(DrawData::new(), State::new(), Resources::empty());
+        pico8.set_map(map);
+
+        assert_eq!(pico8.map_size(), (256, 64));
+        assert_eq!(pico8.mget(200, 60), 7);
+        pico8.mset(255, 63, 9);
+        assert_eq!(pico8.mget(255, 63), 9);
+    }
+
+    #[test]
+    fn spr16_draws_a_2x2_block() {
+        let mut resources = Resources::empty();
+        // A pixel in the top left corner of sprites 1, 2, 17 and 18.
+        for (sprite, color) in [(1, 8), (2, 9), (17, 10), (18, 11)] {
+            let (x, y) = resources.sprite_sheet.sprite_position(sprite);
+            resources.sset(x, y, color);
+        }
+        // And in sprite 15's, the last column, and sprite 241's, the last row.
+        resources.sset(120, 0, 12);
+        let (x, y) = resources.sprite_sheet.sprite_position(241);
+        resources.sset(x, y, 12);
+        let mut pico8 = Pico8::new(DrawData::new(), State::new(), resources);
+        pico8.cls(Color::WHITE);
+
+        pico8.spr16(1, 10, 20);
+        let corners = [(10, 20), (18, 20), (10, 28), (18, 28)];
+        let colors = corners.map(|(x, y)| pico8.draw_data.pixel(x, y));
+        assert_eq!(colors, [8, 9, 10, 11].map(|color| Some(Color::from(color))));
+
+        // Those don't have a whole block, nothing is drawn.
+        pico8.cls(Color::WHITE);
+        pico8.spr16(15, 0, 0);
+        pico8.spr16(241, 0, 20);
+        assert_eq!(pico8.draw_data.pixel(0, 0), Some(Color::WHITE));
+        assert_eq!(pico8.draw_data.pixel(0, 20), Some(Color::WHITE));
+        assert_eq!(pico8.draw_data.pixel(0, 28), Some(Color::WHITE));
+    }
+
     #[test]
     fn rnd_works() {
         for _ in 0..100 {
@@ -248,4 +1263,11 @@ mod tests {
             assert!(0.0 < random_value && random_value < 50.0);
         }
     }
+
+    #[test]
+    fn rnd_of_nothing_is_zero() {
+        assert_eq!(rnd(0.0), 0.0);
+        assert_eq!(rnd(-3.0), 0.0);
+        assert_eq!(rnd(f32::NAN), 0.0);
+    }
 }
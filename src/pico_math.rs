@@ -0,0 +1,375 @@
+//! Pico8's 16.16 fixed-point numbers, for ports of carts that rely on their quirks:
+//! wrapping around at 32768, rounding down to 1/65536, `%` never being negative...
+//! Needs the `pico_math` feature.
+//!
+//! The drawing functions take anything that converts into an `i32`,
+//! so [`Num`] coordinates work directly (rounded down, like in Pico8).
+//!
+//! ```no_run
+//! # use runty8::pico_math::{self, Num};
+//! # use runty8::Pico8;
+//! # fn draw(pico8: &mut Pico8, t: Num) {
+//! let x = Num::from(64) + pico_math::cos(t) * Num::from(20);
+//! pico8.circfill(x, 64, 3, 8);
+//! # }
+//! ```
+use std::fmt::{self, Display};
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Rem, RemAssign, Sub, SubAssign,
+};
+
+/// A Pico8 number: 16 bits of integer part and 16 of fraction, from -32768 to 32767.99998.
+///
+/// Every operation wraps around like in Pico8, `32767 + 1` is `-32768`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Num(i32);
+
+impl Num {
+    const ONE: i32 = 1 << 16;
+
+    /// The number with these bits, `0x0001_8000` is 1.5.
+    pub const fn from_bits(bits: i32) -> Self {
+        Self(bits)
+    }
+
+    pub const fn to_bits(self) -> i32 {
+        self.0
+    }
+
+    pub fn to_f32(self) -> f32 {
+        self.to_f64() as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        f64::from(self.0) / f64::from(Self::ONE)
+    }
+
+    // Rounded to the nearest 1/65536, and wrapped around like the integer conversion.
+    fn from_f64(value: f64) -> Self {
+        Self((value * f64::from(Self::ONE)).round() as i64 as i32)
+    }
+}
+
+/// Wraps around: 32768 becomes -32768.
+impl From<i32> for Num {
+    fn from(value: i32) -> Self {
+        Self(value.wrapping_shl(16))
+    }
+}
+
+/// Rounded to the nearest 1/65536, like Pico8 reads number literals.
+impl From<f32> for Num {
+    fn from(value: f32) -> Self {
+        Self::from_f64(f64::from(value))
+    }
+}
+
+/// Rounded down, like Pico8 does with coordinates: -0.5 is -1.
+impl From<Num> for i32 {
+    fn from(value: Num) -> Self {
+        value.0 >> 16
+    }
+}
+
+impl From<Num> for f32 {
+    fn from(value: Num) -> Self {
+        value.to_f32()
+    }
+}
+
+/// At most 4 decimals, without trailing zeros, like Pico8's `print`.
+impl Display for Num {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = format!("{:.4}", self.to_f64());
+        let text = text.trim_end_matches('0').trim_end_matches('.');
+
+        f.write_str(if text == "-0" { "0" } else { text })
+    }
+}
+
+impl Add for Num {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0.wrapping_add(rhs.0))
+    }
+}
+
+impl Sub for Num {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0.wrapping_sub(rhs.0))
+    }
+}
+
+impl Mul for Num {
+    type Output = Self;
+
+    // The fraction bits below 1/65536 are dropped (rounding down), the integer bits above 32767 wrap.
+    fn mul(self, rhs: Self) -> Self {
+        Self(((i64::from(self.0) * i64::from(rhs.0)) >> 16) as i32)
+    }
+}
+
+impl Div for Num {
+    type Output = Self;
+
+    /// Dividing by 0 gives the largest number with the dividend's sign.
+    fn div(self, rhs: Self) -> Self {
+        if rhs.0 == 0 {
+            return Self(if self.0 < 0 { i32::MIN + 1 } else { i32::MAX });
+        }
+
+        Self(((i64::from(self.0) << 16) / i64::from(rhs.0)) as i32)
+    }
+}
+
+impl Rem for Num {
+    type Output = Self;
+
+    /// Never negative, whatever the signs. `x % 0` is 0.
+    fn rem(self, rhs: Self) -> Self {
+        if rhs.0 == 0 {
+            return Self(0);
+        }
+
+        Self(self.0.wrapping_rem_euclid(rhs.0))
+    }
+}
+
+impl Neg for Num {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(self.0.wrapping_neg())
+    }
+}
+
+impl AddAssign for Num {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Num {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for Num {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl DivAssign for Num {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl RemAssign for Num {
+    fn rem_assign(&mut self, rhs: Self) {
+        *self = *self % rhs;
+    }
+}
+
+/// Rounded down, `flr(-1.5)` is -2.
+pub fn flr(x: Num) -> Num {
+    Num(x.0 & !0xFFFF)
+}
+
+/// Rounded up, `ceil(-1.5)` is -1.
+pub fn ceil(x: Num) -> Num {
+    -flr(-x)
+}
+
+/// The middle one of the three, in any order.
+pub fn mid(x: Num, y: Num, z: Num) -> Num {
+    x.min(y).max(x.max(y).min(z))
+}
+
+/// -1 for negative numbers, 1 otherwise (also for 0).
+pub fn sgn(x: Num) -> Num {
+    Num::from(if x.0 < 0 { -1 } else { 1 })
+}
+
+/// The sine of `turns` (1 is a full circle), upside down like the screen: `sin(0.25)` is -1.
+pub fn sin(turns: Num) -> Num {
+    Num::from_f64(-(turns.to_f64() * std::f64::consts::TAU).sin())
+}
+
+/// The cosine of `turns` (1 is a full circle).
+pub fn cos(turns: Num) -> Num {
+    Num::from_f64((turns.to_f64() * std::f64::consts::TAU).cos())
+}
+
+/// The direction of (`dx`, `dy`) in turns, from 0 up to 1, upside down like [`sin`]:
+/// `atan2(0, -1)` (up on the screen) is 0.25. `atan2(0, 0)` is 0.25 too.
+pub fn atan2(dx: Num, dy: Num) -> Num {
+    if dx.0 == 0 && dy.0 == 0 {
+        return Num(Num::ONE / 4);
+    }
+
+    let turns = (-dy.to_f64()).atan2(dx.to_f64()) / std::f64::consts::TAU;
+
+    // Just under a full turn can round up to 1.
+    Num(Num::from_f64(turns.rem_euclid(1.0)).0 & 0xFFFF)
+}
+
+/// Rounded down to 1/65536, 0 for negative numbers.
+pub fn sqrt(x: Num) -> Num {
+    if x.0 <= 0 {
+        return Num(0);
+    }
+
+    Num(((x.0 as u64) << 16).isqrt() as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::draw_data::DrawData;
+    use crate::runtime::state::State;
+    use crate::{Color, Pico8, Resources};
+
+    fn n(value: f32) -> Num {
+        Num::from(value)
+    }
+
+    // Pico8's results, as given by its manual and the Pico8 wiki, in its own hex notation
+    // (`0x0001.8000` is 1.5) where the fraction matters to the last bit.
+    #[test]
+    fn matches_pico8() {
+        let table: &[(&str, Num, i32)] = &[
+            ("32767 + 1", Num::from(32767) + Num::from(1), -32768 << 16),
+            ("-32768 - 1", Num::from(-32768) - Num::from(1), 32767 << 16),
+            ("256 * 256", Num::from(256) * Num::from(256), 0),
+            ("200 * 200", Num::from(200) * Num::from(200), -25536 << 16),
+            ("0.5 * 0.5", n(0.5) * n(0.5), 0x0000_4000),
+            ("0x0000.0001 * 0.5", Num::from_bits(1) * n(0.5), 0),
+            ("1 / 3", Num::from(1) / Num::from(3), 0x0000_5555),
+            ("1 / 0", Num::from(1) / Num::from(0), 0x7FFF_FFFF),
+            ("-1 / 0", Num::from(-1) / Num::from(0), -0x7FFF_FFFF),
+            ("7 % 3", Num::from(7) % Num::from(3), 1 << 16),
+            ("-7 % 3", Num::from(-7) % Num::from(3), 2 << 16),
+            ("7 % -3", Num::from(7) % Num::from(-3), 1 << 16),
+            ("5.5 % 2", n(5.5) % Num::from(2), 0x0001_8000),
+            ("-0.25 % 1", n(-0.25) % Num::from(1), 0x0000_C000),
+            ("5 % 0", Num::from(5) % Num::from(0), 0),
+            ("0.1", n(0.1), 0x0000_199A),
+            ("flr(1.5)", flr(n(1.5)), 1 << 16),
+            ("flr(-1.5)", flr(n(-1.5)), -2 << 16),
+            ("flr(-0.5)", flr(n(-0.5)), -1 << 16),
+            ("ceil(1.5)", ceil(n(1.5)), 2 << 16),
+            ("ceil(-1.5)", ceil(n(-1.5)), -1 << 16),
+            ("ceil(-0.5)", ceil(n(-0.5)), 0),
+            (
+                "mid(7, 5, 10)",
+                mid(Num::from(7), Num::from(5), Num::from(10)),
+                7 << 16,
+            ),
+            (
+                "mid(10, 5, 7)",
+                mid(Num::from(10), Num::from(5), Num::from(7)),
+                7 << 16,
+            ),
+            ("sgn(0)", sgn(Num::from(0)), 1 << 16),
+            ("sgn(-0.5)", sgn(n(-0.5)), -1 << 16),
+            ("sin(0)", sin(Num::from(0)), 0),
+            ("sin(0.25)", sin(n(0.25)), -1 << 16),
+            ("sin(0.5)", sin(n(0.5)), 0),
+            ("sin(0.75)", sin(n(0.75)), 1 << 16),
+            ("cos(0)", cos(Num::from(0)), 1 << 16),
+            ("cos(0.25)", cos(n(0.25)), 0),
+            ("cos(0.5)", cos(n(0.5)), -1 << 16),
+            ("atan2(1, 0)", atan2(Num::from(1), Num::from(0)), 0),
+            (
+                "atan2(0, -1)",
+                atan2(Num::from(0), Num::from(-1)),
+                0x0000_4000,
+            ),
+            (
+                "atan2(-1, 0)",
+                atan2(Num::from(-1), Num::from(0)),
+                0x0000_8000,
+            ),
+            (
+                "atan2(0, 1)",
+                atan2(Num::from(0), Num::from(1)),
+                0x0000_C000,
+            ),
+            (
+                "atan2(1, -1)",
+                atan2(Num::from(1), Num::from(-1)),
+                0x0000_2000,
+            ),
+            (
+                "atan2(0, 0)",
+                atan2(Num::from(0), Num::from(0)),
+                0x0000_4000,
+            ),
+            ("sqrt(4)", sqrt(Num::from(4)), 2 << 16),
+            ("sqrt(2)", sqrt(Num::from(2)), 0x0001_6A09),
+            ("sqrt(0.25)", sqrt(n(0.25)), 0x0000_8000),
+            ("sqrt(-1)", sqrt(Num::from(-1)), 0),
+        ];
+
+        for &(expression, result, bits) in table {
+            assert_eq!(result, Num::from_bits(bits), "{expression}");
+        }
+    }
+
+    #[test]
+    fn converts_like_pico8() {
+        assert_eq!(Num::from(40000), Num::from(-25536));
+        assert_eq!(i32::from(n(2.75)), 2);
+        assert_eq!(i32::from(n(-0.5)), -1);
+        assert_eq!(n(-1.25).to_f32(), -1.25);
+
+        let texts = [(n(1.5), "1.5"), (Num::from(1) / Num::from(3), "0.3333")];
+        for (number, text) in texts {
+            assert_eq!(number.to_string(), text);
+        }
+        assert_eq!(Num::from(-3).to_string(), "-3");
+        assert_eq!(Num::from(0).to_string(), "0");
+    }
+
+    #[test]
+    fn compound_assignment_wraps_too() {
+        let mut x = Num::from(32000);
+        x += Num::from(1000);
+        assert_eq!(x, Num::from(-32536));
+        x -= Num::from(-1);
+        x *= Num::from(2);
+        x /= Num::from(4);
+        x %= Num::from(100);
+        // -65070 wraps around to 466.
+        assert_eq!(x, n(16.5));
+    }
+
+    #[test]
+    fn numbers_are_drawing_coordinates() {
+        let mut pico8 = Pico8::new(DrawData::new(), State::new(), Resources::empty());
+
+        pico8.pset(n(2.75), n(-0.5) + Num::from(4), Color::RED);
+        pico8.rectfill(n(10.5), 10, n(11.9), 10, Color::BLUE);
+
+        assert_eq!(pico8.draw_data.pixel(2, 3), Some(Color::RED));
+        assert_eq!(pico8.draw_data.pixel(10, 10), Some(Color::BLUE));
+        assert_eq!(pico8.draw_data.pixel(11, 10), Some(Color::BLUE));
+        assert_eq!(pico8.draw_data.pixel(12, 10), Some(Color::BLACK));
+
+        // Clipping, the camera and the map too.
+        pico8.mset(n(1.5), 0, 7);
+        assert_eq!(pico8.mget(1, n(0.25)), 7);
+        pico8.clip(n(20.5), 20, 2, 2);
+        pico8.camera_push(n(-0.5), 0);
+        pico8.rectfill(0, 0, 127, 127, Color::GREEN);
+        pico8.camera_pop();
+        assert_eq!(pico8.draw_data.pixel(20, 20), Some(Color::GREEN));
+        assert_eq!(pico8.draw_data.pixel(22, 20), Some(Color::BLACK));
+    }
+}
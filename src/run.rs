@@ -1,56 +1,150 @@
 use crate::app::AppCompat;
 use crate::controller::{Controller, Scene};
-use crate::graphics::{whole_screen_vertex_buffer, FRAGMENT_SHADER, VERTEX_SHADER};
-use crate::{Event, KeyState, MouseButton, MouseEvent, Resources};
+use crate::graphics::{
+    whole_screen_vertex_buffer, Vertex, Viewport, CRT_FRAGMENT_SHADER, FRAGMENT_SHADER,
+    VERTEX_SHADER,
+};
+use crate::runtime::frame_clock::{FrameClock, FRAME_MILLIS};
+use crate::{
+    CrtFilter, CursorMode, Error, Event, KeyState, MouseButton, MouseEvent, Resources, ScalingMode,
+};
 use crate::{Key, KeyboardEvent};
 use glium::backend::Facade;
-use glium::glutin::dpi::{LogicalPosition, LogicalSize};
+use glium::glutin::dpi::{LogicalSize, PhysicalSize};
 use glium::glutin::event::{self, ElementState, KeyboardInput};
 use glium::glutin::event_loop::{ControlFlow, EventLoop};
 use glium::index::NoIndices;
 use glium::texture::{RawImage2d, SrgbTexture2d};
 use glium::uniforms::{MagnifySamplerFilter, Sampler};
-use glium::{glutin, Display, Program, Surface};
+use glium::{glutin, Display, DrawParameters, Program, Rect, Surface, VertexBuffer};
 use glium::{uniform, Frame};
+use std::time::Instant;
 
-pub(crate) fn run_app<Game: AppCompat + 'static>(scene: Scene, resources: Resources) {
+pub(crate) fn run_app<Game: AppCompat + 'static>(
+    scene: Scene,
+    resources: Resources,
+) -> Result<(), Error> {
     let event_loop = glutin::event_loop::EventLoop::new();
-    let display = make_display(&event_loop, "Runty8");
-    let scale_factor = display.gl_window().window().scale_factor();
-    let mut logical_size = display
-        .gl_window()
-        .window()
-        .inner_size()
-        .to_logical(scale_factor);
+    let display = make_display(&event_loop, "Runty8")?;
+    let mut window_size = display.gl_window().window().inner_size();
 
-    let (indices, program) = make_gl_program(&display);
+    // Both compiled up front, so that turning the filter on doesn't stall a frame.
+    let (indices, program) = make_gl_program(&display, FRAGMENT_SHADER)?;
+    let (_, crt_program) = make_gl_program(&display, CRT_FRAGMENT_SHADER)?;
+    let vertex_buffer = whole_screen_vertex_buffer(&display)
+        .map_err(|error| Error::ShaderCompile(error.to_string()))?;
 
     let mut controller = Controller::<Game>::init(scene, resources);
+    let mut os_cursor = OsCursor::new();
+    let mut frame_clock = FrameClock::new();
+    let mut last_tick = Instant::now();
     event_loop.run(move |glutin_event, _, control_flow| {
-        let event: Option<Event> =
-            translate_event(&glutin_event, scale_factor, &mut logical_size, control_flow);
+        let event: Option<Event> = translate_event(
+            &glutin_event,
+            &mut window_size,
+            controller.scaling_mode(),
+            control_flow,
+        );
 
-        controller.step(event);
+        // Late ticks run the updates they missed, up to a point.
+        match event {
+            Some(Event::Tick { .. }) => {
+                let now = Instant::now();
+                let elapsed_millis = now.duration_since(last_tick).as_secs_f64() * 1000.0;
+                let (updates, dropped) =
+                    frame_clock.advance(elapsed_millis * controller.time_scale());
+                last_tick = now;
+
+                controller.drop_updates(dropped);
+                for _ in 0..updates {
+                    controller.step(Some(Event::Tick {
+                        delta_millis: FRAME_MILLIS,
+                        // Numbered by the controller, which counts the frames.
+                        frame: 0,
+                    }));
+                }
+            }
+            event => controller.step(event),
+        }
 
         if let Some(new_title) = controller.take_new_title() {
             display.gl_window().window().set_title(&new_title);
         }
 
-        do_draw(
+        // The cursor is given back when the window isn't being used, so it doesn't stay hidden.
+        match glutin_event {
+            event::Event::WindowEvent {
+                event: glutin::event::WindowEvent::Focused(focused),
+                ..
+            } => os_cursor.focused = focused,
+            event::Event::LoopDestroyed => {
+                os_cursor.focused = false;
+                controller.exit();
+            }
+            _ => {}
+        }
+        if let Some(visible) = os_cursor.update(controller.cursor_mode()) {
+            display.gl_window().window().set_cursor_visible(visible);
+        }
+
+        let (program, crt_filter) = match controller.crt_filter() {
+            Some(crt_filter) => (&crt_program, crt_filter),
+            None => (&program, CrtFilter::default()),
+        };
+        let presentation = Presentation {
+            border_rgb: controller.border_rgb(),
+            viewport: Viewport::new(
+                controller.scaling_mode(),
+                (window_size.width, window_size.height),
+            ),
+            crt_filter,
+        };
+        if let Err(error) = do_draw(
             &display,
             display.draw(),
             controller.screen_buffer(),
+            presentation,
+            &vertex_buffer,
             &indices,
-            &program,
-        );
+            program,
+        ) {
+            eprintln!("Couldn't draw frame: {error}");
+
+            *control_flow = ControlFlow::Exit;
+        }
     });
 }
 
+// Whether the operating system's cursor should be visible.
+struct OsCursor {
+    focused: bool,
+    visible: bool,
+}
+
+impl OsCursor {
+    // The window starts with the cursor hidden.
+    fn new() -> Self {
+        Self {
+            focused: true,
+            visible: false,
+        }
+    }
+
+    // Returns the new visibility, if it changed.
+    fn update(&mut self, mode: CursorMode) -> Option<bool> {
+        let visible = !self.focused || mode == CursorMode::Native;
+        let changed = visible != self.visible;
+        self.visible = visible;
+
+        changed.then_some(visible)
+    }
+}
+
 /// Translates a glutin::event::Event into a runty8 Event.
 fn translate_event(
     event: &glutin::event::Event<()>,
-    hidpi_factor: f64,
-    window_size: &mut LogicalSize<f64>,
+    window_size: &mut PhysicalSize<u32>,
+    scaling_mode: ScalingMode,
     control_flow: &mut ControlFlow,
 ) -> Option<Event> {
     match event {
@@ -62,28 +156,30 @@ fn translate_event(
             }
             // TODO: Force aspect ratio on resize.
             &glutin::event::WindowEvent::Resized(new_size) => {
-                let new_size: LogicalSize<f64> = new_size.to_logical(hidpi_factor);
-
                 *window_size = new_size;
 
                 None
             }
             glutin::event::WindowEvent::CursorMoved { position, .. } => {
-                let logical_mouse: LogicalPosition<f64> = position.to_logical(hidpi_factor);
+                let viewport = Viewport::new(scaling_mode, (window_size.width, window_size.height));
+                let (x, y) = viewport.to_screen(position.x, position.y);
 
-                Some(Event::Mouse(MouseEvent::Move {
-                    x: (logical_mouse.x / window_size.width * 128.).floor() as i32,
-                    y: (logical_mouse.y / window_size.height * 128.).floor() as i32,
-                }))
+                Some(Event::Mouse(MouseEvent::Move { x, y }))
             }
             glutin::event::WindowEvent::MouseInput {
-                button: event::MouseButton::Left,
+                button,
                 state: input_state,
                 ..
             } => {
+                let button = match button {
+                    event::MouseButton::Left => MouseButton::Left,
+                    event::MouseButton::Right => MouseButton::Right,
+                    event::MouseButton::Middle => MouseButton::Middle,
+                    event::MouseButton::Other(_) => return None,
+                };
                 let mouse_event = match input_state {
-                    ElementState::Pressed => MouseEvent::Down(MouseButton::Left),
-                    ElementState::Released => MouseEvent::Up(MouseButton::Left),
+                    ElementState::Pressed => MouseEvent::Down(button),
+                    ElementState::Released => MouseEvent::Up(button),
                 };
 
                 Some(Event::Mouse(mouse_event))
@@ -91,22 +187,19 @@ fn translate_event(
             glutin::event::WindowEvent::KeyboardInput { input, .. } => {
                 handle_keyboard_event(input).map(Event::Keyboard)
             }
+            &glutin::event::WindowEvent::ReceivedCharacter(character) => {
+                Some(Event::Character(character))
+            }
             _ => None,
         },
         event::Event::NewEvents(cause) => match cause {
-            glutin::event::StartCause::ResumeTimeReached {
-                start,
-                requested_resume,
-            } => {
+            // How many updates it's worth is up to the frame clock.
+            glutin::event::StartCause::ResumeTimeReached { .. } => {
                 set_next_timer(control_flow);
 
-                let delta: Result<i32, _> = requested_resume
-                    .duration_since(*start)
-                    .as_millis()
-                    .try_into();
-
                 Some(Event::Tick {
-                    delta_millis: delta.unwrap().try_into().unwrap(),
+                    delta_millis: FRAME_MILLIS,
+                    frame: 0,
                 })
             }
             glutin::event::StartCause::Init => {
@@ -140,48 +233,84 @@ fn set_next_timer(control_flow: &mut ControlFlow) {
     *control_flow = glutin::event_loop::ControlFlow::WaitUntil(next_frame_time);
 }
 
+// How the screen is shown in the window, this frame.
+struct Presentation {
+    border_rgb: (u8, u8, u8),
+    viewport: Viewport,
+    // Only used by the CRT program.
+    crt_filter: CrtFilter,
+}
+
 fn do_draw(
     display: &impl Facade,
     mut target: Frame,
-    buffer: &[u8],
+    buffer: Vec<u8>,
+    presentation: Presentation,
+    vertex_buffer: &VertexBuffer<Vertex>,
     indices: &NoIndices,
     program: &Program,
-) {
-    target.clear_color(1.0, 0.0, 0.0, 1.0);
-    let image = RawImage2d::from_raw_rgb(buffer.to_vec(), (128, 128));
-    let texture = SrgbTexture2d::new(display, image).unwrap();
+) -> Result<(), String> {
+    let Presentation {
+        border_rgb: (border_r, border_g, border_b),
+        viewport,
+        crt_filter,
+    } = presentation;
+    let channel = |value: u8| value as f32 / 255.0;
+    target.clear_color_srgb(channel(border_r), channel(border_g), channel(border_b), 1.0);
+    let image = RawImage2d::from_raw_rgb(buffer, (128, 128));
+    let texture = match SrgbTexture2d::new(display, image) {
+        Ok(texture) => texture,
+        Err(error) => {
+            // Frames must always be finished, even if we couldn't draw anything on them.
+            target.finish().map_err(|error| error.to_string())?;
+
+            return Err(error.to_string());
+        }
+    };
     let uniforms = uniform! {
-        tex: Sampler::new(&texture).magnify_filter(MagnifySamplerFilter::Nearest)
+        tex: Sampler::new(&texture).magnify_filter(MagnifySamplerFilter::Nearest),
+        scanlines: crt_filter.scanlines,
+        curvature: crt_filter.curvature,
+        vignette: crt_filter.vignette,
     };
-    target
-        .draw(
-            &whole_screen_vertex_buffer(display),
-            indices,
-            program,
-            &uniforms,
-            &Default::default(),
-        )
-        .unwrap();
-    target.finish().unwrap();
+    // GL viewports start at the bottom left.
+    let (_, window_height) = target.get_dimensions();
+    let parameters = DrawParameters {
+        viewport: Some(Rect {
+            left: viewport.x,
+            bottom: window_height.saturating_sub(viewport.y + viewport.height),
+            width: viewport.width,
+            height: viewport.height,
+        }),
+        ..Default::default()
+    };
+    let draw_result = target.draw(vertex_buffer, indices, program, &uniforms, &parameters);
+    target.finish().map_err(|error| error.to_string())?;
+
+    draw_result.map_err(|error| error.to_string())
 }
 
-fn make_display(event_loop: &EventLoop<()>, title: &str) -> Display {
+fn make_display(event_loop: &EventLoop<()>, title: &str) -> Result<Display, Error> {
     let wb = glutin::window::WindowBuilder::new()
         .with_inner_size(LogicalSize::new(640.0, 640.0))
         .with_title(title);
     let cb = glutin::ContextBuilder::new();
-    let display = glium::Display::new(wb, cb, event_loop).unwrap();
+    let display = glium::Display::new(wb, cb, event_loop)
+        .map_err(|error| Error::WindowCreation(error.to_string()))?;
     {
         display.gl_window().window().set_cursor_visible(false);
     }
 
-    display
+    Ok(display)
 }
 
-fn make_gl_program(display: &impl Facade) -> (NoIndices, Program) {
+fn make_gl_program(
+    display: &impl Facade,
+    fragment_shader: &str,
+) -> Result<(NoIndices, Program), Error> {
     let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
-    let program =
-        glium::Program::from_source(display, VERTEX_SHADER, FRAGMENT_SHADER, None).unwrap();
+    let program = glium::Program::from_source(display, VERTEX_SHADER, fragment_shader, None)
+        .map_err(|error| Error::ShaderCompile(error.to_string()))?;
 
-    (indices, program)
+    Ok((indices, program))
 }
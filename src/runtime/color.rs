@@ -0,0 +1,147 @@
+use std::fmt::{self, Display};
+
+/// One of the 16 colors of the Pico8 palette.
+///
+/// Plain numbers convert into colors (only the lowest 4 bits are kept),
+/// so `draw.rectfill(0, 0, 7, 7, 8)` still works.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Color(u8);
+
+impl Color {
+    pub const BLACK: Color = Color(0);
+    pub const DARK_BLUE: Color = Color(1);
+    pub const DARK_PURPLE: Color = Color(2);
+    pub const DARK_GREEN: Color = Color(3);
+    pub const BROWN: Color = Color(4);
+    pub const DARK_GREY: Color = Color(5);
+    pub const LIGHT_GREY: Color = Color(6);
+    pub const WHITE: Color = Color(7);
+    pub const RED: Color = Color(8);
+    pub const ORANGE: Color = Color(9);
+    pub const YELLOW: Color = Color(10);
+    pub const GREEN: Color = Color(11);
+    pub const BLUE: Color = Color(12);
+    pub const LAVENDER: Color = Color(13);
+    pub const PINK: Color = Color(14);
+    pub const LIGHT_PEACH: Color = Color(15);
+
+    /// Returns `None` if `index` isn't in `0..=15`.
+    pub const fn new(index: u8) -> Option<Color> {
+        if index < 16 {
+            Some(Color(index))
+        } else {
+            None
+        }
+    }
+
+    /// Keeps the lowest 4 bits of `index`, like Pico8 does.
+    pub const fn from_index_lossy(index: u8) -> Color {
+        Color(index & 0xF)
+    }
+
+    /// Position in the palette, in `0..=15`.
+    pub const fn index(self) -> u8 {
+        self.0
+    }
+
+    /// The red, green and blue components this color is displayed with.
+    pub const fn rgb(self) -> (u8, u8, u8) {
+        let [_, r, g, b] = RGB[self.0 as usize].to_be_bytes();
+
+        (r, g, b)
+    }
+
+    /// All the colors, in palette order.
+    pub fn all() -> impl Iterator<Item = Color> {
+        (0..16).map(Color)
+    }
+}
+
+impl From<u8> for Color {
+    fn from(index: u8) -> Self {
+        Self::from_index_lossy(index)
+    }
+}
+
+impl From<Color> for u8 {
+    fn from(color: Color) -> Self {
+        color.index()
+    }
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The red, green and blue components of Pico8's extended color `128 + extended`,
+/// which games can only show through `pal(c, 128 + extended, 1)`.
+/// `extended` wraps around past 15.
+pub(crate) const fn extended_rgb(extended: u8) -> (u8, u8, u8) {
+    let [_, r, g, b] = EXTENDED_RGB[(extended & 0xF) as usize].to_be_bytes();
+
+    (r, g, b)
+}
+
+// 0xRRGGBB
+const EXTENDED_RGB: [u32; 16] = [
+    0x291814, //
+    0x111D35, //
+    0x422136, //
+    0x125359, //
+    0x742F29, //
+    0x49333B, //
+    0xA28879, //
+    0xF3EF7D, //
+    0xBE1250, //
+    0xFF6C24, //
+    0xA8E72E, //
+    0x00B543, //
+    0x065AB5, //
+    0x754665, //
+    0xFF6E59, //
+    0xFF9D81, //
+];
+
+// 0xRRGGBB
+const RGB: [u32; 16] = [
+    0x000000, //
+    0x1D2B53, //
+    0x7E2553, //
+    0x008751, //
+    0xAB5236, //
+    0x5F574F, //
+    0xC2C3C7, //
+    0xFFF1E8, //
+    0xFF004D, //
+    0xFFA300, //
+    0xFFEC27, //
+    0x00E436, //
+    0x29ADFF, //
+    0x83769C, //
+    0xFF77A8, //
+    0xFFCCAA, //
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_indices() {
+        assert_eq!(Color::new(15), Some(Color::LIGHT_PEACH));
+        assert_eq!(Color::new(16), None);
+        assert_eq!(Color::from_index_lossy(37), Color::DARK_GREY);
+        assert_eq!(Color::from(8), Color::RED);
+    }
+
+    #[test]
+    fn converts_to_rgb() {
+        assert_eq!(Color::BLACK.rgb(), (0, 0, 0));
+        assert_eq!(Color::RED.rgb(), (0xFF, 0x00, 0x4D));
+        assert_eq!(Color::BLUE.to_string(), "12");
+        assert_eq!(extended_rgb(0), (0x29, 0x18, 0x14));
+        assert_eq!(extended_rgb(15), (0xFF, 0x9D, 0x81));
+    }
+}
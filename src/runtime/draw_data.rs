@@ -1,42 +1,234 @@
-use crate::runtime::flags::Flags;
-use crate::runtime::map::Map;
-use crate::runtime::sprite_sheet::SpriteSheet;
-use crate::{draw, font};
+use crate::font::{self, Font};
+use crate::glyphs;
+use crate::{draw, Resources};
+use itertools::Itertools;
 
-use super::sprite_sheet::{Color, Sprite};
+use super::color::{self, Color};
+use super::palette::Palette;
+use super::sprite_sheet::{Sprite, SpriteSheet};
 
 const WIDTH: usize = 128;
-const NUM_COMPONENTS: usize = 3;
 
-type Buffer = [u8; NUM_COMPONENTS * WIDTH * WIDTH];
-const BLACK_BUFFER: Buffer = [0; NUM_COMPONENTS * WIDTH * WIDTH];
+// Color indices, one per pixel. Past the 16 colors, `EXTENDED` + n is Pico8's color 128 + n,
+// which only the editor draws.
+type Buffer = [u8; WIDTH * WIDTH];
+const BLACK_BUFFER: Buffer = [0; WIDTH * WIDTH];
+const EXTENDED: u8 = 16;
+
+// How many clip regions can be pushed at once, deeper nesting is surely a missing `clip_pop`.
+const MAX_CLIP_DEPTH: usize = 16;
+
+/// The part of the screen that gets drawn on, `x0..x1` by `y0..y1`, in screen coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ClipRect {
+    x0: i32,
+    y0: i32,
+    x1: i32,
+    y1: i32,
+}
+
+impl ClipRect {
+    const SCREEN: Self = Self {
+        x0: 0,
+        y0: 0,
+        x1: WIDTH as i32,
+        y1: WIDTH as i32,
+    };
+
+    /// The `width` x `height` rectangle at (`x`, `y`), cut to the screen.
+    fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self {
+            x0: x,
+            y0: y,
+            x1: x.saturating_add(width.max(0)),
+            y1: y.saturating_add(height.max(0)),
+        }
+        .intersect(Self::SCREEN)
+    }
 
-const ORIGINAL_PALETTE: [Color; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+    // Disjoint rectangles intersect into an empty range at x0, y0.
+    fn intersect(self, other: Self) -> Self {
+        let (x0, y0) = (self.x0.max(other.x0), self.y0.max(other.y0));
+
+        Self {
+            x0,
+            y0,
+            x1: self.x1.min(other.x1).max(x0),
+            y1: self.y1.min(other.y1).max(y0),
+        }
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        (self.x0..self.x1).contains(&x) && (self.y0..self.y1).contains(&y)
+    }
+}
+
+const ORIGINAL_PALETTE: [Color; 16] = [
+    Color::BLACK,
+    Color::DARK_BLUE,
+    Color::DARK_PURPLE,
+    Color::DARK_GREEN,
+    Color::BROWN,
+    Color::DARK_GREY,
+    Color::LIGHT_GREY,
+    Color::WHITE,
+    Color::RED,
+    Color::ORANGE,
+    Color::YELLOW,
+    Color::GREEN,
+    Color::BLUE,
+    Color::LAVENDER,
+    Color::PINK,
+    Color::LIGHT_PEACH,
+];
 
 // Handles actually drawing to the screen buffer
 #[derive(Debug)]
 pub(crate) struct DrawData {
     buffer: Buffer,
+    // What was drawn by the end of the last frame, for feedback effects.
+    previous: Buffer,
     // Maybe these properties below should be in internal state?
+    transparent_color: Option<Color>,
+    draw_palette: [Color; 16],
+    // Applied to the whole screen when it's displayed, like Pico8's `pal(c0, c1, 1)`.
+    screen_palette: [Color; 16],
+    // One bit per color set with `screen_pal`, which the cycle leaves alone.
+    screen_pal_set: u16,
+    pal_cycle: Option<PalCycle>,
+    camera: (i32, i32),
+    // Used by `print`, `None` for the built-in one.
+    font: Option<Font>,
+    saved_states: Vec<DrawState>,
+    // Only pixels in here get drawn.
+    clip: ClipRect,
+    // The regions `clip_pop` goes back to.
+    saved_clips: Vec<ClipRect>,
+    // The cameras `camera_pop` goes back to.
+    saved_cameras: Vec<(i32, i32)>,
+}
+
+/// Screen palette slots rotated by one every `period` frames, see `Pico8::pal_cycle`.
+#[derive(Debug, Clone)]
+struct PalCycle {
+    colors: Vec<Color>,
+    period: u32,
+    // The frame the cycle started at, when nothing is rotated yet.
+    start: u64,
+}
+
+/// What `push_state` saves and `pop_state` restores.
+#[derive(Debug, Clone)]
+struct DrawState {
     transparent_color: Option<Color>,
     draw_palette: [Color; 16],
     camera: (i32, i32),
+    font: Option<Font>,
+    clip: ClipRect,
 }
 
 impl DrawData {
     pub(crate) fn new() -> Self {
         DrawData {
             buffer: BLACK_BUFFER,
-            transparent_color: Some(0),
+            previous: BLACK_BUFFER,
+            transparent_color: Some(Color::BLACK),
             draw_palette: ORIGINAL_PALETTE,
+            screen_palette: ORIGINAL_PALETTE,
+            screen_pal_set: 0,
+            pal_cycle: None,
             camera: (0, 0),
+            font: None,
+            saved_states: vec![],
+            clip: ClipRect::SCREEN,
+            saved_clips: vec![],
+            saved_cameras: vec![],
+        }
+    }
+
+    /// Saves the palette, transparent color, camera, font and clip region, to be restored by `pop_state`.
+    /// The regions pushed with `clip_push` stay, only the current one is saved.
+    pub(crate) fn push_state(&mut self) {
+        self.saved_states.push(DrawState {
+            transparent_color: self.transparent_color,
+            draw_palette: self.draw_palette,
+            camera: self.camera,
+            font: self.font.clone(),
+            clip: self.clip,
+        });
+    }
+
+    /// Restores the state saved by the last `push_state`, does nothing if there's none.
+    pub(crate) fn pop_state(&mut self) {
+        if let Some(state) = self.saved_states.pop() {
+            self.transparent_color = state.transparent_color;
+            self.draw_palette = state.draw_palette;
+            self.camera = state.camera;
+            self.font = state.font;
+            self.clip = state.clip;
+        }
+    }
+
+    /// Only draws inside the `width` x `height` rectangle at (`x`, `y`) on the screen, from now on.
+    /// Replaces the current clip region, pushed or not.
+    pub(crate) fn clip(&mut self, x: i32, y: i32, width: i32, height: i32) {
+        self.clip = ClipRect::new(x, y, width, height);
+    }
+
+    /// Like `clip`, inside the current clip region, which `clip_pop` restores.
+    pub(crate) fn clip_push(&mut self, x: i32, y: i32, width: i32, height: i32) {
+        debug_assert!(
+            self.saved_clips.len() < MAX_CLIP_DEPTH,
+            "Clip regions nested more than {MAX_CLIP_DEPTH} deep, is a clip_pop missing?"
+        );
+
+        self.saved_clips.push(self.clip);
+        self.clip = self.clip.intersect(ClipRect::new(x, y, width, height));
+    }
+
+    /// Restores the clip region from before the last `clip_push`, does nothing if there's none.
+    pub(crate) fn clip_pop(&mut self) {
+        if let Some(clip) = self.saved_clips.pop() {
+            self.clip = clip;
         }
     }
 
+    /// Font used by `print`, `None` for the built-in one.
+    pub(crate) fn set_font(&mut self, font: Option<Font>) {
+        self.font = font;
+    }
+
+    /// Swaps everything that changes how things get drawn with `other`'s: palettes,
+    /// transparency, camera, clip, font and what's been pushed, but keeps this screen.
+    /// So that the game and the editor each draw with their own state.
+    #[cfg(feature = "editor")]
+    pub(crate) fn swap_draw_state(&mut self, other: &mut DrawData) {
+        std::mem::swap(&mut self.buffer, &mut other.buffer);
+        std::mem::swap(self, other);
+    }
+
+    pub(crate) fn font(&self) -> &Font {
+        self.font.as_ref().unwrap_or(Font::builtin())
+    }
+
     pub(crate) fn append_camera(&mut self, x: i32, y: i32) {
         self.camera(self.camera.0 + x, self.camera.1 + y);
     }
 
+    /// Moves the camera by (`dx`, `dy`), until the matching `camera_pop`.
+    pub(crate) fn camera_push(&mut self, dx: i32, dy: i32) {
+        self.saved_cameras.push(self.camera);
+        self.append_camera(dx, dy);
+    }
+
+    /// Restores the camera from before the last `camera_push`, does nothing if there's none.
+    /// Undoes any `camera` call made since then too.
+    pub(crate) fn camera_pop(&mut self) {
+        if let Some(camera) = self.saved_cameras.pop() {
+            self.camera = camera;
+        }
+    }
+
     fn set_pixel_with_transparency(&mut self, index: usize, color: Color) {
         if let Some(transparent_color) = self.transparent_color {
             if color == transparent_color {
@@ -50,35 +242,104 @@ impl DrawData {
     #[allow(clippy::only_used_in_recursion)]
     fn set_pixel(&mut self, index: usize, color: Color) {
         // https://pico-8.fandom.com/wiki/Pal
-        let color = self.draw_palette[color as usize];
-        let c = get_color(color);
-
-        #[allow(clippy::identity_op)]
-        {
-            let r = ((c >> 16) & 0x0000FF) as u8;
-            let g = ((c >> 8) & 0x0000FF) as u8;
-            let b = ((c >> 0) & 0x0000FF) as u8;
-            self.buffer[NUM_COMPONENTS * index + 0] = r;
-            self.buffer[NUM_COMPONENTS * index + 1] = g;
-            self.buffer[NUM_COMPONENTS * index + 2] = b;
+        self.buffer[index] = self.draw_palette[color.index() as usize].index();
+    }
+
+    /// Red, green and blue components of every pixel, row by row, as `colors` shows them.
+    /// With `screen_frame`, through the screen palette as it is on that frame
+    /// (which the palette cycle depends on), without it as the pixels were drawn.
+    pub(crate) fn rgb(&self, screen_frame: Option<u64>, colors: &Palette) -> Vec<u8> {
+        let palette = match screen_frame {
+            Some(frame) => self.screen_palette(frame),
+            None => ORIGINAL_PALETTE,
+        };
+
+        self.buffer
+            .iter()
+            .flat_map(|&color| {
+                let (r, g, b) = match color.checked_sub(EXTENDED) {
+                    Some(extended) => color::extended_rgb(extended),
+                    None => colors.rgb(palette[color as usize]),
+                };
+                [r, g, b]
+            })
+            .collect()
+    }
+
+    /// Color of the pixel at (x, y) on the screen, ignoring the camera and the screen palette.
+    pub(crate) fn pixel(&self, x: i32, y: i32) -> Option<Color> {
+        let index = self.index(x, y)?;
+
+        Some(Color::from_index_lossy(self.buffer[index]))
+    }
+
+    /// Keeps the screen as it is now as the previous frame, called once everything's drawn.
+    /// The screen itself stays as it is, like in Pico8.
+    pub(crate) fn keep_frame(&mut self) {
+        self.previous = self.buffer;
+    }
+
+    /// Color of the pixel at (x, y) in the previous frame, ignoring the camera.
+    pub(crate) fn previous_pixel(&self, x: i32, y: i32) -> Option<Color> {
+        let index = self.index(x, y)?;
+
+        Some(Color::from_index_lossy(self.previous[index]))
+    }
+
+    /// Copies the `width` x `height` pixels at (`sx`, `sy`) in the previous frame to (`dx`, `dy`).
+    /// The destination goes through the camera and clip region, the colors are copied as they are.
+    pub(crate) fn blit_previous(
+        &mut self,
+        (sx, sy): (i32, i32),
+        (width, height): (i32, i32),
+        (dx, dy): (i32, i32),
+    ) {
+        for y in 0..height {
+            for x in 0..width {
+                let Some(source) = self.index(sx + x, sy + y) else {
+                    continue;
+                };
+                let (screen_x, screen_y) = self.apply_camera(dx + x, dy + y);
+                if let Some(index) = self.clipped_index(screen_x, screen_y) {
+                    self.buffer[index] = self.previous[source];
+                }
+            }
         }
     }
 
-    pub(crate) fn buffer(&self) -> &Buffer {
-        &self.buffer
+    /// Sets the pixel at (x, y) on the screen as it is, ignoring the camera and draw palette.
+    pub(crate) fn set_screen_pixel(&mut self, x: i32, y: i32, color: Color) {
+        if let Some(index) = self.index(x, y) {
+            self.buffer[index] = color.index();
+        }
     }
 
-    fn print_char(&mut self, index: usize, x: i32, y: i32, color: Color) {
-        let char_data = font::FONT.get(index).unwrap_or(&font::MISSING_CHAR);
+    fn print_char(&mut self, font: &Font, char: char, x: i32, y: i32, color: Color) {
+        if char == glyphs::VARIATION_SELECTOR {
+            return;
+        }
+        if let Some(pixels) = font::wide_glyph(char) {
+            for (index, _) in pixels.iter().enumerate().filter(|(_, set)| **set != 0) {
+                let index = index as i32;
+                self.pset(
+                    x + index % font::WIDE_GLYPH_WIDTH,
+                    y + index / font::WIDE_GLYPH_WIDTH,
+                    color,
+                );
+            }
 
-        for x_offset in 0..4_i32 {
-            for y_offset in 0..6 {
-                let print = char_data[(x_offset + y_offset * 4) as usize] != 0;
+            return;
+        }
 
-                if print {
-                    self.pset(x + x_offset, y + y_offset, color);
+        let (width, height) = font.glyph_size();
+        match font.glyph(char) {
+            Some(pixels) => {
+                for (index, _) in pixels.iter().enumerate().filter(|(_, set)| **set) {
+                    let index = index as i32;
+                    self.pset(x + index % width, y + index / width, color);
                 }
             }
+            None => self.rect(x, y, x + width - 1, y + height - 1, color),
         }
     }
 
@@ -86,6 +347,13 @@ impl DrawData {
         (x - self.camera.0, y - self.camera.1)
     }
 
+    /// Like `index`, for drawing: `None` outside the clip region too.
+    fn clipped_index(&self, x: i32, y: i32) -> Option<usize> {
+        self.clip
+            .contains(x, y)
+            .then(|| x as usize + y as usize * WIDTH)
+    }
+
     /// Returns the linear index of the pixel with (x, y) coordinates in the screen
     fn index(&self, x: i32, y: i32) -> Option<usize> {
         let x_in_bounds = 0 <= x && x < WIDTH as i32;
@@ -99,16 +367,12 @@ impl DrawData {
     }
 
     pub(crate) fn raw_spr(&mut self, sprite: &Sprite, x: i32, y: i32) {
-        let buffer = &sprite.sprite;
+        for (i, j, color) in sprite.iter_pixels() {
+            let x = x + i as i32;
+            let y = y + j as i32;
 
-        for i in 0..8 {
-            for j in 0..8 {
-                let x = x + i;
-                let y = y + j;
-
-                if let Some(index) = self.index(x, y) {
-                    self.set_pixel_with_transparency(index, buffer[(i + j * 8) as usize]);
-                }
+            if let Some(index) = self.clipped_index(x, y) {
+                self.set_pixel_with_transparency(index, color);
             }
         }
     }
@@ -148,16 +412,39 @@ impl DrawData {
 impl DrawData {
     pub(crate) fn pal(&mut self, c0: Color, c1: Color) {
         // https://pico-8.fandom.com/wiki/Pal
-        self.draw_palette[c0 as usize] = c1;
+        self.draw_palette[c0.index() as usize] = c1;
     }
 
     pub(crate) fn camera(&mut self, x: i32, y: i32) {
         self.camera = (x, y);
     }
 
+    #[cfg(any(test, feature = "editor"))]
+    pub(crate) fn get_camera(&self) -> (i32, i32) {
+        self.camera
+    }
+
+    /// The clip region as `x, y, width, height`, what `clip` takes.
+    #[cfg(feature = "editor")]
+    pub(crate) fn get_clip(&self) -> (i32, i32, i32, i32) {
+        let ClipRect { x0, y0, x1, y1 } = self.clip;
+
+        (x0, y0, x1 - x0, y1 - y0)
+    }
+
+    #[cfg(feature = "editor")]
+    pub(crate) fn get_pal(&self) -> [Color; 16] {
+        self.draw_palette
+    }
+
+    #[cfg(feature = "editor")]
+    pub(crate) fn get_palt(&self) -> Option<Color> {
+        self.transparent_color
+    }
+
     pub(crate) fn pset(&mut self, x: i32, y: i32, color: Color) {
         let (x, y) = self.apply_camera(x, y);
-        if let Some(index) = self.index(x, y) {
+        if let Some(index) = self.clipped_index(x, y) {
             self.set_pixel(index, color);
         }
     }
@@ -168,6 +455,19 @@ impl DrawData {
         }
     }
 
+    /// Fills the rectangle with Pico8's extended color `128 + extended`, for the editor's
+    /// screen palette previews. The palettes don't apply, and the pixels read back as
+    /// the color 16 places before.
+    #[cfg(feature = "editor")]
+    pub(crate) fn rectfill_extended(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, extended: u8) {
+        for (x, y) in (y0..=y1).flat_map(|y| (x0..=x1).map(move |x| (x, y))) {
+            let (x, y) = self.apply_camera(x, y);
+            if let Some(index) = self.clipped_index(x, y) {
+                self.buffer[index] = EXTENDED + (extended & 0xF);
+            }
+        }
+    }
+
     pub(crate) fn rect(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
         self.line(x0, y0, x1, y0, color);
         self.line(x0, y0, x0, y1, color);
@@ -181,10 +481,49 @@ impl DrawData {
         }
     }
 
+    /// Displays every `c0` pixel on the screen as `c1`, whenever it was drawn.
+    pub(crate) fn screen_pal(&mut self, c0: Color, c1: Color) {
+        self.screen_palette[c0.index() as usize] = c1;
+        self.screen_pal_set |= 1 << c0.index();
+    }
+
+    /// Rotates the screen palette slots of `colors` by one every `period` frames,
+    /// counting from `frame`. Slots set with `screen_pal` keep their color.
+    pub(crate) fn pal_cycle(&mut self, colors: &[Color], period: u32, frame: u64) {
+        self.pal_cycle = (colors.len() > 1).then(|| PalCycle {
+            colors: colors.to_vec(),
+            period: period.max(1),
+            start: frame,
+        });
+    }
+
+    pub(crate) fn pal_cycle_stop(&mut self) {
+        self.pal_cycle = None;
+    }
+
+    /// The screen palette on `frame`, with the cycle applied.
+    fn screen_palette(&self, frame: u64) -> [Color; 16] {
+        let mut palette = self.screen_palette;
+        if let Some(cycle) = &self.pal_cycle {
+            let len = cycle.colors.len();
+            let steps = frame.saturating_sub(cycle.start) / u64::from(cycle.period);
+            let step = (steps % len as u64) as usize;
+            for (i, &slot) in cycle.colors.iter().enumerate() {
+                if self.screen_pal_set & (1 << slot.index()) == 0 {
+                    palette[slot.index() as usize] = cycle.colors[(i + step) % len];
+                }
+            }
+        }
+
+        palette
+    }
+
     pub(crate) fn reset_pal(&mut self) {
         self.draw_palette = ORIGINAL_PALETTE;
+        self.screen_palette = ORIGINAL_PALETTE;
+        self.screen_pal_set = 0;
         // pal() resets transparency to default
-        self.palt(Some(0));
+        self.palt(Some(Color::BLACK));
     }
 
     pub(crate) fn palt(&mut self, transparent_color: Option<Color>) {
@@ -196,7 +535,7 @@ impl DrawData {
     //
     // See: https://github.com/egordorichev/pemsa/blob/master/src/pemsa/graphics/pemsa_graphics_api.cpp#L393
     pub(crate) fn circ(&mut self, cx: i32, cy: i32, radius: i32, color: Color) {
-        fn plot(this: &mut DrawData, cx: i32, cy: i32, x: i32, y: i32, c: u8) {
+        fn plot(this: &mut DrawData, cx: i32, cy: i32, x: i32, y: i32, c: Color) {
             let points = [
                 (x, y),
                 (-x, y),
@@ -221,7 +560,7 @@ impl DrawData {
     //
     // See: https://github.com/egordorichev/pemsa/blob/master/src/pemsa/graphics/pemsa_graphics_api.cpp#L393
     pub(crate) fn circfill(&mut self, cx: i32, cy: i32, radius: i32, color: Color) {
-        fn plot(this: &mut DrawData, cx: i32, cy: i32, x: i32, y: i32, c: u8) {
+        fn plot(this: &mut DrawData, cx: i32, cy: i32, x: i32, y: i32, c: Color) {
             this.line(cx - x, cy + y, cx + x, cy + y, c);
 
             if y != 0 {
@@ -232,12 +571,75 @@ impl DrawData {
         self.quarter_bresenham(cx, cy, radius, color, plot);
     }
 
+    /// Prints `str` with the current font, following the control codes in it
+    /// (see [`font::layout`]).
     pub(crate) fn print(&mut self, str: &str, x: i32, y: i32, color: Color) {
-        for (pos, char) in str.chars().enumerate() {
-            let index = char as u8 - font::FIRST_CHAR as u8;
+        let font = self.font.take();
+        self.print_with(font.as_ref().unwrap_or(Font::builtin()), str, x, y, color);
+        self.font = font;
+    }
+
+    /// Like `print`, with `font` instead of the current one.
+    pub(crate) fn print_with(&mut self, font: &Font, str: &str, x: i32, y: i32, color: Color) {
+        for glyph in font::layout(str, font) {
+            let color = glyph.color.map_or(color, Color::from);
+
+            self.print_char(font, glyph.char, x + glyph.x, y + glyph.y, color);
+        }
+    }
+
+    /// Like `print`, with a drop shadow in `shadow_color` one pixel down and to the right.
+    pub(crate) fn print_shadowed(
+        &mut self,
+        str: &str,
+        x: i32,
+        y: i32,
+        color: Color,
+        shadow_color: Color,
+    ) {
+        self.print_solid(str, x + 1, y + 1, shadow_color);
+        self.print(str, x, y, color);
+    }
+
+    /// Like `print`, surrounded by `outline_color` on all 8 sides.
+    pub(crate) fn print_outlined(
+        &mut self,
+        str: &str,
+        x: i32,
+        y: i32,
+        color: Color,
+        outline_color: Color,
+    ) {
+        for (dx, dy) in (-1..=1).cartesian_product(-1..=1) {
+            if (dx, dy) != (0, 0) {
+                self.print_solid(str, x + dx, y + dy, outline_color);
+            }
+        }
+        self.print(str, x, y, color);
+    }
+
+    // Like `print`, all in `color`: shadows and outlines ignore color codes.
+    fn print_solid(&mut self, str: &str, x: i32, y: i32, color: Color) {
+        let font = self.font.take();
+        let current = font.as_ref().unwrap_or(Font::builtin());
 
-            self.print_char(index as usize, x + (pos * 4) as i32, y, color);
+        for glyph in font::layout(str, current) {
+            self.print_char(current, glyph.char, x + glyph.x, y + glyph.y, color);
         }
+        self.font = font;
+    }
+
+    /// Prints every character of `str` as a glyph with the current font, on a single line.
+    pub(crate) fn print_raw(&mut self, str: &str, x: i32, y: i32, color: Color) {
+        let font = self.font.take();
+        let current = font.as_ref().unwrap_or(Font::builtin());
+
+        let mut cursor = x;
+        for char in str.chars() {
+            self.print_char(current, char, cursor + current.glyph_offset(char), y, color);
+            cursor += current.char_advance(char);
+        }
+        self.font = font;
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -252,17 +654,14 @@ impl DrawData {
         flip_x: bool,
         flip_y: bool,
     ) {
-        let buffer = &sprite.sprite;
-
-        for i in 0..8 {
-            for j in 0..8 {
-                let world_x = if flip_x { x + 7 - i } else { x + i };
-                let world_y = if flip_y { y + 7 - j } else { y + j };
-
-                let (x, y) = self.apply_camera(world_x, world_y);
-                if let Some(index) = self.index(x, y) {
-                    self.set_pixel_with_transparency(index, buffer[(i + j * 8) as usize])
-                }
+        for (i, j, color) in sprite.iter_pixels() {
+            let (i, j) = (i as i32, j as i32);
+            let world_x = if flip_x { x + 7 - i } else { x + i };
+            let world_y = if flip_y { y + 7 - j } else { y + j };
+
+            let (x, y) = self.apply_camera(world_x, world_y);
+            if let Some(index) = self.clipped_index(x, y) {
+                self.set_pixel_with_transparency(index, color)
             }
         }
     }
@@ -271,6 +670,37 @@ impl DrawData {
         self.spr_(sprite, x, y, 1.0, 1.0, false, false)
     }
 
+    /// Sprite `spr` of any sprite sheet, the game's or not. Nothing past the end of the sheet.
+    pub(crate) fn spr_from(&mut self, sprite_sheet: &SpriteSheet, spr: usize, x: i32, y: i32) {
+        if let Some(sprite) = sprite_sheet.try_get_sprite(spr) {
+            self.spr(sprite, x, y)
+        }
+    }
+
+    /// The `(x, y, width, height)` rectangle `from` of the sprite sheet,
+    /// stretched over the `to` rectangle of the screen.
+    pub(crate) fn sspr(
+        &mut self,
+        sprite_sheet: &SpriteSheet,
+        (sx, sy, sw, sh): (i32, i32, i32, i32),
+        (dx, dy, dw, dh): (i32, i32, i32, i32),
+    ) {
+        if dw <= 0 || dh <= 0 {
+            return;
+        }
+
+        for j in 0..dh {
+            for i in 0..dw {
+                let color = sprite_sheet.sget(sx + i * sw / dw, sy + j * sh / dh);
+
+                let (x, y) = self.apply_camera(dx + i, dy + j);
+                if let Some(index) = self.clipped_index(x, y) {
+                    self.set_pixel_with_transparency(index, color);
+                }
+            }
+        }
+    }
+
     pub(crate) fn cls_color(&mut self, color: Color) {
         self.rectfill(0, 0, 127, 127, color);
     }
@@ -287,22 +717,20 @@ impl DrawData {
         cell_h: i32,
         // TODO: Use layer
         layer: u8,
-        map: &Map,
-        flags: &Flags,
-        sprite_sheet: &SpriteSheet,
+        resources: &Resources,
     ) {
         // TODO: Handle like pico8
         for (i_x, map_x) in (cell_x..=(cell_x + cell_w)).enumerate() {
             for (i_y, map_y) in (cell_y..=(cell_y + cell_h)).enumerate() {
-                let spr = map.mget(map_x, map_y);
+                let spr = resources.mget(map_x, map_y);
 
-                let flags = flags.get(spr.into()).unwrap();
+                let flags = resources.fget(spr.into());
 
                 if flags & layer == layer {
                     let x = screen_x + 8 * i_x as i32;
                     let y = screen_y + 8 * i_y as i32;
 
-                    let spr = sprite_sheet.get_sprite(spr as usize);
+                    let spr = resources.sprite_sheet.get_sprite(spr as usize);
                     self.spr(spr, x, y);
                 }
             }
@@ -316,50 +744,259 @@ impl Default for DrawData {
     }
 }
 
-// Pico8 api
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_states_are_restored() {
+        let mut draw_data = DrawData::new();
+
+        draw_data.push_state();
+        draw_data.pal(Color::RED, Color::BLUE);
+        draw_data.camera(1, 1);
+
+        draw_data.push_state();
+        draw_data.palt(None);
+        draw_data.append_camera(2, 2);
+
+        draw_data.push_state();
+        draw_data.reset_pal();
+        draw_data.camera(0, 0);
+        draw_data.set_font(Some(Font::builtin().clone().monospaced()));
+        draw_data.pop_state();
+
+        assert_eq!(draw_data.font, None);
+        assert_eq!(draw_data.get_camera(), (3, 3));
+        assert_eq!(draw_data.transparent_color, None);
+        assert_eq!(
+            draw_data.draw_palette[Color::RED.index() as usize],
+            Color::BLUE
+        );
+
+        draw_data.pop_state();
+        assert_eq!(draw_data.get_camera(), (1, 1));
+        assert_eq!(draw_data.transparent_color, Some(Color::BLACK));
+        assert_eq!(
+            draw_data.draw_palette[Color::RED.index() as usize],
+            Color::BLUE
+        );
+
+        draw_data.pop_state();
+        assert_eq!(draw_data.get_camera(), (0, 0));
+        assert_eq!(draw_data.draw_palette, ORIGINAL_PALETTE);
+
+        // Unbalanced pops are ignored.
+        draw_data.pop_state();
+        assert_eq!(draw_data.draw_palette, ORIGINAL_PALETTE);
+    }
 
-fn get_color(index: Color) -> u32 {
-    COLORS[index as usize]
-}
+    #[cfg(feature = "editor")]
+    #[test]
+    fn extended_colors_are_only_shown() {
+        let mut draw_data = DrawData::new();
+        draw_data.camera(-1, 0);
+        draw_data.rectfill_extended(0, 0, 1, 0, 12);
+
+        assert_eq!(draw_data.pixel(0, 0), Some(Color::BLACK));
+        assert_eq!(draw_data.pixel(1, 0), Some(Color::BLUE));
+        let rgb = draw_data.rgb(Some(0), &Palette::pico8());
+        let (r, g, b) = color::extended_rgb(12);
+        assert_eq!(rgb[..9], [0, 0, 0, r, g, b, r, g, b]);
+    }
 
-// Add _FF at the end for alpha
-pub const COLORS: [u32; 16] = [
-    0x000000, //
-    0x1D2B53, //
-    0x7E2553, //
-    0x008751, //
-    0xAB5236, //
-    0x5F574F, //
-    0xC2C3C7, //
-    0xFFF1E8, //
-    0xFF004D, //
-    0xFFA300, //
-    0xFFEC27, //
-    0x00E436, //
-    0x29ADFF, //
-    0x83769C, //
-    0xFF77A8, //
-    0xFFCCAA, //
-];
+    #[test]
+    fn cycled_colors_rotate_every_period() {
+        let mut draw_data = DrawData::new();
+        let blues = [Color::DARK_BLUE, Color::BLUE, Color::LAVENDER];
+        draw_data.pal_cycle(&blues, 4, 10);
+
+        let shown = |draw_data: &DrawData, frame| {
+            let palette = draw_data.screen_palette(frame);
+            blues.map(|color| palette[color.index() as usize])
+        };
+        assert_eq!(shown(&draw_data, 10), blues);
+        assert_eq!(shown(&draw_data, 13), blues);
+        assert_eq!(
+            shown(&draw_data, 14),
+            [Color::BLUE, Color::LAVENDER, Color::DARK_BLUE]
+        );
+        assert_eq!(shown(&draw_data, 22), blues);
+        // Colors outside the cycle don't move.
+        assert_eq!(draw_data.screen_palette(14)[8], Color::RED);
+
+        draw_data.pal_cycle_stop();
+        assert_eq!(draw_data.screen_palette(14), ORIGINAL_PALETTE);
+    }
+
+    #[test]
+    fn screen_pal_wins_over_the_cycle() {
+        let mut draw_data = DrawData::new();
+        let blues = [Color::DARK_BLUE, Color::BLUE, Color::LAVENDER];
+        draw_data.pal_cycle(&blues, 1, 0);
+        draw_data.screen_pal(Color::BLUE, Color::BLUE);
+
+        let palette = draw_data.screen_palette(1);
+        assert_eq!(palette[Color::DARK_BLUE.index() as usize], Color::BLUE);
+        assert_eq!(palette[Color::BLUE.index() as usize], Color::BLUE);
+        assert_eq!(palette[Color::LAVENDER.index() as usize], Color::DARK_BLUE);
+
+        // Back to cycling once the screen palette is reset, the cycle isn't.
+        draw_data.reset_pal();
+        let palette = draw_data.screen_palette(1);
+        assert_eq!(palette[Color::BLUE.index() as usize], Color::LAVENDER);
+    }
+
+    fn is_empty(clip: ClipRect) -> bool {
+        !(0..WIDTH as i32)
+            .cartesian_product(0..WIDTH as i32)
+            .any(|(x, y)| clip.contains(x, y))
+    }
+
+    #[test]
+    fn pushed_clips_are_intersected() {
+        let screen = ClipRect::SCREEN;
+        let parent = ClipRect::new(10, 10, 20, 20);
+
+        assert_eq!(ClipRect::new(-5, 120, 20, 20), ClipRect::new(0, 120, 15, 8));
+        assert_eq!(
+            parent.intersect(ClipRect::new(20, 0, 100, 15)),
+            ClipRect::new(20, 10, 10, 5)
+        );
+        assert_eq!(parent.intersect(screen), parent);
+
+        let disjoint = parent.intersect(ClipRect::new(40, 0, 10, 10));
+        assert!(is_empty(disjoint));
+        assert!(is_empty(ClipRect::new(200, 200, 10, 10)));
+        assert!(is_empty(ClipRect::new(0, 0, -4, 10)));
+    }
+
+    #[test]
+    fn popping_restores_the_previous_clip() {
+        let mut draw_data = DrawData::new();
+
+        draw_data.clip_push(0, 0, 2, 2);
+        draw_data.clip_push(1, 1, 10, 10);
+        draw_data.rectfill(0, 0, 3, 3, Color::RED);
+        draw_data.clip_pop();
+        draw_data.rectfill(0, 0, 3, 3, Color::BLUE);
+
+        #[rustfmt::skip]
+        assert_eq!(colors(&draw_data, 3, 3), [
+            12, 12, 0,
+            12, 12, 0,
+            0, 0, 0,
+        ]);
+
+        draw_data.clip_push(5, 5, 1, 1);
+        draw_data.pset(0, 0, Color::RED);
+        draw_data.clip_pop();
+        draw_data.clip_pop();
+        assert_eq!(draw_data.clip, ClipRect::SCREEN);
+        draw_data.pset(2, 2, Color::RED);
+        assert_eq!(draw_data.pixel(2, 2), Some(Color::RED));
+        assert_eq!(draw_data.pixel(0, 0), Some(Color::BLUE));
+
+        // Unbalanced pops are ignored.
+        draw_data.clip_pop();
+        assert_eq!(draw_data.clip, ClipRect::SCREEN);
+    }
+
+    #[test]
+    fn pushed_cameras_add_up() {
+        let mut draw_data = DrawData::new();
+        draw_data.camera(10, 0);
+
+        draw_data.camera_push(-4, 2);
+        draw_data.camera_push(-6, 1);
+        assert_eq!(draw_data.get_camera(), (0, 3));
+        draw_data.pset(0, 0, Color::RED);
+
+        draw_data.camera(50, 50);
+        draw_data.camera_pop();
+        assert_eq!(draw_data.get_camera(), (6, 2));
+        draw_data.camera_pop();
+        assert_eq!(draw_data.get_camera(), (10, 0));
+
+        // Unbalanced pops are ignored.
+        draw_data.camera_pop();
+        assert_eq!(draw_data.get_camera(), (10, 0));
+        assert_eq!(colors(&draw_data, 1, 1), [0]);
+    }
+
+    #[test]
+    fn pushed_cameras_dont_move_the_clip() {
+        let mut draw_data = DrawData::new();
+        draw_data.clip_push(0, 0, 2, 1);
+        draw_data.camera_push(-1, 0);
+        draw_data.line(0, 0, 3, 0, Color::RED);
+        draw_data.camera_pop();
+        draw_data.clip_pop();
+
+        assert_eq!(colors(&draw_data, 3, 1), [0, 8, 0]);
+    }
+
+    #[test]
+    fn clip_ignores_the_camera() {
+        let mut draw_data = DrawData::new();
+        draw_data.camera(-2, 0);
+        draw_data.clip(0, 0, 3, 1);
+        draw_data.line(0, 0, 5, 0, Color::RED);
+
+        assert_eq!(colors(&draw_data, 4, 1), [0, 0, 8, 0]);
+    }
+
+    // Colors of the pixels in the `width` x `height` rectangle at the top left, row by row.
+    fn colors(draw_data: &DrawData, width: i32, height: i32) -> Vec<u8> {
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| draw_data.pixel(x, y).unwrap().index())
+            .collect()
+    }
+
+    #[test]
+    fn shadows_ignore_color_codes() {
+        let mut draw_data = DrawData::new();
+        draw_data.print_shadowed("\x0c8.", 0, 4, Color::WHITE, Color::BLUE);
+
+        assert_eq!(colors(&draw_data, 2, 2), [0, 0, 0, 0]);
+        assert_eq!(draw_data.pixel(0, 8), Some(Color::RED));
+        assert_eq!(draw_data.pixel(1, 9), Some(Color::BLUE));
+        assert_eq!(draw_data.pixel(1, 8), Some(Color::BLACK));
+    }
+
+    #[test]
+    fn outlines_surround_every_pixel() {
+        let mut draw_data = DrawData::new();
+        draw_data.print_outlined(".", 1, -3, Color::WHITE, Color::BLUE);
+
+        #[rustfmt::skip]
+        assert_eq!(colors(&draw_data, 4, 3), [
+            12, 12, 12, 0,
+            12, 7, 12, 0,
+            12, 12, 12, 0,
+        ]);
+    }
+}
 
 /// Named constants for the colors in the Pico8 palette.
 pub mod colors {
-    use crate::runtime::sprite_sheet::Color;
-
-    pub const BLACK: Color = 0;
-    pub const DARK_BLUE: Color = 1;
-    pub const DARK_PURPLE: Color = 2;
-    pub const DARK_GREEN: Color = 3;
-    pub const BROWN: Color = 4;
-    pub const DARK_GREY: Color = 5;
-    pub const LIGHT_GREY: Color = 6;
-    pub const WHITE: Color = 7;
-    pub const RED: Color = 8;
-    pub const ORANGE: Color = 9;
-    pub const YELLOW: Color = 10;
-    pub const GREEN: Color = 11;
-    pub const BLUE: Color = 12;
-    pub const LAVENDER: Color = 13;
-    pub const PINK: Color = 14;
-    pub const LIGHT_PEACH: Color = 15;
+    use crate::runtime::color::Color;
+
+    pub const BLACK: Color = Color::BLACK;
+    pub const DARK_BLUE: Color = Color::DARK_BLUE;
+    pub const DARK_PURPLE: Color = Color::DARK_PURPLE;
+    pub const DARK_GREEN: Color = Color::DARK_GREEN;
+    pub const BROWN: Color = Color::BROWN;
+    pub const DARK_GREY: Color = Color::DARK_GREY;
+    pub const LIGHT_GREY: Color = Color::LIGHT_GREY;
+    pub const WHITE: Color = Color::WHITE;
+    pub const RED: Color = Color::RED;
+    pub const ORANGE: Color = Color::ORANGE;
+    pub const YELLOW: Color = Color::YELLOW;
+    pub const GREEN: Color = Color::GREEN;
+    pub const BLUE: Color = Color::BLUE;
+    pub const LAVENDER: Color = Color::LAVENDER;
+    pub const PINK: Color = Color::PINK;
+    pub const LIGHT_PEACH: Color = Color::LIGHT_PEACH;
 }
@@ -1,12 +1,15 @@
 use std::fmt::Display;
+use std::io::{self, Write};
+use std::ops::Range;
 
 use itertools::Itertools;
 
-use crate::editor::serialize::Serialize;
 use crate::runtime::sprite_sheet::SpriteSheet;
+use crate::serialize::{split_version, version_header, Serialize};
 
+/// Eight flags per sprite, to tag them for the game's logic (solid, deadly, ...).
 #[derive(Debug)]
-pub(crate) struct Flags {
+pub struct Flags {
     flags: [u8; SpriteSheet::SPRITE_COUNT],
 }
 
@@ -39,13 +42,29 @@ impl Flags {
         Self { flags }
     }
 
-    fn len(&self) -> usize {
-        self.flags.len()
+    /// Flags from their raw bitfields, one per sprite (see [`Flags::as_slice`]).
+    pub fn from_slice(flags: &[u8]) -> Result<Self, String> {
+        let flags = flags.try_into().map_err(|_| {
+            format!(
+                "Expected flags for {} sprites, got {}",
+                SpriteSheet::SPRITE_COUNT,
+                flags.len()
+            )
+        })?;
+
+        Ok(Self::with_flags(flags))
+    }
+
+    /// Rust source for a `pub static` array named `ident` with every sprite's flags,
+    /// to embed in a game and load with [`Flags::from_slice`].
+    pub fn to_rust_source(&self, ident: &str) -> String {
+        crate::serialize::rust_array(ident, &self.flags, 16)
     }
 
-    #[allow(clippy::only_used_in_recursion)] // clippy is wrong
     fn set(&mut self, index: usize, value: u8) {
-        self.flags[index] = value;
+        if let Some(flags) = self.flags.get_mut(index) {
+            *flags = value;
+        }
     }
 
     pub fn get(&self, index: usize) -> Option<u8> {
@@ -53,50 +72,101 @@ impl Flags {
         self.flags.get(index).copied()
     }
 
+    /// Every sprite with flags and its bitfield, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, u8)> + '_ {
+        self.flags.iter().copied().enumerate()
+    }
+
+    /// The raw bitfields, one per sprite.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.flags
+    }
+
+    /// How many sprites have `flag` set, 0 for flags above 7.
+    pub fn count_with_flag(&self, flag: u8) -> usize {
+        self.iter()
+            .filter(|&(_, flags)| flag <= 7 && flags & (1 << flag) != 0)
+            .count()
+    }
+
+    /// Sets all the flags of every sprite in `sprites` to `flags`.
+    /// The part of the range past the last sprite with flags is ignored.
+    pub fn set_range(&mut self, sprites: Range<usize>, flags: u8) {
+        let end = sprites.end.min(self.flags.len());
+        let start = sprites.start.min(end);
+
+        self.flags[start..end].fill(flags);
+    }
+
+    /// Sprites without flags (past the first 256) count as having none.
+    #[cfg(feature = "editor")]
+    pub(crate) fn swap(&mut self, a: usize, b: usize) {
+        let (flags_a, flags_b) = (self.fget(a), self.fget(b));
+        self.set(a, flags_b);
+        self.set(b, flags_a);
+    }
+
+    // Pico8's fget(n)
+    /// All the flags of a sprite as a bitfield, 0 for sprites that don't exist.
+    pub fn fget(&self, sprite: usize) -> u8 {
+        self.get(sprite).unwrap_or(0)
+    }
+
     // Pico8's fset(n, v)
+    /// Sets all the flags of a sprite at once, returning them.
+    /// Does nothing for sprites that don't exist.
     pub fn fset_all(&mut self, sprite: usize, flags: u8) -> u8 {
         self.set(sprite, flags);
 
-        flags
+        self.fget(sprite)
     }
 
     // Pico8's fset(n, f, v)
+    /// Returns the sprite's new flags.
+    /// Does nothing for sprites that don't exist, or flags above 7.
     pub fn fset(&mut self, sprite: usize, flag: usize, value: bool) -> u8 {
-        // TODO: Check what pico8 does in these cases:
-        assert!(flag <= 7);
+        let flags = self.fget(sprite);
+        if flag > 7 {
+            return flags;
+        }
 
         let value = value as u8;
-        let mut flags = self.get(sprite).unwrap();
-        flags = (flags & !(1u8 << flag)) | (value << flag);
+        let flags = (flags & !(1u8 << flag)) | (value << flag);
 
-        self.set(sprite, flags);
-
-        flags
+        self.fset_all(sprite, flags)
     }
 
+    /// False for sprites that don't exist, or flags above 7.
     pub fn fget_n(&self, sprite: usize, flag: u8) -> bool {
-        // TODO: Check what pico8 does in these cases:
-        assert!(sprite < self.len());
-        assert!(flag <= 7);
-
-        let res = (self.get(sprite).unwrap() & (1 << flag)) >> flag;
-        assert!(res == 0 || res == 1);
-
-        res != 0
+        flag <= 7 && self.fget(sprite) & (1 << flag) != 0
     }
 
     pub fn deserialize(file_contents: &str) -> Result<Self, String> {
-        // let flags_vec: Result<Vec<u8>, String> = file_contents
-        let flags_vec: Result<Vec<u8>, String> = file_contents
-            .lines()
-            .map(
-                |line| u8::from_str_radix(line, 2), //  line.parse::<u8>().map_err(|e| format!("{:?}", e))
-            )
-            .collect::<Result<Vec<u8>, _>>()
-            .map_err(|err| format!("{:?}", err));
+        let flags_vec = match split_version(file_contents)? {
+            // One binary number per line.
+            (1, contents) => contents
+                .lines()
+                .map(|line| u8::from_str_radix(line, 2))
+                .collect::<Result<Vec<u8>, _>>()
+                .map_err(|err| format!("{:?}", err))?,
+            // Hex, two digits per sprite, like Pico8's __gff__ section.
+            (2, contents) => {
+                let digits: Vec<char> = contents.chars().filter(|c| !c.is_whitespace()).collect();
+
+                digits
+                    .chunks(2)
+                    .map(|pair| {
+                        let pair: String = pair.iter().collect();
+
+                        u8::from_str_radix(&pair, 16).map_err(|_| format!("Invalid flags: {pair}"))
+                    })
+                    .collect::<Result<Vec<u8>, _>>()?
+            }
+            (version, _) => return Err(format!("Unsupported flags version {version}")),
+        };
 
         let flags_array: [u8; SpriteSheet::SPRITE_COUNT] =
-            flags_vec?.try_into().map_err(|v: Vec<u8>| {
+            flags_vec.try_into().map_err(|v: Vec<u8>| {
                 format!(
                     "Incorrect number of elements, needed: {}, got: {}",
                     SpriteSheet::SPRITE_COUNT,
@@ -108,12 +178,24 @@ impl Flags {
     }
 }
 
+impl Flags {
+    // Version 1 files have no header, and one flag byte (in binary) per line.
+    // Version 2 files use the same layout as Pico8's __gff__ section.
+    const VERSION: u32 = 2;
+    const SPRITES_PER_LINE: usize = 128;
+}
+
 impl Serialize for Flags {
-    fn serialize(&self) -> String {
-        self.flags
-            .iter()
-            .map(|flag| format!("{:0>8b}", flag))
-            .join("\n")
+    fn serialize(&self, writer: &mut dyn Write) -> io::Result<()> {
+        write!(writer, "{}", version_header(Self::VERSION))?;
+        for chunk in self.flags.chunks(Self::SPRITES_PER_LINE) {
+            writeln!(writer)?;
+            for flags in chunk {
+                write!(writer, "{:02x}", flags)?;
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -122,3 +204,98 @@ impl Default for Flags {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialize::to_string;
+
+    #[test]
+    fn whole_bitfield_and_single_flags_agree() {
+        let mut flags = Flags::new();
+
+        flags.fset_all(3, 0b1000_0010);
+        assert_eq!(flags.fget(3), 0b1000_0010);
+        assert!(flags.fget_n(3, 1));
+        assert!(flags.fget_n(3, 7));
+        assert!(!flags.fget_n(3, 0));
+
+        assert_eq!(flags.fset(3, 0, true), 0b1000_0011);
+        assert_eq!(flags.fset(3, 7, false), 0b0000_0011);
+        assert_eq!(flags.fget(3), 0b0000_0011);
+    }
+
+    #[test]
+    fn out_of_range_is_ignored() {
+        let mut flags = Flags::new();
+
+        assert_eq!(flags.fset_all(SpriteSheet::SPRITE_COUNT, 0xFF), 0);
+        assert_eq!(flags.fset(1000, 0, true), 0);
+        assert_eq!(flags.fset(0, 8, true), 0);
+        assert_eq!(flags.fget(1000), 0);
+        assert!(!flags.fget_n(1000, 0));
+        assert!(!flags.fget_n(0, 8));
+        assert!((0..SpriteSheet::SPRITE_COUNT).all(|sprite| flags.fget(sprite) == 0));
+    }
+
+    #[test]
+    fn bulk_operations() {
+        let mut flags = Flags::new();
+
+        flags.set_range(2..5, 0b0000_0101);
+        flags.fset_all(10, 0b0000_0001);
+        assert_eq!(flags.count_with_flag(0), 4);
+        assert_eq!(flags.count_with_flag(2), 3);
+        assert_eq!(flags.count_with_flag(1), 0);
+        assert_eq!(flags.count_with_flag(8), 0);
+
+        let tagged: Vec<_> = flags.iter().filter(|&(_, flags)| flags != 0).collect();
+        assert_eq!(tagged, [(2, 5), (3, 5), (4, 5), (10, 1)]);
+        assert_eq!(flags.iter().count(), SpriteSheet::SPRITE_COUNT);
+
+        let copy = Flags::from_slice(flags.as_slice()).unwrap();
+        assert_eq!(copy.as_slice(), flags.as_slice());
+        assert!(Flags::from_slice(&[0; 3]).is_err());
+    }
+
+    #[test]
+    fn set_range_stops_at_the_last_sprite() {
+        let mut flags = Flags::new();
+
+        flags.set_range(250..1000, 0xFF);
+        assert_eq!(flags.count_with_flag(7), 6);
+        flags.set_range(1000..2000, 0x01);
+        // Reversed, so empty.
+        flags.set_range(std::ops::Range { start: 5, end: 2 }, 0x01);
+        assert_eq!(flags.count_with_flag(0), 6);
+    }
+
+    #[test]
+    fn serializes_like_gff() {
+        let mut flags = Flags::new();
+        flags.fset_all(0, 0x01);
+        flags.fset_all(1, 0xA3);
+        flags.fset_all(255, 0xFF);
+
+        let serialized = to_string(&flags);
+        let lines: Vec<&str> = serialized.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "version 2");
+        assert_eq!(lines[1].len(), 256);
+        assert!(lines[1].starts_with("01a300"));
+        assert!(lines[2].ends_with("00ff"));
+
+        let deserialized = Flags::deserialize(&serialized).unwrap();
+        assert_eq!(deserialized.flags, flags.flags);
+    }
+
+    #[test]
+    fn reads_unversioned_flags() {
+        let mut legacy = vec!["00000000"; SpriteSheet::SPRITE_COUNT];
+        legacy[2] = "10000001";
+
+        let flags = Flags::deserialize(&legacy.join("\n")).unwrap();
+        assert_eq!(flags.fget(2), 0b1000_0001);
+        assert_eq!(flags.fget(3), 0);
+    }
+}
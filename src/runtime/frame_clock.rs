@@ -0,0 +1,60 @@
+//! Decides how many updates to run for the time that passed, so that games keep their speed
+//! when a frame comes late (the window stalled, say).
+
+/// Length of an update, at 30 updates per second.
+pub(crate) const FRAME_MILLIS: f64 = 1000.0 / 30.0;
+
+// Most updates run in a row to catch up, the rest are dropped.
+const MAX_CATCH_UP_UPDATES: u32 = 4;
+
+#[derive(Debug)]
+pub(crate) struct FrameClock {
+    // Time that passed without an update for it yet.
+    pending_millis: f64,
+}
+
+impl FrameClock {
+    pub(crate) fn new() -> Self {
+        Self {
+            pending_millis: 0.0,
+        }
+    }
+
+    /// Adds the time since the last call, returning how many updates to run now,
+    /// and how many were dropped for being too far behind.
+    pub(crate) fn advance(&mut self, elapsed_millis: f64) -> (u32, u32) {
+        self.pending_millis += elapsed_millis.max(0.0);
+
+        let due = (self.pending_millis / FRAME_MILLIS).floor() as u32;
+        self.pending_millis -= due as f64 * FRAME_MILLIS;
+        let updates = due.min(MAX_CATCH_UP_UPDATES);
+
+        (updates, due - updates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn late_frames_catch_up() {
+        let mut clock = FrameClock::new();
+
+        assert_eq!(clock.advance(FRAME_MILLIS), (1, 0));
+        // Early, the update waits for the rest of its time.
+        assert_eq!(clock.advance(FRAME_MILLIS / 2.0), (0, 0));
+        assert_eq!(clock.advance(FRAME_MILLIS / 2.0 + 50.0), (2, 0));
+        assert_eq!(clock.advance(FRAME_MILLIS / 2.0), (1, 0));
+    }
+
+    #[test]
+    fn catching_up_is_bounded() {
+        let mut clock = FrameClock::new();
+
+        assert_eq!(clock.advance(FRAME_MILLIS * 10.5), (4, 6));
+        // Only the leftover half frame is kept.
+        assert_eq!(clock.advance(FRAME_MILLIS * 0.4), (0, 0));
+        assert_eq!(clock.advance(FRAME_MILLIS * 0.2), (1, 0));
+    }
+}
@@ -1,31 +1,62 @@
 use crate::{Key, KeyState, KeyboardEvent};
 
+/// A key (or mouse button) as of the latest events.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct KeyInput {
+    // Whether it's down, `None` until it's been pressed or released once.
+    pub(crate) down: Option<bool>,
+    // Whether it went down since the last update, even if it's been released since.
+    pub(crate) pressed: bool,
+}
+
+impl KeyInput {
+    pub(crate) fn set(&mut self, down: bool) {
+        // Repeated presses from holding the key down don't count as new ones.
+        if down && self.down != Some(true) {
+            self.pressed = true;
+        }
+        self.down = Some(down);
+    }
+
+    /// The input for this update, forgetting its presses so the next update doesn't see them again.
+    pub(crate) fn take(&mut self) -> Self {
+        let input = *self;
+        self.pressed = false;
+
+        input
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct Keys {
-    pub(crate) left: Option<bool>,
-    pub(crate) right: Option<bool>,
-    pub(crate) up: Option<bool>,
-    pub(crate) down: Option<bool>,
-    pub(crate) x: Option<bool>,
-    pub(crate) c: Option<bool>,
-    pub(crate) mouse: Option<bool>,
+    pub(crate) left: KeyInput,
+    pub(crate) right: KeyInput,
+    pub(crate) up: KeyInput,
+    pub(crate) down: KeyInput,
+    pub(crate) x: KeyInput,
+    pub(crate) c: KeyInput,
+    pub(crate) mouse: KeyInput,
+    pub(crate) mouse_right: KeyInput,
+    pub(crate) mouse_middle: KeyInput,
 }
 
 impl Keys {
     pub(crate) fn new() -> Self {
         Self {
-            left: None,
-            right: None,
-            up: None,
-            down: None,
-            x: None,
-            c: None,
-            mouse: None,
+            left: KeyInput::default(),
+            right: KeyInput::default(),
+            up: KeyInput::default(),
+            down: KeyInput::default(),
+            x: KeyInput::default(),
+            c: KeyInput::default(),
+            mouse: KeyInput::default(),
+            mouse_right: KeyInput::default(),
+            mouse_middle: KeyInput::default(),
         }
     }
 
     pub(crate) fn on_event(&mut self, event: KeyboardEvent) {
-        let mut other = None;
+        let mut other = KeyInput::default();
         let key_ref = match event.key {
             Key::X => &mut self.x,
             Key::C => &mut self.c,
@@ -35,6 +66,6 @@ impl Keys {
             Key::DownArrow => &mut self.down,
             _ => &mut other,
         };
-        *key_ref = Some(event.state == KeyState::Down);
+        key_ref.set(event.state == KeyState::Down);
     }
 }
@@ -1,14 +1,20 @@
-use crate::editor::serialize::Serialize;
+use crate::serialize::{
+    first_line_of, read_grid, split_version, version_header, Recovery, Serialize,
+};
+use std::io::{self, Write};
 
 use super::sprite_sheet::Sprite;
 use itertools::Itertools;
 
 type SpriteId = u8;
 
+/// The game's map: one sprite number per cell.
 #[derive(Debug, Clone)]
-pub(crate) struct Map {
-    // Don't really want the size to change
-    pub(crate) map: [SpriteId; Self::MAP_SIZE],
+pub struct Map {
+    width: usize,
+    height: usize,
+    // One sprite per cell, row by row.
+    pub(crate) map: Vec<SpriteId>,
 }
 
 impl Map {
@@ -23,44 +29,84 @@ impl Map {
     const SCREENS_HEIGHT: usize = 4; // map is 4 screens tall (actually 2, bottom 2 are shared with spritesheet)
 
     const SPRITES_PER_SCREEN_ROW: usize = Self::SCREEN_SIZE_PIXELS / Sprite::WIDTH;
+    /// Size of the Pico8 map, in sprites.
     pub const WIDTH_SPRITES: usize = Self::SCREENS_WIDTH * Self::SPRITES_PER_SCREEN_ROW;
     pub const HEIGHT_SPRITES: usize = Self::SCREENS_HEIGHT * Self::SPRITES_PER_SCREEN_ROW;
-    const MAP_SIZE: usize = Self::WIDTH_SPRITES * Self::HEIGHT_SPRITES;
+
+    // Version 1 files have no header, and are always Pico8 sized.
+    // Version 2 adds the width and height (in sprites) on the line after the header.
+    const VERSION: u32 = 2;
 
     // TODO: Make pub(crate)
     pub fn new() -> Self {
-        let mut map = [0; Self::MAP_SIZE];
+        let mut map = Self::with_size(Self::WIDTH_SPRITES, Self::HEIGHT_SPRITES);
+
+        map.map[0] = 1;
+        map.map[1] = 1;
+        map.map[2] = 1;
 
-        map[0] = 1;
-        map[1] = 1;
-        map[2] = 1;
+        map
+    }
 
-        Map { map }
+    /// An empty map of `width` by `height` sprites, see [`crate::Resources::set_map`].
+    /// Maps of other sizes than Pico8's won't work in Pico8.
+    pub fn with_size(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            map: vec![0; width * height],
+        }
     }
 
-    pub(crate) fn mget(&self, cel_x: i32, cel_y: i32) -> u8 {
-        let index = Self::index(cel_x, cel_y);
+    /// Width in sprites.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Height in sprites.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Sprite in cell (`cel_x`, `cel_y`), 0 outside of the map.
+    ///
+    /// Reads the map's own data, even for the rows shared with the sprite sheet,
+    /// see [`crate::Resources::mget`] to read those like Pico8 does.
+    pub fn mget(&self, cel_x: i32, cel_y: i32) -> u8 {
+        let index = self.index(cel_x, cel_y);
 
         // TODO: Handle like pico8
         // TODO2: I think it returns 0 if outside bounds?
         index.map(|index| self.map[index]).unwrap_or(0)
     }
 
-    pub(crate) fn mset(&mut self, cel_x: usize, cel_y: usize, sprite: u8) {
-        let index = cel_x + cel_y * Map::WIDTH_SPRITES;
-        // TODO: Handle like pico8
-        assert!(index <= self.map.len());
+    /// Writes outside of the map are ignored.
+    ///
+    /// Writes the map's own data, see [`crate::Resources::mset`] to write it like Pico8 does.
+    pub fn mset(&mut self, cel_x: i32, cel_y: i32, sprite: u8) {
+        if let Some(index) = self.index(cel_x, cel_y) {
+            self.map[index] = sprite;
+        }
+    }
+
+    /// In Pico8, rows 32 to 63 of the map are stored in the lower half of the sprite sheet.
+    /// Returns where (x, y) lives in that shared memory, or `None` if it isn't shared.
+    pub(crate) fn shared_offset(x: i32, y: i32) -> Option<usize> {
+        const FIRST_SHARED_ROW: i32 = Map::HEIGHT_SPRITES as i32 / 2;
+
+        let x_in_bounds = (0..Self::WIDTH_SPRITES as i32).contains(&x);
+        let y_in_bounds = (FIRST_SHARED_ROW..Self::HEIGHT_SPRITES as i32).contains(&y);
 
-        self.map[index] = sprite;
+        if x_in_bounds && y_in_bounds {
+            Some((x + (y - FIRST_SHARED_ROW) * Self::WIDTH_SPRITES as i32) as usize)
+        } else {
+            None
+        }
     }
 
-    fn index(x: i32, y: i32) -> Option<usize> {
-        if x >= 0
-            && (x as usize) < Map::WIDTH_SPRITES
-            && y >= 0
-            && (y as usize) < Map::HEIGHT_SPRITES
-        {
-            Some(x as usize + y as usize * Map::WIDTH_SPRITES)
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x >= 0 && (x as usize) < self.width && y >= 0 && (y as usize) < self.height {
+            Some(x as usize + y as usize * self.width)
         } else {
             None
         }
@@ -68,28 +114,98 @@ impl Map {
 }
 
 impl Map {
-    // TODO: Make sure this works
-    pub(crate) fn deserialize(str: &str) -> Result<Self, String> {
-        let map: [SpriteId; Self::MAP_SIZE] = str
-            .split_ascii_whitespace()
-            .map(|num| u8::from_str_radix(num, 16).unwrap())
-            .collect::<Vec<_>>()
-            .try_into()
-            .map_err(|error: Vec<u8>| format!("Error deserializing map {}", error.len()))?;
+    /// Reads a `map.txt` file, for build scripts that embed it with [`Map::to_rust_source`].
+    /// Fails on maps with any row too long or too short, or the wrong number of rows,
+    /// saying which line.
+    pub fn deserialize(str: &str) -> Result<Self, String> {
+        Self::deserialize_with(str, &mut Recovery::Strict)
+    }
+
+    /// Like [`Map::deserialize`], fixing maps with missing or extra cells if `recovery` says so.
+    pub(crate) fn deserialize_with(str: &str, recovery: &mut Recovery) -> Result<Self, String> {
+        let (width, height, cells) = match split_version(str)? {
+            (1, cells) => (Self::WIDTH_SPRITES, Self::HEIGHT_SPRITES, cells),
+            (2, rest) => {
+                let (size, cells) = rest.split_once('\n').unwrap_or((rest, ""));
+                let (width, height) = size
+                    .split_ascii_whitespace()
+                    .map(str::parse)
+                    .collect_tuple()
+                    .and_then(|(width, height)| Some((width.ok()?, height.ok()?)))
+                    .ok_or_else(|| format!("Invalid map size: {size}"))?;
+
+                (width, height, cells)
+            }
+            (version, _) => return Err(format!("Unsupported map version {version}")),
+        };
+
+        // One row per line.
+        let map = read_grid(
+            cells,
+            (width, height),
+            first_line_of(str, cells),
+            |line| {
+                line.split_ascii_whitespace()
+                    .map(|num| {
+                        u8::from_str_radix(num, 16).map_err(|_| format!("Invalid sprite: {num}"))
+                    })
+                    .collect()
+            },
+            recovery,
+        )?;
+
+        Self::from_raw(width, height, &map)
+    }
+
+    /// A `width` by `height` map from its cells, row by row (see [`Map::to_rust_source`]).
+    pub fn from_raw(width: usize, height: usize, cells: &[u8]) -> Result<Self, String> {
+        if cells.len() != width * height {
+            return Err(format!(
+                "Error deserializing map: expected {width}x{height} sprites, got {}",
+                cells.len()
+            ));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            map: cells.to_vec(),
+        })
+    }
 
-        Ok(Self { map })
+    /// Rust source for a `pub static` array named `ident` with the map's cells, one row per line,
+    /// and `{ident}_WIDTH`/`{ident}_HEIGHT` constants with its size.
+    /// Meant to be embedded in a game, and loaded with [`Map::from_raw`].
+    pub fn to_rust_source(&self, ident: &str) -> String {
+        format!(
+            "pub const {ident}_WIDTH: usize = {};\npub const {ident}_HEIGHT: usize = {};\n{}",
+            self.width,
+            self.height,
+            crate::serialize::rust_array(ident, &self.map, self.width)
+        )
     }
 }
 
 impl Serialize for Map {
-    // TODO: Make sure this works
-    fn serialize(&self) -> String {
-        self.map
-            .iter()
-            .chunks(Map::WIDTH_SPRITES)
-            .into_iter()
-            .map(|chunk| chunk.map(|n| format!("{:0>2X}", n)).join(" "))
-            .join("\n")
+    fn serialize(&self, writer: &mut dyn Write) -> io::Result<()> {
+        write!(
+            writer,
+            "{}\n{} {}\n",
+            version_header(Self::VERSION),
+            self.width,
+            self.height
+        )?;
+        for (index, row) in self.map.chunks(self.width).enumerate() {
+            if index > 0 {
+                writeln!(writer)?;
+            }
+            for (column, n) in row.iter().enumerate() {
+                let separator = if column > 0 { " " } else { "" };
+                write!(writer, "{separator}{:0>2X}", n)?;
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -98,3 +214,95 @@ impl Default for Map {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialize::to_string;
+
+    #[test]
+    fn reads_and_writes_inside_bounds() {
+        let mut map = Map::with_size(256, 64);
+        assert_eq!((map.width(), map.height()), (256, 64));
+
+        map.mset(255, 63, 7);
+        map.mset(256, 0, 8);
+        map.mset(0, 64, 8);
+        map.mset(-1, 0, 8);
+
+        assert_eq!(map.mget(255, 63), 7);
+        assert_eq!(map.mget(256, 0), 0);
+        assert!(map.map.iter().all(|&sprite| sprite == 0 || sprite == 7));
+    }
+
+    #[test]
+    fn serialization_keeps_the_size() {
+        let mut map = Map::with_size(256, 64);
+        map.mset(200, 40, 0x3A);
+
+        let serialized = to_string(&map);
+        assert!(serialized.starts_with("version 2\n256 64\n"));
+
+        let deserialized = Map::deserialize(&serialized).unwrap();
+        assert_eq!((deserialized.width(), deserialized.height()), (256, 64));
+        assert_eq!(deserialized.mget(200, 40), 0x3A);
+        assert_eq!(deserialized.map, map.map);
+    }
+
+    #[test]
+    fn reads_unversioned_maps() {
+        let row = vec!["01"; Map::WIDTH_SPRITES].join(" ");
+        let legacy = vec![row; Map::HEIGHT_SPRITES].join("\n");
+
+        let map = Map::deserialize(&legacy).unwrap();
+        assert_eq!((map.width(), map.height()), (128, 64));
+        assert_eq!(map.mget(127, 63), 1);
+
+        assert!(Map::deserialize("01 02").is_err());
+        assert!(Map::deserialize("version 2\n2 1\n01").is_err());
+        assert!(Map::deserialize("version 3\n").is_err());
+    }
+
+    const FIXTURE: &str = include_str!("../test_data/golden/map.txt");
+
+    #[test]
+    fn maps_with_the_wrong_dimensions_are_rejected() {
+        let lines: Vec<_> = FIXTURE.lines().collect();
+        let one_line_short = lines[..lines.len() - 1].join("\n");
+        let one_cell_long = FIXTURE
+            .replacen('\n', " 00\n", 3)
+            .replacen(" 00\n", "\n", 2);
+
+        assert!(Map::deserialize(FIXTURE).is_ok());
+        for (map, error) in [
+            (one_line_short.as_str(), "63 lines, needed 64"),
+            (&one_cell_long, "Line 3 has 129 values, needed 128"),
+            ("", "0 lines, needed 64"),
+            ("version 2\n2 2\n01 02\n03", "Line 4 has 1 values, needed 2"),
+            ("version 2\n1 1\nXX", "Line 3: Invalid sprite: XX"),
+        ] {
+            assert_eq!(Map::deserialize(map).unwrap_err(), error);
+        }
+    }
+
+    #[test]
+    fn broken_maps_can_be_padded_and_truncated() {
+        let mut warnings = vec![];
+        let mut recovery = Recovery::PadAndTruncate(&mut warnings);
+
+        let map = Map::deserialize_with("version 2\n3 2\n01 02\n03 04 05 06", &mut recovery);
+        assert_eq!(map.unwrap().map, [1, 2, 0, 3, 4, 5]);
+        let map = Map::deserialize_with("", &mut recovery).unwrap();
+        assert_eq!((map.width(), map.height()), (128, 64));
+        assert!(map.map.iter().all(|&sprite| sprite == 0));
+
+        assert_eq!(
+            warnings,
+            [
+                "Line 3 has 2 values, needed 3, padded with zeros",
+                "Line 4 has 4 values, needed 3, dropped the rest",
+                "0 lines, needed 64, padded with zeros",
+            ]
+        );
+    }
+}
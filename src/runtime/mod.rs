@@ -1,6 +1,9 @@
+pub(crate) mod color;
 pub(crate) mod draw_data;
 pub(crate) mod flags;
+pub(crate) mod frame_clock;
 pub(crate) mod input;
 pub(crate) mod map;
+pub(crate) mod palette;
 pub(crate) mod sprite_sheet;
 pub mod state;
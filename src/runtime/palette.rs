@@ -0,0 +1,142 @@
+use std::io::{self, Write};
+
+use crate::runtime::color::Color;
+use crate::serialize::{split_version, version_header, Serialize};
+
+/// The red, green and blue components each of the 16 colors is displayed with.
+///
+/// Drawing still uses the 16 colors: only what they look like on the screen
+/// (and in exported images) changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    colors: [(u8, u8, u8); 16],
+}
+
+impl Palette {
+    // One `RRGGBB` hex color per line, in palette order.
+    const VERSION: u32 = 1;
+
+    pub(crate) fn file_name() -> String {
+        "palette.txt".to_owned()
+    }
+
+    /// Pico8's own colors, the default.
+    pub fn pico8() -> Self {
+        let mut colors = [(0, 0, 0); 16];
+        for color in Color::all() {
+            colors[color.index() as usize] = color.rgb();
+        }
+
+        Self { colors }
+    }
+
+    pub fn rgb(&self, color: impl Into<Color>) -> (u8, u8, u8) {
+        self.colors[color.into().index() as usize]
+    }
+
+    pub fn set_rgb(&mut self, color: impl Into<Color>, rgb: (u8, u8, u8)) {
+        self.colors[color.into().index() as usize] = rgb;
+    }
+
+    /// Whether every color is the same as in [`Palette::pico8`].
+    pub fn is_pico8(&self) -> bool {
+        *self == Self::pico8()
+    }
+
+    /// `RRGGBB`, like `FF004D`.
+    pub(crate) fn to_hex((r, g, b): (u8, u8, u8)) -> String {
+        format!("{r:02X}{g:02X}{b:02X}")
+    }
+
+    /// Reads `RRGGBB` (or `#RRGGBB`), in either case.
+    pub(crate) fn parse_hex(hex: &str) -> Option<(u8, u8, u8)> {
+        let hex = hex.trim();
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+
+        let component = |range| u8::from_str_radix(&hex[range], 16).ok();
+
+        Some((component(0..2)?, component(2..4)?, component(4..6)?))
+    }
+
+    /// A palette saved by the editor, in `palette.txt`.
+    /// Games can embed the file and load it with [`Pico8::set_palette`](crate::Pico8::set_palette).
+    pub fn deserialize(file_contents: &str) -> Result<Self, String> {
+        let lines = match split_version(file_contents)? {
+            (1, lines) => lines,
+            (version, _) => return Err(format!("Unsupported palette version {version}")),
+        };
+
+        let colors: Vec<(u8, u8, u8)> = lines
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Self::parse_hex(line).ok_or_else(|| format!("Invalid color: {line}")))
+            .collect::<Result<_, _>>()?;
+
+        let colors = colors.try_into().map_err(|colors: Vec<_>| {
+            format!(
+                "Incorrect number of colors, needed: 16, got: {}",
+                colors.len()
+            )
+        })?;
+
+        Ok(Self { colors })
+    }
+}
+
+impl Serialize for Palette {
+    fn serialize(&self, writer: &mut dyn Write) -> io::Result<()> {
+        write!(writer, "{}", version_header(Self::VERSION))?;
+        for &rgb in &self.colors {
+            write!(writer, "\n{}", Self::to_hex(rgb))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::pico8()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialize::to_string;
+
+    #[test]
+    fn palette_round_trip() {
+        let mut palette = Palette::pico8();
+        palette.set_rgb(Color::RED, (0x12, 0xAB, 0x00));
+
+        assert_eq!(Palette::deserialize(&to_string(&palette)), Ok(palette));
+        assert!(!palette.is_pico8());
+        assert!(Palette::pico8().is_pico8());
+    }
+
+    #[test]
+    fn corrupt_palettes_are_rejected() {
+        let fifteen_colors = "version 1\n".to_owned() + &"000000\n".repeat(15);
+
+        for corrupt in [
+            fifteen_colors.as_str(),
+            "version 1\nFF004",
+            "version 1\nGG0000",
+            "version 2\n",
+        ] {
+            assert!(Palette::deserialize(corrupt).is_err(), "{corrupt}");
+        }
+    }
+
+    #[test]
+    fn hex_colors() {
+        assert_eq!(Palette::parse_hex("#ff004d"), Some((0xFF, 0x00, 0x4D)));
+        assert_eq!(Palette::parse_hex("FF004D"), Some((0xFF, 0x00, 0x4D)));
+        assert_eq!(Palette::parse_hex("FF0"), None);
+        assert_eq!(Palette::to_hex((0xFF, 0x00, 0x4D)), "FF004D");
+    }
+}
@@ -1,12 +1,15 @@
-pub type Color = u8; // Actually a u4
+use super::color::Color;
 
-use itertools::Itertools;
-
-use crate::editor::serialize::Serialize;
+use crate::serialize::{
+    first_line_of, read_grid, split_version, version_header, Recovery, Serialize,
+};
+use std::io::{self, Write};
 
+/// The game's sprites.
 #[derive(Debug)]
-pub(crate) struct SpriteSheet {
-    pub(crate) sprite_sheet: Vec<Color>,
+pub struct SpriteSheet {
+    // Color indices, one per pixel.
+    pub(crate) sprite_sheet: Vec<u8>,
 }
 
 impl SpriteSheet {
@@ -16,28 +19,34 @@ impl SpriteSheet {
 }
 
 impl SpriteSheet {
-    pub const SPRITES_PER_ROW: usize = 16;
-
+    /// Sprites in a Pico8 sized sheet.
+    ///
     /// This is kind of a lie.
     /// The pico8 sprite sheet supports 128 "real" sprites
     /// The other 128 share memory with the map,
     /// and will override its data if used
     pub const SPRITE_COUNT: usize = 256;
+    pub const HEIGHT: usize = 128;
+    // In the serialized sheet.
+    const PIXELS_PER_LINE: usize = 128;
 
     #[allow(dead_code)]
     pub fn new() -> Self {
+        Self::with_size(SpriteSheetSize::Pico8)
+    }
+
+    pub(crate) fn with_size(size: SpriteSheetSize) -> Self {
         Self {
-            sprite_sheet: vec![0; Self::SPRITE_COUNT * Sprite::WIDTH * Sprite::HEIGHT],
+            sprite_sheet: vec![0; size.sprite_count() * Sprite::WIDTH * Sprite::HEIGHT],
         }
     }
 
-    fn with_vec(sprite_sheet: Vec<Color>) -> Result<Self, String> {
-        const REQUIRED_BYTES: usize = SpriteSheet::SPRITE_COUNT * Sprite::WIDTH * Sprite::HEIGHT;
-
-        if sprite_sheet.len() != REQUIRED_BYTES {
+    fn with_vec(sprite_sheet: Vec<u8>) -> Result<Self, String> {
+        if SpriteSheetSize::from_len(sprite_sheet.len()).is_none() {
             Err(format!(
-                "[SpriteSheet] Needed {} bytes, got {}",
-                REQUIRED_BYTES,
+                "[SpriteSheet] Needed {} or {} bytes, got {}",
+                SpriteSheetSize::Pico8.sprite_count() * Sprite::WIDTH * Sprite::HEIGHT,
+                SpriteSheetSize::Double.sprite_count() * Sprite::WIDTH * Sprite::HEIGHT,
                 sprite_sheet.len()
             ))
         } else {
@@ -45,95 +54,333 @@ impl SpriteSheet {
         }
     }
 
-    /// Sets the pixel at coordinate (x,y) in the spritesheet to a specified color
-    pub fn set(&mut self, x: usize, y: usize, c: Color) {
-        self.sprite_sheet[Self::to_linear_index(x, y)] = c;
+    /// How big the sheet is, see [`crate::Pico8::set_sprite_sheet_size`].
+    pub fn size(&self) -> SpriteSheetSize {
+        SpriteSheetSize::from_len(self.sprite_sheet.len()).unwrap()
+    }
+
+    /// Grows or shrinks the sheet, keeping the sprites that fit.
+    /// New sprites are blank.
+    ///
+    /// Sprites keep their numbers, but sprites past the first row
+    /// end up at different pixel coordinates, since the rows change length.
+    pub(crate) fn resize(&mut self, size: SpriteSheetSize) {
+        self.sprite_sheet
+            .resize(size.sprite_count() * Sprite::WIDTH * Sprite::HEIGHT, 0);
+    }
+
+    /// How many sprites the sheet holds.
+    pub fn sprite_count(&self) -> usize {
+        self.size().sprite_count()
+    }
+
+    /// Width of the sheet, in pixels.
+    pub fn width(&self) -> usize {
+        self.size().width()
+    }
+
+    pub(crate) fn sprites_per_row(&self) -> usize {
+        self.width() / Sprite::WIDTH
+    }
+
+    /// The top left pixel of `sprite`.
+    pub(crate) fn sprite_position(&self, sprite: usize) -> (i32, i32) {
+        let sprite = sprite % self.sprite_count();
+        let per_row = self.sprites_per_row();
+
+        (
+            ((sprite % per_row) * Sprite::WIDTH) as i32,
+            ((sprite / per_row) * Sprite::HEIGHT) as i32,
+        )
+    }
+
+    /// The four sprites of the 16x16 "metasprite" whose top left sprite is `sprite`, row by row:
+    /// `sprite`, the one on its right, and the two below them.
+    ///
+    /// `None` for sprites in the sheet's last column or row, their blocks don't wrap around.
+    pub fn metasprite(&self, sprite: usize) -> Option<[usize; 4]> {
+        let per_row = self.sprites_per_row();
+        let last_row = self.sprite_count() / per_row - 1;
+        let (column, row) = (sprite % per_row, sprite / per_row);
+
+        (column + 1 < per_row && row < last_row).then_some([
+            sprite,
+            sprite + 1,
+            sprite + per_row,
+            sprite + per_row + 1,
+        ])
+    }
+
+    /// Like `to_linear_index`, but returns `None` for coordinates outside the sheet.
+    pub(crate) fn checked_index(&self, x: i32, y: i32) -> Option<usize> {
+        let in_bounds =
+            (0..self.width() as i32).contains(&x) && (0..Self::HEIGHT as i32).contains(&y);
+
+        in_bounds.then(|| self.to_linear_index(x as usize, y as usize))
+    }
+
+    /// The pixel at (x, y), black outside the sheet.
+    pub(crate) fn sget(&self, x: i32, y: i32) -> Color {
+        self.checked_index(x, y)
+            .map(|index| Color::from_index_lossy(self.sprite_sheet[index]))
+            .unwrap_or(Color::BLACK)
     }
 
-    pub fn to_linear_index(x: usize, y: usize) -> usize {
+    /// Where pixel (x, y) lives in memory, which is laid out sprite by sprite.
+    pub fn to_linear_index(&self, x: usize, y: usize) -> usize {
         let x_part = 64 * (x / 8) + x % 8;
-        let y_part = 16 * 64 * (y / 8) + 8 * (y % 8);
+        let y_part = self.sprites_per_row() * 64 * (y / 8) + 8 * (y % 8);
 
         y_part + x_part
     }
 
+    /// Sprite number `sprite`, `None` past the end of the sheet.
+    pub fn try_get_sprite(&self, sprite: usize) -> Option<&Sprite> {
+        (sprite < self.sprite_count()).then(|| self.get_sprite(sprite))
+    }
+
+    #[cfg(any(test, feature = "editor"))]
+    pub(crate) fn try_get_sprite_mut(&mut self, sprite: usize) -> Option<&mut Sprite> {
+        (sprite < self.sprite_count()).then(|| self.get_sprite_mut(sprite))
+    }
+
+    /// Sprite number `sprite`.
+    ///
+    /// Asking for one past the end of the sheet is a bug: it panics in debug builds,
+    /// and gets the last sprite in release ones. See [`SpriteSheet::try_get_sprite`].
     pub fn get_sprite(&self, sprite: usize) -> &Sprite {
         let index = self.sprite_index(sprite);
 
-        Sprite::new(&self.sprite_sheet[index..(index + Sprite::WIDTH * Sprite::HEIGHT)])
+        Sprite::from_slice(&self.sprite_sheet[index..(index + Sprite::WIDTH * Sprite::HEIGHT)])
     }
 
+    #[cfg(any(test, feature = "editor"))]
     pub(crate) fn get_sprite_mut(&mut self, sprite: usize) -> &mut Sprite {
         let index = self.sprite_index(sprite);
 
-        Sprite::new_mut(&mut self.sprite_sheet[index..(index + Sprite::WIDTH * Sprite::HEIGHT)])
+        Sprite::from_slice_mut(
+            &mut self.sprite_sheet[index..(index + Sprite::WIDTH * Sprite::HEIGHT)],
+        )
+    }
+
+    #[cfg(feature = "editor")]
+    pub(crate) fn swap_sprites(&mut self, a: usize, b: usize) {
+        const SIZE: usize = Sprite::WIDTH * Sprite::HEIGHT;
+
+        if a == b {
+            return;
+        }
+
+        let (low, high) = (a.min(b), a.max(b));
+        let high_index = self.sprite_index(high);
+        let (start, end) = self.sprite_sheet.split_at_mut(high_index);
+
+        start[low * SIZE..(low + 1) * SIZE].swap_with_slice(&mut end[..SIZE]);
+    }
+
+    /// Reads the lower half of the sprite sheet as map cells, one byte per cell,
+    /// the way Pico8 shares that memory with map rows 32 to 63.
+    /// `offset` is counted in cells from the start of the shared region.
+    pub(crate) fn shared_map_cell(&self, offset: usize) -> u8 {
+        let (left, right) = self.shared_pixels(offset);
+
+        self.sprite_sheet[left] | (self.sprite_sheet[right] << 4)
+    }
+
+    pub(crate) fn set_shared_map_cell(&mut self, offset: usize, sprite: u8) {
+        let (left, right) = self.shared_pixels(offset);
+
+        self.sprite_sheet[left] = sprite & 0xF;
+        self.sprite_sheet[right] = sprite >> 4;
+    }
+
+    // Each byte holds two pixels, with the left one in the low nibble.
+    fn shared_pixels(&self, offset: usize) -> (usize, usize) {
+        let bytes_per_row = self.width() / 2;
+        let first_shared_row = Self::HEIGHT / 2;
+
+        let x = (offset % bytes_per_row) * 2;
+        let y = first_shared_row + offset / bytes_per_row;
+
+        (self.to_linear_index(x, y), self.to_linear_index(x + 1, y))
     }
 
     fn sprite_index(&self, sprite: usize) -> usize {
+        let count = self.sprite_count();
+        debug_assert!(
+            sprite < count,
+            "Sprite {sprite} is past the end of a {count} sprite sheet"
+        );
+
         // How many pixels we need to skip to get to the start of this sprite.
-        sprite * Sprite::WIDTH * Sprite::HEIGHT
+        sprite.min(count - 1) * Sprite::WIDTH * Sprite::HEIGHT
     }
 
+    /// A sprite sheet from its raw pixels (see [`SpriteSheet::to_rust_source`]).
+    pub fn from_raw(pixels: &[u8]) -> Result<Self, String> {
+        if let Some(invalid) = pixels.iter().find(|&&color| color > 15) {
+            return Err(format!("[SpriteSheet] Invalid color {invalid}"));
+        }
+
+        Self::with_vec(pixels.to_vec())
+    }
+
+    /// Rust source for a `pub static` array named `ident` with the sheet's pixels,
+    /// to embed in a game and load with [`SpriteSheet::from_raw`].
+    /// Pixels are stored sprite by sprite, one sprite per line.
+    pub fn to_rust_source(&self, ident: &str) -> String {
+        crate::serialize::rust_array(ident, &self.sprite_sheet, Sprite::WIDTH * Sprite::HEIGHT)
+    }
+
+    /// Fails on sheets with any line too long or too short, or the wrong number of lines,
+    /// saying which line.
     pub fn deserialize(str: &str) -> Result<Self, String> {
-        let sprite_sheet = str
-            .as_bytes()
-            .iter()
-            .copied()
-            .filter_map(|c| (c as char).to_digit(16))
-            .map(|c| c as u8)
-            .collect();
+        Self::deserialize_with(str, &mut Recovery::Strict)
+    }
+
+    /// Like [`SpriteSheet::deserialize`], fixing sheets with missing or extra pixels
+    /// if `recovery` says so.
+    pub(crate) fn deserialize_with(str: &str, recovery: &mut Recovery) -> Result<Self, String> {
+        let (version, contents) = split_version(str)?;
+        let size = match version {
+            // Only Pico8 sized sheets were supported.
+            1 => SpriteSheetSize::Pico8,
+            // Either size, whichever the line count is closest to.
+            2 if contents.lines().count() <= SpriteSheetSize::Pico8.lines() => {
+                SpriteSheetSize::Pico8
+            }
+            2 => SpriteSheetSize::Double,
+            _ => return Err(format!("Unknown sprite sheet version: {version}")),
+        };
+
+        let sprite_sheet = read_grid(
+            contents,
+            (Self::PIXELS_PER_LINE, size.lines()),
+            first_line_of(str, contents),
+            |line| {
+                line.trim()
+                    .chars()
+                    .map(|c| {
+                        c.to_digit(16)
+                            .map(|color| color as u8)
+                            .ok_or_else(|| format!("Invalid color {c}"))
+                    })
+                    .collect()
+            },
+            recovery,
+        )?;
 
         Self::with_vec(sprite_sheet)
     }
 }
 
 impl Serialize for SpriteSheet {
-    fn serialize(&self) -> String {
-        let lines = self.sprite_sheet.chunks(128).map(|chunk| {
-            Itertools::intersperse(chunk.iter().map(|n| format!("{:X}", n)), "".to_owned())
-                .collect()
-        });
+    /// Pixels in memory order, 128 per line.
+    /// Pico8 sized sheets are written without a version header,
+    /// so that older versions can still read them.
+    fn serialize(&self, writer: &mut dyn Write) -> io::Result<()> {
+        if self.size() == SpriteSheetSize::Double {
+            writeln!(writer, "{}", version_header(2))?;
+        }
+        for (index, line) in self.sprite_sheet.chunks(Self::PIXELS_PER_LINE).enumerate() {
+            if index > 0 {
+                writeln!(writer)?;
+            }
+            for n in line {
+                write!(writer, "{:X}", n)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// How big the sprite sheet is, see [`crate::Pico8::set_sprite_sheet_size`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpriteSheetSize {
+    /// 128x128 pixels, 256 sprites, like in Pico8.
+    #[default]
+    Pico8,
+    /// 256x128 pixels, 512 sprites.
+    Double,
+}
+
+impl SpriteSheetSize {
+    /// Width of the sheet, in pixels.
+    pub fn width(self) -> usize {
+        match self {
+            Self::Pico8 => 128,
+            Self::Double => 256,
+        }
+    }
+
+    pub fn sprite_count(self) -> usize {
+        self.width() * SpriteSheet::HEIGHT / (Sprite::WIDTH * Sprite::HEIGHT)
+    }
+
+    // Of the serialized sheet.
+    fn lines(self) -> usize {
+        self.width() * SpriteSheet::HEIGHT / SpriteSheet::PIXELS_PER_LINE
+    }
 
-        Itertools::intersperse(lines, "\n".to_owned()).collect::<String>()
+    fn from_len(pixels: usize) -> Option<Self> {
+        [Self::Pico8, Self::Double]
+            .into_iter()
+            .find(|size| size.width() * SpriteSheet::HEIGHT == pixels)
     }
 }
 
+/// An 8x8 sprite, stored row by row as one color index per pixel.
+///
+/// Sprites borrowed from a [`SpriteSheet`] are `&Sprite`/`&mut Sprite`,
+/// copying one out gives an owned `Sprite`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(transparent)]
 pub struct Sprite {
-    pub sprite: [Color],
+    pixels: [u8; Sprite::PIXELS],
 }
 
 impl Sprite {
     pub const WIDTH: usize = 8;
     pub const HEIGHT: usize = 8;
+    const PIXELS: usize = Self::WIDTH * Self::HEIGHT;
 
-    pub(crate) fn new(sprite: &[u8]) -> &Self {
-        unsafe { &*(sprite as *const [u8] as *const Self) }
+    #[cfg(any(test, feature = "editor"))]
+    pub(crate) const EMPTY: Sprite = Sprite::from_pixels([0; Self::PIXELS]);
+
+    /// Color indices are kept as they are, they're only checked when read.
+    pub(crate) const fn from_pixels(pixels: [u8; Self::PIXELS]) -> Self {
+        Self { pixels }
     }
 
-    pub fn to_owned(&self) -> Vec<Color> {
-        let sprite = &self.sprite;
+    fn from_slice(pixels: &[u8]) -> &Self {
+        let pixels: &[u8; Self::PIXELS] = pixels.try_into().unwrap();
 
-        sprite.to_vec()
+        // SAFETY: `Sprite` is a `repr(transparent)` wrapper around `[u8; PIXELS]`.
+        unsafe { &*(pixels as *const [u8; Self::PIXELS] as *const Self) }
     }
 
-    fn new_mut(sprite: &mut [u8]) -> &mut Self {
-        unsafe { &mut *(sprite as *mut [u8] as *mut Self) }
-    }
+    #[cfg(any(test, feature = "editor"))]
+    fn from_slice_mut(pixels: &mut [u8]) -> &mut Self {
+        let pixels: &mut [u8; Self::PIXELS] = pixels.try_into().unwrap();
 
-    pub(crate) fn set(&mut self, index: usize, color: Color) {
-        self.sprite[index] = color;
+        // SAFETY: `Sprite` is a `repr(transparent)` wrapper around `[u8; PIXELS]`.
+        unsafe { &mut *(pixels as *mut [u8; Self::PIXELS] as *mut Self) }
     }
 
-    pub fn pset(&mut self, x: isize, y: isize, color: Color) {
-        // TODO: is unwrapping here ok? Why?
+    /// Writes outside of the sprite are ignored,
+    /// so brushes can overlap the edges.
+    pub fn pset(&mut self, x: isize, y: isize, color: impl Into<Color>) {
         if let Some(index) = Self::index(x, y) {
-            self.set(index, color);
+            self.pixels[index] = color.into().index();
         }
     }
 
+    /// Returns black for pixels outside of the sprite.
     pub fn pget(&self, x: isize, y: isize) -> Color {
-        self.sprite[Self::index(x, y).unwrap()]
+        Self::index(x, y)
+            .map(|index| Color::from_index_lossy(self.pixels[index]))
+            .unwrap_or_default()
     }
 
     fn index(x: isize, y: isize) -> Option<usize> {
@@ -147,62 +394,474 @@ impl Sprite {
         }
     }
 
-    pub(crate) fn shift_up(&mut self) {
-        self.sprite.rotate_left(8);
+    /// Every pixel as `(x, y, color)`, row by row.
+    pub fn iter_pixels(&self) -> impl Iterator<Item = (usize, usize, Color)> + '_ {
+        self.pixels.iter().enumerate().map(|(index, &color)| {
+            (
+                index % Self::WIDTH,
+                index / Self::WIDTH,
+                Color::from_index_lossy(color),
+            )
+        })
     }
 
-    pub(crate) fn shift_down(&mut self) {
-        self.sprite.rotate_right(8);
+    /// Moves every pixel up by `pixels`, wrapping them around to the bottom.
+    pub fn shift_up_wrapped(&mut self, pixels: usize) {
+        self.shift(0, -(pixels as isize), true);
     }
 
-    pub(crate) fn shift_left(&mut self) {
-        self.sprite
-            .chunks_mut(Sprite::WIDTH)
-            .for_each(|row| row.rotate_left(1));
+    pub fn shift_down_wrapped(&mut self, pixels: usize) {
+        self.shift(0, pixels as isize, true);
     }
 
-    pub(crate) fn shift_right(&mut self) {
-        self.sprite
-            .chunks_mut(Sprite::WIDTH)
-            .for_each(|row| row.rotate_right(1));
+    pub fn shift_left_wrapped(&mut self, pixels: usize) {
+        self.shift(-(pixels as isize), 0, true);
     }
 
-    pub(crate) fn iter(&self) -> impl Iterator<Item = Color> + '_ {
-        self.sprite.iter().copied()
+    pub fn shift_right_wrapped(&mut self, pixels: usize) {
+        self.shift(pixels as isize, 0, true);
     }
 
-    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = &mut Color> + '_ {
-        self.sprite.iter_mut()
+    fn shift(&mut self, dx: isize, dy: isize, wrap: bool) {
+        let original = self.pixels;
+        let (width, height) = (Self::WIDTH as isize, Self::HEIGHT as isize);
+
+        for y in 0..height {
+            for x in 0..width {
+                let (from_x, from_y) = if wrap {
+                    ((x - dx).rem_euclid(width), (y - dy).rem_euclid(height))
+                } else {
+                    (x - dx, y - dy)
+                };
+
+                self.pixels[Self::index(x, y).unwrap()] = Self::index(from_x, from_y)
+                    .map(|index| original[index])
+                    .unwrap_or(0);
+            }
+        }
     }
 
     pub fn flip_horizontally(&mut self) {
-        for row in self.sprite.chunks_mut(Self::WIDTH) {
-            row.reverse()
-        }
+        self.rows_mut().for_each(|row| row.reverse());
     }
 
     pub fn flip_vertically(&mut self) {
-        for x in 0..(Self::WIDTH as isize) {
-            for y in 0..((Self::HEIGHT / 2) as isize) {
-                self.sprite.swap(
-                    Self::index(x, y).unwrap(),
-                    Self::index(x, Self::HEIGHT as isize - 1 - y).unwrap(),
-                );
-            }
+        for y in 0..Self::HEIGHT / 2 {
+            let (top, bottom) = self
+                .pixels
+                .split_at_mut((Self::HEIGHT - 1 - y) * Self::WIDTH);
+
+            top[y * Self::WIDTH..(y + 1) * Self::WIDTH].swap_with_slice(&mut bottom[..Self::WIDTH]);
+        }
+    }
+
+    /// Rotates the sprite 90 degrees clockwise.
+    pub fn rotate_clockwise(&mut self) {
+        let original = self.pixels;
+
+        for (index, &pixel) in original.iter().enumerate() {
+            let (x, y) = (index % Self::WIDTH, index / Self::WIDTH);
+
+            self.pixels[(Self::HEIGHT - 1 - y) + x * Self::WIDTH] = pixel;
         }
     }
+
+    fn rows_mut(&mut self) -> impl Iterator<Item = &mut [u8]> {
+        self.pixels.chunks_mut(Self::WIDTH)
+    }
+}
+
+// Shifts that don't wrap around, unlike the editor's.
+impl Sprite {
+    /// Moves every pixel up by `pixels`.
+    /// The ones that fall off the edge are lost, the uncovered ones become black.
+    pub fn shift_up(&mut self, pixels: usize) {
+        self.shift(0, -(pixels as isize), false);
+    }
+
+    pub fn shift_down(&mut self, pixels: usize) {
+        self.shift(0, pixels as isize, false);
+    }
+
+    pub fn shift_left(&mut self, pixels: usize) {
+        self.shift(-(pixels as isize), 0, false);
+    }
+
+    pub fn shift_right(&mut self, pixels: usize) {
+        self.shift(pixels as isize, 0, false);
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::serialize::{to_string, Recovery};
 
     #[test]
     fn indexing_works() {
-        assert_eq!(SpriteSheet::to_linear_index(7, 0), 7);
-        assert_eq!(SpriteSheet::to_linear_index(0, 1), 8);
-        assert_eq!(SpriteSheet::to_linear_index(8, 0), 64);
-        assert_eq!(SpriteSheet::to_linear_index(8, 1), 64 + 8);
-        assert_eq!(SpriteSheet::to_linear_index(1, 9), 1033);
+        let sprite_sheet = SpriteSheet::new();
+
+        assert_eq!(sprite_sheet.to_linear_index(7, 0), 7);
+        assert_eq!(sprite_sheet.to_linear_index(0, 1), 8);
+        assert_eq!(sprite_sheet.to_linear_index(8, 0), 64);
+        assert_eq!(sprite_sheet.to_linear_index(8, 1), 64 + 8);
+        assert_eq!(sprite_sheet.to_linear_index(1, 9), 1033);
+    }
+
+    #[test]
+    fn checked_indexing_works() {
+        let sprite_sheet = SpriteSheet::new();
+
+        assert_eq!(sprite_sheet.checked_index(1, 9), Some(1033));
+        assert_eq!(sprite_sheet.checked_index(127, 127), Some(127 * 128 + 127));
+        assert_eq!(sprite_sheet.checked_index(-1, 0), None);
+        assert_eq!(sprite_sheet.checked_index(0, 128), None);
+        assert_eq!(sprite_sheet.checked_index(128, 0), None);
+    }
+
+    #[test]
+    fn metasprites_dont_wrap_around() {
+        let sprite_sheet = SpriteSheet::new();
+
+        assert_eq!(sprite_sheet.metasprite(0), Some([0, 1, 16, 17]));
+        assert_eq!(sprite_sheet.metasprite(14), Some([14, 15, 30, 31]));
+        assert_eq!(sprite_sheet.metasprite(238), Some([238, 239, 254, 255]));
+        // The last column and row.
+        assert_eq!(sprite_sheet.metasprite(15), None);
+        assert_eq!(sprite_sheet.metasprite(240), None);
+        assert_eq!(sprite_sheet.metasprite(255), None);
+
+        let double = SpriteSheet::with_size(SpriteSheetSize::Double);
+        assert_eq!(double.metasprite(15), Some([15, 16, 47, 48]));
+        assert_eq!(double.metasprite(31), None);
+        assert_eq!(double.sprite_position(33), (8, 8));
+    }
+
+    #[test]
+    fn double_sheets_have_longer_rows() {
+        let sprite_sheet = SpriteSheet::with_size(SpriteSheetSize::Double);
+
+        assert_eq!(sprite_sheet.sprite_count(), 512);
+        // Sprite 16 is still on the first row, sprite 32 starts the second one.
+        assert_eq!(sprite_sheet.to_linear_index(128, 0), 16 * 64);
+        assert_eq!(sprite_sheet.to_linear_index(0, 8), 32 * 64);
+        assert_eq!(sprite_sheet.checked_index(255, 127), Some(512 * 64 - 1));
+        assert_eq!(sprite_sheet.checked_index(256, 0), None);
+        assert_eq!(sprite_sheet.checked_index(0, 128), None);
+    }
+
+    #[test]
+    fn resizing_keeps_sprite_numbers() {
+        let mut sprite_sheet = SpriteSheet::new();
+        sprite_sheet.get_sprite_mut(200).pset(1, 1, 8);
+
+        sprite_sheet.resize(SpriteSheetSize::Double);
+        sprite_sheet.get_sprite_mut(511).pset(2, 2, 9);
+        assert_eq!(sprite_sheet.get_sprite(200).pget(1, 1), Color::RED);
+        assert_eq!(sprite_sheet.get_sprite(511).pget(2, 2), Color::ORANGE);
+        assert_eq!(sprite_sheet.try_get_sprite(512), None);
+
+        sprite_sheet.resize(SpriteSheetSize::Pico8);
+        assert_eq!(sprite_sheet.size(), SpriteSheetSize::Pico8);
+        assert_eq!(sprite_sheet.get_sprite(200).pget(1, 1), Color::RED);
+        assert_eq!(sprite_sheet.get_sprite(255), &Sprite::EMPTY);
+        assert_eq!(sprite_sheet.try_get_sprite(511), None);
+    }
+
+    #[test]
+    fn only_double_sheets_are_versioned() {
+        let mut sprite_sheet = SpriteSheet::new();
+        sprite_sheet.get_sprite_mut(3).pset(0, 0, 7);
+        let serialized = to_string(&sprite_sheet);
+        assert!(!serialized.starts_with("version"));
+        assert_eq!(
+            SpriteSheet::deserialize(&serialized).unwrap().sprite_sheet,
+            sprite_sheet.sprite_sheet
+        );
+
+        sprite_sheet.resize(SpriteSheetSize::Double);
+        sprite_sheet.get_sprite_mut(400).pset(7, 0, 12);
+        let serialized = to_string(&sprite_sheet);
+        assert!(serialized.starts_with("version 2\n"));
+        let deserialized = SpriteSheet::deserialize(&serialized).unwrap();
+        assert_eq!(deserialized.size(), SpriteSheetSize::Double);
+        assert_eq!(deserialized.sprite_sheet, sprite_sheet.sprite_sheet);
+
+        // Unversioned files can only hold Pico8 sized sheets.
+        let unversioned = serialized.split_once('\n').unwrap().1;
+        assert!(SpriteSheet::deserialize(unversioned).is_err());
+    }
+
+    const FIXTURE: &str = include_str!("../test_data/golden/sprite_sheet.txt");
+
+    // The fixture with `edit` applied to its lines.
+    fn edited_fixture(edit: impl FnOnce(&mut Vec<String>)) -> String {
+        let mut lines = FIXTURE.lines().map(str::to_owned).collect();
+        edit(&mut lines);
+
+        lines.join("\n")
+    }
+
+    #[test]
+    fn sheets_with_the_wrong_dimensions_are_rejected() {
+        let one_line_short = edited_fixture(|lines| {
+            lines.pop();
+        });
+        let one_pixel_long = edited_fixture(|lines| lines[12].push('0'));
+        let one_pixel_short = edited_fixture(|lines| {
+            lines[12].pop();
+        });
+
+        assert!(SpriteSheet::deserialize(FIXTURE).is_ok());
+        for (sheet, error) in [
+            (one_line_short.as_str(), "127 lines, needed 128"),
+            (&one_pixel_long, "Line 13 has 129 values, needed 128"),
+            (&one_pixel_short, "Line 13 has 127 values, needed 128"),
+            ("", "0 lines, needed 128"),
+            ("0G", "Line 1: Invalid color G"),
+        ] {
+            assert_eq!(SpriteSheet::deserialize(sheet).unwrap_err(), error);
+        }
+
+        // Line numbers count the version header.
+        let mut double = to_string(&SpriteSheet::with_size(SpriteSheetSize::Double));
+        double.push('0');
+        assert_eq!(
+            SpriteSheet::deserialize(&double).unwrap_err(),
+            "Line 257 has 129 values, needed 128"
+        );
+    }
+
+    #[test]
+    fn broken_sheets_can_be_padded_and_truncated() {
+        let expected = SpriteSheet::deserialize(FIXTURE).unwrap();
+        let recover = |sheet: &str| {
+            let mut warnings = vec![];
+            let recovered =
+                SpriteSheet::deserialize_with(sheet, &mut Recovery::PadAndTruncate(&mut warnings))
+                    .unwrap();
+
+            (recovered.sprite_sheet, warnings)
+        };
+
+        // The last line is all zeros in the fixture.
+        let one_line_short = edited_fixture(|lines| {
+            lines.pop();
+        });
+        assert_eq!(
+            recover(&one_line_short),
+            (
+                expected.sprite_sheet.clone(),
+                vec!["127 lines, needed 128, padded with zeros".to_owned()]
+            )
+        );
+
+        // The extra pixel doesn't shift the rest of the sheet.
+        let one_pixel_long = edited_fixture(|lines| lines[12].push('F'));
+        assert_eq!(
+            recover(&one_pixel_long),
+            (
+                expected.sprite_sheet,
+                vec!["Line 13 has 129 values, needed 128, dropped the rest".to_owned()]
+            )
+        );
+
+        let (empty, warnings) = recover("");
+        assert_eq!(empty, SpriteSheet::new().sprite_sheet);
+        assert_eq!(warnings, ["0 lines, needed 128, padded with zeros"]);
+    }
+
+    #[test]
+    #[cfg(feature = "editor")]
+    fn swapping_sprites_works() {
+        let mut sprite_sheet = SpriteSheet::new();
+        sprite_sheet.get_sprite_mut(3).pset(0, 0, 7);
+        sprite_sheet.get_sprite_mut(70).pset(7, 7, 8);
+
+        sprite_sheet.swap_sprites(70, 3);
+
+        assert_eq!(sprite_sheet.get_sprite(3).pget(7, 7), Color::RED);
+        assert_eq!(sprite_sheet.get_sprite(3).pget(0, 0), Color::BLACK);
+        assert_eq!(sprite_sheet.get_sprite(70).pget(0, 0), Color::WHITE);
+    }
+
+    #[test]
+    fn the_whole_sheet_is_addressable() {
+        let mut sprite_sheet = SpriteSheet::new();
+        sprite_sheet.get_sprite_mut(255).pset(7, 7, 9);
+
+        // The last pixel of the sheet.
+        assert_eq!(sprite_sheet.sprite_sheet[128 * 128 - 1], 9);
+        assert_eq!(sprite_sheet.get_sprite(255).pget(7, 7), Color::ORANGE);
+
+        // Past the end there's nothing.
+        assert_eq!(
+            sprite_sheet
+                .try_get_sprite(255)
+                .map(|sprite| sprite.pget(7, 7)),
+            Some(Color::ORANGE)
+        );
+        assert_eq!(sprite_sheet.try_get_sprite(256), None);
+        assert!(sprite_sheet.try_get_sprite_mut(usize::MAX).is_none());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Sprite 256 is past the end")]
+    fn sprites_past_the_end_are_a_bug() {
+        SpriteSheet::new().get_sprite(256);
+    }
+
+    #[test]
+    fn last_sprite_survives_serialization() {
+        let mut sprite_sheet = SpriteSheet::new();
+        sprite_sheet.get_sprite_mut(255).pset(3, 4, 12);
+
+        let deserialized = SpriteSheet::deserialize(&to_string(&sprite_sheet)).unwrap();
+
+        assert_eq!(deserialized.get_sprite(255).pget(3, 4), Color::BLUE);
+        assert_eq!(deserialized.sprite_sheet, sprite_sheet.sprite_sheet);
+    }
+
+    // 0 1 2 3 4 5 6 7
+    // 8 9 ...
+    fn numbered() -> Sprite {
+        let mut pixels = [0; Sprite::PIXELS];
+        for (index, pixel) in pixels.iter_mut().enumerate() {
+            *pixel = index as u8;
+        }
+
+        Sprite::from_pixels(pixels)
+    }
+
+    fn raw(sprite: &Sprite, x: usize, y: usize) -> u8 {
+        sprite.pixels[x + y * Sprite::WIDTH]
+    }
+
+    #[test]
+    fn out_of_range_pixels_are_ignored() {
+        let mut sprite = Sprite::EMPTY;
+
+        for (x, y) in [(-1, 0), (0, -1), (8, 0), (0, 8), (100, 100)] {
+            sprite.pset(x, y, Color::RED);
+            assert_eq!(sprite.pget(x, y), Color::BLACK);
+        }
+        assert!(sprite
+            .iter_pixels()
+            .all(|(.., color)| color == Color::BLACK));
+
+        sprite.pset(7, 7, Color::RED);
+        assert_eq!(sprite.pget(7, 7), Color::RED);
+    }
+
+    #[test]
+    fn iterates_pixels_row_by_row() {
+        let mut sprite = Sprite::EMPTY;
+        sprite.pset(1, 0, Color::RED);
+        sprite.pset(0, 1, Color::BLUE);
+
+        let pixels: Vec<_> = sprite.iter_pixels().collect();
+        assert_eq!(pixels.len(), 64);
+        assert_eq!(pixels[1], (1, 0, Color::RED));
+        assert_eq!(pixels[8], (0, 1, Color::BLUE));
+        assert_eq!(pixels[63], (7, 7, Color::BLACK));
+    }
+
+    #[test]
+    fn shifting_wraps_around() {
+        let mut sprite = numbered();
+        sprite.shift_up_wrapped(1);
+        assert_eq!(raw(&sprite, 0, 0), 8);
+        assert_eq!(raw(&sprite, 0, 7), 0);
+
+        let mut sprite = numbered();
+        sprite.shift_down_wrapped(1);
+        assert_eq!(raw(&sprite, 0, 0), 56);
+        assert_eq!(raw(&sprite, 0, 1), 0);
+
+        let mut sprite = numbered();
+        sprite.shift_left_wrapped(1);
+        assert_eq!(raw(&sprite, 0, 0), 1);
+        assert_eq!(raw(&sprite, 7, 0), 0);
+        assert_eq!(raw(&sprite, 7, 1), 8);
+
+        let mut sprite = numbered();
+        sprite.shift_right_wrapped(1);
+        assert_eq!(raw(&sprite, 0, 0), 7);
+        assert_eq!(raw(&sprite, 1, 0), 0);
+    }
+
+    #[test]
+    fn wrapping_all_the_way_around_is_a_no_op() {
+        let shifts: [fn(&mut Sprite, usize); 4] = [
+            Sprite::shift_up_wrapped,
+            Sprite::shift_down_wrapped,
+            Sprite::shift_left_wrapped,
+            Sprite::shift_right_wrapped,
+        ];
+
+        for shift in shifts {
+            let mut sprite = numbered();
+            for _ in 0..4 {
+                shift(&mut sprite, 2);
+            }
+
+            assert_eq!(sprite, numbered());
+        }
+    }
+
+    #[test]
+    fn shifting_discards_pixels() {
+        let mut sprite = numbered();
+        sprite.shift_left(2);
+        assert_eq!(raw(&sprite, 0, 0), 2);
+        assert_eq!(raw(&sprite, 5, 1), 15);
+        assert_eq!(raw(&sprite, 6, 1), 0);
+        assert_eq!(raw(&sprite, 7, 1), 0);
+
+        let mut sprite = numbered();
+        sprite.shift_down(3);
+        assert_eq!(raw(&sprite, 1, 2), 0);
+        assert_eq!(raw(&sprite, 1, 3), 1);
+
+        // Shifting back doesn't bring anything back.
+        sprite.shift_up(3);
+        assert_eq!(raw(&sprite, 1, 0), 1);
+        assert_eq!(raw(&sprite, 1, 7), 0);
+    }
+
+    #[test]
+    fn flipping_works() {
+        let mut sprite = numbered();
+        sprite.flip_horizontally();
+        assert_eq!(raw(&sprite, 0, 0), 7);
+        assert_eq!(raw(&sprite, 7, 1), 8);
+
+        let mut sprite = numbered();
+        sprite.flip_vertically();
+        assert_eq!(raw(&sprite, 0, 0), 56);
+        assert_eq!(raw(&sprite, 1, 7), 1);
+        assert_eq!(raw(&sprite, 2, 3), 34);
+
+        let mut twice = numbered();
+        twice.flip_vertically();
+        twice.flip_vertically();
+        assert_eq!(twice, numbered());
+    }
+
+    #[test]
+    fn rotating_works() {
+        let mut sprite = numbered();
+        sprite.rotate_clockwise();
+
+        // The left column becomes the top row.
+        assert_eq!(raw(&sprite, 7, 0), 0);
+        assert_eq!(raw(&sprite, 0, 0), 56);
+        assert_eq!(raw(&sprite, 7, 7), 7);
+
+        for _ in 0..3 {
+            sprite.rotate_clockwise();
+        }
+        assert_eq!(sprite, numbered());
     }
 }
@@ -1,4 +1,5 @@
-use crate::runtime::input::Keys;
+use crate::runtime::input::{KeyInput, Keys};
+use crate::MouseButton;
 use ButtonState::*;
 
 #[derive(Debug)]
@@ -12,6 +13,11 @@ pub(crate) struct State {
     pub mouse_x: i32,
     pub mouse_y: i32,
     mouse_pressed: ButtonState,
+    mouse_right_pressed: ButtonState,
+    mouse_middle_pressed: ButtonState,
+    frames: u64,
+    // Updates skipped since the start, for being too far behind.
+    dropped_updates: u32,
 }
 
 impl State {
@@ -26,6 +32,10 @@ impl State {
             mouse_x: 64,
             mouse_y: 64,
             mouse_pressed: NotPressed,
+            mouse_right_pressed: NotPressed,
+            mouse_middle_pressed: NotPressed,
+            frames: 0,
+            dropped_updates: 0,
         }
     }
 
@@ -34,14 +44,66 @@ impl State {
         self.mouse_y = mouse_y;
     }
 
-    pub(crate) fn update_keys(&mut self, keys: &Keys) {
-        self.left.update(keys.left);
-        self.right.update(keys.right);
-        self.up.update(keys.up);
-        self.down.update(keys.down);
-        self.x.update(keys.x);
-        self.c.update(keys.c);
-        self.mouse_pressed.update(keys.mouse);
+    pub(crate) fn tick(&mut self) {
+        self.frames += 1;
+    }
+
+    pub(crate) fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    pub(crate) fn drop_updates(&mut self, count: u32) {
+        self.dropped_updates += count;
+    }
+
+    pub(crate) fn dropped_updates(&self) -> u32 {
+        self.dropped_updates
+    }
+
+    /// Called once per update, so that every update sees its own presses.
+    pub(crate) fn update_keys(&mut self, keys: &mut Keys) {
+        for (button, key) in self.buttons(keys) {
+            button.update(key.take());
+        }
+    }
+
+    /// Nothing reads as pressed until `resume_keys`, for while the game isn't getting input.
+    #[cfg(feature = "editor")]
+    pub(crate) fn release_keys(&mut self) {
+        for (button, _) in self.buttons(&mut Keys::new()) {
+            button.unpress();
+        }
+    }
+
+    /// Picks the keys up as they are now, after `release_keys`:
+    /// keys that were held all along are held, not just pressed,
+    /// and what happened to them in the meantime is dropped.
+    #[cfg(feature = "editor")]
+    pub(crate) fn resume_keys(&mut self, keys: &mut Keys) {
+        for (button, key) in self.buttons(keys) {
+            *button = if key.take().down == Some(true) {
+                Held
+            } else {
+                NotPressed
+            };
+        }
+    }
+
+    fn buttons<'a>(
+        &'a mut self,
+        keys: &'a mut Keys,
+    ) -> [(&'a mut ButtonState, &'a mut KeyInput); 9] {
+        [
+            (&mut self.left, &mut keys.left),
+            (&mut self.right, &mut keys.right),
+            (&mut self.up, &mut keys.up),
+            (&mut self.down, &mut keys.down),
+            (&mut self.x, &mut keys.x),
+            (&mut self.c, &mut keys.c),
+            (&mut self.mouse_pressed, &mut keys.mouse),
+            (&mut self.mouse_right_pressed, &mut keys.mouse_right),
+            (&mut self.mouse_middle_pressed, &mut keys.mouse_middle),
+        ]
     }
 
     pub(crate) fn button(&self, button: Button) -> &ButtonState {
@@ -56,6 +118,14 @@ impl State {
         }
     }
 
+    pub(crate) fn mouse_button(&self, button: MouseButton) -> &ButtonState {
+        match button {
+            MouseButton::Left => &self.mouse_pressed,
+            MouseButton::Right => &self.mouse_right_pressed,
+            MouseButton::Middle => &self.mouse_middle_pressed,
+        }
+    }
+
     pub(crate) fn mouse(&self) -> (i32, i32) {
         (self.mouse_x, self.mouse_y)
     }
@@ -69,8 +139,15 @@ pub(crate) enum ButtonState {
 }
 
 impl ButtonState {
-    fn update(&mut self, is_pressed: Option<bool>) {
-        match is_pressed {
+    fn update(&mut self, input: KeyInput) {
+        // Taps shorter than an update still count as a press, released on the next one.
+        if input.pressed {
+            *self = JustPressed;
+
+            return;
+        }
+
+        match input.down {
             Some(is_pressed) => {
                 if is_pressed {
                     self.press()
@@ -126,3 +203,75 @@ pub enum Button {
     C,
     Mouse,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Key, KeyState, KeyboardEvent};
+
+    fn key(key: Key, state: KeyState) -> KeyboardEvent {
+        KeyboardEvent { key, state }
+    }
+
+    #[test]
+    fn taps_between_updates_are_pressed_for_one_update() {
+        let mut state = State::new();
+        let mut keys = Keys::new();
+
+        keys.on_event(key(Key::X, KeyState::Down));
+        keys.on_event(key(Key::X, KeyState::Up));
+        // Catching up runs several updates in a row, only the first one sees the tap.
+        state.update_keys(&mut keys);
+        assert!(state.button(Button::X).btnp());
+        state.update_keys(&mut keys);
+        assert!(!state.button(Button::X).btn());
+        state.update_keys(&mut keys);
+        assert!(!state.button(Button::X).btn());
+    }
+
+    #[test]
+    fn held_keys_are_pressed_once() {
+        let mut state = State::new();
+        let mut keys = Keys::new();
+
+        keys.on_event(key(Key::C, KeyState::Down));
+        state.update_keys(&mut keys);
+        assert!(state.button(Button::C).btnp());
+
+        // Key repeats while held aren't new presses.
+        keys.on_event(key(Key::C, KeyState::Down));
+        state.update_keys(&mut keys);
+        assert!(state.button(Button::C).btn());
+        assert!(!state.button(Button::C).btnp());
+
+        // Released and pressed again within an update is.
+        keys.on_event(key(Key::C, KeyState::Up));
+        keys.on_event(key(Key::C, KeyState::Down));
+        state.update_keys(&mut keys);
+        assert!(state.button(Button::C).btnp());
+    }
+
+    #[cfg(feature = "editor")]
+    #[test]
+    fn resumed_keys_are_held_not_pressed() {
+        let mut state = State::new();
+        let mut keys = Keys::new();
+
+        keys.on_event(key(Key::X, KeyState::Down));
+        state.update_keys(&mut keys);
+        state.release_keys();
+        assert!(!state.button(Button::X).btn());
+
+        // Tapped while released.
+        keys.on_event(key(Key::C, KeyState::Down));
+        keys.on_event(key(Key::C, KeyState::Up));
+        state.resume_keys(&mut keys);
+        assert!(state.button(Button::X).btn());
+        assert!(!state.button(Button::X).btnp());
+        assert!(!state.button(Button::C).btn());
+
+        state.update_keys(&mut keys);
+        assert!(!state.button(Button::X).btnp());
+        assert!(!state.button(Button::C).btn());
+    }
+}
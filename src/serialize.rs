@@ -0,0 +1,322 @@
+use itertools::Itertools;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+/// Streams `serializable` into a temporary file next to `file_path`, then moves it over `file_path`,
+/// so that a failed write doesn't leave a half written file behind.
+pub(crate) fn write_file(file_path: &str, serializable: &impl Serialize) -> io::Result<()> {
+    let temp_path = format!("{file_path}.tmp");
+
+    let mut writer = BufWriter::new(File::create(&temp_path)?);
+    serializable.serialize(&mut writer)?;
+    writer.flush()?;
+    drop(writer);
+
+    std::fs::rename(&temp_path, file_path)
+}
+
+pub trait Serialize {
+    /// Writes the serialized form to `writer` as it goes, without building it in memory first.
+    fn serialize(&self, writer: &mut dyn Write) -> io::Result<()>;
+}
+
+impl<T: Serialize + ?Sized> Serialize for &T {
+    fn serialize(&self, writer: &mut dyn Write) -> io::Result<()> {
+        T::serialize(self, writer)
+    }
+}
+
+/// The serialized form of `serializable`, for tests.
+#[cfg(test)]
+pub(crate) fn to_string(serializable: &impl Serialize) -> String {
+    let mut bytes = vec![];
+    serializable
+        .serialize(&mut bytes)
+        .expect("Writing to a Vec can't fail");
+
+    String::from_utf8(bytes).expect("Serialized data should be UTF-8")
+}
+
+const VERSION_PREFIX: &str = "version ";
+
+/// First line of versioned asset files.
+pub(crate) fn version_header(version: u32) -> String {
+    format!("{VERSION_PREFIX}{version}")
+}
+
+/// Splits the version header off an asset file, returning the version and the rest of the file.
+/// Files written before versioning have no header, and are version 1.
+pub(crate) fn split_version(str: &str) -> Result<(u32, &str), String> {
+    match str.strip_prefix(VERSION_PREFIX) {
+        Some(rest) => {
+            let (version, rest) = rest.split_once('\n').unwrap_or((rest, ""));
+            let version = version
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid version header: {version}"))?;
+
+            Ok((version, rest))
+        }
+        None => Ok((1, str)),
+    }
+}
+
+/// What deserializers do with files that don't have exactly the lines,
+/// and values per line, that they need.
+pub(crate) enum Recovery<'a> {
+    /// Fails, saying which line is wrong.
+    Strict,
+    /// Pads what's missing with zeros and drops what's extra,
+    /// adding a warning to the list for each fix.
+    PadAndTruncate(&'a mut Vec<String>),
+}
+
+/// Reads `rows` lines of `columns` values each, split into values by `parse_line`.
+/// `first_line` is the number of the first line of `contents` in the whole file, for messages.
+pub(crate) fn read_grid<T: Default + Clone>(
+    contents: &str,
+    (columns, rows): (usize, usize),
+    first_line: usize,
+    parse_line: impl Fn(&str) -> Result<Vec<T>, String>,
+    recovery: &mut Recovery,
+) -> Result<Vec<T>, String> {
+    let lines: Vec<&str> = contents.lines().collect();
+    let mut grid = Vec::with_capacity(columns * rows);
+
+    for (index, line) in lines.iter().take(rows).enumerate() {
+        let line_number = first_line + index;
+        let mut values =
+            parse_line(line).map_err(|reason| format!("Line {line_number}: {reason}"))?;
+
+        if values.len() != columns {
+            let problem = format!(
+                "Line {line_number} has {} values, needed {columns}",
+                values.len()
+            );
+            match recovery {
+                Recovery::Strict => return Err(problem),
+                Recovery::PadAndTruncate(warnings) => {
+                    let fix = if values.len() < columns {
+                        "padded with zeros"
+                    } else {
+                        "dropped the rest"
+                    };
+                    warnings.push(format!("{problem}, {fix}"));
+                    values.resize(columns, T::default());
+                }
+            }
+        }
+        grid.extend(values);
+    }
+
+    if lines.len() != rows {
+        let problem = format!("{} lines, needed {rows}", lines.len());
+        match recovery {
+            Recovery::Strict => return Err(problem),
+            Recovery::PadAndTruncate(warnings) => {
+                let fix = if lines.len() < rows {
+                    "padded with zeros".to_owned()
+                } else {
+                    format!("dropped the lines after line {}", first_line + rows - 1)
+                };
+                warnings.push(format!("{problem}, {fix}"));
+                grid.resize(columns * rows, T::default());
+            }
+        }
+    }
+
+    Ok(grid)
+}
+
+/// The number of the line `rest` starts on in `file`, for a `rest` split off its end
+/// (like by [`split_version`]).
+pub(crate) fn first_line_of(file: &str, rest: &str) -> usize {
+    file[..file.len() - rest.len()].matches('\n').count() + 1
+}
+
+// How many frames a setting has to stay the same before it's written,
+// so that fades or scrolling around the map don't write the file every frame.
+const SAVE_DELAY_FRAMES: u32 = 30;
+
+/// Decides when changed settings get written to their file.
+#[derive(Debug)]
+pub(crate) struct Saver<T> {
+    saved: T,
+    // What hasn't been written yet, and for how many frames it's stayed the same.
+    pending: Option<(T, u32)>,
+}
+
+impl<T: PartialEq + Clone> Saver<T> {
+    /// `saved` is what's currently in the file.
+    pub(crate) fn new(saved: T) -> Self {
+        Self {
+            saved,
+            pending: None,
+        }
+    }
+
+    /// Called every frame with the current value, returns it once it's time to write it.
+    pub(crate) fn tick(&mut self, current: T) -> Option<T> {
+        if current == self.saved {
+            self.pending = None;
+
+            return None;
+        }
+
+        match &mut self.pending {
+            Some((pending, frames)) if *pending == current => {
+                *frames += 1;
+                if *frames < SAVE_DELAY_FRAMES {
+                    return None;
+                }
+
+                self.flush(current)
+            }
+            _ => {
+                self.pending = Some((current, 0));
+
+                None
+            }
+        }
+    }
+
+    /// The current value if it hasn't been written yet, without waiting. For when the game exits.
+    pub(crate) fn flush(&mut self, current: T) -> Option<T> {
+        self.pending = None;
+        if current == self.saved {
+            return None;
+        }
+        self.saved = current.clone();
+
+        Some(current)
+    }
+}
+
+/// Rust source for a `pub static` array named `ident` holding `data`, `per_line` numbers per line.
+pub(crate) fn rust_array(ident: &str, data: &[u8], per_line: usize) -> String {
+    let lines = data
+        .chunks(per_line)
+        .map(|chunk| format!("    {},", chunk.iter().join(", ")))
+        .join("\n");
+
+    format!(
+        "pub static {ident}: [u8; {}] = [\n{lines}\n];\n",
+        data.len()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::volume::Volume;
+
+    #[test]
+    fn version_header_round_trips() {
+        let file = format!("{}\ncontents", version_header(3));
+
+        assert_eq!(split_version(&file), Ok((3, "contents")));
+        assert_eq!(split_version("01 02"), Ok((1, "01 02")));
+        assert!(split_version("version x\n").is_err());
+    }
+
+    #[test]
+    fn writes_files_through_a_temporary_file() {
+        let dir = std::env::temp_dir().join(format!("runty8-serialize-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir_path = dir.to_str().unwrap();
+        let volume = Volume::new();
+
+        std::fs::write(dir.join("volume.txt"), "old contents").unwrap();
+        write_file(&format!("{dir_path}/volume.txt"), &volume).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(dir.join("volume.txt")).unwrap(),
+            to_string(&volume)
+        );
+        assert!(!dir.join("volume.txt.tmp").exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn settings_are_saved_once_they_settle() {
+        let mut saver = Saver::new(0);
+        assert_eq!(saver.tick(0), None);
+
+        // A fade, nothing is written while it goes on.
+        for frame in 0..SAVE_DELAY_FRAMES * 2 {
+            assert_eq!(saver.tick(frame + 1), None);
+        }
+
+        for _ in 0..SAVE_DELAY_FRAMES {
+            assert_eq!(saver.tick(5), None);
+        }
+        assert_eq!(saver.tick(5), Some(5));
+        // Only written once.
+        assert_eq!(saver.tick(5), None);
+
+        // Exiting writes what's left right away, and only if there's something.
+        assert_eq!(saver.tick(2), None);
+        assert_eq!(saver.flush(2), Some(2));
+        assert_eq!(saver.flush(2), None);
+    }
+
+    fn digits(line: &str) -> Result<Vec<u8>, String> {
+        line.chars()
+            .map(|c| {
+                c.to_digit(10)
+                    .map(|digit| digit as u8)
+                    .ok_or_else(|| format!("Invalid digit {c}"))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn grids_need_exact_dimensions() {
+        let read = |contents| read_grid(contents, (3, 2), 2, digits, &mut Recovery::Strict);
+
+        assert_eq!(read("123\n456"), Ok(vec![1, 2, 3, 4, 5, 6]));
+        // A final newline isn't an extra line.
+        assert_eq!(read("123\n456\n"), Ok(vec![1, 2, 3, 4, 5, 6]));
+
+        assert_eq!(read("123"), Err("1 lines, needed 2".to_owned()));
+        assert_eq!(read("123\n456\n789"), Err("3 lines, needed 2".to_owned()));
+        assert_eq!(
+            read("123\n4567"),
+            Err("Line 3 has 4 values, needed 3".to_owned())
+        );
+        assert_eq!(
+            read("12\n456"),
+            Err("Line 2 has 2 values, needed 3".to_owned())
+        );
+        assert_eq!(read("123\n4x6"), Err("Line 3: Invalid digit x".to_owned()));
+    }
+
+    #[test]
+    fn grids_can_be_padded_and_truncated() {
+        let mut warnings = vec![];
+        let mut recovery = Recovery::PadAndTruncate(&mut warnings);
+
+        let grid = read_grid("12\n4567\n789", (3, 2), 1, digits, &mut recovery);
+        assert_eq!(grid, Ok(vec![1, 2, 0, 4, 5, 6]));
+        let grid = read_grid("", (3, 2), 1, digits, &mut recovery);
+        assert_eq!(grid, Ok(vec![0; 6]));
+        assert_eq!(
+            warnings,
+            [
+                "Line 1 has 2 values, needed 3, padded with zeros",
+                "Line 2 has 4 values, needed 3, dropped the rest",
+                "3 lines, needed 2, dropped the lines after line 2",
+                "0 lines, needed 2, padded with zeros",
+            ]
+        );
+    }
+
+    #[test]
+    fn writes_rust_arrays() {
+        assert_eq!(
+            rust_array("DATA", &[1, 2, 3, 255, 0], 2),
+            "pub static DATA: [u8; 5] = [\n    1, 2,\n    3, 255,\n    0,\n];\n"
+        );
+    }
+}
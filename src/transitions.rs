@@ -0,0 +1,195 @@
+//! Screen transitions, to go from one scene to another.
+//!
+//! They're functions of how far along the transition is (`t`, from `0.0` to `1.0`),
+//! so games drive them from their own state. Call them at the end of `draw`:
+//! [`screen_wipe`] and [`dissolve`] work on whatever has been drawn so far.
+//!
+//! ```no_run
+//! # use runty8::{transitions, Pico8};
+//! # fn draw(pico8: &mut Pico8, fade_out_frames: i32) {
+//! // Fade to black over 15 frames.
+//! transitions::fade_palette(pico8, fade_out_frames as f32 / 15.0);
+//! # }
+//! ```
+use crate::{Color, Pico8};
+
+// Each color darkened step by step until it's black, a common Pico8 fade table.
+const FADE_STEPS: usize = 7;
+const FADE_TABLE: [[u8; FADE_STEPS]; 16] = [
+    [0, 0, 0, 0, 0, 0, 0],
+    [1, 1, 1, 0, 0, 0, 0],
+    [2, 2, 2, 1, 0, 0, 0],
+    [3, 3, 3, 1, 0, 0, 0],
+    [4, 2, 2, 2, 1, 0, 0],
+    [5, 5, 1, 1, 1, 0, 0],
+    [6, 13, 13, 5, 5, 1, 0],
+    [7, 6, 13, 13, 5, 1, 0],
+    [8, 8, 2, 2, 2, 0, 0],
+    [9, 4, 4, 4, 5, 0, 0],
+    [10, 9, 4, 4, 5, 5, 0],
+    [11, 3, 3, 3, 3, 0, 0],
+    [12, 12, 3, 1, 1, 1, 0],
+    [13, 5, 5, 1, 1, 1, 0],
+    [14, 13, 4, 2, 2, 1, 0],
+    [15, 13, 13, 5, 5, 1, 0],
+];
+
+// How many more pixels the slowest column of `screen_wipe` has left to go than the fastest one.
+const MAX_WIPE_DELAY: i32 = 24;
+
+/// Darkens the whole screen through the screen palette (see [`Pico8::screen_pal`]):
+/// `0.0` shows the original colors, `1.0` is all black.
+///
+/// Only needs to be called when `t` changes, the screen palette stays until it's reset.
+pub fn fade_palette(pico8: &mut Pico8, t: f32) {
+    let step = (progress(t) * (FADE_STEPS - 1) as f32).round() as usize;
+
+    for (color, ramp) in Color::all().zip(FADE_TABLE) {
+        pico8.screen_pal(color, ramp[step]);
+    }
+}
+
+/// Melts the screen down, column by column, uncovering `color`:
+/// `0.0` leaves the screen as it is, `1.0` is all `color`.
+pub fn screen_wipe(pico8: &mut Pico8, t: f32, color: impl Into<Color>) {
+    let color = color.into();
+    let distance = (progress(t) * (128 + MAX_WIPE_DELAY) as f32) as i32;
+
+    for x in 0..128 {
+        // Pairs of columns move together, a bit behind or ahead of their neighbours.
+        let delay = (hash(x / 2) % MAX_WIPE_DELAY as u32) as i32;
+        let offset = (distance - delay).clamp(0, 128);
+        if offset == 0 {
+            continue;
+        }
+
+        for y in (0..128).rev() {
+            let moved = pico8.draw_data.pixel(x, y - offset).unwrap_or(color);
+            pico8.draw_data.set_screen_pixel(x, y, moved);
+        }
+    }
+}
+
+/// Covers the screen with `color`, a few scattered pixels at a time:
+/// `0.0` leaves the screen as it is, `1.0` is all `color`.
+pub fn dissolve(pico8: &mut Pico8, t: f32, color: impl Into<Color>) {
+    let color = color.into();
+    // Out of 256, so that every pixel is covered at `1.0`.
+    let covered = (progress(t) * 256.0) as u32;
+
+    for (x, y) in (0..128).flat_map(|y| (0..128).map(move |x| (x, y))) {
+        if hash(x + 128 * y) % 256 < covered {
+            pico8.draw_data.set_screen_pixel(x, y, color);
+        }
+    }
+}
+
+fn progress(t: f32) -> f32 {
+    if t.is_nan() {
+        0.0
+    } else {
+        t.clamp(0.0, 1.0)
+    }
+}
+
+// Scatters consecutive numbers, the same every frame.
+fn hash(n: i32) -> u32 {
+    let mut n = n as u32;
+    n = (n ^ 61) ^ (n >> 16);
+    n = n.wrapping_mul(9);
+    n ^= n >> 4;
+    n = n.wrapping_mul(0x27d4_eb2d);
+
+    n ^ (n >> 15)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::draw_data::DrawData;
+    use crate::runtime::state::State;
+    use crate::{Palette, Resources};
+
+    fn pico8() -> Pico8 {
+        Pico8::new(DrawData::new(), State::new(), Resources::empty())
+    }
+
+    fn count(pico8: &Pico8, color: Color) -> usize {
+        (0..128)
+            .flat_map(|y| (0..128).map(move |x| (x, y)))
+            .filter(|&(x, y)| pico8.draw_data.pixel(x, y) == Some(color))
+            .count()
+    }
+
+    #[test]
+    fn fading_darkens_every_color() {
+        let mut pico8 = pico8();
+        pico8.cls(7);
+
+        fade_palette(&mut pico8, 0.0);
+        assert_eq!(
+            pico8.draw_data.rgb(Some(0), &Palette::pico8()),
+            pico8.draw_data.rgb(None, &Palette::pico8())
+        );
+
+        fade_palette(&mut pico8, 0.5);
+        let (r, g, b) = Color::LAVENDER.rgb();
+        assert_eq!(
+            pico8.draw_data.rgb(Some(0), &Palette::pico8())[..3],
+            [r, g, b]
+        );
+
+        fade_palette(&mut pico8, 1.0);
+        assert!(pico8
+            .draw_data
+            .rgb(Some(0), &Palette::pico8())
+            .iter()
+            .all(|&component| component == 0));
+        // The framebuffer is left alone.
+        assert_eq!(count(&pico8, Color::WHITE), 128 * 128);
+
+        pico8.reset_pal();
+        assert_eq!(
+            pico8.draw_data.rgb(Some(0), &Palette::pico8()),
+            pico8.draw_data.rgb(None, &Palette::pico8())
+        );
+    }
+
+    #[test]
+    fn wiping_moves_columns_down() {
+        let mut pico8 = pico8();
+        pico8.cls(7);
+        pico8.pset(10, 0, 8);
+
+        screen_wipe(&mut pico8, 0.0, 0);
+        assert_eq!(count(&pico8, Color::BLACK), 0);
+
+        screen_wipe(&mut pico8, 0.5, 0);
+        let black = count(&pico8, Color::BLACK);
+        assert!(0 < black && black < 128 * 128);
+        let red_y = (0..128).find(|&y| pico8.draw_data.pixel(10, y) == Some(Color::RED));
+        assert!(red_y.is_some_and(|y| y > 0));
+
+        screen_wipe(&mut pico8, 1.0, 0);
+        assert_eq!(count(&pico8, Color::BLACK), 128 * 128);
+    }
+
+    #[test]
+    fn dissolving_covers_more_and_more_pixels() {
+        let mut pico8 = pico8();
+
+        pico8.cls(7);
+        dissolve(&mut pico8, 0.0, 0);
+        assert_eq!(count(&pico8, Color::BLACK), 0);
+
+        dissolve(&mut pico8, 0.25, 0);
+        let quarter = count(&pico8, Color::BLACK);
+        dissolve(&mut pico8, 0.5, 0);
+        let half = count(&pico8, Color::BLACK);
+        assert!(quarter < half);
+        assert!((7000..9400).contains(&half), "{half}");
+
+        dissolve(&mut pico8, 1.0, 0);
+        assert_eq!(count(&pico8, Color::BLACK), 128 * 128);
+    }
+}
@@ -1,22 +1,161 @@
 pub mod button;
 pub mod cursor;
+pub mod dialog;
+pub mod drag;
+pub mod dropdown;
+pub mod focus;
+pub mod hover;
+pub mod scroll_area;
+#[cfg(feature = "editor")]
 pub mod slider;
+#[cfg(test)]
+pub(crate) mod testing;
 pub mod text;
-use crate::{Event, Pico8};
+pub mod text_input;
+pub mod tooltip;
+use crate::{Event, KeyState, MouseEvent, Pico8};
+use drag::Drag;
+use focus::{Focus, Focusable};
+use hover::HoverTracker;
 use std::{fmt::Debug, marker::PhantomData};
 
 pub struct DispatchEvent<'a, Msg> {
     queue: &'a mut Vec<Msg>,
+    drag: &'a mut Drag,
+    hover: &'a mut HoverTracker,
+    consumed: bool,
 }
 
 impl<'a, Msg> DispatchEvent<'a, Msg> {
-    pub(crate) fn new(queue: &'a mut Vec<Msg>) -> Self {
-        Self { queue }
+    pub(crate) fn new(
+        queue: &'a mut Vec<Msg>,
+        drag: &'a mut Drag,
+        hover: &'a mut HoverTracker,
+    ) -> Self {
+        Self {
+            queue,
+            drag,
+            hover,
+            consumed: false,
+        }
     }
 
     pub fn call(&mut self, msg: Msg) {
         self.queue.push(msg);
     }
+
+    /// Payload of the element being dragged, if any.
+    pub fn dragged(&self) -> Option<usize> {
+        self.drag.payload()
+    }
+
+    pub fn start_drag(&mut self, payload: usize) {
+        self.drag.start(payload);
+    }
+
+    /// Stops the current event from reaching the elements that haven't seen it yet
+    /// (elements in lower layers, or later in the same layer).
+    ///
+    /// Meant for things drawn on top of others, so clicks don't fall through them.
+    /// Ticks shouldn't be consumed.
+    pub fn consume(&mut self) {
+        self.consumed = true;
+    }
+
+    pub fn is_consumed(&self) -> bool {
+        self.consumed
+    }
+}
+
+/// Layers of a [`Tree`], drawn in this order.
+/// Input goes through them in reverse, so the topmost layer gets it first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    Base,
+    Overlay,
+    Cursor,
+}
+
+impl Layer {
+    const ALL: [Layer; 3] = [Layer::Base, Layer::Overlay, Layer::Cursor];
+}
+
+/// Everything about the ui that outlives a single frame's view:
+/// keyboard focus, the drag in progress and what's being hovered.
+#[derive(Debug)]
+pub(crate) struct UiState {
+    focus: Focus,
+    drag: Drag,
+    hover: HoverTracker,
+}
+
+impl UiState {
+    pub(crate) fn new() -> Self {
+        Self {
+            focus: Focus::new(),
+            drag: Drag::new(),
+            hover: HoverTracker::new(),
+        }
+    }
+
+    #[cfg(feature = "editor")]
+    pub(crate) fn clear_focus(&mut self) {
+        self.focus.clear();
+    }
+
+    /// Sends `event` (if any) to the view, pushing the resulting messages to `queue`.
+    ///
+    /// Returns whether the event was a key press taken by the ui (focused widgets,
+    /// cancelling a drag), in which case it shouldn't trigger anything else.
+    pub(crate) fn dispatch<Msg: Copy + Debug>(
+        &mut self,
+        view: &mut dyn Widget<Msg = Msg>,
+        event: Option<Event>,
+        cursor_position: (i32, i32),
+        queue: &mut Vec<Msg>,
+    ) -> bool {
+        // Picks up focus changes made by the app since the last event,
+        // like focusing a text input from a key combo.
+        self.focus.sync(view);
+        self.focus.apply(view);
+
+        let event = match event {
+            Some(event) => event,
+            None => return false,
+        };
+
+        if self.drag.before_event(event) {
+            return true;
+        }
+
+        // Key presses go to the focused widget (if any).
+        let keyboard_captured = match event {
+            Event::Keyboard(key_event) => {
+                self.focus.on_key(key_event)
+                    || (self.focus.has_focus() && key_event.state == KeyState::Down)
+            }
+            _ => false,
+        };
+        // Tab and Escape move the focus before the widgets see the event,
+        // otherwise `sync` would hand it back to the widget that had it.
+        self.focus.apply(view);
+
+        // Mouse moves are handled with the new position.
+        let cursor_position = match event {
+            Event::Mouse(MouseEvent::Move { x, y }) => (x, y),
+            _ => cursor_position,
+        };
+
+        let dispatch_event = &mut DispatchEvent::new(queue, &mut self.drag, &mut self.hover);
+        view.on_event(event, cursor_position, dispatch_event);
+        self.drag.after_event(event);
+        self.hover.after_event(event);
+
+        self.focus.sync(view);
+        self.focus.apply(view);
+
+        keyboard_captured
+    }
 }
 
 pub trait Widget {
@@ -30,10 +169,53 @@ pub trait Widget {
     );
 
     fn draw(&mut self, draw: &mut Pico8);
+
+    /// Draws the parts of this widget that belong to `layer`.
+    /// Only widgets that can contain elements in other layers, or that draw popups
+    /// in [`Layer::Overlay`], need to implement this (and draw every layer in `draw`, see [`draw_layers`]).
+    fn draw_layer(&mut self, draw: &mut Pico8, layer: Layer) {
+        if layer == Layer::Base {
+            self.draw(draw);
+        }
+    }
+
+    /// Same as [`Widget::draw_layer`], for events.
+    fn on_event_layer(
+        &mut self,
+        event: Event,
+        cursor_position: (i32, i32),
+        dispatch_event: &mut DispatchEvent<Self::Msg>,
+        layer: Layer,
+    ) {
+        if layer == Layer::Base {
+            self.on_event(event, cursor_position, dispatch_event);
+        }
+    }
+
+    /// Calls `f` with every focusable widget in this subtree, in tree order.
+    /// Widgets containing other elements should forward this to them.
+    fn visit_focusable(&mut self, _f: &mut dyn FnMut(&mut dyn Focusable)) {}
+
+    /// Called right before drawing by widgets that hit test their content, like [`button::Button`],
+    /// with how the user is interacting with them.
+    /// Widgets containing other elements should forward this to them.
+    fn set_interaction(&mut self, _interaction: Interaction) {}
+}
+
+/// Whether the widget around a [`DrawFn`] (a button, say) is hovered, pressed or focused,
+/// for drawing it accordingly. See [`DrawFn::with_interaction`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Interaction {
+    /// The cursor is over it.
+    pub hovered: bool,
+    /// It's held down, with the cursor over it.
+    pub pressed: bool,
+    /// It has the keyboard focus.
+    pub focused: bool,
 }
 
 pub struct Tree<'a, Msg> {
-    children: Vec<Element<'a, Msg>>,
+    children: Vec<(Layer, Element<'a, Msg>)>,
 }
 
 pub struct Element<'a, Msg> {
@@ -71,33 +253,75 @@ struct Map<'a, Msg, BigMsg> {
     f: Box<dyn Fn(Msg) -> BigMsg + 'a>,
 }
 
-impl<'a, Msg: Copy + Debug + 'a, BigMsg: Copy + Debug + 'a> Widget for Map<'a, Msg, BigMsg> {
-    type Msg = BigMsg;
-
-    fn on_event(
+impl<'a, Msg: Copy + Debug + 'a, BigMsg> Map<'a, Msg, BigMsg> {
+    // Sends the event to the wrapped element, to all of it or only to the given layer.
+    fn dispatch(
         &mut self,
         event: Event,
         cursor_position: (i32, i32),
-        dispatch_event: &mut DispatchEvent<Self::Msg>,
+        dispatch_event: &mut DispatchEvent<BigMsg>,
+        layer: Option<Layer>,
     ) {
         // TODO: Find a better way of doing this, we're now allocating a new vec
         // For every component that uses map, this is the problem we wanted to avoid
         // by introducing DispatchEvent
         let mut queue_small = vec![];
-        let mut dispatch_event_small = DispatchEvent::new(&mut queue_small);
+        let mut dispatch_event_small =
+            DispatchEvent::new(&mut queue_small, dispatch_event.drag, dispatch_event.hover);
+        dispatch_event_small.consumed = dispatch_event.consumed;
 
-        self.element
-            .as_widget_mut()
-            .on_event(event, cursor_position, &mut dispatch_event_small);
+        let widget = self.element.as_widget_mut();
+        match layer {
+            Some(layer) => {
+                widget.on_event_layer(event, cursor_position, &mut dispatch_event_small, layer)
+            }
+            None => widget.on_event(event, cursor_position, &mut dispatch_event_small),
+        }
+        dispatch_event.consumed = dispatch_event_small.consumed;
 
         for small_msg in queue_small {
             dispatch_event.call((self.f)(small_msg));
         }
     }
+}
+
+impl<'a, Msg: Copy + Debug + 'a, BigMsg: Copy + Debug + 'a> Widget for Map<'a, Msg, BigMsg> {
+    type Msg = BigMsg;
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        cursor_position: (i32, i32),
+        dispatch_event: &mut DispatchEvent<Self::Msg>,
+    ) {
+        self.dispatch(event, cursor_position, dispatch_event, None)
+    }
+
+    fn on_event_layer(
+        &mut self,
+        event: Event,
+        cursor_position: (i32, i32),
+        dispatch_event: &mut DispatchEvent<Self::Msg>,
+        layer: Layer,
+    ) {
+        self.dispatch(event, cursor_position, dispatch_event, Some(layer))
+    }
 
     fn draw(&mut self, draw: &mut Pico8) {
         self.element.as_widget_mut().draw(draw)
     }
+
+    fn draw_layer(&mut self, draw: &mut Pico8, layer: Layer) {
+        self.element.as_widget_mut().draw_layer(draw, layer)
+    }
+
+    fn visit_focusable(&mut self, f: &mut dyn FnMut(&mut dyn Focusable)) {
+        self.element.as_widget_mut().visit_focusable(f)
+    }
+
+    fn set_interaction(&mut self, interaction: Interaction) {
+        self.element.as_widget_mut().set_interaction(interaction)
+    }
 }
 
 impl<'a, Msg> Tree<'a, Msg> {
@@ -105,12 +329,31 @@ impl<'a, Msg> Tree<'a, Msg> {
         Self::with_children(vec![])
     }
 
+    /// An empty tree with room for `capacity` children,
+    /// for views that know how many they push so the tree doesn't have to grow.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            children: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Keeps the room `children` has left, for pushing more.
     pub fn with_children(children: Vec<Element<'a, Msg>>) -> Self {
-        Self { children }
+        let mut tree = Self::with_capacity(children.capacity());
+        tree.children
+            .extend(children.into_iter().map(|element| (Layer::Base, element)));
+
+        tree
+    }
+
+    pub fn push(self, element: impl Into<Element<'a, Msg>>) -> Self {
+        self.push_to(Layer::Base, element)
     }
 
-    pub fn push(mut self, element: impl Into<Element<'a, Msg>>) -> Self {
-        self.children.push(element.into());
+    /// Pushes an element to the given layer, so it's drawn above (and gets input before)
+    /// the elements in lower layers, no matter where they are in the tree.
+    pub fn push_to(mut self, layer: Layer, element: impl Into<Element<'a, Msg>>) -> Self {
+        self.children.push((layer, element.into()));
         self
     }
 }
@@ -130,35 +373,97 @@ impl<'a, Msg: Copy + Debug> Widget for Tree<'a, Msg> {
         cursor_position: (i32, i32),
         dispatch_event: &mut DispatchEvent<Self::Msg>,
     ) {
-        for element in self.children.iter_mut() {
-            element
-                .widget
-                .on_event(event, cursor_position, dispatch_event);
+        for layer in Layer::ALL.into_iter().rev() {
+            self.on_event_layer(event, cursor_position, dispatch_event, layer);
+        }
+    }
+
+    fn on_event_layer(
+        &mut self,
+        event: Event,
+        cursor_position: (i32, i32),
+        dispatch_event: &mut DispatchEvent<Self::Msg>,
+        layer: Layer,
+    ) {
+        for (element_layer, element) in self.children.iter_mut() {
+            if dispatch_event.is_consumed() {
+                return;
+            }
+
+            // Base elements can contain elements from any layer,
+            // but everything inside a higher layer element stays in that layer.
+            match *element_layer {
+                Layer::Base => {
+                    element
+                        .widget
+                        .on_event_layer(event, cursor_position, dispatch_event, layer)
+                }
+                element_layer if element_layer == layer => {
+                    element
+                        .widget
+                        .on_event(event, cursor_position, dispatch_event)
+                }
+                _ => {}
+            }
         }
     }
 
     fn draw(&mut self, draw: &mut Pico8) {
-        for element in self.children.iter_mut() {
-            element.widget.draw(draw);
+        draw_layers(self, draw);
+    }
+
+    fn draw_layer(&mut self, draw: &mut Pico8, layer: Layer) {
+        for (element_layer, element) in self.children.iter_mut() {
+            match *element_layer {
+                Layer::Base => element.widget.draw_layer(draw, layer),
+                element_layer if element_layer == layer => element.widget.draw(draw),
+                _ => {}
+            }
+        }
+    }
+
+    fn visit_focusable(&mut self, f: &mut dyn FnMut(&mut dyn Focusable)) {
+        for (_, element) in self.children.iter_mut() {
+            element.widget.visit_focusable(f);
+        }
+    }
+
+    fn set_interaction(&mut self, interaction: Interaction) {
+        for (_, element) in self.children.iter_mut() {
+            element.widget.set_interaction(interaction);
         }
     }
 }
 
-pub struct DrawFn<'a, Msg> {
-    pd: PhantomData<Msg>,
-    f: Box<dyn FnMut(&mut Pico8) + 'a>,
+type DrawCallback<'a> = Box<dyn FnMut(&mut Pico8, Interaction) + 'a>;
+
+// Holds the callback itself rather than a box of it,
+// so that only the element it ends up in is allocated.
+pub struct DrawFn<'a, Msg, F = DrawCallback<'a>> {
+    pd: PhantomData<(Msg, &'a ())>,
+    f: F,
+    interaction: Interaction,
 }
 
 impl<'a, Msg: Copy + Debug + 'a> DrawFn<'a, Msg> {
-    pub fn new(f: impl FnMut(&mut Pico8) + 'a) -> Self {
-        Self {
-            f: Box::new(f),
+    pub fn new(
+        mut f: impl FnMut(&mut Pico8) + 'a,
+    ) -> DrawFn<'a, Msg, impl FnMut(&mut Pico8, Interaction) + 'a> {
+        DrawFn::with_interaction(move |draw, _| f(draw))
+    }
+
+    /// Like [`DrawFn::new`], also getting whether the widget it's drawn in is hovered,
+    /// pressed or focused. Outside of such widgets, it's all `false`.
+    pub fn with_interaction<F: FnMut(&mut Pico8, Interaction) + 'a>(f: F) -> DrawFn<'a, Msg, F> {
+        DrawFn {
+            f,
             pd: PhantomData,
+            interaction: Interaction::default(),
         }
     }
 }
 
-impl<'a, Msg: Copy + Debug> Widget for DrawFn<'a, Msg> {
+impl<'a, Msg: Copy + Debug, F: FnMut(&mut Pico8, Interaction)> Widget for DrawFn<'a, Msg, F> {
     type Msg = Msg;
 
     fn on_event(
@@ -170,8 +475,29 @@ impl<'a, Msg: Copy + Debug> Widget for DrawFn<'a, Msg> {
     }
 
     fn draw(&mut self, draw: &mut Pico8) {
-        (self.f)(draw);
+        (self.f)(draw, self.interaction);
     }
+
+    fn set_interaction(&mut self, interaction: Interaction) {
+        self.interaction = interaction;
+    }
+}
+
+/// Draws every layer of `widget`, in order.
+/// For the `draw` of widgets implementing [`Widget::draw_layer`].
+pub(crate) fn draw_layers<W: Widget + ?Sized>(widget: &mut W, draw: &mut Pico8) {
+    for layer in Layer::ALL {
+        widget.draw_layer(draw, layer);
+    }
+}
+
+/// Draws a popup (a tooltip, a dropdown list...) during [`Layer::Overlay`],
+/// in screen coordinates no matter the camera of the widgets around it.
+pub(crate) fn draw_popup(draw: &mut Pico8, f: impl FnOnce(&mut Pico8)) {
+    draw.with_saved_state(|draw| {
+        draw.camera(0, 0);
+        f(draw);
+    });
 }
 
 impl<'a, Msg: Copy + Debug + 'a, T: Widget<Msg = Msg> + 'a> From<T> for Element<'a, Msg> {
@@ -179,3 +505,87 @@ impl<'a, Msg: Copy + Debug + 'a, T: Widget<Msg = Msg> + 'a> From<T> for Element<
         Element::new(val)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Sends its id when it gets an event, and consumes it if asked to.
+    struct Probe {
+        id: usize,
+        consume: bool,
+    }
+
+    impl Widget for Probe {
+        type Msg = usize;
+
+        fn on_event(
+            &mut self,
+            _event: Event,
+            _cursor_position: (i32, i32),
+            dispatch_event: &mut DispatchEvent<Self::Msg>,
+        ) {
+            dispatch_event.call(self.id);
+
+            if self.consume {
+                dispatch_event.consume();
+            }
+        }
+
+        fn draw(&mut self, _draw: &mut Pico8) {}
+    }
+
+    fn probe(id: usize) -> Probe {
+        Probe { id, consume: false }
+    }
+
+    fn consuming_probe(id: usize) -> Probe {
+        Probe { id, consume: true }
+    }
+
+    fn send(tree: Tree<'_, usize>) -> Vec<usize> {
+        let mut queue = vec![];
+        let mut element: Element<'_, usize> = tree.into();
+
+        element.as_widget_mut().on_event(
+            Event::Tick {
+                delta_millis: 0.0,
+                frame: 0,
+            },
+            (0, 0),
+            &mut DispatchEvent::new(&mut queue, &mut Drag::new(), &mut HoverTracker::new()),
+        );
+
+        queue
+    }
+
+    #[test]
+    fn higher_layers_get_events_first() {
+        let nested = Tree::new().push(probe(1)).push_to(Layer::Cursor, probe(2));
+        let tree = Tree::new()
+            .push_to(Layer::Overlay, probe(0))
+            .push(nested)
+            .push(probe(3))
+            .push_to(Layer::Overlay, probe(4));
+
+        assert_eq!(send(tree), [2, 0, 4, 1, 3]);
+    }
+
+    #[test]
+    fn consumed_events_dont_fall_through() {
+        let tree = Tree::new()
+            .push(probe(0))
+            .push_to(Layer::Overlay, consuming_probe(1))
+            .push_to(Layer::Overlay, probe(2));
+
+        assert_eq!(send(tree), [1]);
+
+        // Also through `Element::map`.
+        let mapped: Element<'_, usize> = Tree::new()
+            .push_to(Layer::Overlay, consuming_probe(1))
+            .into();
+        let tree = Tree::new().push(probe(0)).push(mapped.map(|id| id + 10));
+
+        assert_eq!(send(tree), [11]);
+    }
+}
@@ -1,7 +1,9 @@
-use crate::Pico8;
-use crate::{Event, MouseButton};
+use crate::{Color, Pico8};
+use crate::{Event, Key, KeyState, KeyboardEvent, MouseButton};
 
-use super::{DispatchEvent, Element, Widget};
+use super::focus::Focusable;
+use super::tooltip::{self, HoverTimer};
+use super::{draw_layers, DispatchEvent, Element, Interaction, Layer, Widget};
 use std::fmt::Debug;
 
 pub struct Button<'a, Msg> {
@@ -10,13 +12,27 @@ pub struct Button<'a, Msg> {
     width: i32,
     height: i32,
     on_press: Option<Msg>,
-    on_hover: Option<Msg>,
-    on_leave: Option<Msg>,
+    on_right_press: Option<Msg>,
+    on_key_press: Option<fn(Key) -> Msg>,
     state: &'a mut State,
     content: Element<'a, Msg>,
     active_mode: ActiveMode,
+    tooltip: Option<&'a str>,
+    focusable: bool,
+    disabled: bool,
+    repeat: Option<Repeat>,
 }
 
+/// Frames to wait before repeating a held press, and between repeats.
+#[derive(Clone, Copy)]
+struct Repeat {
+    delay: u32,
+    interval: u32,
+}
+
+// Disabled buttons have their content drawn with this palette.
+const DISABLED_PALETTE: [u8; 16] = [0, 5, 5, 5, 5, 5, 6, 6, 5, 5, 6, 5, 5, 5, 5, 6];
+
 #[derive(PartialEq)]
 enum ActiveMode {
     Release,
@@ -27,7 +43,14 @@ enum ActiveMode {
 pub struct State {
     pressed: bool,
     mouse_pressed: bool,
+    // Same as the two above, for the right mouse button.
+    right_pressed: bool,
+    right_mouse_pressed: bool,
     mouse_contained: bool,
+    hover_timer: HoverTimer,
+    focused: bool,
+    // Frames the button has been held down for, used for repeating presses.
+    held_frames: u32,
 }
 
 impl State {
@@ -35,9 +58,37 @@ impl State {
         Self {
             pressed: false,
             mouse_pressed: false,
+            right_pressed: false,
+            right_mouse_pressed: false,
             mouse_contained: false,
+            hover_timer: HoverTimer::new(),
+            focused: false,
+            held_frames: 0,
         }
     }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Hands the focus to the button, like pressing Tab until it's reached.
+    pub fn focus(&mut self) {
+        self.focused = true;
+    }
+
+    pub fn blur(&mut self) {
+        self.focused = false;
+    }
+}
+
+impl Focusable for State {
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
 }
 
 impl Default for State {
@@ -62,11 +113,15 @@ impl<'a, Msg> Button<'a, Msg> {
             width,
             height,
             on_press,
-            on_hover: None,
-            on_leave: None,
+            on_right_press: None,
+            on_key_press: None,
             state,
             content: content.into(),
             active_mode: ActiveMode::Release,
+            tooltip: None,
+            focusable: false,
+            disabled: false,
+            repeat: None,
         }
     }
 
@@ -76,14 +131,49 @@ impl<'a, Msg> Button<'a, Msg> {
         self
     }
 
-    pub fn on_hover(mut self, on_hover: Msg) -> Self {
-        self.on_hover = Some(on_hover);
+    /// Sent when the right mouse button is pressed on the button, or dragged onto it while held.
+    /// Always sent on press, even for buttons that send `on_press` on release.
+    pub fn on_right_press(mut self, msg: Msg) -> Self {
+        self.on_right_press = Some(msg);
+
+        self
+    }
+
+    /// Lets the button be reached with Tab, and pressed with Enter or Space while focused.
+    pub fn focusable(mut self) -> Self {
+        self.focusable = true;
+
+        self
+    }
+
+    /// Sent with the other keys pressed while the button is focused, like arrows to move around.
+    pub fn on_key_press(mut self, on_key_press: fn(Key) -> Msg) -> Self {
+        self.on_key_press = Some(on_key_press);
+
+        self
+    }
+
+    /// Disabled buttons don't send any messages, and their content is drawn greyed out.
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
 
         self
     }
 
-    pub fn on_leave(mut self, on_leave: Msg) -> Self {
-        self.on_leave = Some(on_leave);
+    /// Keeps sending the press message while the button is held down:
+    /// first after `delay_frames`, then every `interval_frames`.
+    pub fn repeat_on_hold(mut self, delay_frames: u32, interval_frames: u32) -> Self {
+        self.repeat = Some(Repeat {
+            delay: delay_frames,
+            interval: interval_frames.max(1),
+        });
+
+        self
+    }
+
+    /// Text shown next to the cursor when it rests over the button.
+    pub fn tooltip(mut self, text: &'a str) -> Self {
+        self.tooltip = Some(text);
 
         self
     }
@@ -94,11 +184,22 @@ impl<'a, Msg> Button<'a, Msg> {
 
         contains_x && contains_y
     }
-}
 
-struct Delta<T> {
-    old: T,
-    new: T,
+    // A state that was focused while its button was focusable keeps the flag
+    // after the button stops being focusable (the focus only visits focusable ones).
+    fn focused(&self) -> bool {
+        self.focusable && self.state.focused
+    }
+
+    // As of the last event, which hit tested the button.
+    fn interaction(&self) -> Interaction {
+        Interaction {
+            hovered: self.state.mouse_contained,
+            // Pressed buttons only look pressed while releasing would press them.
+            pressed: self.state.pressed && self.state.mouse_contained,
+            focused: self.focused(),
+        }
+    }
 }
 
 impl<'a, Msg: Copy + Debug + 'a> Widget for Button<'a, Msg> {
@@ -113,6 +214,19 @@ impl<'a, Msg: Copy + Debug + 'a> Widget for Button<'a, Msg> {
         use crate::MouseEvent::*;
         use Event::*;
 
+        if self.tooltip.is_some() {
+            let hovering = self.contains(cursor_position.0, cursor_position.1);
+            self.state.hover_timer.on_event(event, hovering);
+        }
+
+        if self.disabled {
+            self.state.mouse_contained = self.contains(cursor_position.0, cursor_position.1);
+            self.state.pressed = false;
+            self.state.held_frames = 0;
+
+            return;
+        }
+
         // TODO: Dispatch events for content?
         match event {
             Mouse(Down(MouseButton::Left)) => {
@@ -126,14 +240,21 @@ impl<'a, Msg: Copy + Debug + 'a> Widget for Button<'a, Msg> {
                     }
 
                     self.state.pressed = true;
+                    self.state.held_frames = 0;
                 }
             }
             Mouse(Up(MouseButton::Left)) => {
                 self.state.mouse_pressed = false;
 
+                // Releasing after repeating shouldn't send yet another message.
+                let repeated = self
+                    .repeat
+                    .is_some_and(|repeat| self.state.held_frames >= repeat.delay);
+
                 if self.contains(cursor_position.0, cursor_position.1)
                     && self.state.pressed
                     && self.active_mode == ActiveMode::Release
+                    && !repeated
                 {
                     if let Some(on_press) = self.on_press {
                         dispatch_event.call(on_press);
@@ -141,40 +262,41 @@ impl<'a, Msg: Copy + Debug + 'a> Widget for Button<'a, Msg> {
                 }
 
                 self.state.pressed = false;
+                self.state.held_frames = 0;
             }
-            Mouse(Move { .. }) => {
-                let currently_contained = self.contains(cursor_position.0, cursor_position.1);
-
-                match (Delta {
-                    old: self.state.mouse_contained,
-                    new: currently_contained,
-                }) {
-                    Delta {
-                        old: false,
-                        new: true,
-                    } => {
-                        if let Some(on_hover) = self.on_hover {
-                            dispatch_event.call(on_hover);
-                        }
-                    }
-                    Delta {
-                        old: true,
-                        new: false,
-                    } => {
-                        if let Some(on_leave) = self.on_leave {
-                            dispatch_event.call(on_leave)
-                        }
+            Mouse(Down(MouseButton::Right)) => {
+                self.state.right_mouse_pressed = true;
+
+                if self.contains(cursor_position.0, cursor_position.1) {
+                    if let Some(on_right_press) = self.on_right_press {
+                        dispatch_event.call(on_right_press);
                     }
-                    _ => {}
+
+                    self.state.right_pressed = true;
                 }
+            }
+            Mouse(Up(MouseButton::Right)) => {
+                self.state.right_mouse_pressed = false;
+                self.state.right_pressed = false;
+            }
+            Mouse(Move { .. }) => {
+                self.state.mouse_contained = self.contains(cursor_position.0, cursor_position.1);
 
-                self.state.mouse_contained = currently_contained;
+                if !self.state.mouse_contained {
+                    self.state.right_pressed = false;
+                } else if self.state.right_mouse_pressed && !self.state.right_pressed {
+                    self.state.right_pressed = true;
+                    if let Some(on_right_press) = self.on_right_press {
+                        dispatch_event.call(on_right_press);
+                    }
+                }
 
                 match self.active_mode {
                     ActiveMode::Press => {
                         if self.state.mouse_contained {
                             if self.state.mouse_pressed && !self.state.pressed {
                                 self.state.pressed = true;
+                                self.state.held_frames = 0;
                                 if let Some(on_press) = self.on_press {
                                     dispatch_event.call(on_press);
                                 }
@@ -186,13 +308,398 @@ impl<'a, Msg: Copy + Debug + 'a> Widget for Button<'a, Msg> {
                     ActiveMode::Release => {}
                 };
             }
+            Keyboard(KeyboardEvent {
+                key: Key::Enter | Key::Space,
+                state: KeyState::Down,
+            }) if self.focused() => {
+                if let Some(on_press) = self.on_press {
+                    dispatch_event.call(on_press);
+                }
+            }
+            Keyboard(KeyboardEvent {
+                key,
+                state: KeyState::Down,
+            }) if self.focused() => {
+                if let Some(on_key_press) = self.on_key_press {
+                    dispatch_event.call(on_key_press(key));
+                }
+            }
+            Tick { .. } => {
+                if let Some(repeat) = self.repeat {
+                    if self.state.pressed && self.contains(cursor_position.0, cursor_position.1) {
+                        self.state.held_frames += 1;
+                        let held_frames = self.state.held_frames;
+
+                        if held_frames >= repeat.delay
+                            && (held_frames - repeat.delay).is_multiple_of(repeat.interval)
+                        {
+                            if let Some(on_press) = self.on_press {
+                                dispatch_event.call(on_press);
+                            }
+                        }
+                    }
+                }
+            }
             _ => {}
         }
     }
 
     fn draw(&mut self, draw: &mut Pico8) {
-        draw.append_camera(-self.x, -self.y);
-        self.content.as_widget_mut().draw(draw);
-        draw.append_camera(self.x, self.y);
+        draw_layers(self, draw);
+    }
+
+    // The tooltip goes above everything else, the rest (content included) in the base layer.
+    fn draw_layer(&mut self, draw: &mut Pico8, layer: Layer) {
+        match layer {
+            Layer::Base => {
+                draw.with_saved_state(|draw| {
+                    if self.disabled {
+                        for (color, grey) in Color::all().zip(DISABLED_PALETTE) {
+                            draw.pal(color, grey);
+                        }
+                    }
+
+                    let interaction = self.interaction();
+                    draw.with_camera(-self.x, -self.y, |draw| {
+                        let content = self.content.as_widget_mut();
+                        content.set_interaction(interaction);
+                        content.draw(draw);
+                    });
+                });
+
+                if self.focused() {
+                    draw.rect(
+                        self.x - 1,
+                        self.y - 1,
+                        self.x + self.width,
+                        self.y + self.height,
+                        10,
+                    );
+                }
+            }
+            Layer::Overlay => {
+                if let Some(text) = self.tooltip {
+                    if self.state.hover_timer.is_showing() {
+                        tooltip::draw_tooltip(draw, text);
+                    }
+                }
+            }
+            Layer::Cursor => {}
+        }
+    }
+
+    fn visit_focusable(&mut self, f: &mut dyn FnMut(&mut dyn Focusable)) {
+        if self.focusable {
+            f(self.state)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::drag::Drag;
+    use crate::ui::hover::HoverTracker;
+    use crate::ui::DrawFn;
+    use crate::MouseEvent;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Pressed;
+
+    const INSIDE: (i32, i32) = (4, 4);
+
+    fn mouse_down() -> Event {
+        Event::Mouse(MouseEvent::Down(MouseButton::Left))
+    }
+
+    fn mouse_up() -> Event {
+        Event::Mouse(MouseEvent::Up(MouseButton::Left))
+    }
+
+    fn tick() -> Event {
+        Event::Tick {
+            delta_millis: 0.0,
+            frame: 0,
+        }
+    }
+
+    // Sends every event to a freshly built button (like a view would every frame),
+    // and returns how many messages it sent.
+    fn count_presses(
+        state: &mut State,
+        events: impl IntoIterator<Item = Event>,
+        build: impl Fn(Button<'_, Pressed>) -> Button<'_, Pressed>,
+    ) -> usize {
+        let mut queue = vec![];
+        let mut drag = Drag::new();
+
+        for event in events {
+            let mut button = build(Button::new(
+                0,
+                0,
+                8,
+                8,
+                Some(Pressed),
+                state,
+                DrawFn::new(|_| {}),
+            ));
+            button.on_event(
+                event,
+                INSIDE,
+                &mut DispatchEvent::new(&mut queue, &mut drag, &mut HoverTracker::new()),
+            );
+        }
+
+        queue.len()
+    }
+
+    #[test]
+    fn click_sends_one_message() {
+        let mut state = State::new();
+
+        assert_eq!(
+            count_presses(&mut state, [mouse_down(), mouse_up()], |b| b),
+            1
+        );
+        assert_eq!(
+            count_presses(&mut state, [mouse_down(), mouse_up()], |b| b
+                .event_on_press()),
+            1
+        );
+    }
+
+    #[test]
+    fn disabled_buttons_send_nothing() {
+        let mut state = State::new();
+        let events = [mouse_down(), tick(), tick(), mouse_up()];
+
+        let count = count_presses(&mut state, events, |b| {
+            b.disabled(true).repeat_on_hold(0, 1).event_on_press()
+        });
+
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn disabled_buttons_leave_the_palette_as_it_was() {
+        let mut state = State::new();
+        let mut harness = crate::ui::testing::Harness::new();
+
+        let view = crate::ui::Tree::new()
+            .push(DrawFn::new(|draw| draw.pal(8, 12)))
+            .push(
+                Button::new(
+                    0,
+                    0,
+                    8,
+                    8,
+                    None,
+                    &mut state,
+                    DrawFn::new(|draw| draw.pset(0, 0, 8)),
+                )
+                .disabled(true),
+            )
+            .push(DrawFn::new(|draw| draw.pset(20, 20, 8)));
+        harness.step::<Pressed>(view, tick());
+
+        assert_eq!(harness.pixel(0, 0), Color::from(DISABLED_PALETTE[8]));
+        assert_eq!(harness.pixel(20, 20), Color::from(12));
+    }
+
+    #[test]
+    fn holding_repeats_after_delay() {
+        let mut state = State::new();
+        let events = std::iter::once(mouse_down()).chain(std::iter::repeat_n(tick(), 10));
+
+        // Pressed at 0, then repeats at frames 4, 6, 8 and 10.
+        let count = count_presses(&mut state, events, |b| {
+            b.event_on_press().repeat_on_hold(4, 2)
+        });
+
+        assert_eq!(count, 5);
+    }
+
+    #[test]
+    fn release_mode_repeats_without_extra_release_message() {
+        let mut state = State::new();
+        let events = std::iter::once(mouse_down())
+            .chain(std::iter::repeat_n(tick(), 6))
+            .chain(std::iter::once(mouse_up()));
+
+        // Repeats at frames 4, 5 and 6, nothing on release.
+        let count = count_presses(&mut state, events, |b| b.repeat_on_hold(4, 1));
+        assert_eq!(count, 3);
+
+        // A short click still sends a message on release.
+        let events = [mouse_down(), tick(), mouse_up()];
+        let count = count_presses(&mut state, events, |b| b.repeat_on_hold(4, 1));
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn focused_buttons_send_the_keys_pressed() {
+        let mut state = State::new();
+        state.set_focused(true);
+        let key_down = |key| {
+            Event::Keyboard(KeyboardEvent {
+                key,
+                state: KeyState::Down,
+            })
+        };
+
+        let count = count_presses(&mut state, [key_down(Key::LeftArrow)], |b| {
+            b.focusable().on_key_press(|_| Pressed)
+        });
+        assert_eq!(count, 1);
+
+        // The focus left with the button's focusability, even if the state still says focused.
+        let count = count_presses(&mut state, [key_down(Key::LeftArrow)], |b| {
+            b.on_key_press(|_| Pressed)
+        });
+        assert_eq!(count, 0);
+        let count = count_presses(&mut state, [key_down(Key::Enter)], |b| b);
+        assert_eq!(count, 0);
+    }
+
+    mod harness {
+        use super::*;
+        use crate::ui::testing::{click, mouse_down, mouse_move, mouse_up, Harness};
+
+        fn button(state: &mut State) -> Button<'_, Pressed> {
+            Button::new(
+                10,
+                10,
+                8,
+                8,
+                Some(Pressed),
+                state,
+                DrawFn::new(|draw| draw.rectfill(0, 0, 7, 7, 8)),
+            )
+        }
+
+        fn view(state: &mut State) -> Element<'_, Pressed> {
+            button(state).into()
+        }
+
+        fn on_press_view(state: &mut State) -> Element<'_, Pressed> {
+            button(state).event_on_press().into()
+        }
+
+        #[test]
+        fn click_inside_vs_outside() {
+            let mut harness = Harness::new();
+            let mut state = State::new();
+
+            assert_eq!(harness.run(&mut state, view, click(12, 12)), [Pressed]);
+            assert_eq!(harness.run(&mut state, view, click(30, 30)), []);
+
+            // Pressing inside, but releasing outside.
+            let events = [
+                mouse_move(12, 12),
+                mouse_down(),
+                mouse_move(30, 30),
+                mouse_up(),
+            ];
+            assert_eq!(harness.run(&mut state, view, events), []);
+
+            // The content is drawn relative to the button.
+            assert_eq!(harness.pixel(10, 10), Color::RED);
+            assert_eq!(harness.pixel(9, 9), Color::BLACK);
+        }
+
+        #[test]
+        fn content_is_drawn_hovered_and_pressed() {
+            fn view(state: &mut State) -> Element<'_, Pressed> {
+                let content = DrawFn::with_interaction(|draw, interaction| {
+                    let color = match interaction {
+                        Interaction { pressed: true, .. } => 8,
+                        Interaction { hovered: true, .. } => 12,
+                        _ => 1,
+                    };
+                    draw.rectfill(0, 0, 7, 7, color);
+                });
+
+                Button::new(10, 10, 8, 8, Some(Pressed), state, content).into()
+            }
+
+            let mut harness = Harness::new();
+            let mut state = State::new();
+
+            harness.run(&mut state, view, [mouse_move(0, 0)]);
+            assert_eq!(harness.pixel(10, 10), Color::DARK_BLUE);
+            harness.run(&mut state, view, [mouse_move(12, 12)]);
+            assert_eq!(harness.pixel(10, 10), Color::BLUE);
+            harness.run(&mut state, view, [mouse_down()]);
+            assert_eq!(harness.pixel(10, 10), Color::RED);
+
+            // Dragged off, it doesn't look pressed, until it's dragged back.
+            harness.run(&mut state, view, [mouse_move(30, 30)]);
+            assert_eq!(harness.pixel(10, 10), Color::DARK_BLUE);
+            harness.run(&mut state, view, [mouse_move(12, 12)]);
+            assert_eq!(harness.pixel(10, 10), Color::RED);
+            harness.run(&mut state, view, [mouse_up()]);
+            assert_eq!(harness.pixel(10, 10), Color::BLUE);
+        }
+
+        #[test]
+        fn event_on_press_sends_before_release() {
+            let mut harness = Harness::new();
+            let mut state = State::new();
+            let pressed = harness.run(
+                &mut state,
+                on_press_view,
+                [mouse_move(12, 12), mouse_down()],
+            );
+            assert_eq!(pressed, [Pressed]);
+            assert_eq!(harness.run(&mut state, on_press_view, [mouse_up()]), []);
+
+            // Dragging onto the button while pressed also counts.
+            let events = [mouse_move(30, 30), mouse_down(), mouse_move(12, 12)];
+            assert_eq!(harness.run(&mut state, on_press_view, events), [Pressed]);
+
+            // Regular buttons wait for the release.
+            let mut state = State::new();
+            let pressed = harness.run(&mut state, view, [mouse_move(12, 12), mouse_down()]);
+            assert_eq!(pressed, []);
+        }
+
+        #[test]
+        fn right_button_sends_its_own_message() {
+            #[derive(Clone, Copy, Debug, PartialEq)]
+            enum Msg {
+                Left,
+                Right,
+            }
+
+            fn view(state: &mut State) -> Element<'_, Msg> {
+                Button::new(10, 10, 8, 8, Some(Msg::Left), state, DrawFn::new(|_| {}))
+                    .on_right_press(Msg::Right)
+                    .into()
+            }
+
+            let right_down = Event::Mouse(MouseEvent::Down(MouseButton::Right));
+            let right_up = Event::Mouse(MouseEvent::Up(MouseButton::Right));
+            let mut harness = Harness::new();
+            let mut state = State::new();
+
+            assert_eq!(harness.run(&mut state, view, click(12, 12)), [Msg::Left]);
+
+            // Sent on press, and again when dragged back onto the button.
+            let events = [
+                mouse_move(12, 12),
+                right_down,
+                mouse_move(13, 13),
+                mouse_move(30, 30),
+                mouse_move(12, 12),
+                right_up,
+            ];
+            assert_eq!(
+                harness.run(&mut state, view, events),
+                [Msg::Right, Msg::Right]
+            );
+
+            // Nothing once released.
+            assert_eq!(harness.run(&mut state, view, [mouse_move(13, 12)]), []);
+        }
     }
 }
@@ -1,13 +1,81 @@
 use super::{DispatchEvent, Widget};
+use crate::Color;
 use crate::Pico8;
-use crate::{editor, Sprite};
+use crate::Sprite;
 use std::{fmt::Debug, marker::PhantomData};
 
 pub struct Cursor<'a, Msg> {
     state: &'a mut State,
+    kind: CursorKind,
     pd: PhantomData<Msg>,
 }
 
+/// What the mouse cursor looks like.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CursorKind {
+    #[default]
+    Arrow,
+    Pencil,
+    Bucket,
+    Crosshair,
+    Hand,
+    Resize,
+    /// A sprite from the sprite sheet, `hotspot` being the pixel (within the sprite)
+    /// that points at the mouse position.
+    Sprite {
+        sprite: usize,
+        hotspot: (i32, i32),
+    },
+    Hidden,
+}
+
+impl CursorKind {
+    /// The pixel of the cursor's sprite that points at the mouse position.
+    pub fn hotspot(&self) -> (i32, i32) {
+        match self {
+            Self::Arrow => (3, 1),
+            Self::Pencil => (0, 7),
+            Self::Bucket => (1, 6),
+            Self::Crosshair => (3, 3),
+            Self::Hand => (4, 4),
+            Self::Resize => (3, 3),
+            Self::Sprite { hotspot, .. } => *hotspot,
+            Self::Hidden => (0, 0),
+        }
+    }
+
+    fn builtin_sprite(&self) -> Option<&'static Sprite> {
+        match self {
+            Self::Arrow => Some(&MOUSE_SPRITE),
+            Self::Pencil => Some(&PENCIL_SPRITE),
+            Self::Bucket => Some(&BUCKET_SPRITE),
+            Self::Crosshair => Some(&MOUSE_TARGET_SPRITE),
+            Self::Hand => Some(&HAND_SPRITE),
+            Self::Resize => Some(&RESIZE_SPRITE),
+            Self::Sprite { .. } | Self::Hidden => None,
+        }
+    }
+
+    // Top left corner of the cursor's sprite, for the given mouse position.
+    fn sprite_position(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        let (hotspot_x, hotspot_y) = self.hotspot();
+
+        (x - hotspot_x, y - hotspot_y)
+    }
+}
+
+/// How the mouse cursor is shown, whatever its [`CursorKind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CursorMode {
+    /// Drawn as a sprite on the screen, so it looks like the rest of the game.
+    #[default]
+    Sprite,
+    /// The operating system's cursor, which follows the mouse without a frame of delay.
+    Native,
+    /// No cursor at all, for games that don't use the mouse.
+    Hidden,
+}
+
 #[derive(Debug)]
 pub struct State {
     cursor_position: (i32, i32),
@@ -19,6 +87,10 @@ impl State {
             cursor_position: (63, 63),
         }
     }
+
+    pub fn position(&self) -> (i32, i32) {
+        self.cursor_position
+    }
 }
 
 impl Default for State {
@@ -31,9 +103,16 @@ impl<'a, Msg: Copy> Cursor<'a, Msg> {
     pub fn new(state: &'a mut State) -> Self {
         Self {
             state,
+            kind: CursorKind::Arrow,
             pd: PhantomData,
         }
     }
+
+    pub fn kind(mut self, kind: CursorKind) -> Self {
+        self.kind = kind;
+
+        self
+    }
 }
 
 impl<'a, Msg: Copy + Debug> Widget for Cursor<'a, Msg> {
@@ -49,11 +128,147 @@ impl<'a, Msg: Copy + Debug> Widget for Cursor<'a, Msg> {
     }
 
     fn draw(&mut self, draw: &mut Pico8) {
-        draw.palt(Some(0));
-        draw.raw_spr(
-            Sprite::new(editor::MOUSE_SPRITE),
-            self.state.cursor_position.0 - 3,
-            self.state.cursor_position.1 - 1,
-        );
+        draw_cursor(draw, self.kind, self.state.cursor_position);
+    }
+}
+
+pub(crate) fn draw_cursor(draw: &mut Pico8, kind: CursorKind, position: (i32, i32)) {
+    if draw.cursor_mode() != CursorMode::Sprite {
+        return;
+    }
+    let (x, y) = kind.sprite_position(position);
+
+    draw.palt(Some(Color::BLACK));
+    match kind {
+        CursorKind::Sprite { sprite, .. } => draw.spr(sprite, x, y),
+        CursorKind::Hidden => {}
+        builtin => {
+            if let Some(sprite) = builtin.builtin_sprite() {
+                draw.raw_spr(sprite, x, y);
+            }
+        }
+    }
+}
+
+static MOUSE_SPRITE: Sprite = Sprite::from_pixels([
+    0, 0, 0, 0, 0, 0, 0, 0, //
+    0, 0, 0, 1, 0, 0, 0, 0, //
+    0, 0, 1, 7, 1, 0, 0, 0, //
+    0, 0, 1, 7, 7, 1, 0, 0, //
+    0, 0, 1, 7, 7, 7, 1, 0, //
+    0, 0, 1, 7, 7, 7, 7, 1, //
+    0, 0, 1, 7, 7, 1, 1, 0, //
+    0, 0, 0, 1, 1, 7, 1, 0, //
+]);
+
+static MOUSE_TARGET_SPRITE: Sprite = Sprite::from_pixels([
+    0, 0, 0, 1, 0, 0, 0, 0, //
+    0, 0, 1, 7, 1, 0, 0, 0, //
+    0, 1, 0, 0, 0, 1, 0, 0, //
+    1, 7, 0, 0, 0, 7, 1, 0, //
+    0, 1, 0, 0, 0, 1, 0, 0, //
+    0, 0, 1, 7, 1, 0, 0, 0, //
+    0, 0, 0, 1, 0, 0, 0, 0, //
+    0, 0, 0, 0, 0, 0, 0, 0, //
+]);
+
+static PENCIL_SPRITE: Sprite = Sprite::from_pixels([
+    0, 0, 0, 0, 0, 1, 1, 0, //
+    0, 0, 0, 0, 1, 8, 8, 1, //
+    0, 0, 0, 1, 7, 1, 8, 1, //
+    0, 0, 1, 7, 7, 7, 1, 0, //
+    0, 1, 7, 7, 7, 1, 0, 0, //
+    1, 15, 7, 7, 1, 0, 0, 0, //
+    1, 5, 15, 1, 0, 0, 0, 0, //
+    1, 1, 1, 0, 0, 0, 0, 0, //
+]);
+
+static BUCKET_SPRITE: Sprite = Sprite::from_pixels([
+    0, 0, 0, 1, 1, 0, 0, 0, //
+    0, 0, 1, 7, 7, 1, 0, 0, //
+    0, 1, 7, 1, 1, 7, 1, 0, //
+    1, 7, 7, 7, 7, 7, 7, 1, //
+    1, 12, 7, 7, 7, 7, 1, 0, //
+    1, 12, 1, 7, 7, 1, 0, 0, //
+    1, 12, 1, 1, 1, 0, 0, 0, //
+    0, 1, 0, 0, 0, 0, 0, 0, //
+]);
+
+static HAND_SPRITE: Sprite = Sprite::from_pixels([
+    0, 0, 0, 1, 1, 0, 0, 0, //
+    0, 0, 1, 7, 7, 1, 1, 0, //
+    0, 1, 1, 7, 7, 1, 7, 1, //
+    1, 7, 1, 7, 7, 7, 7, 1, //
+    1, 7, 7, 7, 7, 7, 7, 1, //
+    0, 1, 7, 7, 7, 7, 1, 0, //
+    0, 0, 1, 7, 7, 7, 1, 0, //
+    0, 0, 0, 1, 1, 1, 0, 0, //
+]);
+
+static RESIZE_SPRITE: Sprite = Sprite::from_pixels([
+    0, 0, 0, 0, 0, 0, 0, 0, //
+    0, 0, 1, 0, 0, 1, 0, 0, //
+    0, 1, 7, 1, 1, 7, 1, 0, //
+    1, 7, 7, 7, 7, 7, 7, 1, //
+    0, 1, 7, 1, 1, 7, 1, 0, //
+    0, 0, 1, 0, 0, 1, 0, 0, //
+    0, 0, 0, 0, 0, 0, 0, 0, //
+    0, 0, 0, 0, 0, 0, 0, 0, //
+]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hotspot_points_at_the_mouse() {
+        assert_eq!(CursorKind::Arrow.sprite_position((10, 10)), (7, 9));
+        assert_eq!(CursorKind::Crosshair.sprite_position((10, 10)), (7, 7));
+
+        let custom = CursorKind::Sprite {
+            sprite: 3,
+            hotspot: (2, 5),
+        };
+        assert_eq!(custom.sprite_position((10, 10)), (8, 5));
+    }
+
+    #[test]
+    fn only_sprite_cursors_are_drawn() {
+        use crate::runtime::{draw_data::DrawData, state::State};
+        use crate::Resources;
+
+        let mut pico8 = Pico8::new(DrawData::new(), State::new(), Resources::empty());
+        for mode in [CursorMode::Native, CursorMode::Hidden] {
+            pico8.set_cursor_mode(mode);
+            draw_cursor(&mut pico8, CursorKind::Arrow, (10, 10));
+            assert_eq!(pico8.draw_data.pixel(10, 10), Some(Color::BLACK));
+        }
+
+        pico8.set_cursor_mode(CursorMode::Sprite);
+        draw_cursor(&mut pico8, CursorKind::Arrow, (10, 10));
+        assert_eq!(pico8.draw_data.pixel(10, 10), Some(Color::DARK_BLUE));
+    }
+
+    #[test]
+    fn hotspots_are_opaque_pixels() {
+        let kinds = [
+            CursorKind::Arrow,
+            CursorKind::Pencil,
+            CursorKind::Bucket,
+            CursorKind::Hand,
+            CursorKind::Resize,
+        ];
+
+        for kind in kinds {
+            let (x, y) = kind.hotspot();
+            let sprite = kind.builtin_sprite().unwrap();
+
+            assert_ne!(
+                sprite.pget(x as isize, y as isize),
+                Color::BLACK,
+                "{:?}",
+                kind
+            );
+        }
     }
 }
@@ -0,0 +1,442 @@
+use crate::font::{self, GLYPH_HEIGHT};
+use crate::{Event, Key, KeyState, KeyboardEvent, MouseButton, MouseEvent, Pico8};
+
+use super::focus::Focusable;
+use super::{draw_layers, draw_popup, DispatchEvent, Element, Layer, Widget};
+use std::fmt::Debug;
+
+const WIDTH: i32 = 100;
+const PADDING: i32 = 4;
+const BUTTON_HEIGHT: i32 = 9;
+const BUTTON_SPACING: i32 = 4;
+
+/// A modal dialog with a message and a row of buttons, shown on top of `content`.
+///
+/// While the dialog is open, `content` doesn't receive any input (only ticks),
+/// so nothing behind the dialog can be clicked.
+/// Enter activates the default button, Escape the cancel one.
+/// Pressing any button closes the dialog.
+pub struct Dialog<'a, Msg> {
+    content: Element<'a, Msg>,
+    message: &'a str,
+    buttons: Vec<(&'a str, Msg)>,
+    default_button: usize,
+    cancel_button: Option<usize>,
+    state: &'a mut State,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct State {
+    open: bool,
+    hovered: Option<usize>,
+    pressed: Option<usize>,
+}
+
+impl State {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self) {
+        *self = Self {
+            open: true,
+            ..Self::new()
+        };
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+}
+
+// Modal dialogs hold the keyboard focus for as long as they're open.
+impl Focusable for State {
+    fn is_focused(&self) -> bool {
+        self.open
+    }
+
+    fn set_focused(&mut self, _: bool) {}
+}
+
+impl<'a, Msg: Copy + Debug + 'a> Dialog<'a, Msg> {
+    pub fn new(
+        content: impl Into<Element<'a, Msg>>,
+        message: &'a str,
+        state: &'a mut State,
+    ) -> Self {
+        Self {
+            content: content.into(),
+            message,
+            buttons: vec![],
+            default_button: 0,
+            cancel_button: None,
+            state,
+        }
+    }
+
+    pub fn button(mut self, label: &'a str, msg: Msg) -> Self {
+        self.buttons.push((label, msg));
+
+        self
+    }
+
+    /// Button activated by Enter. Defaults to the first button.
+    pub fn default_button(mut self, label: &'a str, msg: Msg) -> Self {
+        self.default_button = self.buttons.len();
+
+        self.button(label, msg)
+    }
+
+    /// Button activated by Escape. Without one, Escape just closes the dialog.
+    pub fn cancel_button(mut self, label: &'a str, msg: Msg) -> Self {
+        self.cancel_button = Some(self.buttons.len());
+
+        self.button(label, msg)
+    }
+
+    fn layout(&self) -> Layout {
+        let lines = font::wrap(self.message, WIDTH - 2 * PADDING);
+        let labels: Vec<_> = self.buttons.iter().map(|(label, _)| *label).collect();
+
+        Layout::new(lines, &labels)
+    }
+
+    fn activate(&mut self, index: usize, dispatch_event: &mut DispatchEvent<Msg>) {
+        if let Some(&(_, msg)) = self.buttons.get(index) {
+            dispatch_event.call(msg);
+        }
+
+        self.state.close();
+    }
+}
+
+impl<'a, Msg: Copy + Debug + 'a> Widget for Dialog<'a, Msg> {
+    type Msg = Msg;
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        cursor_position: (i32, i32),
+        dispatch_event: &mut DispatchEvent<Self::Msg>,
+    ) {
+        if !self.state.open {
+            self.content
+                .as_widget_mut()
+                .on_event(event, cursor_position, dispatch_event);
+
+            return;
+        }
+
+        let button_at_cursor = self.layout().button_at(cursor_position);
+
+        match event {
+            // Keep animations and such running behind the dialog.
+            Event::Tick { .. } => {
+                self.content
+                    .as_widget_mut()
+                    .on_event(event, cursor_position, dispatch_event);
+            }
+            Event::Mouse(MouseEvent::Move { .. }) => {
+                self.state.hovered = button_at_cursor;
+            }
+            Event::Mouse(MouseEvent::Down(MouseButton::Left)) => {
+                self.state.pressed = button_at_cursor;
+            }
+            Event::Mouse(MouseEvent::Up(MouseButton::Left)) => {
+                let pressed = self.state.pressed.take();
+
+                if let Some(index) = pressed.filter(|&index| Some(index) == button_at_cursor) {
+                    self.activate(index, dispatch_event);
+                }
+            }
+            Event::Keyboard(KeyboardEvent {
+                key,
+                state: KeyState::Down,
+            }) => match key {
+                Key::Enter => self.activate(self.default_button, dispatch_event),
+                Key::Escape => match self.cancel_button {
+                    Some(cancel_button) => self.activate(cancel_button, dispatch_event),
+                    None => self.state.close(),
+                },
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn draw(&mut self, draw: &mut Pico8) {
+        draw_layers(self, draw);
+    }
+
+    // Above everything the content draws, but still below the cursor.
+    fn draw_layer(&mut self, draw: &mut Pico8, layer: Layer) {
+        self.content.as_widget_mut().draw_layer(draw, layer);
+
+        if layer != Layer::Overlay || !self.state.open {
+            return;
+        }
+
+        let layout = self.layout();
+        let default_button = self.default_button;
+        let (hovered, pressed) = (self.state.hovered, self.state.pressed);
+
+        draw_popup(draw, |draw| {
+            let Layout { x, y, height, .. } = layout;
+
+            draw.rectfill(x, y, x + WIDTH - 1, y + height - 1, 1);
+            draw.rect(x, y, x + WIDTH - 1, y + height - 1, 7);
+
+            for (line_index, line) in layout.lines.iter().enumerate() {
+                let line_y = y + PADDING + line_index as i32 * GLYPH_HEIGHT;
+                draw.print(line, x + PADDING, line_y, 7);
+            }
+
+            for (index, (label, _)) in self.buttons.iter().enumerate() {
+                let (x0, y0, x1, y1) = layout.buttons[index];
+                let background = match (pressed == Some(index), hovered == Some(index)) {
+                    (true, _) => 5,
+                    (false, true) => 13,
+                    (false, false) => 6,
+                };
+
+                draw.rectfill(x0, y0, x1, y1, background);
+                if index == default_button {
+                    draw.rect(x0 - 1, y0 - 1, x1 + 1, y1 + 1, 10);
+                }
+                draw.print(label, x0 + 3, y0 + 2, 0);
+            }
+        });
+    }
+
+    fn visit_focusable(&mut self, f: &mut dyn FnMut(&mut dyn Focusable)) {
+        if self.state.open {
+            f(self.state)
+        } else {
+            self.content.as_widget_mut().visit_focusable(f)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Layout {
+    x: i32,
+    y: i32,
+    height: i32,
+    lines: Vec<String>,
+    // (x0, y0, x1, y1) of every button, inclusive.
+    buttons: Vec<(i32, i32, i32, i32)>,
+}
+
+impl Layout {
+    fn new(lines: Vec<String>, labels: &[&str]) -> Self {
+        let text_height = lines.len() as i32 * GLYPH_HEIGHT;
+        let height = PADDING + text_height + PADDING + BUTTON_HEIGHT + PADDING;
+        let x = (128 - WIDTH) / 2;
+        let y = (128 - height) / 2;
+
+        let widths: Vec<i32> = labels
+            .iter()
+            .map(|label| font::measure_text(label).0 + 5)
+            .collect();
+        let row_width =
+            widths.iter().sum::<i32>() + BUTTON_SPACING * (widths.len() as i32 - 1).max(0);
+
+        let buttons_y = y + PADDING + text_height + PADDING;
+        let mut button_x = x + (WIDTH - row_width) / 2;
+        let buttons = widths
+            .into_iter()
+            .map(|width| {
+                let button = (
+                    button_x,
+                    buttons_y,
+                    button_x + width - 1,
+                    buttons_y + BUTTON_HEIGHT - 1,
+                );
+                button_x += width + BUTTON_SPACING;
+
+                button
+            })
+            .collect();
+
+        Self {
+            x,
+            y,
+            height,
+            lines,
+            buttons,
+        }
+    }
+
+    fn button_at(&self, (x, y): (i32, i32)) -> Option<usize> {
+        self.buttons
+            .iter()
+            .position(|&(x0, y0, x1, y1)| x >= x0 && x <= x1 && y >= y0 && y <= y1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::button::{self, Button};
+    use crate::ui::drag::Drag;
+    use crate::ui::hover::HoverTracker;
+    use crate::ui::testing::{key_down, Harness};
+    use crate::ui::{DrawFn, Tree};
+    use crate::Color;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum Msg {
+        Behind,
+        Ok,
+        Cancel,
+    }
+
+    fn send(
+        dialog_state: &mut State,
+        button_state: &mut button::State,
+        event: Event,
+        cursor: (i32, i32),
+    ) -> Vec<Msg> {
+        let mut queue = vec![];
+        let mut drag = Drag::new();
+
+        // A button covering the whole screen, behind the dialog.
+        let behind = Button::new(
+            0,
+            0,
+            128,
+            128,
+            Some(Msg::Behind),
+            button_state,
+            DrawFn::new(|_| {}),
+        );
+        let mut dialog = Dialog::new(behind, "ARE YOU SURE?", dialog_state)
+            .default_button("OK", Msg::Ok)
+            .cancel_button("CANCEL", Msg::Cancel);
+
+        dialog.on_event(
+            event,
+            cursor,
+            &mut DispatchEvent::new(&mut queue, &mut drag, &mut HoverTracker::new()),
+        );
+
+        queue
+    }
+
+    fn click(
+        dialog_state: &mut State,
+        button_state: &mut button::State,
+        cursor: (i32, i32),
+    ) -> Vec<Msg> {
+        let mut msgs = send(
+            dialog_state,
+            button_state,
+            Event::Mouse(MouseEvent::Down(MouseButton::Left)),
+            cursor,
+        );
+        msgs.extend(send(
+            dialog_state,
+            button_state,
+            Event::Mouse(MouseEvent::Up(MouseButton::Left)),
+            cursor,
+        ));
+
+        msgs
+    }
+
+    #[test]
+    fn blocks_input_while_open() {
+        let mut dialog_state = State::new();
+        let mut button_state = button::State::new();
+
+        assert_eq!(
+            click(&mut dialog_state, &mut button_state, (1, 1)),
+            [Msg::Behind]
+        );
+
+        dialog_state.open();
+        assert_eq!(click(&mut dialog_state, &mut button_state, (1, 1)), []);
+        assert!(dialog_state.is_open());
+    }
+
+    #[test]
+    fn clicking_a_button_sends_its_message_and_closes() {
+        let mut dialog_state = State::new();
+        let mut button_state = button::State::new();
+        dialog_state.open();
+
+        let layout = Layout::new(vec!["ARE YOU SURE?".to_owned()], &["OK", "CANCEL"]);
+        let (x0, y0, _, _) = layout.buttons[1];
+
+        assert_eq!(
+            click(&mut dialog_state, &mut button_state, (x0 + 1, y0 + 1)),
+            [Msg::Cancel]
+        );
+        assert!(!dialog_state.is_open());
+    }
+
+    #[test]
+    fn enter_and_escape_activate_default_and_cancel() {
+        let mut dialog_state = State::new();
+        let mut button_state = button::State::new();
+
+        dialog_state.open();
+        assert_eq!(
+            send(
+                &mut dialog_state,
+                &mut button_state,
+                key_down(Key::Enter),
+                (0, 0)
+            ),
+            [Msg::Ok]
+        );
+        assert!(!dialog_state.is_open());
+
+        dialog_state.open();
+        assert_eq!(
+            send(
+                &mut dialog_state,
+                &mut button_state,
+                key_down(Key::Escape),
+                (0, 0)
+            ),
+            [Msg::Cancel]
+        );
+        assert!(!dialog_state.is_open());
+    }
+
+    #[test]
+    fn the_cursor_is_drawn_over_the_dialog() {
+        let mut dialog_state = State::new();
+        dialog_state.open();
+        let layout = Layout::new(vec!["ARE YOU SURE?".to_owned()], &["OK", "CANCEL"]);
+        let (x, y) = (layout.x + 1, layout.y + 1);
+
+        let view = Tree::new()
+            .push(
+                Dialog::new(
+                    DrawFn::new(|draw| draw.cls(8)),
+                    "ARE YOU SURE?",
+                    &mut dialog_state,
+                )
+                .default_button("OK", Msg::Ok)
+                .cancel_button("CANCEL", Msg::Cancel),
+            )
+            .push_to(Layer::Cursor, DrawFn::new(move |draw| draw.pset(x, y, 11)));
+        let mut harness = Harness::new();
+        harness.step(
+            view,
+            Event::Tick {
+                delta_millis: 0.0,
+                frame: 0,
+            },
+        );
+
+        assert_eq!(harness.pixel(x + 1, y), Color::DARK_BLUE);
+        assert_eq!(harness.pixel(x, y), Color::from(11));
+    }
+}
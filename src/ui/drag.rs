@@ -0,0 +1,422 @@
+use crate::{Event, Key, KeyState, KeyboardEvent, MouseButton, MouseEvent, Pico8};
+
+use super::focus::Focusable;
+use super::{draw_layers, draw_popup, DispatchEvent, Element, Layer, Widget};
+use std::fmt::Debug;
+
+// How far (in pixels) the cursor has to move while pressed for a drag to start,
+// so that regular clicks on draggable elements still work.
+const DRAG_THRESHOLD: i32 = 2;
+
+/// The drag in progress (if any), shared by every widget through [`DispatchEvent`].
+#[derive(Debug, Default)]
+pub(crate) struct Drag {
+    payload: Option<usize>,
+}
+
+impl Drag {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn payload(&self) -> Option<usize> {
+        self.payload
+    }
+
+    pub(crate) fn start(&mut self, payload: usize) {
+        self.payload = Some(payload);
+    }
+
+    /// Escape cancels the current drag.
+    /// Returns whether the event was consumed.
+    pub(crate) fn before_event(&mut self, event: Event) -> bool {
+        match event {
+            Event::Keyboard(KeyboardEvent {
+                key: Key::Escape,
+                state: KeyState::Down,
+            }) if self.payload.is_some() => {
+                self.payload = None;
+
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The drag ends once every widget has seen the button being released,
+    /// so drop targets get a chance to handle it no matter where they are in the tree.
+    pub(crate) fn after_event(&mut self, event: Event) {
+        if let Event::Mouse(MouseEvent::Up(MouseButton::Left)) = event {
+            self.payload = None;
+        }
+    }
+}
+
+/// Makes an element draggable. While dragging, a ghost of it follows the cursor.
+///
+/// The dragged element keeps receiving events even if the cursor leaves it,
+/// until the mouse button is released.
+pub struct Draggable<'a, Msg> {
+    element: Element<'a, Msg>,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    payload: usize,
+    ghost_sprite: Option<usize>,
+    state: &'a mut State,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct State {
+    // Where the mouse button was pressed, relative to the element.
+    grab_offset: Option<(i32, i32)>,
+    pressed_at: (i32, i32),
+    dragging: bool,
+}
+
+impl State {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.dragging
+    }
+}
+
+impl<'a, Msg: Copy + Debug + 'a> Draggable<'a, Msg> {
+    pub fn new(
+        element: impl Into<Element<'a, Msg>>,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        payload: usize,
+        state: &'a mut State,
+    ) -> Self {
+        Self {
+            element: element.into(),
+            x,
+            y,
+            width,
+            height,
+            payload,
+            ghost_sprite: None,
+            state,
+        }
+    }
+
+    /// Sprite drawn under the cursor while dragging.
+    /// Defaults to an outline the size of the element.
+    pub fn ghost_sprite(mut self, sprite: usize) -> Self {
+        self.ghost_sprite = Some(sprite);
+
+        self
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        let contains_x = x >= self.x && x < self.x + self.width;
+        let contains_y = y >= self.y && y < self.y + self.height;
+
+        contains_x && contains_y
+    }
+}
+
+impl<'a, Msg: Copy + Debug + 'a> Widget for Draggable<'a, Msg> {
+    type Msg = Msg;
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        cursor_position: (i32, i32),
+        dispatch_event: &mut DispatchEvent<Self::Msg>,
+    ) {
+        let (x, y) = cursor_position;
+        let contained = self.contains(x, y);
+        let state = &mut *self.state;
+
+        // The drag was cancelled (or taken over by someone else).
+        if state.dragging && dispatch_event.dragged() != Some(self.payload) {
+            state.dragging = false;
+            state.grab_offset = None;
+        }
+
+        match event {
+            Event::Mouse(MouseEvent::Down(MouseButton::Left)) if contained => {
+                state.grab_offset = Some((x - self.x, y - self.y));
+                state.pressed_at = cursor_position;
+            }
+            Event::Mouse(MouseEvent::Move { .. }) => {
+                if let (Some(_), false) = (state.grab_offset, state.dragging) {
+                    let (pressed_x, pressed_y) = state.pressed_at;
+
+                    if (x - pressed_x).abs().max((y - pressed_y).abs()) >= DRAG_THRESHOLD {
+                        state.dragging = true;
+                        dispatch_event.start_drag(self.payload);
+                    }
+                }
+            }
+            Event::Mouse(MouseEvent::Up(MouseButton::Left)) => {
+                state.grab_offset = None;
+                state.dragging = false;
+            }
+            _ => {}
+        }
+
+        self.element
+            .as_widget_mut()
+            .on_event(event, cursor_position, dispatch_event);
+    }
+
+    fn draw(&mut self, draw: &mut Pico8) {
+        draw_layers(self, draw);
+    }
+
+    // The ghost follows the cursor above everything else.
+    fn draw_layer(&mut self, draw: &mut Pico8, layer: Layer) {
+        self.element.as_widget_mut().draw_layer(draw, layer);
+
+        if let (Layer::Overlay, true, Some((offset_x, offset_y))) =
+            (layer, self.state.dragging, self.state.grab_offset)
+        {
+            let (mouse_x, mouse_y) = draw.mouse();
+            let (x, y) = (mouse_x - offset_x, mouse_y - offset_y);
+            let (width, height) = (self.width, self.height);
+
+            draw_popup(draw, |draw| match self.ghost_sprite {
+                Some(sprite) => {
+                    draw.palt(None);
+                    draw.spr(sprite, x, y);
+                    draw.rect(x - 1, y - 1, x + width, y + height, 7);
+                }
+                None => draw.rect(x, y, x + width - 1, y + height - 1, 7),
+            });
+        }
+    }
+
+    fn visit_focusable(&mut self, f: &mut dyn FnMut(&mut dyn Focusable)) {
+        self.element.as_widget_mut().visit_focusable(f)
+    }
+}
+
+/// An area that accepts [`Draggable`] elements being dropped on it.
+// Like `DrawFn`, holds `on_drop` itself instead of a box of it.
+pub struct DropTarget<'a, Msg, F = Box<dyn Fn(usize) -> Msg + 'a>> {
+    element: Element<'a, Msg>,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    on_drop: F,
+}
+
+impl<'a, Msg: Copy + Debug + 'a> DropTarget<'a, Msg> {
+    /// `on_drop` receives the payload of the element that was dropped.
+    pub fn new<F: Fn(usize) -> Msg + 'a>(
+        element: impl Into<Element<'a, Msg>>,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        on_drop: F,
+    ) -> DropTarget<'a, Msg, F> {
+        DropTarget {
+            element: element.into(),
+            x,
+            y,
+            width,
+            height,
+            on_drop,
+        }
+    }
+}
+
+impl<'a, Msg, F> DropTarget<'a, Msg, F> {
+    fn contains(&self, x: i32, y: i32) -> bool {
+        let contains_x = x >= self.x && x < self.x + self.width;
+        let contains_y = y >= self.y && y < self.y + self.height;
+
+        contains_x && contains_y
+    }
+}
+
+impl<'a, Msg: Copy + Debug + 'a, F: Fn(usize) -> Msg> Widget for DropTarget<'a, Msg, F> {
+    type Msg = Msg;
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        cursor_position: (i32, i32),
+        dispatch_event: &mut DispatchEvent<Self::Msg>,
+    ) {
+        if let Event::Mouse(MouseEvent::Up(MouseButton::Left)) = event {
+            if let Some(payload) = dispatch_event.dragged() {
+                if self.contains(cursor_position.0, cursor_position.1) {
+                    dispatch_event.call((self.on_drop)(payload));
+                }
+            }
+        }
+
+        self.element
+            .as_widget_mut()
+            .on_event(event, cursor_position, dispatch_event);
+    }
+
+    fn draw(&mut self, draw: &mut Pico8) {
+        self.element.as_widget_mut().draw(draw);
+    }
+
+    fn draw_layer(&mut self, draw: &mut Pico8, layer: Layer) {
+        self.element.as_widget_mut().draw_layer(draw, layer);
+    }
+
+    fn visit_focusable(&mut self, f: &mut dyn FnMut(&mut dyn Focusable)) {
+        self.element.as_widget_mut().visit_focusable(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::hover::HoverTracker;
+    use crate::ui::{DrawFn, Tree};
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Dropped {
+        payload: usize,
+        target: usize,
+    }
+
+    // Two slots side by side, each one draggable and a drop target.
+    fn view(states: &mut [State]) -> Element<'_, Dropped> {
+        let children = states
+            .iter_mut()
+            .enumerate()
+            .map(|(index, state)| {
+                let x = index as i32 * 8;
+                let draggable = Draggable::new(DrawFn::new(|_| {}), x, 0, 8, 8, index, state);
+
+                DropTarget::new(draggable, x, 0, 8, 8, move |payload| Dropped {
+                    payload,
+                    target: index,
+                })
+                .into()
+            })
+            .collect::<Vec<_>>();
+
+        Tree::with_children(children).into()
+    }
+
+    fn run(drag: &mut Drag, states: &mut [State], events: &[(Event, (i32, i32))]) -> Vec<Dropped> {
+        let mut queue = vec![];
+
+        for &(event, cursor_position) in events {
+            if drag.before_event(event) {
+                continue;
+            }
+
+            view(states).as_widget_mut().on_event(
+                event,
+                cursor_position,
+                &mut DispatchEvent::new(&mut queue, drag, &mut HoverTracker::new()),
+            );
+            drag.after_event(event);
+        }
+
+        queue
+    }
+
+    fn mouse_move((x, y): (i32, i32)) -> (Event, (i32, i32)) {
+        (Event::Mouse(MouseEvent::Move { x, y }), (x, y))
+    }
+
+    fn mouse_down(position: (i32, i32)) -> (Event, (i32, i32)) {
+        (Event::Mouse(MouseEvent::Down(MouseButton::Left)), position)
+    }
+
+    fn mouse_up(position: (i32, i32)) -> (Event, (i32, i32)) {
+        (Event::Mouse(MouseEvent::Up(MouseButton::Left)), position)
+    }
+
+    #[test]
+    fn dropping_on_a_target_sends_the_payload() {
+        let mut drag = Drag::new();
+        let mut states = vec![State::new(); 2];
+
+        let dropped = run(
+            &mut drag,
+            &mut states,
+            &[
+                mouse_down((2, 2)),
+                mouse_move((6, 2)),
+                mouse_move((12, 2)),
+                mouse_up((12, 2)),
+            ],
+        );
+
+        assert_eq!(
+            dropped,
+            [Dropped {
+                payload: 0,
+                target: 1
+            }]
+        );
+        assert_eq!(drag.payload(), None);
+        assert!(!states[0].is_dragging());
+    }
+
+    #[test]
+    fn clicking_doesnt_drag() {
+        let mut drag = Drag::new();
+        let mut states = vec![State::new(); 2];
+
+        let dropped = run(
+            &mut drag,
+            &mut states,
+            &[mouse_down((2, 2)), mouse_move((3, 2)), mouse_up((3, 2))],
+        );
+
+        assert!(dropped.is_empty());
+    }
+
+    #[test]
+    fn releasing_elsewhere_cancels() {
+        let mut drag = Drag::new();
+        let mut states = vec![State::new(); 2];
+
+        let dropped = run(
+            &mut drag,
+            &mut states,
+            &[mouse_down((2, 2)), mouse_move((60, 60)), mouse_up((60, 60))],
+        );
+
+        assert!(dropped.is_empty());
+        assert_eq!(drag.payload(), None);
+    }
+
+    #[test]
+    fn escape_cancels() {
+        let mut drag = Drag::new();
+        let mut states = vec![State::new(); 2];
+        let escape = Event::Keyboard(KeyboardEvent {
+            key: Key::Escape,
+            state: KeyState::Down,
+        });
+
+        let dropped = run(
+            &mut drag,
+            &mut states,
+            &[
+                mouse_down((2, 2)),
+                mouse_move((12, 2)),
+                (escape, (12, 2)),
+                mouse_move((13, 2)),
+                mouse_up((13, 2)),
+            ],
+        );
+
+        assert!(dropped.is_empty());
+        assert!(!states[0].is_dragging());
+    }
+}
@@ -0,0 +1,539 @@
+use crate::font;
+use crate::{Event, Key, KeyState, KeyboardEvent, MouseButton, MouseEvent, Pico8};
+
+use super::{draw_layers, draw_popup, DispatchEvent, Layer, Widget};
+use std::fmt::Debug;
+
+const HEIGHT: i32 = 7;
+const ITEM_HEIGHT: i32 = 7;
+
+type OnSelect<'a, Msg> = Box<dyn Fn(usize) -> Msg + 'a>;
+
+/// A button showing the current choice, which opens a list of options when clicked.
+pub struct DropDown<'a, Msg> {
+    x: i32,
+    y: i32,
+    width: i32,
+    options: &'a [&'a str],
+    selected: usize,
+    on_select: OnSelect<'a, Msg>,
+    state: &'a mut State,
+}
+
+/// A list of options that opens at the cursor when right clicking inside an area.
+pub struct ContextMenu<'a, Msg> {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    options: &'a [&'a str],
+    on_select: OnSelect<'a, Msg>,
+    state: &'a mut State,
+}
+
+/// Shared by [`DropDown`] and [`ContextMenu`].
+#[derive(Debug, Clone, Default)]
+pub struct State {
+    open: bool,
+    hovered: Option<usize>,
+    // Where the context menu was opened, unused by dropdowns.
+    position: (i32, i32),
+}
+
+impl State {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+        self.hovered = None;
+    }
+}
+
+impl<'a, Msg> DropDown<'a, Msg> {
+    pub fn new(
+        x: i32,
+        y: i32,
+        width: i32,
+        options: &'a [&'a str],
+        selected: usize,
+        on_select: impl Fn(usize) -> Msg + 'a,
+        state: &'a mut State,
+    ) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            options,
+            selected,
+            on_select: Box::new(on_select),
+            state,
+        }
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        let contains_x = x >= self.x && x < self.x + self.width;
+        let contains_y = y >= self.y && y < self.y + HEIGHT;
+
+        contains_x && contains_y
+    }
+
+    fn list(&self) -> OptionList {
+        OptionList::new(
+            self.x,
+            self.y + HEIGHT,
+            self.y,
+            self.width,
+            self.options.len(),
+        )
+    }
+
+    fn draw_button(&self, draw: &mut Pico8) {
+        let (x, y) = (self.x, self.y);
+        let x1 = x + self.width - 1;
+
+        draw.rectfill(x, y, x1, y + HEIGHT - 1, 7);
+        if let Some(choice) = self.options.get(self.selected) {
+            draw.print(choice, x + 1, y + 1, 0);
+        }
+
+        // Arrow pointing down
+        draw.line(x1 - 5, y + 2, x1 - 1, y + 2, 0);
+        draw.line(x1 - 4, y + 3, x1 - 2, y + 3, 0);
+        draw.pset(x1 - 3, y + 4, 0);
+    }
+}
+
+impl<'a, Msg: Copy + Debug + 'a> Widget for DropDown<'a, Msg> {
+    type Msg = Msg;
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        cursor_position: (i32, i32),
+        dispatch_event: &mut DispatchEvent<Self::Msg>,
+    ) {
+        if self.state.open {
+            let list = self.list();
+
+            if let Some(index) = list.on_event(self.state, event, cursor_position) {
+                dispatch_event.call((self.on_select)(index));
+            }
+            // Clicks on the list (or closing it) shouldn't reach what's underneath.
+            if let Event::Mouse(_) = event {
+                dispatch_event.consume();
+            }
+        } else if matches!(event, Event::Mouse(MouseEvent::Down(MouseButton::Left)))
+            && self.contains(cursor_position.0, cursor_position.1)
+        {
+            self.state.open = true;
+            self.state.hovered = Some(self.selected);
+        }
+    }
+
+    // The open list gets input along with the overlays, before everything underneath.
+    fn on_event_layer(
+        &mut self,
+        event: Event,
+        cursor_position: (i32, i32),
+        dispatch_event: &mut DispatchEvent<Self::Msg>,
+        layer: Layer,
+    ) {
+        let list_layer = if self.state.open {
+            Layer::Overlay
+        } else {
+            Layer::Base
+        };
+
+        if layer == list_layer {
+            self.on_event(event, cursor_position, dispatch_event);
+        }
+    }
+
+    fn draw(&mut self, draw: &mut Pico8) {
+        draw_layers(self, draw);
+    }
+
+    // The open list goes above everything else.
+    fn draw_layer(&mut self, draw: &mut Pico8, layer: Layer) {
+        match layer {
+            Layer::Base => self.draw_button(draw),
+            Layer::Overlay if self.state.open => {
+                self.list().draw(draw, self.options, self.state.hovered);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'a, Msg> ContextMenu<'a, Msg> {
+    /// The menu opens when right clicking inside the given area.
+    pub fn new(
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        options: &'a [&'a str],
+        on_select: impl Fn(usize) -> Msg + 'a,
+        state: &'a mut State,
+    ) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+            options,
+            on_select: Box::new(on_select),
+            state,
+        }
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        let contains_x = x >= self.x && x < self.x + self.width;
+        let contains_y = y >= self.y && y < self.y + self.height;
+
+        contains_x && contains_y
+    }
+
+    fn list(&self) -> OptionList {
+        let (x, y) = self.state.position;
+        let width = self
+            .options
+            .iter()
+            .map(|option| font::measure_text(option).0)
+            .max();
+        let width = width.unwrap_or(0) + 3;
+
+        OptionList::new(x, y, y, width, self.options.len())
+    }
+}
+
+impl<'a, Msg: Copy + Debug + 'a> Widget for ContextMenu<'a, Msg> {
+    type Msg = Msg;
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        cursor_position: (i32, i32),
+        dispatch_event: &mut DispatchEvent<Self::Msg>,
+    ) {
+        if self.state.open {
+            let list = self.list();
+
+            if let Some(index) = list.on_event(self.state, event, cursor_position) {
+                dispatch_event.call((self.on_select)(index));
+            }
+            // Clicks on the list (or closing it) shouldn't reach what's underneath.
+            if let Event::Mouse(_) = event {
+                dispatch_event.consume();
+            }
+        } else if matches!(event, Event::Mouse(MouseEvent::Down(MouseButton::Right)))
+            && self.contains(cursor_position.0, cursor_position.1)
+        {
+            self.state.open = true;
+            self.state.hovered = None;
+            self.state.position = cursor_position;
+        }
+    }
+
+    // Same as `DropDown`.
+    fn on_event_layer(
+        &mut self,
+        event: Event,
+        cursor_position: (i32, i32),
+        dispatch_event: &mut DispatchEvent<Self::Msg>,
+        layer: Layer,
+    ) {
+        let list_layer = if self.state.open {
+            Layer::Overlay
+        } else {
+            Layer::Base
+        };
+
+        if layer == list_layer {
+            self.on_event(event, cursor_position, dispatch_event);
+        }
+    }
+
+    fn draw(&mut self, draw: &mut Pico8) {
+        draw_layers(self, draw);
+    }
+
+    fn draw_layer(&mut self, draw: &mut Pico8, layer: Layer) {
+        if layer == Layer::Overlay && self.state.open {
+            self.list().draw(draw, self.options, self.state.hovered);
+        }
+    }
+}
+
+/// Geometry and behaviour of an open list of options.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OptionList {
+    x: i32,
+    y: i32,
+    width: i32,
+    len: usize,
+}
+
+impl OptionList {
+    /// The list opens downwards from `below`, unless it doesn't fit on the screen,
+    /// in which case it opens upwards from `above`.
+    fn new(x: i32, below: i32, above: i32, width: i32, len: usize) -> Self {
+        let height = len as i32 * ITEM_HEIGHT;
+
+        let y = if below + height > 128 {
+            above - height
+        } else {
+            below
+        };
+        let x = x.min(128 - width);
+
+        Self {
+            x: x.max(0),
+            y: y.max(0),
+            width,
+            len,
+        }
+    }
+
+    fn index_at(&self, (x, y): (i32, i32)) -> Option<usize> {
+        let contains_x = x >= self.x && x < self.x + self.width;
+        let contains_y = y >= self.y && y < self.y + self.len as i32 * ITEM_HEIGHT;
+
+        (contains_x && contains_y).then(|| ((y - self.y) / ITEM_HEIGHT) as usize)
+    }
+
+    /// Returns the index of the option that got selected, if any.
+    fn on_event(&self, state: &mut State, event: Event, cursor: (i32, i32)) -> Option<usize> {
+        let selected = match event {
+            Event::Mouse(MouseEvent::Move { .. }) => {
+                if let Some(index) = self.index_at(cursor) {
+                    state.hovered = Some(index);
+                }
+
+                None
+            }
+            // Clicking anywhere closes the list, selecting the option under the cursor if any.
+            Event::Mouse(MouseEvent::Down(_)) => {
+                let selected = self.index_at(cursor);
+                state.close();
+
+                selected
+            }
+            Event::Keyboard(KeyboardEvent {
+                key,
+                state: KeyState::Down,
+            }) => match key {
+                Key::UpArrow => {
+                    state.hovered = Some(state.hovered.map_or(0, |h| h.saturating_sub(1)));
+
+                    None
+                }
+                Key::DownArrow => {
+                    let last = self.len.saturating_sub(1);
+                    state.hovered = Some(state.hovered.map_or(0, |h| (h + 1).min(last)));
+
+                    None
+                }
+                Key::Enter => {
+                    let selected = state.hovered;
+                    state.close();
+
+                    selected
+                }
+                Key::Escape => {
+                    state.close();
+
+                    None
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+
+        selected.filter(|&index| index < self.len)
+    }
+
+    /// Part of [`Layer::Overlay`], so the list is drawn above everything else.
+    fn draw(&self, draw: &mut Pico8, options: &[&str], hovered: Option<usize>) {
+        let list = *self;
+
+        draw_popup(draw, |draw| {
+            let x1 = list.x + list.width - 1;
+
+            for (index, option) in options.iter().enumerate() {
+                let y = list.y + index as i32 * ITEM_HEIGHT;
+                let (background, foreground) = if hovered == Some(index) {
+                    (12, 7)
+                } else {
+                    (7, 0)
+                };
+
+                draw.rectfill(list.x, y, x1, y + ITEM_HEIGHT - 1, background);
+                draw.print(option, list.x + 1, y + 1, foreground);
+            }
+
+            let y1 = list.y + list.len as i32 * ITEM_HEIGHT - 1;
+            draw.rect(list.x - 1, list.y - 1, x1 + 1, y1 + 1, 0);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::button::{self, Button};
+    use crate::ui::testing::{click, key_down, mouse_move, Harness};
+    use crate::ui::{DrawFn, Element, Tree};
+    use crate::Color;
+
+    #[test]
+    fn opens_upwards_near_the_bottom() {
+        let list = OptionList::new(0, 10, 3, 20, 3);
+        assert_eq!(list.y, 10);
+
+        let list = OptionList::new(0, 120, 113, 20, 3);
+        assert_eq!(list.y, 113 - 3 * ITEM_HEIGHT);
+    }
+
+    #[test]
+    fn stays_inside_the_screen_horizontally() {
+        let list = OptionList::new(120, 10, 3, 20, 3);
+
+        assert_eq!(list.x, 108);
+    }
+
+    #[test]
+    fn context_menus_are_as_wide_as_their_widest_option() {
+        let mut state = State::new();
+        state.position = (120, 10);
+        let options = ["...", "AB"];
+        let menu = ContextMenu::new(0, 0, 128, 128, &options, |index| index, &mut state);
+
+        let list = menu.list();
+        // The periods are narrower than the two letters.
+        assert_eq!(list.width, font::measure_text("AB").0 + 3);
+        assert_eq!(list.x, 128 - list.width);
+    }
+
+    #[test]
+    fn clicking_an_option_selects_it() {
+        let list = OptionList::new(0, 10, 3, 20, 3);
+        let mut state = State {
+            open: true,
+            ..State::new()
+        };
+
+        let click = Event::Mouse(MouseEvent::Down(MouseButton::Left));
+        let selected = list.on_event(&mut state, click, (5, 10 + ITEM_HEIGHT + 2));
+
+        assert_eq!(selected, Some(1));
+        assert!(!state.is_open());
+    }
+
+    #[test]
+    fn clicking_elsewhere_closes_without_selecting() {
+        let list = OptionList::new(0, 10, 3, 20, 3);
+        let mut state = State {
+            open: true,
+            ..State::new()
+        };
+
+        let click = Event::Mouse(MouseEvent::Down(MouseButton::Left));
+
+        assert_eq!(list.on_event(&mut state, click, (100, 100)), None);
+        assert!(!state.is_open());
+    }
+
+    #[test]
+    fn keyboard_navigation_works() {
+        let list = OptionList::new(0, 10, 3, 20, 3);
+        let mut state = State {
+            open: true,
+            ..State::new()
+        };
+
+        for _ in 0..5 {
+            list.on_event(&mut state, key_down(Key::DownArrow), (0, 0));
+        }
+        list.on_event(&mut state, key_down(Key::UpArrow), (0, 0));
+
+        assert_eq!(
+            list.on_event(&mut state, key_down(Key::Enter), (0, 0)),
+            Some(1)
+        );
+        assert!(!state.is_open());
+    }
+
+    #[test]
+    fn escape_closes() {
+        let list = OptionList::new(0, 10, 3, 20, 3);
+        let mut state = State {
+            open: true,
+            ..State::new()
+        };
+
+        assert_eq!(
+            list.on_event(&mut state, key_down(Key::Escape), (0, 0)),
+            None
+        );
+        assert!(!state.is_open());
+    }
+
+    #[test]
+    fn clicking_an_option_doesnt_reach_the_elements_underneath() {
+        const BEHIND: usize = 99;
+        const OPTIONS: &[&str] = &["ONE", "TWO"];
+
+        // The dropdown comes after the button, but the open list still gets clicks first.
+        fn view((button, dropdown): &mut (button::State, State)) -> Element<'_, usize> {
+            Tree::new()
+                .push(
+                    Button::new(0, 0, 128, 128, Some(BEHIND), button, DrawFn::new(|_| {}))
+                        .event_on_press(),
+                )
+                .push(DropDown::new(0, 0, 20, OPTIONS, 0, |i| i, dropdown))
+                .into()
+        }
+
+        let mut states = (button::State::new(), State::new());
+        let mut harness = Harness::new();
+
+        // Opening it is a click on the button too, but picking an option isn't.
+        let opening = harness.run(&mut states, view, click(5, 2));
+        let picking = harness.run(&mut states, view, click(5, HEIGHT + ITEM_HEIGHT + 2));
+
+        assert_eq!(opening, vec![BEHIND]);
+        assert_eq!(picking, vec![1]);
+    }
+
+    #[test]
+    fn the_open_list_is_drawn_above_later_elements_but_below_the_cursor() {
+        let options = ["ONE", "TWO"];
+        let mut state = State {
+            open: true,
+            ..State::new()
+        };
+        let mut harness = Harness::new();
+
+        let view = Tree::new()
+            .push(DropDown::new(0, 0, 20, &options, 0, |i| i, &mut state))
+            .push(DrawFn::new(|draw| draw.rectfill(0, 0, 127, 127, 8)))
+            .push_to(
+                Layer::Cursor,
+                DrawFn::new(|draw| draw.pset(19, HEIGHT + ITEM_HEIGHT, 11)),
+            );
+        harness.step(view, mouse_move(100, 100));
+
+        // The second option, not hovered.
+        assert_eq!(harness.pixel(18, HEIGHT + ITEM_HEIGHT), Color::WHITE);
+        assert_eq!(harness.pixel(19, HEIGHT + ITEM_HEIGHT), Color::from(11));
+        // Covered by the later element.
+        assert_eq!(harness.pixel(10, 3), Color::from(8));
+    }
+}
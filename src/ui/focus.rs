@@ -0,0 +1,242 @@
+use crate::{Key, KeyState, KeyboardEvent};
+
+use super::Widget;
+use std::fmt::Debug;
+
+/// Widgets that can receive keyboard focus, cycled through with Tab / Shift+Tab.
+///
+/// Containers expose their focusable children through [`Widget::visit_focusable`].
+pub trait Focusable {
+    fn is_focused(&self) -> bool;
+
+    fn set_focused(&mut self, focused: bool);
+}
+
+/// The view is rebuilt every frame, so focusable widgets are identified
+/// by their position (in tree order) among all the focusable widgets of the view.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct FocusId(usize);
+
+/// Keeps track of which widget has keyboard focus across frames.
+#[derive(Debug, Default)]
+pub(crate) struct Focus {
+    focused: Option<FocusId>,
+    // Number of focusable widgets in the view, as of the last `apply`.
+    count: usize,
+    shift: bool,
+}
+
+impl Focus {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn has_focus(&self) -> bool {
+        self.focused.is_some()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.focused = None;
+    }
+
+    /// Tells every focusable widget in the view whether it's the focused one.
+    pub(crate) fn apply<Msg: Copy + Debug>(&mut self, widget: &mut dyn Widget<Msg = Msg>) {
+        let mut index = 0;
+
+        widget.visit_focusable(&mut |focusable| {
+            focusable.set_focused(self.focused == Some(FocusId(index)));
+            index += 1;
+        });
+
+        self.count = index;
+        if self
+            .focused
+            .is_some_and(|FocusId(focused)| focused >= index)
+        {
+            self.focused = None;
+        }
+    }
+
+    /// Picks up focus changes made by the widgets themselves,
+    /// like a text input focusing itself when clicked.
+    pub(crate) fn sync<Msg: Copy + Debug>(&mut self, widget: &mut dyn Widget<Msg = Msg>) {
+        let mut index = 0;
+        let mut newly_focused = None;
+        let mut still_focused = false;
+
+        widget.visit_focusable(&mut |focusable| {
+            if focusable.is_focused() {
+                if self.focused == Some(FocusId(index)) {
+                    still_focused = true;
+                } else if newly_focused.is_none() {
+                    newly_focused = Some(FocusId(index));
+                }
+            }
+            index += 1;
+        });
+
+        self.focused = newly_focused.or(if still_focused { self.focused } else { None });
+    }
+
+    /// Handles Tab, Shift+Tab and Escape (which drops the focus).
+    /// Returns whether the event was consumed.
+    pub(crate) fn on_key(&mut self, event: KeyboardEvent) -> bool {
+        match (event.key, event.state) {
+            (Key::Shift, state) => {
+                self.shift = state == KeyState::Down;
+
+                false
+            }
+            (Key::Tab, KeyState::Down) if self.count > 0 => {
+                let last = self.count - 1;
+
+                let next = match (self.focused, self.shift) {
+                    (None, false) => 0,
+                    (None, true) => last,
+                    (Some(FocusId(focused)), false) if focused >= last => 0,
+                    (Some(FocusId(focused)), false) => focused + 1,
+                    (Some(FocusId(0)), true) => last,
+                    (Some(FocusId(focused)), true) => focused - 1,
+                };
+                self.focused = Some(FocusId(next));
+
+                true
+            }
+            (Key::Escape, KeyState::Down) if self.has_focus() => {
+                self.clear();
+
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::button::{self, Button};
+    use crate::ui::testing::{key_down, keyboard_down};
+    use crate::ui::text_input::{self, TextInput};
+    use crate::ui::{DrawFn, Element, Tree};
+
+    #[derive(Clone, Copy, Debug)]
+    struct Msg;
+
+    fn view(states: &mut [button::State]) -> Element<'_, Msg> {
+        let children = states
+            .iter_mut()
+            .enumerate()
+            .map(|(index, state)| {
+                Button::new(index as i32 * 8, 0, 8, 8, None, state, DrawFn::new(|_| {}))
+                    .focusable()
+                    .into()
+            })
+            .collect::<Vec<_>>();
+
+        Tree::with_children(children).into()
+    }
+
+    fn focused(states: &[button::State]) -> Vec<bool> {
+        states.iter().map(|state| state.is_focused()).collect()
+    }
+
+    #[test]
+    fn tab_cycles_through_focusable_widgets() {
+        let mut focus = Focus::new();
+        let mut states = vec![button::State::new(); 3];
+
+        focus.apply(view(&mut states).as_widget_mut());
+        assert_eq!(focused(&states), [false, false, false]);
+
+        for expected in [
+            [true, false, false],
+            [false, true, false],
+            [false, false, true],
+        ] {
+            assert!(focus.on_key(keyboard_down(Key::Tab)));
+            focus.apply(view(&mut states).as_widget_mut());
+            assert_eq!(focused(&states), expected);
+        }
+
+        // Wraps around
+        focus.on_key(keyboard_down(Key::Tab));
+        focus.apply(view(&mut states).as_widget_mut());
+        assert_eq!(focused(&states), [true, false, false]);
+    }
+
+    #[test]
+    fn shift_tab_goes_backwards() {
+        let mut focus = Focus::new();
+        let mut states = vec![button::State::new(); 3];
+        focus.apply(view(&mut states).as_widget_mut());
+
+        focus.on_key(keyboard_down(Key::Shift));
+        focus.on_key(keyboard_down(Key::Tab));
+        focus.apply(view(&mut states).as_widget_mut());
+        assert_eq!(focused(&states), [false, false, true]);
+
+        focus.on_key(keyboard_down(Key::Tab));
+        focus.apply(view(&mut states).as_widget_mut());
+        assert_eq!(focused(&states), [false, true, false]);
+    }
+
+    #[test]
+    fn escape_drops_focus() {
+        let mut focus = Focus::new();
+        let mut states = vec![button::State::new(); 2];
+        focus.apply(view(&mut states).as_widget_mut());
+
+        focus.on_key(keyboard_down(Key::Tab));
+        assert!(focus.on_key(keyboard_down(Key::Escape)));
+        focus.apply(view(&mut states).as_widget_mut());
+
+        assert_eq!(focused(&states), [false, false]);
+        assert!(!focus.on_key(keyboard_down(Key::Escape)));
+    }
+
+    #[test]
+    fn widgets_can_take_focus() {
+        fn view(states: &mut [text_input::State]) -> Element<'_, Msg> {
+            let children = states
+                .iter_mut()
+                .enumerate()
+                .map(|(index, state)| TextInput::new(0, index as i32 * 8, 40, state).into())
+                .collect::<Vec<_>>();
+
+            Tree::with_children(children).into()
+        }
+
+        let mut focus = Focus::new();
+        let mut states = vec![text_input::State::new(); 3];
+        states[0].focus();
+        focus.sync(view(&mut states).as_widget_mut());
+
+        // Clicking on another input focuses it, the old one loses focus.
+        states[1].focus();
+        focus.sync(view(&mut states).as_widget_mut());
+        focus.apply(view(&mut states).as_widget_mut());
+        let focused: Vec<_> = states.iter().map(|state| state.is_focused()).collect();
+        assert_eq!(focused, [false, true, false]);
+
+        states[1].blur();
+        focus.sync(view(&mut states).as_widget_mut());
+        assert!(!focus.has_focus());
+    }
+
+    #[test]
+    fn keys_move_the_focus_through_the_ui() {
+        use crate::ui::testing::Harness;
+
+        let mut harness = Harness::new();
+        let mut states = vec![button::State::new(); 2];
+        let mut press = |states: &mut Vec<button::State>, key| {
+            harness.run(states, |states| view(states), [key_down(key)]);
+            focused(states)
+        };
+
+        assert_eq!(press(&mut states, Key::Tab), [true, false]);
+        assert_eq!(press(&mut states, Key::Tab), [false, true]);
+        assert_eq!(press(&mut states, Key::Escape), [false, false]);
+    }
+}
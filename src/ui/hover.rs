@@ -0,0 +1,214 @@
+use crate::{Event, Pico8};
+
+use super::focus::Focusable;
+use super::{DispatchEvent, Element, Layer, Widget};
+use std::collections::HashSet;
+use std::fmt::Debug;
+
+/// Which [`Hover`] areas had the cursor inside them, frame to frame.
+///
+/// Areas are identified by their position in tree order (among other hover areas),
+/// so there's no state for the app to keep around.
+#[derive(Debug, Default)]
+pub(crate) struct HoverTracker {
+    previous: HashSet<usize>,
+    current: HashSet<usize>,
+    next_id: usize,
+}
+
+impl HoverTracker {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    // Registers the next hover area (in tree order) for this frame,
+    // returning whether the cursor was inside it on the previous frame.
+    fn register(&mut self, inside: bool) -> bool {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        if inside {
+            self.current.insert(id);
+        }
+
+        self.previous.contains(&id)
+    }
+
+    /// Hit testing happens once per frame, on ticks.
+    pub(crate) fn after_event(&mut self, event: Event) {
+        if let Event::Tick { .. } = event {
+            self.previous = std::mem::take(&mut self.current);
+            self.next_id = 0;
+        }
+    }
+}
+
+/// Sends messages when the cursor enters, leaves, or stays inside an area.
+///
+/// Checked once per frame, so moving in and out of the area within
+/// a single frame doesn't send anything.
+pub struct Hover<'a, Msg> {
+    element: Element<'a, Msg>,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    on_enter: Option<Msg>,
+    on_leave: Option<Msg>,
+    on_hover: Option<Msg>,
+}
+
+impl<'a, Msg: Copy + Debug + 'a> Hover<'a, Msg> {
+    pub fn new(
+        element: impl Into<Element<'a, Msg>>,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    ) -> Self {
+        Self {
+            element: element.into(),
+            x,
+            y,
+            width,
+            height,
+            on_enter: None,
+            on_leave: None,
+            on_hover: None,
+        }
+    }
+
+    pub fn on_enter(mut self, msg: Msg) -> Self {
+        self.on_enter = Some(msg);
+
+        self
+    }
+
+    pub fn on_leave(mut self, msg: Msg) -> Self {
+        self.on_leave = Some(msg);
+
+        self
+    }
+
+    /// Sent every frame the cursor is inside the area (including the one it entered on).
+    pub fn on_hover(mut self, msg: Msg) -> Self {
+        self.on_hover = Some(msg);
+
+        self
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        let contains_x = x >= self.x && x < self.x + self.width;
+        let contains_y = y >= self.y && y < self.y + self.height;
+
+        contains_x && contains_y
+    }
+}
+
+impl<'a, Msg: Copy + Debug + 'a> Widget for Hover<'a, Msg> {
+    type Msg = Msg;
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        cursor_position: (i32, i32),
+        dispatch_event: &mut DispatchEvent<Self::Msg>,
+    ) {
+        if let Event::Tick { .. } = event {
+            let inside = self.contains(cursor_position.0, cursor_position.1);
+            let was_inside = dispatch_event.hover.register(inside);
+
+            let msgs = match (was_inside, inside) {
+                (false, true) => [self.on_enter, self.on_hover],
+                (true, true) => [None, self.on_hover],
+                (true, false) => [self.on_leave, None],
+                (false, false) => [None, None],
+            };
+
+            for msg in msgs.into_iter().flatten() {
+                dispatch_event.call(msg);
+            }
+        }
+
+        self.element
+            .as_widget_mut()
+            .on_event(event, cursor_position, dispatch_event);
+    }
+
+    fn draw(&mut self, draw: &mut Pico8) {
+        self.element.as_widget_mut().draw(draw);
+    }
+
+    fn draw_layer(&mut self, draw: &mut Pico8, layer: Layer) {
+        self.element.as_widget_mut().draw_layer(draw, layer);
+    }
+
+    fn visit_focusable(&mut self, f: &mut dyn FnMut(&mut dyn Focusable)) {
+        self.element.as_widget_mut().visit_focusable(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::drag::Drag;
+    use crate::ui::{DrawFn, Tree};
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum Msg {
+        Enter(usize),
+        Leave(usize),
+        Hover(usize),
+    }
+
+    // Two areas side by side, returns the messages sent on each frame.
+    fn run(cursor_positions: &[(i32, i32)]) -> Vec<Vec<Msg>> {
+        let mut tracker = HoverTracker::new();
+        let mut drag = Drag::new();
+        let tick = Event::Tick {
+            delta_millis: 0.0,
+            frame: 0,
+        };
+
+        cursor_positions
+            .iter()
+            .map(|&cursor_position| {
+                let mut queue = vec![];
+                let children = (0..2)
+                    .map(|index| {
+                        Hover::new(DrawFn::new(|_| {}), index as i32 * 8, 0, 8, 8)
+                            .on_enter(Msg::Enter(index))
+                            .on_leave(Msg::Leave(index))
+                            .on_hover(Msg::Hover(index))
+                            .into()
+                    })
+                    .collect();
+
+                Tree::with_children(children).on_event(
+                    tick,
+                    cursor_position,
+                    &mut DispatchEvent::new(&mut queue, &mut drag, &mut tracker),
+                );
+                tracker.after_event(tick);
+
+                queue
+            })
+            .collect()
+    }
+
+    #[test]
+    fn enter_hover_and_leave() {
+        let frames = run(&[(20, 20), (2, 2), (3, 3), (10, 2), (20, 20)]);
+
+        assert_eq!(
+            frames,
+            [
+                vec![],
+                vec![Msg::Enter(0), Msg::Hover(0)],
+                vec![Msg::Hover(0)],
+                vec![Msg::Leave(0), Msg::Enter(1), Msg::Hover(1)],
+                vec![Msg::Leave(1)],
+            ]
+        );
+    }
+}
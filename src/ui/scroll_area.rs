@@ -0,0 +1,292 @@
+use crate::{Event, Key, KeyState, KeyboardEvent, MouseButton, MouseEvent, Pico8};
+
+use super::focus::Focusable;
+use super::{draw_layers, DispatchEvent, Element, Interaction, Layer, Widget};
+use std::fmt::Debug;
+
+// The scroll bar, on the right edge of the area.
+const BAR_WIDTH: i32 = 3;
+// Scrolled by one line of text per arrow key press.
+const STEP: i32 = 6;
+// Where the content thinks the cursor is while it's outside of the area.
+const OUTSIDE: (i32, i32) = (-1000, -1000);
+
+/// How far down a [`ScrollArea`] is scrolled, kept between frames.
+#[derive(Debug, Clone, Default)]
+pub struct State {
+    offset: i32,
+}
+
+impl State {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pixels of content hidden above the area.
+    pub fn offset(&self) -> i32 {
+        self.offset
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.offset = 0;
+    }
+}
+
+/// Shows the part of taller `content` that fits in a `width` by `height` area,
+/// scrolled with the up and down arrows while the cursor is over it,
+/// or by clicking on the scroll bar.
+///
+/// `content` is laid out as if the area was as tall as it, starting at the area's top.
+/// It only gets the cursor while it's inside the area, so that what's scrolled out can't be clicked.
+pub struct ScrollArea<'a, Msg> {
+    content: Element<'a, Msg>,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    content_height: i32,
+    state: &'a mut State,
+}
+
+impl<'a, Msg: Copy + Debug + 'a> ScrollArea<'a, Msg> {
+    pub fn new(
+        content: impl Into<Element<'a, Msg>>,
+        (x, y, width, height): (i32, i32, i32, i32),
+        content_height: i32,
+        state: &'a mut State,
+    ) -> Self {
+        // The content might have gotten shorter since the last frame.
+        let max_offset = (content_height - height).max(0);
+        state.offset = state.offset.clamp(0, max_offset);
+
+        Self {
+            content: content.into(),
+            x,
+            y,
+            width,
+            height,
+            content_height,
+            state,
+        }
+    }
+
+    fn max_offset(&self) -> i32 {
+        (self.content_height - self.height).max(0)
+    }
+
+    fn scroll_to(&mut self, offset: i32) {
+        self.state.offset = offset.clamp(0, self.max_offset());
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        let contains_x = x >= self.x && x < self.x + self.width;
+        let contains_y = y >= self.y && y < self.y + self.height;
+
+        contains_x && contains_y
+    }
+
+    fn bar_x(&self) -> i32 {
+        self.x + self.width - BAR_WIDTH
+    }
+
+    // Top and bottom of the bar's thumb, which is as tall (relative to the bar)
+    // as the visible part of the content.
+    fn thumb(&self) -> (i32, i32) {
+        let content_height = self.content_height.max(self.height).max(1);
+        let top = self.y + self.state.offset * self.height / content_height;
+        let length = (self.height * self.height / content_height).max(2);
+
+        (top, top + length - 1)
+    }
+}
+
+impl<'a, Msg: Copy + Debug + 'a> Widget for ScrollArea<'a, Msg> {
+    type Msg = Msg;
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        cursor_position: (i32, i32),
+        dispatch_event: &mut DispatchEvent<Self::Msg>,
+    ) {
+        let (x, y) = cursor_position;
+        let inside = self.contains(x, y);
+        let on_bar = inside && x >= self.bar_x();
+
+        match event {
+            Event::Keyboard(KeyboardEvent {
+                key,
+                state: KeyState::Down,
+            }) if inside => match key {
+                Key::UpArrow => self.scroll_to(self.state.offset - STEP),
+                Key::DownArrow => self.scroll_to(self.state.offset + STEP),
+                _ => {}
+            },
+            // The thumb's middle goes where the bar was clicked.
+            Event::Mouse(MouseEvent::Down(MouseButton::Left)) if on_bar => {
+                let (top, bottom) = self.thumb();
+                let thumb_middle = (top + bottom) / 2;
+                let content_height = self.content_height.max(1);
+                self.scroll_to(
+                    self.state.offset + (y - thumb_middle) * content_height / self.height,
+                );
+                dispatch_event.consume();
+                return;
+            }
+            _ => {}
+        }
+
+        let content_cursor = if inside && !on_bar {
+            (x, y + self.state.offset)
+        } else {
+            OUTSIDE
+        };
+        self.content
+            .as_widget_mut()
+            .on_event(event, content_cursor, dispatch_event);
+    }
+
+    fn draw(&mut self, draw: &mut Pico8) {
+        draw_layers(self, draw);
+    }
+
+    // Only the base layer is clipped, so popups can go past the area.
+    fn draw_layer(&mut self, draw: &mut Pico8, layer: Layer) {
+        if layer != Layer::Base {
+            draw.with_camera(0, self.state.offset, |draw| {
+                self.content.as_widget_mut().draw_layer(draw, layer)
+            });
+
+            return;
+        }
+
+        draw.clip_push(self.x, self.y, self.width - BAR_WIDTH, self.height);
+        draw.with_camera(0, self.state.offset, |draw| {
+            self.content.as_widget_mut().draw_layer(draw, layer)
+        });
+        draw.clip_pop();
+
+        // Only when there's something to scroll to.
+        if self.max_offset() > 0 {
+            let bar_x = self.bar_x();
+            let (top, bottom) = self.thumb();
+            draw.rectfill(
+                bar_x,
+                self.y,
+                bar_x + BAR_WIDTH - 1,
+                self.y + self.height - 1,
+                5,
+            );
+            draw.rectfill(bar_x, top, bar_x + BAR_WIDTH - 1, bottom, 6);
+        }
+    }
+
+    fn visit_focusable(&mut self, f: &mut dyn FnMut(&mut dyn Focusable)) {
+        self.content.as_widget_mut().visit_focusable(f)
+    }
+
+    fn set_interaction(&mut self, interaction: Interaction) {
+        self.content.as_widget_mut().set_interaction(interaction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::button::{self, Button};
+    use crate::ui::testing::{click, key_down, mouse_move, Harness};
+    use crate::ui::{DrawFn, Tree};
+    use crate::Color;
+
+    // Ten 8 pixel rows, in a 20 pixel tall area at (10, 10): the rows are buttons sending their index.
+    fn list((scroll, buttons): &mut (State, Vec<button::State>)) -> Element<'_, usize> {
+        let rows = buttons.iter_mut().enumerate().map(|(row, state)| {
+            let y = 10 + 8 * row as i32;
+            let color = row as u8 + 1;
+
+            Button::new(
+                10,
+                y,
+                40,
+                8,
+                Some(row),
+                state,
+                DrawFn::new(move |draw| draw.rectfill(0, 0, 39, 7, color)),
+            )
+            .into()
+        });
+
+        ScrollArea::new(
+            Tree::with_children(rows.collect()),
+            (10, 10, 43, 20),
+            80,
+            scroll,
+        )
+        .into()
+    }
+
+    #[test]
+    fn arrow_keys_scroll_while_hovered() {
+        let mut harness = Harness::new();
+        let mut state = (State::new(), vec![button::State::new(); 10]);
+
+        // Not over the area.
+        harness.run(&mut state, list, [key_down(Key::DownArrow)]);
+        assert_eq!(state.0.offset(), 0);
+
+        let keys = [mouse_move(20, 20)]
+            .into_iter()
+            .chain([key_down(Key::DownArrow); 3]);
+        harness.run(&mut state, list, keys);
+        assert_eq!(state.0.offset(), 3 * STEP);
+
+        // Stops at the ends.
+        harness.run(&mut state, list, [key_down(Key::DownArrow); 20]);
+        assert_eq!(state.0.offset(), 80 - 20);
+        harness.run(&mut state, list, [key_down(Key::UpArrow); 20]);
+        assert_eq!(state.0.offset(), 0);
+    }
+
+    #[test]
+    fn the_content_is_clipped_and_scrolled() {
+        let mut harness = Harness::new();
+        let mut state = (State::new(), vec![button::State::new(); 10]);
+
+        harness.run(&mut state, list, [mouse_move(0, 0)]);
+        assert_eq!(harness.pixel(20, 10), Color::from(1));
+        assert_eq!(harness.pixel(20, 29), Color::from(3));
+        // Below the area.
+        assert_eq!(harness.pixel(20, 30), Color::BLACK);
+
+        // Down to the third row.
+        state.0.offset = 16;
+        harness.run(&mut state, list, [mouse_move(0, 0)]);
+        assert_eq!(harness.pixel(20, 10), Color::from(3));
+    }
+
+    #[test]
+    fn clicks_reach_the_scrolled_content_inside_the_area() {
+        let mut harness = Harness::new();
+        let mut state = (State::new(), vec![button::State::new(); 10]);
+        state.0.offset = 16;
+
+        // The first visible row is the third one.
+        assert_eq!(harness.run(&mut state, list, click(20, 12)), [2]);
+        // The fourth row is right below the area, out of view.
+        assert_eq!(harness.run(&mut state, list, click(20, 32)), []);
+    }
+
+    #[test]
+    fn clicking_the_bar_jumps_there() {
+        let mut harness = Harness::new();
+        let mut state = (State::new(), vec![button::State::new(); 10]);
+
+        // The bottom of the bar.
+        let msgs = harness.run(&mut state, list, click(51, 29));
+        assert_eq!(msgs, []);
+        assert_eq!(state.0.offset(), 80 - 20);
+
+        harness.run(&mut state, list, click(51, 10));
+        assert_eq!(state.0.offset(), 0);
+    }
+}
@@ -4,8 +4,9 @@ use super::{
     button::{self, Button},
     DrawFn, Element, Tree,
 };
+use crate::SpriteSheet;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum SliderValue {
     Tiny,
     Small,
@@ -57,9 +58,11 @@ pub(crate) fn view<'a, Msg: Debug + Copy + 'a>(
     y: i32,
     value: SliderValue,
     on_press: impl Fn(SliderValue) -> Msg,
-    on_hover: Msg,
     state: &'a mut State,
+    sprites: &'a SpriteSheet,
 ) -> Element<'a, Msg> {
+    // The notches, and the drawing on top.
+    let mut children = Vec::with_capacity(5);
     let buttons = [
         &mut state.button_tiny,
         &mut state.button_small,
@@ -80,22 +83,48 @@ pub(crate) fn view<'a, Msg: Debug + Copy + 'a>(
             button_state,
             Tree::new(),
         )
-        .on_hover(on_hover)
         .event_on_press()
         .into()
-    })
-    .collect();
+    });
+    children.extend(buttons);
 
-    Tree::with_children(buttons)
+    Tree::with_children(children)
         .push(DrawFn::new(move |draw| {
             // TODO: Use spr_ when width and height parameters are implemented.
-            draw.spr(64, x, y);
-            draw.spr(65, x + 8, y);
-            draw.spr(66, x + 16, y);
-            draw.spr(67, x + 24, y);
+            draw.spr_from(sprites, 64, x, y);
+            draw.spr_from(sprites, 65, x + 8, y);
+            draw.spr_from(sprites, 66, x + 16, y);
+            draw.spr_from(sprites, 67, x + 24, y);
 
             // Draw selection indicator
-            draw.spr(68, x + value.to_index() * 8 - 2, y);
+            draw.spr_from(sprites, 68, x + value.to_index() * 8 - 2, y);
         }))
         .into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::testing::{click, Harness};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Msg {
+        Selected(SliderValue),
+    }
+
+    fn slider((state, sprites): &mut (State, SpriteSheet)) -> Element<'_, Msg> {
+        view(20, 20, SliderValue::Tiny, Msg::Selected, state, sprites)
+    }
+
+    #[test]
+    fn clicking_a_notch_selects_it() {
+        let mut harness = Harness::new();
+        let mut state = (State::new(), SpriteSheet::new());
+
+        let msgs = harness.run(&mut state, slider, click(35, 24));
+        assert_eq!(msgs, [Msg::Selected(SliderValue::Medium)]);
+
+        let msgs = harness.run(&mut state, slider, click(19, 24));
+        assert_eq!(msgs, [Msg::Selected(SliderValue::Tiny)]);
+    }
+}
@@ -0,0 +1,155 @@
+//! Runs widgets without a window, for tests.
+
+use crate::pico8::Pico8;
+use crate::runtime::draw_data::DrawData;
+use crate::runtime::state::State;
+use crate::{Color, Event, Key, KeyState, KeyboardEvent, MouseButton, MouseEvent, Resources};
+
+use super::{Element, UiState};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt::Debug;
+
+/// Feeds events to views the same way the runtime does,
+/// drawing every frame to an in-memory screen.
+#[derive(Debug)]
+pub(crate) struct Harness {
+    pico8: Pico8,
+    ui: UiState,
+}
+
+impl Harness {
+    /// Starts with an empty sprite sheet, map and flags.
+    pub(crate) fn new() -> Self {
+        Self::with_resources(Resources::empty())
+    }
+
+    /// For views that draw sprites or the map.
+    pub(crate) fn with_resources(resources: Resources) -> Self {
+        Self {
+            pico8: Pico8::new(DrawData::new(), State::new(), resources),
+            ui: UiState::new(),
+        }
+    }
+
+    /// Sends a single event to `view` and draws it, returning the messages it sent.
+    pub(crate) fn step<'a, Msg: Copy + Debug + 'a>(
+        &mut self,
+        view: impl Into<Element<'a, Msg>>,
+        event: Event,
+    ) -> Vec<Msg> {
+        let mut view = view.into();
+        let mut queue = vec![];
+
+        let cursor_position = self.pico8.mouse();
+        self.ui.dispatch(
+            view.as_widget_mut(),
+            Some(event),
+            cursor_position,
+            &mut queue,
+        );
+
+        view.as_widget_mut().draw(&mut self.pico8);
+
+        match event {
+            Event::Mouse(MouseEvent::Move { x, y }) => self.pico8.state.on_mouse_move(x, y),
+            Event::Tick { .. } => self.pico8.state.tick(),
+            _ => {}
+        }
+
+        queue
+    }
+
+    /// Sends every event to a view freshly built from `state` (like the runtime does
+    /// every frame), returning all the messages sent along the way.
+    pub(crate) fn run<S, Msg: Copy + Debug>(
+        &mut self,
+        state: &mut S,
+        mut view: impl FnMut(&mut S) -> Element<'_, Msg>,
+        events: impl IntoIterator<Item = Event>,
+    ) -> Vec<Msg> {
+        events
+            .into_iter()
+            .flat_map(|event| self.step(view(state), event))
+            .collect()
+    }
+
+    /// Color of a pixel on the screen, as of the last frame drawn.
+    pub(crate) fn pixel(&self, x: i32, y: i32) -> Color {
+        self.pico8.draw_data.pixel(x, y).unwrap()
+    }
+}
+
+pub(crate) fn mouse_move(x: i32, y: i32) -> Event {
+    Event::Mouse(MouseEvent::Move { x, y })
+}
+
+pub(crate) fn mouse_down() -> Event {
+    Event::Mouse(MouseEvent::Down(MouseButton::Left))
+}
+
+pub(crate) fn mouse_up() -> Event {
+    Event::Mouse(MouseEvent::Up(MouseButton::Left))
+}
+
+/// Moving to (x, y), then pressing and releasing the left button.
+pub(crate) fn click(x: i32, y: i32) -> [Event; 3] {
+    [mouse_move(x, y), mouse_down(), mouse_up()]
+}
+
+/// Pressing `key`, for widgets that get keyboard events on their own.
+pub(crate) fn keyboard_down(key: Key) -> KeyboardEvent {
+    KeyboardEvent {
+        key,
+        state: KeyState::Down,
+    }
+}
+
+pub(crate) fn key_down(key: Key) -> Event {
+    Event::Keyboard(keyboard_down(key))
+}
+
+#[cfg(feature = "editor")]
+pub(crate) fn tick() -> Event {
+    Event::Tick {
+        delta_millis: 0.0,
+        frame: 0,
+    }
+}
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// The system allocator, counting allocations per thread so that tests
+/// running in parallel don't see each other's.
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // Threads that are shutting down have no counter anymore.
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// How many times `f` allocated (or grew an allocation), on the current thread.
+#[cfg(feature = "editor")]
+pub(crate) fn count_allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+
+    ALLOCATIONS.with(Cell::get) - before
+}
@@ -1,3 +1,4 @@
+use crate::font::{self, GLYPH_HEIGHT};
 use crate::Pico8;
 use std::{fmt::Debug, marker::PhantomData};
 
@@ -10,19 +11,86 @@ pub struct Text<'a, Msg> {
     x: i32,
     y: i32,
     color: Color,
+    max_width: Option<i32>,
+    line_spacing: i32,
+    align: Align,
     pd: PhantomData<Msg>,
 }
 
+/// Horizontal alignment of each line, within the text's max width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
 impl<'a, Msg> Text<'a, Msg> {
-    pub fn new(text: &'a str, x: i32, y: i32, color: Color) -> Self {
+    pub fn new(text: &'a str, x: i32, y: i32, color: impl Into<Color>) -> Self {
         Self {
             x,
             y,
             text,
-            color,
+            color: color.into(),
+            max_width: None,
+            line_spacing: 0,
+            align: Align::Left,
             pd: PhantomData,
         }
     }
+
+    /// Wraps the text so that no line is wider than `max_width` pixels.
+    pub fn max_width(mut self, max_width: i32) -> Self {
+        self.max_width = Some(max_width);
+
+        self
+    }
+
+    /// Extra pixels between lines.
+    pub fn line_spacing(mut self, line_spacing: i32) -> Self {
+        self.line_spacing = line_spacing;
+
+        self
+    }
+
+    /// Only has an effect when a max width is set.
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+
+        self
+    }
+
+    /// How many pixels down the last line ends.
+    pub fn height(&self) -> i32 {
+        let lines = self.lines().len() as i32;
+
+        (lines * (GLYPH_HEIGHT + self.line_spacing) - self.line_spacing).max(0)
+    }
+
+    // Lines to print, with their position.
+    fn lines(&self) -> Vec<(String, i32, i32)> {
+        let lines = match self.max_width {
+            Some(max_width) => font::wrap(self.text, max_width),
+            None => self.text.split('\n').map(str::to_owned).collect(),
+        };
+
+        lines
+            .into_iter()
+            .enumerate()
+            .map(|(index, line)| {
+                let free_space = self.max_width.unwrap_or(0) - font::measure_text(&line).0;
+                let x = self.x
+                    + match self.align {
+                        Align::Left => 0,
+                        Align::Center => free_space.max(0) / 2,
+                        Align::Right => free_space.max(0),
+                    };
+                let y = self.y + index as i32 * (GLYPH_HEIGHT + self.line_spacing);
+
+                (line, x, y)
+            })
+            .collect()
+    }
 }
 
 impl<'a, Msg: Copy + Debug> Widget for Text<'a, Msg> {
@@ -37,6 +105,41 @@ impl<'a, Msg: Copy + Debug> Widget for Text<'a, Msg> {
     }
 
     fn draw(&mut self, draw: &mut Pico8) {
-        draw.print(self.text, self.x, self.y, self.color);
+        for (line, x, y) in self.lines() {
+            draw.print(&line, x, y, self.color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug)]
+    struct Msg;
+
+    fn positions(text: Text<'_, Msg>) -> Vec<(i32, i32)> {
+        text.lines().into_iter().map(|(_, x, y)| (x, y)).collect()
+    }
+
+    #[test]
+    fn wraps_and_spaces_lines() {
+        let text = Text::<Msg>::new("AB CD", 10, 20, 7)
+            .max_width(8)
+            .line_spacing(2);
+
+        let lines: Vec<_> = text.lines().into_iter().map(|(line, ..)| line).collect();
+        assert_eq!(lines, ["AB", "CD"]);
+        assert_eq!(text.height(), 14);
+        assert_eq!(positions(text), [(10, 20), (10, 28)]);
+    }
+
+    #[test]
+    fn alignment_works() {
+        let text = || Text::<Msg>::new("AB", 0, 0, 7).max_width(20);
+
+        assert_eq!(positions(text()), [(0, 0)]);
+        assert_eq!(positions(text().align(Align::Center)), [(6, 0)]);
+        assert_eq!(positions(text().align(Align::Right)), [(12, 0)]);
     }
 }
@@ -0,0 +1,415 @@
+use crate::Pico8;
+use crate::{Event, Font, Key, KeyState, KeyboardEvent, MouseButton, MouseEvent};
+
+use super::focus::Focusable;
+use super::{DispatchEvent, Widget};
+use std::fmt::Debug;
+
+const HEIGHT: i32 = 7;
+const PADDING: i32 = 1;
+const BLINK_FRAMES: i32 = 15;
+
+type OnChange<'a, Msg> = Box<dyn Fn(&str) -> Msg + 'a>;
+
+/// A focusable, single line text field.
+pub struct TextInput<'a, Msg> {
+    x: i32,
+    y: i32,
+    width: i32,
+    state: &'a mut State,
+    on_change: Option<OnChange<'a, Msg>>,
+    on_submit: Option<Msg>,
+    max_length: Option<usize>,
+    numeric: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct State {
+    text: String,
+    // Position of the cursor, in characters.
+    // Only ascii characters are accepted, so it's also a byte index.
+    cursor: usize,
+    // Index of the first visible character.
+    scroll: usize,
+    focused: bool,
+    blink_timer: i32,
+}
+
+impl State {
+    pub fn new() -> Self {
+        Self::with_text(String::new())
+    }
+
+    /// Starts with `text`, keeping only the characters that could be typed, uppercased.
+    pub fn with_text(text: String) -> Self {
+        let text = typeable(&text);
+
+        Self {
+            cursor: text.len(),
+            text,
+            scroll: 0,
+            focused: false,
+            blink_timer: 0,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replaces the text, keeping only the characters that could be typed, uppercased.
+    pub fn set_text(&mut self, text: String) {
+        let text = typeable(&text);
+        self.cursor = text.len();
+        self.scroll = 0;
+        self.text = text;
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn focus(&mut self) {
+        self.focused = true;
+        self.blink_timer = 0;
+    }
+
+    pub fn blur(&mut self) {
+        self.focused = false;
+    }
+
+    /// Returns whether the text changed.
+    fn insert(&mut self, character: char, max_length: Option<usize>) -> bool {
+        if !is_typeable(character) {
+            return false;
+        }
+        if max_length.is_some_and(|max_length| self.text.len() >= max_length) {
+            return false;
+        }
+
+        // Uppercase like the rest of the editor's text.
+        self.text
+            .insert(self.cursor, character.to_ascii_uppercase());
+        self.cursor += 1;
+
+        true
+    }
+
+    fn backspace(&mut self) -> bool {
+        if self.cursor == 0 {
+            return false;
+        }
+
+        self.cursor -= 1;
+        self.text.remove(self.cursor);
+
+        true
+    }
+
+    fn delete(&mut self) -> bool {
+        if self.cursor >= self.text.len() {
+            return false;
+        }
+
+        self.text.remove(self.cursor);
+
+        true
+    }
+
+    fn move_cursor(&mut self, key: Key) {
+        self.cursor = match key {
+            Key::LeftArrow => self.cursor.saturating_sub(1),
+            Key::RightArrow => (self.cursor + 1).min(self.text.len()),
+            Key::Home => 0,
+            Key::End => self.text.len(),
+            _ => self.cursor,
+        };
+    }
+
+    // Scrolls just enough so that the cursor is visible.
+    fn scroll_to_cursor(&mut self, visible_chars: usize) {
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor > self.scroll + visible_chars {
+            self.scroll = self.cursor - visible_chars;
+        }
+    }
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, Msg> TextInput<'a, Msg> {
+    pub fn new(x: i32, y: i32, width: i32, state: &'a mut State) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            state,
+            on_change: None,
+            on_submit: None,
+            max_length: None,
+            numeric: false,
+        }
+    }
+
+    /// Message to send whenever the text is edited.
+    pub fn on_change(mut self, on_change: impl Fn(&str) -> Msg + 'a) -> Self {
+        self.on_change = Some(Box::new(on_change));
+
+        self
+    }
+
+    /// Message to send when enter is pressed while focused.
+    pub fn on_submit(mut self, on_submit: Msg) -> Self {
+        self.on_submit = Some(on_submit);
+
+        self
+    }
+
+    pub fn max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+
+        self
+    }
+
+    /// Only lets digits be typed.
+    pub fn numeric(mut self) -> Self {
+        self.numeric = true;
+
+        self
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        let contains_x = x >= self.x && x < self.x + self.width;
+        let contains_y = y >= self.y && y < self.y + HEIGHT;
+
+        contains_x && contains_y
+    }
+
+    fn visible_chars(&self) -> usize {
+        // Leave room for the cursor at the end.
+        ((self.width - 2 * PADDING - 1) / font().advance()).max(0) as usize
+    }
+}
+
+impl<'a, Msg: Copy + Debug + 'a> Widget for TextInput<'a, Msg> {
+    type Msg = Msg;
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        cursor_position: (i32, i32),
+        dispatch_event: &mut DispatchEvent<Self::Msg>,
+    ) {
+        let changed = match event {
+            Event::Mouse(MouseEvent::Down(MouseButton::Left)) => {
+                if self.contains(cursor_position.0, cursor_position.1) {
+                    self.state.focus();
+                } else {
+                    self.state.blur();
+                }
+
+                false
+            }
+            Event::Tick { .. } => {
+                self.state.blink_timer = (self.state.blink_timer + 1) % (2 * BLINK_FRAMES);
+
+                false
+            }
+            _ if !self.state.focused => false,
+            Event::Character(character) if self.numeric && !character.is_ascii_digit() => false,
+            Event::Character(character) => self.state.insert(character, self.max_length),
+            Event::Keyboard(KeyboardEvent {
+                key,
+                state: KeyState::Down,
+            }) => match key {
+                Key::Backspace => self.state.backspace(),
+                Key::Delete => self.state.delete(),
+                Key::Enter => {
+                    if let Some(on_submit) = self.on_submit {
+                        dispatch_event.call(on_submit);
+                    }
+
+                    false
+                }
+                Key::LeftArrow | Key::RightArrow | Key::Home | Key::End => {
+                    self.state.move_cursor(key);
+                    self.state.blink_timer = 0;
+
+                    false
+                }
+                _ => false,
+            },
+            _ => false,
+        };
+
+        if changed {
+            self.state.blink_timer = 0;
+
+            if let Some(on_change) = &self.on_change {
+                dispatch_event.call(on_change(&self.state.text));
+            }
+        }
+
+        let visible_chars = self.visible_chars();
+        self.state.scroll_to_cursor(visible_chars);
+    }
+
+    fn draw(&mut self, draw: &mut Pico8) {
+        let (x, y) = (self.x, self.y);
+        let visible_chars = self.visible_chars();
+        let state = &self.state;
+
+        draw.rectfill(x, y, x + self.width - 1, y + HEIGHT - 1, 7);
+
+        let end = (state.scroll + visible_chars).min(state.text.len());
+        let visible_text = &state.text[state.scroll..end];
+        draw.print_with(font(), visible_text, x + PADDING, y + PADDING, 0);
+
+        if state.focused && state.blink_timer < BLINK_FRAMES {
+            let cursor_x =
+                x + PADDING + (state.cursor - state.scroll) as i32 * font().advance() - 1;
+
+            draw.line(cursor_x, y + 1, cursor_x, y + HEIGHT - 2, 8);
+        }
+    }
+
+    fn visit_focusable(&mut self, f: &mut dyn FnMut(&mut dyn Focusable)) {
+        f(self.state)
+    }
+}
+
+impl Focusable for State {
+    fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        if focused && !self.focused {
+            self.focus();
+        } else if !focused {
+            self.blur();
+        }
+    }
+}
+
+// Monospaced, so that the cursor and the visible characters are found by counting columns.
+fn font() -> &'static Font {
+    Font::builtin_monospaced()
+}
+
+fn is_typeable(character: char) -> bool {
+    character.is_ascii() && !character.is_ascii_control()
+}
+
+// Uppercase like the rest of the editor's text.
+fn typeable(text: &str) -> String {
+    text.chars()
+        .filter(|&character| is_typeable(character))
+        .map(|character| character.to_ascii_uppercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::testing::{click, Harness};
+    use crate::ui::Element;
+    use crate::Color;
+
+    #[test]
+    fn editing_works() {
+        let mut state = State::new();
+
+        for character in "hello".chars() {
+            state.insert(character, None);
+        }
+        assert_eq!(state.text(), "HELLO");
+
+        state.move_cursor(Key::Home);
+        state.delete();
+        state.move_cursor(Key::End);
+        state.backspace();
+        assert_eq!(state.text(), "ELL");
+
+        state.move_cursor(Key::LeftArrow);
+        state.insert('!', None);
+        assert_eq!(state.text(), "EL!L");
+    }
+
+    #[test]
+    fn max_length_is_respected() {
+        let mut state = State::new();
+
+        for character in "12345".chars() {
+            state.insert(character, Some(3));
+        }
+
+        assert_eq!(state.text(), "123");
+    }
+
+    #[test]
+    fn control_characters_are_ignored() {
+        let mut state = State::new();
+
+        assert!(!state.insert('\u{8}', None));
+        assert!(!state.insert('\r', None));
+        assert_eq!(state.text(), "");
+    }
+
+    #[test]
+    fn starting_text_is_limited_to_what_can_be_typed() {
+        let mut state = State::with_text("héllo\twörld".to_owned());
+        assert_eq!(state.text(), "HLLOWRLD");
+
+        // Editing works on it like on typed text.
+        state.backspace();
+        state.move_cursor(Key::Home);
+        state.delete();
+        assert_eq!(state.text(), "LLOWRL");
+
+        state.set_text("ça va".to_owned());
+        assert_eq!(state.text(), "A VA");
+        state.scroll_to_cursor(2);
+        state.backspace();
+        assert_eq!(state.text(), "A V");
+    }
+
+    #[test]
+    fn scrolls_to_keep_cursor_visible() {
+        let mut state = State::with_text("ABCDEFGHIJ".to_owned());
+
+        state.scroll_to_cursor(4);
+        assert_eq!(state.scroll, 6);
+
+        state.move_cursor(Key::Home);
+        state.scroll_to_cursor(4);
+        assert_eq!(state.scroll, 0);
+    }
+
+    #[test]
+    fn the_cursor_stays_after_narrow_characters() {
+        fn input(state: &mut State) -> Element<'_, ()> {
+            TextInput::new(10, 10, 40, state).into()
+        }
+        let mut harness = Harness::new();
+        let mut state = State::new();
+
+        harness.run(&mut state, input, click(12, 12));
+        let typed = "I.I.".chars().map(Event::Character);
+        harness.run(&mut state, input, typed);
+
+        // Every character takes a 4 pixel column, the last period is drawn in the fourth one
+        // and the cursor is just after it.
+        let fourth_column = (23..26).flat_map(|x| (11..16).map(move |y| (x, y)));
+        assert!(fourth_column
+            .map(|(x, y)| harness.pixel(x, y))
+            .any(|color| color == Color::BLACK));
+        assert_eq!(harness.pixel(10 + 1 + 4 * 4 - 1, 12), Color::RED);
+        assert_ne!(harness.pixel(10 + 1 + 4 * 3 - 1, 12), Color::RED);
+    }
+}
@@ -0,0 +1,225 @@
+use crate::font;
+use crate::{Event, MouseEvent, Pico8};
+
+use super::focus::Focusable;
+use super::{draw_layers, draw_popup, DispatchEvent, Element, Layer, Widget};
+use std::fmt::Debug;
+
+/// Frames the cursor has to rest over a widget before its tooltip shows up.
+pub const TOOLTIP_DELAY: u32 = 15;
+
+const HEIGHT: i32 = 9;
+// Offset from the cursor, so the tooltip doesn't cover it.
+const CURSOR_OFFSET: (i32, i32) = (4, 6);
+
+/// Tracks how long the cursor has been resting over a widget.
+///
+/// Widgets that support tooltips keep one of these in their state.
+#[derive(Debug, Clone, Default)]
+pub struct HoverTimer {
+    frames: u32,
+    // Set on click, until the cursor leaves the widget.
+    dismissed: bool,
+}
+
+impl HoverTimer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `hovering` is whether the cursor is currently over the widget.
+    pub fn on_event(&mut self, event: Event, hovering: bool) {
+        if !hovering {
+            *self = Self::new();
+            return;
+        }
+
+        match event {
+            Event::Tick { .. } => self.frames = self.frames.saturating_add(1),
+            Event::Mouse(MouseEvent::Down(_)) => self.dismissed = true,
+            // The cursor has to be still for the tooltip to appear,
+            // but it can move around once it's visible.
+            Event::Mouse(MouseEvent::Move { .. }) if !self.is_showing() => self.frames = 0,
+            _ => {}
+        }
+    }
+
+    pub fn is_showing(&self) -> bool {
+        !self.dismissed && self.frames >= TOOLTIP_DELAY
+    }
+}
+
+/// Adds a tooltip to any element, shown while the cursor rests over the given area.
+pub struct Tooltip<'a, Msg> {
+    element: Element<'a, Msg>,
+    text: &'a str,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    state: &'a mut HoverTimer,
+}
+
+impl<'a, Msg: Copy + Debug + 'a> Tooltip<'a, Msg> {
+    pub fn wrap(
+        element: impl Into<Element<'a, Msg>>,
+        text: &'a str,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        state: &'a mut HoverTimer,
+    ) -> Self {
+        Self {
+            element: element.into(),
+            text,
+            x,
+            y,
+            width,
+            height,
+            state,
+        }
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        let contains_x = x >= self.x && x < self.x + self.width;
+        let contains_y = y >= self.y && y < self.y + self.height;
+
+        contains_x && contains_y
+    }
+}
+
+impl<'a, Msg: Copy + Debug + 'a> Widget for Tooltip<'a, Msg> {
+    type Msg = Msg;
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        cursor_position: (i32, i32),
+        dispatch_event: &mut DispatchEvent<Self::Msg>,
+    ) {
+        let hovering = self.contains(cursor_position.0, cursor_position.1);
+        self.state.on_event(event, hovering);
+
+        self.element
+            .as_widget_mut()
+            .on_event(event, cursor_position, dispatch_event);
+    }
+
+    fn draw(&mut self, draw: &mut Pico8) {
+        draw_layers(self, draw);
+    }
+
+    fn draw_layer(&mut self, draw: &mut Pico8, layer: Layer) {
+        self.element.as_widget_mut().draw_layer(draw, layer);
+
+        if layer == Layer::Overlay && self.state.is_showing() {
+            draw_tooltip(draw, self.text);
+        }
+    }
+
+    fn visit_focusable(&mut self, f: &mut dyn FnMut(&mut dyn Focusable)) {
+        self.element.as_widget_mut().visit_focusable(f)
+    }
+}
+
+/// Draws a tooltip next to the cursor, for widgets drawing [`Layer::Overlay`].
+pub(crate) fn draw_tooltip(draw: &mut Pico8, text: &str) {
+    let width = tooltip_width(text);
+    let (x, y) = tooltip_position(width, draw.mouse());
+
+    draw_popup(draw, |draw| {
+        draw.rectfill(x, y, x + width - 1, y + HEIGHT - 1, 0);
+        draw.rect(x, y, x + width - 1, y + HEIGHT - 1, 7);
+        draw.print(text, x + 2, y + 2, 7);
+    });
+}
+
+// The text, with a pixel of room and the border on each side.
+fn tooltip_width(text: &str) -> i32 {
+    font::measure_text(text).0 + 3
+}
+
+// Top left corner of a tooltip `width` pixels wide, kept inside the screen.
+fn tooltip_position(width: i32, cursor: (i32, i32)) -> (i32, i32) {
+    let x = (cursor.0 + CURSOR_OFFSET.0).min(128 - width).max(0);
+    let y = if cursor.1 + CURSOR_OFFSET.1 + HEIGHT > 128 {
+        // Not enough room below the cursor, show it above instead.
+        cursor.1 - HEIGHT - 1
+    } else {
+        cursor.1 + CURSOR_OFFSET.1
+    };
+
+    (x, y.max(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MouseButton;
+
+    fn tick(timer: &mut HoverTimer, frames: u32) {
+        for _ in 0..frames {
+            timer.on_event(
+                Event::Tick {
+                    delta_millis: 0.0,
+                    frame: 0,
+                },
+                true,
+            );
+        }
+    }
+
+    #[test]
+    fn shows_after_resting() {
+        let mut timer = HoverTimer::new();
+
+        tick(&mut timer, TOOLTIP_DELAY - 1);
+        assert!(!timer.is_showing());
+
+        tick(&mut timer, 1);
+        assert!(timer.is_showing());
+    }
+
+    #[test]
+    fn moving_off_hides_immediately() {
+        let mut timer = HoverTimer::new();
+        tick(&mut timer, TOOLTIP_DELAY);
+
+        timer.on_event(Event::Mouse(MouseEvent::Move { x: 0, y: 0 }), false);
+
+        assert!(!timer.is_showing());
+    }
+
+    #[test]
+    fn clicking_dismisses_until_leaving() {
+        let mut timer = HoverTimer::new();
+        tick(&mut timer, TOOLTIP_DELAY);
+
+        timer.on_event(Event::Mouse(MouseEvent::Down(MouseButton::Left)), true);
+        tick(&mut timer, TOOLTIP_DELAY);
+        assert!(!timer.is_showing());
+
+        timer.on_event(Event::Mouse(MouseEvent::Move { x: 0, y: 0 }), false);
+        tick(&mut timer, TOOLTIP_DELAY);
+        assert!(timer.is_showing());
+    }
+
+    #[test]
+    fn tooltip_stays_on_screen() {
+        assert_eq!(tooltip_position(15, (10, 10)), (14, 16));
+        // Too far right
+        assert_eq!(tooltip_position(15, (125, 10)), (113, 16));
+        // Too far down
+        assert_eq!(tooltip_position(15, (10, 125)), (14, 115));
+    }
+
+    #[test]
+    fn tooltips_fit_their_text() {
+        assert_eq!(tooltip_width("ABC"), 15);
+        // Narrow characters take less room, so the tooltip can stay closer to the cursor.
+        let narrow = tooltip_width("...");
+        assert!(narrow < 15);
+        assert_eq!(tooltip_position(narrow, (125, 10)), (128 - narrow, 16));
+    }
+}
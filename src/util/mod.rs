@@ -1 +1,2 @@
+#[cfg(feature = "editor")]
 pub(crate) mod vec2;
@@ -0,0 +1,159 @@
+//! Runs the `runty8` binary, needs the `cli` feature.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use runty8::{Flags, SpriteSheet};
+
+fn runty8(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_runty8"))
+        .args(args)
+        .output()
+        .expect("Couldn't run runty8")
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+// An empty directory, only for `name`.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("runty8-cli-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+
+    dir
+}
+
+fn path(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+// Width and height from the IHDR chunk.
+fn png_size(file: &Path) -> (u32, u32) {
+    let png = fs::read(file).unwrap();
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+    let size = |start: usize| u32::from_be_bytes(png[start..start + 4].try_into().unwrap());
+
+    (size(16), size(20))
+}
+
+#[test]
+fn edit_reports_broken_assets_before_opening_a_window() {
+    let dir = temp_dir("edit");
+    fs::write(dir.join("map.txt"), "version 99\n").unwrap();
+
+    let output = runty8(&["edit", path(&dir)]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with("runty8: Couldn't load asset"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn export_png_writes_the_map_and_sprite_sheet() {
+    let dir = temp_dir("export-png");
+    for file in ["map.txt", "sprite_sheet.txt", "sprite_flags.txt"] {
+        fs::copy(Path::new("examples/celeste").join(file), dir.join(file)).unwrap();
+    }
+
+    let output = runty8(&["export-png", path(&dir)]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!("{}/map.png\n", path(&dir))));
+    assert_eq!(png_size(&dir.join("map.png")), (1024, 512));
+    assert_eq!(png_size(&dir.join("sprite_sheet.png")), (128, 128));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn export_png_fails_without_an_assets_directory() {
+    let dir = temp_dir("export-png-missing").join("missing");
+
+    let output = runty8(&["export-png", path(&dir)]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains(path(&dir)));
+    assert!(!dir.exists());
+}
+
+#[test]
+fn export_wav_writes_a_music_pattern() {
+    let dir = temp_dir("export-wav");
+    fs::copy("examples/jump_sound/sfx.txt", dir.join("sfx.txt")).unwrap();
+    // Sfx 0 on the first channel, then stop.
+    fs::write(dir.join("music.txt"), "version 1\n04 00414243\n").unwrap();
+
+    let output = runty8(&["export-wav", path(&dir), "0"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let wav = fs::read(dir.join("music_00.wav")).unwrap();
+    assert!(wav.starts_with(b"RIFF"));
+    assert!(wav.len() > 44);
+
+    // Nothing to play there.
+    let output = runty8(&["export-wav", path(&dir), "1"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(!dir.join("music_01.wav").exists());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn convert_imports_a_cart() {
+    let dir = temp_dir("convert");
+    let cart = dir.join("game.p8");
+    let assets = dir.join("assets");
+    fs::write(
+        &cart,
+        "pico-8 cartridge // http://www.pico-8.com\n\
+         version 41\n\
+         __lua__\n\
+         print('hi')\n\
+         __gfx__\n\
+         000000000000000000000000\n\
+         000000000e00000000000000\n\
+         __gff__\n\
+         0003\n",
+    )
+    .unwrap();
+
+    let output = runty8(&["convert", path(&cart), path(&assets)]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(String::from_utf8_lossy(&output.stdout).lines().count(), 5);
+
+    let sprite_sheet = fs::read_to_string(assets.join("sprite_sheet.txt")).unwrap();
+    let sprite_sheet = SpriteSheet::deserialize(&sprite_sheet).unwrap();
+    assert_eq!(sprite_sheet.get_sprite(1).pget(1, 1), runty8::Color::PINK);
+    let flags = Flags::deserialize(&fs::read_to_string(assets.join("sprite_flags.txt")).unwrap());
+    assert_eq!(flags.unwrap().fget(1), 3);
+    for file in ["map.txt", "sfx.txt", "music.txt"] {
+        assert!(assets.join(file).exists());
+    }
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn convert_rejects_files_that_arent_carts() {
+    let dir = temp_dir("convert-invalid");
+    let cart = dir.join("game.p8");
+    fs::write(&cart, "print('hi')\n").unwrap();
+
+    let output = runty8(&["convert", path(&cart), path(&dir.join("assets"))]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains(path(&cart)));
+    assert!(!dir.join("assets").exists());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn wrong_arguments_print_the_usage() {
+    for args in [&[][..], &["edit"], &["play", "game.p8"]] {
+        let output = runty8(args);
+
+        assert_eq!(output.status.code(), Some(2));
+        assert!(stderr(&output).contains("Usage:"));
+    }
+}
@@ -0,0 +1,44 @@
+//! Runs the gallery example's demos without a window, so that they can't silently break.
+//! Needs the `bench` feature, for `Headless`.
+
+#[path = "../examples/gallery/drawing.rs"]
+mod drawing;
+#[path = "../examples/gallery/hello.rs"]
+mod hello;
+#[path = "../examples/gallery/particles.rs"]
+mod particles;
+#[path = "../examples/gallery/platformer.rs"]
+mod platformer;
+
+use runty8::bench::Headless;
+use runty8::App;
+
+fn run<A: App>(frames: usize) -> A {
+    let mut headless = Headless::with_assets("examples/gallery").unwrap();
+    let pico8 = headless.pico8();
+
+    let mut app = A::init(pico8);
+    for _ in 0..frames {
+        app.update(pico8);
+        app.draw(pico8);
+    }
+
+    app
+}
+
+#[test]
+fn demos_run() {
+    run::<hello::Hello>(60);
+    run::<drawing::Drawing>(60);
+    run::<particles::Particles>(60);
+}
+
+#[test]
+fn platformer_player_lands_on_the_ground() {
+    let game = run::<platformer::Platformer>(60);
+
+    assert!(game.on_ground);
+    // Standing on the top row of ground, row 13 of the map.
+    assert_eq!(game.y as i32, 13 * 8 - 8);
+    assert_eq!(game.x, 16.0);
+}